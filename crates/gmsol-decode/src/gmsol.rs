@@ -2,10 +2,14 @@ use crate::{impl_decode_for_cpi_event, impl_decode_for_zero_copy};
 
 use gmsol_store::{
     events::{
-        BorrowingFeesUpdated, DepositExecuted, DepositRemoved, GlvDepositRemoved, GlvPricing,
-        GlvWithdrawalRemoved, GtUpdated, MarketFeesUpdated, MarketStateUpdated, OrderRemoved,
-        PositionDecreased, PositionIncreased, ShiftRemoved, SwapExecuted, TradeEvent,
-        WithdrawalExecuted, WithdrawalRemoved,
+        BatchOiCapUpdated, BatchPositionsPrepared, BorrowingFeesUpdated, DepositExecuted,
+        DepositRemoved, DustDepositCancelled, EmergencyWithdrawal, FeesClaimedWithSplit,
+        GlvBatchShiftCreated, GlvDepositRemoved, GlvPricing, GlvWithdrawalRemoved, GtUpdated,
+        KeeperTipPaid, MarketFeesUpdated, MarketNameUpdated, MarketStateCompressed,
+        MarketStateExport, MarketStateUpdated, MarketSupplyDiscrepancy, OracleRotated,
+        OrderCallbackPending, OrderRemoved, PositionDecreased, PositionIncreased, ShiftRemoved,
+        StuckClaimableAccountRecovered, SwapExecuted, TradeEvent, UserAccountFrozen,
+        UserAccountUnfrozen, WithdrawalExecuted, WithdrawalRemoved,
     },
     states::{
         Deposit, GlvDeposit, GlvShift, GlvWithdrawal, Market, Order, Position, Shift, Store,
@@ -28,21 +32,37 @@ impl_decode_for_zero_copy!(GlvShift);
 
 impl_decode_for_cpi_event!(DepositRemoved);
 impl_decode_for_cpi_event!(DepositExecuted);
+impl_decode_for_cpi_event!(DustDepositCancelled);
 impl_decode_for_cpi_event!(WithdrawalRemoved);
 impl_decode_for_cpi_event!(WithdrawalExecuted);
 impl_decode_for_cpi_event!(ShiftRemoved);
 impl_decode_for_cpi_event!(GlvDepositRemoved);
 impl_decode_for_cpi_event!(GlvWithdrawalRemoved);
 impl_decode_for_cpi_event!(GlvPricing);
+impl_decode_for_cpi_event!(GlvBatchShiftCreated);
 impl_decode_for_cpi_event!(PositionIncreased);
 impl_decode_for_cpi_event!(PositionDecreased);
 impl_decode_for_cpi_event!(OrderRemoved);
+impl_decode_for_cpi_event!(OrderCallbackPending);
 impl_decode_for_cpi_event!(TradeEvent);
 impl_decode_for_cpi_event!(MarketFeesUpdated);
 impl_decode_for_cpi_event!(BorrowingFeesUpdated);
 impl_decode_for_cpi_event!(MarketStateUpdated);
 impl_decode_for_cpi_event!(SwapExecuted);
 impl_decode_for_cpi_event!(GtUpdated);
+impl_decode_for_cpi_event!(EmergencyWithdrawal);
+impl_decode_for_cpi_event!(MarketStateCompressed);
+impl_decode_for_cpi_event!(UserAccountFrozen);
+impl_decode_for_cpi_event!(UserAccountUnfrozen);
+impl_decode_for_cpi_event!(OracleRotated);
+impl_decode_for_cpi_event!(FeesClaimedWithSplit);
+impl_decode_for_cpi_event!(MarketNameUpdated);
+impl_decode_for_cpi_event!(MarketStateExport);
+impl_decode_for_cpi_event!(StuckClaimableAccountRecovered);
+impl_decode_for_cpi_event!(BatchOiCapUpdated);
+impl_decode_for_cpi_event!(KeeperTipPaid);
+impl_decode_for_cpi_event!(BatchPositionsPrepared);
+impl_decode_for_cpi_event!(MarketSupplyDiscrepancy);
 
 untagged!(
     GMSOLAccountData,
@@ -68,21 +88,37 @@ untagged!(
     [
         DepositExecuted,
         DepositRemoved,
+        DustDepositCancelled,
         WithdrawalExecuted,
         WithdrawalRemoved,
         ShiftRemoved,
         GlvDepositRemoved,
         GlvWithdrawalRemoved,
         GlvPricing,
+        GlvBatchShiftCreated,
         PositionIncreased,
         PositionDecreased,
         OrderRemoved,
+        OrderCallbackPending,
         TradeEvent,
         MarketFeesUpdated,
         BorrowingFeesUpdated,
         MarketStateUpdated,
         SwapExecuted,
         GtUpdated,
+        EmergencyWithdrawal,
+        MarketStateCompressed,
+        UserAccountFrozen,
+        UserAccountUnfrozen,
+        OracleRotated,
+        FeesClaimedWithSplit,
+        MarketNameUpdated,
+        MarketStateExport,
+        StuckClaimableAccountRecovered,
+        BatchOiCapUpdated,
+        KeeperTipPaid,
+        BatchPositionsPrepared,
+        MarketSupplyDiscrepancy,
         UnknownOwnedData
     ]
 );
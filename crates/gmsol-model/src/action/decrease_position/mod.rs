@@ -665,13 +665,13 @@ where
 
         self.position.on_decreased()?;
 
-        let mut report = Box::new(DecreasePositionReport::new(
+        let mut report = Box::new(DecreasePositionReport::try_new(
             &self.params,
             execution,
             self.withdrawable_collateral_amount,
             self.size_delta_usd,
             should_remove,
-        ));
+        )?);
 
         // Swap collateral tokens to pnl tokens.
         {
@@ -771,4 +771,54 @@ mod tests {
         println!("{market:#?}");
         Ok(())
     }
+
+    #[test]
+    fn fee_breakdown() -> crate::Result<()> {
+        use crate::num::UnsignedAbs;
+        use num_traits::Signed;
+
+        let mut market = TestMarket::<u64, 9>::default();
+        let prices = Prices::new_for_test(120, 120, 1);
+        market.deposit(1_000_000_000, 0, prices)?.execute()?;
+        market.deposit(0, 1_000_000_000, prices)?.execute()?;
+        let mut position = TestPosition::long(true);
+        let _ = position
+            .ops(&mut market)
+            .increase(
+                Prices::new_for_test(123, 123, 1),
+                100_000_000,
+                80_000_000_000,
+                None,
+            )?
+            .execute()?;
+
+        let report = position
+            .ops(&mut market)
+            .decrease(
+                Prices::new_for_test(125, 125, 1),
+                40_000_000_000,
+                None,
+                0,
+                Default::default(),
+            )?
+            .execute()?;
+        println!("{report:#?}");
+
+        let breakdown = report.fee_breakdown();
+        // The collateral token is the long token.
+        let total_cost_value = report.fees().total_cost_amount()? * 125;
+        assert!(total_cost_value > 0);
+        assert_eq!(breakdown.total_fee_value()?, total_cost_value);
+        assert_eq!(*breakdown.swap_fee_value(), 0);
+
+        let price_impact_value = report.price_impact_value();
+        let expected_impact = if price_impact_value.is_negative() {
+            price_impact_value.unsigned_abs()
+        } else {
+            0
+        };
+        assert_eq!(*breakdown.price_impact_value(), expected_impact);
+        assert_eq!(breakdown.total_value()?, total_cost_value + expected_impact);
+        Ok(())
+    }
 }
@@ -3,7 +3,13 @@ use std::fmt;
 #[cfg(feature = "anchor-lang")]
 use anchor_lang::Space;
 
-use crate::{num::Unsigned, params::fee::PositionFees, position::InsolventCloseStep};
+use num_traits::{CheckedAdd, CheckedMul};
+
+use crate::{
+    num::Unsigned,
+    params::fee::{FeeBreakdown, PositionFees},
+    position::InsolventCloseStep,
+};
 
 use super::{ClaimableCollateral, DecreasePositionParams, ProcessCollateralResult};
 
@@ -27,6 +33,7 @@ pub struct DecreasePositionReport<Unsigned, Signed> {
     initial_size_delta_usd: Unsigned,
     size_delta_usd: Unsigned,
     fees: PositionFees<Unsigned>,
+    fee_breakdown: FeeBreakdown<Unsigned>,
     pnl: Pnl<Signed>,
     insolvent_close_step: Option<InsolventCloseStep>,
     // Output
@@ -49,6 +56,7 @@ where
     const INIT_SPACE: usize = Signed::INIT_SPACE
         + 6 * Unsigned::INIT_SPACE
         + PositionFees::<Unsigned>::INIT_SPACE
+        + FeeBreakdown::<Unsigned>::INIT_SPACE
         + Pnl::<Signed>::INIT_SPACE
         + 1
         + InsolventCloseStep::INIT_SPACE
@@ -75,6 +83,7 @@ where
             .field("initial_size_delta_usd", &self.initial_size_delta_usd)
             .field("size_delta_usd", &self.size_delta_usd)
             .field("fees", &self.fees)
+            .field("fee_breakdown", &self.fee_breakdown)
             .field("pnl", &self.pnl)
             .field("insolvent_close_step", &self.insolvent_close_step)
             .field("should_remove", &self.should_remove)
@@ -99,13 +108,16 @@ where
 }
 
 impl<T: Unsigned + Clone> DecreasePositionReport<T, T::Signed> {
-    pub(super) fn new(
+    pub(super) fn try_new(
         params: &DecreasePositionParams<T>,
         execution: ProcessCollateralResult<T>,
         withdrawable_collateral_amount: T,
         size_delta_usd: T,
         should_remove: bool,
-    ) -> Self {
+    ) -> crate::Result<Self>
+    where
+        T: Ord + CheckedAdd + CheckedMul,
+    {
         let claimable_funding_long_token_amount = execution
             .fees
             .funding_fees()
@@ -116,7 +128,16 @@ impl<T: Unsigned + Clone> DecreasePositionReport<T, T::Signed> {
             .funding_fees()
             .claimable_short_token_amount()
             .clone();
-        Self {
+        let prices = params.prices();
+        let collateral_token_price = if execution.is_output_token_long {
+            &prices.long_token_price
+        } else {
+            &prices.short_token_price
+        };
+        let fee_breakdown = FeeBreakdown::default()
+            .set_position_fees(&execution.fees, collateral_token_price.pick_price(false))?
+            .set_price_impact(&execution.price_impact_value);
+        Ok(Self {
             price_impact_value: execution.price_impact_value,
             price_impact_diff: execution.price_impact_diff,
             execution_price: execution.execution_price,
@@ -125,6 +146,7 @@ impl<T: Unsigned + Clone> DecreasePositionReport<T, T::Signed> {
             initial_size_delta_usd: params.initial_size_delta_usd.clone(),
             size_delta_usd,
             fees: execution.fees,
+            fee_breakdown,
             pnl: execution.pnl,
             insolvent_close_step: execution.collateral.insolvent_close_step,
             // Output
@@ -139,7 +161,7 @@ impl<T: Unsigned + Clone> DecreasePositionReport<T, T::Signed> {
             claimable_funding_short_token_amount,
             for_holding: execution.collateral.for_holding,
             for_user: execution.collateral.for_user,
-        }
+        })
     }
 
     /// Get size delta in tokens.
@@ -177,6 +199,11 @@ impl<T: Unsigned + Clone> DecreasePositionReport<T, T::Signed> {
         &self.fees
     }
 
+    /// Get fee breakdown.
+    pub fn fee_breakdown(&self) -> &FeeBreakdown<T> {
+        &self.fee_breakdown
+    }
+
     /// Returns whether the output token (collateral token) is the long token.
     pub fn is_output_token_long(&self) -> bool {
         self.is_output_token_long
@@ -6,7 +6,7 @@ use crate::{
         SwapMarketMutExt,
     },
     num::{MulDiv, Unsigned, UnsignedAbs},
-    params::Fees,
+    params::{FeeBreakdown, Fees},
     price::{Price, Prices},
     utils, BalanceExt, PnlFactorKind, PoolExt,
 };
@@ -89,6 +89,7 @@ pub struct DepositReport<Unsigned, Signed> {
     minted: Unsigned,
     price_impact: Signed,
     fees: [Fees<Unsigned>; 2],
+    fee_breakdown: FeeBreakdown<Unsigned>,
 }
 
 #[cfg(feature = "gmsol-utils")]
@@ -100,25 +101,34 @@ where
     const INIT_SPACE: usize = DepositParams::<Unsigned>::INIT_SPACE
         + Unsigned::INIT_SPACE
         + Signed::INIT_SPACE
-        + 2 * Fees::<Unsigned>::INIT_SPACE;
+        + 2 * Fees::<Unsigned>::INIT_SPACE
+        + FeeBreakdown::<Unsigned>::INIT_SPACE;
 }
 
 impl<T> DepositReport<T, T::Signed>
 where
     T: Unsigned,
 {
-    fn new(
+    fn try_new(
         params: DepositParams<T>,
         price_impact: T::Signed,
         minted: T,
         fees: [Fees<T>; 2],
-    ) -> Self {
-        Self {
+    ) -> crate::Result<Self>
+    where
+        T: Clone + Ord + CheckedAdd + CheckedMul,
+    {
+        let fee_breakdown = FeeBreakdown::default()
+            .add_swap_fees(&fees[0], params.long_token_price().pick_price(false))?
+            .add_swap_fees(&fees[1], params.short_token_price().pick_price(false))?
+            .set_price_impact(&price_impact);
+        Ok(Self {
             params,
             minted,
             price_impact,
             fees,
-        }
+            fee_breakdown,
+        })
     }
 
     /// Get minted.
@@ -145,6 +155,11 @@ where
     pub fn short_token_fees(&self) -> &Fees<T> {
         &self.fees[1]
     }
+
+    /// Get fee breakdown.
+    pub fn fee_breakdown(&self) -> &FeeBreakdown<T> {
+        &self.fee_breakdown
+    }
 }
 
 impl<const DECIMALS: u8, M: LiquidityMarketMut<DECIMALS>> Deposit<M, DECIMALS> {
@@ -388,7 +403,7 @@ where
                     .ok_or(crate::Error::Overflow)?;
                 all_fees[1] = fees;
             }
-            DepositReport::new(self.params, price_impact, market_token_to_mint, all_fees)
+            DepositReport::try_new(self.params, price_impact, market_token_to_mint, all_fees)?
         };
         self.market.mint(&report.minted)?;
         Ok(report)
@@ -583,4 +598,43 @@ mod tests {
         println!("{market:#?}");
         Ok(())
     }
+
+    #[test]
+    fn fee_breakdown() -> crate::Result<()> {
+        use crate::num::UnsignedAbs;
+        use num_traits::Signed;
+
+        let mut market = TestMarket::<u64, 9>::default();
+        let prices = Prices::new_for_test(120, 120, 1);
+        market
+            .deposit(1_000_000_000, 1_000_000_000, prices)?
+            .execute()?;
+        // An imbalanced deposit has negative price impact.
+        let report = market
+            .deposit(1_000_000_000, 10_000_000, prices)?
+            .execute()?;
+        println!("{report:#?}");
+        assert!(report.price_impact().is_negative());
+
+        let breakdown = report.fee_breakdown();
+        let long_fees = report.long_token_fees();
+        let short_fees = report.short_token_fees();
+        let swap_fee_value =
+            (long_fees.fee_amount_for_pool() + long_fees.fee_amount_for_receiver()) * 120
+                + (short_fees.fee_amount_for_pool() + short_fees.fee_amount_for_receiver());
+        assert_eq!(*breakdown.swap_fee_value(), swap_fee_value);
+        assert_eq!(
+            *breakdown.price_impact_value(),
+            report.price_impact().unsigned_abs()
+        );
+        assert_eq!(*breakdown.position_fee_value(), 0);
+        assert_eq!(*breakdown.borrowing_fee_value(), 0);
+        assert_eq!(*breakdown.funding_fee_value(), 0);
+        assert_eq!(*breakdown.liquidation_fee_value(), 0);
+        assert_eq!(
+            breakdown.total_value()?,
+            swap_fee_value + report.price_impact().unsigned_abs()
+        );
+        Ok(())
+    }
 }
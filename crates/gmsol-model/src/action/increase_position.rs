@@ -1,10 +1,10 @@
-use num_traits::{CheckedAdd, CheckedDiv, CheckedNeg, Signed, Zero};
+use num_traits::{CheckedAdd, CheckedDiv, CheckedMul, CheckedNeg, Signed, Zero};
 use std::fmt;
 
 use crate::{
     market::{BaseMarketExt, BaseMarketMutExt, PerpMarketExt, PositionImpactMarketMutExt},
     num::Unsigned,
-    params::fee::PositionFees,
+    params::fee::{FeeBreakdown, PositionFees},
     position::{CollateralDelta, Position, PositionExt},
     price::{Price, Prices},
     BorrowingFeeMarketExt, PerpMarketMut, PoolExt, PositionMut, PositionMutExt,
@@ -70,6 +70,7 @@ pub struct IncreasePositionReport<Unsigned, Signed> {
     execution: ExecutionParams<Unsigned, Signed>,
     collateral_delta_amount: Signed,
     fees: PositionFees<Unsigned>,
+    fee_breakdown: FeeBreakdown<Unsigned>,
     /// Output amounts that must be processed.
     claimable_funding_long_token_amount: Unsigned,
     claimable_funding_short_token_amount: Unsigned,
@@ -85,6 +86,7 @@ where
         + ExecutionParams::<Unsigned, Signed>::INIT_SPACE
         + Signed::INIT_SPACE
         + PositionFees::<Unsigned>::INIT_SPACE
+        + FeeBreakdown::<Unsigned>::INIT_SPACE
         + 2 * Unsigned::INIT_SPACE;
 }
 
@@ -98,6 +100,7 @@ where
             .field("execution", &self.execution)
             .field("collateral_delta_amount", &self.collateral_delta_amount)
             .field("fees", &self.fees)
+            .field("fee_breakdown", &self.fee_breakdown)
             .field(
                 "claimable_funding_long_token_amount",
                 &self.claimable_funding_long_token_amount,
@@ -111,24 +114,32 @@ where
 }
 
 impl<T: Unsigned + Clone> IncreasePositionReport<T, T::Signed> {
-    fn new(
+    fn try_new(
         params: IncreasePositionParams<T>,
         execution: ExecutionParams<T, T::Signed>,
         collateral_delta_amount: T::Signed,
         fees: PositionFees<T>,
-    ) -> Self {
+        collateral_token_price: &Price<T>,
+    ) -> crate::Result<Self>
+    where
+        T: Ord + CheckedAdd + CheckedMul,
+    {
         let claimable_funding_long_token_amount =
             fees.funding_fees().claimable_long_token_amount().clone();
         let claimable_funding_short_token_amount =
             fees.funding_fees().claimable_short_token_amount().clone();
-        Self {
+        let fee_breakdown = FeeBreakdown::default()
+            .set_position_fees(&fees, collateral_token_price.pick_price(false))?
+            .set_price_impact(&execution.price_impact_value);
+        Ok(Self {
             params,
             execution,
             collateral_delta_amount,
             fees,
+            fee_breakdown,
             claimable_funding_long_token_amount,
             claimable_funding_short_token_amount,
-        }
+        })
     }
 
     /// Get claimable funding amounts, returns `(long_amount, short_amount)`.
@@ -159,6 +170,11 @@ impl<T: Unsigned + Clone> IncreasePositionReport<T, T::Signed> {
     pub fn fees(&self) -> &PositionFees<T> {
         &self.fees
     }
+
+    /// Get fee breakdown.
+    pub fn fee_breakdown(&self) -> &FeeBreakdown<T> {
+        &self.fee_breakdown
+    }
 }
 
 /// Execution Params for increasing position.
@@ -538,12 +554,14 @@ where
 
         self.position.on_increased()?;
 
-        Ok(IncreasePositionReport::new(
+        let collateral_token_price = self.collateral_price().clone();
+        IncreasePositionReport::try_new(
             self.params,
             execution,
             collateral_delta_amount,
             fees,
-        ))
+            &collateral_token_price,
+        )
     }
 }
 
@@ -578,4 +596,40 @@ mod tests {
         println!("{position:#?}");
         Ok(())
     }
+
+    #[test]
+    fn fee_breakdown() -> crate::Result<()> {
+        let mut market = TestMarket::<u64, 9>::default();
+        let prices = Prices::new_for_test(120, 120, 1);
+        market.deposit(1_000_000_000, 0, prices)?.execute()?;
+        market.deposit(0, 1_000_000_000, prices)?.execute()?;
+        let mut position = TestPosition::long(true);
+        let report = position
+            .ops(&mut market)
+            .increase(
+                Prices::new_for_test(123, 123, 1),
+                100_000_000,
+                8_000_000_000,
+                None,
+            )?
+            .execute()?;
+        println!("{report:#?}");
+
+        let breakdown = report.fee_breakdown();
+        // The collateral token is the long token.
+        let total_cost_value = report.fees().total_cost_amount()? * 123;
+        assert!(total_cost_value > 0);
+        assert_eq!(breakdown.total_fee_value()?, total_cost_value);
+        assert_eq!(*breakdown.swap_fee_value(), 0);
+
+        let price_impact_value = *report.execution().price_impact_value();
+        let expected_impact = if price_impact_value.is_negative() {
+            price_impact_value.unsigned_abs()
+        } else {
+            0
+        };
+        assert_eq!(*breakdown.price_impact_value(), expected_impact);
+        assert_eq!(breakdown.total_value()?, total_cost_value + expected_impact);
+        Ok(())
+    }
 }
@@ -5,7 +5,7 @@ use crate::{
     num::{MulDiv, Unsigned, UnsignedAbs},
     params::Fees,
     price::{Price, Prices},
-    BalanceExt, Delta, PnlFactorKind, Pool, SwapMarketExt, SwapMarketMut,
+    BalanceExt, Delta, PnlFactorKind, Pool, SwapMarket, SwapMarketExt, SwapMarketMut,
 };
 
 use num_traits::{CheckedAdd, CheckedMul, CheckedNeg, CheckedSub, Signed, Zero};
@@ -19,7 +19,7 @@ pub struct Swap<M: BaseMarket<DECIMALS>, const DECIMALS: u8> {
     params: SwapParams<M::Num>,
 }
 
-impl<const DECIMALS: u8, M: SwapMarketMut<DECIMALS>> Swap<M, DECIMALS> {
+impl<const DECIMALS: u8, M: SwapMarket<DECIMALS>> Swap<M, DECIMALS> {
     /// Create a new swap in the given market.
     pub fn try_new(
         market: M,
@@ -269,6 +269,15 @@ impl<const DECIMALS: u8, M: SwapMarketMut<DECIMALS>> Swap<M, DECIMALS> {
 
         Ok((cache, result))
     }
+
+    /// Simulate the swap without modifying the market.
+    pub fn simulate(self) -> crate::Result<SwapReport<M::Num, <M::Num as Unsigned>::Signed>> {
+        let (_, result) = self.try_execute()?;
+        Ok(SwapReport {
+            params: self.params,
+            result,
+        })
+    }
 }
 
 impl<const DECIMALS: u8, M> MarketAction for Swap<M, DECIMALS>
@@ -544,11 +553,12 @@ where
 #[cfg(test)]
 mod tests {
     use crate::{
-        market::{LiquidityMarketMutExt, SwapMarketMutExt},
+        market::{LiquidityMarketMutExt, SwapMarketExt, SwapMarketMutExt},
+        params::PriceImpactParams,
         pool::Balance,
         price::Prices,
-        test::TestMarket,
-        BaseMarket, LiquidityMarket, MarketAction,
+        test::{TestMarket, TestMarketConfig},
+        BaseMarket, LiquidityMarket, MarketAction, SwapMarket,
     };
 
     #[test]
@@ -716,4 +726,50 @@ mod tests {
 
         Ok(())
     }
+
+    /// A test for simulating swaps.
+    #[test]
+    fn simulate_swap() -> crate::Result<()> {
+        let mut market = TestMarket::<u64, 9>::with_config(TestMarketConfig {
+            swap_impact_params: PriceImpactParams::builder()
+                .exponent(2_000_000_000)
+                .positive_factor(0)
+                .negative_factor(0)
+                .build(),
+            ..Default::default()
+        });
+        let prices = Prices::new_for_test(120, 120, 1);
+        market.deposit(1_000_000, 120_000_000, prices)?.execute()?;
+
+        // A balanced pool without swap impact only charges the swap fee.
+        let before_market = market.clone();
+        let token_in_amount = 100_000;
+        let report = market.simulate_swap(true, token_in_amount, prices)?;
+        let fees = report.token_in_fees();
+        let fee_factor = *market.swap_fee_params()?.factor(false);
+        assert_eq!(*report.price_impact(), 0);
+        assert_eq!(
+            fees.fee_amount_for_receiver() + fees.fee_amount_for_pool(),
+            token_in_amount * fee_factor / 1_000_000_000,
+        );
+        assert_eq!(
+            before_market.liquidity_pool()?.long_amount()?,
+            market.liquidity_pool()?.long_amount()?
+        );
+        assert_eq!(
+            before_market.claimable_fee_pool()?.long_amount()?,
+            market.claimable_fee_pool()?.long_amount()?
+        );
+
+        // The simulation should match the execution.
+        let executed = market.swap(true, token_in_amount, prices)?.execute()?;
+        assert_eq!(executed.token_out_amount(), report.token_out_amount());
+
+        // A swap improving the balance of the pool has positive impact.
+        let mut market = TestMarket::<u64, 9>::default();
+        market.deposit(1_000_000_000, 0, prices)?.execute()?;
+        let report = market.simulate_swap(false, 100_000_000, prices)?;
+        assert!(*report.price_impact() > 0);
+        Ok(())
+    }
 }
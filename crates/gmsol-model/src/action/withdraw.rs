@@ -1,7 +1,7 @@
 use crate::{
     market::{BaseMarket, BaseMarketExt, LiquidityMarketExt, LiquidityMarketMut},
     num::{MulDiv, Unsigned, UnsignedAbs},
-    params::Fees,
+    params::{FeeBreakdown, Fees},
     price::{Price, Prices},
     utils, BalanceExt, PnlFactorKind, PoolExt,
 };
@@ -62,12 +62,15 @@ pub struct WithdrawReport<T> {
     short_token_fees: Fees<T>,
     long_token_output: T,
     short_token_output: T,
+    fee_breakdown: FeeBreakdown<T>,
 }
 
 #[cfg(feature = "gmsol-utils")]
 impl<T: gmsol_utils::InitSpace> gmsol_utils::InitSpace for WithdrawReport<T> {
-    const INIT_SPACE: usize =
-        WithdrawParams::<T>::INIT_SPACE + 2 * Fees::<T>::INIT_SPACE + 2 * T::INIT_SPACE;
+    const INIT_SPACE: usize = WithdrawParams::<T>::INIT_SPACE
+        + 2 * Fees::<T>::INIT_SPACE
+        + 2 * T::INIT_SPACE
+        + FeeBreakdown::<T>::INIT_SPACE;
 }
 
 impl<T> WithdrawReport<T> {
@@ -97,6 +100,11 @@ impl<T> WithdrawReport<T> {
     pub fn short_token_output(&self) -> &T {
         &self.short_token_output
     }
+
+    /// Get fee breakdown.
+    pub fn fee_breakdown(&self) -> &FeeBreakdown<T> {
+        &self.fee_breakdown
+    }
 }
 
 impl<const DECIMALS: u8, M: LiquidityMarketMut<DECIMALS>> Withdrawal<M, DECIMALS> {
@@ -235,12 +243,23 @@ impl<const DECIMALS: u8, M: LiquidityMarketMut<DECIMALS>> MarketAction for Withd
 
         self.market.burn(&self.params.market_token_amount)?;
 
+        let fee_breakdown = FeeBreakdown::default()
+            .add_swap_fees(
+                &long_token_fees,
+                self.params.long_token_price().pick_price(true),
+            )?
+            .add_swap_fees(
+                &short_token_fees,
+                self.params.short_token_price().pick_price(true),
+            )?;
+
         Ok(WithdrawReport {
             params: self.params,
             long_token_fees,
             short_token_fees,
             long_token_output: long_token_amount,
             short_token_output: short_token_amount,
+            fee_breakdown,
         })
     }
 }
@@ -350,4 +369,27 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn fee_breakdown() -> crate::Result<()> {
+        let mut market = TestMarket::<u64, 9>::default();
+        let prices = Prices::new_for_test(120, 120, 1);
+        market
+            .deposit(1_000_000_000, 1_000_000_000, prices)?
+            .execute()?;
+        let report = market.withdraw(1_000_000_000, prices)?.execute()?;
+        println!("{report:#?}");
+
+        let breakdown = report.fee_breakdown();
+        let long_fees = report.long_token_fees();
+        let short_fees = report.short_token_fees();
+        let swap_fee_value =
+            (long_fees.fee_amount_for_pool() + long_fees.fee_amount_for_receiver()) * 120
+                + (short_fees.fee_amount_for_pool() + short_fees.fee_amount_for_receiver());
+        assert!(swap_fee_value > 0);
+        assert_eq!(*breakdown.swap_fee_value(), swap_fee_value);
+        assert_eq!(*breakdown.price_impact_value(), 0);
+        assert_eq!(breakdown.total_value()?, swap_fee_value);
+        Ok(())
+    }
 }
@@ -103,4 +103,7 @@ pub enum Error {
     /// Unable to get funding factor when the open interest is empty.
     #[error("unable to get funding factor when the open interest is empty")]
     UnableToGetFundingFactorEmptyOpenInterest,
+    /// Max leverage exceeded.
+    #[error("max leverage exceeded")]
+    MaxLeverageExceeded,
 }
@@ -8,7 +8,7 @@ use crate::{
 };
 use num_traits::{CheckedAdd, CheckedSub, Signed, Zero};
 
-use super::get_msg_by_side;
+use super::{get_msg_by_side, utils::MarketUtils};
 
 /// Base Market trait.
 pub trait BaseMarket<const DECIMALS: u8> {
@@ -136,6 +136,66 @@ impl<M: BaseMarket<DECIMALS>, const DECIMALS: u8> BaseMarket<DECIMALS> for &mut
     }
 }
 
+impl<M: BaseMarket<DECIMALS>, const DECIMALS: u8> BaseMarket<DECIMALS> for &M {
+    type Num = M::Num;
+
+    type Signed = M::Signed;
+
+    type Pool = M::Pool;
+
+    fn liquidity_pool(&self) -> crate::Result<&Self::Pool> {
+        (**self).liquidity_pool()
+    }
+
+    fn swap_impact_pool(&self) -> crate::Result<&Self::Pool> {
+        (**self).swap_impact_pool()
+    }
+
+    fn claimable_fee_pool(&self) -> crate::Result<&Self::Pool> {
+        (**self).claimable_fee_pool()
+    }
+
+    fn open_interest_pool(&self, is_long: bool) -> crate::Result<&Self::Pool> {
+        (**self).open_interest_pool(is_long)
+    }
+
+    fn open_interest_in_tokens_pool(&self, is_long: bool) -> crate::Result<&Self::Pool> {
+        (**self).open_interest_in_tokens_pool(is_long)
+    }
+
+    fn collateral_sum_pool(&self, is_long: bool) -> crate::Result<&Self::Pool> {
+        (**self).collateral_sum_pool(is_long)
+    }
+
+    fn usd_to_amount_divisor(&self) -> Self::Num {
+        (**self).usd_to_amount_divisor()
+    }
+
+    fn max_pool_amount(&self, is_long_token: bool) -> crate::Result<Self::Num> {
+        (**self).max_pool_amount(is_long_token)
+    }
+
+    fn pnl_factor_config(&self, kind: PnlFactorKind, is_long: bool) -> crate::Result<Self::Num> {
+        (**self).pnl_factor_config(kind, is_long)
+    }
+
+    fn reserve_factor(&self) -> crate::Result<Self::Num> {
+        (**self).reserve_factor()
+    }
+
+    fn open_interest_reserve_factor(&self) -> crate::Result<Self::Num> {
+        (**self).open_interest_reserve_factor()
+    }
+
+    fn max_open_interest(&self, is_long: bool) -> crate::Result<Self::Num> {
+        (**self).max_open_interest(is_long)
+    }
+
+    fn ignore_open_interest_for_usage_factor(&self) -> crate::Result<bool> {
+        (**self).ignore_open_interest_for_usage_factor()
+    }
+}
+
 impl<M: BaseMarketMut<DECIMALS>, const DECIMALS: u8> BaseMarketMut<DECIMALS> for &mut M {
     fn liquidity_pool_mut(&mut self) -> crate::Result<&mut Self::Pool> {
         (**self).liquidity_pool_mut()
@@ -242,6 +302,27 @@ pub trait BaseMarketExt<const DECIMALS: u8>: BaseMarket<DECIMALS> {
             .0)
     }
 
+    /// Get the PnL-to-pool-value factor for one side.
+    ///
+    /// Unlike [`pnl_factor`](BaseMarketExt::pnl_factor), a positive pnl is first capped
+    /// by the max pnl factor of the given `kind`, which is the pnl that will be accounted
+    /// into the pool value for that kind of action. The returned factor is in
+    /// [`UNIT`](FixedPointOps::UNIT) terms.
+    fn pnl_to_pool_value_factor(
+        &self,
+        prices: &Prices<Self::Num>,
+        kind: PnlFactorKind,
+        is_long: bool,
+        maximize: bool,
+    ) -> crate::Result<Self::Signed> {
+        let pool_value = self.pool_value_without_pnl_for_one_side(prices, is_long, !maximize)?;
+        let pnl = self.pnl(&prices.index_token_price, is_long, maximize)?;
+        let capped_pnl = self.cap_pnl(is_long, &pnl, &pool_value, kind)?;
+        crate::utils::div_to_factor_signed(&capped_pnl, &pool_value).ok_or(
+            crate::Error::Computation("calculating pnl to pool value factor"),
+        )
+    }
+
     /// Validate (primary) pool amount.
     fn validate_pool_amount(&self, is_long_token: bool) -> crate::Result<()> {
         let amount = self.liquidity_pool()?.amount(is_long_token)?;
@@ -499,3 +580,116 @@ impl<T: Unsigned> PnlFactorExceeded<T> {
         crate::utils::apply_factor(&self.pool_value, &diff_factor)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        market::LiquidityMarketMutExt,
+        price::Prices,
+        test::{TestMarket, TestPosition},
+        MarketAction, PositionMutExt,
+    };
+
+    use super::*;
+
+    const KINDS: [PnlFactorKind; 5] = [
+        PnlFactorKind::MaxAfterDeposit,
+        PnlFactorKind::MaxAfterWithdrawal,
+        PnlFactorKind::MaxForTrader,
+        PnlFactorKind::ForAdl,
+        PnlFactorKind::MinAfterAdl,
+    ];
+
+    fn open_long(
+        market: &mut TestMarket<u64, 9>,
+        prices: Prices<u64>,
+        size: u64,
+    ) -> crate::Result<TestPosition<u64, 9>> {
+        let mut position = TestPosition::long(true);
+        _ = position
+            .ops(market)
+            .increase(prices, 100_000_000, size, None)?
+            .execute()?;
+        Ok(position)
+    }
+
+    #[test]
+    fn pnl_to_pool_value_factor_for_profitable_pool() -> crate::Result<()> {
+        let mut market = TestMarket::<u64, 9>::default();
+        let prices = Prices::new_for_test(120, 120, 1);
+        market.deposit(1_000_000_000, 0, prices)?.execute()?;
+        market.deposit(0, 100_000_000_000, prices)?.execute()?;
+        open_long(&mut market, prices, 50_000_000_000)?;
+
+        let prices = Prices::new_for_test(240, 120, 1);
+        let pnl_factor = market.pnl_factor(&prices, true, true)?;
+        assert!(pnl_factor > 300_000_000 && pnl_factor < 600_000_000);
+
+        for kind in KINDS {
+            let factor = market.pnl_to_pool_value_factor(&prices, kind, true, true)?;
+            let max_factor = market.pnl_factor_config(kind, true)?;
+            if pnl_factor.unsigned_abs() > max_factor {
+                assert_eq!(factor.unsigned_abs(), max_factor, "{kind:?}");
+            } else {
+                assert_eq!(factor, pnl_factor, "{kind:?}");
+            }
+        }
+
+        // No open interest for short side.
+        assert_eq!(
+            market.pnl_to_pool_value_factor(&prices, PnlFactorKind::ForAdl, false, true)?,
+            0
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn pnl_to_pool_value_factor_for_loss_heavy_pool() -> crate::Result<()> {
+        let mut market = TestMarket::<u64, 9>::default();
+        let prices = Prices::new_for_test(120, 120, 1);
+        market.deposit(1_000_000_000, 0, prices)?.execute()?;
+        market.deposit(0, 100_000_000_000, prices)?.execute()?;
+        open_long(&mut market, prices, 50_000_000_000)?;
+
+        let prices = Prices::new_for_test(30, 120, 1);
+        let pnl_factor = market.pnl_factor(&prices, true, false)?;
+        assert!(pnl_factor.is_negative());
+
+        // Losses are never capped.
+        for kind in KINDS {
+            let factor = market.pnl_to_pool_value_factor(&prices, kind, true, false)?;
+            assert_eq!(factor, pnl_factor, "{kind:?}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn pnl_to_pool_value_factor_for_pure_market() -> crate::Result<()> {
+        let mut market = TestMarket::<u64, 9>::default().into_pure();
+        let prices = Prices::new_for_test(120, 120, 120);
+        market.deposit(2_000_000_000, 0, prices)?.execute()?;
+        // Both sides share the same pool, so a long-token-only deposit fills both of them.
+        let pool = market.liquidity_pool()?;
+        assert!(pool.short_amount()? > 0);
+        assert_eq!(pool.long_amount()?, pool.short_amount()?);
+        open_long(&mut market, prices, 50_000_000_000)?;
+
+        let prices = Prices::new_for_test(240, 240, 240);
+        let pnl_factor = market.pnl_factor(&prices, true, true)?;
+        assert!(pnl_factor.is_positive());
+        for kind in KINDS {
+            let factor = market.pnl_to_pool_value_factor(&prices, kind, true, true)?;
+            let max_factor = market.pnl_factor_config(kind, true)?;
+            assert_eq!(factor, pnl_factor.min(max_factor as i64), "{kind:?}");
+        }
+
+        let prices = Prices::new_for_test(60, 60, 60);
+        let pnl_factor = market.pnl_factor(&prices, true, false)?;
+        assert!(pnl_factor.is_negative());
+        for kind in KINDS {
+            let factor = market.pnl_to_pool_value_factor(&prices, kind, true, false)?;
+            assert_eq!(factor, pnl_factor, "{kind:?}");
+        }
+        Ok(())
+    }
+}
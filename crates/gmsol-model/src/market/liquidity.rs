@@ -4,7 +4,7 @@ use crate::{
     action::{deposit::Deposit, withdraw::Withdrawal},
     fixed::FixedPointOps,
     market::utils::MarketUtils,
-    num::{Unsigned, UnsignedAbs},
+    num::{MulDiv, Unsigned, UnsignedAbs},
     price::Prices,
     BorrowingFeeMarket, PnlFactorKind, PositionImpactMarket,
 };
@@ -169,6 +169,51 @@ pub trait LiquidityMarketExt<const DECIMALS: u8>: LiquidityMarket<DECIMALS> {
         crate::utils::market_token_amount_to_usd(&one, &pool_value.unsigned_abs(), &supply)
             .ok_or(crate::Error::Computation("calculating market token price"))
     }
+
+    /// Get the amount of market tokens to mint for the given USD value of liquidity.
+    ///
+    /// Fees and price impact are not taken into account. Pass `maximize = true` to
+    /// use the same pool value as deposit execution, which yields fewer market tokens.
+    fn market_token_amount_for_usd(
+        &self,
+        usd_value: &Self::Num,
+        prices: &Prices<Self::Num>,
+        maximize: bool,
+    ) -> crate::Result<Self::Num> {
+        let divisor = self.usd_to_amount_divisor();
+        if divisor.is_zero() {
+            return Err(crate::Error::Computation("usd to amount divisor is zero"));
+        }
+        let pool_value = self.pool_value(prices, PnlFactorKind::MaxAfterDeposit, maximize)?;
+        if pool_value.is_negative() {
+            return Err(crate::Error::InvalidPoolValue(
+                "market token amount for usd: the pool value is negative",
+            ));
+        }
+        let pool_value = pool_value.unsigned_abs();
+        let supply = self.total_supply();
+        if supply.is_zero() {
+            // First deposit: one market token per unit of USD, and the existing
+            // pool value (if any) is credited to the depositor.
+            pool_value
+                .checked_add(usd_value)
+                .and_then(|value| value.checked_div(&divisor))
+                .ok_or(crate::Error::Computation(
+                    "calculating market token amount for the first deposit",
+                ))
+        } else {
+            if pool_value.is_zero() {
+                return Err(crate::Error::InvalidPoolValue(
+                    "market token amount for usd: the pool value is zero with non-zero supply",
+                ));
+            }
+            supply
+                .checked_mul_div(usd_value, &pool_value)
+                .ok_or(crate::Error::Computation(
+                    "calculating market token amount for usd",
+                ))
+        }
+    }
 }
 
 impl<M: LiquidityMarket<DECIMALS>, const DECIMALS: u8> LiquidityMarketExt<DECIMALS> for M {}
@@ -202,3 +247,84 @@ pub trait LiquidityMarketMutExt<const DECIMALS: u8>: LiquidityMarketMut<DECIMALS
 }
 
 impl<M: LiquidityMarketMut<DECIMALS>, const DECIMALS: u8> LiquidityMarketMutExt<DECIMALS> for M {}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        params::{FeeParams, PriceImpactParams},
+        test::{TestMarket, TestMarketConfig},
+        MarketAction,
+    };
+
+    use super::*;
+
+    fn market_without_fees_and_impact() -> TestMarket<u64, 9> {
+        TestMarket::<u64, 9>::with_config(TestMarketConfig {
+            swap_impact_params: PriceImpactParams::builder()
+                .exponent(2_000_000_000)
+                .positive_factor(0)
+                .negative_factor(0)
+                .build(),
+            swap_fee_params: FeeParams::builder()
+                .fee_receiver_factor(0)
+                .positive_impact_fee_factor(0)
+                .negative_impact_fee_factor(0)
+                .build(),
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn market_token_amount_for_first_deposit() -> crate::Result<()> {
+        let mut market = market_without_fees_and_impact();
+        let prices = Prices::new_for_test(120, 120, 1);
+        assert!(market.total_supply().is_zero());
+
+        let usd_value = 120 * 1_000_000_000 + 120_000_000_000;
+        let expected = market.market_token_amount_for_usd(&usd_value, &prices, true)?;
+        assert_eq!(expected, usd_value);
+
+        let report = market
+            .deposit(1_000_000_000, 120_000_000_000, prices)?
+            .execute()?;
+        assert_eq!(*report.minted(), expected);
+        Ok(())
+    }
+
+    #[test]
+    fn market_token_amount_for_normal_deposit() -> crate::Result<()> {
+        let mut market = market_without_fees_and_impact();
+        let prices = Prices::new_for_test(120, 120, 1);
+        market
+            .deposit(1_000_000_000, 120_000_000_000, prices)?
+            .execute()?;
+
+        // The market token price has doubled.
+        let prices = Prices::new_for_test(120, 360, 1);
+        let usd_value = 360 * 1_000_000 + 360_000_000;
+        let expected = market.market_token_amount_for_usd(&usd_value, &prices, true)?;
+        assert_eq!(expected, usd_value / 2);
+
+        let report = market.deposit(1_000_000, 360_000_000, prices)?.execute()?;
+        assert_eq!(*report.minted(), expected);
+        Ok(())
+    }
+
+    #[test]
+    fn market_token_amount_for_pure_market_deposit() -> crate::Result<()> {
+        // The long token and the short token are the same in a pure market.
+        let mut market = market_without_fees_and_impact();
+        let prices = Prices::new_for_test(120, 120, 120);
+        market
+            .deposit(1_000_000_000, 1_000_000_000, prices)?
+            .execute()?;
+
+        let usd_value = 120 * 2_000_000;
+        let expected = market.market_token_amount_for_usd(&usd_value, &prices, true)?;
+        assert_eq!(expected, usd_value);
+
+        let report = market.deposit(1_000_000, 1_000_000, prices)?.execute()?;
+        assert_eq!(*report.minted(), expected);
+        Ok(())
+    }
+}
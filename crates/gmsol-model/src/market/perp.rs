@@ -103,6 +103,18 @@ pub trait PerpMarketMut<const DECIMALS: u8>:
     ) -> crate::Result<()> {
         Ok(())
     }
+
+    /// Apply delta to the virtual open interest shared with other markets, if any.
+    ///
+    /// Nothing happens if the market does not have a
+    /// [virtual open interest pool](crate::PositionImpactMarket::virtual_open_interest_pool).
+    fn apply_delta_to_virtual_open_interest(
+        &mut self,
+        _is_long: bool,
+        _delta: &Self::Signed,
+    ) -> crate::Result<()> {
+        Ok(())
+    }
 }
 
 impl<M: PerpMarket<DECIMALS>, const DECIMALS: u8> PerpMarket<DECIMALS> for &mut M {
@@ -195,6 +207,14 @@ impl<M: PerpMarketMut<DECIMALS>, const DECIMALS: u8> PerpMarketMut<DECIMALS> for
     ) -> crate::Result<()> {
         (**self).on_insufficient_funding_fee_payment(paid_in_collateral_amount, cost_amount)
     }
+
+    fn apply_delta_to_virtual_open_interest(
+        &mut self,
+        is_long: bool,
+        delta: &Self::Signed,
+    ) -> crate::Result<()> {
+        (**self).apply_delta_to_virtual_open_interest(is_long, delta)
+    }
 }
 
 /// Extension trait for [`PerpMarket`].
@@ -221,6 +241,42 @@ pub trait PerpMarketExt<const DECIMALS: u8>: PerpMarket<DECIMALS> {
             .amount(is_long_collateral)
     }
 
+    /// Get the open interest (in USD) for one side.
+    #[inline]
+    fn open_interest_for_one_side(&self, is_long: bool) -> crate::Result<Self::Num> {
+        self.open_interest()?.amount(is_long)
+    }
+
+    /// Get the net open interest (in USD), i.e., `long OI - short OI`.
+    fn net_open_interest(&self) -> crate::Result<Self::Signed> {
+        let open_interest = self.open_interest()?;
+        open_interest
+            .amount(true)?
+            .checked_signed_sub(open_interest.amount(false)?)
+    }
+
+    /// Get the open interest skew factor, i.e., `(long OI - short OI) / (long OI + short OI)`.
+    ///
+    /// A positive factor means that the longs are paying the shorts for funding.
+    /// Returns zero if there is no open interest.
+    fn open_interest_skew_factor(&self) -> crate::Result<Self::Signed> {
+        use num_traits::{CheckedAdd, Zero};
+
+        let open_interest = self.open_interest()?;
+        let long = open_interest.amount(true)?;
+        let short = open_interest.amount(false)?;
+        let total = long
+            .checked_add(&short)
+            .ok_or(crate::Error::Computation("calculating total open interest"))?;
+        if total.is_zero() {
+            return Ok(Zero::zero());
+        }
+        let net = long.checked_signed_sub(short)?;
+        crate::utils::div_to_factor_signed(&net, &total).ok_or(crate::Error::Computation(
+            "calculating open interest skew factor",
+        ))
+    }
+
     /// Validate open interest reserve.
     fn validate_open_interest_reserve(
         &self,
@@ -264,6 +320,56 @@ pub trait PerpMarketExt<const DECIMALS: u8>: PerpMarket<DECIMALS> {
         ))
     }
 
+    /// Validate that the leverage of a position with the given size and collateral value
+    /// does not exceed the max leverage of the market, i.e.,
+    /// `collateral_usd >= size_usd * min_collateral_factor`.
+    ///
+    /// The `collateral_usd` should be the effective collateral value, i.e., the collateral
+    /// value after deducting the pending fees.
+    fn validate_leverage(
+        &self,
+        size_usd: &Self::Num,
+        collateral_usd: &Self::Num,
+    ) -> crate::Result<()> {
+        let params = self.position_params()?;
+        let min_collateral_usd =
+            crate::utils::apply_factor(size_usd, params.min_collateral_factor()).ok_or(
+                crate::Error::Computation("calculating min collateral usd for leverage"),
+            )?;
+        if *collateral_usd < min_collateral_usd {
+            Err(crate::Error::MaxLeverageExceeded)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Get the max position size in USD allowed for the given collateral value,
+    /// i.e., the max `size_usd` that passes [`validate_leverage`](PerpMarketExt::validate_leverage).
+    ///
+    /// # Errors
+    /// - Returns [`DividedByZero`](crate::Error::DividedByZero) if the min collateral factor is zero.
+    fn max_position_size_for_collateral(
+        &self,
+        collateral_usd: &Self::Num,
+    ) -> crate::Result<Self::Num> {
+        use num_traits::{CheckedAdd, CheckedSub, One, Zero};
+
+        let params = self.position_params()?;
+        let min_collateral_factor = params.min_collateral_factor();
+        if min_collateral_factor.is_zero() {
+            return Err(crate::Error::DividedByZero);
+        }
+        // Since `apply_factor` rounds down, the max size is the largest `size` such that
+        // `size * factor < (collateral_usd + 1) * UNIT`.
+        let bound = collateral_usd
+            .checked_add(&One::one())
+            .and_then(|bound| crate::utils::div_to_factor(&bound, min_collateral_factor, true))
+            .ok_or(crate::Error::Computation("calculating max position size"))?;
+        bound
+            .checked_sub(&One::one())
+            .ok_or(crate::Error::Computation("calculating max position size"))
+    }
+
     /// Caps positive position price impact in-place.
     /// If `impact` is not positive, the function does nothing.
     fn cap_positive_position_price_impact(
@@ -346,10 +452,153 @@ pub trait PerpMarketExt<const DECIMALS: u8>: PerpMarket<DECIMALS> {
         }
         Ok(impact_diff)
     }
+
+    /// Estimate the expected value of providing liquidity to the market over the given time horizon.
+    ///
+    /// The estimation assumes that `trading_volume` (in USD) is traded during the time horizon,
+    /// the current open interest and borrowing rates stay unchanged, and the current pending
+    /// PnL of the traders is realized against the pool.
+    fn expected_value(
+        &self,
+        prices: &Prices<Self::Num>,
+        trading_volume: &Self::Num,
+        time_horizon_secs: u64,
+    ) -> crate::Result<ExpectedValue<Self::Num>> {
+        use crate::{
+            market::BorrowingFeeMarketExt,
+            num::{MulDiv, UnsignedAbs},
+            utils,
+        };
+        use num_traits::{
+            CheckedAdd, CheckedDiv, CheckedMul, CheckedSub, FromPrimitive, Signed, Zero,
+        };
+
+        if time_horizon_secs == 0 {
+            return Err(crate::Error::InvalidArgument(
+                "time horizon must be positive",
+            ));
+        }
+
+        let two = Self::Num::from_u64(2).ok_or(crate::Error::Convert)?;
+        let duration = Self::Num::from_u64(time_horizon_secs).ok_or(crate::Error::Convert)?;
+
+        // The order fees are estimated with the average of the fee factors for
+        // positive and negative impacts, excluding the part for the receiver.
+        let fee_params = self.order_fee_params()?;
+        let fee_for_positive_impact =
+            fee_params
+                .fee(true, trading_volume)
+                .ok_or(crate::Error::Computation(
+                    "calculating fee for positive impact",
+                ))?;
+        let fee_for_negative_impact =
+            fee_params
+                .fee(false, trading_volume)
+                .ok_or(crate::Error::Computation(
+                    "calculating fee for negative impact",
+                ))?;
+        let fee = fee_for_positive_impact
+            .checked_add(&fee_for_negative_impact)
+            .and_then(|fee| fee.checked_div(&two))
+            .ok_or(crate::Error::Computation("calculating total fee"))?;
+        let fee_revenue = fee_params
+            .receiver_fee(&fee)
+            .and_then(|receiver_fee| fee.checked_sub(&receiver_fee))
+            .ok_or(crate::Error::Computation(
+                "calculating expected fee revenue",
+            ))?;
+
+        let mut borrowing_fee = Self::Num::zero();
+        for is_long in [true, false] {
+            let open_interest = self.open_interest()?.amount(is_long)?;
+            let factor = self
+                .borrowing_factor_per_second(is_long, prices)?
+                .checked_mul(&duration)
+                .ok_or(crate::Error::Computation(
+                    "calculating borrowing factor delta",
+                ))?;
+            borrowing_fee = utils::apply_factor(&open_interest, &factor)
+                .and_then(|fee| borrowing_fee.checked_add(&fee))
+                .ok_or(crate::Error::Computation(
+                    "calculating expected borrowing fee",
+                ))?;
+        }
+        let borrowing_revenue = utils::apply_factor(
+            &borrowing_fee,
+            self.borrowing_fee_params()?.receiver_factor(),
+        )
+        .and_then(|receiver_fee| borrowing_fee.checked_sub(&receiver_fee))
+        .ok_or(crate::Error::Computation(
+            "calculating expected borrowing revenue",
+        ))?;
+
+        let pnl = self
+            .pnl(&prices.index_token_price, true, true)?
+            .checked_add(&self.pnl(&prices.index_token_price, false, true)?)
+            .ok_or(crate::Error::Computation("calculating total pnl"))?;
+        let pnl_exposure = Self::Signed::zero()
+            .checked_sub(&pnl)
+            .ok_or(crate::Error::Computation("calculating pnl exposure"))?;
+
+        let net_value = fee_revenue
+            .checked_add(&borrowing_revenue)
+            .ok_or(crate::Error::Computation("calculating expected revenue"))?
+            .to_signed()?
+            .checked_add(&pnl_exposure)
+            .ok_or(crate::Error::Computation("calculating expected net value"))?;
+
+        let pool_value = self
+            .pool_value_without_pnl_for_one_side(prices, true, false)?
+            .checked_add(&self.pool_value_without_pnl_for_one_side(prices, false, false)?)
+            .ok_or(crate::Error::Computation("calculating pool value"))?;
+
+        let annualized_yield_factor = if net_value.is_positive() {
+            let seconds_per_year =
+                Self::Num::from_u64(SECONDS_PER_YEAR).ok_or(crate::Error::Convert)?;
+            net_value
+                .unsigned_abs()
+                .checked_mul_div(&seconds_per_year, &duration)
+                .and_then(|annualized| utils::div_to_factor(&annualized, &pool_value, false))
+                .ok_or(crate::Error::Computation(
+                    "calculating annualized yield factor",
+                ))?
+        } else {
+            Zero::zero()
+        };
+
+        Ok(ExpectedValue {
+            fee_revenue,
+            borrowing_revenue,
+            pnl_exposure,
+            net_value,
+            pool_value,
+            annualized_yield_factor,
+        })
+    }
 }
 
 impl<M: PerpMarket<DECIMALS>, const DECIMALS: u8> PerpMarketExt<DECIMALS> for M {}
 
+const SECONDS_PER_YEAR: u64 = 365 * 24 * 3600;
+
+/// Expected value of providing liquidity to a market.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExpectedValue<T: Unsigned> {
+    /// Expected order fee revenue (in USD) for the liquidity providers.
+    pub fee_revenue: T,
+    /// Expected borrowing fee revenue (in USD) for the liquidity providers.
+    pub borrowing_revenue: T,
+    /// Expected PnL (in USD) of the liquidity providers against the current positions.
+    pub pnl_exposure: T::Signed,
+    /// Expected net value (in USD).
+    pub net_value: T::Signed,
+    /// The pool value (in USD) without PnL.
+    pub pool_value: T,
+    /// Annualized yield factor of the expected net value to the pool value.
+    /// Zero if the expected net value is not positive.
+    pub annualized_yield_factor: T,
+}
+
 /// Extension trait for [`PerpMarketMut`].
 pub trait PerpMarketMutExt<const DECIMALS: u8>: PerpMarketMut<DECIMALS> {
     /// Create a [`UpdateFundingState`] action.
@@ -387,3 +636,188 @@ pub trait PerpMarketMutExt<const DECIMALS: u8>: PerpMarketMut<DECIMALS> {
 }
 
 impl<M: PerpMarketMut<DECIMALS>, const DECIMALS: u8> PerpMarketMutExt<DECIMALS> for M {}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        market::LiquidityMarketMutExt,
+        price::Prices,
+        test::{TestMarket, TestPosition},
+        BaseMarketMut, MarketAction, PositionExt, PositionMutExt, PositionState,
+    };
+
+    use super::*;
+
+    #[test]
+    fn leverage_boundary() -> crate::Result<()> {
+        let market = TestMarket::<u64, 9>::default();
+        // The min collateral factor is 1%, i.e., the max leverage is 100x.
+        let collateral_usd = 100_000_000_000;
+
+        let max_size = market.max_position_size_for_collateral(&collateral_usd)?;
+        assert_eq!(max_size / 100, collateral_usd);
+        market.validate_leverage(&max_size, &collateral_usd)?;
+        assert!(matches!(
+            market.validate_leverage(&(max_size + 1), &collateral_usd),
+            Err(crate::Error::MaxLeverageExceeded)
+        ));
+
+        assert_eq!(market.max_position_size_for_collateral(&0)?, 99);
+        market.validate_leverage(&0, &0)?;
+        Ok(())
+    }
+
+    #[test]
+    fn leverage_with_pending_fees() -> crate::Result<()> {
+        let mut market = TestMarket::<u64, 9>::default();
+        let prices = Prices::new_for_test(120, 120, 1);
+        market.deposit(1_000_000_000, 0, prices)?.execute()?;
+        market.deposit(0, 100_000_000_000, prices)?.execute()?;
+
+        let mut position = TestPosition::long(true);
+        _ = position
+            .ops(&mut market)
+            .increase(prices, 100_000_000, 50_000_000_000, None)?
+            .execute()?;
+
+        let (size_usd, collateral_usd, effective_collateral_usd) = {
+            let ops = position.ops(&mut market);
+            let size_usd = *ops.size_in_usd();
+            let collateral_usd = ops.collateral_value(&prices)?;
+            let cost_usd = ops
+                .position_fees(&prices.long_token_price, &size_usd, false, false)?
+                .total_cost_amount()?
+                * prices.long_token_price.pick_price(false);
+            assert!(cost_usd > 0);
+            (size_usd, collateral_usd, collateral_usd - cost_usd)
+        };
+
+        market.validate_leverage(&size_usd, &effective_collateral_usd)?;
+
+        // The pending fees reduce the max position size.
+        let max_size = market.max_position_size_for_collateral(&collateral_usd)?;
+        let effective_max_size =
+            market.max_position_size_for_collateral(&effective_collateral_usd)?;
+        assert!(effective_max_size < max_size);
+        market.validate_leverage(&max_size, &collateral_usd)?;
+        market.validate_leverage(&effective_max_size, &effective_collateral_usd)?;
+        assert!(matches!(
+            market.validate_leverage(&max_size, &effective_collateral_usd),
+            Err(crate::Error::MaxLeverageExceeded)
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn open_interest_skew() -> crate::Result<()> {
+        let mut market = TestMarket::<u64, 9>::default();
+        assert_eq!(market.net_open_interest()?, 0);
+        assert_eq!(market.open_interest_skew_factor()?, 0);
+
+        // Balanced open interest.
+        market
+            .open_interest_pool_mut(true)?
+            .apply_delta_amount(true, &10_000_000)?;
+        market
+            .open_interest_pool_mut(false)?
+            .apply_delta_amount(false, &10_000_000)?;
+        assert_eq!(market.open_interest_for_one_side(true)?, 10_000_000);
+        assert_eq!(market.open_interest_for_one_side(false)?, 10_000_000);
+        assert_eq!(market.net_open_interest()?, 0);
+        assert_eq!(market.open_interest_skew_factor()?, 0);
+
+        // Skewed to long, with the collateral of both sides counted.
+        market
+            .open_interest_pool_mut(true)?
+            .apply_delta_amount(false, &20_000_000)?;
+        assert_eq!(market.open_interest_for_one_side(true)?, 30_000_000);
+        assert_eq!(market.net_open_interest()?, 20_000_000);
+        assert_eq!(market.open_interest_skew_factor()?, 500_000_000);
+
+        // Skewed to short.
+        market
+            .open_interest_pool_mut(false)?
+            .apply_delta_amount(true, &50_000_000)?;
+        assert_eq!(market.open_interest_for_one_side(false)?, 60_000_000);
+        assert_eq!(market.net_open_interest()?, -30_000_000);
+        assert_eq!(market.open_interest_skew_factor()?, -333_333_333);
+        Ok(())
+    }
+
+    #[test]
+    fn expected_value() -> crate::Result<()> {
+        let mut market = TestMarket::<u64, 9>::default();
+        let prices = Prices::new_for_test(100, 100, 1);
+
+        // Pool value = 1,000,000 * 100 = 100,000,000.
+        market
+            .liquidity_pool_mut()?
+            .apply_delta_amount(true, &1_000_000)?;
+        // Long open interest of 25,000,000 with no pending pnl.
+        market
+            .open_interest_pool_mut(true)?
+            .apply_delta_amount(true, &25_000_000)?;
+        market
+            .open_interest_in_tokens_pool_mut(true)?
+            .apply_delta_amount(true, &250_000)?;
+
+        let time_horizon = 365 * 24 * 3600;
+        let volume = 1_000_000_000;
+        let value = market.expected_value(&prices, &volume, time_horizon)?;
+
+        // fee = volume * (0.0005 + 0.0007) / 2 * (1 - 0.37).
+        assert_eq!(value.fee_revenue, 378_000);
+        // borrowing = open interest * 4e-9 * time horizon * (1 - 0.37).
+        assert_eq!(value.borrowing_revenue, 1_986_768);
+        assert_eq!(value.pnl_exposure, 0);
+        assert_eq!(value.net_value, 2_364_768);
+        assert_eq!(value.pool_value, 100_000_000);
+
+        let expected = (volume as f64 * 0.0006 * 0.63
+            + 25_000_000.0 * 4e-9 * time_horizon as f64 * 0.63)
+            / 100_000_000.0;
+        let actual = value.annualized_yield_factor as f64 / 1e9;
+        assert!((actual - expected).abs() / expected < 0.01);
+
+        // The yield is annualized.
+        let value = market.expected_value(&prices, &(volume / 365), 24 * 3600)?;
+        let actual = value.annualized_yield_factor as f64 / 1e9;
+        assert!((actual - expected).abs() / expected < 0.01);
+
+        // Profitable positions are exposures of the pool.
+        let prices = Prices::new_for_test(120, 100, 1);
+        let value = market.expected_value(&prices, &volume, time_horizon)?;
+        assert_eq!(value.pnl_exposure, -5_000_000);
+        assert_eq!(
+            value.net_value,
+            (value.fee_revenue + value.borrowing_revenue) as i64 - 5_000_000
+        );
+        assert!(value.net_value < 0);
+        assert_eq!(value.annualized_yield_factor, 0);
+
+        assert!(market.expected_value(&prices, &volume, 0).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn position_price_impact_direction() -> crate::Result<()> {
+        use crate::market::PositionImpactMarketExt;
+
+        let mut market = TestMarket::<u64, 9>::default();
+        let prices = Prices::new_for_test(120, 120, 1);
+        market.deposit(1_000_000_000, 0, prices)?.execute()?;
+        market.deposit(0, 100_000_000_000, prices)?.execute()?;
+
+        let mut position = TestPosition::long(true);
+        _ = position
+            .ops(&mut market)
+            .increase(prices, 100_000_000, 8_000_000_000, None)?
+            .execute()?;
+
+        // Increasing the skew has negative impact, while reducing it has positive impact.
+        let size = 1_000_000_000;
+        assert!(market.position_price_impact(true, &size)?.is_negative());
+        assert!(market.position_price_impact(false, &size)?.is_positive());
+        Ok(())
+    }
+}
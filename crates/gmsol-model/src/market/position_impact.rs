@@ -1,9 +1,9 @@
-use num_traits::{CheckedSub, FromPrimitive, Zero};
+use num_traits::{CheckedSub, FromPrimitive, One, Zero};
 
 use crate::{
     action::distribute_position_impact::DistributePositionImpact,
     params::{position::PositionImpactDistributionParams, PriceImpactParams},
-    Balance, BaseMarket, BaseMarketMut, Pool,
+    Balance, BalanceExt, BaseMarket, BaseMarketExt, BaseMarketMut, Pool,
 };
 
 /// A market with position impact pool.
@@ -21,6 +21,15 @@ pub trait PositionImpactMarket<const DECIMALS: u8>: BaseMarket<DECIMALS> {
 
     /// Get the passed time in seconds for the given kind of clock.
     fn passed_in_seconds_for_position_impact_distribution(&self) -> crate::Result<u64>;
+
+    /// Get the virtual open interest pool shared with other markets, if any.
+    ///
+    /// The long amount is the total virtual long open interest, while the short amount
+    /// is the total virtual short open interest. When it is provided, it is used in place
+    /// of the open interest of this market to calculate the position price impact.
+    fn virtual_open_interest_pool(&self) -> crate::Result<Option<&Self::Pool>> {
+        Ok(None)
+    }
 }
 
 /// A mutable market with position impact pool.
@@ -56,6 +65,10 @@ impl<M: PositionImpactMarket<DECIMALS>, const DECIMALS: u8> PositionImpactMarket
     fn passed_in_seconds_for_position_impact_distribution(&self) -> crate::Result<u64> {
         (**self).passed_in_seconds_for_position_impact_distribution()
     }
+
+    fn virtual_open_interest_pool(&self) -> crate::Result<Option<&Self::Pool>> {
+        (**self).virtual_open_interest_pool()
+    }
 }
 
 impl<M: PositionImpactMarketMut<DECIMALS>, const DECIMALS: u8> PositionImpactMarketMut<DECIMALS>
@@ -114,6 +127,41 @@ pub trait PositionImpactMarketExt<const DECIMALS: u8>: PositionImpactMarket<DECI
                 ))?;
         Ok((distribution_amount, next_amount))
     }
+
+    /// Get the position price impact of changing the open interest of the given side
+    /// by `size_delta_usd`, without modifying the market.
+    fn position_price_impact(
+        &self,
+        is_long: bool,
+        size_delta_usd: &Self::Signed,
+    ) -> crate::Result<Self::Signed> {
+        let (delta_long_usd_value, delta_short_usd_value) = if is_long {
+            (size_delta_usd.clone(), Zero::zero())
+        } else {
+            (Zero::zero(), size_delta_usd.clone())
+        };
+
+        // Since the amounts of open interest are already usd amounts,
+        // the price should be `one`.
+        let usd_price = One::one();
+
+        // Use the virtual open interest in place of the local one if provided.
+        let delta = match self.virtual_open_interest_pool()? {
+            Some(virtual_open_interest) => virtual_open_interest.pool_delta_with_values(
+                delta_long_usd_value,
+                delta_short_usd_value,
+                &usd_price,
+                &usd_price,
+            )?,
+            None => self.open_interest()?.pool_delta_with_values(
+                delta_long_usd_value,
+                delta_short_usd_value,
+                &usd_price,
+                &usd_price,
+            )?,
+        };
+        delta.price_impact(&self.position_impact_params()?)
+    }
 }
 
 impl<M: PositionImpactMarket<DECIMALS> + ?Sized, const DECIMALS: u8>
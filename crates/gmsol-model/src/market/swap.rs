@@ -1,7 +1,7 @@
 use num_traits::{CheckedAdd, CheckedDiv, CheckedMul, CheckedNeg, CheckedSub, One, Signed, Zero};
 
 use crate::{
-    action::swap::Swap,
+    action::swap::{Swap, SwapReport},
     num::{Unsigned, UnsignedAbs},
     params::{FeeParams, PriceImpactParams},
     price::{Price, Prices},
@@ -39,6 +39,16 @@ impl<M: SwapMarket<DECIMALS>, const DECIMALS: u8> SwapMarket<DECIMALS> for &mut
     }
 }
 
+impl<M: SwapMarket<DECIMALS>, const DECIMALS: u8> SwapMarket<DECIMALS> for &M {
+    fn swap_impact_params(&self) -> crate::Result<PriceImpactParams<Self::Num>> {
+        (**self).swap_impact_params()
+    }
+
+    fn swap_fee_params(&self) -> crate::Result<FeeParams<Self::Num>> {
+        (**self).swap_fee_params()
+    }
+}
+
 impl<M: SwapMarketMut<DECIMALS>, const DECIMALS: u8> SwapMarketMut<DECIMALS> for &mut M {
     fn swap_impact_pool_mut(&mut self) -> crate::Result<&mut Self::Pool> {
         (**self).swap_impact_pool_mut()
@@ -47,6 +57,19 @@ impl<M: SwapMarketMut<DECIMALS>, const DECIMALS: u8> SwapMarketMut<DECIMALS> for
 
 /// Extension trait for [`SwapMarket`].
 pub trait SwapMarketExt<const DECIMALS: u8>: SwapMarket<DECIMALS> {
+    /// Simulate a swap without modifying the market.
+    fn simulate_swap(
+        &self,
+        is_token_in_long: bool,
+        token_in_amount: Self::Num,
+        prices: Prices<Self::Num>,
+    ) -> crate::Result<SwapReport<Self::Num, <Self::Num as Unsigned>::Signed>>
+    where
+        Self: Sized,
+    {
+        Swap::try_new(self, is_token_in_long, token_in_amount, prices)?.simulate()
+    }
+
     /// Get the swap impact amount with cap.
     fn swap_impact_amount_with_cap(
         &self,
@@ -1,7 +1,12 @@
-use num_traits::{CheckedAdd, CheckedSub, Zero};
+use num_traits::{CheckedAdd, CheckedMul, CheckedSub, Signed, Zero};
 use typed_builder::TypedBuilder;
 
-use crate::{fixed::FixedPointOps, num::Unsigned, price::Price, utils};
+use crate::{
+    fixed::FixedPointOps,
+    num::{Unsigned, UnsignedAbs},
+    price::Price,
+    utils,
+};
 
 /// Fee Parameters.
 #[derive(Debug, Clone, Copy, TypedBuilder)]
@@ -27,8 +32,9 @@ impl<T> FeeParams<T> {
         &self.fee_receiver_factor
     }
 
+    /// Get the fee factor for the given price impact direction.
     #[inline]
-    fn factor(&self, is_positive_impact: bool) -> &T {
+    pub fn factor(&self, is_positive_impact: bool) -> &T {
         if is_positive_impact {
             &self.positive_impact_fee_factor
         } else {
@@ -159,6 +165,8 @@ pub struct BorrowingFeeParams<T> {
 
 impl<T> BorrowingFeeParams<T> {
     /// Get borrowing exponent factor.
+    ///
+    /// Only used if the kink model is disabled, see [`BorrowingFeeKinkModelParams`].
     pub fn exponent(&self, is_long: bool) -> &T {
         if is_long {
             &self.exponent_for_long
@@ -168,6 +176,8 @@ impl<T> BorrowingFeeParams<T> {
     }
 
     /// Get borrowing factor.
+    ///
+    /// Only used if the kink model is disabled, see [`BorrowingFeeKinkModelParams`].
     pub fn factor(&self, is_long: bool) -> &T {
         if is_long {
             &self.factor_for_long
@@ -188,6 +198,14 @@ impl<T> BorrowingFeeParams<T> {
 }
 
 /// Borrowing Fee Kink Model Parameters.
+///
+/// The borrowing factor per second is a two-slope function of the usage factor `u`:
+/// - `min + u * base` if `u <= optimal`;
+/// - `min + u * base + (u - optimal) * (above_optimal - base) / (1 - optimal)` otherwise,
+///
+/// where `min` is the min borrowing factor, `base` is the base borrowing factor,
+/// `optimal` is the optimal usage factor (the kink) and `above_optimal` is the
+/// above optimal usage borrowing factor.
 #[derive(Debug, Clone, Copy, TypedBuilder)]
 pub struct BorrowingFeeKinkModelParams<T> {
     long: BorrowingFeeKinkModelParamsForOneSide<T>,
@@ -220,7 +238,18 @@ impl<T> BorrowingFeeKinkModelParams<T> {
             .above_optimal_usage_borrowing_factor
     }
 
+    /// Get min borrowing factor, i.e., the borrowing factor per second at zero usage.
+    ///
+    /// Returns `None` if it is not set, which is equivalent to zero.
+    pub fn min_borrowing_factor(&self, is_long: bool) -> Option<&T> {
+        self.params_for_one_side(is_long)
+            .min_borrowing_factor
+            .as_ref()
+    }
+
     /// Calculate borrowing factor per second.
+    ///
+    /// Returns `None` if the kink model is disabled, i.e., the optimal usage factor is zero.
     pub fn borrowing_factor_per_second<const DECIMALS: u8, M>(
         &self,
         market: &M,
@@ -245,6 +274,10 @@ impl<T> BorrowingFeeKinkModelParams<T> {
         let base_borrowing_factor = self.base_borrowing_factor(is_long);
 
         let borrowing_factor_per_second = utils::apply_factor(&usage_factor, base_borrowing_factor)
+            .and_then(|factor| match self.min_borrowing_factor(is_long) {
+                Some(min_factor) => factor.checked_add(min_factor),
+                None => Some(factor),
+            })
             .ok_or(crate::Error::Computation(
                 "borrowing fee kink model: calculating borrowing factor per second",
             ))?;
@@ -298,6 +331,8 @@ pub struct BorrowingFeeKinkModelParamsForOneSide<T> {
     optimal_usage_factor: T,
     base_borrowing_factor: T,
     above_optimal_usage_borrowing_factor: T,
+    #[builder(default, setter(strip_option))]
+    min_borrowing_factor: Option<T>,
 }
 
 /// Funding Fee Parameters.
@@ -365,8 +400,6 @@ impl<T> FundingFeeParams<T> {
     where
         T: Ord + Unsigned,
     {
-        use num_traits::Signed;
-
         let is_skew_the_same_direction_as_funding = (funding_factor_per_second.is_positive()
             && *long_open_interest > *short_open_interest)
             || (funding_factor_per_second.is_negative()
@@ -862,3 +895,211 @@ impl<T: Zero> Default for PositionFees<T> {
         }
     }
 }
+
+/// Fee Breakdown.
+///
+/// The USD values of the fee components and the negative price impact
+/// deducted by an action.
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "anchor-lang",
+    derive(anchor_lang::AnchorDeserialize, anchor_lang::AnchorSerialize)
+)]
+pub struct FeeBreakdown<T> {
+    swap_fee_value: T,
+    position_fee_value: T,
+    borrowing_fee_value: T,
+    funding_fee_value: T,
+    liquidation_fee_value: T,
+    price_impact_value: T,
+}
+
+#[cfg(feature = "gmsol-utils")]
+impl<T: gmsol_utils::InitSpace> gmsol_utils::InitSpace for FeeBreakdown<T> {
+    const INIT_SPACE: usize = 6 * T::INIT_SPACE;
+}
+
+impl<T: Zero> Default for FeeBreakdown<T> {
+    fn default() -> Self {
+        Self {
+            swap_fee_value: Zero::zero(),
+            position_fee_value: Zero::zero(),
+            borrowing_fee_value: Zero::zero(),
+            funding_fee_value: Zero::zero(),
+            liquidation_fee_value: Zero::zero(),
+            price_impact_value: Zero::zero(),
+        }
+    }
+}
+
+impl<T> FeeBreakdown<T> {
+    /// Get swap fee value.
+    pub fn swap_fee_value(&self) -> &T {
+        &self.swap_fee_value
+    }
+
+    /// Get position fee value.
+    pub fn position_fee_value(&self) -> &T {
+        &self.position_fee_value
+    }
+
+    /// Get borrowing fee value.
+    pub fn borrowing_fee_value(&self) -> &T {
+        &self.borrowing_fee_value
+    }
+
+    /// Get funding fee value.
+    pub fn funding_fee_value(&self) -> &T {
+        &self.funding_fee_value
+    }
+
+    /// Get liquidation fee value.
+    pub fn liquidation_fee_value(&self) -> &T {
+        &self.liquidation_fee_value
+    }
+
+    /// Get the value of the negative price impact.
+    ///
+    /// Positive price impact is not deducted, so it is recorded as zero.
+    pub fn price_impact_value(&self) -> &T {
+        &self.price_impact_value
+    }
+
+    /// Get total fee value, excluding price impact.
+    pub fn total_fee_value(&self) -> crate::Result<T>
+    where
+        T: CheckedAdd,
+    {
+        self.swap_fee_value
+            .checked_add(&self.position_fee_value)
+            .and_then(|acc| acc.checked_add(&self.borrowing_fee_value))
+            .and_then(|acc| acc.checked_add(&self.funding_fee_value))
+            .and_then(|acc| acc.checked_add(&self.liquidation_fee_value))
+            .ok_or(crate::Error::Computation("calculating total fee value"))
+    }
+
+    /// Get total deducted value, including the negative price impact.
+    pub fn total_value(&self) -> crate::Result<T>
+    where
+        T: CheckedAdd,
+    {
+        self.total_fee_value()?
+            .checked_add(&self.price_impact_value)
+            .ok_or(crate::Error::Computation(
+                "calculating total deducted value",
+            ))
+    }
+}
+
+impl<T: Unsigned + Clone + CheckedAdd + CheckedMul> FeeBreakdown<T> {
+    /// Add the value of the given swap fees charged in a token with the given price.
+    pub(crate) fn add_swap_fees(mut self, fees: &Fees<T>, price: &T) -> crate::Result<Self> {
+        let value = fees
+            .fee_amount_for_pool()
+            .checked_add(fees.fee_amount_for_receiver())
+            .and_then(|amount| amount.checked_mul(price))
+            .ok_or(crate::Error::Computation("calculating swap fee value"))?;
+        self.swap_fee_value = self
+            .swap_fee_value
+            .checked_add(&value)
+            .ok_or(crate::Error::Overflow)?;
+        Ok(self)
+    }
+
+    /// Set the values of the given position fees paid in collateral tokens
+    /// with the given price.
+    pub(crate) fn set_position_fees(
+        mut self,
+        fees: &PositionFees<T>,
+        collateral_token_price: &T,
+    ) -> crate::Result<Self> {
+        let to_value = |amount: &T| {
+            amount
+                .checked_mul(collateral_token_price)
+                .ok_or(crate::Error::Computation("calculating position fee values"))
+        };
+        let order_fees = fees.order_fees().fee_amounts();
+        self.position_fee_value = to_value(
+            &order_fees
+                .fee_amount_for_pool()
+                .checked_add(order_fees.fee_amount_for_receiver())
+                .ok_or(crate::Error::Overflow)?,
+        )?;
+        self.borrowing_fee_value = to_value(fees.borrowing_fees().fee_amount())?;
+        self.funding_fee_value = to_value(fees.funding_fees().amount())?;
+        self.liquidation_fee_value = match fees.liquidation_fees() {
+            Some(liquidation) => to_value(liquidation.fee_amount())?,
+            None => Zero::zero(),
+        };
+        Ok(self)
+    }
+
+    /// Set the price impact value. Only negative price impact is recorded.
+    pub(crate) fn set_price_impact(mut self, price_impact_value: &T::Signed) -> Self {
+        self.price_impact_value = if price_impact_value.is_negative() {
+            price_impact_value.unsigned_abs()
+        } else {
+            Zero::zero()
+        };
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test::TestMarket;
+
+    #[test]
+    fn borrowing_fee_kink_model() -> crate::Result<()> {
+        let market = TestMarket::<u64, 9>::default();
+        let for_one_side = BorrowingFeeKinkModelParamsForOneSide::builder()
+            .optimal_usage_factor(800_000_000)
+            .base_borrowing_factor(1_000)
+            .above_optimal_usage_borrowing_factor(11_000)
+            .min_borrowing_factor(100)
+            .build();
+        let params = BorrowingFeeKinkModelParams::builder()
+            .long(for_one_side)
+            .short(for_one_side)
+            .build();
+
+        // The usage factor is `reserved_value / pool_value` for the default test market.
+        let pool_value = 1_000_000_000;
+        let factor_at = |usage: u64| {
+            params
+                .borrowing_factor_per_second(&market, true, &(usage * 10_000_000), &pool_value)
+                .map(|factor| factor.expect("the kink model is enabled"))
+        };
+
+        // Below the kink: `min + u * base`.
+        assert_eq!(factor_at(0)?, 100);
+        assert_eq!(factor_at(60)?, 100 + 600);
+        assert_eq!(factor_at(80)?, 100 + 800);
+
+        // Above the kink: `min + u * base + (u - kink) * (above - base) / (1 - kink)`.
+        assert_eq!(factor_at(90)?, 100 + 900 + 5_000);
+        assert_eq!(factor_at(100)?, 100 + 11_000);
+
+        // The slope jumps at the kink.
+        let slope_below = factor_at(80)? - factor_at(60)?;
+        let slope_above = factor_at(100)? - factor_at(80)?;
+        assert!(slope_above > slope_below * 10);
+
+        // The kink model is disabled if the optimal usage factor is zero.
+        let disabled = BorrowingFeeKinkModelParamsForOneSide::builder()
+            .optimal_usage_factor(0)
+            .base_borrowing_factor(1_000)
+            .above_optimal_usage_borrowing_factor(11_000)
+            .build();
+        let params = BorrowingFeeKinkModelParams::builder()
+            .long(disabled)
+            .short(disabled)
+            .build();
+        assert!(params
+            .borrowing_factor_per_second(&market, true, &0, &pool_value)?
+            .is_none());
+        Ok(())
+    }
+}
@@ -7,6 +7,6 @@ pub mod position;
 /// Fee Parameters.
 pub mod fee;
 
-pub use fee::{FeeParams, Fees};
+pub use fee::{FeeBreakdown, FeeParams, Fees};
 pub use position::PositionParams;
 pub use price_impact::PriceImpactParams;
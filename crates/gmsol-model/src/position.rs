@@ -1,6 +1,6 @@
 use std::{fmt, ops::Deref};
 
-use num_traits::{One, Signed, Zero};
+use num_traits::{Signed, Zero};
 
 use crate::{
     action::{
@@ -12,12 +12,12 @@ use crate::{
     fixed::FixedPointOps,
     market::{
         utils::MarketUtils, BaseMarketExt, BorrowingFeeMarket, BorrowingFeeMarketExt, PerpMarket,
-        PerpMarketExt, PositionImpactMarket,
+        PerpMarketExt, PositionImpactMarketExt,
     },
     num::{MulDiv, Num, Unsigned, UnsignedAbs},
     params::fee::{FundingFees, PositionFees},
     price::{Price, Prices},
-    Balance, BalanceExt, BaseMarket, PerpMarketMut, PnlFactorKind, Pool, PoolExt,
+    Balance, BaseMarket, PerpMarketMut, PnlFactorKind, Pool, PoolExt,
 };
 
 /// Read-only access to the position state.
@@ -90,6 +90,20 @@ pub trait Position<const DECIMALS: u8>: PositionState<DECIMALS> {
 
     /// Called from `validate_position` to add supplementary checks.
     fn on_validate(&self) -> crate::Result<()>;
+
+    /// Get the total [excess collateral value](PositionExt::cross_margin_excess_value) of the
+    /// other positions of the same owner in cross margin mode, which can be negative.
+    ///
+    /// The value is added to the remaining collateral value of this position in the leverage
+    /// and liquidation checks, so that the positions are checked by their total remaining
+    /// collateral value against their total required collateral value.
+    /// Defaults to zero, i.e., the position is isolated.
+    fn cross_margin_collateral_value(
+        &self,
+        _prices: &Prices<Self::Num>,
+    ) -> crate::Result<Self::Signed> {
+        Ok(Zero::zero())
+    }
 }
 
 /// Position with mutable access.
@@ -170,6 +184,13 @@ impl<const DECIMALS: u8, P: Position<DECIMALS>> Position<DECIMALS> for &mut P {
     fn on_validate(&self) -> crate::Result<()> {
         (**self).on_validate()
     }
+
+    fn cross_margin_collateral_value(
+        &self,
+        prices: &Prices<Self::Num>,
+    ) -> crate::Result<Self::Signed> {
+        (**self).cross_margin_collateral_value(prices)
+    }
 }
 
 impl<const DECIMALS: u8, P: PositionStateMut<DECIMALS>> PositionStateMut<DECIMALS> for &mut P {
@@ -288,12 +309,19 @@ pub trait PositionExt<const DECIMALS: u8>: Position<DECIMALS> {
                     .clone(),
             );
 
+        let cross_margin_collateral_value = self.cross_margin_collateral_value(prices)?;
+        let effective_collateral_value = remaining_collateral_value
+            .checked_add(&cross_margin_collateral_value)
+            .ok_or(crate::Error::Computation(
+                "adding cross margin collateral value",
+            ))?;
+
         match check_collateral(
             &delta.next_size_in_usd,
             &min_collateral_factor,
             None,
             true,
-            &remaining_collateral_value,
+            &effective_collateral_value,
         )? {
             CheckCollateralResult::Sufficient => Ok(WillCollateralBeSufficient::Sufficient(
                 remaining_collateral_value,
@@ -449,14 +477,13 @@ pub trait PositionExt<const DECIMALS: u8>: Position<DECIMALS> {
         Ok(())
     }
 
-    /// Check if the position is liquidatable.
-    ///
-    /// Return [`LiquidatableReason`] if it is liquidatable, `None` otherwise.
-    fn check_liquidatable(
+    /// Get the remaining collateral value if the position were closed at the given prices,
+    /// i.e., the collateral value plus the PnL and the capped negative price impact, minus
+    /// the costs (excluding the liquidation fees).
+    fn remaining_collateral_value(
         &self,
         prices: &Prices<Self::Num>,
-        should_validate_min_collateral_usd: bool,
-    ) -> crate::Result<Option<LiquidatableReason>> {
+    ) -> crate::Result<Self::Signed> {
         use num_traits::{CheckedAdd, CheckedMul, CheckedSub};
 
         let size_in_usd = self.size_in_usd();
@@ -497,7 +524,7 @@ pub trait PositionExt<const DECIMALS: u8>: Position<DECIMALS> {
                 "overflow calculating collateral cost value",
             ))?;
 
-        let remaining_collateral_value = collateral_value
+        collateral_value
             .to_signed()?
             .checked_add(&pnl)
             .and_then(|v| {
@@ -506,7 +533,59 @@ pub trait PositionExt<const DECIMALS: u8>: Position<DECIMALS> {
             })
             .ok_or(crate::Error::Computation(
                 "calculating remaining collateral value",
-            ))?;
+            ))
+    }
+
+    /// Get the excess of the [remaining collateral value](PositionExt::remaining_collateral_value)
+    /// over the min collateral value required for the leverage, which can be negative.
+    ///
+    /// This is the value shared with the other positions of the same owner in cross margin mode.
+    fn cross_margin_excess_value(&self, prices: &Prices<Self::Num>) -> crate::Result<Self::Signed> {
+        use num_traits::CheckedSub;
+
+        let remaining_collateral_value = self.remaining_collateral_value(prices)?;
+        let min_collateral_value = crate::utils::apply_factor::<_, DECIMALS>(
+            self.size_in_usd(),
+            self.market().position_params()?.min_collateral_factor(),
+        )
+        .ok_or(crate::Error::Computation(
+            "calculating min collateral value for leverage",
+        ))?;
+        remaining_collateral_value
+            .checked_sub(&min_collateral_value.to_signed()?)
+            .ok_or(crate::Error::Computation(
+                "calculating excess collateral value",
+            ))
+    }
+
+    /// Check if the position is liquidatable.
+    ///
+    /// Return [`LiquidatableReason`] if it is liquidatable, `None` otherwise.
+    fn check_liquidatable(
+        &self,
+        prices: &Prices<Self::Num>,
+        should_validate_min_collateral_usd: bool,
+    ) -> crate::Result<Option<LiquidatableReason>> {
+        use num_traits::CheckedAdd;
+
+        let size_in_usd = self.size_in_usd();
+
+        let remaining_collateral_value = self.remaining_collateral_value(prices)?;
+
+        let cross_margin_collateral_value = self.cross_margin_collateral_value(prices)?;
+        let remaining_collateral_value = if cross_margin_collateral_value.is_zero() {
+            remaining_collateral_value
+        } else {
+            // A position in cross margin mode must still be able to cover its own losses.
+            if !remaining_collateral_value.is_positive() {
+                return Ok(Some(LiquidatableReason::NotPositive));
+            }
+            remaining_collateral_value
+                .checked_add(&cross_margin_collateral_value)
+                .ok_or(crate::Error::Computation(
+                    "adding cross margin collateral value",
+                ))?
+        };
 
         let params = self.market().position_params()?;
 
@@ -530,47 +609,8 @@ pub trait PositionExt<const DECIMALS: u8>: Position<DECIMALS> {
 
     /// Get position price impact.
     fn position_price_impact(&self, size_delta_usd: &Self::Signed) -> crate::Result<Self::Signed> {
-        struct ReassignedValues<T> {
-            delta_long_usd_value: T,
-            delta_short_usd_value: T,
-        }
-
-        impl<T: Zero + Clone> ReassignedValues<T> {
-            fn new(is_long: bool, size_delta_usd: &T) -> Self {
-                if is_long {
-                    Self {
-                        delta_long_usd_value: size_delta_usd.clone(),
-                        delta_short_usd_value: Zero::zero(),
-                    }
-                } else {
-                    Self {
-                        delta_long_usd_value: Zero::zero(),
-                        delta_short_usd_value: size_delta_usd.clone(),
-                    }
-                }
-            }
-        }
-
-        // Since the amounts of open interest are already usd amounts,
-        // the price should be `one`.
-        let usd_price = One::one();
-
-        let ReassignedValues {
-            delta_long_usd_value,
-            delta_short_usd_value,
-        } = ReassignedValues::new(self.is_long(), size_delta_usd);
-
-        let price_impact_value = self
-            .market()
-            .open_interest()?
-            .pool_delta_with_values(
-                delta_long_usd_value,
-                delta_short_usd_value,
-                &usd_price,
-                &usd_price,
-            )?
-            .price_impact(&self.market().position_impact_params()?)?;
-        Ok(price_impact_value)
+        self.market()
+            .position_price_impact(self.is_long(), size_delta_usd)
     }
 
     /// Get position price impact usd and cap the value if it is positive.
@@ -706,6 +746,31 @@ pub trait PositionExt<const DECIMALS: u8>: Position<DECIMALS> {
 
 impl<const DECIMALS: u8, P: Position<DECIMALS>> PositionExt<DECIMALS> for P {}
 
+/// Calculate the borrowing fee value the given position will owe after `elapsed_secs` seconds
+/// have passed since the last borrowing state update of its market.
+///
+/// The borrowing factor per second is calculated with the given prices and the current
+/// state of the market, and is assumed to stay unchanged during the elapsed time.
+pub fn pending_borrowing_fee<const DECIMALS: u8, P: Position<DECIMALS> + ?Sized>(
+    position: &P,
+    elapsed_secs: u64,
+    prices: &Prices<P::Num>,
+) -> crate::Result<P::Num> {
+    use num_traits::CheckedSub;
+
+    let (next_factor, _) = position.market().next_cumulative_borrowing_factor(
+        position.is_long(),
+        prices,
+        elapsed_secs,
+    )?;
+    let diff_factor = next_factor
+        .checked_sub(position.borrowing_factor())
+        .ok_or(crate::Error::Computation("invalid next borrowing factor"))?;
+    crate::utils::apply_factor(position.size_in_usd(), &diff_factor).ok_or(
+        crate::Error::Computation("calculating pending borrowing fee"),
+    )
+}
+
 /// Extension trait for [`PositionMut`] with utils.
 pub trait PositionMutExt<const DECIMALS: u8>: PositionMut<DECIMALS>
 where
@@ -796,6 +861,9 @@ where
             open_interest_in_tokens.apply_delta_to_short_amount(size_delta_in_tokens)?;
         }
 
+        self.market_mut()
+            .apply_delta_to_virtual_open_interest(is_long, size_delta_usd)?;
+
         Ok(())
     }
 
@@ -975,3 +1043,164 @@ pub enum InsolventCloseStep {
     /// Price impact diff.
     Diff,
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        test::{TestMarket, TestPosition},
+        BaseMarketMut, BorrowingFeeMarketMut, LiquidityMarketMutExt, MarketAction,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_pending_borrowing_fee() -> crate::Result<()> {
+        let mut market = TestMarket::<u64, 9>::default();
+        let prices = Prices::new_for_test(100, 100, 1);
+
+        // Pool value of the long side = 1,000,000 * 100 = 100,000,000.
+        market
+            .liquidity_pool_mut()?
+            .apply_delta_amount(true, &1_000_000)?;
+        // Reserved value of the long side = 250,000 * 100 = 25,000,000.
+        market
+            .open_interest_in_tokens_pool_mut(true)?
+            .apply_delta_amount(true, &250_000)?;
+        market
+            .borrowing_factor_pool_mut()?
+            .apply_delta_amount(true, &1_000)?;
+
+        // With the kink model, the borrowing factor per second
+        // = usage factor * base borrowing factor
+        // = 0.25 * (0.6 / SECONDS_PER_YEAR) = 0.25 * 19e-9 = 4e-9 (rounded down).
+        assert_eq!(market.borrowing_factor_per_second(true, &prices)?, 4);
+        assert_eq!(market.cumulative_borrowing_factor(true)?, 1_000);
+
+        let mut position = TestPosition::long(true);
+        let mut position = position.ops(&mut market);
+        *position.size_in_usd_mut() = 1_000_000_000_000;
+        *position.borrowing_factor_mut() = 400;
+
+        // fee = size_in_usd * (cumulative factor + 4e-9 * elapsed - position factor).
+        assert_eq!(pending_borrowing_fee(&position, 0, &prices)?, 600_000);
+        assert_eq!(
+            pending_borrowing_fee(&position, 0, &prices)?,
+            position.pending_borrowing_fee_value()?
+        );
+        assert_eq!(pending_borrowing_fee(&position, 1_000, &prices)?, 4_600_000);
+        assert_eq!(
+            pending_borrowing_fee(&position, 86_400, &prices)?,
+            346_200_000
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_pending_borrowing_fee_without_reserved_value() -> crate::Result<()> {
+        let mut market = TestMarket::<u64, 9>::default();
+        let prices = Prices::new_for_test(100, 100, 1);
+
+        market
+            .liquidity_pool_mut()?
+            .apply_delta_amount(false, &1_000_000)?;
+        market
+            .borrowing_factor_pool_mut()?
+            .apply_delta_amount(false, &2_000)?;
+
+        let mut position = TestPosition::short(false);
+        let mut position = position.ops(&mut market);
+        *position.size_in_usd_mut() = 1_000_000_000_000;
+        *position.borrowing_factor_mut() = 500;
+
+        // No borrowing fee accrues over time without reserved value.
+        for elapsed in [0, 1, 3_600, 86_400 * 365] {
+            assert_eq!(
+                pending_borrowing_fee(&position, elapsed, &prices)?,
+                1_500_000
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn cross_margin_liquidation_threshold() -> crate::Result<()> {
+        let mut market = TestMarket::<u64, 9>::default();
+        let prices = Prices::new_for_test(120, 120, 1);
+        market.deposit(100_000_000_000, 0, prices)?.execute()?;
+        market.deposit(0, 1_000_000_000_000, prices)?.execute()?;
+
+        let mut position = TestPosition::long(false);
+        _ = position
+            .ops(&mut market)
+            .increase(prices, 100_000_000_000, 800_000_000_000, None)?
+            .execute()?;
+
+        // A short position with enough collateral, which gains as the price drops.
+        let mut hedge = TestPosition::short(false);
+        _ = hedge
+            .ops(&mut market)
+            .increase(prices, 500_000_000_000, 100_000_000_000, None)?
+            .execute()?;
+
+        // A highly leveraged long position, which is underwater as soon as the price drops.
+        let mut underwater = TestPosition::long(false);
+        _ = underwater
+            .ops(&mut market)
+            .increase(prices, 10_000_000_000, 800_000_000_000, None)?
+            .execute()?;
+
+        // Returns the highest index price at which the position is liquidatable.
+        let liquidation_price =
+            |market: &mut TestMarket<u64, 9>, siblings: &[TestPosition<u64, 9>]| {
+                let mut position = position;
+                for price in (1..=120).rev() {
+                    let prices = Prices::new_for_test(price, price, 1);
+                    if position
+                        .cross_margin_ops(market, siblings)
+                        .check_liquidatable(&prices, true)?
+                        .is_some()
+                    {
+                        return Ok(Some(price));
+                    }
+                }
+                crate::Result::Ok(None)
+            };
+
+        let isolated = liquidation_price(&mut market, &[])?.expect("must be liquidatable");
+        let hedged = liquidation_price(&mut market, &[hedge])?.expect("must be liquidatable");
+        let with_underwater =
+            liquidation_price(&mut market, &[underwater])?.expect("must be liquidatable");
+        let with_both =
+            liquidation_price(&mut market, &[hedge, underwater])?.expect("must be liquidatable");
+        assert!(hedged < isolated);
+        // The negative excess of a sibling counts against the position.
+        assert!(with_underwater > isolated);
+        assert!(hedged <= with_both && with_both <= with_underwater);
+
+        // The shared value is the sum of the excess values of the siblings.
+        let prices = Prices::new_for_test(110, 110, 1);
+        let excess = hedge
+            .ops(&mut market)
+            .cross_margin_excess_value(&prices)?
+            .checked_add(
+                underwater
+                    .ops(&mut market)
+                    .cross_margin_excess_value(&prices)?,
+            )
+            .unwrap();
+        assert_eq!(
+            position
+                .cross_margin_ops(&mut market, &[hedge, underwater])
+                .cross_margin_collateral_value(&prices)?,
+            excess
+        );
+
+        // The position must still be liquidated once its own collateral is used up,
+        // even if there is enough shared collateral.
+        let reason = position
+            .cross_margin_ops(&mut market, &[hedge])
+            .check_liquidatable(&Prices::new_for_test(100, 100, 1), true)?;
+        assert!(matches!(reason, Some(LiquidatableReason::NotPositive)));
+        Ok(())
+    }
+}
@@ -25,16 +25,19 @@ use crate::{
     },
     pool::{Balance, Delta, Pool},
     position::Position,
-    BaseMarketMut, BorrowingFeeMarket, PerpMarketMut, PositionImpactMarketMut, PositionMut,
-    PositionState, PositionStateMut, SwapMarketMut,
+    price::Prices,
+    BaseMarketMut, BorrowingFeeMarket, PerpMarketMut, PositionExt, PositionImpactMarketMut,
+    PositionMut, PositionState, PositionStateMut, SwapMarketMut,
 };
-use num_traits::{CheckedSub, Signed};
+use num_traits::{CheckedAdd, CheckedSub, Signed, Zero};
 
 /// Test Pool.
 #[derive(Debug, Default, Clone, Copy)]
 pub struct TestPool<T> {
     long_amount: T,
     short_amount: T,
+    /// For a pure pool, only the `long_amount` field is used.
+    is_pure: bool,
 }
 
 impl<T> Balance for TestPool<T>
@@ -46,11 +49,20 @@ where
     type Signed = T::Signed;
 
     fn long_amount(&self) -> crate::Result<Self::Num> {
-        Ok(self.long_amount.clone())
+        if self.is_pure {
+            // Round up so that both sides add up to the total amount.
+            Ok(self.long_amount.clone() - self.short_amount()?)
+        } else {
+            Ok(self.long_amount.clone())
+        }
     }
 
     fn short_amount(&self) -> crate::Result<Self::Num> {
-        Ok(self.short_amount.clone())
+        if self.is_pure {
+            Ok(self.long_amount.clone() / (T::one() + T::one()))
+        } else {
+            Ok(self.short_amount.clone())
+        }
     }
 }
 
@@ -85,6 +97,9 @@ where
     }
 
     fn apply_delta_to_short_amount(&mut self, delta: &Self::Signed) -> Result<(), crate::Error> {
+        if self.is_pure {
+            return self.apply_delta_to_long_amount(delta);
+        }
         if delta.is_positive() {
             self.short_amount = self
                 .short_amount
@@ -188,6 +203,17 @@ impl<T: Unsigned, const DECIMALS: u8> TestMarket<T, DECIMALS> {
     pub fn move_clock_forward(&mut self, duration: Duration) {
         self.clock.move_forward(duration);
     }
+
+    /// Convert into a pure market, i.e. a market whose long token and short token are the same.
+    ///
+    /// Must be called before any action is executed.
+    pub fn into_pure(mut self) -> Self {
+        for pool in [&mut self.primary, &mut self.swap_impact, &mut self.fee] {
+            debug_assert!(pool.long_amount.is_zero() && pool.short_amount.is_zero());
+            pool.is_pure = true;
+        }
+        self
+    }
 }
 
 impl TestMarket<u64, 9> {
@@ -837,6 +863,21 @@ where
         TestPositionOps {
             market,
             position: self,
+            cross_margin_siblings: &[],
+        }
+    }
+
+    /// Create a [`TestPositionOps`] for ops in cross margin mode with the given
+    /// other positions of the same owner.
+    pub fn cross_margin_ops<'a>(
+        &'a mut self,
+        market: &'a mut TestMarket<T, DECIMALS>,
+        siblings: &'a [Self],
+    ) -> TestPositionOps<'a, T, DECIMALS> {
+        TestPositionOps {
+            market,
+            position: self,
+            cross_margin_siblings: siblings,
         }
     }
 
@@ -873,6 +914,7 @@ where
 {
     market: &'a mut TestMarket<T, DECIMALS>,
     position: &'a mut TestPosition<T, DECIMALS>,
+    cross_margin_siblings: &'a [TestPosition<T, DECIMALS>],
 }
 
 impl<T, const DECIMALS: u8> PositionState<DECIMALS> for TestPositionOps<'_, T, DECIMALS>
@@ -939,6 +981,22 @@ where
     fn on_validate(&self) -> crate::Result<()> {
         Ok(())
     }
+
+    fn cross_margin_collateral_value(
+        &self,
+        prices: &Prices<Self::Num>,
+    ) -> crate::Result<Self::Signed> {
+        let mut value = Self::Signed::zero();
+        for sibling in self.cross_margin_siblings {
+            let mut market = self.market.clone();
+            let mut sibling = sibling.clone();
+            let excess = sibling.ops(&mut market).cross_margin_excess_value(prices)?;
+            value = value.checked_add(&excess).ok_or(crate::Error::Computation(
+                "adding cross margin excess value",
+            ))?;
+        }
+        Ok(value)
+    }
 }
 
 impl<T, const DECIMALS: u8> PositionMut<DECIMALS> for TestPositionOps<'_, T, DECIMALS>
@@ -217,7 +217,17 @@ impl<C: Deref<Target = impl Signer> + Clone> PullOracle for ChainlinkPullOracle<
         let tasks = feed_ids
             .iter()
             .map(|feed_id| self.chainlink.latest_report(feed_id));
-        let price_updates = futures_util::future::try_join_all(tasks).await?;
+        let price_updates = match self.gmsol.max_concurrency() {
+            Some(limit) => {
+                use futures_util::{stream, StreamExt, TryStreamExt};
+
+                stream::iter(tasks)
+                    .buffered(limit.max(1))
+                    .try_collect::<Vec<_>>()
+                    .await?
+            }
+            None => futures_util::future::try_join_all(tasks).await?,
+        };
 
         let updates = price_updates
             .into_iter()
@@ -200,6 +200,9 @@ struct UpdateGlvArgs {
     /// Minimum shift value.
     #[arg(long)]
     shift_min_value: Option<u128>,
+    /// Withdrawal cooldown seconds after a GLV deposit.
+    #[arg(long)]
+    withdrawal_cooldown_secs: Option<u32>,
 }
 
 impl<'a> From<&'a UpdateGlvArgs> for UpdateGlvParams {
@@ -209,6 +212,7 @@ impl<'a> From<&'a UpdateGlvArgs> for UpdateGlvParams {
             shift_min_interval_secs: args.shift_min_interval_secs,
             shift_max_price_impact_factor: args.shift_max_price_impact_factor,
             shift_min_value: args.shift_min_value,
+            withdrawal_cooldown_secs: args.withdrawal_cooldown_secs,
         }
     }
 }
@@ -548,6 +552,8 @@ struct GlvConfig {
     shift_max_price_impact_factor: Option<SerdeFactor>,
     #[serde_as(as = "Option<serde_with::DisplayFromStr>")]
     shift_min_value: Option<SerdeFactor>,
+    #[serde_as(as = "Option<serde_with::DisplayFromStr>")]
+    withdrawal_cooldown: Option<humantime::Duration>,
 }
 
 impl TryFrom<GlvConfig> for UpdateGlvParams {
@@ -565,6 +571,10 @@ impl TryFrom<GlvConfig> for UpdateGlvParams {
                 .transpose()?,
             shift_max_price_impact_factor: config.shift_max_price_impact_factor.map(|f| f.0),
             shift_min_value: config.shift_min_value.map(|f| f.0),
+            withdrawal_cooldown_secs: config
+                .withdrawal_cooldown
+                .map(|d| d.as_secs().try_into().map_err(gmsol::Error::unknown))
+                .transpose()?,
         })
     }
 }
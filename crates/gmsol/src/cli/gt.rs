@@ -253,13 +253,18 @@ impl Args {
                         let exchanges = client.gt_exchanges(store, &owner).await?;
 
                         let mut table = Table::new();
-                        table.set_titles(row!["Pubkey", "Vault", "Amount",]);
+                        table.set_titles(row!["Pubkey", "Vault", "Amount", "Completed",]);
                         table.set_format(table_format());
 
                         for (address, exchange) in exchanges {
                             let amount =
                                 unsigned_amount_to_decimal(exchange.amount(), decimals).normalize();
-                            table.add_row(row![address, exchange.vault(), amount]);
+                            table.add_row(row![
+                                address,
+                                exchange.vault(),
+                                amount,
+                                exchange.is_completed()
+                            ]);
                         }
 
                         println!("{table}");
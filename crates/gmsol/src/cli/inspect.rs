@@ -1041,6 +1041,7 @@ impl InspectArgs {
                     "Shift min value: {}",
                     unsigned_value_to_decimal(glv.shift_min_value()).normalize()
                 );
+                println!("Withdrawal cooldown: {}s", glv.withdrawal_cooldown_secs());
                 println!(
                     "Min tokens for first deposit: {}",
                     unsigned_amount_to_decimal(
@@ -2,6 +2,7 @@ use std::{
     collections::BTreeMap,
     ops::Deref,
     sync::{Arc, OnceLock},
+    time::Duration,
 };
 
 use anchor_client::{
@@ -16,15 +17,31 @@ use anchor_client::{
 
 use gmsol_model::{price::Prices, PnlFactorKind};
 use gmsol_solana_utils::{
-    bundle_builder::{BundleBuilder, BundleOptions, CreateBundleOptions},
+    bundle_builder::{BundleBuilder, BundleOptions},
     cluster::Cluster,
     program::Program,
     transaction_builder::{Config, TransactionBuilder},
 };
 use gmsol_store::{
     states::{
-        deposit::find_first_deposit_receiver_pda, market::status::MarketStatus,
-        position::PositionKind, user::ReferralCodeBytes, NonceBytes, PriceProviderKind,
+        config_snapshot::{ConfigDiff, ConfigSnapshot},
+        deposit::find_first_deposit_receiver_pda,
+        gt::ReferralRewardFactorsProblem,
+        market::{
+            analytics::OhlcvData,
+            config::MarketConfigProblem,
+            status::{
+                ArbitrageOpportunity, LpValueInfo, MarketExpectedValue, MarketStatus,
+                MarketTokenBacking, PriceImpactSensitivity, SupplyVerification, SwapFeeBreakdown,
+            },
+            PendingCounts,
+        },
+        position::{
+            AdlEstimate, BreakEvenPrice, CollateralCoverage, LiquidationImpact, PositionInfo,
+            PositionKind,
+        },
+        user::{ReferralCodeBytes, ReferralStats, UserActivity},
+        NonceBytes, PriceFeedStatus, PriceProviderKind,
     },
     utils::pubkey::optional_address,
 };
@@ -34,7 +51,7 @@ use tokio::sync::OnceCell;
 use typed_builder::TypedBuilder;
 
 use crate::{
-    store::market::MarketOps,
+    store::{gt::GtOps, market::MarketOps, store_ops::StoreOps, user::UserOps},
     types,
     utils::{
         account_with_context, accounts_lazy_with_context, workarounds::zero_copy::SharedZeroCopy,
@@ -57,6 +74,18 @@ pub struct ClientOptions {
     commitment: CommitmentConfig,
     #[builder(default)]
     subscription: SubscriptionConfig,
+    /// The initial timeout for confirming transactions.
+    /// `None` means the default of the RPC client is used.
+    #[builder(default)]
+    confirm_timeout: Option<Duration>,
+    /// The default compute unit price in micro lamports for the transactions
+    /// built by the client. `None` means the default of `ComputeBudget` is used.
+    #[builder(default)]
+    default_compute_unit_price: Option<u64>,
+    /// The max number of concurrent requests made by the client.
+    /// `None` means no limit.
+    #[builder(default)]
+    max_concurrency: Option<usize>,
 }
 
 impl Default for ClientOptions {
@@ -75,6 +104,7 @@ pub struct Client<C> {
     rpc: OnceLock<RpcClient>,
     pub_sub: OnceCell<PubsubClient>,
     subscription_config: SubscriptionConfig,
+    max_concurrency: Option<usize>,
 }
 
 impl<C: Clone + Deref<Target = impl Signer>> Client<C> {
@@ -90,13 +120,18 @@ impl<C: Clone + Deref<Target = impl Signer>> Client<C> {
             timelock_program_id,
             commitment,
             subscription,
+            confirm_timeout,
+            default_compute_unit_price,
+            max_concurrency,
         } = options;
         let anchor = anchor_client::Client::new_with_options(
             cluster.clone().into(),
             payer.clone(),
             commitment,
         );
-        let cfg = Config::new(cluster, payer, commitment);
+        let cfg = Config::new(cluster, payer, commitment)
+            .set_confirm_timeout(confirm_timeout)
+            .set_compute_unit_price_micro_lamports(default_compute_unit_price);
         Ok(Self {
             store_program: Program::new(store_program_id.unwrap_or(gmsol_store::id()), cfg.clone()),
             treasury_program: Program::new(
@@ -112,6 +147,7 @@ impl<C: Clone + Deref<Target = impl Signer>> Client<C> {
             pub_sub: OnceCell::default(),
             rpc: Default::default(),
             subscription_config: subscription,
+            max_concurrency,
         })
     }
 
@@ -134,6 +170,9 @@ impl<C: Clone + Deref<Target = impl Signer>> Client<C> {
                 timelock_program_id: Some(*self.timelock_program_id()),
                 commitment: self.commitment(),
                 subscription: self.subscription_config.clone(),
+                confirm_timeout: self.confirm_timeout(),
+                default_compute_unit_price: self.default_compute_unit_price(),
+                max_concurrency: self.max_concurrency(),
             },
         )
     }
@@ -149,6 +188,7 @@ impl<C: Clone + Deref<Target = impl Signer>> Client<C> {
             pub_sub: OnceCell::default(),
             rpc: Default::default(),
             subscription_config: self.subscription_config.clone(),
+            max_concurrency: self.max_concurrency,
         })
     }
 
@@ -178,6 +218,21 @@ impl<C: Clone + Deref<Target = impl Signer>> Client<C> {
         *self.cfg.commitment()
     }
 
+    /// Get the initial timeout for confirming transactions.
+    pub fn confirm_timeout(&self) -> Option<Duration> {
+        self.cfg.confirm_timeout()
+    }
+
+    /// Get the default compute unit price in micro lamports.
+    pub fn default_compute_unit_price(&self) -> Option<u64> {
+        self.cfg.compute_unit_price_micro_lamports()
+    }
+
+    /// Get the max number of concurrent requests.
+    pub fn max_concurrency(&self) -> Option<usize> {
+        self.max_concurrency
+    }
+
     /// Get the payer.
     pub fn payer(&self) -> Pubkey {
         self.cfg.payer()
@@ -245,11 +300,7 @@ impl<C: Clone + Deref<Target = impl Signer>> Client<C> {
 
     /// Create a bundle builder with the given options.
     pub fn bundle_with_options(&self, options: BundleOptions) -> BundleBuilder<'_, C> {
-        BundleBuilder::new_with_options(CreateBundleOptions {
-            cluster: self.cluster().clone(),
-            commitment: self.commitment(),
-            options,
-        })
+        BundleBuilder::from_rpc_client_with_options(self.cfg.rpc(), options)
     }
 
     /// Create a [`BundleBuilder`]
@@ -387,6 +438,35 @@ impl<C: Clone + Deref<Target = impl Signer>> Client<C> {
         crate::pda::find_referral_code_pda(store, code, self.store_program_id()).0
     }
 
+    /// Find referral reward vault address.
+    pub fn find_referral_reward_vault_address(&self, store: &Pubkey) -> Pubkey {
+        crate::pda::find_referral_reward_vault_pda(store, self.store_program_id()).0
+    }
+
+    /// Find liquidity mining campaign address.
+    pub fn find_liquidity_mining_campaign_address(
+        &self,
+        store: &Pubkey,
+        campaign_index: u8,
+    ) -> Pubkey {
+        crate::pda::find_liquidity_mining_campaign_pda(
+            store,
+            campaign_index,
+            self.store_program_id(),
+        )
+        .0
+    }
+
+    /// Find liquidity mining vault address.
+    pub fn find_liquidity_mining_vault_address(&self, campaign: &Pubkey, mint: &Pubkey) -> Pubkey {
+        crate::pda::find_liquidity_mining_vault_pda(campaign, mint, self.store_program_id()).0
+    }
+
+    /// Find LP balance checkpoint address.
+    pub fn find_lp_balance_checkpoint_address(&self, campaign: &Pubkey, owner: &Pubkey) -> Pubkey {
+        crate::pda::find_lp_balance_checkpoint_pda(campaign, owner, self.store_program_id()).0
+    }
+
     /// Find GLV token address.
     pub fn find_glv_token_address(&self, store: &Pubkey, index: u16) -> Pubkey {
         types::Glv::find_glv_token_pda(store, index, self.store_program_id()).0
@@ -397,6 +477,25 @@ impl<C: Clone + Deref<Target = impl Signer>> Client<C> {
         types::Glv::find_glv_pda(glv_token, self.store_program_id()).0
     }
 
+    /// Find GLV treasury address.
+    pub fn find_glv_treasury_address(&self, store: &Pubkey, glv: &Pubkey) -> Pubkey {
+        types::GlvTreasury::find_glv_treasury_pda(store, glv, self.store_program_id()).0
+    }
+
+    /// Find GLV deposit cooldown address.
+    pub fn find_glv_deposit_cooldown_address(
+        &self,
+        glv: &Pubkey,
+        token_account: &Pubkey,
+    ) -> Pubkey {
+        types::GlvDepositCooldown::find_glv_deposit_cooldown_pda(
+            glv,
+            token_account,
+            self.store_program_id(),
+        )
+        .0
+    }
+
     /// Find GLV deposit address.
     pub fn find_glv_deposit_address(
         &self,
@@ -438,6 +537,85 @@ impl<C: Clone + Deref<Target = impl Signer>> Client<C> {
         crate::pda::find_gt_exchange_pda(vault, owner, self.store_program_id()).0
     }
 
+    /// Find keeper deadline extension address.
+    pub fn find_keeper_deadline_extension_address(&self, store: &Pubkey) -> Pubkey {
+        crate::pda::find_keeper_deadline_extension_pda(store, self.store_program_id()).0
+    }
+
+    /// Find order callback authority address.
+    pub fn find_order_callback_authority_address(&self, store: &Pubkey) -> Pubkey {
+        crate::pda::find_order_callback_authority_pda(store, self.store_program_id()).0
+    }
+
+    /// Find keeper priority address.
+    pub fn find_keeper_priority_address(&self, store: &Pubkey, keeper: &Pubkey) -> Pubkey {
+        crate::pda::find_keeper_priority_pda(store, keeper, self.store_program_id()).0
+    }
+
+    /// Find the config snapshot address of the given snapshot index.
+    pub fn find_config_snapshot_address(&self, store: &Pubkey, index: u64) -> Pubkey {
+        crate::pda::find_config_snapshot_pda(store, index, self.store_program_id()).0
+    }
+
+    /// Find virtual pool address.
+    pub fn find_virtual_pool_address(&self, store: &Pubkey, key: &Pubkey) -> Pubkey {
+        crate::pda::find_virtual_pool_pda(store, key, self.store_program_id()).0
+    }
+
+    /// Find market OI caps address.
+    pub fn find_market_oi_caps_address(&self, store: &Pubkey, market_token: &Pubkey) -> Pubkey {
+        crate::pda::find_market_oi_caps_pda(store, market_token, self.store_program_id()).0
+    }
+
+    /// Find market analytics address.
+    pub fn find_market_analytics_address(
+        &self,
+        store: &Pubkey,
+        market_token: &Pubkey,
+        period_secs: u64,
+        period_key: i64,
+    ) -> Pubkey {
+        crate::pda::find_market_analytics_pda(
+            store,
+            market_token,
+            period_secs,
+            period_key,
+            self.store_program_id(),
+        )
+        .0
+    }
+
+    /// Find fee structure proposal address.
+    pub fn find_fee_structure_proposal_address(
+        &self,
+        store: &Pubkey,
+        market_token: &Pubkey,
+        nonce: &NonceBytes,
+    ) -> Pubkey {
+        crate::pda::find_fee_structure_proposal_pda(
+            store,
+            market_token,
+            nonce,
+            self.store_program_id(),
+        )
+        .0
+    }
+
+    /// Find fee structure vote address.
+    pub fn find_fee_structure_vote_address(&self, proposal: &Pubkey, voter: &Pubkey) -> Pubkey {
+        crate::pda::find_fee_structure_vote_pda(proposal, voter, self.store_program_id()).0
+    }
+
+    /// Find price adjustment schedule address.
+    pub fn find_price_adjustment_schedule_address(&self, store: &Pubkey, token: &Pubkey) -> Pubkey {
+        crate::pda::find_price_adjustment_schedule_pda(store, token, self.store_program_id()).0
+    }
+
+    /// Find last oracle price address.
+    pub fn find_last_oracle_price_address(&self, store: &Pubkey, token: &Pubkey) -> Pubkey {
+        crate::pda::find_last_oracle_price_pda(store, token, self.store_program_id()).0
+    }
+
     /// Find Custom Price Feed address.
     pub fn find_price_feed_address(
         &self,
@@ -619,6 +797,26 @@ impl<C: Clone + Deref<Target = impl Signer>> Client<C> {
         self.account(address).await?.ok_or(crate::Error::NotFound)
     }
 
+    /// Fetch the address of the token map to use for the given market.
+    ///
+    /// Returns the override token map of the market if it is set,
+    /// otherwise the token map of the store.
+    pub async fn market_token_map_address(&self, market: &Pubkey) -> crate::Result<Option<Pubkey>> {
+        let market = self.market(market).await?;
+        self.resolve_token_map_address(&market).await
+    }
+
+    /// Fetch the address of the token map to use for the given [`Market`](types::Market) account.
+    pub async fn resolve_token_map_address(
+        &self,
+        market: &types::Market,
+    ) -> crate::Result<Option<Pubkey>> {
+        match market.token_map() {
+            Some(token_map) => Ok(Some(*token_map)),
+            None => self.authorized_token_map_address(&market.store).await,
+        }
+    }
+
     /// Fetch the authorized token map of the given store.
     pub async fn authorized_token_map(&self, store: &Pubkey) -> crate::Result<types::TokenMap> {
         let address = self
@@ -685,6 +883,90 @@ impl<C: Clone + Deref<Target = impl Signer>> Client<C> {
             .0)
     }
 
+    /// Fetch the market account and check whether its state hash matches the expected one.
+    ///
+    /// The hash is the same as the `state_hash` of the
+    /// [`MarketStateExport`](gmsol_store::events::MarketStateExport) event.
+    pub async fn verify_market_state(
+        &self,
+        market: &Pubkey,
+        expected_hash: &[u8; 32],
+    ) -> crate::Result<bool> {
+        let market = self.market(market).await?;
+        Ok(market.state_hash() == *expected_hash)
+    }
+
+    /// Fetch the out-of-range configs of the market with the market token address.
+    ///
+    /// An empty list means that the market config is safe to use.
+    pub async fn market_config_problems(
+        &self,
+        store: &Pubkey,
+        market_token: &Pubkey,
+    ) -> crate::Result<Vec<MarketConfigProblem>> {
+        let req = self.validate_market_config(store, market_token);
+        let problems = crate::utils::view::<Vec<MarketConfigProblem>>(
+            &self.store_program().rpc(),
+            &req.signed_transaction_with_options(true, None).await?,
+        )
+        .await?;
+        Ok(problems)
+    }
+
+    /// Fetch the number of pending deposits, withdrawals and orders of the market
+    /// with the market token address.
+    pub async fn market_pending_counts(
+        &self,
+        store: &Pubkey,
+        market_token: &Pubkey,
+    ) -> crate::Result<PendingCounts> {
+        let req = self.get_market_pending_counts(store, market_token);
+        let counts = crate::utils::view::<PendingCounts>(
+            &self.store_program().rpc(),
+            &req.signed_transaction_with_options(true, None).await?,
+        )
+        .await?;
+        Ok(counts)
+    }
+
+    /// Fetch the names of all the market config keys supported by the store program.
+    pub async fn market_config_keys(&self, store: &Pubkey) -> crate::Result<Vec<String>> {
+        let mut names = Vec::new();
+        loop {
+            let start = u16::try_from(names.len()).map_err(crate::Error::unknown)?;
+            let req = self.list_market_config_keys(store, start);
+            let page = crate::utils::view::<Vec<String>>(
+                &self.store_program().rpc(),
+                &req.signed_transaction_with_options(true, None).await?,
+            )
+            .await?;
+            if page.is_empty() {
+                break;
+            }
+            names.extend(page);
+        }
+        Ok(names)
+    }
+
+    /// Fetch the names of all the market config flags supported by the store program.
+    pub async fn market_config_flags(&self, store: &Pubkey) -> crate::Result<Vec<String>> {
+        let mut names = Vec::new();
+        loop {
+            let start = u16::try_from(names.len()).map_err(crate::Error::unknown)?;
+            let req = self.list_market_config_flags(store, start);
+            let page = crate::utils::view::<Vec<String>>(
+                &self.store_program().rpc(),
+                &req.signed_transaction_with_options(true, None).await?,
+            )
+            .await?;
+            if page.is_empty() {
+                break;
+            }
+            names.extend(page);
+        }
+        Ok(names)
+    }
+
     /// Fetch [`MarketStatus`] with the market token address.
     pub async fn market_status(
         &self,
@@ -709,6 +991,351 @@ impl<C: Clone + Deref<Target = impl Signer>> Client<C> {
         Ok(status)
     }
 
+    /// Fetch the [`OhlcvData`] of the period starting at `period_start` from the given
+    /// market analytics account.
+    pub async fn ohlcv_period(
+        &self,
+        store: &Pubkey,
+        analytics: &Pubkey,
+        period_start: i64,
+    ) -> crate::Result<OhlcvData> {
+        let req = self.get_ohlcv_period(store, analytics, period_start);
+        let data = crate::utils::view::<OhlcvData>(
+            &self.store_program().rpc(),
+            &req.signed_transaction_with_options(true, None).await?,
+        )
+        .await?;
+        Ok(data)
+    }
+
+    /// Fetch [`MarketExpectedValue`] with the market token address.
+    pub async fn market_expected_value(
+        &self,
+        store: &Pubkey,
+        market_token: &Pubkey,
+        prices: Prices<u128>,
+        trading_volume_usd: u128,
+        time_horizon_secs: u64,
+    ) -> crate::Result<MarketExpectedValue> {
+        let req = self.get_market_expected_value(
+            store,
+            market_token,
+            prices,
+            trading_volume_usd,
+            time_horizon_secs,
+        );
+        let value = crate::utils::view::<MarketExpectedValue>(
+            &self.store_program().rpc(),
+            &req.signed_transaction_with_options(true, None).await?,
+        )
+        .await?;
+        Ok(value)
+    }
+
+    /// Fetch [`SwapFeeBreakdown`] of swapping `amount` of `input_token` to `output_token`
+    /// in the given market.
+    pub async fn market_swap_fees(
+        &self,
+        store: &Pubkey,
+        market_token: &Pubkey,
+        input_token: &Pubkey,
+        output_token: &Pubkey,
+        amount: u64,
+        prices: Prices<u128>,
+    ) -> crate::Result<SwapFeeBreakdown> {
+        let req = self.get_market_swap_fees(
+            store,
+            market_token,
+            input_token,
+            output_token,
+            amount,
+            prices,
+        );
+        let breakdown = crate::utils::view::<SwapFeeBreakdown>(
+            &self.store_program().rpc(),
+            &req.signed_transaction_with_options(true, None).await?,
+        )
+        .await?;
+        Ok(breakdown)
+    }
+
+    /// Fetch the [`PriceImpactSensitivity`] of opening long and short positions in
+    /// the given market.
+    ///
+    /// Returns `(long, short)`.
+    pub async fn get_price_impact_curve(
+        &self,
+        store: &Pubkey,
+        oracle: &Pubkey,
+        market_token: &Pubkey,
+        prices: Prices<u128>,
+    ) -> crate::Result<(PriceImpactSensitivity, PriceImpactSensitivity)> {
+        let fetch = |direction: i8| {
+            let req = self.get_market_price_impact_sensitivity(
+                store,
+                oracle,
+                market_token,
+                prices,
+                direction,
+            );
+            async move {
+                crate::utils::view::<PriceImpactSensitivity>(
+                    &self.store_program().rpc(),
+                    &req.signed_transaction_with_options(true, None).await?,
+                )
+                .await
+            }
+        };
+        let long = fetch(1).await?;
+        let short = fetch(-1).await?;
+        Ok((long, short))
+    }
+
+    /// Fetch the [`ArbitrageOpportunity`] of swapping `amount` of `input_token` to
+    /// `output_token` through the markets of the given prices.
+    ///
+    /// The `prices` are the unit prices of tokens keyed by the market token of each market.
+    pub async fn swap_arbitrage_opportunity(
+        &self,
+        store: &Pubkey,
+        input_token: &Pubkey,
+        output_token: &Pubkey,
+        amount: u64,
+        prices: BTreeMap<Pubkey, Prices<u128>>,
+    ) -> crate::Result<ArbitrageOpportunity> {
+        let req =
+            self.get_swap_arbitrage_opportunity(store, input_token, output_token, amount, prices);
+        let opportunity = crate::utils::view::<ArbitrageOpportunity>(
+            &self.store_program().rpc(),
+            &req.signed_transaction_with_options(true, None).await?,
+        )
+        .await?;
+        Ok(opportunity)
+    }
+
+    /// Watch [`MarketStatus`] of the given market periodically.
+    ///
+    /// The `prices` function is called on each tick to get the prices used to
+    /// compute the status, e.g. the latest unit prices from Hermes. A status is
+    /// only yielded when it differs from the last yielded one. Ticks whose prices
+    /// or status cannot be fetched are skipped.
+    pub fn watch_market_status<'a, F, Fut>(
+        &'a self,
+        store: &Pubkey,
+        market_token: &Pubkey,
+        interval: Duration,
+        mut prices: F,
+    ) -> impl futures_util::Stream<Item = MarketStatus> + 'a
+    where
+        F: FnMut() -> Fut + 'a,
+        Fut: std::future::Future<Output = crate::Result<Prices<u128>>> + 'a,
+    {
+        let store = *store;
+        let market_token = *market_token;
+        async_stream::stream! {
+            let mut interval = tokio::time::interval(interval);
+            let mut last = None;
+            loop {
+                interval.tick().await;
+                let prices = match (prices)().await {
+                    Ok(prices) => prices,
+                    Err(err) => {
+                        tracing::warn!(%err, %market_token, "failed to fetch prices, skipping");
+                        continue;
+                    }
+                };
+                let status = match self
+                    .market_status(&store, &market_token, prices, true, false)
+                    .await
+                {
+                    Ok(status) => status,
+                    Err(err) => {
+                        tracing::warn!(%err, %market_token, "failed to fetch market status, skipping");
+                        continue;
+                    }
+                };
+                let Ok(bytes) = status.try_to_vec() else {
+                    continue;
+                };
+                if last.as_ref() == Some(&bytes) {
+                    continue;
+                }
+                last = Some(bytes);
+                yield status;
+            }
+        }
+    }
+
+    /// Fetch [`PositionInfo`] of the given position.
+    pub async fn position_info(
+        &self,
+        position: &Pubkey,
+        prices: Prices<u128>,
+    ) -> crate::Result<PositionInfo> {
+        let account = self.position(position).await?;
+        let req = self.get_position_info(&account.store, &account.market_token, position, prices);
+        let info = crate::utils::view::<PositionInfo>(
+            &self.store_program().rpc(),
+            &req.signed_transaction_with_options(true, None).await?,
+        )
+        .await?;
+        Ok(info)
+    }
+
+    /// Estimate the size delta to auto-deleverage for the given position.
+    pub async fn estimate_adl_amount(
+        &self,
+        position: &Pubkey,
+        prices: Prices<u128>,
+    ) -> crate::Result<AdlEstimate> {
+        let account = self.position(position).await?;
+        let req =
+            self.estimate_adl_size_delta(&account.store, &account.market_token, position, prices);
+        let estimate = crate::utils::view::<AdlEstimate>(
+            &self.store_program().rpc(),
+            &req.signed_transaction_with_options(true, None).await?,
+        )
+        .await?;
+        Ok(estimate)
+    }
+
+    /// Estimate the impact on the market of liquidating the given position.
+    pub async fn liquidation_impact(
+        &self,
+        position: &Pubkey,
+        prices: Prices<u128>,
+    ) -> crate::Result<LiquidationImpact> {
+        let account = self.position(position).await?;
+        let req =
+            self.get_liquidation_impact(&account.store, &account.market_token, position, prices);
+        let impact = crate::utils::view::<LiquidationImpact>(
+            &self.store_program().rpc(),
+            &req.signed_transaction_with_options(true, None).await?,
+        )
+        .await?;
+        Ok(impact)
+    }
+
+    /// Fetch [`CollateralCoverage`] of the given position.
+    pub async fn collateral_coverage(
+        &self,
+        position: &Pubkey,
+        prices: Prices<u128>,
+    ) -> crate::Result<CollateralCoverage> {
+        let account = self.position(position).await?;
+        let req = self.get_collateral_coverage_ratio(
+            &account.store,
+            &account.market_token,
+            position,
+            prices,
+        );
+        let coverage = crate::utils::view::<CollateralCoverage>(
+            &self.store_program().rpc(),
+            &req.signed_transaction_with_options(true, None).await?,
+        )
+        .await?;
+        Ok(coverage)
+    }
+
+    /// Fetch [`BreakEvenPrice`] of the given position.
+    pub async fn position_break_even_price(
+        &self,
+        position: &Pubkey,
+        prices: Prices<u128>,
+    ) -> crate::Result<BreakEvenPrice> {
+        let account = self.position(position).await?;
+        let req = self.get_position_break_even_price(
+            &account.store,
+            &account.market_token,
+            position,
+            prices,
+        );
+        let price = crate::utils::view::<BreakEvenPrice>(
+            &self.store_program().rpc(),
+            &req.signed_transaction_with_options(true, None).await?,
+        )
+        .await?;
+        Ok(price)
+    }
+
+    /// Fetch the config snapshot with the given index.
+    pub async fn config_snapshot(
+        &self,
+        store: &Pubkey,
+        index: u64,
+    ) -> crate::Result<ConfigSnapshot> {
+        let req = self.get_config_snapshot(store, index);
+        let snapshot = crate::utils::view::<ConfigSnapshot>(
+            &self.store_program().rpc(),
+            &req.signed_transaction_with_options(true, None).await?,
+        )
+        .await?;
+        Ok(snapshot)
+    }
+
+    /// Fetch the config entries changed from snapshot `index_a` to snapshot `index_b`.
+    pub async fn config_snapshot_diff(
+        &self,
+        store: &Pubkey,
+        index_a: u64,
+        index_b: u64,
+    ) -> crate::Result<Vec<ConfigDiff>> {
+        let req = self.compare_config_snapshots(store, index_a, index_b);
+        let diff = crate::utils::view::<Vec<ConfigDiff>>(
+            &self.store_program().rpc(),
+            &req.signed_transaction_with_options(true, None).await?,
+        )
+        .await?;
+        Ok(diff)
+    }
+
+    /// Fetch the referral stats of the user account of the given owner.
+    pub async fn referral_stats(
+        &self,
+        store: &Pubkey,
+        owner: &Pubkey,
+    ) -> crate::Result<ReferralStats> {
+        let req = self.get_referral_stats(store, owner);
+        let stats = crate::utils::view::<ReferralStats>(
+            &self.store_program().rpc(),
+            &req.signed_transaction_with_options(true, None).await?,
+        )
+        .await?;
+        Ok(stats)
+    }
+
+    /// Fetch the activity of the user account of the given owner.
+    pub async fn user_activity(
+        &self,
+        store: &Pubkey,
+        owner: &Pubkey,
+    ) -> crate::Result<UserActivity> {
+        let req = self.get_user_activity(store, owner);
+        let activity = crate::utils::view::<UserActivity>(
+            &self.store_program().rpc(),
+            &req.signed_transaction_with_options(true, None).await?,
+        )
+        .await?;
+        Ok(activity)
+    }
+
+    /// Fetch the problems of the given referral reward factors.
+    ///
+    /// The factors are valid if the returned list is empty.
+    pub async fn referral_reward_factors_problems(
+        &self,
+        store: &Pubkey,
+        factors: Vec<u128>,
+    ) -> crate::Result<Vec<ReferralRewardFactorsProblem>> {
+        let req = self.validate_referral_reward_factors(store, factors);
+        let problems = crate::utils::view::<Vec<ReferralRewardFactorsProblem>>(
+            &self.store_program().rpc(),
+            &req.signed_transaction_with_options(true, None).await?,
+        )
+        .await?;
+        Ok(problems)
+    }
+
     /// Fetch current market token price with the market token address.
     pub async fn market_token_price(
         &self,
@@ -727,6 +1354,55 @@ impl<C: Clone + Deref<Target = impl Signer>> Client<C> {
         Ok(price)
     }
 
+    /// Fetch the underlying tokens backing one unit of market token.
+    pub async fn market_token_backing(
+        &self,
+        store: &Pubkey,
+        market_token: &Pubkey,
+        prices: Prices<u128>,
+    ) -> crate::Result<MarketTokenBacking> {
+        let req = self.get_market_token_backing(store, market_token, prices);
+        let backing = crate::utils::view::<MarketTokenBacking>(
+            &self.store_program().rpc(),
+            &req.signed_transaction_with_options(true, None).await?,
+        )
+        .await?;
+        Ok(backing)
+    }
+
+    /// Fetch the value of the liquidity pool and the market token with the prices in the oracle.
+    pub async fn lp_nav(
+        &self,
+        store: &Pubkey,
+        oracle: &Pubkey,
+        market_token: &Pubkey,
+        maximize: bool,
+    ) -> crate::Result<LpValueInfo> {
+        let req = self.get_lp_nav(store, oracle, market_token, maximize);
+        let info = crate::utils::view::<LpValueInfo>(
+            &self.store_program().rpc(),
+            &req.signed_transaction_with_options(true, None).await?,
+        )
+        .await?;
+        Ok(info)
+    }
+
+    /// Verify the market token supply against the market balances.
+    pub async fn market_token_supply_verification(
+        &self,
+        store: &Pubkey,
+        market_token: &Pubkey,
+        prices: Prices<u128>,
+    ) -> crate::Result<SupplyVerification> {
+        let req = self.verify_market_token_supply(store, market_token, prices);
+        let verification = crate::utils::view::<SupplyVerification>(
+            &self.store_program().rpc(),
+            &req.signed_transaction_with_options(true, None).await?,
+        )
+        .await?;
+        Ok(verification)
+    }
+
     /// Fetch all [`Position`](types::Position) accounts of the given owner of the given store.
     pub async fn positions(
         &self,
@@ -775,6 +1451,33 @@ impl<C: Clone + Deref<Target = impl Signer>> Client<C> {
         Ok(position.0)
     }
 
+    /// Get the addresses of the other positions of the owner in the same market that are
+    /// required to execute orders of the given position.
+    ///
+    /// Returns an empty list if the position does not exist or is not in cross margin mode.
+    pub async fn cross_margin_positions(
+        &self,
+        position: &Pubkey,
+        long_token: &Pubkey,
+        short_token: &Pubkey,
+    ) -> crate::Result<Vec<Pubkey>> {
+        let Some(position) = self
+            .account::<ZeroCopy<types::Position>>(position)
+            .await?
+            .map(|position| position.0)
+        else {
+            return Ok(vec![]);
+        };
+        if !position.is_cross_margin()? {
+            return Ok(vec![]);
+        }
+        Ok(position.find_cross_margin_sibling_addresses(
+            long_token,
+            short_token,
+            self.store_program_id(),
+        )?)
+    }
+
     /// Fetch [`Order`](types::Order) account with its address.
     pub async fn order(&self, address: &Pubkey) -> crate::Result<types::Order> {
         Ok(self
@@ -857,6 +1560,27 @@ impl<C: Clone + Deref<Target = impl Signer>> Client<C> {
             .map(|a| a.0))
     }
 
+    /// Fetch [`PriceFeedStatus`] of the given price feed.
+    pub async fn price_feed_status(&self, price_feed: &Pubkey) -> crate::Result<PriceFeedStatus> {
+        use crate::store::oracle::OracleOps;
+
+        let feed = self
+            .price_feed(price_feed)
+            .await?
+            .ok_or(crate::Error::NotFound)?;
+        let token_map = self
+            .authorized_token_map_address(feed.store())
+            .await?
+            .ok_or(crate::Error::invalid_argument("token map is not set"))?;
+        let req = self.get_price_feed_status(feed.store(), &token_map, price_feed);
+        let status = crate::utils::view::<PriceFeedStatus>(
+            &self.store_program().rpc(),
+            &req.signed_transaction_with_options(true, None).await?,
+        )
+        .await?;
+        Ok(status)
+    }
+
     /// Fetch [`InstructionBuffer`] account with its address.
     pub async fn instruction_buffer(
         &self,
@@ -1142,6 +1866,113 @@ impl<C: Clone + Deref<Target = impl Signer>> Client<C> {
         Err(crate::Error::unknown("the watch stream end"))
     }
 
+    /// Wait for the given transaction to be confirmed and return the terminal result
+    /// of the `action` it executed or cancelled.
+    ///
+    /// If the transaction failed (e.g. the execution error was thrown because
+    /// `throw_on_execution_error` was set), the on-chain error is returned instead.
+    #[cfg(feature = "decode")]
+    pub async fn await_action_result(
+        &self,
+        signature: &anchor_client::solana_sdk::signature::Signature,
+        action: &Pubkey,
+    ) -> crate::Result<crate::store::events::ActionResult> {
+        self.await_action_result_with_config(
+            signature,
+            action,
+            std::time::Duration::from_secs(1),
+            std::time::Duration::from_secs(60),
+            None,
+        )
+        .await
+    }
+
+    /// Wait for the given transaction to be confirmed and return the terminal result
+    /// of the `action` with the given config.
+    ///
+    /// The status of the transaction is polled every `polling` until it is confirmed,
+    /// and an error is returned if it is still not confirmed after `timeout`.
+    #[cfg(feature = "decode")]
+    pub async fn await_action_result_with_config(
+        &self,
+        signature: &anchor_client::solana_sdk::signature::Signature,
+        action: &Pubkey,
+        polling: std::time::Duration,
+        timeout: std::time::Duration,
+        commitment: Option<CommitmentConfig>,
+    ) -> crate::Result<crate::store::events::ActionResult> {
+        use anchor_client::{solana_client::rpc_config::RpcTransactionConfig, ClientError};
+        use gmsol_decode::{decoder::TransactionDecoder, Decode};
+        use solana_transaction_status::{
+            option_serializer::OptionSerializer, UiTransactionEncoding,
+        };
+
+        use crate::store::events::{ActionResult, StoreCPIEvent};
+
+        let commitment = commitment.unwrap_or(self.commitment());
+        let client = self.rpc();
+
+        let deadline = tokio::time::Instant::now() + timeout;
+        loop {
+            let status = client
+                .get_signature_status_with_commitment(signature, commitment)
+                .await
+                .map_err(ClientError::from)?;
+            if status.is_some() {
+                break;
+            }
+            if tokio::time::Instant::now() + polling > deadline {
+                return Err(crate::Error::unknown(format!(
+                    "timed out waiting for the transaction `{signature}` to be confirmed"
+                )));
+            }
+            tokio::time::sleep(polling).await;
+        }
+
+        let tx = client
+            .get_transaction_with_config(
+                signature,
+                RpcTransactionConfig {
+                    encoding: Some(UiTransactionEncoding::Base64),
+                    commitment: Some(commitment),
+                    max_supported_transaction_version: Some(0),
+                },
+            )
+            .await
+            .map_err(ClientError::from)?;
+
+        let Some(meta) = tx.transaction.meta.as_ref() else {
+            return Err(crate::Error::unknown("missing transaction meta"));
+        };
+        if let Some(err) = &meta.err {
+            if let OptionSerializer::Some(logs) = &meta.log_messages {
+                if let Some(err) = crate::error::anchor_error_from_logs(logs) {
+                    return Err(crate::Error::Anchor(err));
+                }
+            }
+            return Err(crate::Error::unknown(format!("transaction failed: {err}")));
+        }
+
+        let mut decoder = TransactionDecoder::new(tx.slot, *signature, &tx.transaction);
+        let events = decoder
+            .add_cpi_event_authority_and_program_id(
+                self.store_event_authority(),
+                *self.store_program_id(),
+            )?
+            .extract_cpi_events()?;
+        let events = events
+            .events
+            .iter()
+            .map(|event| StoreCPIEvent::decode(event).map_err(crate::Error::from))
+            .collect::<crate::Result<Vec<_>>>()?;
+
+        ActionResult::from_events(action, events).ok_or_else(|| {
+            crate::Error::unknown(format!(
+                "action `{action}` is not completed or cancelled in the transaction"
+            ))
+        })
+    }
+
     /// Shutdown the client gracefully.
     pub async fn shutdown(&self) -> crate::Result<()> {
         self.pub_sub().await?.shutdown().await
@@ -1238,3 +2069,31 @@ impl From<StoreFilter> for RpcFilterType {
         ))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use anchor_client::solana_sdk::{compute_budget::ComputeBudgetInstruction, signature::Keypair};
+
+    use super::*;
+
+    #[test]
+    fn default_compute_unit_price() -> crate::Result<()> {
+        let price = 1_000_000;
+        let client = Client::new_with_options(
+            Cluster::Localnet,
+            Arc::new(Keypair::new()),
+            ClientOptions::builder()
+                .default_compute_unit_price(Some(price))
+                .build(),
+        )?;
+        assert_eq!(client.default_compute_unit_price(), Some(price));
+
+        let instructions = client.store_transaction().instructions();
+        assert!(instructions.contains(&ComputeBudgetInstruction::set_compute_unit_price(price)));
+
+        let cloned = client.try_clone_with_payer(Arc::new(Keypair::new()))?;
+        let instructions = cloned.store_transaction().instructions();
+        assert!(instructions.contains(&ComputeBudgetInstruction::set_compute_unit_price(price)));
+        Ok(())
+    }
+}
@@ -1,24 +1,38 @@
 use std::{
     collections::{BTreeMap, HashMap},
+    ops::Deref,
     sync::Arc,
     task::Poll,
     time::Duration,
 };
 
-use anchor_client::solana_sdk::{
-    commitment_config::CommitmentConfig, pubkey::Pubkey, signature::Keypair,
+use anchor_client::{
+    anchor_lang::Discriminator,
+    solana_client::{
+        rpc_config::RpcAccountInfoConfig,
+        rpc_filter::{Memcmp, RpcFilterType},
+    },
+    solana_sdk::{
+        commitment_config::CommitmentConfig, pubkey::Pubkey, signature::Keypair, signer::Signer,
+    },
 };
 use futures_util::Stream;
+use gmsol_model::{price::Prices, Balance, PoolKind};
 use gmsol_solana_utils::cluster::Cluster;
+use solana_account_decoder::{UiAccountEncoding, UiDataSliceConfig};
 use tokio::sync::watch;
 use tokio_stream::wrappers::WatchStream;
 use tower::discover::Change;
 use tracing::Instrument;
 
 use crate::{
+    client::StoreFilter,
     pda::find_default_store,
     types,
-    utils::{shared_signer, ProgramAccountsConfig, SignerRef, WithContext},
+    utils::{
+        rpc::accounts::{get_program_accounts_with_context, ProgramAccountsConfigForRpc},
+        shared_signer, ProgramAccountsConfig, SignerRef, WithContext, ZeroCopy,
+    },
     Client, ClientOptions,
 };
 
@@ -188,6 +202,230 @@ impl Watcher {
     }
 }
 
+/// Find all markets of the given store, returning their addresses, metas and
+/// whether they are enabled.
+///
+/// Only the headers of the market accounts are fetched.
+pub async fn find_markets<C: Clone + Deref<Target = impl Signer>>(
+    client: &Client<C>,
+    store: &Pubkey,
+) -> crate::Result<Vec<(Pubkey, types::MarketMeta, bool)>> {
+    let discriminator_len = types::Market::DISCRIMINATOR.len();
+    let filters = vec![
+        RpcFilterType::Memcmp(Memcmp::new_base58_encoded(0, &types::Market::DISCRIMINATOR)),
+        StoreFilter::new(store, bytemuck::offset_of!(types::Market, store)).into(),
+    ];
+    let config = ProgramAccountsConfigForRpc {
+        filters: Some(filters),
+        account_config: RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            data_slice: Some(UiDataSliceConfig {
+                offset: 0,
+                length: discriminator_len + types::Market::header_len(),
+            }),
+            ..Default::default()
+        },
+    };
+    let accounts = get_program_accounts_with_context(
+        &client.store_program().rpc(),
+        client.store_program_id(),
+        config,
+    )
+    .await?
+    .into_value();
+    accounts
+        .into_iter()
+        .map(|(address, account)| {
+            let (meta, is_enabled) = account
+                .data
+                .get(discriminator_len..)
+                .and_then(types::Market::decode_header)
+                .ok_or_else(|| crate::Error::invalid_argument("invalid market header"))?;
+            Ok((address, meta, is_enabled))
+        })
+        .collect()
+}
+
+/// Basic information of a discovered market.
+#[derive(Debug, Clone)]
+pub struct MarketInfo {
+    /// Market address.
+    pub address: Pubkey,
+    /// Market token mint.
+    pub market_token: Pubkey,
+    /// Index token.
+    pub index_token: Pubkey,
+    /// Long token.
+    pub long_token: Pubkey,
+    /// Short token.
+    pub short_token: Pubkey,
+    /// Whether the market is enabled.
+    pub is_enabled: bool,
+    /// Long token amount of the primary pool.
+    pub long_pool_amount: u128,
+    /// Short token amount of the primary pool.
+    pub short_pool_amount: u128,
+    /// Unit prices of the market tokens, if fetched.
+    pub prices: Option<Prices<u128>>,
+}
+
+impl MarketInfo {
+    fn from_market(address: Pubkey, market: &types::Market) -> crate::Result<Self> {
+        let meta = market.meta();
+        let (long_pool_amount, short_pool_amount) = match market.pool(PoolKind::Primary) {
+            Some(pool) => (pool.long_amount()?, pool.short_amount()?),
+            None => (0, 0),
+        };
+        Ok(Self {
+            address,
+            market_token: meta.market_token_mint,
+            index_token: meta.index_token_mint,
+            long_token: meta.long_token_mint,
+            short_token: meta.short_token_mint,
+            is_enabled: market.is_enabled(),
+            long_pool_amount,
+            short_pool_amount,
+            prices: None,
+        })
+    }
+}
+
+async fn fetch_markets<C: Clone + Deref<Target = impl Signer>>(
+    client: &Client<C>,
+    store: &Pubkey,
+) -> crate::Result<Vec<(Pubkey, types::Market)>> {
+    let markets = client
+        .store_accounts_with_config::<ZeroCopy<types::Market>>(
+            Some(StoreFilter::new(
+                store,
+                bytemuck::offset_of!(types::Market, store),
+            )),
+            Some(RpcFilterType::DataSize(
+                (8 + std::mem::size_of::<types::Market>()) as u64,
+            )),
+            ProgramAccountsConfig::default(),
+        )
+        .await?
+        .into_value()
+        .into_iter()
+        .map(|(pubkey, m)| (pubkey, m.0))
+        .collect();
+    Ok(markets)
+}
+
+/// Discover all markets of the given store, including the disabled ones.
+pub async fn discover_markets<C: Clone + Deref<Target = impl Signer>>(
+    client: &Client<C>,
+    store: &Pubkey,
+) -> crate::Result<Vec<MarketInfo>> {
+    fetch_markets(client, store)
+        .await?
+        .iter()
+        .map(|(address, market)| MarketInfo::from_market(*address, market))
+        .collect()
+}
+
+/// Discover all markets of the given store, with the unit prices of their tokens
+/// fetched from Pyth in parallel.
+///
+/// The `prices` field is left as `None` for markets whose prices cannot be fetched.
+#[cfg(feature = "pyth-pull-oracle")]
+pub async fn discover_markets_with_prices<C: Clone + Deref<Target = impl Signer>>(
+    client: &Client<C>,
+    store: &Pubkey,
+    hermes: &crate::pyth::Hermes,
+) -> crate::Result<Vec<MarketInfo>> {
+    use futures_util::{stream::FuturesUnordered, StreamExt};
+
+    let markets = fetch_markets(client, store).await?;
+
+    let mut token_maps = HashMap::<Pubkey, types::TokenMap>::default();
+    let mut token_map_addresses = Vec::with_capacity(markets.len());
+    for (_, market) in markets.iter() {
+        let address = client.resolve_token_map_address(market).await?;
+        if let Some(address) = address {
+            if !token_maps.contains_key(&address) {
+                token_maps.insert(address, client.token_map(&address).await?);
+            }
+        }
+        token_map_addresses.push(address);
+    }
+
+    let mut tasks = markets
+        .iter()
+        .zip(token_map_addresses)
+        .enumerate()
+        .filter_map(|(idx, ((_, market), address))| {
+            let token_map = token_maps.get(&address?)?;
+            Some(async move { (idx, hermes.unit_prices_for_market(token_map, market).await) })
+        })
+        .collect::<FuturesUnordered<_>>();
+
+    let mut infos = markets
+        .iter()
+        .map(|(address, market)| MarketInfo::from_market(*address, market))
+        .collect::<crate::Result<Vec<_>>>()?;
+
+    while let Some((idx, prices)) = tasks.next().await {
+        match prices {
+            Ok(prices) => infos[idx].prices = Some(prices),
+            Err(err) => {
+                tracing::warn!(market = %infos[idx].address, %err, "failed to fetch prices");
+            }
+        }
+    }
+
+    Ok(infos)
+}
+
+/// Cached results of [`discover_markets`].
+pub struct MarketInfoDiscovery<'a, C> {
+    client: &'a Client<C>,
+    store: Pubkey,
+    markets: Vec<MarketInfo>,
+}
+
+impl<'a, C: Clone + Deref<Target = impl Signer>> MarketInfoDiscovery<'a, C> {
+    /// Discover the markets of the given store and cache the results.
+    pub async fn new(client: &'a Client<C>, store: &Pubkey) -> crate::Result<Self> {
+        let mut discovery = Self {
+            client,
+            store: *store,
+            markets: Vec::default(),
+        };
+        discovery.refresh().await?;
+        Ok(discovery)
+    }
+
+    /// Re-run the discovery and replace the cached results.
+    pub async fn refresh(&mut self) -> crate::Result<&[MarketInfo]> {
+        self.markets = discover_markets(self.client, &self.store).await?;
+        Ok(&self.markets)
+    }
+
+    /// Re-run the discovery with prices and replace the cached results.
+    #[cfg(feature = "pyth-pull-oracle")]
+    pub async fn refresh_with_prices(
+        &mut self,
+        hermes: &crate::pyth::Hermes,
+    ) -> crate::Result<&[MarketInfo]> {
+        self.markets = discover_markets_with_prices(self.client, &self.store, hermes).await?;
+        Ok(&self.markets)
+    }
+
+    /// Get the cached markets.
+    pub fn markets(&self) -> &[MarketInfo] {
+        &self.markets
+    }
+
+    /// Get the cached market info by market token.
+    pub fn get(&self, market_token: &Pubkey) -> Option<&MarketInfo> {
+        self.markets
+            .iter()
+            .find(|info| info.market_token == *market_token)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use futures_util::{future::poll_fn, pin_mut};
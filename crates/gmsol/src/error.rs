@@ -1,4 +1,8 @@
-use anchor_client::{solana_client::pubsub_client::PubsubClientError, solana_sdk};
+use anchor_client::{
+    anchor_lang::{error::ERROR_CODE_OFFSET, prelude::ProgramError},
+    solana_client::{client_error::ClientError, pubsub_client::PubsubClientError},
+    solana_sdk::{self, instruction::InstructionError, transaction::TransactionError},
+};
 use tokio_stream::wrappers::errors::BroadcastStreamRecvError;
 
 pub use gmsol_store::CoreError;
@@ -117,6 +121,45 @@ impl Error {
         };
         Some(error.error_code_number)
     }
+
+    /// Get the custom program error code carried by this error, if any.
+    ///
+    /// Unlike [`Error::anchor_error_code`], this also extracts the code from the
+    /// transaction errors returned by simulation or confirmation.
+    pub fn program_error_code(&self) -> Option<u32> {
+        match self {
+            Self::Anchor(error) => Some(error.error_code_number),
+            Self::Client(error) => match error.as_ref() {
+                anchor_client::ClientError::SolanaClientError(error) => {
+                    custom_error_code_from_client_error(error)
+                }
+                anchor_client::ClientError::ProgramError(ProgramError::Custom(code)) => Some(*code),
+                anchor_client::ClientError::AnchorError(
+                    anchor_client::anchor_lang::error::Error::AnchorError(error),
+                ) => Some(error.error_code_number),
+                _ => None,
+            },
+            Self::SolanaUtils(gmsol_solana_utils::Error::Client(error)) => {
+                custom_error_code_from_client_error(error)
+            }
+            _ => None,
+        }
+    }
+
+    /// Get the [`CoreError`] if this error is caused by a custom program error
+    /// whose code matches one of the [`CoreError`] variants.
+    ///
+    /// Note that the error code does not identify the program that throws it,
+    /// so the caller should make sure the error comes from the store program.
+    pub fn as_core_error(&self) -> Option<CoreError> {
+        self.program_error_code().and_then(core_error_from_code)
+    }
+}
+
+/// Convert the given custom program error code into [`CoreError`].
+pub fn core_error_from_code(code: u32) -> Option<CoreError> {
+    let index = code.checked_sub(ERROR_CODE_OFFSET)?;
+    CoreError::try_from(index).ok()
 }
 
 impl From<anchor_client::ClientError> for Error {
@@ -186,9 +229,7 @@ pub enum ErrorOrigin {
     AccountName(String),
 }
 
-fn handle_solana_client_error(
-    error: &anchor_client::solana_client::client_error::ClientError,
-) -> Option<Error> {
+fn handle_solana_client_error(error: &ClientError) -> Option<Error> {
     use anchor_client::solana_client::{
         client_error::ClientErrorKind,
         rpc_request::{RpcError, RpcResponseErrorData},
@@ -210,6 +251,37 @@ fn handle_solana_client_error(
         return None;
     };
 
+    anchor_error_from_logs(logs).map(Error::Anchor)
+}
+
+fn custom_error_code_from_client_error(error: &ClientError) -> Option<u32> {
+    use anchor_client::solana_client::{
+        client_error::ClientErrorKind,
+        rpc_request::{RpcError, RpcResponseErrorData},
+    };
+
+    let error = match error.kind() {
+        ClientErrorKind::TransactionError(error) => error,
+        ClientErrorKind::RpcError(RpcError::RpcResponseError {
+            data: RpcResponseErrorData::SendTransactionPreflightFailure(simulation),
+            ..
+        }) => {
+            if let Some(error) = simulation.logs.as_deref().and_then(anchor_error_from_logs) {
+                return Some(error.error_code_number);
+            }
+            simulation.err.as_ref()?
+        }
+        _ => return None,
+    };
+
+    match error {
+        TransactionError::InstructionError(_, InstructionError::Custom(code)) => Some(*code),
+        _ => None,
+    }
+}
+
+/// Parse the first [`AnchorError`] found in the program logs.
+pub(crate) fn anchor_error_from_logs(logs: &[String]) -> Option<AnchorError> {
     for log in logs {
         if log.starts_with("Program log: AnchorError") {
             let log = log.trim_start_matches("Program log: AnchorError ");
@@ -251,10 +323,10 @@ fn handle_solana_client_error(
                 error_code_number: number,
                 error_msg: message.trim().to_string(),
                 error_origin: origin,
-                logs: logs.clone(),
+                logs: logs.to_vec(),
             };
 
-            return Some(Error::Anchor(error));
+            return Some(error);
         }
     }
 
@@ -266,3 +338,49 @@ impl<T> From<(T, gmsol_solana_utils::Error)> for Error {
         Self::SolanaUtils(err)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_core_error_from_logs() {
+        let logs = [
+            "Program Gmso1uvJnLbawvw7yezdfCDcPydwW2s2iqG3w6MDucLo invoke [1]",
+            "Program log: Instruction: ExecuteDecreaseOrder",
+            "Program log: AnchorError thrown in programs/gmsol-store/src/ops/order.rs:1557. Error Code: InsufficientOutputAmount. Error Number: 6038. Error Message: Insufficient output amount.",
+            "Program Gmso1uvJnLbawvw7yezdfCDcPydwW2s2iqG3w6MDucLo failed: custom program error: 0x1796",
+        ]
+        .map(String::from);
+        let code = u32::from(CoreError::InsufficientOutputAmount);
+        let error = Error::Anchor(anchor_error_from_logs(&logs).expect("must be found"));
+        assert_eq!(error.anchor_error_code(), Some(6038));
+        assert_eq!(error.program_error_code(), Some(code));
+        assert_eq!(error.as_core_error().map(u32::from), Some(code));
+    }
+
+    #[test]
+    fn test_core_error_from_code() {
+        let code = u32::from(CoreError::InsufficientOutputAmount);
+        assert_eq!(core_error_from_code(code).map(u32::from), Some(code));
+        assert_eq!(
+            core_error_from_code(ERROR_CODE_OFFSET).map(u32::from),
+            Some(u32::from(CoreError::NonDefaultStore))
+        );
+        assert!(core_error_from_code(0).is_none());
+        assert!(core_error_from_code(ERROR_CODE_OFFSET - 1).is_none());
+        assert!(core_error_from_code(u32::MAX).is_none());
+    }
+
+    #[test]
+    fn test_core_error_from_transaction_error() {
+        let code = u32::from(CoreError::InsufficientOutputAmount);
+        let error = ClientError::from(TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(code),
+        ));
+        let error = Error::from(anchor_client::ClientError::SolanaClientError(error));
+        assert_eq!(error.program_error_code(), Some(code));
+        assert_eq!(error.as_core_error().map(u32::from), Some(code));
+    }
+}
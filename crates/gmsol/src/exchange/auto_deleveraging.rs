@@ -137,13 +137,9 @@ impl UpdateAdlHint {
     ) -> crate::Result<Self> {
         use gmsol_store::states::common::token_with_feeds::token_records;
 
-        let store_address = market.store;
-        let token_map_address = client
-            .authorized_token_map_address(&store_address)
-            .await?
-            .ok_or(crate::Error::invalid_argument(
-                "token map is not configurated for the store",
-            ))?;
+        let token_map_address = client.resolve_token_map_address(market).await?.ok_or(
+            crate::Error::invalid_argument("token map is not configurated for the market"),
+        )?;
         let token_map = client.token_map(&token_map_address).await?;
         let meta = market.meta();
 
@@ -15,19 +15,20 @@ use gmsol_store::{
     ops::deposit::CreateDepositParams,
     states::{
         common::{action::Action, swap::SwapActionParams, TokensWithFeed},
+        user::ReferralCodeBytes,
         Deposit, NonceBytes, PriceProviderKind, TokenMapAccess,
     },
 };
 
 use crate::{
     exchange::ExchangeOps,
-    store::{token::TokenAccountOps, utils::FeedsParser},
+    store::{token::TokenAccountOps, user::UserOps, utils::FeedsParser},
     utils::builder::{
         FeedAddressMap, FeedIds, MakeBundleBuilder, PullOraclePriceConsumer, SetExecutionFee,
     },
 };
 
-use super::{generate_nonce, get_ata_or_owner};
+use super::{generate_nonce, get_ata_or_owner, set_referrer_if_needed};
 
 #[cfg(feature = "pyth-pull-oracle")]
 use crate::pyth::pull_oracle::Prices;
@@ -53,6 +54,8 @@ pub struct CreateDepositBuilder<'a, C> {
     receiver: Option<Pubkey>,
     nonce: Option<NonceBytes>,
     should_unwrap_native_token: bool,
+    should_wrap_native_token: bool,
+    referral_code: Option<ReferralCodeBytes>,
 }
 
 impl<C> CreateDepositBuilder<'_, C> {
@@ -99,6 +102,23 @@ impl<C> CreateDepositBuilder<'_, C> {
         self.should_unwrap_native_token = should_unwrap;
         self
     }
+
+    /// Set whether to wrap native SOL into the WSOL account of the payer before
+    /// creating the deposit. Only applies to the initial tokens that are WSOL and
+    /// are using the default token account.
+    /// Defaults to should not wrap.
+    pub fn should_wrap_native_token(&mut self, should_wrap: bool) -> &mut Self {
+        self.should_wrap_native_token = should_wrap;
+        self
+    }
+
+    /// Set the referral code to set the referrer of the owner with before creating the deposit.
+    /// Ignored if the owner already has a referrer.
+    /// Defaults to `None`.
+    pub fn referral_code(&mut self, code: Option<ReferralCodeBytes>) -> &mut Self {
+        self.referral_code = code;
+        self
+    }
 }
 
 impl<'a, C, S> CreateDepositBuilder<'a, C>
@@ -124,6 +144,8 @@ where
             receiver: None,
             nonce: None,
             should_unwrap_native_token: true,
+            should_wrap_native_token: false,
+            referral_code: None,
         }
     }
 
@@ -228,6 +250,8 @@ where
             initial_short_token_amount,
             min_market_token,
             should_unwrap_native_token,
+            should_wrap_native_token,
+            referral_code,
             ..
         } = self;
         let nonce = nonce.unwrap_or_else(generate_nonce);
@@ -235,6 +259,7 @@ where
         let receiver = self.get_receiver();
         let deposit = client.find_deposit_address(store, &owner, &nonce);
         let market = client.find_market_address(store, market_token);
+        let user = client.find_user_address(store, &owner);
 
         let (long_token, short_token) = self.get_or_fetch_initial_tokens(&market).await?;
 
@@ -266,6 +291,30 @@ where
             ));
         }
 
+        if let Some(set_referrer) = set_referrer_if_needed(*client, store, *referral_code).await? {
+            prepare = prepare
+                .merge(client.prepare_user(store)?)
+                .merge(set_referrer);
+        }
+
+        if *should_wrap_native_token {
+            let native_mint = anchor_spl::token::spl_token::native_mint::ID;
+            let mut amount = 0u64;
+            if long_token == Some(native_mint) && self.initial_long_token_account.is_none() {
+                amount = amount
+                    .checked_add(*initial_long_token_amount)
+                    .ok_or(crate::Error::NumberOutOfRange)?;
+            }
+            if short_token == Some(native_mint) && self.initial_short_token_account.is_none() {
+                amount = amount
+                    .checked_add(*initial_short_token_amount)
+                    .ok_or(crate::Error::NumberOutOfRange)?;
+            }
+            if amount != 0 {
+                prepare = prepare.merge(client.wrap_native(amount)?);
+            }
+        }
+
         let create = client
             .store_transaction()
             .accounts(crate::utils::fix_optional_account_metas(
@@ -274,6 +323,7 @@ where
                     receiver,
                     store: *store,
                     market,
+                    user,
                     deposit,
                     market_token: *market_token,
                     initial_long_token: long_token,
@@ -346,6 +396,7 @@ pub struct CloseDepositBuilder<'a, C> {
 pub struct CloseDepositHint {
     owner: Pubkey,
     receiver: Pubkey,
+    market: Pubkey,
     market_token: Pubkey,
     market_token_account: Pubkey,
     initial_long_token: Option<Pubkey>,
@@ -361,6 +412,7 @@ impl CloseDepositHint {
         Self {
             owner: *deposit.header().owner(),
             receiver: deposit.header().receiver(),
+            market: *deposit.header().market(),
             market_token: deposit.tokens().market_token(),
             market_token_account: deposit.tokens().market_token_account(),
             initial_long_token: deposit.tokens().initial_long_token.token(),
@@ -464,6 +516,7 @@ where
                 &gmsol_store::id(),
                 client.store_program_id(),
             ))
+            .accounts(vec![AccountMeta::new(hint.market, false)])
             .anchor_args(instruction::CloseDeposit {
                 reason: self.reason.clone(),
             }))
@@ -482,6 +535,8 @@ pub struct ExecuteDepositBuilder<'a, C> {
     token_map: Option<Pubkey>,
     cancel_on_execution_error: bool,
     close: bool,
+    keeper_long_token_account: Option<Pubkey>,
+    keeper_short_token_account: Option<Pubkey>,
 }
 
 /// Hint for executing deposit.
@@ -543,6 +598,8 @@ where
             token_map: None,
             cancel_on_execution_error,
             close: true,
+            keeper_long_token_account: None,
+            keeper_short_token_account: None,
         }
     }
 
@@ -552,6 +609,19 @@ where
         self
     }
 
+    /// Set the token accounts for receiving the keeper tips in long token and short token.
+    ///
+    /// No tip is paid for the side whose account is not provided.
+    pub fn keeper_tip_accounts(
+        &mut self,
+        long_token_account: Option<Pubkey>,
+        short_token_account: Option<Pubkey>,
+    ) -> &mut Self {
+        self.keeper_long_token_account = long_token_account;
+        self.keeper_short_token_account = short_token_account;
+        self
+    }
+
     /// Set hint with the given deposit.
     pub fn hint(
         &mut self,
@@ -574,7 +644,7 @@ where
         match &self.hint {
             Some(hint) => Ok(hint.clone()),
             None => {
-                let map = self.client.authorized_token_map(&self.store).await?;
+                let map = self.client.token_map(&self.get_token_map().await?).await?;
                 let deposit = self.client.deposit(&self.deposit).await?;
                 let hint = ExecuteDepositHint::new(&deposit, &map)?;
                 self.hint = Some(hint.clone());
@@ -587,9 +657,10 @@ where
         if let Some(address) = self.token_map {
             Ok(address)
         } else {
+            let deposit = self.client.deposit(&self.deposit).await?;
             Ok(self
                 .client
-                .authorized_token_map_address(&self.store)
+                .market_token_map_address(deposit.header().market())
                 .await?
                 .ok_or(crate::Error::NotFound)?)
         }
@@ -662,6 +733,8 @@ impl<'a, C: Deref<Target = impl Signer> + Clone> MakeBundleBuilder<'a, C>
                     initial_long_token_escrow: hint.initial_long_token_escrow,
                     initial_short_token_escrow: hint.initial_short_token_escrow,
                     chainlink_program: None,
+                    keeper_long_token_account: self.keeper_long_token_account,
+                    keeper_short_token_account: self.keeper_short_token_account,
                     event_authority: client.store_event_authority(),
                     program: *client.store_program_id(),
                 },
@@ -682,6 +755,9 @@ impl<'a, C: Deref<Target = impl Signer> + Clone> MakeBundleBuilder<'a, C>
                 .hint(CloseDepositHint {
                     owner: hint.owner,
                     receiver: hint.receiver,
+                    market: self
+                        .client
+                        .find_market_address(store, &hint.market_token_mint),
                     market_token: hint.market_token_mint,
                     market_token_account: hint.market_token_escrow,
                     initial_long_token: hint.initial_long_token,
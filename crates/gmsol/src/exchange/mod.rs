@@ -19,30 +19,51 @@ pub mod position_cut;
 /// Treasury.
 pub mod treasury;
 
-use std::{future::Future, ops::Deref};
+use std::{collections::HashMap, future::Future, ops::Deref};
 
 use anchor_client::{
     anchor_lang::system_program,
-    solana_sdk::{pubkey::Pubkey, signer::Signer},
+    solana_sdk::{
+        address_lookup_table::AddressLookupTableAccount, instruction::AccountMeta, pubkey::Pubkey,
+        signer::Signer,
+    },
 };
 use auto_deleveraging::UpdateAdlBuilder;
-use gmsol_solana_utils::transaction_builder::TransactionBuilder;
+use gmsol_solana_utils::{
+    bundle_builder::BundleBuilder, compute_budget::ComputeBudget,
+    transaction_builder::TransactionBuilder,
+};
 use gmsol_store::{
-    accounts, instruction,
+    accounts,
+    events::OrderCallbackPending,
+    instruction,
+    instructions::{MarketWithPriceCapsParams, TokenPriceCapConfig, ValidationReport},
     ops::order::PositionCutKind,
     states::{
+        common::{action::Action, TokensWithFeed},
         feature::{ActionDisabledFlag, DomainDisabledFlag},
         order::OrderKind,
-        NonceBytes, UpdateOrderParams,
+        position::PositionSpec,
+        user::{ReferralCodeBytes, UserHeader},
+        AmountKey, NonceBytes, PriceProviderKind, UpdateOrderParams,
     },
 };
-use order::{CloseOrderBuilder, OrderParams};
+use order::{CloseOrderBuilder, OrderParams, INVOKE_ORDER_CALLBACK_COMPUTE_BUDGET};
 use position_cut::PositionCutBuilder;
 use rand::{distributions::Standard, Rng};
 use shift::{CloseShiftBuilder, CreateShiftBuilder, ExecuteShiftBuilder};
 use treasury::ClaimFeesBuilder;
 
-use crate::store::market::VaultOps;
+use crate::{
+    store::{market::VaultOps, user::UserOps},
+    utils::{
+        builder::{
+            MakeBundleBuilder, PostPullOraclePrices, PriceUpdateInstructions,
+            PullOraclePriceConsumer,
+        },
+        ZeroCopy,
+    },
+};
 
 use self::{
     deposit::{CloseDepositBuilder, CreateDepositBuilder, ExecuteDepositBuilder},
@@ -61,6 +82,27 @@ pub trait ExchangeOps<C> {
         enable: bool,
     ) -> TransactionBuilder<C>;
 
+    /// Grant (or update) the keeper deadline extension.
+    fn grant_keeper_deadline_extension(
+        &self,
+        store: &Pubkey,
+        extension_secs: u32,
+        duration_secs: u32,
+    ) -> TransactionBuilder<C>;
+
+    /// Revoke the keeper deadline extension.
+    fn revoke_keeper_deadline_extension(&self, store: &Pubkey) -> TransactionBuilder<C>;
+
+    /// Create or update the priority config of the given keeper.
+    fn set_keeper_priority(
+        &self,
+        store: &Pubkey,
+        keeper: &Pubkey,
+        priority: u8,
+        max_concurrent_executions: u16,
+        order_kinds: impl IntoIterator<Item = OrderKind>,
+    ) -> TransactionBuilder<C>;
+
     /// Claim fees.
     fn claim_fees(
         &self,
@@ -82,6 +124,21 @@ pub trait ExchangeOps<C> {
         token_map: Option<&Pubkey>,
     ) -> impl Future<Output = crate::Result<(TransactionBuilder<C>, Pubkey)>>;
 
+    /// Create a new market, set the price caps of the given tokens in the same instruction,
+    /// and return its token mint address.
+    #[allow(clippy::too_many_arguments)]
+    fn create_market_with_price_caps(
+        &self,
+        store: &Pubkey,
+        name: &str,
+        index_token: &Pubkey,
+        long_token: &Pubkey,
+        short_token: &Pubkey,
+        enable: bool,
+        price_caps: Vec<TokenPriceCapConfig>,
+        token_map: Option<&Pubkey>,
+    ) -> impl Future<Output = crate::Result<(TransactionBuilder<C>, Pubkey)>>;
+
     /// Fund the given market.
     fn fund_market(
         &self,
@@ -127,6 +184,15 @@ pub trait ExchangeOps<C> {
         cancel_on_execution_error: bool,
     ) -> ExecuteWithdrawalBuilder<C>;
 
+    /// Prepare multiple position accounts at once.
+    ///
+    /// Returns the addresses of the positions in the same order as `positions_spec`.
+    fn batch_prepare_positions(
+        &self,
+        store: &Pubkey,
+        positions_spec: Vec<PositionSpec>,
+    ) -> crate::Result<TransactionBuilder<C, Vec<Pubkey>>>;
+
     /// Create an order.
     fn create_order(
         &self,
@@ -157,6 +223,13 @@ pub trait ExchangeOps<C> {
     /// Close an order.
     fn close_order(&self, order: &Pubkey) -> crate::Result<CloseOrderBuilder<C>>;
 
+    /// Invoke the pending callback of an executed order with the result from the
+    /// [`OrderCallbackPending`] event.
+    fn invoke_order_callback(
+        &self,
+        event: &OrderCallbackPending,
+    ) -> impl Future<Output = crate::Result<TransactionBuilder<C>>>;
+
     /// Cancel order if the position does not exist.
     fn cancel_order_if_no_position(
         &self,
@@ -398,6 +471,24 @@ pub trait ExchangeOps<C> {
         shift: &Pubkey,
         cancel_on_execution_error: bool,
     ) -> ExecuteShiftBuilder<C>;
+
+    /// Execute an action with the prices posted by the given pull oracle.
+    ///
+    /// The returned bundle posts the prices, executes the action and then closes the price
+    /// accounts, in that order, with the given address lookup tables attached to every
+    /// transaction. The feeds to update are picked by the expected providers of the tokens in
+    /// the token map, and an error is returned if any of them is a pull-based provider that
+    /// is not supported by `oracle`. Use [`WithPullOracle`](crate::utils::builder::WithPullOracle)
+    /// to wrap `execute` with another oracle when prices from multiple providers are required.
+    fn execute_with_prices<'a, O, T>(
+        &'a self,
+        oracle: O,
+        execute: T,
+        alts: impl IntoIterator<Item = AddressLookupTableAccount>,
+    ) -> impl Future<Output = crate::Result<BundleBuilder<'a, C>>>
+    where
+        O: PostPullOraclePrices<'a, C>,
+        T: PullOraclePriceConsumer + MakeBundleBuilder<'a, C>;
 }
 
 impl<S, C> ExchangeOps<C> for crate::Client<C>
@@ -424,6 +515,58 @@ where
             })
     }
 
+    fn grant_keeper_deadline_extension(
+        &self,
+        store: &Pubkey,
+        extension_secs: u32,
+        duration_secs: u32,
+    ) -> TransactionBuilder<C> {
+        self.store_transaction()
+            .anchor_args(gmsol_store::instruction::GrantKeeperDeadlineExtension {
+                extension_secs,
+                duration_secs,
+            })
+            .anchor_accounts(gmsol_store::accounts::GrantKeeperDeadlineExtension {
+                authority: self.payer(),
+                store: *store,
+                deadline_extension: self.find_keeper_deadline_extension_address(store),
+                system_program: system_program::ID,
+            })
+    }
+
+    fn revoke_keeper_deadline_extension(&self, store: &Pubkey) -> TransactionBuilder<C> {
+        self.store_transaction()
+            .anchor_args(gmsol_store::instruction::RevokeKeeperDeadlineExtension {})
+            .anchor_accounts(gmsol_store::accounts::RevokeKeeperDeadlineExtension {
+                authority: self.payer(),
+                store: *store,
+                deadline_extension: self.find_keeper_deadline_extension_address(store),
+            })
+    }
+
+    fn set_keeper_priority(
+        &self,
+        store: &Pubkey,
+        keeper: &Pubkey,
+        priority: u8,
+        max_concurrent_executions: u16,
+        order_kinds: impl IntoIterator<Item = OrderKind>,
+    ) -> TransactionBuilder<C> {
+        self.store_transaction()
+            .anchor_args(gmsol_store::instruction::SetKeeperPriority {
+                keeper: *keeper,
+                priority,
+                max_concurrent_executions,
+                order_kinds: order_kinds.into_iter().map(u8::from).collect(),
+            })
+            .anchor_accounts(gmsol_store::accounts::SetKeeperPriority {
+                authority: self.payer(),
+                store: *store,
+                keeper_priority: self.find_keeper_priority_address(store, keeper),
+                system_program: system_program::ID,
+            })
+    }
+
     fn claim_fees(
         &self,
         store: &Pubkey,
@@ -526,6 +669,62 @@ where
         ))
     }
 
+    async fn create_market_with_price_caps(
+        &self,
+        store: &Pubkey,
+        name: &str,
+        index_token: &Pubkey,
+        long_token: &Pubkey,
+        short_token: &Pubkey,
+        enable: bool,
+        price_caps: Vec<TokenPriceCapConfig>,
+        token_map: Option<&Pubkey>,
+    ) -> crate::Result<(TransactionBuilder<C>, Pubkey)> {
+        let token_map = match token_map {
+            Some(token_map) => *token_map,
+            None => self
+                .authorized_token_map_address(store)
+                .await?
+                .ok_or(crate::Error::NotFound)?,
+        };
+        let authority = self.payer();
+        let market_token =
+            self.find_market_token_address(store, index_token, long_token, short_token);
+        let prepare_long_token_vault = self.initialize_market_vault(store, long_token).0;
+        let prepare_short_token_vault = self.initialize_market_vault(store, short_token).0;
+        let prepare_market_token_vault = self.initialize_market_vault(store, &market_token).0;
+        let builder = self
+            .store_transaction()
+            .anchor_accounts(gmsol_store::accounts::InitializeMarketWithPriceCaps {
+                authority,
+                store: *store,
+                token_map,
+                market: self.find_market_address(store, &market_token),
+                market_token_mint: market_token,
+                long_token_mint: *long_token,
+                short_token_mint: *short_token,
+                long_token_vault: self.find_market_vault_address(store, long_token),
+                short_token_vault: self.find_market_vault_address(store, short_token),
+                system_program: system_program::ID,
+                token_program: anchor_spl::token::ID,
+            })
+            .anchor_args(gmsol_store::instruction::InitializeMarketWithPriceCaps {
+                params: MarketWithPriceCapsParams {
+                    index_token_mint: *index_token,
+                    name: name.to_string(),
+                    enable,
+                    price_caps,
+                },
+            });
+        Ok((
+            prepare_long_token_vault
+                .merge(prepare_short_token_vault)
+                .merge(builder)
+                .merge(prepare_market_token_vault),
+            market_token,
+        ))
+    }
+
     async fn fund_market(
         &self,
         store: &Pubkey,
@@ -564,6 +763,53 @@ where
             }))
     }
 
+    fn batch_prepare_positions(
+        &self,
+        store: &Pubkey,
+        positions_spec: Vec<PositionSpec>,
+    ) -> crate::Result<TransactionBuilder<C, Vec<Pubkey>>> {
+        let owner = self.payer();
+        let positions = positions_spec
+            .iter()
+            .map(|spec| {
+                self.find_position_address(
+                    store,
+                    &owner,
+                    &spec.market_token,
+                    &spec.collateral_token,
+                    spec.kind(),
+                )
+            })
+            .collect::<crate::Result<Vec<_>>>()?;
+        let remaining_accounts = positions_spec
+            .iter()
+            .zip(positions.iter())
+            .flat_map(|(spec, position)| {
+                [
+                    AccountMeta::new_readonly(
+                        self.find_market_address(store, &spec.market_token),
+                        false,
+                    ),
+                    AccountMeta::new(*position, false),
+                ]
+            })
+            .collect::<Vec<_>>();
+        Ok(self
+            .store_transaction()
+            .anchor_args(instruction::BatchPreparePositions {
+                specs: positions_spec,
+            })
+            .anchor_accounts(accounts::BatchPreparePositions {
+                owner,
+                store: *store,
+                system_program: system_program::ID,
+                event_authority: self.store_event_authority(),
+                program: *self.store_program_id(),
+            })
+            .accounts(remaining_accounts)
+            .output(positions))
+    }
+
     fn create_order(
         &self,
         store: &Pubkey,
@@ -606,6 +852,36 @@ where
         Ok(CloseOrderBuilder::new(self, order))
     }
 
+    async fn invoke_order_callback(
+        &self,
+        event: &OrderCallbackPending,
+    ) -> crate::Result<TransactionBuilder<C>> {
+        let max_compute_units = u32::try_from(
+            *self
+                .store(&event.store)
+                .await?
+                .get_amount_by_key(AmountKey::MaxOrderCallbackComputeUnits),
+        )
+        .map_err(|_| crate::Error::NumberOutOfRange)?;
+
+        Ok(self
+            .store_transaction()
+            .anchor_args(instruction::InvokeOrderCallback {
+                transfer_out: event.transfer_out,
+            })
+            .anchor_accounts(accounts::InvokeOrderCallback {
+                authority: self.payer(),
+                store: event.store,
+                order: event.order,
+                callback_authority: self.find_order_callback_authority_address(&event.store),
+                callback_program: event.program,
+                callback_account: event.account,
+            })
+            .compute_budget(ComputeBudget::default().with_limit(
+                INVOKE_ORDER_CALLBACK_COMPUTE_BUDGET.saturating_add(max_compute_units),
+            )))
+    }
+
     async fn cancel_order_if_no_position(
         &self,
         store: &Pubkey,
@@ -691,6 +967,65 @@ where
     ) -> ExecuteShiftBuilder<C> {
         ExecuteShiftBuilder::new(self, oracle, shift, cancel_on_execution_error)
     }
+
+    async fn execute_with_prices<'a, O, T>(
+        &'a self,
+        oracle: O,
+        mut execute: T,
+        alts: impl IntoIterator<Item = AddressLookupTableAccount>,
+    ) -> crate::Result<BundleBuilder<'a, C>>
+    where
+        O: PostPullOraclePrices<'a, C>,
+        T: PullOraclePriceConsumer + MakeBundleBuilder<'a, C>,
+    {
+        let alts = alts
+            .into_iter()
+            .map(|alt| (alt.key, alt.addresses))
+            .collect::<HashMap<_, _>>();
+
+        let feed_ids = execute.feed_ids().await?;
+        let price_updates = oracle.fetch_price_updates(&feed_ids, None).await?;
+        let (instructions, map) = oracle
+            .fetch_price_update_instructions(&price_updates, Default::default())
+            .await?;
+
+        for provider in feed_ids.providers.iter() {
+            let provider = PriceProviderKind::try_from(*provider)
+                .map_err(|_| crate::Error::invalid_argument("unsupported price provider"))?;
+            // Chainlink Data Feeds are push-based, so no prices are required to be posted.
+            if !matches!(provider, PriceProviderKind::Chainlink) && !map.contains_key(&provider) {
+                return Err(crate::Error::invalid_argument(format!(
+                    "prices from `{provider}` are required but not provided by the oracle"
+                )));
+            }
+        }
+
+        for (provider, map) in map {
+            execute.process_feeds(provider, map)?;
+        }
+
+        let consume = execute.build().await?;
+
+        let PriceUpdateInstructions {
+            post: mut tx,
+            close,
+        } = instructions;
+        tx.append(consume, false)?;
+        tx.append(close, true)?;
+
+        if alts.is_empty() {
+            return Ok(tx);
+        }
+
+        let mut bundle = tx.try_clone_empty()?;
+        for txn in tx.into_builders() {
+            bundle
+                .try_push_with_opts(txn.lookup_tables(alts.clone()), true)
+                .map_err(|(_, err)| err)?;
+        }
+
+        Ok(bundle)
+    }
 }
 
 impl<C: Deref<Target = impl Signer> + Clone> crate::Client<C> {
@@ -704,6 +1039,196 @@ impl<C: Deref<Target = impl Signer> + Clone> crate::Client<C> {
         builder.receiver(Some(self.find_first_deposit_owner_address()));
         builder
     }
+
+    /// Build a transaction for checking whether the given action would pass the pre-execution checks.
+    pub fn validate_execution_params(
+        &self,
+        store: &Pubkey,
+        oracle: &Pubkey,
+        market: &Pubkey,
+        action: &Pubkey,
+        escrows: impl IntoIterator<Item = Pubkey>,
+        execution_fee: u64,
+    ) -> TransactionBuilder<C> {
+        self.store_transaction()
+            .anchor_args(instruction::ValidateExecutionParams { execution_fee })
+            .anchor_accounts(accounts::ValidateExecutionParams {
+                store: *store,
+                oracle: *oracle,
+                market: *market,
+                action: *action,
+            })
+            .accounts(
+                escrows
+                    .into_iter()
+                    .map(|pubkey| AccountMeta::new_readonly(pubkey, false))
+                    .collect::<Vec<_>>(),
+            )
+    }
+
+    /// Check whether the given deposit would pass the pre-execution checks.
+    pub async fn pre_validate_deposit(
+        &self,
+        store: &Pubkey,
+        oracle: &Pubkey,
+        deposit: &Pubkey,
+        execution_fee: u64,
+    ) -> crate::Result<ValidationReport> {
+        let account = self.deposit(deposit).await?;
+        let header = account.header();
+        let tokens = account.tokens();
+        let escrows = [&tokens.initial_long_token, &tokens.initial_short_token]
+            .into_iter()
+            .filter_map(|token| token.account());
+        let req = self.validate_execution_params(
+            store,
+            oracle,
+            header.market(),
+            deposit,
+            escrows,
+            execution_fee,
+        );
+        self.view_validation_report(req).await
+    }
+
+    /// Check whether the given withdrawal would pass the pre-execution checks.
+    pub async fn pre_validate_withdrawal(
+        &self,
+        store: &Pubkey,
+        oracle: &Pubkey,
+        withdrawal: &Pubkey,
+        execution_fee: u64,
+    ) -> crate::Result<ValidationReport> {
+        let account = self.withdrawal(withdrawal).await?;
+        let header = account.header();
+        let req = self.validate_execution_params(
+            store,
+            oracle,
+            header.market(),
+            withdrawal,
+            [account.tokens().market_token_account()],
+            execution_fee,
+        );
+        self.view_validation_report(req).await
+    }
+
+    /// Check whether the given order would pass the pre-execution checks.
+    pub async fn pre_validate_order(
+        &self,
+        store: &Pubkey,
+        oracle: &Pubkey,
+        order: &Pubkey,
+        execution_fee: u64,
+    ) -> crate::Result<ValidationReport> {
+        let account = self.order(order).await?;
+        let header = account.header();
+        let req = self.validate_execution_params(
+            store,
+            oracle,
+            header.market(),
+            order,
+            account.tokens().initial_collateral().account(),
+            execution_fee,
+        );
+        self.view_validation_report(req).await
+    }
+
+    /// Build a transaction for getting the tokens and feeds required for executing the given action.
+    ///
+    /// The `to_market` is required only if the action is a shift.
+    pub fn get_required_feeds(
+        &self,
+        store: &Pubkey,
+        token_map: &Pubkey,
+        market: &Pubkey,
+        to_market: Option<&Pubkey>,
+        action: &Pubkey,
+    ) -> TransactionBuilder<C> {
+        self.store_transaction()
+            .anchor_args(instruction::GetRequiredFeeds {})
+            .accounts(crate::utils::fix_optional_account_metas(
+                accounts::GetRequiredFeeds {
+                    store: *store,
+                    token_map: *token_map,
+                    market: *market,
+                    to_market: to_market.copied(),
+                    action: *action,
+                },
+                &crate::program_ids::DEFAULT_GMSOL_STORE_ID,
+                self.store_program_id(),
+            ))
+    }
+
+    /// Get the tokens and feeds required for executing the given action.
+    ///
+    /// The action must be a deposit, withdrawal, order or shift.
+    pub async fn required_feeds(
+        &self,
+        store: &Pubkey,
+        action: &Pubkey,
+    ) -> crate::Result<TokensWithFeed> {
+        use anchor_client::anchor_lang::Discriminator;
+        use gmsol_store::{
+            states::{Deposit, Order, Shift, Withdrawal},
+            utils::de::try_deserailize,
+        };
+
+        let data = self
+            .store_program()
+            .rpc()
+            .get_account_data(action)
+            .await
+            .map_err(anchor_client::ClientError::from)?;
+        let discriminator = data
+            .get(..8)
+            .ok_or(crate::Error::invalid_argument("invalid action account"))?;
+
+        let (market, to_market) = if discriminator == Deposit::DISCRIMINATOR {
+            let deposit = try_deserailize::<Deposit>(&data)?;
+            (*deposit.header().market(), None)
+        } else if discriminator == Withdrawal::DISCRIMINATOR {
+            let withdrawal = try_deserailize::<Withdrawal>(&data)?;
+            (*withdrawal.header().market(), None)
+        } else if discriminator == Order::DISCRIMINATOR {
+            let order = try_deserailize::<Order>(&data)?;
+            (*order.header().market(), None)
+        } else if discriminator == Shift::DISCRIMINATOR {
+            let shift = try_deserailize::<Shift>(&data)?;
+            let to_market = self.find_market_address(store, &shift.tokens().to_market_token());
+            (*shift.header().market(), Some(to_market))
+        } else {
+            return Err(crate::Error::invalid_argument(
+                "the action must be a deposit, withdrawal, order or shift",
+            ));
+        };
+
+        let token_map = self
+            .resolve_token_map_address(&*self.market(&market).await?)
+            .await?
+            .ok_or(crate::Error::invalid_argument(
+                "token map is not set for this market",
+            ))?;
+
+        let req = self.get_required_feeds(store, &token_map, &market, to_market.as_ref(), action);
+        let feeds = crate::utils::view::<TokensWithFeed>(
+            &self.store_program().rpc(),
+            &req.signed_transaction_with_options(true, None).await?,
+        )
+        .await?;
+        Ok(feeds)
+    }
+
+    async fn view_validation_report(
+        &self,
+        req: TransactionBuilder<'_, C>,
+    ) -> crate::Result<ValidationReport> {
+        let report = crate::utils::view::<ValidationReport>(
+            &self.store_program().rpc(),
+            &req.signed_transaction_with_options(true, None).await?,
+        )
+        .await?;
+        Ok(report)
+    }
 }
 
 pub(crate) fn generate_nonce() -> NonceBytes {
@@ -715,6 +1240,29 @@ pub(crate) fn generate_nonce() -> NonceBytes {
         .unwrap()
 }
 
+/// Returns the transaction to set the referrer of the payer with the given referral code,
+/// or `None` if no code is provided or the referrer of the payer has already been set.
+///
+/// The user account must have been prepared before the returned transaction.
+pub(crate) async fn set_referrer_if_needed<'a, C: Deref<Target = impl Signer> + Clone>(
+    client: &'a crate::Client<C>,
+    store: &Pubkey,
+    code: Option<ReferralCodeBytes>,
+) -> crate::Result<Option<TransactionBuilder<'a, C>>> {
+    let Some(code) = code else {
+        return Ok(None);
+    };
+    let user = client.find_user_address(store, &client.payer());
+    let has_referrer = client
+        .account::<ZeroCopy<UserHeader>>(&user)
+        .await?
+        .is_some_and(|user| user.0.referral().referrer().is_some());
+    if has_referrer {
+        return Ok(None);
+    }
+    Ok(Some(client.set_referrer(store, code, None).await?))
+}
+
 pub(crate) fn get_ata_or_owner(
     owner: &Pubkey,
     mint: &Pubkey,
@@ -24,9 +24,9 @@ use gmsol_store::{
     states::{
         common::{action::Action, swap::SwapActionParams, TokensWithFeed},
         order::{Order, OrderKind},
-        position::PositionKind,
-        user::UserHeader,
-        Market, MarketMeta, NonceBytes, PriceProviderKind, Pyth, Store, TokenMapAccess,
+        position::{MarginMode, Position, PositionKind},
+        user::{ReferralCodeBytes, UserHeader},
+        AmountKey, Market, MarketMeta, NonceBytes, PriceProviderKind, Pyth, Store, TokenMapAccess,
     },
 };
 
@@ -40,11 +40,14 @@ use crate::{
     },
 };
 
-use super::{generate_nonce, get_ata_or_owner, ExchangeOps};
+use super::{generate_nonce, get_ata_or_owner, set_referrer_if_needed, ExchangeOps};
 
 /// `execute_order` compute budget.
 pub const EXECUTE_ORDER_COMPUTE_BUDGET: u32 = 400_000;
 
+/// `invoke_order_callback` compute budget, excluding the compute units of the callback.
+pub const INVOKE_ORDER_CALLBACK_COMPUTE_BUDGET: u32 = 50_000;
+
 /// Order Params.
 #[derive(Debug, Clone)]
 pub struct OrderParams {
@@ -102,7 +105,14 @@ pub struct CreateOrderBuilder<'a, C> {
     long_token_account: Option<Pubkey>,
     short_token_account: Option<Pubkey>,
     should_unwrap_native_token: bool,
+    should_wrap_native_token: bool,
     receiver: Pubkey,
+    position: Option<Pubkey>,
+    assigned_keeper: Option<Pubkey>,
+    fallback_to_market_after_secs: Option<u32>,
+    callback: Option<(Pubkey, Pubkey, [u8; 32])>,
+    margin_mode: Option<MarginMode>,
+    referral_code: Option<ReferralCodeBytes>,
 }
 
 /// Create Order Hint.
@@ -139,7 +149,14 @@ where
             long_token_account: None,
             short_token_account: None,
             should_unwrap_native_token: true,
+            should_wrap_native_token: false,
             receiver: client.payer(),
+            position: None,
+            assigned_keeper: None,
+            fallback_to_market_after_secs: None,
+            callback: None,
+            margin_mode: None,
+            referral_code: None,
         }
     }
 
@@ -237,6 +254,15 @@ where
         self
     }
 
+    /// Set whether to wrap native SOL into the WSOL account of the payer before
+    /// creating the order. Only applies when the initial collateral token is WSOL
+    /// and is using the associated token account of the payer.
+    /// Defaults to should not wrap.
+    pub fn should_wrap_native_token(&mut self, should_wrap: bool) -> &mut Self {
+        self.should_wrap_native_token = should_wrap;
+        self
+    }
+
     /// Set receiver.
     /// Defaults to the payer.
     pub fn receiver(&mut self, receiver: Pubkey) -> &mut Self {
@@ -244,6 +270,55 @@ where
         self
     }
 
+    /// Assign the order to the given keeper.
+    /// Defaults to not assigned.
+    pub fn assigned_keeper(&mut self, keeper: Pubkey) -> &mut Self {
+        self.assigned_keeper = Some(keeper);
+        self
+    }
+
+    /// Invoke the given callback program with the given account and data
+    /// after the order is executed.
+    /// Defaults to no callback.
+    pub fn callback(&mut self, program: Pubkey, account: Pubkey, data: [u8; 32]) -> &mut Self {
+        self.callback = Some((program, account, data));
+        self
+    }
+
+    /// Allow the order to be executed as a market order if it has not been
+    /// triggered after the given number of seconds (limit increase / limit decrease order only).
+    /// Defaults to not allowed.
+    pub fn fallback_to_market_after_secs(&mut self, secs: u32) -> &mut Self {
+        self.fallback_to_market_after_secs = Some(secs);
+        self
+    }
+
+    /// Set the margin mode of the position (position order only).
+    ///
+    /// The margin mode of an existing position can only be changed while it is empty.
+    /// Defaults to the margin mode of the position, or isolated for a new position.
+    pub fn margin_mode(&mut self, mode: MarginMode) -> &mut Self {
+        self.margin_mode = Some(mode);
+        self
+    }
+
+    /// Set the referral code to set the referrer of the owner with before creating the order.
+    /// Ignored if the owner already has a referrer.
+    /// Defaults to `None`.
+    pub fn referral_code(&mut self, code: Option<ReferralCodeBytes>) -> &mut Self {
+        self.referral_code = code;
+        self
+    }
+
+    /// Use the given position instead of deriving it from the owner and the market
+    /// (position order only).
+    ///
+    /// The position will be validated to belong to the owner and the market when building.
+    pub fn position(&mut self, address: &Pubkey) -> &mut Self {
+        self.position = Some(*address);
+        self
+    }
+
     fn market(&self) -> Pubkey {
         self.client
             .find_market_address(&self.store, &self.market_token)
@@ -269,7 +344,7 @@ where
         Ok(output_token)
     }
 
-    async fn position(&mut self) -> crate::Result<Option<Pubkey>> {
+    async fn get_position(&mut self) -> crate::Result<Option<Pubkey>> {
         let output_token = self.output_token().await?;
         match &self.params.kind {
             OrderKind::MarketIncrease
@@ -278,14 +353,40 @@ where
             | OrderKind::LimitIncrease
             | OrderKind::LimitDecrease
             | OrderKind::StopLossDecrease => {
-                let position = self.client.find_position_address(
+                let owner = self.client.payer();
+                let kind = self.params.to_position_kind()?;
+                let derived = self.client.find_position_address(
                     &self.store,
-                    &self.client.payer(),
+                    &owner,
                     &self.market_token,
                     &output_token,
-                    self.params.to_position_kind()?,
+                    kind,
                 )?;
-                Ok(Some(position))
+                let Some(address) = self.position else {
+                    return Ok(Some(derived));
+                };
+                match self.client.account::<ZeroCopy<Position>>(&address).await? {
+                    Some(ZeroCopy(position)) => {
+                        let is_valid = position.store == self.store
+                            && position.owner == owner
+                            && position.market_token == self.market_token
+                            && position.collateral_token == output_token
+                            && position.kind()? == kind;
+                        if !is_valid {
+                            return Err(crate::Error::invalid_argument(format!(
+                                "position `{address}` does not belong to the owner or the market"
+                            )));
+                        }
+                    }
+                    // The position will be created by the order, so it must be the derived one.
+                    None if address != derived => {
+                        return Err(crate::Error::invalid_argument(format!(
+                            "position `{address}` does not exist and is not the derived position"
+                        )));
+                    }
+                    None => {}
+                }
+                Ok(Some(address))
             }
             OrderKind::MarketSwap | OrderKind::LimitSwap => Ok(None),
             kind => Err(crate::Error::invalid_argument(format!(
@@ -396,7 +497,7 @@ where
             } else {
                 None
             };
-        let position = self.position().await?;
+        let position = self.get_position().await?;
         let user = self.client.find_user_address(&self.store, owner);
 
         let kind = self.params.kind;
@@ -418,6 +519,12 @@ where
             acceptable_price: self.params.acceptable_price,
             should_unwrap_native_token: self.should_unwrap_native_token,
             valid_from_ts: self.params.valid_from_ts,
+            assigned_keeper: self.assigned_keeper,
+            fallback_to_market_after_secs: self.fallback_to_market_after_secs,
+            callback_program: self.callback.map(|(program, _, _)| program),
+            callback_account: self.callback.map(|(_, account, _)| account),
+            callback_data: self.callback.map(|(_, _, data)| data),
+            margin_mode: self.margin_mode,
         };
 
         let prepare = match kind {
@@ -552,6 +659,21 @@ where
             }
         };
 
+        let prepare = match (initial_collateral_token, initial_collateral_token_account) {
+            (Some(token), Some(account))
+                if self.should_wrap_native_token
+                    && token == anchor_spl::token::spl_token::native_mint::ID
+                    && account == get_associated_token_address(owner, &token)
+                    && self.params.initial_collateral_delta_amount != 0 =>
+            {
+                prepare.merge(
+                    self.client
+                        .wrap_native(self.params.initial_collateral_delta_amount)?,
+                )
+            }
+            _ => prepare,
+        };
+
         let prepare_user = self
             .client
             .store_transaction()
@@ -562,6 +684,11 @@ where
                 system_program: system_program::ID,
             })
             .anchor_args(instruction::PrepareUser {});
+        let prepare_user =
+            match set_referrer_if_needed(self.client, &self.store, self.referral_code).await? {
+                Some(set_referrer) => prepare_user.merge(set_referrer),
+                None => prepare_user,
+            };
 
         let create = self
             .client
@@ -618,12 +745,16 @@ pub struct ExecuteOrderBuilder<'a, C> {
     price_provider: Pubkey,
     feeds_parser: FeedsParser,
     recent_timestamp: i64,
+    use_deadline_extension: bool,
     hint: Option<ExecuteOrderHint>,
     token_map: Option<Pubkey>,
     cancel_on_execution_error: bool,
     close: bool,
     event_buffer_index: u16,
     alts: HashMap<Pubkey, Vec<Pubkey>>,
+    market_analytics: Vec<Pubkey>,
+    keeper_long_token_account: Option<Pubkey>,
+    keeper_short_token_account: Option<Pubkey>,
 }
 
 /// Hint for executing order.
@@ -650,6 +781,8 @@ pub struct ExecuteOrderHint {
     pub feeds: TokensWithFeed,
     swap: SwapActionParams,
     should_unwrap_native_token: bool,
+    virtual_pool: Option<Pubkey>,
+    has_callback: bool,
 }
 
 impl ExecuteOrderHint {
@@ -730,12 +863,16 @@ where
             price_provider: Pyth::id(),
             feeds_parser: Default::default(),
             recent_timestamp: recent_timestamp()?,
+            use_deadline_extension: false,
             hint: None,
             token_map: None,
             cancel_on_execution_error,
             close: true,
             event_buffer_index: 0,
             alts: Default::default(),
+            market_analytics: Default::default(),
+            keeper_long_token_account: None,
+            keeper_short_token_account: None,
         })
     }
 
@@ -746,17 +883,39 @@ where
     }
 
     /// Set whether to close order after execution.
+    ///
+    /// An order whose callback becomes pending after execution is never closed here, so that
+    /// the callback can be invoked with [`ExchangeOps::invoke_order_callback`] before closing it.
     pub fn close(&mut self, close: bool) -> &mut Self {
         self.close = close;
         self
     }
 
+    /// Set the token accounts for receiving the keeper tips in long token and short token.
+    ///
+    /// No tip is paid for the side whose account is not provided.
+    pub fn keeper_tip_accounts(
+        &mut self,
+        long_token_account: Option<Pubkey>,
+        short_token_account: Option<Pubkey>,
+    ) -> &mut Self {
+        self.keeper_long_token_account = long_token_account;
+        self.keeper_short_token_account = short_token_account;
+        self
+    }
+
     /// Set event buffer index.
     pub fn event_buffer_index(&mut self, index: u16) -> &mut Self {
         self.event_buffer_index = index;
         self
     }
 
+    /// Add a market analytics account of the market to record the trade in.
+    pub fn add_market_analytics(&mut self, address: Pubkey) -> &mut Self {
+        self.market_analytics.push(address);
+        self
+    }
+
     /// Insert an Address Lookup Table.
     pub fn add_alt(&mut self, account: AddressLookupTableAccount) -> &mut Self {
         self.alts.insert(account.key, account.addresses);
@@ -806,6 +965,10 @@ where
             long_token_and_account: tokens.long_token().token_and_account(),
             short_token_and_account: tokens.short_token().token_and_account(),
             should_unwrap_native_token: order.header().should_unwrap_native_token(),
+            virtual_pool: market
+                .enabled_virtual_pool_key()
+                .map(|key| self.client.find_virtual_pool_address(&self.store, key)),
+            has_callback: order.callback().is_some(),
         });
         Ok(self)
     }
@@ -842,6 +1005,15 @@ where
         self
     }
 
+    /// Set whether to use the keeper deadline extension of the store.
+    ///
+    /// The extension account must exist when enabled. Only take effect for decrease orders.
+    /// Default to `false`.
+    pub fn use_deadline_extension(&mut self, enable: bool) -> &mut Self {
+        self.use_deadline_extension = enable;
+        self
+    }
+
     /// Get claimable accounts.
     ///
     /// The returned values are of the form `[long_for_user, short_for_user, pnl_for_holding]`.
@@ -860,12 +1032,13 @@ where
         if let Some(address) = self.token_map {
             Ok(address)
         } else {
+            let order = self.client.order(&self.order).await?;
             let address = self
                 .client
-                .authorized_token_map_address(&self.store)
+                .market_token_map_address(order.header().market())
                 .await?
                 .ok_or(crate::Error::invalid_argument(
-                    "token map is not set for this store",
+                    "token map is not set for this market",
                 ))?;
             self.token_map = Some(address);
             Ok(address)
@@ -904,6 +1077,14 @@ impl<'a, C: Deref<Target = impl Signer> + Clone> MakeBundleBuilder<'a, C>
                 is_signer: false,
                 is_writable: true,
             });
+        let cross_margin_positions = match hint.position.as_ref() {
+            Some(position) => {
+                self.client
+                    .cross_margin_positions(position, &hint.long_token_mint, &hint.short_token_mint)
+                    .await?
+            }
+            None => vec![],
+        };
         let event = self.client.find_trade_event_buffer_address(
             &self.store,
             &authority,
@@ -953,6 +1134,11 @@ impl<'a, C: Deref<Target = impl Signer> + Clone> MakeBundleBuilder<'a, C>
                             claimable_long_token_account_for_user,
                             claimable_short_token_account_for_user,
                             claimable_pnl_token_account_for_holding,
+                            deadline_extension: self.use_deadline_extension.then(|| {
+                                self.client
+                                    .find_keeper_deadline_extension_address(&self.store)
+                            }),
+                            virtual_pool: hint.virtual_pool,
                             event_authority: self.client.store_event_authority(),
                             token_program: anchor_spl::token::ID,
                             system_program: system_program::ID,
@@ -986,6 +1172,8 @@ impl<'a, C: Deref<Target = impl Signer> + Clone> MakeBundleBuilder<'a, C>
                                 .ok_or(crate::Error::invalid_argument("missing short token"))?,
                             program: *self.client.store_program_id(),
                             chainlink_program: None,
+                            keeper_long_token_account: self.keeper_long_token_account,
+                            keeper_short_token_account: self.keeper_short_token_account,
                         },
                         &crate::program_ids::DEFAULT_GMSOL_STORE_ID,
                         self.client.store_program_id(),
@@ -1046,6 +1234,9 @@ impl<'a, C: Deref<Target = impl Signer> + Clone> MakeBundleBuilder<'a, C>
                             .map(|(_, account)| account),
                         program: *self.client.store_program_id(),
                         chainlink_program: None,
+                        virtual_pool: hint.virtual_pool,
+                        keeper_long_token_account: self.keeper_long_token_account,
+                        keeper_short_token_account: self.keeper_short_token_account,
                     },
                     &crate::program_ids::DEFAULT_GMSOL_STORE_ID,
                     self.client.store_program_id(),
@@ -1057,8 +1248,30 @@ impl<'a, C: Deref<Target = impl Signer> + Clone> MakeBundleBuilder<'a, C>
                 }),
         };
 
+        // The order must be kept for invoking the pending callback.
+        let has_pending_callback = hint.has_callback
+            && *hint
+                .store
+                .get_amount_by_key(AmountKey::MaxOrderCallbackComputeUnits)
+                != 0;
+
         execute_order = execute_order
-            .accounts(feeds.into_iter().chain(swap_markets).collect::<Vec<_>>())
+            .accounts(
+                feeds
+                    .into_iter()
+                    .chain(swap_markets)
+                    .chain(
+                        cross_margin_positions
+                            .into_iter()
+                            .map(|address| AccountMeta::new_readonly(address, false)),
+                    )
+                    .chain(
+                        self.market_analytics
+                            .iter()
+                            .map(|address| AccountMeta::new(*address, false)),
+                    )
+                    .collect::<Vec<_>>(),
+            )
             .compute_budget(ComputeBudget::default().with_limit(EXECUTE_ORDER_COMPUTE_BUDGET))
             .lookup_tables(self.alts.clone());
 
@@ -1078,7 +1291,7 @@ impl<'a, C: Deref<Target = impl Signer> + Clone> MakeBundleBuilder<'a, C>
             execute_order = prepare_event_buffer.merge(execute_order);
         }
 
-        if self.close {
+        if self.close && !has_pending_callback {
             let close = self
                 .client
                 .close_order(&self.order)?
@@ -1087,6 +1300,9 @@ impl<'a, C: Deref<Target = impl Signer> + Clone> MakeBundleBuilder<'a, C>
                     owner: hint.owner,
                     receiver: hint.receiver,
                     store: self.store,
+                    market: self
+                        .client
+                        .find_market_address(&self.store, &hint.market_token),
                     initial_collateral_token_and_account: hint.initial_collateral_token_and_account,
                     final_output_token_and_account: hint.final_output_token_and_account,
                     long_token_and_account: hint.long_token_and_account,
@@ -1174,6 +1390,7 @@ pub struct CloseOrderHint {
     pub(super) owner: Pubkey,
     pub(super) receiver: Pubkey,
     pub(super) store: Pubkey,
+    pub(super) market: Pubkey,
     pub(super) initial_collateral_token_and_account: Option<(Pubkey, Pubkey)>,
     pub(super) final_output_token_and_account: Option<(Pubkey, Pubkey)>,
     pub(super) long_token_and_account: Option<(Pubkey, Pubkey)>,
@@ -1201,6 +1418,7 @@ impl CloseOrderHint {
             owner: *owner,
             receiver: order.header().receiver(),
             store: *store,
+            market: *order.header().market(),
             user: user_address,
             referrer,
             initial_collateral_token_and_account: tokens.initial_collateral().token_and_account(),
@@ -1336,6 +1554,7 @@ where
                 &gmsol_store::ID,
                 self.client.store_program_id(),
             ))
+            .accounts(vec![AccountMeta::new(hint.market, false)])
             .anchor_args(instruction::CloseOrder {
                 reason: self.reason.clone(),
             }))
@@ -2,7 +2,10 @@ use std::{collections::HashMap, ops::Deref, sync::Arc};
 
 use anchor_client::{
     anchor_lang::{system_program, Id},
-    solana_sdk::{address_lookup_table::AddressLookupTableAccount, pubkey::Pubkey, signer::Signer},
+    solana_sdk::{
+        address_lookup_table::AddressLookupTableAccount, instruction::AccountMeta, pubkey::Pubkey,
+        signer::Signer,
+    },
 };
 use anchor_spl::associated_token::get_associated_token_address;
 use gmsol_solana_utils::{
@@ -43,6 +46,7 @@ pub struct PositionCutBuilder<'a, C> {
     kind: PositionCutKind,
     nonce: Option<NonceBytes>,
     recent_timestamp: i64,
+    use_deadline_extension: bool,
     execution_fee: u64,
     oracle: Pubkey,
     position: Pubkey,
@@ -69,6 +73,8 @@ pub struct PositionCutHint {
     token_map: Pubkey,
     market: Pubkey,
     position_size: u128,
+    virtual_pool: Option<Pubkey>,
+    cross_margin_positions: Vec<Pubkey>,
 }
 
 impl PositionCutHint {
@@ -79,15 +85,19 @@ impl PositionCutHint {
     ) -> crate::Result<Self> {
         let store_address = position.store;
         let store = client.store(&store_address).await?;
+        let market = client.find_market_address(&store_address, &position.market_token);
+        let market_account = client.market(&market).await?;
         let token_map_address = client
-            .authorized_token_map_address(&store_address)
+            .resolve_token_map_address(&market_account)
             .await?
             .ok_or(crate::Error::invalid_argument(
-                "token map is not configurated for the store",
+                "token map is not configurated for the market",
             ))?;
         let token_map = client.token_map(&token_map_address).await?;
-        let market = client.find_market_address(&store_address, &position.market_token);
-        let meta = *client.market(&market).await?.meta();
+        let meta = *market_account.meta();
+        let virtual_pool = market_account
+            .enabled_virtual_pool_key()
+            .map(|key| client.find_virtual_pool_address(&store_address, key));
         let user = client.find_user_address(&store_address, &position.owner);
         let user = client
             .account::<ZeroCopy<UserHeader>>(&user)
@@ -97,10 +107,12 @@ impl PositionCutHint {
         Self::try_new(
             position,
             store,
+            &token_map_address,
             &token_map,
             market,
             meta,
             user.as_ref(),
+            virtual_pool.as_ref(),
             client.store_program_id(),
         )
     }
@@ -109,10 +121,12 @@ impl PositionCutHint {
     pub fn try_new(
         position: &Position,
         store: Arc<Store>,
+        token_map_address: &Pubkey,
         token_map: &TokenMap,
         market: Pubkey,
         market_meta: MarketMeta,
         user: Option<&UserHeader>,
+        virtual_pool: Option<&Pubkey>,
         program_id: &Pubkey,
     ) -> crate::Result<Self> {
         use gmsol_store::states::common::token_with_feeds::token_records;
@@ -130,15 +144,22 @@ impl PositionCutHint {
         let user_address =
             crate::pda::find_user_pda(&position.store, &position.owner, program_id).0;
         let referrer = user.and_then(|user| user.referral().referrer().copied());
+        let cross_margin_positions = if position.is_cross_margin()? {
+            position.find_cross_margin_sibling_addresses(
+                &market_meta.long_token_mint,
+                &market_meta.short_token_mint,
+                program_id,
+            )?
+        } else {
+            vec![]
+        };
 
         Ok(Self {
             store_address: position.store,
             owner: position.owner,
             user: user_address,
             referrer,
-            token_map: *store.token_map().ok_or(crate::Error::invalid_argument(
-                "missing token map for the store",
-            ))?,
+            token_map: *token_map_address,
             market,
             store,
             tokens_with_feed,
@@ -146,6 +167,8 @@ impl PositionCutHint {
             pnl_token: market_meta.pnl_token(position.try_is_long()?),
             meta: market_meta,
             position_size: position.state.size_in_usd,
+            virtual_pool: virtual_pool.copied(),
+            cross_margin_positions,
         })
     }
 
@@ -168,6 +191,7 @@ impl<'a, C: Deref<Target = impl Signer> + Clone> PositionCutBuilder<'a, C> {
             oracle: *oracle,
             nonce: None,
             recent_timestamp: recent_timestamp()?,
+            use_deadline_extension: false,
             execution_fee: 0,
             position: *position,
             price_provider: Pyth::id(),
@@ -204,6 +228,14 @@ impl<'a, C: Deref<Target = impl Signer> + Clone> PositionCutBuilder<'a, C> {
         self
     }
 
+    /// Set whether to use the keeper deadline extension of the store.
+    ///
+    /// The extension account must exist when enabled. Default to `false`.
+    pub fn use_deadline_extension(&mut self, enable: bool) -> &mut Self {
+        self.use_deadline_extension = enable;
+        self
+    }
+
     /// Set hint with the given position for position cut.
     pub fn hint(&mut self, hint: PositionCutHint) -> &mut Self {
         self.hint = Some(hint);
@@ -332,11 +364,21 @@ impl<'a, C: Deref<Target = impl Signer> + Clone> MakeBundleBuilder<'a, C>
                     event_authority: self.client.store_event_authority(),
                     program: *self.client.store_program_id(),
                     chainlink_program: None,
+                    deadline_extension: self
+                        .use_deadline_extension
+                        .then(|| self.client.find_keeper_deadline_extension_address(&store)),
+                    virtual_pool: hint.virtual_pool,
                 },
                 &crate::program_ids::DEFAULT_GMSOL_STORE_ID,
                 self.client.store_program_id(),
             ))
             .accounts(feeds)
+            .accounts(
+                hint.cross_margin_positions
+                    .iter()
+                    .map(|address| AccountMeta::new_readonly(*address, false))
+                    .collect(),
+            )
             .compute_budget(ComputeBudget::default().with_limit(POSITION_CUT_COMPUTE_BUDGET))
             .lookup_tables(self.alts.clone());
 
@@ -371,6 +413,7 @@ impl<'a, C: Deref<Target = impl Signer> + Clone> MakeBundleBuilder<'a, C>
                     owner,
                     receiver: owner,
                     store,
+                    market: hint.market,
                     initial_collateral_token_and_account: None,
                     final_output_token_and_account: Some((
                         hint.collateral_token,
@@ -15,7 +15,7 @@ use gmsol_store::{
     ops::shift::CreateShiftParams,
     states::{
         common::{action::Action, TokensWithFeed},
-        HasMarketMeta, NonceBytes, PriceProviderKind, Shift, Store, TokenMapAccess,
+        HasMarketMeta, NonceBytes, PriceProviderKind, Shift, TokenMapAccess,
     },
 };
 
@@ -169,6 +169,7 @@ impl<'a, C: Deref<Target = impl Signer> + Clone> CreateShiftBuilder<'a, C> {
                 store: self.store,
                 from_market,
                 to_market,
+                user: self.client.find_user_address(&self.store, &owner),
                 shift,
                 from_market_token: self.from_market_token,
                 to_market_token: self.to_market_token,
@@ -340,7 +341,7 @@ impl ExecuteShiftHint {
     /// Create hint for `execute_shift` instruction.
     pub fn new(
         shift: &Shift,
-        store: &Store,
+        token_map_address: &Pubkey,
         map: &impl TokenMapAccess,
         from_market: &impl HasMarketMeta,
         to_market: &impl HasMarketMeta,
@@ -361,7 +362,7 @@ impl ExecuteShiftHint {
             from_market_token_escrow: token_infos.from_market_token_account(),
             to_market_token: token_infos.to_market_token(),
             to_market_token_escrow: token_infos.to_market_token_account(),
-            token_map: *store.token_map().ok_or(crate::Error::NotFound)?,
+            token_map: *token_map_address,
             feeds,
         })
     }
@@ -413,11 +414,6 @@ impl<'a, C: Deref<Target = impl Signer> + Clone> ExecuteShiftBuilder<'a, C> {
                     .account::<ZeroCopy<Shift>>(&self.shift)
                     .await?
                     .ok_or(crate::Error::NotFound)?;
-                let store = self.client.store(shift.0.header().store()).await?;
-                let token_map_address = store
-                    .token_map()
-                    .ok_or(crate::Error::invalid_argument("token map is not set"))?;
-                let token_map = self.client.token_map(token_map_address).await?;
                 let from_market_token = shift.0.tokens().from_market_token();
                 let to_market_token = shift.0.tokens().to_market_token();
                 let from_market = self
@@ -428,9 +424,15 @@ impl<'a, C: Deref<Target = impl Signer> + Clone> ExecuteShiftBuilder<'a, C> {
                     .client
                     .find_market_address(shift.0.header().store(), &to_market_token);
                 let to_market = self.client.market(&to_market).await?;
+                let token_map_address = self
+                    .client
+                    .resolve_token_map_address(&from_market)
+                    .await?
+                    .ok_or(crate::Error::invalid_argument("token map is not set"))?;
+                let token_map = self.client.token_map(&token_map_address).await?;
                 let hint = ExecuteShiftHint::new(
                     &shift.0,
-                    &store,
+                    &token_map_address,
                     &token_map,
                     &*from_market,
                     &*to_market,
@@ -16,6 +16,7 @@ pub struct ClaimFeesBuilder<'a, C> {
     market_token: Pubkey,
     is_long_token: bool,
     hint_token: Option<Pubkey>,
+    split: bool,
 }
 
 impl<'a, C: Deref<Target = impl Signer> + Clone> ClaimFeesBuilder<'a, C> {
@@ -32,6 +33,7 @@ impl<'a, C: Deref<Target = impl Signer> + Clone> ClaimFeesBuilder<'a, C> {
             market_token: *market_token,
             is_long_token,
             hint_token: None,
+            split: false,
         }
     }
 
@@ -41,6 +43,15 @@ impl<'a, C: Deref<Target = impl Signer> + Clone> ClaimFeesBuilder<'a, C> {
         self
     }
 
+    /// Set whether to split the claimed fees between the treasury receiver
+    /// and the GT buyback address according to the buyback fee factor.
+    ///
+    /// Defaults to `false`.
+    pub fn set_split(&mut self, split: bool) -> &mut Self {
+        self.split = split;
+        self
+    }
+
     /// Build.
     pub async fn build(&self) -> crate::Result<TransactionBuilder<'a, C>> {
         let market = self
@@ -58,6 +69,45 @@ impl<'a, C: Deref<Target = impl Signer> + Clone> ClaimFeesBuilder<'a, C> {
         let vault = self.client.find_market_vault_address(&self.store, &token);
         // FIXME: read program id from the market.
         let token_program = anchor_spl::token::ID;
+
+        if self.split {
+            let store = self.client.store(&self.store).await?;
+            let receiver = store.receiver();
+            let buyback = *store.buyback();
+            let treasury_target =
+                get_associated_token_address_with_program_id(&receiver, &token, &token_program);
+            let buyback_target =
+                get_associated_token_address_with_program_id(&buyback, &token, &token_program);
+
+            let prepare = self
+                .client
+                .prepare_associated_token_account(&token, &token_program, Some(&receiver))
+                .merge(self.client.prepare_associated_token_account(
+                    &token,
+                    &token_program,
+                    Some(&buyback),
+                ));
+
+            let rpc = self
+                .client
+                .store_transaction()
+                .anchor_accounts(accounts::ClaimFeesSplit {
+                    authority,
+                    store: self.store,
+                    market,
+                    token_mint: token,
+                    vault,
+                    treasury_target,
+                    buyback_target,
+                    token_program,
+                    event_authority: self.client.store_event_authority(),
+                    program: *self.client.store_program_id(),
+                })
+                .anchor_args(instruction::ClaimFeesSplit {});
+
+            return Ok(prepare.merge(rpc));
+        }
+
         let target =
             get_associated_token_address_with_program_id(&authority, &token, &token_program);
 
@@ -268,6 +268,7 @@ where
                 system_program: system_program::ID,
                 associated_token_program: anchor_spl::associated_token::ID,
                 market,
+                user: self.client.find_user_address(&self.store, &owner),
                 withdrawal,
                 owner,
                 receiver,
@@ -334,6 +335,7 @@ pub struct CloseWithdrawalBuilder<'a, C> {
 pub struct CloseWithdrawalHint {
     owner: Pubkey,
     receiver: Pubkey,
+    market: Pubkey,
     market_token: Pubkey,
     final_long_token: Pubkey,
     final_short_token: Pubkey,
@@ -349,6 +351,7 @@ impl<'a> From<&'a Withdrawal> for CloseWithdrawalHint {
         Self {
             owner: *withdrawal.header().owner(),
             receiver: withdrawal.header().receiver(),
+            market: *withdrawal.header().market(),
             market_token: tokens.market_token(),
             final_long_token: tokens.final_long_token(),
             final_short_token: tokens.final_short_token(),
@@ -441,6 +444,7 @@ where
                 associated_token_program: anchor_spl::associated_token::ID,
                 program: *self.client.store_program_id(),
             })
+            .accounts(vec![AccountMeta::new(hint.market, false)])
             .anchor_args(instruction::CloseWithdrawal {
                 reason: self.reason.clone(),
             }))
@@ -460,6 +464,8 @@ pub struct ExecuteWithdrawalBuilder<'a, C> {
     token_map: Option<Pubkey>,
     cancel_on_execution_error: bool,
     close: bool,
+    keeper_long_token_account: Option<Pubkey>,
+    keeper_short_token_account: Option<Pubkey>,
 }
 
 /// Hint for withdrawal execution.
@@ -524,6 +530,8 @@ where
             token_map: None,
             cancel_on_execution_error,
             close: true,
+            keeper_long_token_account: None,
+            keeper_short_token_account: None,
         }
     }
 
@@ -539,6 +547,19 @@ where
         self
     }
 
+    /// Set the token accounts for receiving the keeper tips in long token and short token.
+    ///
+    /// No tip is paid for the side whose account is not provided.
+    pub fn keeper_tip_accounts(
+        &mut self,
+        long_token_account: Option<Pubkey>,
+        short_token_account: Option<Pubkey>,
+    ) -> &mut Self {
+        self.keeper_long_token_account = long_token_account;
+        self.keeper_short_token_account = short_token_account;
+        self
+    }
+
     /// Set hint with the given withdrawal.
     pub fn hint(
         &mut self,
@@ -561,7 +582,7 @@ where
         match &self.hint {
             Some(hint) => Ok(hint.clone()),
             None => {
-                let map = self.client.authorized_token_map(&self.store).await?;
+                let map = self.client.token_map(&self.get_token_map().await?).await?;
                 let withdrawal: ZeroCopy<Withdrawal> = self
                     .client
                     .account(&self.withdrawal)
@@ -578,9 +599,10 @@ where
         if let Some(address) = self.token_map {
             Ok(address)
         } else {
+            let withdrawal = self.client.withdrawal(&self.withdrawal).await?;
             Ok(self
                 .client
-                .authorized_token_map_address(&self.store)
+                .market_token_map_address(withdrawal.header().market())
                 .await?
                 .ok_or(crate::Error::NotFound)?)
         }
@@ -645,6 +667,8 @@ impl<'a, C: Deref<Target = impl Signer> + Clone> MakeBundleBuilder<'a, C>
                         .client
                         .find_market_vault_address(&self.store, &hint.market_token),
                     chainlink_program: None,
+                    keeper_long_token_account: self.keeper_long_token_account,
+                    keeper_short_token_account: self.keeper_short_token_account,
                     event_authority: self.client.store_event_authority(),
                     program: *self.client.store_program_id(),
                 },
@@ -669,6 +693,9 @@ impl<'a, C: Deref<Target = impl Signer> + Clone> MakeBundleBuilder<'a, C>
                 .hint(CloseWithdrawalHint {
                     owner: hint.owner,
                     receiver: hint.receiver,
+                    market: self
+                        .client
+                        .find_market_address(&self.store, &hint.market_token),
                     market_token: hint.market_token,
                     final_long_token: hint.final_long_token,
                     final_short_token: hint.final_short_token,
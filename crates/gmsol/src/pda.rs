@@ -4,10 +4,14 @@ use gmsol_store::{
     states::{
         glv::GlvWithdrawal,
         gt::{GtExchange, GtExchangeVault},
+        keeper::{KeeperDeadlineExtension, KeeperPriority},
+        liquidity_mining::{LiquidityMiningCampaign, LpBalanceCheckpoint},
         position::PositionKind,
-        user::{ReferralCodeBytes, ReferralCodeV2, UserHeader},
-        Deposit, GlvDeposit, NonceBytes, Order, Position, PriceFeed, PriceProviderKind, Seed,
-        Shift, Store, Withdrawal, MAX_ROLE_NAME_LEN,
+        user::{ReferralCodeBytes, ReferralCodeV2, ReferralRewardVault, UserHeader},
+        Deposit, FeeStructureProposal, FeeStructureVote, GlobalConfigSnapshot, GlvDeposit,
+        LastOraclePrice, MarketAnalytics, MarketOiCaps, NonceBytes, Order, Position,
+        PriceAdjustmentSchedule, PriceFeed, PriceProviderKind, Seed, Shift, Store, VirtualPool,
+        Withdrawal, MAX_ROLE_NAME_LEN,
     },
     utils::fixed_str::fixed_str_to_bytes,
 };
@@ -215,6 +219,58 @@ pub fn find_referral_code_pda(
     )
 }
 
+/// Find the PDA for the referral reward vault.
+pub fn find_referral_reward_vault_pda(store: &Pubkey, store_program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[ReferralRewardVault::SEED, store.as_ref()],
+        store_program_id,
+    )
+}
+
+/// Find the PDA for a liquidity mining campaign.
+pub fn find_liquidity_mining_campaign_pda(
+    store: &Pubkey,
+    campaign_index: u8,
+    store_program_id: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            LiquidityMiningCampaign::SEED,
+            store.as_ref(),
+            &[campaign_index],
+        ],
+        store_program_id,
+    )
+}
+
+/// Find the PDA for a liquidity mining vault.
+pub fn find_liquidity_mining_vault_pda(
+    campaign: &Pubkey,
+    mint: &Pubkey,
+    store_program_id: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            gmsol_store::constants::LIQUIDITY_MINING_VAULT_SEED,
+            campaign.as_ref(),
+            mint.as_ref(),
+        ],
+        store_program_id,
+    )
+}
+
+/// Find the PDA for an LP balance checkpoint.
+pub fn find_lp_balance_checkpoint_pda(
+    campaign: &Pubkey,
+    owner: &Pubkey,
+    store_program_id: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[LpBalanceCheckpoint::SEED, campaign.as_ref(), owner.as_ref()],
+        store_program_id,
+    )
+}
+
 /// Find the PDA for a GLV deposit account.
 pub fn find_glv_deposit_pda(
     store: &Pubkey,
@@ -271,6 +327,158 @@ pub fn find_gt_exchange_pda(
     )
 }
 
+/// Find the PDA for keeper deadline extension account.
+pub fn find_keeper_deadline_extension_pda(
+    store: &Pubkey,
+    store_program_id: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[KeeperDeadlineExtension::SEED, store.as_ref()],
+        store_program_id,
+    )
+}
+
+/// Find the PDA for the order callback authority.
+pub fn find_order_callback_authority_pda(
+    store: &Pubkey,
+    store_program_id: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            gmsol_store::constants::ORDER_CALLBACK_AUTHORITY_SEED,
+            store.as_ref(),
+        ],
+        store_program_id,
+    )
+}
+
+/// Find the PDA for keeper priority account.
+pub fn find_keeper_priority_pda(
+    store: &Pubkey,
+    keeper: &Pubkey,
+    store_program_id: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[KeeperPriority::SEED, store.as_ref(), keeper.as_ref()],
+        store_program_id,
+    )
+}
+
+/// Find the PDA for the config snapshot account of the given snapshot index.
+pub fn find_config_snapshot_pda(
+    store: &Pubkey,
+    index: u64,
+    store_program_id: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            GlobalConfigSnapshot::SEED,
+            store.as_ref(),
+            &GlobalConfigSnapshot::slot_seed(index),
+        ],
+        store_program_id,
+    )
+}
+
+/// Find the PDA for virtual pool account.
+pub fn find_virtual_pool_pda(
+    store: &Pubkey,
+    key: &Pubkey,
+    store_program_id: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[VirtualPool::SEED, store.as_ref(), key.as_ref()],
+        store_program_id,
+    )
+}
+
+/// Find the PDA for market OI caps account.
+pub fn find_market_oi_caps_pda(
+    store: &Pubkey,
+    market_token: &Pubkey,
+    store_program_id: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[MarketOiCaps::SEED, store.as_ref(), market_token.as_ref()],
+        store_program_id,
+    )
+}
+
+/// Find the PDA for market analytics account.
+pub fn find_market_analytics_pda(
+    store: &Pubkey,
+    market_token: &Pubkey,
+    period_secs: u64,
+    period_key: i64,
+    store_program_id: &Pubkey,
+) -> (Pubkey, u8) {
+    MarketAnalytics::find_market_analytics_pda(
+        store,
+        market_token,
+        period_secs,
+        period_key,
+        store_program_id,
+    )
+}
+
+/// Find the PDA for fee structure proposal account.
+pub fn find_fee_structure_proposal_pda(
+    store: &Pubkey,
+    market_token: &Pubkey,
+    nonce: &NonceBytes,
+    store_program_id: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            FeeStructureProposal::SEED,
+            store.as_ref(),
+            market_token.as_ref(),
+            nonce,
+        ],
+        store_program_id,
+    )
+}
+
+/// Find the PDA for fee structure vote account.
+pub fn find_fee_structure_vote_pda(
+    proposal: &Pubkey,
+    voter: &Pubkey,
+    store_program_id: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[FeeStructureVote::SEED, proposal.as_ref(), voter.as_ref()],
+        store_program_id,
+    )
+}
+
+/// Find the PDA for price adjustment schedule account.
+pub fn find_price_adjustment_schedule_pda(
+    store: &Pubkey,
+    token: &Pubkey,
+    store_program_id: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            PriceAdjustmentSchedule::SEED,
+            store.as_ref(),
+            token.as_ref(),
+        ],
+        store_program_id,
+    )
+}
+
+/// Find the PDA for last oracle price account.
+pub fn find_last_oracle_price_pda(
+    store: &Pubkey,
+    token: &Pubkey,
+    store_program_id: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[LastOraclePrice::SEED, store.as_ref(), token.as_ref()],
+        store_program_id,
+    )
+}
+
 /// Fint the PDA for custom price feed account.
 pub fn find_price_feed_pda(
     store: &Pubkey,
@@ -1,10 +1,17 @@
+use anchor_client::solana_sdk::pubkey::Pubkey;
+
 use crate::{
     decode::untagged,
     types::{
-        BorrowingFeesUpdated, DepositExecuted, DepositRemoved, GlvDepositRemoved, GlvPricing,
-        GlvWithdrawalRemoved, GtUpdated, MarketFeesUpdated, MarketStateUpdated, OrderRemoved,
-        PositionDecreased, PositionIncreased, ShiftRemoved, SwapExecuted, TradeEvent,
-        WithdrawalExecuted, WithdrawalRemoved,
+        common::action::ActionState, BatchOiCapUpdated, BatchPositionsPrepared,
+        BorrowingFeesUpdated, DepositExecuted, DepositRemoved, DustDepositCancelled,
+        EmergencyWithdrawal, FeesClaimedWithSplit, GlvBatchShiftCreated, GlvDepositRemoved,
+        GlvPricing, GlvWithdrawalRemoved, GtUpdated, KeeperTipPaid, MarketFeesUpdated,
+        MarketNameUpdated, MarketStateCompressed, MarketStateExport, MarketStateUpdated,
+        MarketSupplyDiscrepancy, OracleRotated, OrderCallbackPending, OrderRemoved,
+        PositionDecreased, PositionIncreased, ShiftRemoved, StuckClaimableAccountRecovered,
+        SwapExecuted, TradeEvent, UserAccountFrozen, UserAccountUnfrozen, WithdrawalExecuted,
+        WithdrawalRemoved,
     },
 };
 
@@ -13,20 +20,276 @@ untagged!(
     [
         DepositExecuted,
         DepositRemoved,
+        DustDepositCancelled,
         WithdrawalExecuted,
         WithdrawalRemoved,
         ShiftRemoved,
         GlvDepositRemoved,
         GlvWithdrawalRemoved,
         GlvPricing,
+        GlvBatchShiftCreated,
         PositionIncreased,
         PositionDecreased,
         OrderRemoved,
+        OrderCallbackPending,
         TradeEvent,
         MarketFeesUpdated,
         BorrowingFeesUpdated,
         MarketStateUpdated,
         SwapExecuted,
-        GtUpdated
+        GtUpdated,
+        EmergencyWithdrawal,
+        MarketStateCompressed,
+        UserAccountFrozen,
+        UserAccountUnfrozen,
+        OracleRotated,
+        FeesClaimedWithSplit,
+        MarketNameUpdated,
+        MarketStateExport,
+        StuckClaimableAccountRecovered,
+        BatchOiCapUpdated,
+        BatchPositionsPrepared,
+        MarketSupplyDiscrepancy,
+        KeeperTipPaid
     ]
 );
+
+/// The terminal result of an action.
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug)]
+pub enum ActionResult {
+    /// Deposit.
+    Deposit {
+        /// Removed event.
+        removed: DepositRemoved,
+        /// Executed event.
+        executed: Option<DepositExecuted>,
+    },
+    /// Withdrawal.
+    Withdrawal {
+        /// Removed event.
+        removed: WithdrawalRemoved,
+        /// Executed event.
+        executed: Option<WithdrawalExecuted>,
+    },
+    /// Shift.
+    Shift {
+        /// Removed event.
+        removed: ShiftRemoved,
+    },
+    /// Order.
+    Order {
+        /// Removed event.
+        removed: OrderRemoved,
+        /// Trade event.
+        trade: Option<TradeEvent>,
+    },
+    /// GLV deposit.
+    GlvDeposit {
+        /// Removed event.
+        removed: GlvDepositRemoved,
+    },
+    /// GLV withdrawal.
+    GlvWithdrawal {
+        /// Removed event.
+        removed: GlvWithdrawalRemoved,
+    },
+}
+
+impl ActionResult {
+    /// Find the terminal result of the given action from the events
+    /// emitted by a transaction.
+    ///
+    /// Returns `None` if the action was not removed in these events.
+    pub fn from_events(
+        action: &Pubkey,
+        events: impl IntoIterator<Item = StoreCPIEvent>,
+    ) -> Option<Self> {
+        let mut deposit_executed = None;
+        let mut withdrawal_executed = None;
+        let mut trade = None;
+        for event in events {
+            match event {
+                StoreCPIEvent::DepositExecuted(event) => deposit_executed = Some(event),
+                StoreCPIEvent::WithdrawalExecuted(event) => withdrawal_executed = Some(event),
+                StoreCPIEvent::TradeEvent(event) if event.order == *action => trade = Some(event),
+                // The executed event of a deposit or withdrawal does not contain its address,
+                // but is always followed by the removed event of the same action.
+                StoreCPIEvent::DepositRemoved(removed) => {
+                    let executed = deposit_executed
+                        .take()
+                        .filter(|executed| executed.market_token == removed.market_token);
+                    if removed.deposit == *action {
+                        return Some(Self::Deposit { removed, executed });
+                    }
+                }
+                StoreCPIEvent::WithdrawalRemoved(removed) => {
+                    let executed = withdrawal_executed
+                        .take()
+                        .filter(|executed| executed.market_token == removed.market_token);
+                    if removed.withdrawal == *action {
+                        return Some(Self::Withdrawal { removed, executed });
+                    }
+                }
+                StoreCPIEvent::ShiftRemoved(removed) if removed.shift == *action => {
+                    return Some(Self::Shift { removed });
+                }
+                StoreCPIEvent::OrderRemoved(removed) if removed.order == *action => {
+                    return Some(Self::Order { removed, trade });
+                }
+                StoreCPIEvent::GlvDepositRemoved(removed) if removed.glv_deposit == *action => {
+                    return Some(Self::GlvDeposit { removed });
+                }
+                StoreCPIEvent::GlvWithdrawalRemoved(removed)
+                    if removed.glv_withdrawal == *action =>
+                {
+                    return Some(Self::GlvWithdrawal { removed });
+                }
+                _ => {}
+            }
+        }
+        None
+    }
+
+    /// Get the final state of the action.
+    pub fn state(&self) -> ActionState {
+        match self {
+            Self::Deposit { removed, .. } => removed.state,
+            Self::Withdrawal { removed, .. } => removed.state,
+            Self::Shift { removed } => removed.state,
+            Self::Order { removed, .. } => removed.state,
+            Self::GlvDeposit { removed } => removed.state,
+            Self::GlvWithdrawal { removed } => removed.state,
+        }
+    }
+
+    /// Get the reason why the action was removed.
+    pub fn reason(&self) -> &str {
+        match self {
+            Self::Deposit { removed, .. } => &removed.reason,
+            Self::Withdrawal { removed, .. } => &removed.reason,
+            Self::Shift { removed } => &removed.reason,
+            Self::Order { removed, .. } => &removed.reason,
+            Self::GlvDeposit { removed } => &removed.reason,
+            Self::GlvWithdrawal { removed } => &removed.reason,
+        }
+    }
+
+    /// Returns whether the action was completed.
+    pub fn is_completed(&self) -> bool {
+        matches!(self.state(), ActionState::Completed)
+    }
+
+    /// Get the trade event if this is an executed order.
+    pub fn trade(&self) -> Option<&TradeEvent> {
+        match self {
+            Self::Order { trade, .. } => trade.as_ref(),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anchor_client::anchor_lang::AnchorDeserialize;
+
+    use super::*;
+
+    fn deposit_removed(deposit: Pubkey, market_token: Pubkey) -> StoreCPIEvent {
+        StoreCPIEvent::DepositRemoved(DepositRemoved {
+            id: 0,
+            ts: 0,
+            slot: 0,
+            store: Pubkey::default(),
+            deposit,
+            market_token,
+            owner: Pubkey::default(),
+            state: ActionState::Completed,
+            reason: "executed".to_string(),
+        })
+    }
+
+    fn deposit_executed(market_token: Pubkey) -> StoreCPIEvent {
+        let mut executed = DepositExecuted::deserialize(&mut [0u8; 1024].as_slice())
+            .expect("must be deserializable from zeros");
+        executed.market_token = market_token;
+        StoreCPIEvent::DepositExecuted(executed)
+    }
+
+    fn shift_removed(shift: Pubkey) -> StoreCPIEvent {
+        StoreCPIEvent::ShiftRemoved(ShiftRemoved {
+            id: 0,
+            ts: 0,
+            slot: 0,
+            store: Pubkey::default(),
+            shift,
+            market_token: Pubkey::default(),
+            owner: Pubkey::default(),
+            state: ActionState::Cancelled,
+            reason: "cancelled".to_string(),
+        })
+    }
+
+    #[test]
+    fn from_events_matches_the_action() {
+        let action = Pubkey::new_unique();
+        let other = Pubkey::new_unique();
+        let market_token = Pubkey::new_unique();
+
+        let events = [
+            deposit_removed(other, market_token),
+            shift_removed(other),
+            deposit_removed(action, market_token),
+        ];
+        let result = ActionResult::from_events(&action, events).expect("must be found");
+        let ActionResult::Deposit { removed, .. } = &result else {
+            panic!("must be a deposit result");
+        };
+        assert_eq!(removed.deposit, action);
+        assert!(result.is_completed());
+
+        let result =
+            ActionResult::from_events(&other, [shift_removed(other)]).expect("must be found");
+        assert!(matches!(result, ActionResult::Shift { .. }));
+        assert!(!result.is_completed());
+        assert_eq!(result.reason(), "cancelled");
+
+        assert!(
+            ActionResult::from_events(&action, [deposit_removed(other, market_token)]).is_none()
+        );
+    }
+
+    #[test]
+    fn from_events_attaches_the_executed_event_of_the_action() {
+        let action = Pubkey::new_unique();
+        let other = Pubkey::new_unique();
+        let market_token = Pubkey::new_unique();
+
+        let events = [
+            deposit_executed(market_token),
+            deposit_removed(action, market_token),
+        ];
+        let Some(ActionResult::Deposit { executed, .. }) =
+            ActionResult::from_events(&action, events)
+        else {
+            panic!("must be a deposit result");
+        };
+        assert_eq!(
+            executed.expect("must be executed").market_token,
+            market_token
+        );
+
+        // The executed event belongs to the other deposit.
+        let events = [
+            deposit_executed(market_token),
+            deposit_removed(other, market_token),
+            deposit_removed(action, market_token),
+        ];
+        let Some(ActionResult::Deposit { executed, .. }) =
+            ActionResult::from_events(&action, events)
+        else {
+            panic!("must be a deposit result");
+        };
+        assert!(executed.is_none());
+    }
+}
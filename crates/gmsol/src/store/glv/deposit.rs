@@ -247,6 +247,14 @@ impl<'a, C: Deref<Target = impl Signer> + Clone> CreateGlvDepositBuilder<'a, C>
         let mut initial_long_token = None;
         let mut initial_short_token = None;
 
+        let glv_token_ata = get_associated_token_address_with_program_id(
+            &owner,
+            &self.glv_token,
+            &glv_token_program_id,
+        );
+        let glv_deposit_cooldown = self
+            .client
+            .find_glv_deposit_cooldown_address(&glv, &glv_token_ata);
         let glv_token_escrow = get_associated_token_address_with_program_id(
             &glv_deposit,
             &self.glv_token,
@@ -345,8 +353,11 @@ impl<'a, C: Deref<Target = impl Signer> + Clone> CreateGlvDepositBuilder<'a, C>
                     receiver,
                     store: self.store,
                     market,
+                    user: self.client.find_user_address(&self.store, &owner),
                     glv,
                     glv_deposit,
+                    glv_token_ata,
+                    glv_deposit_cooldown,
                     glv_token: self.glv_token,
                     market_token: self.market_token,
                     initial_long_token,
@@ -720,10 +731,9 @@ impl<'a, C: Deref<Target = impl Signer> + Clone> ExecuteGlvDepositBuilder<'a, C>
                     index_tokens.push(market.meta().index_token_mint);
                 }
 
-                let store = glv_deposit.header().store();
                 let token_map_address = self
                     .client
-                    .authorized_token_map_address(store)
+                    .market_token_map_address(glv_deposit.header().market())
                     .await?
                     .ok_or(crate::Error::NotFound)?;
                 let token_map = self.client.token_map(&token_map_address).await?;
@@ -807,6 +817,14 @@ impl<'a, C: Deref<Target = impl Signer> + Clone> MakeBundleBuilder<'a, C>
             &hint.market_token,
             &token_program_id,
         );
+        let glv_deposit_cooldown = self.client.find_glv_deposit_cooldown_address(
+            &glv,
+            &get_associated_token_address_with_program_id(
+                &hint.owner,
+                &hint.glv_token,
+                &glv_token_program_id,
+            ),
+        );
 
         let feeds = self
             .feeds_parser
@@ -854,6 +872,7 @@ impl<'a, C: Deref<Target = impl Signer> + Clone> MakeBundleBuilder<'a, C>
                     initial_long_token_vault,
                     initial_short_token_vault,
                     market_token_vault,
+                    glv_deposit_cooldown,
                     token_program: token_program_id,
                     glv_token_program: glv_token_program_id,
                     system_program: system_program::ID,
@@ -1,10 +1,14 @@
-use std::{collections::BTreeSet, ops::Deref};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    ops::Deref,
+};
 
 use anchor_client::{
     anchor_lang::system_program,
     solana_sdk::{instruction::AccountMeta, pubkey::Pubkey, signer::Signer},
 };
 use anchor_spl::associated_token::get_associated_token_address_with_program_id;
+use gmsol_model::price::Prices;
 use gmsol_solana_utils::transaction_builder::TransactionBuilder;
 use gmsol_store::{
     accounts, instruction,
@@ -15,6 +19,7 @@ use gmsol_store::{
 };
 
 mod deposit;
+mod rebalance;
 mod shift;
 mod withdrawal;
 
@@ -23,9 +28,10 @@ pub use self::{
         CloseGlvDepositBuilder, CloseGlvDepositHint, CreateGlvDepositBuilder, CreateGlvDepositHint,
         ExecuteGlvDepositBuilder, ExecuteGlvDepositHint,
     },
+    rebalance::{TriggerAutomaticRebalanceDepositBuilder, TriggerAutomaticRebalanceDepositHint},
     shift::{
-        CloseGlvShiftBuilder, CloseGlvShiftHint, CreateGlvShiftBuilder, ExecuteGlvShiftBuilder,
-        ExecuteGlvShiftHint,
+        BatchCreateGlvShiftsBuilder, CloseGlvShiftBuilder, CloseGlvShiftHint,
+        CreateGlvShiftBuilder, ExecuteGlvShiftBuilder, ExecuteGlvShiftHint,
     },
     withdrawal::{
         CloseGlvWithdrawalBuilder, CloseGlvWithdrawalHint, CreateGlvWithdrawalBuilder,
@@ -63,6 +69,15 @@ pub trait GlvOps<C> {
         enable: bool,
     ) -> TransactionBuilder<C>;
 
+    /// Set the min amount of the market token the GLV is targeted to hold.
+    fn set_glv_market_min_amount(
+        &self,
+        store: &Pubkey,
+        glv_token: &Pubkey,
+        market_token: &Pubkey,
+        min_amount: u64,
+    ) -> TransactionBuilder<C>;
+
     /// Update GLV config.
     fn update_glv_config(
         &self,
@@ -89,6 +104,18 @@ pub trait GlvOps<C> {
         token_program_id: Option<&Pubkey>,
     ) -> TransactionBuilder<C>;
 
+    /// Get GLV token price.
+    ///
+    /// The `prices` must contain the prices for every market in the GLV,
+    /// with the market token addresses as keys.
+    fn get_glv_token_price(
+        &self,
+        store: &Pubkey,
+        glv_token: &Pubkey,
+        prices: BTreeMap<Pubkey, Prices<u128>>,
+        maximize: bool,
+    ) -> TransactionBuilder<C>;
+
     /// Create a GLV deposit.
     fn create_glv_deposit(
         &self,
@@ -136,6 +163,13 @@ pub trait GlvOps<C> {
         amount: u64,
     ) -> CreateGlvShiftBuilder<C>;
 
+    /// Create a batch of GLV shifts.
+    fn batch_create_glv_shifts(
+        &self,
+        store: &Pubkey,
+        glv_token: &Pubkey,
+    ) -> BatchCreateGlvShiftsBuilder<C>;
+
     fn close_glv_shift(&self, glv_shift: &Pubkey) -> CloseGlvShiftBuilder<C>;
 
     fn execute_glv_shift(
@@ -144,6 +178,44 @@ pub trait GlvOps<C> {
         glv_shift: &Pubkey,
         cancel_on_execution_error: bool,
     ) -> ExecuteGlvShiftBuilder<C>;
+
+    /// Initialize GLV treasury.
+    fn initialize_glv_treasury(
+        &self,
+        store: &Pubkey,
+        glv_token: &Pubkey,
+        max_rebalance_value_per_day: u128,
+    ) -> TransactionBuilder<C>;
+
+    /// Update GLV treasury.
+    fn update_glv_treasury(
+        &self,
+        store: &Pubkey,
+        glv_token: &Pubkey,
+        max_rebalance_value_per_day: u128,
+    ) -> TransactionBuilder<C>;
+
+    /// Withdraw tokens from GLV treasury.
+    fn withdraw_from_glv_treasury(
+        &self,
+        store: &Pubkey,
+        glv_token: &Pubkey,
+        token: &Pubkey,
+        token_program_id: Option<&Pubkey>,
+        target: &Pubkey,
+        amount: u64,
+    ) -> TransactionBuilder<C>;
+
+    /// Trigger a rebalance deposit funded by the GLV treasury.
+    fn trigger_automatic_rebalance_deposit(
+        &self,
+        store: &Pubkey,
+        oracle: &Pubkey,
+        glv_token: &Pubkey,
+        market_token: &Pubkey,
+        initial_token: &Pubkey,
+        amount: u64,
+    ) -> TriggerAutomaticRebalanceDepositBuilder<C>;
 }
 
 impl<C: Deref<Target = impl Signer> + Clone> GlvOps<C> for crate::Client<C> {
@@ -233,6 +305,24 @@ impl<C: Deref<Target = impl Signer> + Clone> GlvOps<C> for crate::Client<C> {
             })
     }
 
+    fn set_glv_market_min_amount(
+        &self,
+        store: &Pubkey,
+        glv_token: &Pubkey,
+        market_token: &Pubkey,
+        min_amount: u64,
+    ) -> TransactionBuilder<C> {
+        let glv = self.find_glv_address(glv_token);
+        self.store_transaction()
+            .anchor_accounts(accounts::UpdateGlvMarketConfig {
+                authority: self.payer(),
+                store: *store,
+                glv,
+                market_token: *market_token,
+            })
+            .anchor_args(instruction::SetGlvMarketMinAmount { min_amount })
+    }
+
     fn update_glv_config(
         &self,
         store: &Pubkey,
@@ -309,6 +399,34 @@ impl<C: Deref<Target = impl Signer> + Clone> GlvOps<C> for crate::Client<C> {
             .anchor_args(instruction::RemoveGlvMarket {})
     }
 
+    fn get_glv_token_price(
+        &self,
+        store: &Pubkey,
+        glv_token: &Pubkey,
+        prices: BTreeMap<Pubkey, Prices<u128>>,
+        maximize: bool,
+    ) -> TransactionBuilder<C> {
+        let glv = self.find_glv_address(glv_token);
+        let (accounts, _) = split_to_accounts(
+            prices.keys().copied(),
+            &glv,
+            store,
+            self.store_program_id(),
+            &anchor_spl::token::ID,
+            false,
+        );
+        self.store_transaction()
+            .anchor_accounts(accounts::ReadGlv {
+                glv,
+                glv_token: *glv_token,
+            })
+            .anchor_args(instruction::GetGlvTokenPrice {
+                prices: prices.into_values().collect(),
+                maximize,
+            })
+            .accounts(accounts)
+    }
+
     fn create_glv_deposit(
         &self,
         store: &Pubkey,
@@ -372,6 +490,14 @@ impl<C: Deref<Target = impl Signer> + Clone> GlvOps<C> for crate::Client<C> {
         )
     }
 
+    fn batch_create_glv_shifts(
+        &self,
+        store: &Pubkey,
+        glv_token: &Pubkey,
+    ) -> BatchCreateGlvShiftsBuilder<C> {
+        BatchCreateGlvShiftsBuilder::new(self, store, glv_token)
+    }
+
     fn close_glv_shift(&self, glv_shift: &Pubkey) -> CloseGlvShiftBuilder<C> {
         CloseGlvShiftBuilder::new(self, glv_shift)
     }
@@ -386,6 +512,93 @@ impl<C: Deref<Target = impl Signer> + Clone> GlvOps<C> for crate::Client<C> {
         builder.cancel_on_execution_error(cancel_on_execution_error);
         builder
     }
+
+    fn initialize_glv_treasury(
+        &self,
+        store: &Pubkey,
+        glv_token: &Pubkey,
+        max_rebalance_value_per_day: u128,
+    ) -> TransactionBuilder<C> {
+        let glv = self.find_glv_address(glv_token);
+        self.store_transaction()
+            .anchor_accounts(accounts::InitializeGlvTreasury {
+                authority: self.payer(),
+                store: *store,
+                glv,
+                glv_treasury: self.find_glv_treasury_address(store, &glv),
+                system_program: system_program::ID,
+            })
+            .anchor_args(instruction::InitializeGlvTreasury {
+                max_rebalance_value_per_day,
+            })
+    }
+
+    fn update_glv_treasury(
+        &self,
+        store: &Pubkey,
+        glv_token: &Pubkey,
+        max_rebalance_value_per_day: u128,
+    ) -> TransactionBuilder<C> {
+        let glv = self.find_glv_address(glv_token);
+        self.store_transaction()
+            .anchor_accounts(accounts::UpdateGlvTreasury {
+                authority: self.payer(),
+                store: *store,
+                glv_treasury: self.find_glv_treasury_address(store, &glv),
+            })
+            .anchor_args(instruction::UpdateGlvTreasury {
+                max_rebalance_value_per_day,
+            })
+    }
+
+    fn withdraw_from_glv_treasury(
+        &self,
+        store: &Pubkey,
+        glv_token: &Pubkey,
+        token: &Pubkey,
+        token_program_id: Option<&Pubkey>,
+        target: &Pubkey,
+        amount: u64,
+    ) -> TransactionBuilder<C> {
+        let token_program_id = token_program_id.unwrap_or(&anchor_spl::token::ID);
+        let glv = self.find_glv_address(glv_token);
+        let glv_treasury = self.find_glv_treasury_address(store, &glv);
+        self.store_transaction()
+            .anchor_accounts(accounts::WithdrawFromGlvTreasury {
+                authority: self.payer(),
+                store: *store,
+                glv_treasury,
+                token: *token,
+                vault: get_associated_token_address_with_program_id(
+                    &glv_treasury,
+                    token,
+                    token_program_id,
+                ),
+                target: *target,
+                token_program: *token_program_id,
+            })
+            .anchor_args(instruction::WithdrawFromGlvTreasury { amount })
+    }
+
+    fn trigger_automatic_rebalance_deposit(
+        &self,
+        store: &Pubkey,
+        oracle: &Pubkey,
+        glv_token: &Pubkey,
+        market_token: &Pubkey,
+        initial_token: &Pubkey,
+        amount: u64,
+    ) -> TriggerAutomaticRebalanceDepositBuilder<C> {
+        TriggerAutomaticRebalanceDepositBuilder::new(
+            self,
+            store,
+            oracle,
+            glv_token,
+            market_token,
+            initial_token,
+            amount,
+        )
+    }
 }
 
 fn split_to_accounts(
@@ -442,4 +655,21 @@ impl<C: Deref<Target = impl Signer> + Clone> crate::Client<C> {
         builder.receiver(Some(self.find_first_deposit_owner_address()));
         builder
     }
+
+    /// Fetch current GLV token price with the given prices of the GLV markets.
+    pub async fn glv_token_price(
+        &self,
+        store: &Pubkey,
+        glv_token: &Pubkey,
+        prices: BTreeMap<Pubkey, Prices<u128>>,
+        maximize: bool,
+    ) -> crate::Result<u128> {
+        let req = self.get_glv_token_price(store, glv_token, prices, maximize);
+        let price = crate::utils::view::<u128>(
+            &self.store_program().rpc(),
+            &req.signed_transaction_with_options(true, None).await?,
+        )
+        .await?;
+        Ok(price)
+    }
 }
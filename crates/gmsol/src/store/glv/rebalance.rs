@@ -0,0 +1,325 @@
+use std::{collections::HashMap, ops::Deref};
+
+use anchor_client::{
+    anchor_lang::system_program,
+    solana_sdk::{address_lookup_table::AddressLookupTableAccount, pubkey::Pubkey, signer::Signer},
+};
+use anchor_spl::associated_token::get_associated_token_address_with_program_id;
+use gmsol_solana_utils::{
+    bundle_builder::{BundleBuilder, BundleOptions},
+    transaction_builder::TransactionBuilder,
+};
+use gmsol_store::{
+    accounts, instruction,
+    states::{common::TokensWithFeed, GlvTreasury, PriceProviderKind},
+};
+
+use crate::{
+    store::{token::TokenAccountOps, utils::FeedsParser},
+    utils::{
+        builder::{
+            FeedAddressMap, FeedIds, MakeBundleBuilder, PullOraclePriceConsumer, SetExecutionFee,
+        },
+        fix_optional_account_metas, ZeroCopy,
+    },
+};
+
+#[cfg(feature = "pyth-pull-oracle")]
+use crate::pyth::pull_oracle::Prices;
+
+/// Trigger Automatic Rebalance Deposit Builder.
+pub struct TriggerAutomaticRebalanceDepositBuilder<'a, C> {
+    client: &'a crate::Client<C>,
+    store: Pubkey,
+    oracle: Pubkey,
+    glv_token: Pubkey,
+    market_token: Pubkey,
+    initial_token: Pubkey,
+    amount: u64,
+    min_market_token_amount: u64,
+    min_glv_token_amount: u64,
+    hint: Option<TriggerAutomaticRebalanceDepositHint>,
+    feeds_parser: FeedsParser,
+    alts: HashMap<Pubkey, Vec<Pubkey>>,
+}
+
+/// Hint for `trigger_automatic_rebalance_deposit` instruction.
+#[derive(Clone)]
+pub struct TriggerAutomaticRebalanceDepositHint {
+    token_map: Pubkey,
+    glv_deposit: Pubkey,
+    /// Feeds.
+    pub feeds: TokensWithFeed,
+}
+
+impl<'a, C: Deref<Target = impl Signer> + Clone> TriggerAutomaticRebalanceDepositBuilder<'a, C> {
+    pub(super) fn new(
+        client: &'a crate::Client<C>,
+        store: &Pubkey,
+        oracle: &Pubkey,
+        glv_token: &Pubkey,
+        market_token: &Pubkey,
+        initial_token: &Pubkey,
+        amount: u64,
+    ) -> Self {
+        Self {
+            client,
+            store: *store,
+            oracle: *oracle,
+            glv_token: *glv_token,
+            market_token: *market_token,
+            initial_token: *initial_token,
+            amount,
+            min_market_token_amount: 0,
+            min_glv_token_amount: 0,
+            hint: None,
+            feeds_parser: Default::default(),
+            alts: Default::default(),
+        }
+    }
+
+    /// Set min market token amount.
+    pub fn min_market_token_amount(&mut self, amount: u64) -> &mut Self {
+        self.min_market_token_amount = amount;
+        self
+    }
+
+    /// Set min GLV token amount.
+    pub fn min_glv_token_amount(&mut self, amount: u64) -> &mut Self {
+        self.min_glv_token_amount = amount;
+        self
+    }
+
+    /// Set hint.
+    pub fn hint(&mut self, hint: TriggerAutomaticRebalanceDepositHint) -> &mut Self {
+        self.hint = Some(hint);
+        self
+    }
+
+    /// Insert an Address Lookup Table.
+    pub fn add_alt(&mut self, account: AddressLookupTableAccount) -> &mut Self {
+        self.alts.insert(account.key, account.addresses);
+        self
+    }
+
+    /// Parse feeds with the given price udpates map.
+    #[cfg(feature = "pyth-pull-oracle")]
+    pub fn parse_with_pyth_price_updates(&mut self, price_updates: Prices) -> &mut Self {
+        self.feeds_parser.with_pyth_price_updates(price_updates);
+        self
+    }
+
+    /// Get the address of the GLV deposit to create.
+    pub async fn glv_deposit(&mut self) -> crate::Result<Pubkey> {
+        Ok(self.prepare_hint().await?.glv_deposit)
+    }
+
+    /// Prepare hint.
+    pub async fn prepare_hint(&mut self) -> crate::Result<TriggerAutomaticRebalanceDepositHint> {
+        use gmsol_store::states::common::token_with_feeds::token_records;
+
+        let hint = match &self.hint {
+            Some(hint) => hint.clone(),
+            None => {
+                let glv = self.client.find_glv_address(&self.glv_token);
+                let treasury_account = self.client.find_glv_treasury_address(&self.store, &glv);
+                let nonce = self
+                    .client
+                    .account::<ZeroCopy<GlvTreasury>>(&treasury_account)
+                    .await?
+                    .ok_or(crate::Error::NotFound)?
+                    .0
+                    .next_rebalance_deposit_nonce();
+                let glv_deposit =
+                    self.client
+                        .find_glv_deposit_address(&self.store, &treasury_account, &nonce);
+
+                let market = self
+                    .client
+                    .find_market_address(&self.store, &self.market_token);
+                let market = self.client.market(&market).await?;
+                let token_map_address = self
+                    .client
+                    .resolve_token_map_address(&market)
+                    .await?
+                    .ok_or(crate::Error::invalid_argument("token map is not set"))?;
+                let token_map = self.client.token_map(&token_map_address).await?;
+                let records = token_records(&token_map, &[self.initial_token].into())?;
+                let feeds = TokensWithFeed::try_from_records(records)?;
+
+                let hint = TriggerAutomaticRebalanceDepositHint {
+                    token_map: token_map_address,
+                    glv_deposit,
+                    feeds,
+                };
+                self.hint = Some(hint.clone());
+                hint
+            }
+        };
+
+        Ok(hint)
+    }
+
+    async fn build_rpc(&mut self) -> crate::Result<TransactionBuilder<'a, C>> {
+        let hint = self.prepare_hint().await?;
+        let token_program_id = anchor_spl::token::ID;
+        let glv_token_program_id = anchor_spl::token_2022::ID;
+
+        let store = &self.store;
+        let glv_token = &self.glv_token;
+        let market_token = &self.market_token;
+        let initial_token = &self.initial_token;
+        let glv = self.client.find_glv_address(glv_token);
+        let treasury_account = self.client.find_glv_treasury_address(store, &glv);
+        let glv_deposit = hint.glv_deposit;
+
+        // Prepare the escrow accounts.
+        let prepare = self
+            .client
+            .prepare_associated_token_account(glv_token, &glv_token_program_id, Some(&glv_deposit))
+            .merge(self.client.prepare_associated_token_account(
+                market_token,
+                &token_program_id,
+                Some(&glv_deposit),
+            ))
+            .merge(self.client.prepare_associated_token_account(
+                initial_token,
+                &token_program_id,
+                Some(&glv_deposit),
+            ));
+
+        let feeds = self.feeds_parser.parse_and_sort_by_tokens(&hint.feeds)?;
+
+        let rpc = self
+            .client
+            .store_transaction()
+            .accounts(fix_optional_account_metas(
+                accounts::AutoRebalanceDeposit {
+                    authority: self.client.payer(),
+                    store: *store,
+                    token_map: hint.token_map,
+                    oracle: self.oracle,
+                    market: self.client.find_market_address(store, market_token),
+                    glv,
+                    treasury_account,
+                    glv_deposit,
+                    glv_token: *glv_token,
+                    market_token: *market_token,
+                    initial_token: *initial_token,
+                    treasury_vault: get_associated_token_address_with_program_id(
+                        &treasury_account,
+                        initial_token,
+                        &token_program_id,
+                    ),
+                    glv_token_escrow: get_associated_token_address_with_program_id(
+                        &glv_deposit,
+                        glv_token,
+                        &glv_token_program_id,
+                    ),
+                    market_token_escrow: get_associated_token_address_with_program_id(
+                        &glv_deposit,
+                        market_token,
+                        &token_program_id,
+                    ),
+                    initial_token_escrow: get_associated_token_address_with_program_id(
+                        &glv_deposit,
+                        initial_token,
+                        &token_program_id,
+                    ),
+                    system_program: system_program::ID,
+                    token_program: token_program_id,
+                    glv_token_program: glv_token_program_id,
+                    associated_token_program: anchor_spl::associated_token::ID,
+                    chainlink_program: None,
+                },
+                &crate::program_ids::DEFAULT_GMSOL_STORE_ID,
+                self.client.store_program_id(),
+            ))
+            .anchor_args(instruction::TriggerAutomaticRebalanceDeposit {
+                market_token: *market_token,
+                amount: self.amount,
+                min_market_token_amount: self.min_market_token_amount,
+                min_glv_token_amount: self.min_glv_token_amount,
+            })
+            .accounts(feeds)
+            .lookup_tables(self.alts.clone());
+
+        Ok(prepare.merge(rpc))
+    }
+}
+
+#[cfg(feature = "pyth-pull-oracle")]
+mod pyth {
+    use crate::pyth::{pull_oracle::ExecuteWithPythPrices, PythPullOracleContext};
+
+    use super::*;
+
+    impl<'a, C: Deref<Target = impl Signer> + Clone> ExecuteWithPythPrices<'a, C>
+        for TriggerAutomaticRebalanceDepositBuilder<'a, C>
+    {
+        fn set_execution_fee(&mut self, lamports: u64) {
+            SetExecutionFee::set_execution_fee(self, lamports);
+        }
+
+        async fn context(&mut self) -> crate::Result<PythPullOracleContext> {
+            let hint = self.prepare_hint().await?;
+            let ctx = PythPullOracleContext::try_from_feeds(&hint.feeds)?;
+            Ok(ctx)
+        }
+
+        async fn build_rpc_with_price_updates(
+            &mut self,
+            price_updates: Prices,
+        ) -> crate::Result<Vec<TransactionBuilder<'a, C, ()>>> {
+            let txn = self
+                .parse_with_pyth_price_updates(price_updates)
+                .build()
+                .await?;
+            Ok(txn.into_builders())
+        }
+    }
+}
+
+impl<'a, C: Deref<Target = impl Signer> + Clone> MakeBundleBuilder<'a, C>
+    for TriggerAutomaticRebalanceDepositBuilder<'a, C>
+{
+    async fn build_with_options(
+        &mut self,
+        options: BundleOptions,
+    ) -> crate::Result<BundleBuilder<'a, C>> {
+        let mut tx = self.client.bundle_with_options(options);
+
+        tx.try_push(self.build_rpc().await?)?;
+
+        Ok(tx)
+    }
+}
+
+impl<C: Deref<Target = impl Signer> + Clone> PullOraclePriceConsumer
+    for TriggerAutomaticRebalanceDepositBuilder<'_, C>
+{
+    async fn feed_ids(&mut self) -> crate::Result<FeedIds> {
+        let hint = self.prepare_hint().await?;
+        Ok(FeedIds::new(self.store, hint.feeds))
+    }
+
+    fn process_feeds(
+        &mut self,
+        provider: PriceProviderKind,
+        map: FeedAddressMap,
+    ) -> crate::Result<()> {
+        self.feeds_parser
+            .insert_pull_oracle_feed_parser(provider, map);
+        Ok(())
+    }
+}
+
+impl<C> SetExecutionFee for TriggerAutomaticRebalanceDepositBuilder<'_, C> {
+    fn is_execution_fee_estimation_required(&self) -> bool {
+        false
+    }
+
+    fn set_execution_fee(&mut self, _lamports: u64) -> &mut Self {
+        self
+    }
+}
@@ -2,7 +2,10 @@ use std::{collections::HashMap, ops::Deref};
 
 use anchor_client::{
     anchor_lang::system_program,
-    solana_sdk::{address_lookup_table::AddressLookupTableAccount, pubkey::Pubkey, signer::Signer},
+    solana_sdk::{
+        address_lookup_table::AddressLookupTableAccount, instruction::AccountMeta, pubkey::Pubkey,
+        signer::Signer,
+    },
 };
 use anchor_spl::associated_token::get_associated_token_address;
 use gmsol_solana_utils::{
@@ -16,7 +19,7 @@ use gmsol_store::{
     states::{
         common::{action::Action, TokensWithFeed},
         glv::GlvShift,
-        HasMarketMeta, NonceBytes, PriceProviderKind, Shift, Store, TokenMapAccess,
+        Glv, HasMarketMeta, NonceBytes, PriceProviderKind, Shift, TokenMapAccess,
     },
 };
 
@@ -145,6 +148,128 @@ impl<'a, C: Deref<Target = impl Signer> + Clone> CreateGlvShiftBuilder<'a, C> {
     }
 }
 
+/// Batch Create GLV Shifts Builder.
+pub struct BatchCreateGlvShiftsBuilder<'a, C> {
+    client: &'a crate::Client<C>,
+    store: Pubkey,
+    glv_token: Pubkey,
+    shifts: Vec<BatchShift>,
+}
+
+struct BatchShift {
+    from_market_token: Pubkey,
+    to_market_token: Pubkey,
+    execution_fee: u64,
+    amount: u64,
+    min_to_market_token_amount: u64,
+}
+
+impl<'a, C: Deref<Target = impl Signer> + Clone> BatchCreateGlvShiftsBuilder<'a, C> {
+    pub(super) fn new(client: &'a crate::Client<C>, store: &Pubkey, glv_token: &Pubkey) -> Self {
+        Self {
+            client,
+            store: *store,
+            glv_token: *glv_token,
+            shifts: Vec::with_capacity(Glv::MAX_SHIFTS_PER_BATCH),
+        }
+    }
+
+    /// Add a GLV shift to the batch.
+    pub fn add_shift(
+        &mut self,
+        from_market_token: &Pubkey,
+        to_market_token: &Pubkey,
+        amount: u64,
+        min_to_market_token_amount: u64,
+    ) -> &mut Self {
+        self.shifts.push(BatchShift {
+            from_market_token: *from_market_token,
+            to_market_token: *to_market_token,
+            execution_fee: Shift::MIN_EXECUTION_LAMPORTS,
+            amount,
+            min_to_market_token_amount,
+        });
+        self
+    }
+
+    /// Build a [`TransactionBuilder`] to create the GLV shifts and return the addresses
+    /// of the GLV shift accounts to create, in the order they were added.
+    ///
+    /// The addresses are derived from the current batch nonce of the GLV, so the
+    /// transaction must be sent before any other batch is created for the same GLV.
+    pub async fn build_with_addresses(
+        &self,
+    ) -> crate::Result<(TransactionBuilder<'a, C>, Vec<Pubkey>)> {
+        if self.shifts.is_empty() || self.shifts.len() > Glv::MAX_SHIFTS_PER_BATCH {
+            return Err(crate::Error::invalid_argument(format!(
+                "the number of GLV shifts in a batch must be in [1, {}]",
+                Glv::MAX_SHIFTS_PER_BATCH
+            )));
+        }
+
+        let authority = self.client.payer();
+        let glv = self.client.find_glv_address(&self.glv_token);
+        let batch_nonce = self
+            .client
+            .account::<ZeroCopy<Glv>>(&glv)
+            .await?
+            .ok_or(crate::Error::NotFound)?
+            .0
+            .batch_nonce();
+
+        let mut addresses = Vec::with_capacity(self.shifts.len());
+        let mut params = Vec::with_capacity(self.shifts.len());
+        let mut remaining_accounts = Vec::with_capacity(self.shifts.len() * 5);
+        for (index, shift) in self.shifts.iter().enumerate() {
+            let nonce = Glv::batch_shift_nonce(&glv, batch_nonce, index as u8);
+            let glv_shift = self
+                .client
+                .find_shift_address(&self.store, &authority, &nonce);
+            let from_market = self
+                .client
+                .find_market_address(&self.store, &shift.from_market_token);
+            let to_market = self
+                .client
+                .find_market_address(&self.store, &shift.to_market_token);
+            remaining_accounts.extend([
+                AccountMeta::new(glv_shift, false),
+                AccountMeta::new(from_market, false),
+                AccountMeta::new_readonly(to_market, false),
+                AccountMeta::new_readonly(
+                    get_associated_token_address(&glv, &shift.from_market_token),
+                    false,
+                ),
+                AccountMeta::new_readonly(
+                    get_associated_token_address(&glv, &shift.to_market_token),
+                    false,
+                ),
+            ]);
+            params.push(CreateShiftParams {
+                execution_lamports: shift.execution_fee,
+                from_market_token_amount: shift.amount,
+                min_to_market_token_amount: shift.min_to_market_token_amount,
+            });
+            addresses.push(glv_shift);
+        }
+
+        let rpc = self
+            .client
+            .store_transaction()
+            .anchor_accounts(accounts::BatchCreateGlvShifts {
+                authority,
+                store: self.store,
+                glv,
+                system_program: system_program::ID,
+                event_authority: self.client.store_event_authority(),
+                program: *self.client.store_program_id(),
+            })
+            .anchor_args(instruction::BatchCreateGlvShifts { params })
+            .accounts(remaining_accounts);
+
+        Ok((rpc, addresses))
+    }
+}
+
 /// Close GLV Shift Builder.
 pub struct CloseGlvShiftBuilder<'a, C> {
     client: &'a crate::Client<C>,
@@ -278,7 +403,7 @@ impl ExecuteGlvShiftHint {
     /// Create hint for `execute_shift` instruction.
     pub fn new(
         glv_shift: &GlvShift,
-        store: &Store,
+        token_map_address: &Pubkey,
         map: &impl TokenMapAccess,
         from_market: &impl HasMarketMeta,
         to_market: &impl HasMarketMeta,
@@ -297,7 +422,7 @@ impl ExecuteGlvShiftHint {
             funder: *glv_shift.funder(),
             from_market_token: token_infos.from_market_token(),
             to_market_token: token_infos.to_market_token(),
-            token_map: *store.token_map().ok_or(crate::Error::NotFound)?,
+            token_map: *token_map_address,
             feeds,
         })
     }
@@ -359,11 +484,6 @@ impl<'a, C: Deref<Target = impl Signer> + Clone> ExecuteGlvShiftBuilder<'a, C> {
                     .account::<ZeroCopy<GlvShift>>(&self.shift)
                     .await?
                     .ok_or(crate::Error::NotFound)?;
-                let store = self.client.store(shift.0.header().store()).await?;
-                let token_map_address = store
-                    .token_map()
-                    .ok_or(crate::Error::invalid_argument("token map is not set"))?;
-                let token_map = self.client.token_map(token_map_address).await?;
                 let from_market_token = shift.0.tokens().from_market_token();
                 let to_market_token = shift.0.tokens().to_market_token();
                 let from_market = self
@@ -374,9 +494,15 @@ impl<'a, C: Deref<Target = impl Signer> + Clone> ExecuteGlvShiftBuilder<'a, C> {
                     .client
                     .find_market_address(shift.0.header().store(), &to_market_token);
                 let to_market = self.client.market(&to_market).await?;
+                let token_map_address = self
+                    .client
+                    .resolve_token_map_address(&from_market)
+                    .await?
+                    .ok_or(crate::Error::invalid_argument("token map is not set"))?;
+                let token_map = self.client.token_map(&token_map_address).await?;
                 let hint = ExecuteGlvShiftHint::new(
                     &shift.0,
-                    &store,
+                    &token_map_address,
                     &token_map,
                     &*from_market,
                     &*to_market,
@@ -216,6 +216,9 @@ impl<'a, C: Deref<Target = impl Signer> + Clone> CreateGlvWithdrawalBuilder<'a,
                 &glv_token_program_id,
             )
         });
+        let glv_deposit_cooldown = self
+            .client
+            .find_glv_deposit_cooldown_address(&glv, &glv_token_source);
 
         let glv_token_escrow = get_associated_token_address_with_program_id(
             &glv_withdrawal,
@@ -287,6 +290,7 @@ impl<'a, C: Deref<Target = impl Signer> + Clone> CreateGlvWithdrawalBuilder<'a,
                 receiver,
                 store: self.store,
                 market,
+                user: self.client.find_user_address(&self.store, &owner),
                 glv,
                 glv_withdrawal,
                 glv_token: self.glv_token,
@@ -294,6 +298,7 @@ impl<'a, C: Deref<Target = impl Signer> + Clone> CreateGlvWithdrawalBuilder<'a,
                 final_long_token,
                 final_short_token,
                 glv_token_source,
+                glv_deposit_cooldown,
                 glv_token_escrow,
                 market_token_escrow,
                 final_long_token_escrow,
@@ -633,10 +638,9 @@ impl<'a, C: Deref<Target = impl Signer> + Clone> ExecuteGlvWithdrawalBuilder<'a,
                     index_tokens.push(market.meta().index_token_mint);
                 }
 
-                let store = glv_deposit.header().store();
                 let token_map_address = self
                     .client
-                    .authorized_token_map_address(store)
+                    .market_token_map_address(glv_deposit.header().market())
                     .await?
                     .ok_or(crate::Error::NotFound)?;
                 let token_map = self.client.token_map(&token_map_address).await?;
@@ -3,7 +3,7 @@ use std::{future::Future, ops::Deref};
 use crate::utils::ZeroCopy;
 use anchor_client::{
     anchor_lang::system_program,
-    solana_sdk::{pubkey::Pubkey, signer::Signer},
+    solana_sdk::{instruction::AccountMeta, pubkey::Pubkey, signer::Signer},
 };
 use gmsol_solana_utils::transaction_builder::TransactionBuilder;
 use gmsol_store::{
@@ -38,6 +38,13 @@ pub trait GtOps<C> {
         factors: Vec<u128>,
     ) -> TransactionBuilder<C>;
 
+    /// Validate GT referral reward factors without setting them.
+    fn validate_referral_reward_factors(
+        &self,
+        store: &Pubkey,
+        factors: Vec<u128>,
+    ) -> TransactionBuilder<C>;
+
     /// Configurate the time window size for GT exchange.
     fn gt_set_exchange_time_window(&self, store: &Pubkey, window: u32) -> TransactionBuilder<C>;
 
@@ -89,7 +96,7 @@ pub trait GtOps<C> {
         ))
     }
 
-    /// Close a confirmed GT exchange.
+    /// Mark a confirmed GT exchange as completed.
     fn close_gt_exchange(
         &self,
         store: &Pubkey,
@@ -97,6 +104,19 @@ pub trait GtOps<C> {
         hint_owner: Option<&Pubkey>,
         hint_vault: Option<&Pubkey>,
     ) -> impl Future<Output = crate::Result<TransactionBuilder<C>>>;
+
+    /// Close the given completed GT exchanges and fully completed GT exchange vaults,
+    /// refunding their rent.
+    ///
+    /// The `exchanges` are given as `(exchange, owner)` pairs. The rent of the vaults is
+    /// refunded to the `receiver`, which defaults to the payer.
+    fn reclaim_gt_exchange_rent(
+        &self,
+        store: &Pubkey,
+        vaults: &[Pubkey],
+        exchanges: &[(Pubkey, Pubkey)],
+        receiver: Option<&Pubkey>,
+    ) -> crate::Result<TransactionBuilder<C>>;
 }
 
 impl<C: Deref<Target = impl Signer> + Clone> GtOps<C> for crate::Client<C> {
@@ -150,6 +170,16 @@ impl<C: Deref<Target = impl Signer> + Clone> GtOps<C> for crate::Client<C> {
             .anchor_args(instruction::GtSetReferralRewardFactors { factors })
     }
 
+    fn validate_referral_reward_factors(
+        &self,
+        store: &Pubkey,
+        factors: Vec<u128>,
+    ) -> TransactionBuilder<C> {
+        self.store_transaction()
+            .anchor_accounts(accounts::ReadStore { store: *store })
+            .anchor_args(instruction::ValidateReferralRewardFactors { factors })
+    }
+
     fn gt_set_exchange_time_window(&self, store: &Pubkey, window: u32) -> TransactionBuilder<C> {
         self.store_transaction()
             .anchor_accounts(accounts::ConfigurateGt {
@@ -242,6 +272,38 @@ impl<C: Deref<Target = impl Signer> + Clone> GtOps<C> for crate::Client<C> {
             })
             .anchor_args(instruction::CloseGtExchange {}))
     }
+
+    fn reclaim_gt_exchange_rent(
+        &self,
+        store: &Pubkey,
+        vaults: &[Pubkey],
+        exchanges: &[(Pubkey, Pubkey)],
+        receiver: Option<&Pubkey>,
+    ) -> crate::Result<TransactionBuilder<C>> {
+        let num_vaults = vaults
+            .len()
+            .try_into()
+            .map_err(|_| crate::Error::invalid_argument("too many vaults"))?;
+        let remaining_accounts = vaults
+            .iter()
+            .map(|vault| AccountMeta::new(*vault, false))
+            .chain(exchanges.iter().flat_map(|(exchange, owner)| {
+                [
+                    AccountMeta::new(*exchange, false),
+                    AccountMeta::new(*owner, false),
+                ]
+            }))
+            .collect::<Vec<_>>();
+        Ok(self
+            .store_transaction()
+            .anchor_accounts(accounts::ReclaimGtExchangeRent {
+                authority: self.payer(),
+                store: *store,
+                receiver: receiver.copied().unwrap_or(self.payer()),
+            })
+            .anchor_args(instruction::ReclaimGtExchangeRent { vaults: num_vaults })
+            .accounts(remaining_accounts))
+    }
 }
 
 /// Get current time window index.
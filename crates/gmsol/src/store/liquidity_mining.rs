@@ -0,0 +1,184 @@
+use std::ops::Deref;
+
+use anchor_client::{
+    anchor_lang::system_program,
+    solana_sdk::{pubkey::Pubkey, signer::Signer},
+};
+use anchor_spl::associated_token::get_associated_token_address;
+use gmsol_solana_utils::transaction_builder::TransactionBuilder;
+use gmsol_store::{accounts, instruction, states::liquidity_mining::CampaignParams};
+
+/// Liquidity Mining Operations.
+pub trait LiquidityMiningOps<C> {
+    /// Initialize a liquidity mining campaign and deposit the rewards.
+    ///
+    /// The rewards are transferred from the associated token account of the payer
+    /// if `reward_source` is not provided.
+    fn initialize_liquidity_mining_campaign(
+        &self,
+        store: &Pubkey,
+        market_token: &Pubkey,
+        reward_token: &Pubkey,
+        campaign_index: u8,
+        params: CampaignParams,
+        reward_source: Option<&Pubkey>,
+    ) -> TransactionBuilder<C>;
+
+    /// Stake market tokens into a liquidity mining campaign.
+    ///
+    /// The market tokens are transferred from the associated token account of the payer
+    /// if `source` is not provided.
+    fn stake_for_liquidity_mining(
+        &self,
+        store: &Pubkey,
+        market_token: &Pubkey,
+        campaign_index: u8,
+        amount: u64,
+        source: Option<&Pubkey>,
+    ) -> TransactionBuilder<C>;
+
+    /// Unstake market tokens from a liquidity mining campaign.
+    ///
+    /// The market tokens are transferred to the associated token account of the payer
+    /// if `destination` is not provided.
+    fn unstake_from_liquidity_mining(
+        &self,
+        store: &Pubkey,
+        market_token: &Pubkey,
+        campaign_index: u8,
+        amount: u64,
+        destination: Option<&Pubkey>,
+    ) -> TransactionBuilder<C>;
+
+    /// Claim the accrued rewards of a liquidity mining campaign.
+    ///
+    /// The rewards are transferred to the associated token account of the payer
+    /// if `destination` is not provided.
+    fn claim_liquidity_mining_rewards(
+        &self,
+        store: &Pubkey,
+        reward_token: &Pubkey,
+        campaign_index: u8,
+        destination: Option<&Pubkey>,
+    ) -> TransactionBuilder<C>;
+}
+
+impl<C: Deref<Target = impl Signer> + Clone> LiquidityMiningOps<C> for crate::Client<C> {
+    fn initialize_liquidity_mining_campaign(
+        &self,
+        store: &Pubkey,
+        market_token: &Pubkey,
+        reward_token: &Pubkey,
+        campaign_index: u8,
+        params: CampaignParams,
+        reward_source: Option<&Pubkey>,
+    ) -> TransactionBuilder<C> {
+        let authority = self.payer();
+        let campaign = self.find_liquidity_mining_campaign_address(store, campaign_index);
+        let reward_source = reward_source
+            .copied()
+            .unwrap_or_else(|| get_associated_token_address(&authority, reward_token));
+        self.store_transaction()
+            .anchor_accounts(accounts::InitializeLiquidityMiningCampaign {
+                authority,
+                store: *store,
+                market: self.find_market_address(store, market_token),
+                market_token: *market_token,
+                reward_token: *reward_token,
+                campaign,
+                stake_vault: self.find_liquidity_mining_vault_address(&campaign, market_token),
+                reward_vault: self.find_liquidity_mining_vault_address(&campaign, reward_token),
+                reward_source,
+                system_program: system_program::ID,
+                token_program: anchor_spl::token::ID,
+            })
+            .anchor_args(instruction::InitializeLiquidityMiningCampaign {
+                campaign_index,
+                params,
+            })
+    }
+
+    fn stake_for_liquidity_mining(
+        &self,
+        store: &Pubkey,
+        market_token: &Pubkey,
+        campaign_index: u8,
+        amount: u64,
+        source: Option<&Pubkey>,
+    ) -> TransactionBuilder<C> {
+        let owner = self.payer();
+        let campaign = self.find_liquidity_mining_campaign_address(store, campaign_index);
+        let source = source
+            .copied()
+            .unwrap_or_else(|| get_associated_token_address(&owner, market_token));
+        self.store_transaction()
+            .anchor_accounts(accounts::StakeForLiquidityMining {
+                owner,
+                store: *store,
+                campaign,
+                checkpoint: self.find_lp_balance_checkpoint_address(&campaign, &owner),
+                source,
+                stake_vault: self.find_liquidity_mining_vault_address(&campaign, market_token),
+                system_program: system_program::ID,
+                token_program: anchor_spl::token::ID,
+            })
+            .anchor_args(instruction::StakeForLiquidityMining {
+                campaign_index,
+                amount,
+            })
+    }
+
+    fn unstake_from_liquidity_mining(
+        &self,
+        store: &Pubkey,
+        market_token: &Pubkey,
+        campaign_index: u8,
+        amount: u64,
+        destination: Option<&Pubkey>,
+    ) -> TransactionBuilder<C> {
+        let owner = self.payer();
+        let campaign = self.find_liquidity_mining_campaign_address(store, campaign_index);
+        let destination = destination
+            .copied()
+            .unwrap_or_else(|| get_associated_token_address(&owner, market_token));
+        self.store_transaction()
+            .anchor_accounts(accounts::UnstakeFromLiquidityMining {
+                owner,
+                store: *store,
+                campaign,
+                checkpoint: self.find_lp_balance_checkpoint_address(&campaign, &owner),
+                destination,
+                stake_vault: self.find_liquidity_mining_vault_address(&campaign, market_token),
+                token_program: anchor_spl::token::ID,
+            })
+            .anchor_args(instruction::UnstakeFromLiquidityMining {
+                campaign_index,
+                amount,
+            })
+    }
+
+    fn claim_liquidity_mining_rewards(
+        &self,
+        store: &Pubkey,
+        reward_token: &Pubkey,
+        campaign_index: u8,
+        destination: Option<&Pubkey>,
+    ) -> TransactionBuilder<C> {
+        let owner = self.payer();
+        let campaign = self.find_liquidity_mining_campaign_address(store, campaign_index);
+        let destination = destination
+            .copied()
+            .unwrap_or_else(|| get_associated_token_address(&owner, reward_token));
+        self.store_transaction()
+            .anchor_accounts(accounts::ClaimLiquidityMiningRewards {
+                owner,
+                store: *store,
+                campaign,
+                checkpoint: self.find_lp_balance_checkpoint_address(&campaign, &owner),
+                destination,
+                reward_vault: self.find_liquidity_mining_vault_address(&campaign, reward_token),
+                token_program: anchor_spl::token::ID,
+            })
+            .anchor_args(instruction::ClaimLiquidityMiningRewards { campaign_index })
+    }
+}
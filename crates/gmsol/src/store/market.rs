@@ -1,19 +1,26 @@
-use std::ops::Deref;
+use std::{collections::BTreeMap, ops::Deref};
 
 use anchor_client::{
     anchor_lang::system_program,
-    solana_sdk::{pubkey::Pubkey, signer::Signer},
+    solana_sdk::{
+        instruction::AccountMeta, packet::PACKET_DATA_SIZE, pubkey::Pubkey, signer::Signer,
+    },
 };
 use gmsol_model::{price::Prices, PnlFactorKind};
 use gmsol_solana_utils::transaction_builder::TransactionBuilder;
 use gmsol_store::{
     accounts, instruction,
     states::{
-        market::config::{EntryArgs, MarketConfigFlag},
+        market::{
+            config::{EntryArgs, MarketConfigFlag},
+            oi_caps::OiCapUpdate,
+        },
         Factor, MarketConfigKey,
     },
 };
 
+use crate::exchange::generate_nonce;
+
 /// Vault Operations.
 pub trait VaultOps<C> {
     /// Initialize a market vault for the given token.
@@ -22,6 +29,16 @@ pub trait VaultOps<C> {
         store: &Pubkey,
         token: &Pubkey,
     ) -> (TransactionBuilder<C>, Pubkey);
+
+    /// Withdraw tokens from the market vault in emergency mode.
+    fn emergency_vault_withdraw(
+        &self,
+        store: &Pubkey,
+        market_token: &Pubkey,
+        token: &Pubkey,
+        destination: &Pubkey,
+        amount: u64,
+    ) -> TransactionBuilder<C>;
 }
 
 impl<C, S> VaultOps<C> for crate::Client<C>
@@ -49,10 +66,53 @@ where
             .anchor_args(instruction::InitializeMarketVault {});
         (builder, vault)
     }
+
+    fn emergency_vault_withdraw(
+        &self,
+        store: &Pubkey,
+        market_token: &Pubkey,
+        token: &Pubkey,
+        destination: &Pubkey,
+        amount: u64,
+    ) -> TransactionBuilder<C> {
+        self.store_transaction()
+            .anchor_accounts(accounts::EmergencyVaultWithdraw {
+                authority: self.payer(),
+                store: *store,
+                market: self.find_market_address(store, market_token),
+                token_mint: *token,
+                vault: self.find_market_vault_address(store, token),
+                destination: *destination,
+                token_program: anchor_spl::token::ID,
+                event_authority: self.store_event_authority(),
+                program: *self.store_program_id(),
+            })
+            .anchor_args(instruction::EmergencyVaultWithdraw { amount })
+    }
 }
 
 /// Market Ops.
 pub trait MarketOps<C> {
+    /// Check the market config against the validity bounds.
+    fn validate_market_config(
+        &self,
+        store: &Pubkey,
+        market_token: &Pubkey,
+    ) -> TransactionBuilder<C>;
+
+    /// Get the number of pending deposits, withdrawals and orders of the market.
+    fn get_market_pending_counts(
+        &self,
+        store: &Pubkey,
+        market_token: &Pubkey,
+    ) -> TransactionBuilder<C>;
+
+    /// List the names of the supported market config keys, starting from `start`.
+    fn list_market_config_keys(&self, store: &Pubkey, start: u16) -> TransactionBuilder<C>;
+
+    /// List the names of the supported market config flags, starting from `start`.
+    fn list_market_config_flags(&self, store: &Pubkey, start: u16) -> TransactionBuilder<C>;
+
     /// Get market status.
     fn get_market_status(
         &self,
@@ -63,6 +123,49 @@ pub trait MarketOps<C> {
         maximize_pool_value: bool,
     ) -> TransactionBuilder<C>;
 
+    /// Get market expected value.
+    fn get_market_expected_value(
+        &self,
+        store: &Pubkey,
+        market_token: &Pubkey,
+        prices: Prices<u128>,
+        trading_volume_usd: u128,
+        time_horizon_secs: u64,
+    ) -> TransactionBuilder<C>;
+
+    /// Get the fee breakdown of a swap in the market.
+    fn get_market_swap_fees(
+        &self,
+        store: &Pubkey,
+        market_token: &Pubkey,
+        input_token: &Pubkey,
+        output_token: &Pubkey,
+        amount: u64,
+        prices: Prices<u128>,
+    ) -> TransactionBuilder<C>;
+
+    /// Get the price impact sensitivity of opening positions in the market.
+    ///
+    /// A positive `direction` is for long positions, while a negative one is for short positions.
+    fn get_market_price_impact_sensitivity(
+        &self,
+        store: &Pubkey,
+        oracle: &Pubkey,
+        market_token: &Pubkey,
+        prices: Prices<u128>,
+        direction: i8,
+    ) -> TransactionBuilder<C>;
+
+    /// Find the swap path with the highest output among the markets of the given prices.
+    fn get_swap_arbitrage_opportunity(
+        &self,
+        store: &Pubkey,
+        input_token: &Pubkey,
+        output_token: &Pubkey,
+        amount: u64,
+        prices: BTreeMap<Pubkey, Prices<u128>>,
+    ) -> TransactionBuilder<C>;
+
     /// Get market token price.
     fn get_market_token_price(
         &self,
@@ -73,6 +176,76 @@ pub trait MarketOps<C> {
         maximize: bool,
     ) -> TransactionBuilder<C>;
 
+    /// Get the underlying tokens backing one unit of market token.
+    fn get_market_token_backing(
+        &self,
+        store: &Pubkey,
+        market_token: &Pubkey,
+        prices: Prices<u128>,
+    ) -> TransactionBuilder<C>;
+
+    /// Get the value of the liquidity pool and the market token with the prices in the oracle.
+    fn get_lp_nav(
+        &self,
+        store: &Pubkey,
+        oracle: &Pubkey,
+        market_token: &Pubkey,
+        maximize: bool,
+    ) -> TransactionBuilder<C>;
+
+    /// Verify the market token supply against the market balances.
+    fn verify_market_token_supply(
+        &self,
+        store: &Pubkey,
+        market_token: &Pubkey,
+        prices: Prices<u128>,
+    ) -> TransactionBuilder<C>;
+
+    /// Get position info.
+    fn get_position_info(
+        &self,
+        store: &Pubkey,
+        market_token: &Pubkey,
+        position: &Pubkey,
+        prices: Prices<u128>,
+    ) -> TransactionBuilder<C>;
+
+    /// Estimate the size delta to auto-deleverage for the position.
+    fn estimate_adl_size_delta(
+        &self,
+        store: &Pubkey,
+        market_token: &Pubkey,
+        position: &Pubkey,
+        prices: Prices<u128>,
+    ) -> TransactionBuilder<C>;
+
+    /// Estimate the impact on the market of liquidating the position.
+    fn get_liquidation_impact(
+        &self,
+        store: &Pubkey,
+        market_token: &Pubkey,
+        position: &Pubkey,
+        prices: Prices<u128>,
+    ) -> TransactionBuilder<C>;
+
+    /// Get the collateral coverage of the given position.
+    fn get_collateral_coverage_ratio(
+        &self,
+        store: &Pubkey,
+        market_token: &Pubkey,
+        position: &Pubkey,
+        prices: Prices<u128>,
+    ) -> TransactionBuilder<C>;
+
+    /// Get the break-even price of the given position.
+    fn get_position_break_even_price(
+        &self,
+        store: &Pubkey,
+        market_token: &Pubkey,
+        position: &Pubkey,
+        prices: Prices<u128>,
+    ) -> TransactionBuilder<C>;
+
     /// Update market config.
     fn update_market_config(
         &self,
@@ -115,6 +288,77 @@ pub trait MarketOps<C> {
         self.update_market_config_flag(store, market_token, &key, value)
     }
 
+    /// Set the ADL parameters of the given side.
+    ///
+    /// - `trigger`: the max PnL factor that ADL will be enabled when exceeded.
+    /// - `target`: the min PnL factor that must be kept after ADL.
+    fn set_adl_parameters(
+        &self,
+        store: &Pubkey,
+        market_token: &Pubkey,
+        is_long: bool,
+        trigger: &Factor,
+        target: &Factor,
+    ) -> crate::Result<TransactionBuilder<C>> {
+        if target > trigger {
+            return Err(crate::Error::invalid_argument(
+                "the target PnL factor must not be greater than the trigger",
+            ));
+        }
+        let (trigger_key, target_key) = if is_long {
+            (
+                MarketConfigKey::MaxPnlFactorForLongAdl,
+                MarketConfigKey::MinPnlFactorAfterLongAdl,
+            )
+        } else {
+            (
+                MarketConfigKey::MaxPnlFactorForShortAdl,
+                MarketConfigKey::MinPnlFactorAfterShortAdl,
+            )
+        };
+        let set_trigger =
+            self.update_market_config_by_key(store, market_token, trigger_key, trigger)?;
+        let set_target =
+            self.update_market_config_by_key(store, market_token, target_key, target)?;
+        Ok(set_trigger.merge(set_target))
+    }
+
+    /// Set the position size increment (in USD) of the given market.
+    ///
+    /// The size delta of position increases and decreases must be a multiple of the increment.
+    /// Set it to zero to disable the check.
+    fn set_position_size_increment(
+        &self,
+        store: &Pubkey,
+        market_token: &Pubkey,
+        increment_usd: &Factor,
+    ) -> crate::Result<TransactionBuilder<C>> {
+        self.update_market_config_by_key(
+            store,
+            market_token,
+            MarketConfigKey::PositionSizeIncrementUsd,
+            increment_usd,
+        )
+    }
+
+    /// Set the max trade size (in USD) of the given market.
+    ///
+    /// The size delta of a single position increase or decrease must not exceed it.
+    /// Set it to zero to disable the check.
+    fn set_max_trade_size(
+        &self,
+        store: &Pubkey,
+        market_token: &Pubkey,
+        max_trade_size_usd: &Factor,
+    ) -> crate::Result<TransactionBuilder<C>> {
+        self.update_market_config_by_key(
+            store,
+            market_token,
+            MarketConfigKey::MaxTradeSizeUsd,
+            max_trade_size_usd,
+        )
+    }
+
     /// Toggle market.
     fn toggle_market(
         &self,
@@ -124,6 +368,18 @@ pub trait MarketOps<C> {
     ) -> TransactionBuilder<C>;
 
     /// Toggle GT minting.
+    fn export_market_state(&self, store: &Pubkey, market_token: &Pubkey) -> TransactionBuilder<C> {
+        self.store_transaction()
+            .anchor_args(instruction::ExportMarketState {})
+            .anchor_accounts(accounts::ExportMarketState {
+                authority: self.payer(),
+                store: *store,
+                market: self.find_market_address(store, market_token),
+                event_authority: self.store_event_authority(),
+                program: *self.store_program_id(),
+            })
+    }
+
     fn toggle_gt_minting(
         &self,
         store: &Pubkey,
@@ -131,6 +387,102 @@ pub trait MarketOps<C> {
         enable: bool,
     ) -> TransactionBuilder<C>;
 
+    /// Set the override token map of the market.
+    ///
+    /// The market will use the token map of the store if `token_map` is `None`.
+    fn set_market_token_map(
+        &self,
+        store: &Pubkey,
+        market_token: &Pubkey,
+        token_map: Option<&Pubkey>,
+    ) -> TransactionBuilder<C>;
+
+    /// Set the name of the market.
+    fn set_market_name(
+        &self,
+        store: &Pubkey,
+        market_token: &Pubkey,
+        name: &str,
+    ) -> TransactionBuilder<C>;
+
+    /// Close the market.
+    ///
+    /// The market token vault will also be closed if `close_market_token_vault` is `true`.
+    fn close_market(
+        &self,
+        store: &Pubkey,
+        market_token: &Pubkey,
+        close_market_token_vault: bool,
+        receiver: Option<&Pubkey>,
+    ) -> TransactionBuilder<C>;
+
+    /// Zero-fill the reserved ranges of the market.
+    fn compress_pool_state(&self, store: &Pubkey, market_token: &Pubkey) -> TransactionBuilder<C>;
+
+    /// Export the full state of the market, returning its hash.
+    fn export_market_state(&self, store: &Pubkey, market_token: &Pubkey) -> TransactionBuilder<C>;
+
+    /// Initialize a virtual pool with the given key.
+    fn initialize_virtual_pool(
+        &self,
+        store: &Pubkey,
+        key: &Pubkey,
+    ) -> TransactionBuilder<C, Pubkey>;
+
+    /// Add the given market to the virtual pool.
+    fn add_market_to_virtual_pool(
+        &self,
+        store: &Pubkey,
+        market_token: &Pubkey,
+        key: &Pubkey,
+    ) -> TransactionBuilder<C>;
+
+    /// Initialize the OI caps account of the given market.
+    fn initialize_market_oi_caps(
+        &self,
+        store: &Pubkey,
+        market_token: &Pubkey,
+        max_long: Factor,
+        max_short: Factor,
+        adjustment_rate_limit: Factor,
+    ) -> TransactionBuilder<C, Pubkey>;
+
+    /// Adjust the OI caps of the given market.
+    fn update_market_oi_caps(
+        &self,
+        store: &Pubkey,
+        market_token: &Pubkey,
+        new_max_long: Factor,
+        new_max_short: Factor,
+    ) -> TransactionBuilder<C>;
+
+    /// Update the OI caps of multiple markets atomically.
+    fn batch_update_market_oi_caps(
+        &self,
+        store: &Pubkey,
+        updates: Vec<OiCapUpdate>,
+    ) -> TransactionBuilder<C>;
+
+    /// Initialize the analytics account of the given market for the current period.
+    ///
+    /// The `period_key` must be the key of the current period, i.e.,
+    /// `unix_timestamp / period_secs`.
+    fn initialize_market_analytics(
+        &self,
+        store: &Pubkey,
+        market_token: &Pubkey,
+        period_secs: u64,
+        period_key: i64,
+    ) -> TransactionBuilder<C, Pubkey>;
+
+    /// Get the OHLCV data of the period from the given market analytics account.
+    fn get_ohlcv_period(
+        &self,
+        store: &Pubkey,
+        analytics: &Pubkey,
+        period_start: i64,
+    ) -> TransactionBuilder<C>;
+
     /// Initialize Market Config Buffer.
     fn initialize_market_config_buffer<'a>(
         &'a self,
@@ -153,6 +505,16 @@ pub trait MarketOps<C> {
         new_configs: impl IntoIterator<Item = (S, Factor)>,
     ) -> TransactionBuilder<C>;
 
+    /// Push the given entries to Market Config Buffer, split into transactions
+    /// that fit in the transaction size limit.
+    ///
+    /// Returns an error if any single entry cannot fit in a transaction.
+    fn push_market_config_entries(
+        &self,
+        buffer: &Pubkey,
+        entries: Vec<EntryArgs>,
+    ) -> crate::Result<Vec<TransactionBuilder<C>>>;
+
     /// Set the authority of the Market Config Buffer.
     fn set_market_config_buffer_authority(
         &self,
@@ -167,6 +529,31 @@ pub trait MarketOps<C> {
         market_token: &Pubkey,
         buffer: &Pubkey,
     ) -> TransactionBuilder<C>;
+
+    /// Propose a fee structure change for the given market.
+    fn propose_fee_structure_change(
+        &self,
+        store: &Pubkey,
+        market_token: &Pubkey,
+        changes: Vec<EntryArgs>,
+        voting_duration_secs: u32,
+    ) -> TransactionBuilder<C, Pubkey>;
+
+    /// Vote on a fee structure proposal.
+    fn vote_on_fee_structure(
+        &self,
+        store: &Pubkey,
+        proposal: &Pubkey,
+        vote_for: bool,
+    ) -> TransactionBuilder<C>;
+
+    /// Execute an approved fee structure proposal.
+    fn execute_fee_structure_proposal(
+        &self,
+        store: &Pubkey,
+        market_token: &Pubkey,
+        proposal: &Pubkey,
+    ) -> TransactionBuilder<C>;
 }
 
 impl<C, S> MarketOps<C> for crate::Client<C>
@@ -174,6 +561,42 @@ where
     C: Deref<Target = S> + Clone,
     S: Signer,
 {
+    fn validate_market_config(
+        &self,
+        store: &Pubkey,
+        market_token: &Pubkey,
+    ) -> TransactionBuilder<C> {
+        self.store_transaction()
+            .anchor_args(instruction::ValidateMarketConfig {})
+            .anchor_accounts(accounts::ReadMarket {
+                market: self.find_market_address(store, market_token),
+            })
+    }
+
+    fn get_market_pending_counts(
+        &self,
+        store: &Pubkey,
+        market_token: &Pubkey,
+    ) -> TransactionBuilder<C> {
+        self.store_transaction()
+            .anchor_args(instruction::GetMarketPendingCounts {})
+            .anchor_accounts(accounts::ReadMarket {
+                market: self.find_market_address(store, market_token),
+            })
+    }
+
+    fn list_market_config_keys(&self, store: &Pubkey, start: u16) -> TransactionBuilder<C> {
+        self.store_transaction()
+            .anchor_args(instruction::ListMarketConfigKeys { start })
+            .anchor_accounts(accounts::ReadStore { store: *store })
+    }
+
+    fn list_market_config_flags(&self, store: &Pubkey, start: u16) -> TransactionBuilder<C> {
+        self.store_transaction()
+            .anchor_args(instruction::ListMarketConfigFlags { start })
+            .anchor_accounts(accounts::ReadStore { store: *store })
+    }
+
     fn get_market_status(
         &self,
         store: &Pubkey,
@@ -193,60 +616,263 @@ where
             })
     }
 
-    fn get_market_token_price(
+    fn get_market_expected_value(
         &self,
         store: &Pubkey,
         market_token: &Pubkey,
         prices: Prices<u128>,
-        pnl_factor: PnlFactorKind,
-        maximize: bool,
+        trading_volume_usd: u128,
+        time_horizon_secs: u64,
     ) -> TransactionBuilder<C> {
         self.store_transaction()
-            .anchor_args(instruction::GetMarketTokenPrice {
+            .anchor_args(instruction::GetMarketExpectedValue {
                 prices,
-                pnl_factor: pnl_factor.to_string(),
-                maximize,
+                trading_volume_usd,
+                time_horizon_secs,
             })
-            .anchor_accounts(accounts::ReadMarketWithToken {
+            .anchor_accounts(accounts::ReadMarket {
                 market: self.find_market_address(store, market_token),
-                market_token: *market_token,
             })
     }
 
-    fn update_market_config(
+    fn get_market_swap_fees(
         &self,
         store: &Pubkey,
         market_token: &Pubkey,
-        key: &str,
-        value: &Factor,
-    ) -> crate::Result<TransactionBuilder<C>> {
-        let req = self
-            .store_transaction()
-            .anchor_args(instruction::UpdateMarketConfig {
-                key: key.to_string(),
-                value: *value,
+        input_token: &Pubkey,
+        output_token: &Pubkey,
+        amount: u64,
+        prices: Prices<u128>,
+    ) -> TransactionBuilder<C> {
+        self.store_transaction()
+            .anchor_args(instruction::GetMarketSwapFees {
+                input_token: *input_token,
+                output_token: *output_token,
+                amount,
+                prices,
             })
-            .anchor_accounts(accounts::UpdateMarketConfig {
-                authority: self.payer(),
-                store: *store,
+            .anchor_accounts(accounts::ReadMarket {
                 market: self.find_market_address(store, market_token),
-            });
-        Ok(req)
+            })
     }
 
-    fn update_market_config_flag(
+    fn get_market_price_impact_sensitivity(
         &self,
         store: &Pubkey,
+        oracle: &Pubkey,
         market_token: &Pubkey,
-        key: &str,
-        value: bool,
-    ) -> crate::Result<TransactionBuilder<C>> {
-        let req = self
-            .store_transaction()
-            .anchor_args(instruction::UpdateMarketConfigFlag {
-                key: key.to_string(),
-                value,
-            })
+        prices: Prices<u128>,
+        direction: i8,
+    ) -> TransactionBuilder<C> {
+        self.store_transaction()
+            .anchor_args(instruction::GetMarketPriceImpactSensitivity { prices, direction })
+            .anchor_accounts(accounts::GetMarketPriceImpactSensitivity {
+                market: self.find_market_address(store, market_token),
+                oracle: *oracle,
+            })
+    }
+
+    fn get_swap_arbitrage_opportunity(
+        &self,
+        store: &Pubkey,
+        input_token: &Pubkey,
+        output_token: &Pubkey,
+        amount: u64,
+        prices: BTreeMap<Pubkey, Prices<u128>>,
+    ) -> TransactionBuilder<C> {
+        let markets = prices
+            .keys()
+            .map(|market_token| {
+                AccountMeta::new_readonly(self.find_market_address(store, market_token), false)
+            })
+            .collect::<Vec<_>>();
+        self.store_transaction()
+            .anchor_args(instruction::GetSwapArbitrageOpportunity {
+                input_token: *input_token,
+                output_token: *output_token,
+                amount,
+                prices: prices.into_values().collect(),
+            })
+            .anchor_accounts(accounts::GetSwapArbitrageOpportunity { store: *store })
+            .accounts(markets)
+    }
+
+    fn get_market_token_price(
+        &self,
+        store: &Pubkey,
+        market_token: &Pubkey,
+        prices: Prices<u128>,
+        pnl_factor: PnlFactorKind,
+        maximize: bool,
+    ) -> TransactionBuilder<C> {
+        self.store_transaction()
+            .anchor_args(instruction::GetMarketTokenPrice {
+                prices,
+                pnl_factor: pnl_factor.to_string(),
+                maximize,
+            })
+            .anchor_accounts(accounts::ReadMarketWithToken {
+                market: self.find_market_address(store, market_token),
+                market_token: *market_token,
+            })
+    }
+
+    fn get_market_token_backing(
+        &self,
+        store: &Pubkey,
+        market_token: &Pubkey,
+        prices: Prices<u128>,
+    ) -> TransactionBuilder<C> {
+        self.store_transaction()
+            .anchor_args(instruction::GetMarketTokenBacking { prices })
+            .anchor_accounts(accounts::ReadMarketWithToken {
+                market: self.find_market_address(store, market_token),
+                market_token: *market_token,
+            })
+    }
+
+    fn get_lp_nav(
+        &self,
+        store: &Pubkey,
+        oracle: &Pubkey,
+        market_token: &Pubkey,
+        maximize: bool,
+    ) -> TransactionBuilder<C> {
+        self.store_transaction()
+            .anchor_args(instruction::GetLpValuePerToken { maximize })
+            .anchor_accounts(accounts::GetLpValuePerToken {
+                market: self.find_market_address(store, market_token),
+                market_token_mint: *market_token,
+                oracle: *oracle,
+            })
+    }
+
+    fn verify_market_token_supply(
+        &self,
+        store: &Pubkey,
+        market_token: &Pubkey,
+        prices: Prices<u128>,
+    ) -> TransactionBuilder<C> {
+        self.store_transaction()
+            .anchor_args(instruction::VerifyMarketTokenSupply { prices })
+            .anchor_accounts(accounts::VerifyMarketTokenSupply {
+                store: *store,
+                market: self.find_market_address(store, market_token),
+                market_token: *market_token,
+                event_authority: self.store_event_authority(),
+                program: *self.store_program_id(),
+            })
+    }
+
+    fn get_position_info(
+        &self,
+        store: &Pubkey,
+        market_token: &Pubkey,
+        position: &Pubkey,
+        prices: Prices<u128>,
+    ) -> TransactionBuilder<C> {
+        self.store_transaction()
+            .anchor_args(instruction::GetPositionInfo { prices })
+            .anchor_accounts(accounts::ReadPosition {
+                market: self.find_market_address(store, market_token),
+                position: *position,
+            })
+    }
+
+    fn estimate_adl_size_delta(
+        &self,
+        store: &Pubkey,
+        market_token: &Pubkey,
+        position: &Pubkey,
+        prices: Prices<u128>,
+    ) -> TransactionBuilder<C> {
+        self.store_transaction()
+            .anchor_args(instruction::EstimateAdlSizeDelta { prices })
+            .anchor_accounts(accounts::ReadPosition {
+                market: self.find_market_address(store, market_token),
+                position: *position,
+            })
+    }
+
+    fn get_liquidation_impact(
+        &self,
+        store: &Pubkey,
+        market_token: &Pubkey,
+        position: &Pubkey,
+        prices: Prices<u128>,
+    ) -> TransactionBuilder<C> {
+        self.store_transaction()
+            .anchor_args(instruction::GetLiquidationImpact { prices })
+            .anchor_accounts(accounts::ReadPosition {
+                market: self.find_market_address(store, market_token),
+                position: *position,
+            })
+    }
+
+    fn get_collateral_coverage_ratio(
+        &self,
+        store: &Pubkey,
+        market_token: &Pubkey,
+        position: &Pubkey,
+        prices: Prices<u128>,
+    ) -> TransactionBuilder<C> {
+        self.store_transaction()
+            .anchor_args(instruction::GetCollateralCoverageRatio { prices })
+            .anchor_accounts(accounts::ReadPosition {
+                market: self.find_market_address(store, market_token),
+                position: *position,
+            })
+    }
+
+    fn get_position_break_even_price(
+        &self,
+        store: &Pubkey,
+        market_token: &Pubkey,
+        position: &Pubkey,
+        prices: Prices<u128>,
+    ) -> TransactionBuilder<C> {
+        self.store_transaction()
+            .anchor_args(instruction::GetPositionBreakEvenPrice { prices })
+            .anchor_accounts(accounts::ReadPosition {
+                market: self.find_market_address(store, market_token),
+                position: *position,
+            })
+    }
+
+    fn update_market_config(
+        &self,
+        store: &Pubkey,
+        market_token: &Pubkey,
+        key: &str,
+        value: &Factor,
+    ) -> crate::Result<TransactionBuilder<C>> {
+        let req = self
+            .store_transaction()
+            .anchor_args(instruction::UpdateMarketConfig {
+                key: key.to_string(),
+                value: *value,
+            })
+            .anchor_accounts(accounts::UpdateMarketConfig {
+                authority: self.payer(),
+                store: *store,
+                market: self.find_market_address(store, market_token),
+            });
+        Ok(req)
+    }
+
+    fn update_market_config_flag(
+        &self,
+        store: &Pubkey,
+        market_token: &Pubkey,
+        key: &str,
+        value: bool,
+    ) -> crate::Result<TransactionBuilder<C>> {
+        let req = self
+            .store_transaction()
+            .anchor_args(instruction::UpdateMarketConfigFlag {
+                key: key.to_string(),
+                value,
+            })
             .anchor_accounts(accounts::UpdateMarketConfig {
                 authority: self.payer(),
                 store: *store,
@@ -270,6 +896,82 @@ where
             })
     }
 
+    fn set_market_token_map(
+        &self,
+        store: &Pubkey,
+        market_token: &Pubkey,
+        token_map: Option<&Pubkey>,
+    ) -> TransactionBuilder<C> {
+        self.store_transaction()
+            .accounts(crate::utils::fix_optional_account_metas(
+                accounts::SetMarketTokenMap {
+                    authority: self.payer(),
+                    store: *store,
+                    market: self.find_market_address(store, market_token),
+                    token_map: token_map.copied(),
+                },
+                &crate::program_ids::DEFAULT_GMSOL_STORE_ID,
+                self.store_program_id(),
+            ))
+            .anchor_args(instruction::SetMarketTokenMap {})
+    }
+
+    fn set_market_name(
+        &self,
+        store: &Pubkey,
+        market_token: &Pubkey,
+        name: &str,
+    ) -> TransactionBuilder<C> {
+        self.store_transaction()
+            .anchor_args(instruction::SetMarketName {
+                name: name.to_string(),
+            })
+            .anchor_accounts(accounts::SetMarketName {
+                authority: self.payer(),
+                store: *store,
+                market: self.find_market_address(store, market_token),
+                event_authority: self.store_event_authority(),
+                program: *self.store_program_id(),
+            })
+    }
+
+    fn close_market(
+        &self,
+        store: &Pubkey,
+        market_token: &Pubkey,
+        close_market_token_vault: bool,
+        receiver: Option<&Pubkey>,
+    ) -> TransactionBuilder<C> {
+        self.store_transaction()
+            .accounts(crate::utils::fix_optional_account_metas(
+                accounts::CloseMarket {
+                    authority: self.payer(),
+                    store: *store,
+                    market: self.find_market_address(store, market_token),
+                    market_token_mint: *market_token,
+                    market_token_vault: close_market_token_vault
+                        .then(|| self.find_market_vault_address(store, market_token)),
+                    receiver: receiver.copied().unwrap_or(self.payer()),
+                    token_program: anchor_spl::token::ID,
+                },
+                &crate::program_ids::DEFAULT_GMSOL_STORE_ID,
+                self.store_program_id(),
+            ))
+            .anchor_args(instruction::CloseMarket {})
+    }
+
+    fn compress_pool_state(&self, store: &Pubkey, market_token: &Pubkey) -> TransactionBuilder<C> {
+        self.store_transaction()
+            .anchor_args(instruction::CompressPoolState {})
+            .anchor_accounts(accounts::CompressPoolState {
+                authority: self.payer(),
+                store: *store,
+                market: self.find_market_address(store, market_token),
+                event_authority: self.store_event_authority(),
+                program: *self.store_program_id(),
+            })
+    }
+
     fn toggle_gt_minting(
         &self,
         store: &Pubkey,
@@ -285,6 +987,150 @@ where
             })
     }
 
+    fn initialize_virtual_pool(
+        &self,
+        store: &Pubkey,
+        key: &Pubkey,
+    ) -> TransactionBuilder<C, Pubkey> {
+        let virtual_pool = self.find_virtual_pool_address(store, key);
+        self.store_transaction()
+            .anchor_args(instruction::InitializeVirtualPool { key: *key })
+            .anchor_accounts(accounts::InitializeVirtualPool {
+                authority: self.payer(),
+                store: *store,
+                virtual_pool,
+                system_program: system_program::ID,
+            })
+            .output(virtual_pool)
+    }
+
+    fn add_market_to_virtual_pool(
+        &self,
+        store: &Pubkey,
+        market_token: &Pubkey,
+        key: &Pubkey,
+    ) -> TransactionBuilder<C> {
+        self.store_transaction()
+            .anchor_args(instruction::AddMarketToVirtualPool {
+                market_token: *market_token,
+                key: *key,
+            })
+            .anchor_accounts(accounts::AddMarketToVirtualPool {
+                authority: self.payer(),
+                store: *store,
+                market: self.find_market_address(store, market_token),
+                virtual_pool: self.find_virtual_pool_address(store, key),
+            })
+    }
+
+    fn initialize_market_oi_caps(
+        &self,
+        store: &Pubkey,
+        market_token: &Pubkey,
+        max_long: Factor,
+        max_short: Factor,
+        adjustment_rate_limit: Factor,
+    ) -> TransactionBuilder<C, Pubkey> {
+        let oi_caps = self.find_market_oi_caps_address(store, market_token);
+        self.store_transaction()
+            .anchor_args(instruction::InitializeMarketOiCaps {
+                max_long,
+                max_short,
+                adjustment_rate_limit,
+            })
+            .anchor_accounts(accounts::InitializeMarketOiCaps {
+                authority: self.payer(),
+                store: *store,
+                market: self.find_market_address(store, market_token),
+                oi_caps,
+                system_program: system_program::ID,
+            })
+            .output(oi_caps)
+    }
+
+    fn update_market_oi_caps(
+        &self,
+        store: &Pubkey,
+        market_token: &Pubkey,
+        new_max_long: Factor,
+        new_max_short: Factor,
+    ) -> TransactionBuilder<C> {
+        self.store_transaction()
+            .anchor_args(instruction::UpdateMarketOiCaps {
+                new_max_long,
+                new_max_short,
+            })
+            .anchor_accounts(accounts::UpdateMarketOiCaps {
+                authority: self.payer(),
+                store: *store,
+                market: self.find_market_address(store, market_token),
+                oi_caps: self.find_market_oi_caps_address(store, market_token),
+            })
+    }
+
+    fn batch_update_market_oi_caps(
+        &self,
+        store: &Pubkey,
+        updates: Vec<OiCapUpdate>,
+    ) -> TransactionBuilder<C> {
+        let remaining_accounts = updates
+            .iter()
+            .flat_map(|update| {
+                [
+                    AccountMeta::new(self.find_market_address(store, &update.market_token), false),
+                    AccountMeta::new(
+                        self.find_market_oi_caps_address(store, &update.market_token),
+                        false,
+                    ),
+                ]
+            })
+            .collect::<Vec<_>>();
+        self.store_transaction()
+            .anchor_args(instruction::BatchUpdateMarketOiCaps { updates })
+            .anchor_accounts(accounts::BatchUpdateMarketOiCaps {
+                authority: self.payer(),
+                store: *store,
+                event_authority: self.store_event_authority(),
+                program: *self.store_program_id(),
+            })
+            .accounts(remaining_accounts)
+    }
+
+    fn initialize_market_analytics(
+        &self,
+        store: &Pubkey,
+        market_token: &Pubkey,
+        period_secs: u64,
+        period_key: i64,
+    ) -> TransactionBuilder<C, Pubkey> {
+        let analytics =
+            self.find_market_analytics_address(store, market_token, period_secs, period_key);
+        self.store_transaction()
+            .anchor_args(instruction::InitializeMarketAnalytics { period_secs })
+            .anchor_accounts(accounts::InitializeMarketAnalytics {
+                payer: self.payer(),
+                store: *store,
+                market: self.find_market_address(store, market_token),
+                analytics,
+                system_program: system_program::ID,
+            })
+            .output(analytics)
+    }
+
+    fn get_ohlcv_period(
+        &self,
+        store: &Pubkey,
+        analytics: &Pubkey,
+        period_start: i64,
+    ) -> TransactionBuilder<C> {
+        self.store_transaction()
+            .anchor_args(instruction::GetOhlcvPeriod { period_start })
+            .anchor_accounts(accounts::GetOhlcvPeriod {
+                store: *store,
+                analytics: *analytics,
+            })
+    }
+
     fn initialize_market_config_buffer<'a>(
         &'a self,
         store: &Pubkey,
@@ -321,21 +1167,46 @@ where
         buffer: &Pubkey,
         new_configs: impl IntoIterator<Item = (K, Factor)>,
     ) -> TransactionBuilder<C> {
-        self.store_transaction()
-            .anchor_args(instruction::PushToMarketConfigBuffer {
-                new_configs: new_configs
-                    .into_iter()
-                    .map(|(key, value)| EntryArgs {
-                        key: key.to_string(),
-                        value,
-                    })
-                    .collect(),
-            })
-            .anchor_accounts(accounts::PushToMarketConfigBuffer {
-                authority: self.payer(),
-                buffer: *buffer,
-                system_program: system_program::ID,
-            })
+        push_entries(
+            self,
+            buffer,
+            new_configs
+                .into_iter()
+                .map(|(key, value)| EntryArgs {
+                    key: key.to_string(),
+                    value,
+                })
+                .collect(),
+        )
+    }
+
+    fn push_market_config_entries(
+        &self,
+        buffer: &Pubkey,
+        entries: Vec<EntryArgs>,
+    ) -> crate::Result<Vec<TransactionBuilder<C>>> {
+        let mut txns = Vec::default();
+        let mut chunk = Vec::<EntryArgs>::default();
+        for entry in entries {
+            chunk.push(entry);
+            if push_entries(self, buffer, chunk.clone()).transaction_size(true) <= PACKET_DATA_SIZE
+            {
+                continue;
+            }
+            let entry = chunk.pop().expect("must exist");
+            if chunk.is_empty() {
+                return Err(crate::Error::invalid_argument(format!(
+                    "the entry `{}` is too big to fit in a transaction",
+                    entry.key
+                )));
+            }
+            txns.push(push_entries(self, buffer, std::mem::take(&mut chunk)));
+            chunk.push(entry);
+        }
+        if !chunk.is_empty() {
+            txns.push(push_entries(self, buffer, chunk));
+        }
+        Ok(txns)
     }
 
     fn set_market_config_buffer_authority(
@@ -368,4 +1239,165 @@ where
                 buffer: *buffer,
             })
     }
+
+    fn propose_fee_structure_change(
+        &self,
+        store: &Pubkey,
+        market_token: &Pubkey,
+        changes: Vec<EntryArgs>,
+        voting_duration_secs: u32,
+    ) -> TransactionBuilder<C, Pubkey> {
+        let nonce = generate_nonce();
+        let proposal = self.find_fee_structure_proposal_address(store, market_token, &nonce);
+        self.store_transaction()
+            .anchor_args(instruction::ProposeFeeStructureChange {
+                nonce,
+                changes,
+                voting_duration_secs,
+            })
+            .anchor_accounts(accounts::ProposeFeeStructureChange {
+                authority: self.payer(),
+                store: *store,
+                market: self.find_market_address(store, market_token),
+                proposal,
+                system_program: system_program::ID,
+            })
+            .output(proposal)
+    }
+
+    fn vote_on_fee_structure(
+        &self,
+        store: &Pubkey,
+        proposal: &Pubkey,
+        vote_for: bool,
+    ) -> TransactionBuilder<C> {
+        let authority = self.payer();
+        self.store_transaction()
+            .anchor_args(instruction::VoteOnFeeStructure { vote_for })
+            .anchor_accounts(accounts::VoteOnFeeStructure {
+                authority,
+                store: *store,
+                proposal: *proposal,
+                vote: self.find_fee_structure_vote_address(proposal, &authority),
+                system_program: system_program::ID,
+            })
+    }
+
+    fn execute_fee_structure_proposal(
+        &self,
+        store: &Pubkey,
+        market_token: &Pubkey,
+        proposal: &Pubkey,
+    ) -> TransactionBuilder<C> {
+        self.store_transaction()
+            .anchor_args(instruction::ExecuteFeeStructureProposal {})
+            .anchor_accounts(accounts::ExecuteFeeStructureProposal {
+                authority: self.payer(),
+                store: *store,
+                market: self.find_market_address(store, market_token),
+                proposal: *proposal,
+            })
+    }
+}
+
+fn push_entries<C: Deref<Target = impl Signer> + Clone>(
+    client: &crate::Client<C>,
+    buffer: &Pubkey,
+    new_configs: Vec<EntryArgs>,
+) -> TransactionBuilder<C> {
+    client
+        .store_transaction()
+        .anchor_args(instruction::PushToMarketConfigBuffer { new_configs })
+        .anchor_accounts(accounts::PushToMarketConfigBuffer {
+            authority: client.payer(),
+            buffer: *buffer,
+            system_program: system_program::ID,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use anchor_client::{anchor_lang::AnchorDeserialize, solana_sdk::signature::Keypair};
+    use gmsol_solana_utils::cluster::Cluster;
+
+    use super::*;
+
+    #[test]
+    fn push_market_config_entries_in_chunks() -> crate::Result<()> {
+        let client = crate::Client::new(Cluster::Localnet, Arc::new(Keypair::new()))?;
+        let buffer = Pubkey::new_unique();
+        let entries = (0..100)
+            .map(|idx| EntryArgs {
+                key: MarketConfigKey::MinCollateralFactor.to_string(),
+                value: idx,
+            })
+            .collect::<Vec<_>>();
+
+        let txns = client.push_market_config_entries(&buffer, entries)?;
+        assert!(txns.len() > 1);
+        for txn in txns.iter() {
+            assert!(txn.transaction_size(true) <= PACKET_DATA_SIZE);
+        }
+
+        let pushed = txns
+            .iter()
+            .flat_map(|txn| txn.instructions())
+            .filter(|ix| ix.program_id == *client.store_program_id())
+            .map(|ix| {
+                let args =
+                    <instruction::PushToMarketConfigBuffer as AnchorDeserialize>::deserialize(
+                        &mut &ix.data[8..],
+                    )
+                    .expect("must be valid");
+                args.new_configs.len()
+            })
+            .sum::<usize>();
+        assert_eq!(pushed, 100);
+        Ok(())
+    }
+
+    #[test]
+    fn set_adl_parameters_updates_both_factors() -> crate::Result<()> {
+        let client = crate::Client::new(Cluster::Localnet, Arc::new(Keypair::new()))?;
+        let store = Pubkey::new_unique();
+        let market_token = Pubkey::new_unique();
+
+        for (is_long, trigger_key, target_key) in [
+            (
+                true,
+                MarketConfigKey::MaxPnlFactorForLongAdl,
+                MarketConfigKey::MinPnlFactorAfterLongAdl,
+            ),
+            (
+                false,
+                MarketConfigKey::MaxPnlFactorForShortAdl,
+                MarketConfigKey::MinPnlFactorAfterShortAdl,
+            ),
+        ] {
+            let txn = client.set_adl_parameters(&store, &market_token, is_long, &50, &30)?;
+            let updates = txn
+                .instructions()
+                .iter()
+                .filter(|ix| ix.program_id == *client.store_program_id())
+                .map(|ix| {
+                    let args = <instruction::UpdateMarketConfig as AnchorDeserialize>::deserialize(
+                        &mut &ix.data[8..],
+                    )
+                    .expect("must be valid");
+                    (args.key, args.value)
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(
+                updates,
+                [(trigger_key.to_string(), 50), (target_key.to_string(), 30)]
+            );
+        }
+
+        assert!(client
+            .set_adl_parameters(&store, &market_token, true, &30, &50)
+            .is_err());
+        Ok(())
+    }
 }
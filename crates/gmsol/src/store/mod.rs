@@ -31,6 +31,9 @@ pub mod user;
 /// GLV instructions.
 pub mod glv;
 
+/// Liquidity mining instructions.
+pub mod liquidity_mining;
+
 /// Events.
 #[cfg(feature = "decode")]
 pub mod events;
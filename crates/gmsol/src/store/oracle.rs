@@ -1,13 +1,14 @@
-use std::{future::Future, ops::Deref};
+use std::{future::Future, ops::Deref, time::Duration};
 
 use anchor_client::{
     anchor_lang::system_program,
     solana_sdk::{pubkey::Pubkey, signer::Signer},
 };
+use futures_util::Stream;
 use gmsol_solana_utils::transaction_builder::TransactionBuilder;
 use gmsol_store::{
     accounts, instruction,
-    states::{Oracle, PriceProviderKind},
+    states::{Oracle, PriceFeedStatus, PriceProviderKind},
 };
 use gmsol_utils::InitSpace;
 
@@ -41,6 +42,48 @@ pub trait OracleOps<C> {
         access_controller: &Pubkey,
         signed_report: &[u8],
     ) -> crate::Result<TransactionBuilder<C>>;
+
+    /// Get the status of the price feed.
+    fn get_price_feed_status(
+        &self,
+        store: &Pubkey,
+        token_map: &Pubkey,
+        price_feed: &Pubkey,
+    ) -> TransactionBuilder<C>;
+
+    /// Replace the `old_oracle` with the `new_oracle`.
+    fn rotate_oracle(
+        &self,
+        store: &Pubkey,
+        old_oracle: &Pubkey,
+        new_oracle: &Pubkey,
+    ) -> TransactionBuilder<C>;
+
+    /// Close a decommissioned oracle.
+    fn close_decommissioned_oracle(
+        &self,
+        store: &Pubkey,
+        oracle: &Pubkey,
+        receiver: Option<&Pubkey>,
+    ) -> TransactionBuilder<C>;
+
+    /// Initialize the price adjustment schedule of the given token.
+    fn initialize_price_adjustment_schedule(
+        &self,
+        store: &Pubkey,
+        token_map: &Pubkey,
+        token: &Pubkey,
+        factors: [u128; 24],
+    ) -> TransactionBuilder<C, Pubkey>;
+
+    /// Update the factor of the given UTC hour in the price adjustment schedule of the token.
+    fn update_price_adjustment_schedule(
+        &self,
+        store: &Pubkey,
+        token: &Pubkey,
+        hour: u8,
+        factor: u128,
+    ) -> TransactionBuilder<C>;
 }
 
 impl<C, S> OracleOps<C> for crate::Client<C>
@@ -148,4 +191,172 @@ where
                     .map_err(crate::Error::invalid_argument)?,
             }))
     }
+
+    fn get_price_feed_status(
+        &self,
+        store: &Pubkey,
+        token_map: &Pubkey,
+        price_feed: &Pubkey,
+    ) -> TransactionBuilder<C> {
+        self.store_transaction()
+            .anchor_accounts(accounts::GetPriceFeedStatus {
+                store: *store,
+                token_map: *token_map,
+                price_feed: *price_feed,
+            })
+            .anchor_args(instruction::GetPriceFeedStatus {})
+    }
+
+    fn rotate_oracle(
+        &self,
+        store: &Pubkey,
+        old_oracle: &Pubkey,
+        new_oracle: &Pubkey,
+    ) -> TransactionBuilder<C> {
+        self.store_transaction()
+            .anchor_accounts(accounts::RotateOracle {
+                authority: self.payer(),
+                store: *store,
+                old_oracle: *old_oracle,
+                new_oracle: *new_oracle,
+                event_authority: self.store_event_authority(),
+                program: *self.store_program_id(),
+            })
+            .anchor_args(instruction::RotateOracle {})
+    }
+
+    fn close_decommissioned_oracle(
+        &self,
+        store: &Pubkey,
+        oracle: &Pubkey,
+        receiver: Option<&Pubkey>,
+    ) -> TransactionBuilder<C> {
+        self.store_transaction()
+            .anchor_accounts(accounts::CloseDecommissionedOracle {
+                authority: self.payer(),
+                store: *store,
+                oracle: *oracle,
+                receiver: receiver.copied().unwrap_or(self.payer()),
+            })
+            .anchor_args(instruction::CloseDecommissionedOracle {})
+    }
+
+    fn initialize_price_adjustment_schedule(
+        &self,
+        store: &Pubkey,
+        token_map: &Pubkey,
+        token: &Pubkey,
+        factors: [u128; 24],
+    ) -> TransactionBuilder<C, Pubkey> {
+        let schedule = self.find_price_adjustment_schedule_address(store, token);
+        self.store_transaction()
+            .anchor_accounts(accounts::InitializePriceAdjustmentSchedule {
+                authority: self.payer(),
+                store: *store,
+                token_map: *token_map,
+                schedule,
+                system_program: system_program::ID,
+            })
+            .anchor_args(instruction::InitializePriceAdjustmentSchedule {
+                token: *token,
+                factors,
+            })
+            .output(schedule)
+    }
+
+    fn update_price_adjustment_schedule(
+        &self,
+        store: &Pubkey,
+        token: &Pubkey,
+        hour: u8,
+        factor: u128,
+    ) -> TransactionBuilder<C> {
+        self.store_transaction()
+            .anchor_accounts(accounts::UpdatePriceAdjustmentSchedule {
+                authority: self.payer(),
+                store: *store,
+                schedule: self.find_price_adjustment_schedule_address(store, token),
+            })
+            .anchor_args(instruction::UpdatePriceAdjustmentSchedule {
+                token: *token,
+                hour,
+                factor,
+            })
+    }
+}
+
+/// Price feed monitor.
+///
+/// Polls the status of the given price feeds and reports the feeds that are stale
+/// or whose age exceeds the alert threshold.
+pub struct MonitorPriceFeed<'a, C> {
+    client: &'a crate::Client<C>,
+    price_feeds: Vec<Pubkey>,
+    max_age_secs: Option<i64>,
+}
+
+impl<'a, C, S> MonitorPriceFeed<'a, C>
+where
+    C: Deref<Target = S> + Clone,
+    S: Signer,
+{
+    /// Create a new monitor for the given price feeds.
+    pub fn new(
+        client: &'a crate::Client<C>,
+        price_feeds: impl IntoIterator<Item = Pubkey>,
+    ) -> Self {
+        Self {
+            client,
+            price_feeds: price_feeds.into_iter().collect(),
+            max_age_secs: None,
+        }
+    }
+
+    /// Also report the price feeds whose age exceeds the given threshold,
+    /// even if they are not considered stale yet.
+    pub fn alert_on_age(mut self, max_age_secs: i64) -> Self {
+        self.max_age_secs = Some(max_age_secs);
+        self
+    }
+
+    /// Fetch the status of all the monitored price feeds.
+    pub async fn statuses(&self) -> crate::Result<Vec<(Pubkey, PriceFeedStatus)>> {
+        let mut statuses = Vec::with_capacity(self.price_feeds.len());
+        for price_feed in self.price_feeds.iter() {
+            let status = self.client.price_feed_status(price_feed).await?;
+            statuses.push((*price_feed, status));
+        }
+        Ok(statuses)
+    }
+
+    /// Check the monitored price feeds, returning the unhealthy ones.
+    pub async fn check(&self) -> crate::Result<Vec<(Pubkey, PriceFeedStatus)>> {
+        Ok(self
+            .statuses()
+            .await?
+            .into_iter()
+            .filter(|(_, status)| self.is_unhealthy(status))
+            .collect())
+    }
+
+    /// Check the monitored price feeds periodically.
+    ///
+    /// Each item of the stream is the result of a [`check`](Self::check).
+    pub fn watch(
+        &'a self,
+        interval: Duration,
+    ) -> impl Stream<Item = crate::Result<Vec<(Pubkey, PriceFeedStatus)>>> + 'a {
+        let interval = tokio::time::interval(interval);
+        futures_util::stream::unfold(interval, move |mut interval| async move {
+            interval.tick().await;
+            Some((self.check().await, interval))
+        })
+    }
+
+    fn is_unhealthy(&self, status: &PriceFeedStatus) -> bool {
+        status.is_stale
+            || self
+                .max_age_secs
+                .is_some_and(|max_age| status.age_seconds > max_age)
+    }
 }
@@ -44,6 +44,46 @@ pub trait StoreOps<C> {
         key: FactorKey,
         factor: Factor,
     ) -> TransactionBuilder<C>;
+
+    /// Set the minimum USD values of the long and short sides of deposits.
+    fn set_deposit_dust_threshold(
+        &self,
+        store: &Pubkey,
+        min_long_usd: Factor,
+        min_short_usd: Factor,
+    ) -> TransactionBuilder<C>;
+
+    /// Set the trusted Chainlink verifier entry at the given index.
+    /// Use the default pubkey as `verifier` to remove the entry.
+    fn set_trusted_chainlink_verifier(
+        &self,
+        store: &Pubkey,
+        index: u8,
+        verifier: &Pubkey,
+    ) -> TransactionBuilder<C>;
+
+    /// Take a snapshot of the global config.
+    ///
+    /// The `index` must be the [next config snapshot index](gmsol_store::states::Store::next_config_snapshot_index)
+    /// of the store.
+    fn take_config_snapshot(&self, store: &Pubkey, index: u64) -> TransactionBuilder<C>;
+
+    /// Get the config snapshot with the given index.
+    fn get_config_snapshot(&self, store: &Pubkey, index: u64) -> TransactionBuilder<C>;
+
+    /// Compare two config snapshots.
+    fn compare_config_snapshots(
+        &self,
+        store: &Pubkey,
+        index_a: u64,
+        index_b: u64,
+    ) -> TransactionBuilder<C>;
+
+    /// Pause or unpause the store.
+    fn set_store_paused(&self, store: &Pubkey, paused: bool) -> TransactionBuilder<C>;
+
+    /// Enable emergency mode.
+    fn enable_emergency_mode(&self, store: &Pubkey) -> TransactionBuilder<C>;
 }
 
 impl<C, S> StoreOps<C> for crate::Client<C>
@@ -148,4 +188,91 @@ where
             }),
         }
     }
+
+    fn set_deposit_dust_threshold(
+        &self,
+        store: &Pubkey,
+        min_long_usd: Factor,
+        min_short_usd: Factor,
+    ) -> TransactionBuilder<C> {
+        self.store_transaction()
+            .anchor_args(instruction::SetDepositDustThreshold {
+                min_long_usd,
+                min_short_usd,
+            })
+            .anchor_accounts(accounts::InsertConfig {
+                authority: self.payer(),
+                store: *store,
+            })
+    }
+
+    fn set_trusted_chainlink_verifier(
+        &self,
+        store: &Pubkey,
+        index: u8,
+        verifier: &Pubkey,
+    ) -> TransactionBuilder<C> {
+        self.store_transaction()
+            .anchor_args(instruction::SetTrustedChainlinkVerifier {
+                index,
+                verifier: *verifier,
+            })
+            .anchor_accounts(accounts::InsertConfig {
+                authority: self.payer(),
+                store: *store,
+            })
+    }
+
+    fn take_config_snapshot(&self, store: &Pubkey, index: u64) -> TransactionBuilder<C> {
+        self.store_transaction()
+            .anchor_args(instruction::TakeConfigSnapshot {})
+            .anchor_accounts(accounts::TakeConfigSnapshot {
+                authority: self.payer(),
+                store: *store,
+                snapshot: self.find_config_snapshot_address(store, index),
+                system_program: system_program::ID,
+            })
+    }
+
+    fn get_config_snapshot(&self, store: &Pubkey, index: u64) -> TransactionBuilder<C> {
+        self.store_transaction()
+            .anchor_args(instruction::GetConfigSnapshot { index })
+            .anchor_accounts(accounts::ReadConfigSnapshot {
+                store: *store,
+                snapshot: self.find_config_snapshot_address(store, index),
+            })
+    }
+
+    fn compare_config_snapshots(
+        &self,
+        store: &Pubkey,
+        index_a: u64,
+        index_b: u64,
+    ) -> TransactionBuilder<C> {
+        self.store_transaction()
+            .anchor_args(instruction::CompareConfigSnapshots { index_a, index_b })
+            .anchor_accounts(accounts::CompareConfigSnapshots {
+                store: *store,
+                snapshot_a: self.find_config_snapshot_address(store, index_a),
+                snapshot_b: self.find_config_snapshot_address(store, index_b),
+            })
+    }
+
+    fn set_store_paused(&self, store: &Pubkey, paused: bool) -> TransactionBuilder<C> {
+        self.store_transaction()
+            .anchor_args(instruction::SetStorePaused { paused })
+            .anchor_accounts(accounts::SetStorePaused {
+                authority: self.payer(),
+                store: *store,
+            })
+    }
+
+    fn enable_emergency_mode(&self, store: &Pubkey) -> TransactionBuilder<C> {
+        self.store_transaction()
+            .anchor_args(instruction::EnableEmergencyMode {})
+            .anchor_accounts(accounts::EnableEmergencyMode {
+                authority: self.payer(),
+                store: *store,
+            })
+    }
 }
@@ -5,12 +5,25 @@ use anchor_client::{
         system_program::{self, System},
         Id,
     },
-    solana_sdk::{pubkey::Pubkey, signer::Signer},
+    solana_sdk::{instruction::AccountMeta, pubkey::Pubkey, signer::Signer},
 };
 use anchor_spl::{associated_token::get_associated_token_address_with_program_id, token::Token};
 use gmsol_solana_utils::transaction_builder::TransactionBuilder;
 use gmsol_store::{accounts, instruction};
 
+/// A claimable account to close or recover.
+#[derive(Debug, Clone, Copy)]
+pub struct ClaimableAccountToClose {
+    /// The mint of the claimable account.
+    pub mint: Pubkey,
+    /// The owner of the claimable account.
+    pub owner: Pubkey,
+    /// The timestamp for which the claimable account was created.
+    pub timestamp: i64,
+    /// The address of the claimable account.
+    pub account: Pubkey,
+}
+
 /// Token accounts management for GMSOL.
 pub trait TokenAccountOps<C> {
     /// Prepare a claimable account.
@@ -34,6 +47,26 @@ pub trait TokenAccountOps<C> {
         account: &Pubkey,
     ) -> TransactionBuilder<C>;
 
+    /// Close the empty ones of the given claimable accounts.
+    /// Non-empty and uninitialized accounts are skipped.
+    fn close_empty_claimable_accounts(
+        &self,
+        store: &Pubkey,
+        claimable_accounts: &[ClaimableAccountToClose],
+    ) -> TransactionBuilder<C>;
+
+    /// Recover a claimable account that has been dormant for too long.
+    ///
+    /// The remaining balance is returned to the given market, and the rent is sent to
+    /// the payer if `receiver` is not provided.
+    fn recover_stuck_claimable_account(
+        &self,
+        store: &Pubkey,
+        market_token: &Pubkey,
+        claimable_account: &ClaimableAccountToClose,
+        receiver: Option<&Pubkey>,
+    ) -> TransactionBuilder<C>;
+
     /// Prepare associated token account.
     fn prepare_associated_token_account(
         &self,
@@ -41,6 +74,10 @@ pub trait TokenAccountOps<C> {
         token_program_id: &Pubkey,
         owner: Option<&Pubkey>,
     ) -> TransactionBuilder<C>;
+
+    /// Wrap native SOL into the WSOL associated token account of the payer,
+    /// creating the account if needed.
+    fn wrap_native(&self, lamports: u64) -> crate::Result<TransactionBuilder<C>>;
 }
 
 impl<C, S> TokenAccountOps<C> for crate::Client<C>
@@ -93,6 +130,64 @@ where
             })
     }
 
+    fn close_empty_claimable_accounts(
+        &self,
+        store: &Pubkey,
+        claimable_accounts: &[ClaimableAccountToClose],
+    ) -> TransactionBuilder<C> {
+        let authority = self.payer();
+        let timestamps = claimable_accounts
+            .iter()
+            .map(|account| account.timestamp)
+            .collect();
+        let remaining_accounts = claimable_accounts
+            .iter()
+            .flat_map(|account| {
+                [
+                    AccountMeta::new_readonly(account.mint, false),
+                    AccountMeta::new_readonly(account.owner, false),
+                    AccountMeta::new(account.account, false),
+                ]
+            })
+            .collect();
+        self.store_transaction()
+            .anchor_args(instruction::CloseEmptyClaimableAccounts { timestamps })
+            .anchor_accounts(accounts::CloseEmptyClaimableAccounts {
+                authority,
+                store: *store,
+                token_program: Token::id(),
+            })
+            .accounts(remaining_accounts)
+    }
+
+    fn recover_stuck_claimable_account(
+        &self,
+        store: &Pubkey,
+        market_token: &Pubkey,
+        claimable_account: &ClaimableAccountToClose,
+        receiver: Option<&Pubkey>,
+    ) -> TransactionBuilder<C> {
+        let authority = self.payer();
+        let mint = &claimable_account.mint;
+        self.store_transaction()
+            .anchor_args(instruction::RecoverStuckClaimableAccount {
+                timestamp: claimable_account.timestamp,
+            })
+            .anchor_accounts(accounts::RecoverStuckClaimableAccount {
+                authority,
+                store: *store,
+                market: self.find_market_address(store, market_token),
+                mint: *mint,
+                owner: claimable_account.owner,
+                account: claimable_account.account,
+                vault: self.find_market_vault_address(store, mint),
+                receiver: receiver.copied().unwrap_or(authority),
+                token_program: Token::id(),
+                event_authority: self.store_event_authority(),
+                program: *self.store_program_id(),
+            })
+    }
+
     fn prepare_associated_token_account(
         &self,
         mint: &Pubkey,
@@ -114,4 +209,28 @@ where
             })
             .anchor_args(instruction::PrepareAssociatedTokenAccount {})
     }
+
+    fn wrap_native(&self, lamports: u64) -> crate::Result<TransactionBuilder<C>> {
+        use anchor_client::solana_sdk::system_instruction::transfer;
+        use anchor_spl::token::spl_token::{instruction::sync_native, native_mint};
+
+        if lamports == 0 {
+            return Err(crate::Error::invalid_argument("wrapping amount is zero"));
+        }
+        let payer = self.payer();
+        let token_program_id = Token::id();
+        let account = get_associated_token_address_with_program_id(
+            &payer,
+            &native_mint::ID,
+            &token_program_id,
+        );
+        let sync = sync_native(&token_program_id, &account).map_err(crate::Error::unknown)?;
+        let wrap = self
+            .store_transaction()
+            .pre_instruction(transfer(&payer, &account, lamports))
+            .pre_instruction(sync);
+        Ok(self
+            .prepare_associated_token_account(&native_mint::ID, &token_program_id, None)
+            .merge(wrap))
+    }
 }
@@ -1,13 +1,16 @@
 use std::{future::Future, ops::Deref};
 
 use anchor_client::{
-    anchor_lang::system_program,
-    solana_sdk::{pubkey::Pubkey, signer::Signer},
+    anchor_lang::{system_program, Id},
+    solana_sdk::{instruction::AccountMeta, pubkey::Pubkey, signer::Signer},
 };
 use gmsol_solana_utils::transaction_builder::TransactionBuilder;
 use gmsol_store::{
     accounts, instruction,
-    states::{PriceProviderKind, UpdateTokenConfigParams},
+    states::{
+        BridgePremiumConfig, Chainlink, FeedConfigInfo, PriceProviderKind, TokenConfigInfo,
+        UpdateTokenConfigParams,
+    },
 };
 
 use crate::utils::view;
@@ -20,6 +23,7 @@ pub struct TokenConfig {
     decimals: u8,
     precision: u8,
     expected_provider: PriceProviderKind,
+    feeds: Vec<FeedConfigInfo>,
 }
 
 impl TokenConfig {
@@ -47,6 +51,29 @@ impl TokenConfig {
     pub fn is_enabled(&self) -> bool {
         self.is_enabled
     }
+
+    /// Get the configured price feeds.
+    pub fn feeds(&self) -> &[FeedConfigInfo] {
+        &self.feeds
+    }
+}
+
+impl TryFrom<TokenConfigInfo> for TokenConfig {
+    type Error = crate::Error;
+
+    fn try_from(info: TokenConfigInfo) -> crate::Result<Self> {
+        Ok(Self {
+            name: info.name,
+            is_enabled: info.is_enabled,
+            decimals: info.token_decimals,
+            precision: info.precision,
+            expected_provider: info
+                .expected_provider
+                .try_into()
+                .map_err(crate::Error::unknown)?,
+            feeds: info.feeds,
+        })
+    }
 }
 
 /// Token config management for GMSOL.
@@ -104,6 +131,52 @@ pub trait TokenConfigOps<C> {
         provider: PriceProviderKind,
     ) -> TransactionBuilder<C>;
 
+    /// Set feed config of the given provider for the given token.
+    ///
+    /// The `price_multiplier` is given as `(multiplier, denominator)`, `None` for no scaling.
+    #[allow(clippy::too_many_arguments)]
+    fn set_feed_config(
+        &self,
+        store: &Pubkey,
+        token_map: &Pubkey,
+        token: &Pubkey,
+        provider: PriceProviderKind,
+        feed: &Pubkey,
+        timestamp_adjustment: u32,
+        price_band: Option<(u32, u32)>,
+        price_multiplier: Option<(u32, u32)>,
+        bridge_premium: Option<BridgePremiumConfig>,
+    ) -> TransactionBuilder<C>;
+
+    /// Update the bridge premium factor of the given bridged token.
+    fn update_bridge_premium(
+        &self,
+        store: &Pubkey,
+        token_map: &Pubkey,
+        token: &Pubkey,
+        factor: i128,
+    ) -> TransactionBuilder<C>;
+
+    /// Set the max deviation from the last recorded price of the given token.
+    fn set_max_price_deviation(
+        &self,
+        store: &Pubkey,
+        token_map: &Pubkey,
+        token: &Pubkey,
+        max_price_deviation_bps: u16,
+    ) -> TransactionBuilder<C>;
+
+    /// Initialize the last oracle price account of the given token.
+    fn initialize_last_oracle_price(
+        &self,
+        store: &Pubkey,
+        token_map: &Pubkey,
+        token: &Pubkey,
+    ) -> TransactionBuilder<C, Pubkey>;
+
+    /// Clear the last recorded price of the given token.
+    fn clear_last_oracle_price(&self, store: &Pubkey, token: &Pubkey) -> TransactionBuilder<C>;
+
     /// Get the name for the given token.
     fn token_name(&self, token_map: &Pubkey, token: &Pubkey) -> TransactionBuilder<C>;
 
@@ -116,6 +189,9 @@ pub trait TokenConfigOps<C> {
     /// Check if the config of the given token is enbaled.
     fn is_token_config_enabled(&self, token_map: &Pubkey, token: &Pubkey) -> TransactionBuilder<C>;
 
+    /// Get the tokens whose configs are disabled.
+    fn get_disabled_tokens(&self, token_map: &Pubkey) -> TransactionBuilder<C>;
+
     /// Get expected provider for the given token.
     fn token_expected_provider(&self, token_map: &Pubkey, token: &Pubkey) -> TransactionBuilder<C>;
 
@@ -135,12 +211,40 @@ pub trait TokenConfigOps<C> {
         provider: PriceProviderKind,
     ) -> TransactionBuilder<C>;
 
+    /// Get the timestamp of the price reported by the given feed, with the timestamp
+    /// adjustment of the given token and provider applied.
+    fn get_adjusted_timestamp(
+        &self,
+        token_map: &Pubkey,
+        token: &Pubkey,
+        provider: PriceProviderKind,
+        feed: &Pubkey,
+    ) -> TransactionBuilder<C>;
+
+    /// Get the full config of the given token.
+    fn get_token_config(&self, token_map: &Pubkey, token: &Pubkey) -> TransactionBuilder<C>;
+
     /// Get basic token config.
     fn token_config(
         &self,
         token_map: &Pubkey,
         token: &Pubkey,
     ) -> impl Future<Output = crate::Result<TokenConfig>>;
+
+    /// Fetch the tokens whose configs are disabled.
+    fn disabled_tokens(
+        &self,
+        token_map: &Pubkey,
+    ) -> impl Future<Output = crate::Result<Vec<Pubkey>>>;
+
+    /// Fetch the adjusted timestamp of the price reported by the given feed.
+    fn adjusted_timestamp(
+        &self,
+        token_map: &Pubkey,
+        token: &Pubkey,
+        provider: PriceProviderKind,
+        feed: &Pubkey,
+    ) -> impl Future<Output = crate::Result<i64>>;
 }
 
 impl<C, S> TokenConfigOps<C> for crate::Client<C>
@@ -262,6 +366,111 @@ where
             })
     }
 
+    fn set_feed_config(
+        &self,
+        store: &Pubkey,
+        token_map: &Pubkey,
+        token: &Pubkey,
+        provider: PriceProviderKind,
+        feed: &Pubkey,
+        timestamp_adjustment: u32,
+        price_band: Option<(u32, u32)>,
+        price_multiplier: Option<(u32, u32)>,
+        bridge_premium: Option<BridgePremiumConfig>,
+    ) -> TransactionBuilder<C> {
+        let authority = self.payer();
+        let (min_price, max_price) = price_band.unwrap_or_default();
+        let (price_multiplier, price_multiplier_denominator) = price_multiplier.unwrap_or((1, 1));
+        self.store_transaction()
+            .anchor_accounts(accounts::SetFeedConfig {
+                authority,
+                store: *store,
+                token_map: *token_map,
+            })
+            .anchor_args(instruction::SetFeedConfig {
+                token: *token,
+                provider: provider as u8,
+                feed: *feed,
+                timestamp_adjustment,
+                min_price,
+                max_price,
+                price_multiplier,
+                price_multiplier_denominator,
+                bridge_premium,
+            })
+    }
+
+    fn update_bridge_premium(
+        &self,
+        store: &Pubkey,
+        token_map: &Pubkey,
+        token: &Pubkey,
+        factor: i128,
+    ) -> TransactionBuilder<C> {
+        let authority = self.payer();
+        self.store_transaction()
+            .anchor_accounts(accounts::UpdateBridgePremium {
+                authority,
+                store: *store,
+                token_map: *token_map,
+            })
+            .anchor_args(instruction::UpdateBridgePremium {
+                token: *token,
+                factor,
+            })
+    }
+
+    fn set_max_price_deviation(
+        &self,
+        store: &Pubkey,
+        token_map: &Pubkey,
+        token: &Pubkey,
+        max_price_deviation_bps: u16,
+    ) -> TransactionBuilder<C> {
+        let authority = self.payer();
+        self.store_transaction()
+            .anchor_accounts(accounts::SetMaxPriceDeviation {
+                authority,
+                store: *store,
+                token_map: *token_map,
+            })
+            .anchor_args(instruction::SetMaxPriceDeviation {
+                token: *token,
+                max_price_deviation_bps,
+            })
+    }
+
+    fn initialize_last_oracle_price(
+        &self,
+        store: &Pubkey,
+        token_map: &Pubkey,
+        token: &Pubkey,
+    ) -> TransactionBuilder<C, Pubkey> {
+        let authority = self.payer();
+        let last_price = self.find_last_oracle_price_address(store, token);
+        self.store_transaction()
+            .anchor_accounts(accounts::InitializeLastOraclePrice {
+                authority,
+                store: *store,
+                token_map: *token_map,
+                last_price,
+                system_program: system_program::ID,
+            })
+            .anchor_args(instruction::InitializeLastOraclePrice { token: *token })
+            .output(last_price)
+    }
+
+    fn clear_last_oracle_price(&self, store: &Pubkey, token: &Pubkey) -> TransactionBuilder<C> {
+        let authority = self.payer();
+        self.store_transaction()
+            .anchor_accounts(accounts::ClearLastOraclePrice {
+                authority,
+                store: *store,
+                last_price: self.find_last_oracle_price_address(store, token),
+            })
+            .anchor_args(instruction::ClearLastOraclePrice { token: *token })
+    }
+
     fn token_name(&self, token_map: &Pubkey, token: &Pubkey) -> TransactionBuilder<C> {
         self.store_transaction()
             .anchor_args(instruction::TokenName { token: *token })
@@ -294,6 +503,14 @@ where
             })
     }
 
+    fn get_disabled_tokens(&self, token_map: &Pubkey) -> TransactionBuilder<C> {
+        self.store_transaction()
+            .anchor_args(instruction::GetDisabledTokens {})
+            .anchor_accounts(accounts::ReadTokenMap {
+                token_map: *token_map,
+            })
+    }
+
     fn token_expected_provider(&self, token_map: &Pubkey, token: &Pubkey) -> TransactionBuilder<C> {
         self.store_transaction()
             .anchor_args(instruction::TokenExpectedProvider { token: *token })
@@ -334,38 +551,65 @@ where
             })
     }
 
+    fn get_adjusted_timestamp(
+        &self,
+        token_map: &Pubkey,
+        token: &Pubkey,
+        provider: PriceProviderKind,
+        feed: &Pubkey,
+    ) -> TransactionBuilder<C> {
+        let chainlink_program =
+            matches!(provider, PriceProviderKind::Chainlink).then(Chainlink::id);
+        self.store_transaction()
+            .anchor_args(instruction::GetAdjustedTimestamp {
+                token: *token,
+                provider: provider as u8,
+            })
+            .anchor_accounts(accounts::ReadTokenFeed {
+                token_map: *token_map,
+                chainlink_program,
+            })
+            .accounts(vec![AccountMeta::new_readonly(*feed, false)])
+    }
+
+    fn get_token_config(&self, token_map: &Pubkey, token: &Pubkey) -> TransactionBuilder<C> {
+        self.store_transaction()
+            .anchor_args(instruction::GetTokenConfig { token: *token })
+            .anchor_accounts(accounts::ReadTokenMap {
+                token_map: *token_map,
+            })
+    }
+
     async fn token_config(&self, token_map: &Pubkey, token: &Pubkey) -> crate::Result<TokenConfig> {
         let client = self.store_program().rpc();
-        let name = self
-            .token_name(token_map, token)
-            .signed_transaction_with_options(true, None)
-            .await?;
-        let token_decimals = self
-            .token_decimals(token_map, token)
-            .signed_transaction_with_options(true, None)
-            .await?;
-        let precision = self
-            .token_precision(token_map, token)
+        let tx = self
+            .get_token_config(token_map, token)
             .signed_transaction_with_options(true, None)
             .await?;
-        let expected_provider = self
-            .token_expected_provider(token_map, token)
+        view::<TokenConfigInfo>(&client, &tx).await?.try_into()
+    }
+
+    async fn disabled_tokens(&self, token_map: &Pubkey) -> crate::Result<Vec<Pubkey>> {
+        let client = self.store_program().rpc();
+        let tx = self
+            .get_disabled_tokens(token_map)
             .signed_transaction_with_options(true, None)
             .await?;
-        let is_enabled = self
-            .is_token_config_enabled(token_map, token)
+        view(&client, &tx).await
+    }
+
+    async fn adjusted_timestamp(
+        &self,
+        token_map: &Pubkey,
+        token: &Pubkey,
+        provider: PriceProviderKind,
+        feed: &Pubkey,
+    ) -> crate::Result<i64> {
+        let client = self.store_program().rpc();
+        let tx = self
+            .get_adjusted_timestamp(token_map, token, provider, feed)
             .signed_transaction_with_options(true, None)
             .await?;
-
-        Ok(TokenConfig {
-            name: view(&client, &name).await?,
-            decimals: view(&client, &token_decimals).await?,
-            precision: view(&client, &precision).await?,
-            expected_provider: view::<u8>(&client, &expected_provider)
-                .await?
-                .try_into()
-                .map_err(crate::Error::unknown)?,
-            is_enabled: view(&client, &is_enabled).await?,
-        })
+        view(&client, &tx).await
     }
 }
@@ -4,13 +4,14 @@ use anchor_client::{
     anchor_lang::system_program,
     solana_sdk::{pubkey::Pubkey, signer::Signer},
 };
+use anchor_spl::{associated_token::get_associated_token_address, token::spl_token::native_mint};
 use gmsol_solana_utils::transaction_builder::TransactionBuilder;
 use gmsol_store::{
     accounts, instruction,
-    states::user::{ReferralCodeBytes, ReferralCodeV2, UserHeader},
+    states::user::{ReferralCodeBytes, ReferralCodeV2, ReferralRewardVault, UserHeader},
 };
 
-use crate::utils::ZeroCopy;
+use crate::utils::{fix_optional_account_metas, ZeroCopy};
 
 /// User Account Operations.
 pub trait UserOps<C> {
@@ -48,12 +49,58 @@ pub trait UserOps<C> {
     ) -> impl Future<Output = crate::Result<TransactionBuilder<C>>>;
 
     /// Accept referral code transfer.
+    ///
+    /// The transfer reward will be claimed if the referral reward vault exists.
     fn accept_referral_code(
         &self,
         store: &Pubkey,
         code: ReferralCodeBytes,
         hint_owner: Option<Pubkey>,
     ) -> impl Future<Output = crate::Result<TransactionBuilder<C>>>;
+
+    /// Initialize the referral reward vault and fund it with the given amount of lamports.
+    fn initialize_referral_reward_vault(
+        &self,
+        store: &Pubkey,
+        amount: u64,
+    ) -> TransactionBuilder<C>;
+
+    /// Set the reward (in lamports) for accepting a referral code transfer,
+    /// and the min paid fee value of the new owner to be eligible for it.
+    fn set_referral_transfer_reward(
+        &self,
+        store: &Pubkey,
+        amount: u64,
+        min_paid_fee_value: u128,
+    ) -> TransactionBuilder<C>;
+
+    /// Fund the referral reward vault with the fees in wrapped native token
+    /// claimed from the given market.
+    ///
+    /// The payer must be the fee receiver of the store.
+    fn fund_referral_reward_vault_from_fees(
+        &self,
+        store: &Pubkey,
+        market_token: &Pubkey,
+    ) -> TransactionBuilder<C>;
+
+    /// Get the referral stats of the user account of the given owner.
+    fn get_referral_stats(&self, store: &Pubkey, owner: &Pubkey) -> TransactionBuilder<C>;
+
+    /// Get the activity of the user account of the given owner.
+    fn get_user_activity(&self, store: &Pubkey, owner: &Pubkey) -> TransactionBuilder<C>;
+
+    /// Freeze the user account of the given owner.
+    fn freeze_user_account(
+        &self,
+        store: &Pubkey,
+        user_address: &Pubkey,
+        reason_hash: [u8; 32],
+    ) -> TransactionBuilder<C>;
+
+    /// Unfreeze the user account of the given owner.
+    fn unfreeze_user_account(&self, store: &Pubkey, user_address: &Pubkey)
+        -> TransactionBuilder<C>;
 }
 
 impl<C: Deref<Target = impl Signer> + Clone> UserOps<C> for crate::Client<C> {
@@ -230,16 +277,140 @@ impl<C: Deref<Target = impl Signer> + Clone> UserOps<C> for crate::Client<C> {
 
         let user = self.find_user_address(store, &owner);
 
+        let reward_vault = self.find_referral_reward_vault_address(store);
+        let reward_vault = self
+            .account::<ZeroCopy<ReferralRewardVault>>(&reward_vault)
+            .await?
+            .map(|_| reward_vault);
+
         let rpc = self
             .store_transaction()
-            .anchor_accounts(accounts::AcceptReferralCode {
-                next_owner,
-                store: *store,
-                user,
-                referral_code,
-                receiver_user,
-            })
+            .accounts(fix_optional_account_metas(
+                accounts::AcceptReferralCode {
+                    next_owner,
+                    store: *store,
+                    user,
+                    referral_code,
+                    receiver_user,
+                    reward_vault,
+                },
+                &crate::program_ids::DEFAULT_GMSOL_STORE_ID,
+                self.store_program_id(),
+            ))
             .anchor_args(instruction::AcceptReferralCode {});
         Ok(rpc)
     }
+
+    fn initialize_referral_reward_vault(
+        &self,
+        store: &Pubkey,
+        amount: u64,
+    ) -> TransactionBuilder<C> {
+        self.store_transaction()
+            .anchor_accounts(accounts::InitializeReferralRewardVault {
+                authority: self.payer(),
+                store: *store,
+                reward_vault: self.find_referral_reward_vault_address(store),
+                system_program: system_program::ID,
+            })
+            .anchor_args(instruction::InitializeReferralRewardVault { amount })
+    }
+
+    fn set_referral_transfer_reward(
+        &self,
+        store: &Pubkey,
+        amount: u64,
+        min_paid_fee_value: u128,
+    ) -> TransactionBuilder<C> {
+        self.store_transaction()
+            .anchor_accounts(accounts::SetReferralTransferReward {
+                authority: self.payer(),
+                store: *store,
+                reward_vault: self.find_referral_reward_vault_address(store),
+            })
+            .anchor_args(instruction::SetReferralTransferReward {
+                amount,
+                min_paid_fee_value,
+            })
+    }
+
+    fn fund_referral_reward_vault_from_fees(
+        &self,
+        store: &Pubkey,
+        market_token: &Pubkey,
+    ) -> TransactionBuilder<C> {
+        let reward_vault = self.find_referral_reward_vault_address(store);
+        self.store_transaction()
+            .anchor_accounts(accounts::FundReferralRewardVaultFromFees {
+                authority: self.payer(),
+                store: *store,
+                reward_vault,
+                market: self.find_market_address(store, market_token),
+                token_mint: native_mint::ID,
+                vault: self.find_market_vault_address(store, &native_mint::ID),
+                escrow: get_associated_token_address(&reward_vault, &native_mint::ID),
+                system_program: system_program::ID,
+                token_program: anchor_spl::token::ID,
+                associated_token_program: anchor_spl::associated_token::ID,
+                event_authority: self.store_event_authority(),
+                program: *self.store_program_id(),
+            })
+            .anchor_args(instruction::FundReferralRewardVaultFromFees {})
+    }
+
+    fn get_referral_stats(&self, store: &Pubkey, owner: &Pubkey) -> TransactionBuilder<C> {
+        self.store_transaction()
+            .anchor_accounts(accounts::ReadUser {
+                store: *store,
+                user: self.find_user_address(store, owner),
+            })
+            .anchor_args(instruction::GetReferralStats { owner: *owner })
+    }
+
+    fn get_user_activity(&self, store: &Pubkey, owner: &Pubkey) -> TransactionBuilder<C> {
+        self.store_transaction()
+            .anchor_accounts(accounts::ReadUser {
+                store: *store,
+                user: self.find_user_address(store, owner),
+            })
+            .anchor_args(instruction::GetUserActivity { owner: *owner })
+    }
+
+    fn freeze_user_account(
+        &self,
+        store: &Pubkey,
+        user_address: &Pubkey,
+        reason_hash: [u8; 32],
+    ) -> TransactionBuilder<C> {
+        self.store_transaction()
+            .anchor_accounts(accounts::FreezeUserAccount {
+                authority: self.payer(),
+                store: *store,
+                user: self.find_user_address(store, user_address),
+                event_authority: self.store_event_authority(),
+                program: *self.store_program_id(),
+            })
+            .anchor_args(instruction::FreezeUserAccount {
+                user_address: *user_address,
+                reason_hash,
+            })
+    }
+
+    fn unfreeze_user_account(
+        &self,
+        store: &Pubkey,
+        user_address: &Pubkey,
+    ) -> TransactionBuilder<C> {
+        self.store_transaction()
+            .anchor_accounts(accounts::FreezeUserAccount {
+                authority: self.payer(),
+                store: *store,
+                user: self.find_user_address(store, user_address),
+                event_authority: self.store_event_authority(),
+                program: *self.store_program_id(),
+            })
+            .anchor_args(instruction::UnfreezeUserAccount {
+                user_address: *user_address,
+            })
+    }
 }
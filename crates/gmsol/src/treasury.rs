@@ -171,11 +171,13 @@ pub trait TreasuryOps<C> {
     ) -> impl Future<Output = crate::Result<TransactionBuilder<C, Pubkey>>>;
 
     /// Cancel a swap.
+    ///
+    /// The `hint` is in the form of `(market, swap_in_token, swap_out_token)`.
     fn cancel_treasury_swap(
         &self,
         store: &Pubkey,
         order: &Pubkey,
-        hint: Option<(&Pubkey, &Pubkey)>,
+        hint: Option<(&Pubkey, &Pubkey, &Pubkey)>,
     ) -> impl Future<Output = crate::Result<TransactionBuilder<C>>>;
 }
 
@@ -815,16 +817,19 @@ where
         &self,
         store: &Pubkey,
         order: &Pubkey,
-        hint: Option<(&Pubkey, &Pubkey)>,
+        hint: Option<(&Pubkey, &Pubkey, &Pubkey)>,
     ) -> crate::Result<TransactionBuilder<C>> {
         let config = self.find_treasury_config_address(store);
         let receiver = self.find_treasury_receiver_address(&config);
         let user = self.find_user_address(store, &receiver);
 
-        let (swap_in_token, swap_out_token) = match hint {
-            Some((swap_in_token, swap_out_token)) => (*swap_in_token, *swap_out_token),
+        let (market, swap_in_token, swap_out_token) = match hint {
+            Some((market, swap_in_token, swap_out_token)) => {
+                (*market, *swap_in_token, *swap_out_token)
+            }
             None => {
                 let order = self.order(order).await?;
+                let market = *order.header().market();
                 let swap_in_token =
                     order.tokens().initial_collateral().token().ok_or_else(|| {
                         crate::Error::invalid_argument("invalid swap order: missing swap in token")
@@ -834,7 +839,7 @@ where
                     order.tokens().final_output_token().token().ok_or_else(|| {
                         crate::Error::invalid_argument("invalid swap order: missing swap out token")
                     })?;
-                (swap_in_token, swap_out_token)
+                (market, swap_in_token, swap_out_token)
             }
         };
 
@@ -884,7 +889,8 @@ where
                 token_program: token_program_id,
                 associated_token_program: anchor_spl::associated_token::ID,
                 system_program: system_program::ID,
-            });
+            })
+            .accounts(vec![AccountMeta::new(market, false)]);
 
         Ok(prepare.merge(cancel))
     }
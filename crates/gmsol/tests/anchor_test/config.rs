@@ -0,0 +1,60 @@
+use gmsol::store::store_ops::StoreOps;
+use gmsol_store::states::{config_snapshot::ConfigValue, FactorKey};
+
+use crate::anchor_test::setup::{current_deployment, Deployment};
+
+#[tokio::test]
+async fn config_snapshot() -> eyre::Result<()> {
+    let deployment = current_deployment().await?;
+    let _guard = deployment.use_accounts().await?;
+    let span = tracing::info_span!("config_snapshot");
+    let _enter = span.enter();
+
+    let store = &deployment.store;
+    let keeper = deployment.user_client(Deployment::DEFAULT_KEEPER)?;
+
+    let index_a = keeper.store(store).await?.next_config_snapshot_index();
+    let signature = keeper
+        .take_config_snapshot(store, index_a)
+        .send_without_preflight()
+        .await?;
+    tracing::info!(%signature, index=%index_a, "took config snapshot");
+
+    // A zero-amount side is never considered as dust, so this does not affect other tests.
+    let key = FactorKey::MinDepositLongUsd;
+    let signature = keeper
+        .insert_factor(store, key, 1)
+        .send_without_preflight()
+        .await?;
+    tracing::info!(%signature, "inserted factor: {key}");
+
+    let index_b = index_a + 1;
+    let signature = keeper
+        .take_config_snapshot(store, index_b)
+        .send_without_preflight()
+        .await?;
+    tracing::info!(%signature, index=%index_b, "took config snapshot");
+
+    let signature = keeper
+        .insert_factor(store, key, 0)
+        .send_without_preflight()
+        .await?;
+    tracing::info!(%signature, "reset factor: {key}");
+
+    let snapshot = keeper.config_snapshot(store, index_b).await?;
+    assert_eq!(snapshot.index, index_b);
+    let entry = snapshot
+        .entries
+        .iter()
+        .find(|entry| entry.key == key.to_string())
+        .expect("must exist");
+    assert!(entry.value == ConfigValue::Factor(1));
+
+    let diff = keeper.config_snapshot_diff(store, index_a, index_b).await?;
+    assert_eq!(diff.len(), 1);
+    assert_eq!(diff[0].key, key.to_string());
+    assert!(diff[0].previous == ConfigValue::Factor(0));
+    assert!(diff[0].current == ConfigValue::Factor(1));
+
+    Ok(())
+}
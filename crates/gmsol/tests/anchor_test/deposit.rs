@@ -1,4 +1,12 @@
-use gmsol::{exchange::ExchangeOps, store::market::MarketOps, types::MarketConfigKey};
+use std::time::Duration;
+
+use gmsol::{
+    chainlink,
+    constants::MARKET_USD_UNIT,
+    exchange::ExchangeOps,
+    store::{config::ConfigOps, market::MarketOps, store_ops::StoreOps},
+    types::{AmountKey, MarketConfigKey},
+};
 use gmsol_store::CoreError;
 use tracing::Instrument;
 
@@ -99,6 +107,58 @@ async fn single_token_pool_deposit() -> eyre::Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn native_token_deposit() -> eyre::Result<()> {
+    let deployment = setup::current_deployment().await?;
+    let _guard = deployment.use_accounts().await?;
+    let span = tracing::info_span!("native_token_deposit");
+    let _enter = span.enter();
+
+    let client = deployment.locked_user_client().await?;
+    let keeper = deployment.user_client(Deployment::DEFAULT_KEEPER)?;
+    let store = &deployment.store;
+    let oracle = &deployment.oracle();
+    let market_token = deployment.market_token("SOL", "WSOL", "USDG").unwrap();
+    let wsol = deployment.token("WSOL").expect("must exist");
+
+    let wsol_before = deployment
+        .get_user_ata_amount(&wsol.address, None)
+        .await?
+        .unwrap_or(0);
+
+    let amount = 10_000_000;
+
+    // Create a deposit funded directly from native SOL.
+    let (rpc, deposit) = client
+        .create_deposit(store, market_token)
+        .long_token(amount, None, None)
+        .should_wrap_native_token(true)
+        .build_with_address()
+        .await?;
+    let signature = rpc.send().await?;
+    tracing::info!(%deposit, %signature, "created a deposit with native SOL");
+
+    let wsol_after_creation = deployment
+        .get_user_ata_amount(&wsol.address, None)
+        .await?
+        .expect("must exist");
+    let token_escrow = deployment
+        .get_ata_amount(&wsol.address, &deposit)
+        .await?
+        .expect("token escrow must exist");
+    assert_eq!(wsol_after_creation, wsol_before);
+    assert_eq!(token_escrow, amount);
+
+    // Execute.
+    let mut builder = keeper.execute_deposit(store, oracle, &deposit, true);
+    deployment
+        .execute_with_pyth(&mut builder, None, true, true)
+        .instrument(tracing::info_span!("execute", %deposit))
+        .await?;
+
+    Ok(())
+}
+
 #[tokio::test]
 async fn balanced_pool_deposit() -> eyre::Result<()> {
     let deployment = setup::current_deployment().await?;
@@ -290,3 +350,452 @@ async fn first_deposit() -> eyre::Result<()> {
 
     Ok(())
 }
+
+#[tokio::test]
+async fn pre_validate_deposit() -> eyre::Result<()> {
+    let deployment = setup::current_deployment().await?;
+    let _guard = deployment.use_accounts().await?;
+    let span = tracing::info_span!("pre_validate_deposit");
+    let _enter = span.enter();
+
+    let client = deployment.locked_user_client().await?;
+    let keeper = deployment.user_client(Deployment::DEFAULT_KEEPER)?;
+    let store = &deployment.store;
+    let oracle = &deployment.oracle();
+    let market_token = deployment.market_token("SOL", "WSOL", "USDG").unwrap();
+
+    let amount = 1_000_000;
+    deployment
+        .mint_or_transfer_to("USDG", &client.payer(), amount)
+        .await?;
+
+    let (rpc, deposit) = client
+        .create_deposit(store, market_token)
+        .short_token(amount, None, None)
+        .build_with_address()
+        .await?;
+    let signature = rpc.send().await?;
+    tracing::info!(%deposit, %signature, "created deposit");
+
+    // A zero execution fee can never cover the gas.
+    let report = keeper
+        .pre_validate_deposit(store, oracle, &deposit, 0)
+        .await?;
+    assert!(!report.execution_fee_sufficient);
+    assert!(report.market_enabled);
+    assert!(report.escrow_funded);
+    assert!(report.estimated_gas_lamports > 0);
+    assert!(!report.would_succeed);
+
+    // The oracle buffer is cleared after each execution, so only the fee check is expected to change.
+    let report = keeper
+        .pre_validate_deposit(store, oracle, &deposit, report.estimated_gas_lamports)
+        .await?;
+    assert!(report.execution_fee_sufficient);
+    assert!(!report.oracle_freshness_ok);
+    assert!(!report.would_succeed);
+
+    let mut builder = keeper.execute_deposit(store, oracle, &deposit, true);
+    deployment
+        .execute_with_pyth(&mut builder, None, true, true)
+        .instrument(tracing::info_span!("execute", %deposit))
+        .await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn dust_deposit() -> eyre::Result<()> {
+    let deployment = setup::current_deployment().await?;
+    let _guard = deployment.use_accounts().await?;
+    let span = tracing::info_span!("dust_deposit");
+    let _enter = span.enter();
+
+    let client = deployment.locked_user_client().await?;
+    let keeper = deployment.user_client(Deployment::DEFAULT_KEEPER)?;
+    let store = &deployment.store;
+    let oracle = &deployment.oracle();
+    let market_token = deployment.market_token("SOL", "WSOL", "USDG").unwrap();
+    let usdg = deployment.token("USDG").expect("must exist");
+
+    // $5 and $15 deposits.
+    let dust_amount = 500_000_000;
+    let amount = 1_500_000_000;
+    deployment
+        .mint_or_transfer_to("USDG", &client.payer(), dust_amount + amount)
+        .await?;
+
+    let (rpc, dust_deposit) = client
+        .create_deposit(store, market_token)
+        .short_token(dust_amount, None, None)
+        .build_with_address()
+        .await?;
+    let signature = rpc.send().await?;
+    tracing::info!(deposit=%dust_deposit, %signature, "created dust deposit");
+
+    let (rpc, deposit) = client
+        .create_deposit(store, market_token)
+        .short_token(amount, None, None)
+        .build_with_address()
+        .await?;
+    let signature = rpc.send().await?;
+    tracing::info!(%deposit, %signature, "created deposit");
+
+    let usdg_before = deployment
+        .get_user_ata_amount(&usdg.address, None)
+        .await?
+        .expect("must exist");
+    let market_token_before = deployment
+        .get_user_ata_amount(market_token, None)
+        .await?
+        .unwrap_or(0);
+
+    // Other tests may be executing deposits concurrently, so keep the threshold window short.
+    let signature = keeper
+        .set_deposit_dust_threshold(store, 0, 10 * MARKET_USD_UNIT)
+        .send_without_preflight()
+        .await?;
+    tracing::info!(%signature, "set deposit dust threshold");
+
+    let executed = async {
+        let mut builder = keeper.execute_deposit(store, oracle, &dust_deposit, true);
+        deployment
+            .execute_with_pyth(&mut builder, None, true, true)
+            .instrument(tracing::info_span!("execute dust deposit", deposit=%dust_deposit))
+            .await?;
+
+        let usdg_after_cancellation = deployment
+            .get_user_ata_amount(&usdg.address, None)
+            .await?
+            .expect("must exist");
+        let market_token_after_cancellation = deployment
+            .get_user_ata_amount(market_token, None)
+            .await?
+            .unwrap_or(0);
+
+        let mut builder = keeper.execute_deposit(store, oracle, &deposit, true);
+        deployment
+            .execute_with_pyth(&mut builder, None, true, true)
+            .instrument(tracing::info_span!("execute deposit", %deposit))
+            .await?;
+
+        eyre::Ok((usdg_after_cancellation, market_token_after_cancellation))
+    }
+    .await;
+
+    let signature = keeper
+        .set_deposit_dust_threshold(store, 0, 0)
+        .send_without_preflight()
+        .await?;
+    tracing::info!(%signature, "reset deposit dust threshold");
+
+    let (usdg_after_cancellation, market_token_after_cancellation) = executed?;
+
+    // The dust deposit must have been cancelled and refunded.
+    assert_eq!(usdg_after_cancellation, usdg_before + dust_amount);
+    assert_eq!(market_token_after_cancellation, market_token_before);
+
+    let usdg_after_execution = deployment
+        .get_user_ata_amount(&usdg.address, None)
+        .await?
+        .expect("must exist");
+    let market_token_after_execution = deployment
+        .get_user_ata_amount(market_token, None)
+        .await?
+        .expect("must exist");
+    assert_eq!(usdg_after_execution, usdg_after_cancellation);
+    assert!(market_token_after_execution > market_token_after_cancellation);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn execute_deposit_with_prices() -> eyre::Result<()> {
+    let deployment = setup::current_deployment().await?;
+    let _guard = deployment.use_accounts().await?;
+    let span = tracing::info_span!("execute_deposit_with_prices");
+    let _enter = span.enter();
+
+    let Ok(chainlink) = chainlink::Client::from_testnet_defaults() else {
+        tracing::warn!("the envs for Chainlink Data Streams are not set");
+        return Ok(());
+    };
+
+    let client = deployment.locked_user_client().await?;
+    let keeper = deployment.user_client(Deployment::DEFAULT_KEEPER)?;
+    let store = &deployment.store;
+    let oracle = &deployment.oracle();
+    let market_token = deployment.market_token("fETH", "fETH", "USDH").unwrap();
+
+    let amount = 1_000_000;
+    deployment
+        .mint_or_transfer_to("fETH", &client.payer(), amount)
+        .await?;
+
+    let market_token_before = deployment
+        .get_user_ata_amount(market_token, None)
+        .await?
+        .unwrap_or(0);
+
+    let (rpc, deposit) = client
+        .create_deposit(store, market_token)
+        .long_token(amount, None, None)
+        .build_with_address()
+        .await?;
+    let signature = rpc.send_without_preflight().await?;
+    tracing::info!(%deposit, %signature, "created a deposit");
+
+    // Prices are verified by the mock verifier program of the deployment.
+    let ctx = deployment.chainlink_pull_oracle(&keeper).await?;
+    let chainlink = ctx.make_oracle(&chainlink, &keeper, true);
+
+    let execute = keeper.execute_deposit(store, oracle, &deposit, false);
+    tokio::time::sleep(Duration::from_secs(2)).await;
+    let signatures = keeper
+        .execute_with_prices(chainlink, execute, None)
+        .await?
+        .send_all(false)
+        .await
+        .map_err(|(_, err)| err)?;
+    tracing::info!("executed deposit, txns={signatures:#?}");
+
+    let feth = deployment.token("fETH").expect("must exist");
+    assert!(deployment
+        .get_ata_amount(&feth.address, &deposit)
+        .await?
+        .is_none());
+    let market_token_after = deployment
+        .get_user_ata_amount(market_token, None)
+        .await?
+        .expect("market token ata must exist");
+    assert!(market_token_after > market_token_before);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn market_depth_limit() -> eyre::Result<()> {
+    let deployment = setup::current_deployment().await?;
+    let _guard = deployment.use_accounts().await?;
+    let span = tracing::info_span!("market_depth_limit");
+    let _enter = span.enter();
+
+    let client = deployment.locked_user_client().await?;
+    let keeper = deployment.user_client(Deployment::DEFAULT_KEEPER)?;
+    let store = &deployment.store;
+    let oracle = &deployment.oracle();
+    let market_token = deployment.market_token("SOL", "WSOL", "USDG").unwrap();
+
+    let amount = 1_000_000;
+    deployment
+        .mint_or_transfer_to("USDG", &client.payer(), amount * 4)
+        .await?;
+
+    // Allow only 3 more pending deposits in the market.
+    let pending = client
+        .market_pending_counts(store, market_token)
+        .await?
+        .deposits;
+    let limit = u64::from(pending) + 3;
+    let signature = keeper
+        .insert_global_amount_by_key(store, AmountKey::MaxPendingDeposits, &limit)
+        .send_without_preflight()
+        .await?;
+    tracing::info!(%signature, %limit, "set max pending deposits");
+
+    let mut deposits = Vec::with_capacity(4);
+    for _ in 0..3 {
+        let (rpc, deposit) = client
+            .create_deposit(store, market_token)
+            .short_token(amount, None, None)
+            .build_with_address()
+            .await?;
+        let signature = rpc.send().await?;
+        tracing::info!(%deposit, %signature, "created deposit");
+        deposits.push(deposit);
+    }
+    let pending_after = client
+        .market_pending_counts(store, market_token)
+        .await?
+        .deposits;
+    assert_eq!(pending_after, pending + 3);
+
+    let (rpc, _deposit) = client
+        .create_deposit(store, market_token)
+        .short_token(amount, None, None)
+        .build_with_address()
+        .await?;
+    let err = rpc
+        .send()
+        .await
+        .expect_err("should exceed the market depth limit");
+    assert_eq!(
+        gmsol::Error::from(err).anchor_error_code(),
+        Some(CoreError::MarketDepthLimitExceeded.into())
+    );
+
+    // Executing a deposit releases a pending slot.
+    let mut builder = keeper.execute_deposit(store, oracle, &deposits[0], true);
+    deployment
+        .execute_with_pyth(&mut builder, None, true, true)
+        .instrument(tracing::info_span!("execute", deposit=%deposits[0]))
+        .await?;
+
+    let (rpc, deposit) = client
+        .create_deposit(store, market_token)
+        .short_token(amount, None, None)
+        .build_with_address()
+        .await?;
+    let signature = rpc.send().await?;
+    tracing::info!(%deposit, %signature, "created deposit after execution");
+    deposits.push(deposit);
+
+    // Cleanup.
+    let limit = 0;
+    let signature = keeper
+        .insert_global_amount_by_key(store, AmountKey::MaxPendingDeposits, &limit)
+        .send_without_preflight()
+        .await?;
+    tracing::info!(%signature, "removed the limit of pending deposits");
+
+    for deposit in &deposits[1..] {
+        let signature = client
+            .close_deposit(store, deposit)
+            .build()
+            .await?
+            .send()
+            .await?;
+        tracing::info!(%deposit, %signature, "cancelled deposit");
+    }
+
+    // Closing the deposits releases their pending slots.
+    let pending_after = client
+        .market_pending_counts(store, market_token)
+        .await?
+        .deposits;
+    assert_eq!(pending_after, pending);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn keeper_tip() -> eyre::Result<()> {
+    use gmsol_model::{Balance, PoolKind};
+    use spl_associated_token_account::get_associated_token_address;
+
+    let deployment = setup::current_deployment().await?;
+    let _guard = deployment.use_accounts().await?;
+    let span = tracing::info_span!("keeper_tip");
+    let _enter = span.enter();
+
+    let client = deployment.locked_user_client().await?;
+    let keeper = deployment.user_client(Deployment::DEFAULT_KEEPER)?;
+    let store = &deployment.store;
+    let oracle = &deployment.oracle();
+    let market_token = deployment.market_token("SOL", "WSOL", "USDG").unwrap();
+    let market_address = keeper.find_market_address(store, market_token);
+    let wsol = deployment.token("WSOL").expect("must exist");
+    let usdg = deployment.token("USDG").expect("must exist");
+    let keeper_wsol_account = get_associated_token_address(&keeper.payer(), &wsol.address);
+    let keeper_usdg_account = get_associated_token_address(&keeper.payer(), &usdg.address);
+
+    let amount = 1_000_000;
+    deployment
+        .mint_or_transfer_to("USDG", &client.payer(), amount * 2)
+        .await?;
+
+    // Charge swap fees for deposits and set a tip factor above the cap.
+    let market = keeper.market(&market_address).await?;
+    let configs = [
+        (
+            MarketConfigKey::SwapFeeFactorForPositiveImpact,
+            MARKET_USD_UNIT / 200,
+        ),
+        (
+            MarketConfigKey::SwapFeeFactorForNegativeImpact,
+            MARKET_USD_UNIT / 200,
+        ),
+        (MarketConfigKey::KeeperTipFactor, MARKET_USD_UNIT),
+    ];
+    let original = configs.map(|(key, _)| (key, *market.get_config_by_key(key)));
+    for (key, value) in configs {
+        let signature = keeper
+            .update_market_config_by_key(store, market_token, key, &value)?
+            .send_without_preflight()
+            .await?;
+        tracing::info!(%signature, %key, %value, "updated market config");
+    }
+
+    let keeper_usdg_amount = || async {
+        eyre::Ok(
+            deployment
+                .get_ata_amount(&usdg.address, &keeper.payer())
+                .await?
+                .expect("must exist"),
+        )
+    };
+
+    // No tip is paid for a failed execution.
+    let (rpc, deposit) = client
+        .create_deposit(store, market_token)
+        .short_token(amount, None, None)
+        .min_market_token(u64::MAX)
+        .build_with_address()
+        .await?;
+    let signature = rpc.send().await?;
+    tracing::info!(%deposit, %signature, "created deposit that cannot be executed");
+
+    let before = keeper_usdg_amount().await?;
+    let mut builder = keeper.execute_deposit(store, oracle, &deposit, true);
+    builder.keeper_tip_accounts(Some(keeper_wsol_account), Some(keeper_usdg_account));
+    deployment
+        .execute_with_pyth(&mut builder, None, true, true)
+        .instrument(tracing::info_span!("execute", %deposit))
+        .await?;
+    assert_eq!(keeper_usdg_amount().await?, before);
+
+    // The tip is paid on successful execution, capped at 10% of the fees.
+    let (rpc, deposit) = client
+        .create_deposit(store, market_token)
+        .short_token(amount, None, None)
+        .build_with_address()
+        .await?;
+    let signature = rpc.send().await?;
+    tracing::info!(%deposit, %signature, "created deposit");
+
+    let before = keeper_usdg_amount().await?;
+    let claimable_before = keeper
+        .market(&market_address)
+        .await?
+        .pool(PoolKind::ClaimableFee)
+        .expect("must exist")
+        .short_amount()?;
+    let mut builder = keeper.execute_deposit(store, oracle, &deposit, true);
+    builder.keeper_tip_accounts(Some(keeper_wsol_account), Some(keeper_usdg_account));
+    deployment
+        .execute_with_pyth(&mut builder, None, true, true)
+        .instrument(tracing::info_span!("execute", %deposit))
+        .await?;
+    let tip = keeper_usdg_amount().await? - before;
+    let claimable_after = keeper
+        .market(&market_address)
+        .await?
+        .pool(PoolKind::ClaimableFee)
+        .expect("must exist")
+        .short_amount()?;
+    let fees = claimable_after + u128::from(tip) - claimable_before;
+    tracing::info!(%tip, %fees, "keeper tip paid");
+    assert!(tip > 0);
+    assert!(u128::from(tip) <= fees / 10);
+
+    // Cleanup.
+    for (key, value) in original {
+        let signature = keeper
+            .update_market_config_by_key(store, market_token, key, &value)?
+            .send_without_preflight()
+            .await?;
+        tracing::info!(%signature, %key, %value, "restored market config");
+    }
+
+    Ok(())
+}
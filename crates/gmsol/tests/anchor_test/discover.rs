@@ -0,0 +1,115 @@
+use gmsol::{
+    discover::market::{find_markets, MarketInfoDiscovery},
+    exchange::ExchangeOps,
+    store::market::MarketOps,
+};
+
+use crate::anchor_test::setup::{current_deployment, Deployment};
+
+#[tokio::test]
+async fn discover_markets() -> eyre::Result<()> {
+    let deployment = current_deployment().await?;
+    let _guard = deployment.use_accounts().await?;
+    let span = tracing::info_span!("discover_markets");
+    let _enter = span.enter();
+
+    let store = &deployment.store;
+    let keeper = deployment.user_client(Deployment::DEFAULT_KEEPER)?;
+    let token_map = deployment.token_map();
+
+    let mut markets = vec![];
+    let mut builder = keeper.bundle();
+    for [index, long, short] in [
+        ["fETH", "WSOL", "USDG"],
+        ["fETH", "fBTC", "USDG"],
+        ["fETH", "USDG", "USDH"],
+    ] {
+        let index_token = deployment.token(index).expect("must exist").address;
+        let long_token = deployment.token(long).expect("must exist").address;
+        let short_token = deployment.token(short).expect("must exist").address;
+        let (rpc, market_token) = keeper
+            .create_market(
+                store,
+                &format!("{index}/USD[{long}-{short}]"),
+                &index_token,
+                &long_token,
+                &short_token,
+                true,
+                Some(&token_map),
+            )
+            .await?;
+        builder.push(rpc)?;
+        markets.push((market_token, index_token, long_token, short_token));
+    }
+    let signatures = builder.send_all(false).await.map_err(|(_, err)| err)?;
+    tracing::info!(?signatures, "created markets");
+
+    let disabled = markets[2].0;
+    let signature = keeper
+        .toggle_market(store, &disabled, false)
+        .send_without_preflight()
+        .await?;
+    tracing::info!(%signature, market_token=%disabled, "disabled market");
+
+    let mut discovery = MarketInfoDiscovery::new(&keeper, store).await?;
+    for (market_token, index_token, long_token, short_token) in markets.iter() {
+        let info = discovery.get(market_token).expect("must be discovered");
+        assert_eq!(
+            info.address,
+            keeper.find_market_address(store, market_token)
+        );
+        assert_eq!(info.index_token, *index_token);
+        assert_eq!(info.long_token, *long_token);
+        assert_eq!(info.short_token, *short_token);
+        assert_eq!(info.is_enabled, *market_token != disabled);
+        assert_eq!((info.long_pool_amount, info.short_pool_amount), (0, 0));
+    }
+
+    let signature = keeper
+        .toggle_market(store, &disabled, true)
+        .send_without_preflight()
+        .await?;
+    tracing::info!(%signature, market_token=%disabled, "enabled market");
+
+    discovery.refresh().await?;
+    assert!(
+        discovery
+            .get(&disabled)
+            .expect("must be discovered")
+            .is_enabled
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn find_markets_of_store() -> eyre::Result<()> {
+    let deployment = current_deployment().await?;
+    let _guard = deployment.use_accounts().await?;
+    let span = tracing::info_span!("find_markets_of_store");
+    let _enter = span.enter();
+
+    let store = &deployment.store;
+    let client = deployment.user_client(Deployment::DEFAULT_USER)?;
+
+    let expected = client.markets(store).await?;
+
+    // Markets may be created or closed by other tests concurrently, so only the
+    // markets found by both queries are compared.
+    let markets = find_markets(&client, store).await?;
+    let mut matched = 0;
+    for (address, meta, is_enabled) in markets {
+        let Some(market) = expected.get(&address) else {
+            continue;
+        };
+        assert_eq!(meta.market_token_mint, market.meta().market_token_mint);
+        assert_eq!(meta.index_token_mint, market.meta().index_token_mint);
+        assert_eq!(meta.long_token_mint, market.meta().long_token_mint);
+        assert_eq!(meta.short_token_mint, market.meta().short_token_mint);
+        assert_eq!(is_enabled, market.is_enabled());
+        matched += 1;
+    }
+    assert!(matched > 1);
+
+    Ok(())
+}
@@ -1,5 +1,22 @@
-use gmsol::store::glv::GlvOps;
-use gmsol_store::CoreError;
+use std::collections::BTreeMap;
+
+use anchor_spl::associated_token::get_associated_token_address_with_program_id;
+use gmsol::{
+    constants::{MARKET_USD_TO_AMOUNT_DIVISOR, MARKET_USD_UNIT},
+    store::{glv::GlvOps, token::TokenAccountOps},
+    utils::ZeroCopy,
+};
+use gmsol_model::{
+    price::{Price, Prices},
+    PnlFactorKind,
+};
+use gmsol_store::{
+    states::{
+        glv::{GlvMarketFlag, GlvShift, UpdateGlvParams},
+        Glv, GlvDepositCooldown, GlvTreasury,
+    },
+    CoreError,
+};
 use tracing::Instrument;
 
 use crate::anchor_test::setup::{current_deployment, Deployment};
@@ -364,3 +381,703 @@ async fn glv_shift() -> eyre::Result<()> {
 
     Ok(())
 }
+
+#[tokio::test]
+async fn glv_withdrawal_cooldown() -> eyre::Result<()> {
+    let deployment = current_deployment().await?;
+    let _guard = deployment.use_accounts().await?;
+    let span = tracing::info_span!("glv_withdrawal_cooldown");
+    let _enter = span.enter();
+
+    let user = deployment.user_client(Deployment::DEFAULT_USER)?;
+    let receiver = deployment.user_client(Deployment::USER_1)?;
+    let keeper = deployment.user_client(Deployment::DEFAULT_KEEPER)?;
+
+    let store = &deployment.store;
+    let oracle = &deployment.oracle();
+    let glv_token = &deployment.glv_token;
+    let market_token = deployment.market_token("fBTC", "fBTC", "USDG").unwrap();
+
+    let short_token_amount = 1_000 * 100_000_000;
+
+    deployment
+        .mint_or_transfer_to_user(
+            "USDG",
+            Deployment::DEFAULT_USER,
+            2 * short_token_amount + 17,
+        )
+        .await?;
+
+    // Enable the withdrawal cooldown.
+    let signature = keeper
+        .update_glv_config(
+            store,
+            glv_token,
+            UpdateGlvParams {
+                withdrawal_cooldown_secs: Some(3600),
+                ..Default::default()
+            },
+        )
+        .send_without_preflight()
+        .await?;
+    tracing::info!(%signature, "enabled glv withdrawal cooldown");
+
+    let glv = user.find_glv_address(glv_token);
+    let glv_amount = 100 * 1_000_000_000;
+
+    // GLV deposit to another receiver must not start the cooldown of the receiver.
+    let (rpc, deposit) = user
+        .create_glv_deposit(store, glv_token, market_token)
+        .short_token_deposit(short_token_amount, None, None)
+        .receiver(Some(receiver.payer()))
+        .build_with_address()
+        .await?;
+    let signature = rpc.send_without_preflight().await?;
+    tracing::info!(%signature, %deposit, "created a glv deposit to the receiver");
+
+    let mut execute = keeper.execute_glv_deposit(oracle, &deposit, false);
+    deployment
+        .execute_with_pyth(
+            execute
+                .add_alt(deployment.common_alt().clone())
+                .add_alt(deployment.market_alt().clone()),
+            None,
+            false,
+            true,
+        )
+        .instrument(tracing::info_span!("executing glv deposit", glv_deposit=%deposit))
+        .await?;
+
+    let (rpc, withdrawal) = receiver
+        .create_glv_withdrawal(store, glv_token, market_token, glv_amount)
+        .build_with_address()
+        .await?;
+    let signature = rpc.send_without_preflight().await?;
+    tracing::info!(%signature, %withdrawal, "created a glv withdrawal by the receiver");
+
+    let signature = receiver
+        .close_glv_withdrawal(&withdrawal)
+        .build()
+        .await?
+        .send_without_preflight()
+        .await?;
+    tracing::info!(%signature, %withdrawal, "cancelled the glv withdrawal");
+
+    // The cooldown is tracked by the GLV token ATA of the owner and recorded on execution.
+    let glv_token_ata = get_associated_token_address_with_program_id(
+        &user.payer(),
+        glv_token,
+        &anchor_spl::token_2022::ID,
+    );
+    let cooldown_address = user.find_glv_deposit_cooldown_address(&glv, &glv_token_ata);
+    let before = user
+        .account::<ZeroCopy<GlvDepositCooldown>>(&cooldown_address)
+        .await?
+        .map(|cooldown| cooldown.0.last_deposit_at())
+        .unwrap_or_default();
+
+    let (rpc, deposit) = user
+        .create_glv_deposit(store, glv_token, market_token)
+        .short_token_deposit(short_token_amount, None, None)
+        .build_with_address()
+        .await?;
+    let signature = rpc.send_without_preflight().await?;
+    tracing::info!(%signature, %deposit, "created a glv deposit");
+    let last_deposit_at = user
+        .account::<ZeroCopy<GlvDepositCooldown>>(&cooldown_address)
+        .await?
+        .map(|cooldown| cooldown.0.last_deposit_at())
+        .unwrap_or_default();
+    assert_eq!(last_deposit_at, before);
+
+    let mut execute = keeper.execute_glv_deposit(oracle, &deposit, false);
+    deployment
+        .execute_with_pyth(
+            execute
+                .add_alt(deployment.common_alt().clone())
+                .add_alt(deployment.market_alt().clone()),
+            None,
+            false,
+            true,
+        )
+        .instrument(tracing::info_span!("executing glv deposit", glv_deposit=%deposit))
+        .await?;
+    let last_deposit_at = user
+        .account::<ZeroCopy<GlvDepositCooldown>>(&cooldown_address)
+        .await?
+        .map(|cooldown| cooldown.0.last_deposit_at())
+        .unwrap_or_default();
+    assert!(last_deposit_at > before);
+
+    // Withdrawal inside the cooldown must fail.
+    let (rpc, _withdrawal) = user
+        .create_glv_withdrawal(store, glv_token, market_token, glv_amount)
+        .build_with_address()
+        .await?;
+    let err = rpc.send().await.expect_err("should throw an error");
+    assert_eq!(
+        gmsol::Error::from(err).anchor_error_code(),
+        Some(CoreError::GlvWithdrawalCooldownNotPassed.into())
+    );
+
+    // Shorten the cooldown so that it has passed.
+    let signature = keeper
+        .update_glv_config(
+            store,
+            glv_token,
+            UpdateGlvParams {
+                withdrawal_cooldown_secs: Some(1),
+                ..Default::default()
+            },
+        )
+        .send_without_preflight()
+        .await?;
+    tracing::info!(%signature, "shortened glv withdrawal cooldown");
+    tokio::time::sleep(std::time::Duration::from_secs(2)).await;
+
+    // Withdrawal outside the cooldown must succeed.
+    let (rpc, withdrawal) = user
+        .create_glv_withdrawal(store, glv_token, market_token, glv_amount)
+        .build_with_address()
+        .await?;
+    let signature = rpc.send_without_preflight().await?;
+    tracing::info!(%signature, %withdrawal, "created a glv withdrawal");
+
+    let signature = user
+        .close_glv_withdrawal(&withdrawal)
+        .build()
+        .await?
+        .send_without_preflight()
+        .await?;
+    tracing::info!(%signature, %withdrawal, "cancelled the glv withdrawal");
+
+    // Disable the withdrawal cooldown.
+    let signature = keeper
+        .update_glv_config(
+            store,
+            glv_token,
+            UpdateGlvParams {
+                withdrawal_cooldown_secs: Some(0),
+                ..Default::default()
+            },
+        )
+        .send_without_preflight()
+        .await?;
+    tracing::info!(%signature, "disabled glv withdrawal cooldown");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn glv_token_price() -> eyre::Result<()> {
+    let deployment = current_deployment().await?;
+    let _guard = deployment.use_accounts().await?;
+    let span = tracing::info_span!("glv_token_price");
+    let _enter = span.enter();
+
+    let user = deployment.user_client(Deployment::DEFAULT_USER)?;
+    let keeper = deployment.user_client(Deployment::DEFAULT_KEEPER)?;
+
+    let store = &deployment.store;
+    let oracle = &deployment.oracle();
+    let market_token_1 = *deployment.market_token("SOL", "fBTC", "USDG").unwrap();
+    let market_token_2 = *deployment.market_token("fBTC", "fBTC", "USDG").unwrap();
+
+    // Use a dedicated GLV so that its balances are not affected by other tests.
+    let (rpc, glv_token) = keeper.initialize_glv(store, 254, [market_token_1, market_token_2])?;
+    let signature = rpc.send_without_preflight().await?;
+    tracing::info!(%signature, %glv_token, "initialized a new GLV token");
+    for market_token in [market_token_1, market_token_2] {
+        let signature = keeper
+            .toggle_glv_market_flag(
+                store,
+                &glv_token,
+                &market_token,
+                GlvMarketFlag::IsDepositAllowed,
+                true,
+            )
+            .send_without_preflight()
+            .await?;
+        tracing::info!(%signature, %market_token, "enabled GLV deposit for the market");
+    }
+
+    let price = |price: u128| Price {
+        min: price,
+        max: price,
+    };
+    // fBTC has 6 decimals.
+    let btc_price = 100_000 * MARKET_USD_UNIT / 1_000_000;
+    // USDG has 8 decimals.
+    let usd_price = MARKET_USD_UNIT / 100_000_000;
+    // SOL has 9 decimals.
+    let sol_price = 200 * MARKET_USD_UNIT / 1_000_000_000;
+    let prices_1 = Prices {
+        index_token_price: price(sol_price),
+        long_token_price: price(btc_price),
+        short_token_price: price(usd_price),
+    };
+    let prices_2 = Prices {
+        index_token_price: price(btc_price),
+        long_token_price: price(btc_price),
+        short_token_price: price(usd_price),
+    };
+    let prices = BTreeMap::from([(market_token_1, prices_1), (market_token_2, prices_2)]);
+
+    // The GLV token price is one USD before the first mint, and markets with zero balance
+    // are skipped.
+    let glv_price = keeper
+        .glv_token_price(store, &glv_token, prices.clone(), true)
+        .await?;
+    assert_eq!(glv_price, MARKET_USD_UNIT);
+
+    // Deposit to both markets with different amounts.
+    deployment
+        .mint_or_transfer_to_user("fBTC", Deployment::DEFAULT_USER, 3_000)
+        .await?;
+    for (market_token, amount) in [(market_token_1, 1_000), (market_token_2, 2_000)] {
+        let (rpc, deposit) = user
+            .create_glv_deposit(store, &glv_token, &market_token)
+            .long_token_deposit(amount, None, None)
+            .build_with_address()
+            .await?;
+        let signature = rpc.send_without_preflight().await?;
+        tracing::info!(%signature, %deposit, "created a glv deposit");
+
+        let mut execute = keeper.execute_glv_deposit(oracle, &deposit, false);
+        deployment
+            .execute_with_pyth(
+                execute
+                    .add_alt(deployment.common_alt().clone())
+                    .add_alt(deployment.market_alt().clone()),
+                None,
+                false,
+                true,
+            )
+            .instrument(tracing::info_span!("executing glv deposit", glv_deposit=%deposit))
+            .await?;
+    }
+
+    let glv = keeper
+        .account::<ZeroCopy<Glv>>(&keeper.find_glv_address(&glv_token))
+        .await?
+        .expect("must exist")
+        .0;
+    let supply = keeper
+        .account::<anchor_spl::token_interface::Mint>(&glv_token)
+        .await?
+        .expect("must exist")
+        .supply;
+    assert!(supply > 0);
+
+    let one = MARKET_USD_UNIT / MARKET_USD_TO_AMOUNT_DIVISOR;
+    let mut value = 0;
+    for (market_token, prices) in prices.iter() {
+        let balance = glv
+            .market_config(market_token)
+            .expect("must exist")
+            .balance();
+        assert!(balance > 0);
+        let market_token_price = keeper
+            .market_token_price(
+                store,
+                market_token,
+                prices.clone(),
+                PnlFactorKind::MaxAfterDeposit,
+                true,
+            )
+            .await?;
+        value += u128::from(balance) * market_token_price / one;
+    }
+    let expected = value * one / u128::from(supply);
+
+    let glv_price = keeper
+        .glv_token_price(store, &glv_token, prices, true)
+        .await?;
+    tracing::info!(%glv_price, %expected, "fetched GLV token price");
+    // Allow for rounding errors.
+    assert!(glv_price.abs_diff(expected) * 1_000_000 <= expected);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn glv_batch_shift() -> eyre::Result<()> {
+    let deployment = current_deployment().await?;
+    let _guard = deployment.use_accounts().await?;
+    let span = tracing::info_span!("glv_batch_shift");
+    let _enter = span.enter();
+
+    let user = deployment.user_client(Deployment::DEFAULT_USER)?;
+    let keeper = deployment.user_client(Deployment::DEFAULT_KEEPER)?;
+
+    let store = &deployment.store;
+    let oracle = &deployment.oracle();
+    let from_market_token = *deployment.market_token("SOL", "fBTC", "USDG").unwrap();
+    let to_market_token_1 = *deployment.market_token("fBTC", "fBTC", "USDG").unwrap();
+    let [index, long, short] = Deployment::SELECT_GLV_BATCH_SHIFT_MARKET;
+    let to_market_token_2 = *deployment.market_token(index, long, short).unwrap();
+
+    // Use a dedicated GLV with 3 markets.
+    let (rpc, glv_token) = keeper.initialize_glv(
+        store,
+        253,
+        [from_market_token, to_market_token_1, to_market_token_2],
+    )?;
+    let signature = rpc.send_without_preflight().await?;
+    tracing::info!(%signature, %glv_token, "initialized a new GLV token");
+    for market_token in [from_market_token, to_market_token_1, to_market_token_2] {
+        let signature = keeper
+            .toggle_glv_market_flag(
+                store,
+                &glv_token,
+                &market_token,
+                GlvMarketFlag::IsDepositAllowed,
+                true,
+            )
+            .send_without_preflight()
+            .await?;
+        tracing::info!(%signature, %market_token, "enabled GLV deposit for the market");
+    }
+
+    // Disable the shift interval to execute the shifts one after another.
+    let signature = keeper
+        .update_glv_config(
+            store,
+            &glv_token,
+            UpdateGlvParams {
+                shift_min_interval_secs: Some(0),
+                ..Default::default()
+            },
+        )
+        .send_without_preflight()
+        .await?;
+    tracing::info!(%signature, "disabled glv shift interval");
+
+    let long_token_amount = 1_000;
+    let short_token_amount = 1_000 * 100_000_000;
+
+    deployment
+        .mint_or_transfer_to_user("fBTC", Deployment::DEFAULT_USER, long_token_amount + 17)
+        .await?;
+    deployment
+        .mint_or_transfer_to_user("USDG", Deployment::DEFAULT_USER, short_token_amount + 17)
+        .await?;
+
+    // GLV deposit.
+    let (rpc, deposit) = user
+        .create_glv_deposit(store, &glv_token, &from_market_token)
+        .long_token_deposit(long_token_amount, None, None)
+        .short_token_deposit(short_token_amount, None, None)
+        .build_with_address()
+        .await?;
+    let signature = rpc.send_without_preflight().await?;
+    tracing::info!(%signature, %deposit, "created a glv deposit");
+
+    let mut execute = keeper.execute_glv_deposit(oracle, &deposit, false);
+    deployment
+        .execute_with_pyth(
+            execute
+                .add_alt(deployment.common_alt().clone())
+                .add_alt(deployment.market_alt().clone()),
+            None,
+            false,
+            true,
+        )
+        .instrument(tracing::info_span!("executing glv deposit", glv_deposit=%deposit))
+        .await?;
+
+    let glv_address = keeper.find_glv_address(&glv_token);
+    let batch_nonce = keeper
+        .account::<ZeroCopy<Glv>>(&glv_address)
+        .await?
+        .expect("must exist")
+        .0
+        .batch_nonce();
+
+    // Create a batch of GLV shifts.
+    let shifts = [
+        (to_market_token_1, 300 * 1_000_000_000),
+        (to_market_token_2, 200 * 1_000_000_000),
+    ];
+    let mut batch = keeper.batch_create_glv_shifts(store, &glv_token);
+    for (to_market_token, amount) in shifts {
+        batch.add_shift(&from_market_token, &to_market_token, amount, 0);
+    }
+    let (rpc, addresses) = batch.build_with_addresses().await?;
+    let signature = rpc.send_without_preflight().await?;
+    tracing::info!(%signature, ?addresses, "created a batch of glv shifts");
+
+    let glv = keeper
+        .account::<ZeroCopy<Glv>>(&glv_address)
+        .await?
+        .expect("must exist")
+        .0;
+    assert_eq!(glv.batch_nonce(), batch_nonce + 1);
+
+    assert_eq!(addresses.len(), shifts.len());
+    for (shift, (to_market_token, amount)) in addresses.iter().zip(shifts) {
+        let glv_shift = keeper
+            .account::<ZeroCopy<GlvShift>>(shift)
+            .await?
+            .expect("must exist")
+            .0;
+        assert_eq!(*glv_shift.glv(), glv_address);
+        assert_eq!(glv_shift.tokens().from_market_token(), from_market_token);
+        assert_eq!(glv_shift.tokens().to_market_token(), to_market_token);
+        assert_eq!(glv_shift.params().from_market_token_amount(), amount);
+    }
+
+    // Execute the GLV shifts one by one.
+    for shift in addresses.iter() {
+        let mut execute = keeper.execute_glv_shift(oracle, shift, false);
+        deployment
+            .execute_with_pyth(
+                execute
+                    .add_alt(deployment.common_alt().clone())
+                    .add_alt(deployment.market_alt().clone()),
+                None,
+                false,
+                true,
+            )
+            .instrument(tracing::info_span!("executing glv shift", glv_shift=%shift))
+            .await?;
+    }
+
+    let glv = keeper
+        .account::<ZeroCopy<Glv>>(&glv_address)
+        .await?
+        .expect("must exist")
+        .0;
+    for (to_market_token, _) in shifts {
+        let balance = glv
+            .market_config(&to_market_token)
+            .expect("must exist")
+            .balance();
+        assert!(balance > 0);
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn glv_automatic_rebalance_deposit() -> eyre::Result<()> {
+    let deployment = current_deployment().await?;
+    let _guard = deployment.use_accounts().await?;
+    let span = tracing::info_span!("glv_automatic_rebalance_deposit");
+    let _enter = span.enter();
+
+    let keeper = deployment.user_client(Deployment::DEFAULT_KEEPER)?;
+
+    let store = &deployment.store;
+    let oracle = &deployment.oracle();
+    let market_token = *deployment.market_token("SOL", "fBTC", "USDG").unwrap();
+    let other_market_token = *deployment.market_token("fBTC", "fBTC", "USDG").unwrap();
+    let long_token = deployment.token("fBTC").expect("must exist").address;
+
+    // Use a dedicated GLV so that its balances are not affected by other tests.
+    let (rpc, glv_token) = keeper.initialize_glv(store, 252, [market_token, other_market_token])?;
+    let signature = rpc.send_without_preflight().await?;
+    tracing::info!(%signature, %glv_token, "initialized a new GLV token");
+    for market_token in [market_token, other_market_token] {
+        let signature = keeper
+            .toggle_glv_market_flag(
+                store,
+                &glv_token,
+                &market_token,
+                GlvMarketFlag::IsDepositAllowed,
+                true,
+            )
+            .send_without_preflight()
+            .await?;
+        tracing::info!(%signature, %market_token, "enabled GLV deposit for the market");
+    }
+
+    let amount = 1_000;
+    let max_rebalance_value_per_day = 1_000 * MARKET_USD_UNIT;
+
+    // Initialize and fund the GLV treasury.
+    let signature = keeper
+        .initialize_glv_treasury(store, &glv_token, max_rebalance_value_per_day)
+        .send_without_preflight()
+        .await?;
+    tracing::info!(%signature, "initialized GLV treasury");
+    let glv_address = keeper.find_glv_address(&glv_token);
+    let treasury = keeper.find_glv_treasury_address(store, &glv_address);
+    let signature = keeper
+        .prepare_associated_token_account(&long_token, &anchor_spl::token::ID, Some(&treasury))
+        .send_without_preflight()
+        .await?;
+    tracing::info!(%signature, "prepared GLV treasury vault");
+    deployment
+        .mint_or_transfer_to("fBTC", &treasury, 3 * amount)
+        .await?;
+
+    // The market is depleted and below its min allocation.
+    let signature = keeper
+        .set_glv_market_min_amount(store, &glv_token, &market_token, 1_000_000_000_000)
+        .send_without_preflight()
+        .await?;
+    tracing::info!(%signature, "set min amount for the market");
+
+    // The other market has no min allocation.
+    let mut trigger = keeper.trigger_automatic_rebalance_deposit(
+        store,
+        oracle,
+        &glv_token,
+        &other_market_token,
+        &long_token,
+        amount,
+    );
+    let err = deployment
+        .execute_with_pyth(
+            trigger.add_alt(deployment.common_alt().clone()),
+            None,
+            false,
+            false,
+        )
+        .await
+        .expect_err("should throw an error");
+    assert_eq!(
+        err.anchor_error_code(),
+        Some(CoreError::GlvMarketNotBelowMinAllocation.into())
+    );
+
+    let mut trigger = keeper.trigger_automatic_rebalance_deposit(
+        store,
+        oracle,
+        &glv_token,
+        &market_token,
+        &long_token,
+        amount,
+    );
+    let deposit = trigger.glv_deposit().await?;
+    deployment
+        .execute_with_pyth(
+            trigger.add_alt(deployment.common_alt().clone()),
+            None,
+            false,
+            true,
+        )
+        .instrument(tracing::info_span!("triggering rebalance deposit", glv_deposit=%deposit))
+        .await?;
+
+    let rebalanced_value = keeper
+        .account::<ZeroCopy<GlvTreasury>>(&treasury)
+        .await?
+        .expect("must exist")
+        .0
+        .rebalanced_value_today();
+    assert!(rebalanced_value > 0);
+    assert!(rebalanced_value <= max_rebalance_value_per_day);
+
+    let mut execute = keeper.execute_glv_deposit(oracle, &deposit, false);
+    deployment
+        .execute_with_pyth(
+            execute
+                .add_alt(deployment.common_alt().clone())
+                .add_alt(deployment.market_alt().clone()),
+            None,
+            false,
+            true,
+        )
+        .instrument(tracing::info_span!("executing glv deposit", glv_deposit=%deposit))
+        .await?;
+
+    let glv = keeper
+        .account::<ZeroCopy<Glv>>(&glv_address)
+        .await?
+        .expect("must exist")
+        .0;
+    let balance = glv
+        .market_config(&market_token)
+        .expect("must exist")
+        .balance();
+    assert!(balance > 0);
+    let glv_token_ata = get_associated_token_address_with_program_id(
+        &treasury,
+        &glv_token,
+        &anchor_spl::token_2022::ID,
+    );
+    let glv_token_account = keeper
+        .account::<anchor_spl::token_interface::TokenAccount>(&glv_token_ata)
+        .await?
+        .expect("must exist");
+    assert!(glv_token_account.amount > 0);
+
+    // The daily limit (in USD) must be respected.
+    let signature = keeper
+        .update_glv_treasury(store, &glv_token, rebalanced_value)
+        .send_without_preflight()
+        .await?;
+    tracing::info!(%signature, "reduced the daily limit to the value rebalanced today");
+    let mut trigger = keeper.trigger_automatic_rebalance_deposit(
+        store,
+        oracle,
+        &glv_token,
+        &market_token,
+        &long_token,
+        1,
+    );
+    let err = deployment
+        .execute_with_pyth(
+            trigger.add_alt(deployment.common_alt().clone()),
+            None,
+            false,
+            false,
+        )
+        .await
+        .expect_err("should throw an error");
+    assert_eq!(
+        err.anchor_error_code(),
+        Some(CoreError::GlvRebalanceAmountExceedsDailyLimit.into())
+    );
+
+    // Only ADMIN can withdraw from the treasury.
+    let admin = &deployment.client;
+    let target = get_associated_token_address_with_program_id(
+        &admin.payer(),
+        &long_token,
+        &anchor_spl::token::ID,
+    );
+    let signature = admin
+        .prepare_associated_token_account(&long_token, &anchor_spl::token::ID, None)
+        .send_without_preflight()
+        .await?;
+    tracing::info!(%signature, "prepared the withdrawal target");
+    let remaining = deployment
+        .get_ata_amount(&long_token, &treasury)
+        .await?
+        .expect("must exist");
+    assert_eq!(remaining, 2 * amount);
+
+    let err = keeper
+        .withdraw_from_glv_treasury(store, &glv_token, &long_token, None, &target, remaining)
+        .send()
+        .await
+        .expect_err("should throw an error");
+    assert_eq!(
+        gmsol::Error::from(err).anchor_error_code(),
+        Some(CoreError::NotAnAdmin.into())
+    );
+
+    let before = deployment
+        .get_ata_amount(&long_token, &admin.payer())
+        .await?
+        .expect("must exist");
+    let signature = admin
+        .withdraw_from_glv_treasury(store, &glv_token, &long_token, None, &target, remaining)
+        .send_without_preflight()
+        .await?;
+    tracing::info!(%signature, "withdrawn from GLV treasury");
+    let after = deployment
+        .get_ata_amount(&long_token, &admin.payer())
+        .await?
+        .expect("must exist");
+    assert_eq!(after - before, remaining);
+    assert_eq!(
+        deployment.get_ata_amount(&long_token, &treasury).await?,
+        Some(0)
+    );
+
+    Ok(())
+}
@@ -0,0 +1,80 @@
+use std::time::Duration;
+
+use gmsol::{store::gt::GtOps, types::gt::GtExchangeVault, utils::ZeroCopy};
+
+use crate::anchor_test::setup::{current_deployment, Deployment};
+
+#[tokio::test]
+async fn reclaim_gt_exchange_rent() -> eyre::Result<()> {
+    let deployment = current_deployment().await?;
+    let _guard = deployment.use_accounts().await?;
+    let span = tracing::info_span!("reclaim_gt_exchange_rent");
+    let _enter = span.enter();
+
+    let keeper = deployment.user_client(Deployment::DEFAULT_KEEPER)?;
+    let store = &deployment.store;
+
+    let original_window = keeper.store(store).await?.gt().exchange_time_window();
+    let window = 2;
+    let signature = keeper
+        .gt_set_exchange_time_window(store, window)
+        .send_without_preflight()
+        .await?;
+    tracing::info!(%signature, window, "set GT exchange time window");
+
+    // Prepare a vault without exchanges and confirm it after its time window has passed.
+    let (rpc, confirmed) = keeper
+        .prepare_gt_exchange_vault_with_time_window(store, window)?
+        .swap_output(());
+    let signature = rpc.send_without_preflight().await?;
+    tracing::info!(%signature, %confirmed, "prepared GT exchange vault");
+
+    tokio::time::sleep(Duration::from_secs(u64::from(window) * 2)).await;
+
+    let signature = keeper
+        .confirm_gt_exchange_vault(store, &confirmed)
+        .send_without_preflight()
+        .await?;
+    tracing::info!(%signature, %confirmed, "confirmed GT exchange vault");
+
+    // Prepare another vault for the current time window, which cannot be confirmed yet.
+    let (rpc, unconfirmed) = keeper
+        .prepare_gt_exchange_vault_with_time_window(store, window)?
+        .swap_output(());
+    let signature = rpc.send_without_preflight().await?;
+    tracing::info!(%signature, %unconfirmed, "prepared GT exchange vault");
+    assert_ne!(confirmed, unconfirmed);
+
+    let signature = keeper
+        .reclaim_gt_exchange_rent(store, &[confirmed, unconfirmed], &[], None)?
+        .send_without_preflight()
+        .await?;
+    tracing::info!(%signature, "reclaimed GT exchange rent");
+
+    assert!(keeper
+        .account::<ZeroCopy<GtExchangeVault>>(&confirmed)
+        .await?
+        .is_none());
+    let vault = keeper
+        .account::<ZeroCopy<GtExchangeVault>>(&unconfirmed)
+        .await?
+        .expect("the unconfirmed vault must be kept")
+        .0;
+    assert!(!vault.is_confirmed());
+    assert_eq!(vault.pending_exchanges(), Some(0));
+
+    // Only initialized vaults can be passed.
+    let result = keeper
+        .reclaim_gt_exchange_rent(store, &[confirmed], &[], None)?
+        .send_without_preflight()
+        .await;
+    assert!(result.is_err());
+
+    let signature = keeper
+        .gt_set_exchange_time_window(store, original_window)
+        .send_without_preflight()
+        .await?;
+    tracing::info!(%signature, "restored GT exchange time window");
+
+    Ok(())
+}
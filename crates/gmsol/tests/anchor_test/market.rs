@@ -1,6 +1,44 @@
+use std::{
+    collections::BTreeMap,
+    time::{Duration, SystemTime},
+};
+
+use anchor_client::solana_sdk::{blake3, pubkey::Pubkey, signature::Keypair, signer::Signer};
 use gmsol::{
-    exchange::ExchangeOps, store::market::MarketOps, types::market::config::MarketConfigFlag,
+    constants::MARKET_USD_UNIT,
+    exchange::ExchangeOps,
+    store::{
+        config::ConfigOps,
+        liquidity_mining::LiquidityMiningOps,
+        market::{MarketOps, VaultOps},
+        store_ops::StoreOps,
+        token_config::TokenConfigOps,
+    },
+    types::{
+        market::{
+            config::{EntryArgs, MarketConfigFlag},
+            oi_caps::OiCapUpdate,
+        },
+        FeeStructureProposal, Market, MarketConfigKey, MarketOiCaps, UpdateTokenConfigParams,
+        VirtualPool,
+    },
+    utils::ZeroCopy,
+};
+use gmsol_model::{
+    num::MulDiv,
+    price::{Price, Prices},
+    Balance, PoolKind,
+};
+use gmsol_store::{
+    instructions::TokenPriceCapConfig,
+    states::{
+        liquidity_mining::{CampaignParams, LiquidityMiningCampaign, LpBalanceCheckpoint},
+        AddressKey, FactorKey, TokenMapAccess,
+    },
+    CoreError,
 };
+use strum::IntoEnumIterator;
+use tracing::Instrument;
 
 use crate::anchor_test::setup::{current_deployment, Deployment};
 
@@ -31,6 +69,79 @@ async fn claim_fees() -> eyre::Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn claim_fees_split() -> eyre::Result<()> {
+    let deployment = current_deployment().await?;
+    let _guard = deployment.use_accounts().await?;
+    let span = tracing::info_span!("claim_fees_split");
+    let _enter = span.enter();
+
+    let store = &deployment.store;
+    let market_token = deployment
+        .market_token("fBTC", "fBTC", "USDG")
+        .expect("must exist");
+    let fbtc = deployment.token("fBTC").expect("must exist");
+
+    let keeper = deployment.user_client(Deployment::DEFAULT_KEEPER)?;
+    let receiver = deployment.client.payer();
+    let buyback = Keypair::new().pubkey();
+
+    let signature = keeper
+        .insert_global_address_by_key(store, AddressKey::Buyback, &buyback)
+        .send_without_preflight()
+        .await?;
+    tracing::info!(%signature, %buyback, "set buyback address");
+
+    deployment.wait_until_claim_fees_enabled().await;
+
+    // A 70/30 split and the all-to-treasury degenerate case.
+    for percent in [30, 0] {
+        let factor = percent * MARKET_USD_UNIT / 100;
+        let signature = keeper
+            .insert_factor(store, FactorKey::BuybackFeeFactor, factor)
+            .send_without_preflight()
+            .await?;
+        tracing::info!(%signature, %factor, "set buyback fee factor");
+
+        let treasury_before = deployment
+            .get_ata_amount(&fbtc.address, &receiver)
+            .await?
+            .unwrap_or(0);
+        let buyback_before = deployment
+            .get_ata_amount(&fbtc.address, &buyback)
+            .await?
+            .unwrap_or(0);
+
+        let signature = deployment
+            .client
+            .claim_fees(store, market_token, true)
+            .set_split(true)
+            .build()
+            .await?
+            .send_without_preflight()
+            .await?;
+        tracing::info!(%signature, %percent, "claimed fees with split");
+
+        let treasury_amount = deployment
+            .get_ata_amount(&fbtc.address, &receiver)
+            .await?
+            .expect("must exist")
+            - treasury_before;
+        let buyback_amount = deployment
+            .get_ata_amount(&fbtc.address, &buyback)
+            .await?
+            .expect("must exist")
+            - buyback_before;
+        let total = u128::from(treasury_amount + buyback_amount);
+        assert_eq!(u128::from(buyback_amount), total * percent / 100);
+        if percent == 0 {
+            assert_eq!(buyback_amount, 0);
+        }
+    }
+
+    Ok(())
+}
+
 #[tokio::test]
 async fn set_market_config_flag() -> eyre::Result<()> {
     let deployment = current_deployment().await?;
@@ -59,3 +170,1326 @@ async fn set_market_config_flag() -> eyre::Result<()> {
 
     Ok(())
 }
+
+#[tokio::test]
+async fn virtual_pool() -> eyre::Result<()> {
+    let deployment = current_deployment().await?;
+    let _guard = deployment.use_accounts().await?;
+    let span = tracing::info_span!("virtual_pool");
+    let _enter = span.enter();
+
+    let store = &deployment.store;
+    let oracle = &deployment.oracle();
+    let keeper = deployment.user_client(Deployment::DEFAULT_KEEPER)?;
+    let client = deployment.locked_user_client().await?;
+
+    let long_token_amount = 1_000_011;
+    let short_token_amount = 6_000_000_000_013;
+    let market_token = *deployment
+        .prepare_market(
+            ["fBTC", "WSOL", "USDG"],
+            long_token_amount,
+            short_token_amount,
+            true,
+        )
+        .await?;
+    let correlated_market_token = *deployment
+        .prepare_market(
+            Deployment::SELECT_FIRST_DEPOSIT_MARKET,
+            long_token_amount,
+            short_token_amount,
+            true,
+        )
+        .await?;
+
+    // Link the markets with a virtual pool.
+    let key = Pubkey::new_unique();
+    let (rpc, virtual_pool) = keeper.initialize_virtual_pool(store, &key).swap_output(());
+    let signature = rpc.send_without_preflight().await?;
+    tracing::info!(%signature, %virtual_pool, "initialized virtual pool");
+
+    for market_token in [&market_token, &correlated_market_token] {
+        let signature = keeper
+            .add_market_to_virtual_pool(store, market_token, &key)
+            .merge(keeper.update_market_config_flag_by_key(
+                store,
+                market_token,
+                MarketConfigFlag::VirtualPoolEnabled,
+                true,
+            )?)
+            .send_without_preflight()
+            .await?;
+        tracing::info!(%signature, %market_token, "added market to virtual pool");
+    }
+
+    let before = keeper
+        .account::<ZeroCopy<VirtualPool>>(&virtual_pool)
+        .await?
+        .expect("must exist")
+        .0
+        .virtual_long_tokens();
+
+    // Open a position in one of the markets.
+    let collateral_amount = 100 * 100_000_000;
+    let size = 500 * MARKET_USD_UNIT;
+    deployment
+        .mint_or_transfer_to("USDG", &client.payer(), collateral_amount)
+        .await?;
+
+    let (rpc, order) = client
+        .market_increase(store, &market_token, false, collateral_amount, true, size)
+        .build_with_address()
+        .await?;
+    let signature = rpc.send().await?;
+    tracing::info!(%order, %signature, %size, "created an increase position order");
+
+    let mut builder = keeper.execute_order(store, oracle, &order, false)?;
+    deployment
+        .execute_with_pyth(&mut builder, None, true, true)
+        .instrument(tracing::info_span!("execute", order=%order))
+        .await?;
+
+    // The open interest must be shared with the correlated market.
+    let after = keeper
+        .account::<ZeroCopy<VirtualPool>>(&virtual_pool)
+        .await?
+        .expect("must exist")
+        .0
+        .virtual_long_tokens();
+    assert_eq!(after - before, size as i128);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn market_oi_caps() -> eyre::Result<()> {
+    let deployment = current_deployment().await?;
+    let _guard = deployment.use_accounts().await?;
+    let span = tracing::info_span!("market_oi_caps");
+    let _enter = span.enter();
+
+    let store = &deployment.store;
+    let keeper = deployment.user_client(Deployment::DEFAULT_KEEPER)?;
+
+    let [index, long_token, short_token] = Deployment::SELECT_OI_CAPS_MARKET;
+    let market_token = *deployment
+        .market_token(index, long_token, short_token)
+        .expect("must exist");
+    let market_address = keeper.find_market_address(store, &market_token);
+
+    let assert_caps = |market: &Market, max_long: u128, max_short: u128| {
+        assert_eq!(
+            *market.get_config_by_key(MarketConfigKey::MaxOpenInterestForLong),
+            max_long
+        );
+        assert_eq!(
+            *market.get_config_by_key(MarketConfigKey::MaxOpenInterestForShort),
+            max_short
+        );
+    };
+
+    let max_oi = 1_000_000 * MARKET_USD_UNIT;
+    let rate_limit = 500_000 * MARKET_USD_UNIT;
+    let (rpc, oi_caps) = keeper
+        .initialize_market_oi_caps(store, &market_token, max_oi, max_oi, rate_limit)
+        .swap_output(());
+    let signature = rpc.send_without_preflight().await?;
+    tracing::info!(%signature, %oi_caps, "initialized market OI caps");
+    assert_caps(&*keeper.market(&market_address).await?, max_oi, max_oi);
+
+    // Adjustments within the daily rate limit are applied to the market.
+    let new_max_long = max_oi + 100_000 * MARKET_USD_UNIT;
+    let signature = keeper
+        .update_market_oi_caps(store, &market_token, new_max_long, max_oi)
+        .send_without_preflight()
+        .await?;
+    tracing::info!(%signature, %new_max_long, "updated market OI caps");
+    assert_caps(
+        &*keeper.market(&market_address).await?,
+        new_max_long,
+        max_oi,
+    );
+
+    let caps = keeper
+        .account::<ZeroCopy<MarketOiCaps>>(&oi_caps)
+        .await?
+        .expect("must exist")
+        .0;
+    assert_eq!(caps.max_oi_long(), new_max_long);
+    assert_eq!(caps.max_oi_short(), max_oi);
+
+    // Adjustments exceeding the daily rate limit are rejected.
+    let err = keeper
+        .update_market_oi_caps(
+            store,
+            &market_token,
+            new_max_long + 2_000_000 * MARKET_USD_UNIT,
+            max_oi,
+        )
+        .send()
+        .await
+        .expect_err("should throw an error when the adjustment exceeds the rate limit");
+    assert_eq!(
+        err.anchor_error_code(),
+        Some(CoreError::OpenInterestCapAdjustmentRateLimited.into())
+    );
+    assert_caps(
+        &*keeper.market(&market_address).await?,
+        new_max_long,
+        max_oi,
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn batch_update_market_oi_caps() -> eyre::Result<()> {
+    let deployment = current_deployment().await?;
+    let _guard = deployment.use_accounts().await?;
+    let span = tracing::info_span!("batch_update_market_oi_caps");
+    let _enter = span.enter();
+
+    let store = &deployment.store;
+    let keeper = deployment.user_client(Deployment::DEFAULT_KEEPER)?;
+
+    let market_tokens = Deployment::SELECT_BATCH_OI_CAPS_MARKETS.map(|[index, long, short]| {
+        *deployment
+            .market_token(index, long, short)
+            .expect("must exist")
+    });
+
+    let max_oi = 1_000_000 * MARKET_USD_UNIT;
+    let rate_limit = 500_000 * MARKET_USD_UNIT;
+    for market_token in market_tokens.iter() {
+        let signature = keeper
+            .initialize_market_oi_caps(store, market_token, max_oi, max_oi, rate_limit)
+            .send_without_preflight()
+            .await?;
+        tracing::info!(%signature, %market_token, "initialized market OI caps");
+    }
+
+    let assert_caps = |caps: Vec<(u128, u128)>| {
+        let keeper = &keeper;
+        async move {
+            for (market_token, (max_long, max_short)) in market_tokens.iter().zip(caps) {
+                let market = keeper
+                    .market(&keeper.find_market_address(store, market_token))
+                    .await?;
+                assert_eq!(
+                    *market.get_config_by_key(MarketConfigKey::MaxOpenInterestForLong),
+                    max_long
+                );
+                assert_eq!(
+                    *market.get_config_by_key(MarketConfigKey::MaxOpenInterestForShort),
+                    max_short
+                );
+            }
+            eyre::Ok(())
+        }
+    };
+
+    // Update all the markets in one call.
+    let updated = [
+        (max_oi + 100_000 * MARKET_USD_UNIT, max_oi),
+        (max_oi, max_oi - 100_000 * MARKET_USD_UNIT),
+        (
+            max_oi + 200_000 * MARKET_USD_UNIT,
+            max_oi + 200_000 * MARKET_USD_UNIT,
+        ),
+    ];
+    let updates = market_tokens
+        .iter()
+        .zip(updated)
+        .map(|(market_token, (max_oi_long, max_oi_short))| OiCapUpdate {
+            market_token: *market_token,
+            max_oi_long,
+            max_oi_short,
+        })
+        .collect::<Vec<_>>();
+    let signature = keeper
+        .batch_update_market_oi_caps(store, updates.clone())
+        .send_without_preflight()
+        .await?;
+    tracing::info!(%signature, "batch updated market OI caps");
+    assert_caps(updated.to_vec()).await?;
+
+    // The whole batch is rejected if any of the updates exceeds the rate limit.
+    let mut updates = updates;
+    updates[0].max_oi_long += 100_000 * MARKET_USD_UNIT;
+    updates[1].max_oi_short += 100_000 * MARKET_USD_UNIT;
+    updates[2].max_oi_long += 1_000_000 * MARKET_USD_UNIT;
+    let err = keeper
+        .batch_update_market_oi_caps(store, updates)
+        .send()
+        .await
+        .expect_err("should throw an error when any of the updates exceeds the rate limit");
+    assert_eq!(
+        err.anchor_error_code(),
+        Some(CoreError::OpenInterestCapAdjustmentRateLimited.into())
+    );
+    assert_caps(updated.to_vec()).await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn market_token_map() -> eyre::Result<()> {
+    let deployment = current_deployment().await?;
+    let _guard = deployment.use_accounts().await?;
+    let span = tracing::info_span!("market_token_map");
+    let _enter = span.enter();
+
+    let store = &deployment.store;
+    let oracle = &deployment.oracle();
+    let keeper = deployment.user_client(Deployment::DEFAULT_KEEPER)?;
+    let client = deployment.user_client(Deployment::DEFAULT_USER)?;
+
+    let long_token_amount = 1_000_011;
+    let short_token_amount = 1_000_013;
+    let [index, long_token, short_token] = Deployment::SELECT_TOKEN_MAP_MARKET;
+    let market_token = *deployment
+        .prepare_market(
+            Deployment::SELECT_TOKEN_MAP_MARKET,
+            long_token_amount,
+            short_token_amount,
+            true,
+        )
+        .await?;
+    let another_market_token = *deployment
+        .market_token("fBTC", "fBTC", "USDG")
+        .expect("must exist");
+
+    // Create a token map with the same feeds as the store one, but with a timestamp
+    // adjustment of a week to make every price stale.
+    let token_map_keypair = Keypair::new();
+    let (rpc, token_map) = keeper.initialize_token_map(store, &token_map_keypair);
+    let mut builder = keeper.bundle();
+    builder.push(rpc)?;
+    for name in [index, long_token, short_token] {
+        let token = deployment.token(name).expect("must exist");
+        let config = UpdateTokenConfigParams::default()
+            .update_price_feed(
+                &token.config.provider,
+                token.config.feed_id,
+                Some(7 * 24 * 3600),
+            )?
+            .with_expected_provider(token.config.provider)
+            .with_precision(token.config.precision);
+        builder.push(keeper.insert_token_config(
+            store,
+            &token_map,
+            name,
+            &token.address,
+            config,
+            true,
+            true,
+        ))?;
+    }
+    builder.send_all(true).await.map_err(|(_, err)| err)?;
+    tracing::info!(%token_map, "initialized a token map with divergent feed configs");
+
+    let signature = keeper
+        .set_market_token_map(store, &market_token, Some(&token_map))
+        .send_without_preflight()
+        .await?;
+    tracing::info!(%signature, %market_token, "set the token map of the market");
+
+    let market_address = keeper.find_market_address(store, &market_token);
+    let market = keeper.market(&market_address).await?;
+    assert_eq!(market.token_map(), Some(&token_map));
+    assert_eq!(
+        keeper.market_token_map_address(&market_address).await?,
+        Some(token_map)
+    );
+
+    // The other market still uses the token map of the store.
+    let another_market = keeper.find_market_address(store, &another_market_token);
+    assert_eq!(
+        keeper.market_token_map_address(&another_market).await?,
+        Some(deployment.token_map())
+    );
+    deployment
+        .prepare_market(
+            ["fBTC", "fBTC", "USDG"],
+            long_token_amount,
+            short_token_amount,
+            true,
+        )
+        .await?;
+
+    // The deposit cannot be executed with the stale prices from the token map of the market.
+    deployment
+        .mint_or_transfer_to_user(long_token, Deployment::DEFAULT_USER, long_token_amount)
+        .await?;
+    let (rpc, deposit) = client
+        .create_deposit(store, &market_token)
+        .long_token(long_token_amount, None, None)
+        .build_with_address()
+        .await?;
+    let signature = rpc.send_without_preflight().await?;
+    tracing::info!(%signature, %deposit, "created a deposit");
+
+    let mut builder = keeper.execute_deposit(store, oracle, &deposit, false);
+    let res = deployment
+        .execute_with_pyth(&mut builder, None, false, true)
+        .await;
+    assert!(res.is_err());
+
+    // Passing the token map of the store is rejected.
+    let mut builder = keeper.execute_deposit(store, oracle, &deposit, false);
+    builder.token_map(deployment.token_map());
+    let res = deployment
+        .execute_with_pyth(&mut builder, None, false, false)
+        .await;
+    assert!(res.is_err());
+
+    // Fall back to the token map of the store.
+    let signature = keeper
+        .set_market_token_map(store, &market_token, None)
+        .send_without_preflight()
+        .await?;
+    tracing::info!(%signature, %market_token, "removed the token map of the market");
+    let market = keeper.market(&market_address).await?;
+    assert_eq!(market.token_map(), None);
+
+    let mut builder = keeper.execute_deposit(store, oracle, &deposit, false);
+    deployment
+        .execute_with_pyth(&mut builder, None, true, true)
+        .await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn close_market() -> eyre::Result<()> {
+    let deployment = current_deployment().await?;
+    let _guard = deployment.use_accounts().await?;
+    let span = tracing::info_span!("close_market");
+    let _enter = span.enter();
+
+    let store = &deployment.store;
+    let keeper = deployment.user_client(Deployment::DEFAULT_KEEPER)?;
+
+    let [index, long_token, short_token] = Deployment::SELECT_CLOSE_MARKET;
+    let market_token = *deployment
+        .market_token(index, long_token, short_token)
+        .expect("must exist");
+    let market_address = keeper.find_market_address(store, &market_token);
+
+    // The market has never been used, so all of its pools are drained.
+    let market = keeper.market(&market_address).await?;
+    assert!(market.is_drained());
+
+    let (rpc, vault) = keeper.initialize_market_vault(store, &market_token);
+    let signature = rpc.send_without_preflight().await?;
+    tracing::info!(%signature, %vault, "initialized market token vault");
+
+    // Cannot close an enabled market.
+    let err = keeper
+        .close_market(store, &market_token, true, None)
+        .send()
+        .await
+        .expect_err("should throw an error when closing an enabled market");
+    assert_eq!(
+        gmsol::Error::from(err).anchor_error_code(),
+        Some(CoreError::PreconditionsAreNotMet.into())
+    );
+
+    let signature = keeper
+        .toggle_market(store, &market_token, false)
+        .send_without_preflight()
+        .await?;
+    tracing::info!(%signature, %market_token, "disabled market");
+
+    let signature = keeper
+        .close_market(store, &market_token, true, None)
+        .send()
+        .await?;
+    tracing::info!(%signature, %market_token, "closed market");
+
+    assert!(keeper.market(&market_address).await.is_err());
+    assert!(keeper
+        .account::<anchor_spl::token::TokenAccount>(&vault)
+        .await?
+        .is_none());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn close_market_with_residual_balance() -> eyre::Result<()> {
+    let deployment = current_deployment().await?;
+    let _guard = deployment.use_accounts().await?;
+    let span = tracing::info_span!("close_market_with_residual_balance");
+    let _enter = span.enter();
+
+    let store = &deployment.store;
+    let keeper = deployment.user_client(Deployment::DEFAULT_KEEPER)?;
+
+    let market_token = *deployment
+        .prepare_market(
+            Deployment::SELECT_CLOSE_MARKET_WITH_RESIDUAL,
+            1_000_011,
+            1_000_013,
+            true,
+        )
+        .await?;
+    let market_address = keeper.find_market_address(store, &market_token);
+
+    let signature = keeper
+        .toggle_market(store, &market_token, false)
+        .send_without_preflight()
+        .await?;
+    tracing::info!(%signature, %market_token, "disabled market");
+
+    let err = keeper
+        .close_market(store, &market_token, false, None)
+        .send()
+        .await
+        .expect_err("should throw an error when closing a market with residual balance");
+    assert_eq!(
+        gmsol::Error::from(err).anchor_error_code(),
+        Some(CoreError::PreconditionsAreNotMet.into())
+    );
+    assert!(!keeper.market(&market_address).await?.is_drained());
+
+    let signature = keeper
+        .toggle_market(store, &market_token, true)
+        .send_without_preflight()
+        .await?;
+    tracing::info!(%signature, %market_token, "enabled market");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn compress_pool_state() -> eyre::Result<()> {
+    let deployment = current_deployment().await?;
+    let _guard = deployment.use_accounts().await?;
+    let span = tracing::info_span!("compress_pool_state");
+    let _enter = span.enter();
+
+    let store = &deployment.store;
+    let client = &deployment.client;
+    let keeper = deployment.user_client(Deployment::DEFAULT_KEEPER)?;
+
+    let market_token = *deployment
+        .market_token("fBTC", "fBTC", "USDG")
+        .expect("must exist");
+    let market_address = client.find_market_address(store, &market_token);
+    let before = client.market(&market_address).await?;
+
+    // Only ADMIN can compress the market state.
+    let err = keeper
+        .compress_pool_state(store, &market_token)
+        .send()
+        .await
+        .expect_err("should throw an error when compressing by a non-admin");
+    assert_eq!(
+        gmsol::Error::from(err).anchor_error_code(),
+        Some(CoreError::NotAnAdmin.into())
+    );
+
+    let signature = client
+        .compress_pool_state(store, &market_token)
+        .send_without_preflight()
+        .await?;
+    tracing::info!(%signature, %market_token, "compressed market state");
+
+    let after = client.market(&market_address).await?;
+    assert_eq!(after.name()?, before.name()?);
+    assert_eq!(after.store, before.store);
+    assert_eq!(after.meta().market_token_mint, market_token);
+    assert_eq!(after.meta().long_token_mint, before.meta().long_token_mint);
+    assert_eq!(
+        after.meta().short_token_mint,
+        before.meta().short_token_mint
+    );
+
+    // Compressing again is a no-op.
+    let signature = client
+        .compress_pool_state(store, &market_token)
+        .send_without_preflight()
+        .await?;
+    tracing::info!(%signature, %market_token, "compressed market state again");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn export_market_state() -> eyre::Result<()> {
+    let deployment = current_deployment().await?;
+    let _guard = deployment.use_accounts().await?;
+    let span = tracing::info_span!("export_market_state");
+    let _enter = span.enter();
+
+    let store = &deployment.store;
+    let keeper = deployment.user_client(Deployment::DEFAULT_KEEPER)?;
+    let user = deployment.user_client(Deployment::DEFAULT_USER)?;
+
+    let market_token = *deployment
+        .market_token("SOL", "WSOL", "WSOL")
+        .expect("must exist");
+    let market_address = keeper.find_market_address(store, &market_token);
+
+    // Only ORACLE_CONTROLLER can export the market state.
+    let err = user
+        .export_market_state(store, &market_token)
+        .send()
+        .await
+        .expect_err("should throw an error when exporting by a non-oracle-controller");
+    assert_eq!(
+        gmsol::Error::from(err).anchor_error_code(),
+        Some(CoreError::PermissionDenied.into())
+    );
+
+    let export = || async {
+        let tx = keeper
+            .export_market_state(store, &market_token)
+            .signed_transaction_with_options(true, None)
+            .await?;
+        gmsol::utils::view::<[u8; 32]>(&keeper.store_program().rpc(), &tx).await
+    };
+
+    let state_hash = export().await?;
+    let market = keeper.market(&market_address).await?;
+    let expected = blake3::hash(bytemuck::bytes_of(&*market)).to_bytes();
+    assert_eq!(state_hash, expected);
+    assert!(
+        keeper
+            .verify_market_state(&market_address, &state_hash)
+            .await?
+    );
+
+    let signature = keeper
+        .export_market_state(store, &market_token)
+        .send_without_preflight()
+        .await?;
+    tracing::info!(%signature, %market_token, "exported market state");
+
+    // Mutate one field of the market.
+    let key = MarketConfigKey::MinCollateralFactor;
+    let factor = *market.get_config_by_key(key);
+    let signature = keeper
+        .update_market_config_by_key(store, &market_token, key, &(factor + 1))?
+        .send_without_preflight()
+        .await?;
+    tracing::info!(%signature, %market_token, %key, "updated market config");
+
+    let new_state_hash = export().await?;
+    assert_ne!(new_state_hash, state_hash);
+    assert!(
+        !keeper
+            .verify_market_state(&market_address, &state_hash)
+            .await?
+    );
+    assert!(
+        keeper
+            .verify_market_state(&market_address, &new_state_hash)
+            .await?
+    );
+
+    // Restore the market config.
+    let signature = keeper
+        .update_market_config_by_key(store, &market_token, key, &factor)?
+        .send_without_preflight()
+        .await?;
+    tracing::info!(%signature, %market_token, %key, "restored market config");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn validate_market_config() -> eyre::Result<()> {
+    let deployment = current_deployment().await?;
+    let _guard = deployment.use_accounts().await?;
+    let span = tracing::info_span!("validate_market_config");
+    let _enter = span.enter();
+
+    let store = &deployment.store;
+    let keeper = deployment.user_client(Deployment::DEFAULT_KEEPER)?;
+
+    let market_token = *deployment
+        .market_token("SOL", "WSOL", "WSOL")
+        .expect("must exist");
+    let market = keeper
+        .market(&keeper.find_market_address(store, &market_token))
+        .await?;
+
+    let problems = keeper.market_config_problems(store, &market_token).await?;
+    assert!(problems.is_empty());
+
+    // Set deliberately bad configs.
+    let bad = [
+        (MarketConfigKey::SwapImpactExponent, 0),
+        (MarketConfigKey::ReserveFactor, 2 * MARKET_USD_UNIT),
+    ];
+    let original = bad.map(|(key, _)| (key, *market.get_config_by_key(key)));
+    for (key, value) in bad {
+        let signature = keeper
+            .update_market_config_by_key(store, &market_token, key, &value)?
+            .send_without_preflight()
+            .await?;
+        tracing::info!(%signature, %market_token, %key, %value, "set bad market config");
+    }
+
+    let problems = keeper.market_config_problems(store, &market_token).await?;
+    tracing::info!(?problems, "validated market config");
+    let keys = problems
+        .iter()
+        .map(|problem| problem.key.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(keys, ["swap_impact_exponent", "reserve_factor"]);
+
+    // Restore the configs.
+    for (key, value) in original {
+        let signature = keeper
+            .update_market_config_by_key(store, &market_token, key, &value)?
+            .send_without_preflight()
+            .await?;
+        tracing::info!(%signature, %market_token, %key, "restored market config");
+    }
+
+    let problems = keeper.market_config_problems(store, &market_token).await?;
+    assert!(problems.is_empty());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn list_market_config_keys() -> eyre::Result<()> {
+    let deployment = current_deployment().await?;
+    let _guard = deployment.use_accounts().await?;
+    let span = tracing::info_span!("list_market_config_keys");
+    let _enter = span.enter();
+
+    let store = &deployment.store;
+    let client = deployment.user_client(Deployment::DEFAULT_USER)?;
+
+    let keys = client.market_config_keys(store).await?;
+    assert_eq!(keys.len(), MarketConfigKey::iter().count());
+    for (name, key) in keys.iter().zip(MarketConfigKey::iter()) {
+        assert_eq!(*name, key.to_string());
+    }
+
+    let flags = client.market_config_flags(store).await?;
+    assert_eq!(flags.len(), MarketConfigFlag::iter().count());
+    for (name, flag) in flags.iter().zip(MarketConfigFlag::iter()) {
+        assert_eq!(*name, flag.to_string());
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn initialize_market_with_price_caps() -> eyre::Result<()> {
+    let deployment = current_deployment().await?;
+    let _guard = deployment.use_accounts().await?;
+    let span = tracing::info_span!("initialize_market_with_price_caps");
+    let _enter = span.enter();
+
+    let store = &deployment.store;
+    let keeper = deployment.user_client(Deployment::DEFAULT_KEEPER)?;
+    let token_map = deployment.token_map();
+
+    let index_token = deployment.token("fETH").expect("must exist").address;
+    let long_token = deployment.token("USDH").expect("must exist").address;
+    let short_token = deployment.token("USDH").expect("must exist").address;
+    let wsol = deployment.token("WSOL").expect("must exist").address;
+
+    // Only lower bounds are set so that the prices used by other tests are not affected.
+    let price_caps = [(index_token, 1), (long_token, 2), (wsol, 3)]
+        .into_iter()
+        .map(|(token, min_price)| TokenPriceCapConfig {
+            token,
+            min_price,
+            max_price: 0,
+        })
+        .collect::<Vec<_>>();
+
+    let (rpc, market_token) = keeper
+        .create_market_with_price_caps(
+            store,
+            "fETH/USD[USDH-USDH]",
+            &index_token,
+            &long_token,
+            &short_token,
+            true,
+            price_caps.clone(),
+            Some(&token_map),
+        )
+        .await?;
+    let mut builder = keeper.bundle();
+    builder.push(rpc)?;
+    let signatures = builder.send_all(false).await.map_err(|(_, err)| err)?;
+    tracing::info!(?signatures, %market_token, "initialized market with price caps");
+
+    let market = keeper
+        .market(&keeper.find_market_address(store, &market_token))
+        .await?;
+    assert!(market.is_enabled());
+
+    let map = keeper.token_map(&token_map).await?;
+    for cap in price_caps {
+        let config = map.get(&cap.token).expect("must exist");
+        let feed_config = config.get_feed_config(&config.expected_provider()?)?;
+        assert_eq!(
+            feed_config.price_band(),
+            Some((cap.min_price, cap.max_price))
+        );
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn swap_arbitrage_opportunity() -> eyre::Result<()> {
+    let deployment = current_deployment().await?;
+    let _guard = deployment.use_accounts().await?;
+    let span = tracing::info_span!("swap_arbitrage_opportunity");
+    let _enter = span.enter();
+
+    let store = &deployment.store;
+    let keeper = deployment.user_client(Deployment::DEFAULT_KEEPER)?;
+
+    // Two markets with the same pair of collateral tokens.
+    let market_token_1 = *deployment
+        .prepare_market(["fBTC", "fBTC", "USDG"], 1_000_013, 6_000_000_000_017, true)
+        .await?;
+    let market_token_2 = *deployment
+        .prepare_market(["SOL", "fBTC", "USDG"], 1_000_013, 6_000_000_000_017, true)
+        .await?;
+
+    let fbtc = deployment.token("fBTC").expect("must exist").address;
+    let usdg = deployment.token("USDG").expect("must exist").address;
+
+    let price = |price: u128| Price {
+        min: price,
+        max: price,
+    };
+    // fBTC has 6 decimals.
+    let btc_price = 100_000 * MARKET_USD_UNIT / 1_000_000;
+    // USDG has 8 decimals.
+    let usd_price = MARKET_USD_UNIT / 100_000_000;
+    // SOL has 9 decimals.
+    let sol_price = 200 * MARKET_USD_UNIT / 1_000_000_000;
+    let prices_1 = Prices {
+        index_token_price: price(btc_price),
+        long_token_price: price(btc_price),
+        short_token_price: price(usd_price),
+    };
+    let prices_2 = Prices {
+        index_token_price: price(sol_price),
+        long_token_price: price(btc_price),
+        short_token_price: price(usd_price),
+    };
+    let prices = BTreeMap::from([(market_token_1, prices_1), (market_token_2, prices_2)]);
+
+    // Set the swap impact factors of the second market.
+    let market_2 = keeper
+        .market(&keeper.find_market_address(store, &market_token_2))
+        .await?;
+    let keys = [
+        MarketConfigKey::SwapImpactPositiveFactor,
+        MarketConfigKey::SwapImpactNegativeFactor,
+    ];
+    let factors = keys.map(|key| *market_2.get_config_by_key(key));
+    for key in keys {
+        let signature = keeper
+            .update_market_config_by_key(store, &market_token_2, key, &(MARKET_USD_UNIT / 100))?
+            .send_without_preflight()
+            .await?;
+        tracing::info!(%signature, %market_token_2, %key, "set swap impact factor");
+    }
+
+    let amount = 10_000;
+    let opportunity = keeper
+        .swap_arbitrage_opportunity(store, &fbtc, &usdg, amount, prices.clone())
+        .await?;
+    tracing::info!(?opportunity, "found swap arbitrage opportunity");
+
+    let mut fees = Vec::with_capacity(2);
+    for (market_token, prices) in prices.iter() {
+        let breakdown = keeper
+            .market_swap_fees(store, market_token, &fbtc, &usdg, amount, *prices)
+            .await?;
+        fees.push((*market_token, breakdown));
+    }
+    let (best_market_token, best) = fees
+        .iter()
+        .max_by_key(|(_, breakdown)| breakdown.output_amount)
+        .expect("must exist");
+    let (_, worst) = fees
+        .iter()
+        .min_by_key(|(_, breakdown)| breakdown.output_amount)
+        .expect("must exist");
+    // The first market has no swap impact, so the path with the lower impact cost wins.
+    assert!(best.price_impact_usd > worst.price_impact_usd);
+    assert_eq!(opportunity.best_path, [*best_market_token]);
+    assert_eq!(opportunity.input_amount, amount);
+    assert_eq!(opportunity.output_amount, best.output_amount);
+    assert_eq!(opportunity.price_impact_total, best.price_impact_usd);
+
+    // Restore the swap impact factors.
+    for (key, factor) in keys.into_iter().zip(factors) {
+        let signature = keeper
+            .update_market_config_by_key(store, &market_token_2, key, &factor)?
+            .send_without_preflight()
+            .await?;
+        tracing::info!(%signature, %market_token_2, %key, "restored swap impact factor");
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn market_token_backing() -> eyre::Result<()> {
+    let deployment = current_deployment().await?;
+    let _guard = deployment.use_accounts().await?;
+    let span = tracing::info_span!("market_token_backing");
+    let _enter = span.enter();
+
+    let store = &deployment.store;
+    let keeper = deployment.user_client(Deployment::DEFAULT_KEEPER)?;
+
+    // A pure market.
+    let market_token = *deployment
+        .prepare_market(["fBTC", "fBTC", "USDG"], 1_000_019, 6_000_000_000_019, true)
+        .await?;
+
+    let price = |price: u128| Price {
+        min: price,
+        max: price,
+    };
+    // fBTC has 6 decimals.
+    let btc_price = 100_000 * MARKET_USD_UNIT / 1_000_000;
+    // USDG has 8 decimals.
+    let usd_price = MARKET_USD_UNIT / 100_000_000;
+    let prices = Prices {
+        index_token_price: price(btc_price),
+        long_token_price: price(btc_price),
+        short_token_price: price(usd_price),
+    };
+
+    let backing = keeper
+        .market_token_backing(store, &market_token, prices)
+        .await?;
+    tracing::info!(?backing, "fetched market token backing");
+
+    let market = keeper
+        .market(&keeper.find_market_address(store, &market_token))
+        .await?;
+    let pool = market.pool(PoolKind::Primary).expect("must exist");
+    let total = pool.long_amount()? + pool.short_amount()?;
+    let supply = keeper
+        .account::<anchor_spl::token_interface::Mint>(&market_token)
+        .await?
+        .expect("must exist")
+        .supply;
+    assert!(supply > 0);
+
+    // Market tokens have 9 decimals.
+    let one = 1_000_000_000;
+    assert_eq!(
+        backing.long_amount as u128,
+        total * one / u128::from(supply)
+    );
+    assert_eq!(backing.short_amount, 0);
+    assert_eq!(
+        Some(backing.value),
+        (total * btc_price).checked_mul_div(&one, &u128::from(supply))
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn verify_market_token_supply() -> eyre::Result<()> {
+    use anchor_spl::associated_token::get_associated_token_address;
+
+    let deployment = current_deployment().await?;
+    let _guard = deployment.use_accounts().await?;
+    let span = tracing::info_span!("verify_market_token_supply");
+    let _enter = span.enter();
+
+    let store = &deployment.store;
+    let keeper = deployment.user_client(Deployment::DEFAULT_KEEPER)?;
+
+    let market_token = *deployment
+        .prepare_market(
+            Deployment::SELECT_SUPPLY_VERIFICATION_MARKET,
+            1_000_027,
+            1_000_029,
+            true,
+        )
+        .await?;
+
+    let price = |price: u128| Price {
+        min: price,
+        max: price,
+    };
+    // fBTC has 6 decimals.
+    let btc_price = 100_000 * MARKET_USD_UNIT / 1_000_000;
+    // WSOL has 9 decimals.
+    let sol_price = 200 * MARKET_USD_UNIT / 1_000_000_000;
+    let prices = Prices {
+        index_token_price: price(sol_price),
+        long_token_price: price(btc_price),
+        short_token_price: price(btc_price),
+    };
+
+    let verification = keeper
+        .market_token_supply_verification(store, &market_token, prices)
+        .await?;
+    tracing::info!(?verification, "verified market token supply");
+    assert!(verification.current_supply > 0);
+    assert!(verification.is_consistent);
+
+    // Transfer tokens into the market without minting market tokens.
+    let amount = 100_000;
+    deployment
+        .mint_or_transfer_to_user("fBTC", Deployment::DEFAULT_KEEPER, amount)
+        .await?;
+    let fbtc = deployment.token("fBTC").expect("must exist");
+    let source_account = get_associated_token_address(&keeper.payer(), &fbtc.address);
+    let signature = keeper
+        .fund_market(
+            store,
+            &market_token,
+            &source_account,
+            amount,
+            Some(&fbtc.address),
+        )
+        .await?
+        .send()
+        .await?;
+    tracing::info!(%signature, %market_token, "transferred {amount} into the market");
+
+    let verification = keeper
+        .market_token_supply_verification(store, &market_token, prices)
+        .await?;
+    tracing::info!(
+        ?verification,
+        "verified market token supply after the transfer"
+    );
+    assert!(!verification.is_consistent);
+    assert!(verification.delta > 0);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn get_lp_nav_without_oracle_prices() -> eyre::Result<()> {
+    let deployment = current_deployment().await?;
+    let _guard = deployment.use_accounts().await?;
+    let span = tracing::info_span!("get_lp_nav_without_oracle_prices");
+    let _enter = span.enter();
+
+    let store = &deployment.store;
+    let keeper = deployment.user_client(Deployment::DEFAULT_KEEPER)?;
+    let oracle = deployment.oracle();
+
+    let market_token = *deployment
+        .market_token("fBTC", "fBTC", "USDG")
+        .expect("must exist");
+
+    // The prices of the oracle are cleared after each execution.
+    let err = keeper
+        .get_lp_nav(store, &oracle, &market_token, true)
+        .send()
+        .await
+        .expect_err("should throw an error when the oracle prices are missing");
+    assert_eq!(
+        gmsol::Error::from(err).anchor_error_code(),
+        Some(CoreError::MissingOraclePrice.into())
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn set_market_name() -> eyre::Result<()> {
+    let deployment = current_deployment().await?;
+    let _guard = deployment.use_accounts().await?;
+    let span = tracing::info_span!("set_market_name");
+    let _enter = span.enter();
+
+    let store = &deployment.store;
+    let keeper = deployment.user_client(Deployment::DEFAULT_KEEPER)?;
+
+    let market_token = deployment
+        .market_token("fETH", "fETH", "USDH")
+        .expect("must exist");
+    let market_address = keeper.find_market_address(store, market_token);
+    let old_name = keeper.market(&market_address).await?.name()?.to_string();
+
+    let new_name = "fETH/USD[fETH-USDH] (renamed)";
+    let signature = keeper
+        .set_market_name(store, market_token, new_name)
+        .send()
+        .await?;
+    tracing::info!(%signature, %old_name, %new_name, "renamed market");
+    assert_eq!(keeper.market(&market_address).await?.name()?, new_name);
+
+    // A name that is too long is rejected.
+    let err = keeper
+        .set_market_name(store, market_token, &"x".repeat(64))
+        .send()
+        .await
+        .expect_err("should throw an error when the name is too long");
+    assert_eq!(
+        gmsol::Error::from(err).anchor_error_code(),
+        Some(CoreError::ExceedMaxLengthLimit.into())
+    );
+
+    let signature = keeper
+        .set_market_name(store, market_token, &old_name)
+        .send()
+        .await?;
+    tracing::info!(%signature, %old_name, "restored market name");
+    assert_eq!(keeper.market(&market_address).await?.name()?, old_name);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn fee_structure_proposal() -> eyre::Result<()> {
+    let deployment = current_deployment().await?;
+    let _guard = deployment.use_accounts().await?;
+    let span = tracing::info_span!("fee_structure_proposal");
+    let _enter = span.enter();
+
+    let store = &deployment.store;
+    let keeper = deployment.user_client(Deployment::DEFAULT_KEEPER)?;
+    let admin = &deployment.client;
+    let user = deployment.user_client(Deployment::DEFAULT_USER)?;
+
+    let market_token = *deployment
+        .market_token("fETH", "fETH", "USDH")
+        .expect("must exist");
+    let market_address = keeper.find_market_address(store, &market_token);
+    let market = keeper.market(&market_address).await?;
+
+    let keys = [
+        MarketConfigKey::SwapFeeFactorForPositiveImpact,
+        MarketConfigKey::PositionFeeFactorForPositiveImpact,
+    ];
+    let original = keys.map(|key| (key, *market.get_config_by_key(key)));
+    let changes = original
+        .iter()
+        .map(|(key, factor)| EntryArgs {
+            key: key.to_string(),
+            value: factor + 1,
+        })
+        .collect::<Vec<_>>();
+
+    // Only MARKET_KEEPER can propose.
+    let err = user
+        .propose_fee_structure_change(store, &market_token, changes.clone(), 5)
+        .send()
+        .await
+        .expect_err("should throw an error when proposing by a non-market-keeper");
+    assert_eq!(
+        gmsol::Error::from(err).anchor_error_code(),
+        Some(CoreError::PermissionDenied.into())
+    );
+
+    let (rpc, proposal) = keeper
+        .propose_fee_structure_change(store, &market_token, changes, 5)
+        .swap_output(());
+    let signature = rpc.send().await?;
+    tracing::info!(%signature, %proposal, "proposed fee structure change");
+
+    let signature = keeper
+        .vote_on_fee_structure(store, &proposal, true)
+        .send()
+        .await?;
+    tracing::info!(%signature, %proposal, "voted for the proposal");
+
+    // Each keeper can only vote once.
+    keeper
+        .vote_on_fee_structure(store, &proposal, false)
+        .send()
+        .await
+        .expect_err("should throw an error when voting twice");
+
+    let signature = admin
+        .vote_on_fee_structure(store, &proposal, true)
+        .send()
+        .await?;
+    tracing::info!(%signature, %proposal, "voted for the proposal");
+
+    // Cannot be executed before the voting ends.
+    let err = keeper
+        .execute_fee_structure_proposal(store, &market_token, &proposal)
+        .send()
+        .await
+        .expect_err("should throw an error when executing before the voting ends");
+    assert_eq!(
+        gmsol::Error::from(err).anchor_error_code(),
+        Some(CoreError::PreconditionsAreNotMet.into())
+    );
+
+    tokio::time::sleep(Duration::from_secs(7)).await;
+    let signature = keeper
+        .execute_fee_structure_proposal(store, &market_token, &proposal)
+        .send()
+        .await?;
+    tracing::info!(%signature, %proposal, "executed the proposal");
+
+    let state = keeper
+        .account::<FeeStructureProposal>(&proposal)
+        .await?
+        .expect("must exist");
+    assert!(state.is_executed);
+    assert_eq!((state.votes_for, state.votes_against), (2, 0));
+
+    let market = keeper.market(&market_address).await?;
+    for (key, factor) in original {
+        assert_eq!(*market.get_config_by_key(key), factor + 1);
+    }
+
+    // Cannot be executed twice.
+    keeper
+        .execute_fee_structure_proposal(store, &market_token, &proposal)
+        .send()
+        .await
+        .expect_err("should throw an error when executing twice");
+
+    for (key, factor) in original {
+        let signature = keeper
+            .update_market_config_by_key(store, &market_token, key, &factor)?
+            .send_without_preflight()
+            .await?;
+        tracing::info!(%signature, %market_token, %key, "restored market config");
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn liquidity_mining() -> eyre::Result<()> {
+    let deployment = current_deployment().await?;
+    let _guard = deployment.use_accounts().await?;
+    let span = tracing::info_span!("liquidity_mining");
+    let _enter = span.enter();
+
+    let admin = &deployment.client;
+    let user = deployment.user_client(Deployment::DEFAULT_USER)?;
+    let store = &deployment.store;
+    let market_token = *deployment
+        .prepare_market(["fBTC", "fBTC", "USDG"], 1_000_011, 6_000_000_013, true)
+        .await?;
+    let usdg = deployment.token("USDG").expect("must exist").address;
+
+    // The validator clock cannot be advanced, so we use a short campaign and wait.
+    let now = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)?
+        .as_secs() as i64;
+    let duration = 10;
+    let stake_amount = 1_000_000_000;
+    let total_reward = 1_000_000;
+    let params = CampaignParams {
+        start_time: now + 10,
+        end_time: now + 10 + duration,
+        total_reward,
+    };
+
+    deployment
+        .mint_or_transfer_to("USDG", &admin.payer(), total_reward)
+        .await?;
+    let campaign_index = 0;
+    let signature = admin
+        .initialize_liquidity_mining_campaign(
+            store,
+            &market_token,
+            &usdg,
+            campaign_index,
+            params.clone(),
+            None,
+        )
+        .send()
+        .await?;
+    tracing::info!(%signature, "initialized liquidity mining campaign");
+    let campaign = admin.find_liquidity_mining_campaign_address(store, campaign_index);
+    let checkpoint = admin.find_lp_balance_checkpoint_address(&campaign, &user.payer());
+
+    // Stake before the campaign starts.
+    let signature = user
+        .stake_for_liquidity_mining(store, &market_token, campaign_index, stake_amount, None)
+        .send()
+        .await?;
+    tracing::info!(%signature, "staked market tokens");
+    let staked = user
+        .account::<ZeroCopy<LpBalanceCheckpoint>>(&checkpoint)
+        .await?
+        .expect("must exist")
+        .0;
+    assert_eq!(staked.balance(), stake_amount);
+    assert_eq!(staked.reward_per_market_token_paid(), 0);
+
+    let usdg_before = deployment
+        .get_user_ata_amount(&usdg, Some(Deployment::DEFAULT_USER))
+        .await?
+        .unwrap_or(0);
+
+    // Claim in the middle of the campaign.
+    tokio::time::sleep(Duration::from_secs(15)).await;
+    let signature = user
+        .claim_liquidity_mining_rewards(store, &usdg, campaign_index, None)
+        .send()
+        .await?;
+    tracing::info!(%signature, "claimed in the middle of the campaign");
+    let state = user
+        .account::<ZeroCopy<LpBalanceCheckpoint>>(&checkpoint)
+        .await?
+        .expect("must exist")
+        .0;
+    assert!(state.claimed() <= total_reward);
+
+    // Claim after the campaign ends.
+    tokio::time::sleep(Duration::from_secs(10)).await;
+    let signature = user
+        .claim_liquidity_mining_rewards(store, &usdg, campaign_index, None)
+        .send()
+        .await?;
+    tracing::info!(%signature, "claimed after the campaign ends");
+    let state = user
+        .account::<ZeroCopy<LpBalanceCheckpoint>>(&checkpoint)
+        .await?
+        .expect("must exist")
+        .0;
+    let campaign_state = user
+        .account::<ZeroCopy<LiquidityMiningCampaign>>(&campaign)
+        .await?
+        .expect("must exist")
+        .0;
+    let usdg_after = deployment
+        .get_user_ata_amount(&usdg, Some(Deployment::DEFAULT_USER))
+        .await?
+        .expect("must exist");
+    // The only staker receives all the rewards released during the campaign, and never more.
+    assert_eq!(state.claimed(), total_reward);
+    assert_eq!(state.pending_reward(), 0);
+    assert_eq!(usdg_after - usdg_before, total_reward);
+    assert_eq!(campaign_state.total_claimed(), total_reward);
+
+    // Staking after the campaign ends is not allowed.
+    let err = user
+        .stake_for_liquidity_mining(store, &market_token, campaign_index, 1, None)
+        .send()
+        .await
+        .expect_err("should throw an error when staking after the campaign ends");
+    assert_eq!(
+        err.anchor_error_code(),
+        Some(CoreError::PreconditionsAreNotMet.into())
+    );
+
+    let signature = user
+        .unstake_from_liquidity_mining(store, &market_token, campaign_index, stake_amount, None)
+        .send()
+        .await?;
+    tracing::info!(%signature, "unstaked market tokens");
+
+    Ok(())
+}
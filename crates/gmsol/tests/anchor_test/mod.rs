@@ -22,3 +22,11 @@ mod glv;
 mod oracle;
 
 mod token;
+
+mod config;
+
+mod gt;
+
+mod discover;
+
+mod store;
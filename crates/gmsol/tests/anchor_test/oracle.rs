@@ -2,13 +2,21 @@ use std::time::Duration;
 
 use gmsol::{
     chainlink::{self, pull_oracle::parse_feed_id},
+    constants::MARKET_USD_UNIT,
     exchange::ExchangeOps,
-    store::oracle::OracleOps,
-    types::PriceProviderKind,
-    utils::builder::{EstimateFee, MakeBundleBuilder, WithPullOracle},
+    store::{
+        oracle::{MonitorPriceFeed, OracleOps},
+        store_ops::StoreOps,
+    },
+    types::{Oracle, PriceAdjustmentSchedule, PriceProviderKind},
+    utils::{
+        builder::{EstimateFee, MakeBundleBuilder, WithPullOracle},
+        ZeroCopy,
+    },
 };
 
-use anchor_client::solana_sdk::pubkey::Pubkey;
+use anchor_client::solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer};
+use gmsol_store::CoreError;
 
 use crate::anchor_test::setup::{current_deployment, Deployment};
 
@@ -61,6 +69,287 @@ async fn update_chainlink_price_feed() -> eyre::Result<()> {
 
     tracing::info!(%signature, %feed, "updated price feed with chainlink report");
 
+    let status = keeper.price_feed_status(&feed).await?;
+    assert_eq!(status.token, usdg.address);
+    assert_eq!(
+        status.provider,
+        u8::from(PriceProviderKind::ChainlinkDataStreams)
+    );
+    assert!(!status.is_stale);
+
+    let unhealthy = MonitorPriceFeed::new(&keeper, [feed]).check().await?;
+    assert!(unhealthy.is_empty());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn chainlink_verifier_whitelist() -> eyre::Result<()> {
+    let deployment = current_deployment().await?;
+    let _guard = deployment.use_accounts().await?;
+    let span = tracing::info_span!("chainlink_verifier_whitelist");
+    let _enter = span.enter();
+
+    let index = 254;
+    let whitelist_index = 3;
+    let store = &deployment.store;
+    let client = &deployment.client;
+    let chainlink_verifier_program = &deployment.chainlink_verifier_program;
+    let chainlink_access_controller = &deployment.chainlink_access_controller;
+    let keeper = deployment.user_client(Deployment::DEFAULT_KEEPER)?;
+
+    let usdg = deployment.token("USDG").unwrap();
+
+    let feed_id_hex = "0x0003dc85e8b01946bf9dfd8b0db860129181eb6105a8c8981d9f28e00b6f60d9";
+    let feed_id = Pubkey::new_from_array(parse_feed_id(feed_id_hex)?);
+
+    let (rpc, feed) = keeper.initialize_price_feed(
+        store,
+        index,
+        PriceProviderKind::ChainlinkDataStreams,
+        &usdg.address,
+        &feed_id,
+    );
+    let signature = rpc.send_without_preflight().await?;
+    tracing::info!(%signature, %feed, "created a chainlink feed for USDG");
+
+    let chainlink = chainlink::Client::from_testnet_defaults().ok();
+    let report = match chainlink.as_ref() {
+        Some(chainlink) => chainlink.latest_report(feed_id_hex).await?.report_bytes()?,
+        None => vec![0; 64],
+    };
+
+    // Another Chainlink verifier program that is not in the whitelist.
+    let other_verifier_program = if *chainlink_verifier_program == gmsol_mock_chainlink_verifier::ID
+    {
+        gmsol_chainlink_datastreams::verifier::ID
+    } else {
+        gmsol_mock_chainlink_verifier::ID
+    };
+
+    let store_account = client.store(store).await?;
+    assert!(store_account.is_trusted_chainlink_verifier(chainlink_verifier_program));
+    assert!(!store_account.is_trusted_chainlink_verifier(&other_verifier_program));
+
+    let err = keeper
+        .update_price_feed_with_chainlink(
+            store,
+            &feed,
+            &other_verifier_program,
+            chainlink_access_controller,
+            &report,
+        )?
+        .send()
+        .await
+        .expect_err("should throw an error when using an untrusted verifier");
+    assert_eq!(
+        err.anchor_error_code(),
+        Some(CoreError::UntrustedChainlinkVerifier.into())
+    );
+
+    // Trust the other verifier.
+    let signature = client
+        .set_trusted_chainlink_verifier(store, whitelist_index, &other_verifier_program)
+        .send()
+        .await?;
+    tracing::info!(%signature, %other_verifier_program, "trusted the other verifier");
+    let store_account = client.store(store).await?;
+    assert!(store_account.is_trusted_chainlink_verifier(&other_verifier_program));
+
+    // Setting the entry to the default pubkey removes it from the whitelist.
+    let signature = client
+        .set_trusted_chainlink_verifier(store, whitelist_index, &Pubkey::default())
+        .send()
+        .await?;
+    tracing::info!(%signature, "removed the other verifier");
+    let store_account = client.store(store).await?;
+    assert!(!store_account.is_trusted_chainlink_verifier(&other_verifier_program));
+    assert!(!store_account.is_trusted_chainlink_verifier(&Pubkey::default()));
+
+    let err = keeper
+        .update_price_feed_with_chainlink(
+            store,
+            &feed,
+            &other_verifier_program,
+            chainlink_access_controller,
+            &report,
+        )?
+        .send()
+        .await
+        .expect_err("should throw an error when using a removed verifier");
+    assert_eq!(
+        err.anchor_error_code(),
+        Some(CoreError::UntrustedChainlinkVerifier.into())
+    );
+
+    if chainlink.is_none() {
+        tracing::warn!("the envs for Chainlink Data Streams are not set");
+        return Ok(());
+    }
+
+    // The trusted verifier can be used to update the price feed.
+    let signature = keeper
+        .update_price_feed_with_chainlink(
+            store,
+            &feed,
+            chainlink_verifier_program,
+            chainlink_access_controller,
+            &report,
+        )?
+        .send_without_preflight()
+        .await?;
+    tracing::info!(%signature, %feed, "updated price feed with the trusted verifier");
+
+    let status = keeper.price_feed_status(&feed).await?;
+    assert!(!status.is_stale);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn rotate_oracle() -> eyre::Result<()> {
+    let deployment = current_deployment().await?;
+    let _guard = deployment.use_accounts().await?;
+    let span = tracing::info_span!("rotate_oracle");
+    let _enter = span.enter();
+
+    let store = &deployment.store;
+    let admin = &deployment.client;
+    let client = deployment.user_client(Deployment::DEFAULT_USER)?;
+    let keeper = deployment.user_client(Deployment::DEFAULT_KEEPER)?;
+
+    let old_oracle = Keypair::new();
+    let new_oracle = Keypair::new();
+    for oracle in [&old_oracle, &new_oracle] {
+        let (rpc, oracle) = admin.initialize_oracle(store, oracle, None).await?;
+        let signature = rpc.send().await?;
+        tracing::info!(%signature, %oracle, "initialized an oracle");
+    }
+    let old_oracle = old_oracle.pubkey();
+    let new_oracle = new_oracle.pubkey();
+
+    let signature = admin
+        .rotate_oracle(store, &old_oracle, &new_oracle)
+        .send()
+        .await?;
+    tracing::info!(%signature, %old_oracle, %new_oracle, "rotated the oracle");
+
+    let err = admin
+        .rotate_oracle(store, &old_oracle, &new_oracle)
+        .send()
+        .await
+        .expect_err("should throw an error when rotating a decommissioned oracle");
+    assert_eq!(
+        err.anchor_error_code(),
+        Some(CoreError::OracleDecommissioned.into())
+    );
+
+    let market_token = deployment
+        .prepare_market(["fBTC", "fBTC", "USDG"], 1_000_011, 6_000_000_000_013, true)
+        .await?;
+    let amount = 1_000;
+    deployment
+        .mint_or_transfer_to_user("fBTC", Deployment::DEFAULT_USER, amount)
+        .await?;
+    let (rpc, deposit) = client
+        .create_deposit(store, market_token)
+        .long_token(amount, None, None)
+        .build_with_address()
+        .await?;
+    let signature = rpc.send().await?;
+    tracing::info!(%deposit, %signature, "created a deposit");
+
+    // The decommissioned oracle rejects new prices.
+    let mut builder = keeper.execute_deposit(store, &old_oracle, &deposit, false);
+    let err = deployment
+        .execute_with_pyth(&mut builder, None, false, false)
+        .await
+        .expect_err("should throw an error when using a decommissioned oracle");
+    assert_eq!(
+        err.anchor_error_code(),
+        Some(CoreError::OracleDecommissioned.into())
+    );
+
+    // The new oracle accepts new prices.
+    let mut builder = keeper.execute_deposit(store, &new_oracle, &deposit, false);
+    deployment
+        .execute_with_pyth(&mut builder, None, true, true)
+        .await?;
+    tracing::info!(%deposit, "executed the deposit with the new oracle");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn close_decommissioned_oracle() -> eyre::Result<()> {
+    let deployment = current_deployment().await?;
+    let _guard = deployment.use_accounts().await?;
+    let span = tracing::info_span!("close_decommissioned_oracle");
+    let _enter = span.enter();
+
+    let store = &deployment.store;
+    let admin = &deployment.client;
+
+    let old_oracle = Keypair::new();
+    let new_oracle = Keypair::new();
+    for oracle in [&old_oracle, &new_oracle] {
+        let (rpc, oracle) = admin.initialize_oracle(store, oracle, None).await?;
+        let signature = rpc.send().await?;
+        tracing::info!(%signature, %oracle, "initialized an oracle");
+    }
+    let old_oracle = old_oracle.pubkey();
+    let new_oracle = new_oracle.pubkey();
+    let receiver = Pubkey::new_unique();
+
+    // An active oracle cannot be closed.
+    let err = admin
+        .close_decommissioned_oracle(store, &old_oracle, Some(&receiver))
+        .send()
+        .await
+        .expect_err("should throw an error when closing an active oracle");
+    assert_eq!(
+        err.anchor_error_code(),
+        Some(CoreError::PreconditionsAreNotMet.into())
+    );
+
+    let signature = admin
+        .rotate_oracle(store, &old_oracle, &new_oracle)
+        .send()
+        .await?;
+    tracing::info!(%signature, %old_oracle, %new_oracle, "rotated the oracle");
+
+    let oracle = admin
+        .account::<ZeroCopy<Oracle>>(&old_oracle)
+        .await?
+        .expect("must exist")
+        .0;
+    assert!(oracle.is_decommissioned());
+
+    let rpc = admin.store_program().rpc();
+    let rent = rpc
+        .get_account_with_commitment(&old_oracle, admin.commitment())
+        .await?
+        .value
+        .expect("must exist")
+        .lamports;
+
+    let signature = admin
+        .close_decommissioned_oracle(store, &old_oracle, Some(&receiver))
+        .send()
+        .await?;
+    tracing::info!(%signature, %old_oracle, "closed the decommissioned oracle");
+
+    let account = rpc
+        .get_account_with_commitment(&old_oracle, admin.commitment())
+        .await?
+        .value;
+    assert!(account.is_none(), "the oracle must be closed");
+    let balance = rpc
+        .get_balance_with_commitment(&receiver, admin.commitment())
+        .await?
+        .value;
+    assert_eq!(balance, rent);
+
     Ok(())
 }
 
@@ -117,3 +406,55 @@ async fn use_chainlink_data_streams() -> eyre::Result<()> {
     }
     Ok(())
 }
+
+#[tokio::test]
+async fn price_adjustment_schedule() -> eyre::Result<()> {
+    let deployment = current_deployment().await?;
+    let _guard = deployment.use_accounts().await?;
+    let span = tracing::info_span!("price_adjustment_schedule");
+    let _enter = span.enter();
+
+    let store = &deployment.store;
+    let token_map = deployment.token_map();
+    let keeper = deployment.user_client(Deployment::DEFAULT_KEEPER)?;
+    let token = deployment.token("USDH").expect("must exist").address;
+
+    let (rpc, schedule) = keeper
+        .initialize_price_adjustment_schedule(store, &token_map, &token, [MARKET_USD_UNIT; 24])
+        .swap_output(());
+    let signature = rpc.send_without_preflight().await?;
+    tracing::info!(%signature, %schedule, "initialized price adjustment schedule");
+
+    // Widen the price band by 1.5x during 2 PM UTC.
+    let factor = MARKET_USD_UNIT * 3 / 2;
+    let signature = keeper
+        .update_price_adjustment_schedule(store, &token, 14, factor)
+        .send_without_preflight()
+        .await?;
+    tracing::info!(%signature, "updated price adjustment schedule");
+
+    let account = keeper
+        .account::<ZeroCopy<PriceAdjustmentSchedule>>(&schedule)
+        .await?
+        .expect("must exist")
+        .0;
+    assert_eq!(account.token, token);
+    for (hour, value) in account.hourly_adjustment_factors().iter().enumerate() {
+        let expected = if hour == 14 { factor } else { MARKET_USD_UNIT };
+        assert_eq!(*value, expected);
+    }
+    // 2024-01-01T14:30:00Z.
+    assert_eq!(account.factor_at(1_704_119_400), factor);
+
+    let err = keeper
+        .update_price_adjustment_schedule(store, &token, 24, factor)
+        .send()
+        .await
+        .expect_err("should throw an error when the hour is out of range");
+    assert_eq!(
+        err.anchor_error_code(),
+        Some(CoreError::InvalidArgument.into())
+    );
+
+    Ok(())
+}
@@ -1,10 +1,23 @@
-use std::time::Duration;
+use std::time::{Duration, Instant, SystemTime};
 
+use anchor_client::solana_sdk::pubkey::Pubkey;
 use gmsol::{
-    constants::MARKET_USD_UNIT, exchange::ExchangeOps, store::market::MarketOps,
-    types::MarketConfigKey,
+    constants::{
+        DEFAULT_KEEPER_ASSIGNMENT_TIMEOUT, DEFAULT_MAX_ORDER_CALLBACK_COMPUTE_UNITS,
+        DEFAULT_RECENT_TIME_WINDOW, MARKET_USD_UNIT,
+    },
+    exchange::ExchangeOps,
+    store::{config::ConfigOps, market::MarketOps},
+    types::{order::OrderKind, position::PositionSpec, AmountKey, MarketConfigKey, Position},
+    utils::ZeroCopy,
 };
-use gmsol_model::action::decrease_position::DecreasePositionSwapType;
+use gmsol_model::{
+    action::decrease_position::DecreasePositionSwapType,
+    num::MulDiv,
+    price::{Price, Prices},
+    Balance, BaseMarketExt, PoolKind,
+};
+use gmsol_store::CoreError;
 use tracing::Instrument;
 
 use crate::anchor_test::setup::{current_deployment, Deployment};
@@ -691,3 +704,1643 @@ async fn liquidation() -> eyre::Result<()> {
 
     Ok(())
 }
+
+#[tokio::test]
+async fn keeper_deadline_extension() -> eyre::Result<()> {
+    let deployment = current_deployment().await?;
+    let _guard = deployment.use_accounts().await?;
+    let span = tracing::info_span!("keeper_deadline_extension");
+    let _enter = span.enter();
+
+    let store = &deployment.store;
+    let oracle = &deployment.oracle();
+    let keeper = deployment.user_client(Deployment::DEFAULT_KEEPER)?;
+    let client = deployment.locked_user_client().await?;
+
+    let long_token_amount = 1_000_011;
+    let short_token_amount = 6_000_000_000_013;
+    let market_token = deployment
+        .prepare_market(
+            ["fBTC", "fBTC", "USDG"],
+            long_token_amount,
+            short_token_amount,
+            true,
+        )
+        .await?;
+
+    let collateral_amount = 100 * 100_000_000;
+    let size = 500 * MARKET_USD_UNIT;
+    deployment
+        .mint_or_transfer_to("USDG", &client.payer(), collateral_amount)
+        .await?;
+
+    // Open a position.
+    let (rpc, order) = client
+        .market_increase(store, market_token, false, collateral_amount, true, size)
+        .build_with_address()
+        .await?;
+    let signature = rpc.send().await?;
+    tracing::info!(%order, %signature, %size, "created an increase position order");
+
+    let mut builder = keeper.execute_order(store, oracle, &order, false)?;
+    deployment
+        .execute_with_pyth(&mut builder, None, true, true)
+        .instrument(tracing::info_span!("execute", order=%order))
+        .await?;
+
+    // Shrink the recent time window and grant a deadline extension.
+    let window = 5;
+    let extension_secs = 60;
+    let duration_secs = 20;
+    let signature = keeper
+        .insert_global_amount_by_key(store, AmountKey::RecentTimeWindow, &window)
+        .send_without_preflight()
+        .await?;
+    tracing::info!(%signature, %window, "set recent time window");
+
+    let signature = keeper
+        .grant_keeper_deadline_extension(store, extension_secs, duration_secs)
+        .send_without_preflight()
+        .await?;
+    let granted_at = Instant::now();
+    tracing::info!(%signature, %extension_secs, %duration_secs, "granted deadline extension");
+
+    let recent_timestamp = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)?
+        .as_secs() as i64
+        - 30;
+
+    // Execution with an old timestamp should succeed with the extension.
+    let (rpc, order) = client
+        .market_decrease(store, market_token, false, 0, true, size / 2)
+        .build_with_address()
+        .await?;
+    let signature = rpc.send().await?;
+    tracing::info!(%order, %signature, "created a decrease position order");
+
+    let mut builder = keeper.execute_order(store, oracle, &order, false)?;
+    builder
+        .recent_timestamp(recent_timestamp)
+        .use_deadline_extension(true);
+    deployment
+        .execute_with_pyth(&mut builder, None, true, true)
+        .instrument(tracing::info_span!("execute", order=%order))
+        .await?;
+
+    // Execution with the same timestamp should be rejected after the extension expires.
+    let (rpc, order) = client
+        .market_decrease(store, market_token, false, 0, true, size / 2)
+        .build_with_address()
+        .await?;
+    let signature = rpc.send().await?;
+    tracing::info!(%order, %signature, "created a decrease position order");
+
+    let expiration = Duration::from_secs(u64::from(duration_secs) + 2);
+    if let Some(wait) = expiration.checked_sub(granted_at.elapsed()) {
+        tokio::time::sleep(wait).await;
+    }
+
+    let mut builder = keeper.execute_order(store, oracle, &order, false)?;
+    builder
+        .recent_timestamp(recent_timestamp)
+        .use_deadline_extension(true);
+    let err = deployment
+        .execute_with_pyth(&mut builder, None, true, false)
+        .await
+        .expect_err("should throw an error when the extension has expired");
+    tracing::info!(%err, %order, "expected error");
+
+    // Cleanup.
+    let signature = keeper
+        .revoke_keeper_deadline_extension(store)
+        .send_without_preflight()
+        .await?;
+    tracing::info!(%signature, "revoked deadline extension");
+
+    let window = DEFAULT_RECENT_TIME_WINDOW;
+    let signature = keeper
+        .insert_global_amount_by_key(store, AmountKey::RecentTimeWindow, &window)
+        .send_without_preflight()
+        .await?;
+    tracing::info!(%signature, %window, "restored recent time window");
+
+    let mut builder = keeper.execute_order(store, oracle, &order, false)?;
+    deployment
+        .execute_with_pyth(&mut builder, None, true, true)
+        .instrument(tracing::info_span!("execute", order=%order))
+        .await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn keeper_assignment() -> eyre::Result<()> {
+    let deployment = current_deployment().await?;
+    let _guard = deployment.use_accounts().await?;
+    let span = tracing::info_span!("keeper_assignment");
+    let _enter = span.enter();
+
+    let store = &deployment.store;
+    let oracle = &deployment.oracle();
+    let admin = &deployment.client;
+    let keeper = deployment.user_client(Deployment::DEFAULT_KEEPER)?;
+    let client = deployment.locked_user_client().await?;
+
+    let long_token_amount = 1_000_011;
+    let short_token_amount = 6_000_000_000_013;
+    let market_token = deployment
+        .prepare_market(
+            ["fBTC", "fBTC", "USDG"],
+            long_token_amount,
+            short_token_amount,
+            true,
+        )
+        .await?;
+
+    let collateral_amount = 100 * 100_000_000;
+    let size = 500 * MARKET_USD_UNIT;
+    deployment
+        .mint_or_transfer_to("USDG", &client.payer(), collateral_amount)
+        .await?;
+
+    // Set the priority of keeper A.
+    let keeper_a = Pubkey::new_unique();
+    let signature = admin
+        .set_keeper_priority(store, &keeper_a, 1, 4, [OrderKind::MarketIncrease])
+        .send_without_preflight()
+        .await?;
+    tracing::info!(%signature, %keeper_a, "set keeper priority");
+
+    let timeout = 10;
+    let signature = keeper
+        .insert_global_amount_by_key(store, AmountKey::KeeperAssignmentTimeout, &timeout)
+        .send_without_preflight()
+        .await?;
+    tracing::info!(%signature, %timeout, "set keeper assignment timeout");
+
+    // Create an order assigned to keeper A.
+    let (rpc, order) = client
+        .market_increase(store, market_token, false, collateral_amount, true, size)
+        .assigned_keeper(keeper_a)
+        .build_with_address()
+        .await?;
+    let signature = rpc.send().await?;
+    let created_at = Instant::now();
+    tracing::info!(%order, %signature, %size, "created an order assigned to keeper A");
+
+    // Execution by another keeper should be rejected before the timeout.
+    let mut builder = keeper.execute_order(store, oracle, &order, false)?;
+    let err = deployment
+        .execute_with_pyth(&mut builder, None, true, false)
+        .await
+        .expect_err("should throw an error when the order is assigned to another keeper");
+    tracing::info!(%err, %order, "expected error");
+
+    // Execution by another keeper should succeed after the timeout.
+    let expiration = Duration::from_secs(timeout + 2);
+    if let Some(wait) = expiration.checked_sub(created_at.elapsed()) {
+        tokio::time::sleep(wait).await;
+    }
+
+    let mut builder = keeper.execute_order(store, oracle, &order, false)?;
+    deployment
+        .execute_with_pyth(&mut builder, None, true, true)
+        .instrument(tracing::info_span!("execute", order=%order))
+        .await?;
+
+    // Cleanup.
+    let timeout = DEFAULT_KEEPER_ASSIGNMENT_TIMEOUT;
+    let signature = keeper
+        .insert_global_amount_by_key(store, AmountKey::KeeperAssignmentTimeout, &timeout)
+        .send_without_preflight()
+        .await?;
+    tracing::info!(%signature, %timeout, "restored keeper assignment timeout");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn limit_order_fallback_to_market() -> eyre::Result<()> {
+    let deployment = current_deployment().await?;
+    let _guard = deployment.use_accounts().await?;
+    let span = tracing::info_span!("limit_order_fallback_to_market");
+    let _enter = span.enter();
+
+    let store = &deployment.store;
+    let oracle = &deployment.oracle();
+    let keeper = deployment.user_client(Deployment::DEFAULT_KEEPER)?;
+    let client = deployment.locked_user_client().await?;
+
+    let long_token_amount = 1_000_017;
+    let short_token_amount = 6_000_000_000_019;
+    let market_token = deployment
+        .prepare_market(
+            ["fBTC", "fBTC", "USDG"],
+            long_token_amount,
+            short_token_amount,
+            true,
+        )
+        .await?;
+
+    let collateral_amount = 100 * 100_000_000;
+    let size = 500 * MARKET_USD_UNIT;
+    deployment
+        .mint_or_transfer_to("USDG", &client.payer(), collateral_amount)
+        .await?;
+
+    // Create a limit increase order that will never be triggered.
+    let fallback_secs = 10;
+    let (rpc, order) = client
+        .limit_increase(store, market_token, true, size, 1, false, collateral_amount)
+        .acceptable_price(u128::MAX)
+        .fallback_to_market_after_secs(fallback_secs)
+        .build_with_address()
+        .await?;
+    let signature = rpc.send().await?;
+    let created_at = Instant::now();
+    tracing::info!(%order, %signature, %size, "created a limit increase order with fallback");
+
+    // Execution should fail before the fallback window elapses.
+    let mut builder = keeper.execute_order(store, oracle, &order, false)?;
+    let err = deployment
+        .execute_with_pyth(&mut builder, None, true, false)
+        .await
+        .expect_err("should throw an error when the order is not triggered");
+    tracing::info!(%err, %order, "expected error");
+
+    // Execution should succeed as a market order after the fallback window.
+    let window = Duration::from_secs(u64::from(fallback_secs) + 2);
+    if let Some(wait) = window.checked_sub(created_at.elapsed()) {
+        tokio::time::sleep(wait).await;
+    }
+
+    let mut builder = keeper.execute_order(store, oracle, &order, false)?;
+    deployment
+        .execute_with_pyth(&mut builder, None, true, true)
+        .instrument(tracing::info_span!("execute", order=%order))
+        .await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn order_callback() -> eyre::Result<()> {
+    use anchor_client::solana_sdk::system_program;
+    use futures_util::TryStreamExt;
+    use gmsol::store::events::StoreCPIEvent;
+    use gmsol_mock_order_callback::{accounts, instruction, CallbackRecord, ID, RECORD_SEED};
+
+    let deployment = current_deployment().await?;
+    let _guard = deployment.use_accounts().await?;
+    let span = tracing::info_span!("order_callback");
+    let _enter = span.enter();
+
+    let store = &deployment.store;
+    let oracle = &deployment.oracle();
+    let keeper = deployment.user_client(Deployment::DEFAULT_KEEPER)?;
+    let client = deployment.locked_user_client().await?;
+
+    let long_token_amount = 1_000_023;
+    let short_token_amount = 6_000_000_000_029;
+    let market_token = deployment
+        .prepare_market(
+            ["fBTC", "fBTC", "USDG"],
+            long_token_amount,
+            short_token_amount,
+            true,
+        )
+        .await?;
+
+    let collateral_amount = 100 * 100_000_000;
+    let size = 500 * MARKET_USD_UNIT;
+    deployment
+        .mint_or_transfer_to("USDG", &client.payer(), collateral_amount)
+        .await?;
+
+    let max_compute_units = DEFAULT_MAX_ORDER_CALLBACK_COMPUTE_UNITS;
+    let signature = keeper
+        .insert_global_amount_by_key(
+            store,
+            AmountKey::MaxOrderCallbackComputeUnits,
+            &max_compute_units,
+        )
+        .send_without_preflight()
+        .await?;
+    tracing::info!(%signature, %max_compute_units, "set max order callback compute units");
+
+    // Initialize the callback record.
+    let record =
+        Pubkey::find_program_address(&[RECORD_SEED, store.as_ref(), client.payer().as_ref()], &ID)
+            .0;
+    if client.account::<CallbackRecord>(&record).await?.is_none() {
+        let signature = client
+            .store_transaction()
+            .program(ID)
+            .anchor_accounts(accounts::InitializeRecord {
+                payer: client.payer(),
+                record,
+                system_program: system_program::ID,
+            })
+            .anchor_args(instruction::InitializeRecord { store: *store })
+            .send()
+            .await?;
+        tracing::info!(%signature, %record, "initialized callback record");
+    }
+    let invocations = client
+        .account::<CallbackRecord>(&record)
+        .await?
+        .ok_or(gmsol::Error::NotFound)?
+        .invocations;
+
+    // The store program itself cannot be used as the callback program.
+    let err = client
+        .market_increase(store, market_token, false, collateral_amount, true, size)
+        .callback(*client.store_program_id(), record, [1; 32])
+        .build_with_address()
+        .await?
+        .0
+        .send()
+        .await
+        .expect_err("should throw an error when the callback program is the store program");
+    tracing::info!(%err, "expected error");
+
+    // Create an order with callback.
+    let data = [42; 32];
+    let (rpc, order) = client
+        .market_increase(store, market_token, false, collateral_amount, true, size)
+        .callback(ID, record, data)
+        .build_with_address()
+        .await?;
+    let signature = rpc.send().await?;
+    tracing::info!(%order, %signature, %size, "created an order with callback");
+
+    let mut builder = keeper.execute_order(store, oracle, &order, false)?;
+    deployment
+        .execute_with_pyth(&mut builder, None, true, true)
+        .instrument(tracing::info_span!("execute", order=%order))
+        .await?;
+
+    // The callback is not invoked during the execution, but becomes pending.
+    let state = client
+        .account::<CallbackRecord>(&record)
+        .await?
+        .ok_or(gmsol::Error::NotFound)?;
+    assert_eq!(state.invocations, invocations);
+    assert!(keeper
+        .order(&order)
+        .await?
+        .callback()
+        .expect("must have callback")
+        .is_pending());
+
+    let pending = {
+        let events = keeper.historical_store_cpi_events(&order, None).await?;
+        futures_util::pin_mut!(events);
+        let mut pending = None;
+        while let Some(events) = events.try_next().await? {
+            pending = events
+                .into_value()
+                .into_iter()
+                .find_map(|event| match event {
+                    StoreCPIEvent::OrderCallbackPending(event) => Some(event),
+                    _ => None,
+                });
+            if pending.is_some() {
+                break;
+            }
+        }
+        pending.ok_or(gmsol::Error::NotFound)?
+    };
+    assert_eq!(pending.order, order);
+    assert_eq!(pending.program, ID);
+    assert_eq!(pending.account, record);
+
+    // The execution result must match the recorded one.
+    let mut mismatched = pending.clone();
+    mismatched.transfer_out.final_output_token += 1;
+    let err = keeper
+        .invoke_order_callback(&mismatched)
+        .await?
+        .send()
+        .await
+        .expect_err("should throw an error when the execution result is mismatched");
+    tracing::info!(%err, "expected error");
+
+    let signature = keeper.invoke_order_callback(&pending).await?.send().await?;
+    tracing::info!(%order, %signature, "invoked order callback");
+
+    let state = client
+        .account::<CallbackRecord>(&record)
+        .await?
+        .ok_or(gmsol::Error::NotFound)?;
+    assert_eq!(state.invocations, invocations + 1);
+    assert_eq!(state.last_order, order);
+    assert_eq!(state.last_kind, u8::from(OrderKind::MarketIncrease));
+    assert_eq!(state.last_data, data);
+    assert_eq!(
+        state.last_final_output_amount,
+        pending.transfer_out.final_output_token
+    );
+
+    // The callback can only be invoked once.
+    let err = keeper
+        .invoke_order_callback(&pending)
+        .await?
+        .send()
+        .await
+        .expect_err("should throw an error when the callback is not pending");
+    tracing::info!(%err, "expected error");
+
+    let signature = keeper.close_order(&order)?.build().await?.send().await?;
+    tracing::info!(%order, %signature, "closed order");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn position_info() -> eyre::Result<()> {
+    let deployment = current_deployment().await?;
+    let _guard = deployment.use_accounts().await?;
+    let span = tracing::info_span!("position_info");
+    let _enter = span.enter();
+
+    let store = &deployment.store;
+    let oracle = &deployment.oracle();
+    let keeper = deployment.user_client(Deployment::DEFAULT_KEEPER)?;
+    let client = deployment.locked_user_client().await?;
+
+    let long_token_amount = 1_000_011;
+    let short_token_amount = 6_000_000_000_013;
+    let market_token = deployment
+        .prepare_market(
+            ["fBTC", "fBTC", "USDG"],
+            long_token_amount,
+            short_token_amount,
+            true,
+        )
+        .await?;
+
+    let collateral_amount = 100 * 100_000_000;
+    let size = 500 * MARKET_USD_UNIT;
+    deployment
+        .mint_or_transfer_to("USDG", &client.payer(), collateral_amount)
+        .await?;
+
+    // Open a position.
+    let (rpc, order, position) = client
+        .market_increase(store, market_token, false, collateral_amount, true, size)
+        .build_with_addresses()
+        .await?;
+    let position = position.expect("must have position");
+    let signature = rpc.send().await?;
+    tracing::info!(%order, %signature, %size, "created an increase position order");
+
+    let mut builder = keeper.execute_order(store, oracle, &order, false)?;
+    deployment
+        .execute_with_pyth(&mut builder, None, true, true)
+        .instrument(tracing::info_span!("execute", order=%order))
+        .await?;
+
+    let account = client.position(&position).await?;
+    let entry_price = account.state.size_in_usd / account.state.size_in_tokens;
+    let prices = |index_price: u128| {
+        let price = |price: u128| Price {
+            min: price,
+            max: price,
+        };
+        Prices {
+            index_token_price: price(index_price),
+            long_token_price: price(index_price),
+            // USDG has 8 decimals.
+            short_token_price: price(MARKET_USD_UNIT / 100_000_000),
+        }
+    };
+
+    // The position should be healthy at the entry price.
+    let info = client.position_info(&position, prices(entry_price)).await?;
+    tracing::info!(?info, "position info at the entry price");
+    assert_eq!(info.size_in_usd, account.state.size_in_usd);
+    assert_eq!(info.size_in_tokens, account.state.size_in_tokens);
+    assert_eq!(info.collateral_amount, account.state.collateral_amount);
+    assert!(info.net_value.is_positive());
+    assert!(info.leverage.is_some());
+    assert!(!info.is_liquidatable);
+
+    // The position should be liquidatable after the price drops by 90%.
+    let info = client
+        .position_info(&position, prices(entry_price / 10))
+        .await?;
+    tracing::info!(?info, "position info after the price drops");
+    assert!(info.pnl.is_negative());
+    assert!(info.is_liquidatable);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn order_with_explicit_position() -> eyre::Result<()> {
+    let deployment = current_deployment().await?;
+    let _guard = deployment.use_accounts().await?;
+    let span = tracing::info_span!("order_with_explicit_position");
+    let _enter = span.enter();
+
+    let store = &deployment.store;
+    let oracle = &deployment.oracle();
+    let keeper = deployment.user_client(Deployment::DEFAULT_KEEPER)?;
+    let client = deployment.locked_user_client().await?;
+
+    let long_token_amount = 1_000_011;
+    let short_token_amount = 6_000_000_000_013;
+    let market_token = deployment
+        .prepare_market(
+            ["fBTC", "fBTC", "USDG"],
+            long_token_amount,
+            short_token_amount,
+            true,
+        )
+        .await?;
+
+    let collateral_amount = 100 * 100_000_000;
+    let size = 500 * MARKET_USD_UNIT;
+    deployment
+        .mint_or_transfer_to("USDG", &client.payer(), collateral_amount)
+        .await?;
+
+    // Open a position.
+    let (rpc, order, position) = client
+        .market_increase(store, market_token, false, collateral_amount, true, size)
+        .build_with_addresses()
+        .await?;
+    let position = position.expect("must have position");
+    let signature = rpc.send().await?;
+    tracing::info!(%order, %signature, %size, "created an increase position order");
+
+    let mut builder = keeper.execute_order(store, oracle, &order, false)?;
+    deployment
+        .execute_with_pyth(&mut builder, None, true, true)
+        .instrument(tracing::info_span!("execute", order=%order))
+        .await?;
+
+    // The position does not belong to another market.
+    let other_market_token = deployment
+        .market_token("SOL", "fBTC", "USDG")
+        .expect("must exist");
+    let err = client
+        .market_decrease(store, other_market_token, false, 0, true, size)
+        .position(&position)
+        .build_with_addresses()
+        .await
+        .expect_err("should fail with a position of another market");
+    tracing::info!(%err, "expected error");
+
+    // Close the position with the explicitly supplied position.
+    let (rpc, order, supplied) = client
+        .market_decrease(store, market_token, false, 0, true, size)
+        .position(&position)
+        .build_with_addresses()
+        .await?;
+    assert_eq!(supplied, Some(position));
+    let signature = rpc.send().await?;
+    tracing::info!(%order, %signature, %size, "created a decrease position order");
+
+    let mut builder = keeper.execute_order(store, oracle, &order, false)?;
+    deployment
+        .execute_with_pyth(&mut builder, None, true, true)
+        .instrument(tracing::info_span!("execute", order=%order))
+        .await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn batch_prepare_positions() -> eyre::Result<()> {
+    let deployment = current_deployment().await?;
+    let _guard = deployment.use_accounts().await?;
+    let span = tracing::info_span!("batch_prepare_positions");
+    let _enter = span.enter();
+
+    let store = &deployment.store;
+    let client = deployment.user_client(Deployment::DEFAULT_USER)?;
+
+    let usdg = deployment.token("USDG").expect("must exist").address;
+    let fbtc = deployment.token("fBTC").expect("must exist").address;
+    let wsol = deployment.token("WSOL").expect("must exist").address;
+    let btc_market = *deployment
+        .market_token("fBTC", "fBTC", "USDG")
+        .expect("must exist");
+    let sol_market = *deployment
+        .market_token("SOL", "WSOL", "USDG")
+        .expect("must exist");
+
+    let specs = vec![
+        PositionSpec {
+            market_token: btc_market,
+            is_long: true,
+            collateral_token: fbtc,
+        },
+        PositionSpec {
+            market_token: btc_market,
+            is_long: false,
+            collateral_token: usdg,
+        },
+        PositionSpec {
+            market_token: sol_market,
+            is_long: true,
+            collateral_token: wsol,
+        },
+        PositionSpec {
+            market_token: sol_market,
+            is_long: false,
+            collateral_token: usdg,
+        },
+    ];
+
+    let (rpc, positions) = client
+        .batch_prepare_positions(store, specs.clone())?
+        .swap_output(());
+    let signature = rpc.send().await?;
+    tracing::info!(%signature, ?positions, "prepared positions");
+
+    assert_eq!(positions.len(), specs.len());
+    for (spec, address) in specs.iter().zip(positions.iter()) {
+        let position = client
+            .account::<ZeroCopy<Position>>(address)
+            .await?
+            .expect("must exist")
+            .0;
+        assert_eq!(position.store, *store);
+        assert_eq!(position.owner, client.payer());
+        assert_eq!(position.market_token, spec.market_token);
+        assert_eq!(position.collateral_token, spec.collateral_token);
+        assert_eq!(position.kind()?, spec.kind());
+    }
+
+    // Preparing the same positions again is allowed.
+    let signature = client
+        .batch_prepare_positions(store, specs.clone())?
+        .send()
+        .await?;
+    tracing::info!(%signature, "prepared the same positions again");
+
+    // The whole batch is rejected if any of the specs is invalid.
+    let invalid = vec![
+        specs[0],
+        PositionSpec {
+            market_token: sol_market,
+            is_long: true,
+            collateral_token: fbtc,
+        },
+    ];
+    let err = client
+        .batch_prepare_positions(store, invalid)?
+        .send()
+        .await
+        .expect_err("should fail with an invalid collateral token");
+    tracing::info!(%err, "expected error");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn liquidation_impact() -> eyre::Result<()> {
+    let deployment = current_deployment().await?;
+    let _guard = deployment.use_accounts().await?;
+    let span = tracing::info_span!("liquidation_impact");
+    let _enter = span.enter();
+
+    let store = &deployment.store;
+    let oracle = &deployment.oracle();
+    let keeper = deployment.user_client(Deployment::DEFAULT_KEEPER)?;
+    let client = deployment.locked_user_client().await?;
+
+    let long_token_amount = 1_000_011;
+    let short_token_amount = 6_000_000_000_013;
+    let market_token = deployment
+        .prepare_market(
+            ["fBTC", "fBTC", "USDG"],
+            long_token_amount,
+            short_token_amount,
+            true,
+        )
+        .await?;
+
+    // Open a highly leveraged short position.
+    let collateral_amount = 10 * 100_000_000;
+    let size = 500 * MARKET_USD_UNIT;
+    deployment
+        .mint_or_transfer_to("USDG", &client.payer(), collateral_amount)
+        .await?;
+
+    let (rpc, order, position) = client
+        .market_increase(store, market_token, false, collateral_amount, false, size)
+        .build_with_addresses()
+        .await?;
+    let position = position.expect("must have position");
+    let signature = rpc.send().await?;
+    tracing::info!(%order, %signature, %size, "created an increase position order");
+
+    let mut builder = keeper.execute_order(store, oracle, &order, false)?;
+    deployment
+        .execute_with_pyth(&mut builder, None, true, true)
+        .instrument(tracing::info_span!("execute", order=%order))
+        .await?;
+
+    let account = client.position(&position).await?;
+    let entry_price = account.state.size_in_usd / account.state.size_in_tokens;
+    let prices = |index_price: u128| {
+        let price = |price: u128| Price {
+            min: price,
+            max: price,
+        };
+        Prices {
+            index_token_price: price(index_price),
+            long_token_price: price(index_price),
+            // USDG has 8 decimals.
+            short_token_price: price(MARKET_USD_UNIT / 100_000_000),
+        }
+    };
+
+    // The liquidation fee can be fully recovered at the entry price.
+    let impact = client
+        .liquidation_impact(&position, prices(entry_price))
+        .await?;
+    tracing::info!(?impact, "liquidation impact at the entry price");
+    assert_eq!(impact.size_to_close_usd, account.state.size_in_usd);
+    assert!(impact.remaining_collateral_usd > 0);
+    assert_eq!(impact.pool_impact, impact.keeper_reward_usd as i128);
+    assert!(impact.is_adverse_impact_acceptable);
+
+    // The loss exceeds the collateral after the price doubles,
+    // so the liquidation would make the pool worse-off.
+    let impact = client
+        .liquidation_impact(&position, prices(entry_price * 2))
+        .await?;
+    tracing::info!(?impact, "liquidation impact after the price rises");
+    assert_eq!(impact.remaining_collateral_usd, 0);
+    assert!(impact.pool_impact.is_negative());
+    assert!(!impact.is_adverse_impact_acceptable);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn price_impact_sensitivity() -> eyre::Result<()> {
+    let deployment = current_deployment().await?;
+    let _guard = deployment.use_accounts().await?;
+    let span = tracing::info_span!("price_impact_sensitivity");
+    let _enter = span.enter();
+
+    let store = &deployment.store;
+    let oracle = &deployment.oracle();
+    let keeper = deployment.user_client(Deployment::DEFAULT_KEEPER)?;
+    let client = deployment.locked_user_client().await?;
+
+    let long_token_amount = 1_000_011;
+    let short_token_amount = 6_000_000_000_013;
+    let market_token = deployment
+        .prepare_market(
+            ["fBTC", "fBTC", "USDG"],
+            long_token_amount,
+            short_token_amount,
+            true,
+        )
+        .await?;
+
+    // Open a short position to skew the open interest.
+    let collateral_amount = 10 * 100_000_000;
+    let size = 500 * MARKET_USD_UNIT;
+    deployment
+        .mint_or_transfer_to("USDG", &client.payer(), collateral_amount)
+        .await?;
+
+    let (rpc, order, position) = client
+        .market_increase(store, market_token, false, collateral_amount, false, size)
+        .build_with_addresses()
+        .await?;
+    let position = position.expect("must have position");
+    let signature = rpc.send().await?;
+    tracing::info!(%order, %signature, %size, "created an increase position order");
+
+    let mut builder = keeper.execute_order(store, oracle, &order, false)?;
+    deployment
+        .execute_with_pyth(&mut builder, None, true, true)
+        .instrument(tracing::info_span!("execute", order=%order))
+        .await?;
+
+    let account = client.position(&position).await?;
+    let index_price = account.state.size_in_usd / account.state.size_in_tokens;
+    let price = |price: u128| Price {
+        min: price,
+        max: price,
+    };
+    let prices = Prices {
+        index_token_price: price(index_price),
+        long_token_price: price(index_price),
+        // USDG has 8 decimals.
+        short_token_price: price(MARKET_USD_UNIT / 100_000_000),
+    };
+
+    let market = client
+        .market(&client.find_market_address(store, market_token))
+        .await?;
+    let open_interest = market.open_interest()?;
+    let (long_open_interest, short_open_interest) =
+        (open_interest.long_amount()?, open_interest.short_amount()?);
+
+    let (long, short) = client
+        .get_price_impact_curve(store, oracle, market_token, prices)
+        .await?;
+    tracing::info!(?long, ?short, "price impact sensitivity");
+
+    let (increasing, reducing) = if long_open_interest > short_open_interest {
+        (long, short)
+    } else {
+        (short, long)
+    };
+
+    // Increasing the skew is unfavourable, and the impact grows with the size.
+    assert!(increasing.impact_at_1m_usd > 0);
+    assert!(increasing.impact_at_5m_usd > increasing.impact_at_1m_usd);
+    assert!(increasing.impact_at_10m_usd > increasing.impact_at_5m_usd);
+    assert!(increasing.impact_at_50m_usd > increasing.impact_at_10m_usd);
+    assert!(increasing.impact_factor_per_million > 0);
+    assert_eq!(increasing.equilibrium_size_usd, u128::MAX);
+
+    // Reducing the skew is more favourable than increasing it.
+    assert!(reducing.impact_at_1m_usd < increasing.impact_at_1m_usd);
+    assert_eq!(
+        reducing.equilibrium_size_usd,
+        long_open_interest.abs_diff(short_open_interest)
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn collateral_coverage() -> eyre::Result<()> {
+    let deployment = current_deployment().await?;
+    let _guard = deployment.use_accounts().await?;
+    let span = tracing::info_span!("collateral_coverage");
+    let _enter = span.enter();
+
+    let store = &deployment.store;
+    let oracle = &deployment.oracle();
+    let keeper = deployment.user_client(Deployment::DEFAULT_KEEPER)?;
+    let client = deployment.locked_user_client().await?;
+
+    let long_token_amount = 1_000_011;
+    let short_token_amount = 6_000_000_000_013;
+    let market_token = deployment
+        .prepare_market(
+            ["fBTC", "fBTC", "USDG"],
+            long_token_amount,
+            short_token_amount,
+            true,
+        )
+        .await?;
+
+    // Open a 10x leveraged short position.
+    let collateral_amount = 50 * 100_000_000;
+    let size = 500 * MARKET_USD_UNIT;
+    deployment
+        .mint_or_transfer_to("USDG", &client.payer(), collateral_amount)
+        .await?;
+
+    let (rpc, order, position) = client
+        .market_increase(store, market_token, false, collateral_amount, false, size)
+        .build_with_addresses()
+        .await?;
+    let position = position.expect("must have position");
+    let signature = rpc.send().await?;
+    tracing::info!(%order, %signature, %size, "created an increase position order");
+
+    let mut builder = keeper.execute_order(store, oracle, &order, false)?;
+    deployment
+        .execute_with_pyth(&mut builder, None, true, true)
+        .instrument(tracing::info_span!("execute", order=%order))
+        .await?;
+
+    let account = client.position(&position).await?;
+    let entry_price = account.state.size_in_usd / account.state.size_in_tokens;
+    let prices = |index_price: u128| {
+        let price = |price: u128| Price {
+            min: price,
+            max: price,
+        };
+        Prices {
+            index_token_price: price(index_price),
+            long_token_price: price(index_price),
+            // USDG has 8 decimals.
+            short_token_price: price(MARKET_USD_UNIT / 100_000_000),
+        }
+    };
+
+    let coverage = client
+        .collateral_coverage(&position, prices(entry_price))
+        .await?;
+    tracing::info!(?coverage, "collateral coverage at the entry price");
+    assert!(coverage.collateral_usd > 0);
+    assert!(coverage.initial_margin_usd >= coverage.maintenance_margin_usd);
+    assert_eq!(
+        Some(coverage.coverage_ratio),
+        coverage
+            .collateral_usd
+            .checked_mul_div(&MARKET_USD_UNIT, &coverage.maintenance_margin_usd)
+    );
+    assert!(coverage.coverage_ratio > MARKET_USD_UNIT);
+    assert!(!coverage.is_margin_called);
+    assert!(coverage.margin_call_price > entry_price);
+
+    // The position is margin called exactly when the price reaches the margin call price.
+    let margin_call_price = coverage.margin_call_price;
+    let coverage = client
+        .collateral_coverage(&position, prices(margin_call_price - 1))
+        .await?;
+    assert!(!coverage.is_margin_called);
+    let coverage = client
+        .collateral_coverage(&position, prices(margin_call_price))
+        .await?;
+    tracing::info!(?coverage, "collateral coverage at the margin call price");
+    assert!(coverage.is_margin_called);
+    assert!(coverage.coverage_ratio <= MARKET_USD_UNIT);
+    assert_eq!(coverage.margin_call_price, margin_call_price);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn position_break_even_price() -> eyre::Result<()> {
+    let deployment = current_deployment().await?;
+    let _guard = deployment.use_accounts().await?;
+    let span = tracing::info_span!("position_break_even_price");
+    let _enter = span.enter();
+
+    let store = &deployment.store;
+    let oracle = &deployment.oracle();
+    let keeper = deployment.user_client(Deployment::DEFAULT_KEEPER)?;
+    let client = deployment.locked_user_client().await?;
+
+    let long_token_amount = 1_000_011;
+    let short_token_amount = 6_000_000_000_013;
+    let market_token = deployment
+        .prepare_market(
+            ["fBTC", "fBTC", "USDG"],
+            long_token_amount,
+            short_token_amount,
+            true,
+        )
+        .await?;
+
+    // Open a 10x leveraged long position.
+    let collateral_amount = 50 * 100_000_000;
+    let size = 500 * MARKET_USD_UNIT;
+    deployment
+        .mint_or_transfer_to("USDG", &client.payer(), collateral_amount)
+        .await?;
+
+    let (rpc, order, position) = client
+        .market_increase(store, market_token, false, collateral_amount, true, size)
+        .build_with_addresses()
+        .await?;
+    let position = position.expect("must have position");
+    let signature = rpc.send().await?;
+    tracing::info!(%order, %signature, %size, "created an increase position order");
+
+    let mut builder = keeper.execute_order(store, oracle, &order, false)?;
+    deployment
+        .execute_with_pyth(&mut builder, None, true, true)
+        .instrument(tracing::info_span!("execute", order=%order))
+        .await?;
+
+    let account = client.position(&position).await?;
+    let entry_price = account.state.size_in_usd / account.state.size_in_tokens;
+    let price = |price: u128| Price {
+        min: price,
+        max: price,
+    };
+    let prices = Prices {
+        index_token_price: price(entry_price),
+        long_token_price: price(entry_price),
+        // USDG has 8 decimals.
+        short_token_price: price(MARKET_USD_UNIT / 100_000_000),
+    };
+
+    let opened = client.position_break_even_price(&position, prices).await?;
+    tracing::info!(?opened, "break-even price after opening");
+    assert_eq!(opened.entry_price, entry_price);
+    assert!(opened.accumulated_fees_usd > 0);
+    // The fees eat into the profits of a long position.
+    assert!(opened.fee_adjusted_entry_price > opened.entry_price);
+    assert!(opened.break_even_price > opened.fee_adjusted_entry_price);
+    assert!(opened.break_even_moved_by_pct > 0);
+
+    // Pending fees never decrease over time.
+    tokio::time::sleep(Duration::from_secs(5)).await;
+    let later = client.position_break_even_price(&position, prices).await?;
+    tracing::info!(?later, "break-even price after a while");
+    assert_eq!(later.entry_price, entry_price);
+    assert!(later.accumulated_fees_usd >= opened.accumulated_fees_usd);
+    assert!(later.break_even_price >= opened.break_even_price);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn estimate_adl_size_delta() -> eyre::Result<()> {
+    let deployment = current_deployment().await?;
+    let _guard = deployment.use_accounts().await?;
+    let span = tracing::info_span!("estimate_adl_size_delta");
+    let _enter = span.enter();
+
+    let store = &deployment.store;
+    let oracle = &deployment.oracle();
+    let keeper = deployment.user_client(Deployment::DEFAULT_KEEPER)?;
+    let client = deployment.locked_user_client().await?;
+
+    // The long token of the ADL market is USDG, so the long pool is worth about $1,000.
+    let long_token_amount = 1_000 * 100_000_000;
+    let short_token_amount = 1_000_011;
+    let market_token = deployment
+        .prepare_market(
+            Deployment::SELECT_ADL_MARKET,
+            long_token_amount,
+            short_token_amount,
+            true,
+        )
+        .await?;
+
+    let collateral_amount = 100 * 100_000_000;
+    let size = 400 * MARKET_USD_UNIT;
+    deployment
+        .mint_or_transfer_to("USDG", &client.payer(), collateral_amount)
+        .await?;
+
+    // Open a long position.
+    let (rpc, order, position) = client
+        .market_increase(store, market_token, true, collateral_amount, true, size)
+        .build_with_addresses()
+        .await?;
+    let position = position.expect("must have position");
+    let signature = rpc.send().await?;
+    tracing::info!(%order, %signature, %size, "created an increase position order");
+
+    let mut builder = keeper.execute_order(store, oracle, &order, false)?;
+    deployment
+        .execute_with_pyth(&mut builder, None, true, true)
+        .instrument(tracing::info_span!("execute", order=%order))
+        .await?;
+
+    let account = client.position(&position).await?;
+    let size_in_usd = account.state.size_in_usd;
+    let size_in_tokens = account.state.size_in_tokens;
+    let entry_price = size_in_usd / size_in_tokens;
+
+    // Triple the index price to push the pnl factor of the long side above the ADL threshold.
+    let index_price = entry_price * 3;
+    // USDG has 8 decimals.
+    let long_token_price = MARKET_USD_UNIT / 100_000_000;
+    let price = |price: u128| Price {
+        min: price,
+        max: price,
+    };
+    let prices = Prices {
+        index_token_price: price(index_price),
+        long_token_price: price(long_token_price),
+        // fBTC has 6 decimals.
+        short_token_price: price(100_000 * MARKET_USD_UNIT / 1_000_000),
+    };
+
+    let estimate = client.estimate_adl_amount(&position, prices).await?;
+    tracing::info!(?estimate, "estimated ADL size delta");
+
+    // The position is the only one in the market, so the pnl of the long side is its pnl.
+    let market = client
+        .market(&client.find_market_address(store, market_token))
+        .await?;
+    let pool_value = market
+        .pool(PoolKind::Primary)
+        .expect("must exist")
+        .long_amount()?
+        * long_token_price;
+    let pnl = (size_in_tokens * index_price - size_in_usd) as f64;
+    let unit = MARKET_USD_UNIT as f64;
+    let target = estimate.target_pnl_factor as f64 / unit;
+    let pnl_per_unit = pnl / size_in_usd as f64;
+    let expected = (pnl - pool_value as f64 * target) / (pnl_per_unit * (1.0 - target));
+    tracing::info!(%expected, "manually computed ADL size delta");
+
+    assert!(estimate.current_pnl_factor as f64 / unit > 0.55);
+    assert!(expected > 0.0 && expected < size_in_usd as f64);
+    let recommended = estimate.recommended_size_delta_usd as f64;
+    assert!((recommended - expected).abs() / expected < 0.01);
+
+    // No ADL is needed at the entry price.
+    let prices = Prices {
+        index_token_price: price(entry_price),
+        ..prices
+    };
+    let estimate = client.estimate_adl_amount(&position, prices).await?;
+    assert_eq!(estimate.recommended_size_delta_usd, 0);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn position_size_increment() -> eyre::Result<()> {
+    let deployment = current_deployment().await?;
+    let _guard = deployment.use_accounts().await?;
+    let span = tracing::info_span!("position_size_increment");
+    let _enter = span.enter();
+
+    let store = &deployment.store;
+    let oracle = &deployment.oracle();
+    let keeper = deployment.user_client(Deployment::DEFAULT_KEEPER)?;
+    let client = deployment.locked_user_client().await?;
+
+    let long_token_amount = 6_000_000_000_011;
+    let short_token_amount = 6_000_000_000_013;
+    let market_token = deployment
+        .prepare_market(
+            Deployment::SELECT_POSITION_SIZE_INCREMENT_MARKET,
+            long_token_amount,
+            short_token_amount,
+            true,
+        )
+        .await?;
+
+    let increment = 100 * MARKET_USD_UNIT;
+    let signature = keeper
+        .set_position_size_increment(store, market_token, &increment)?
+        .send_without_preflight()
+        .await?;
+    tracing::info!(%signature, %increment, "set position size increment");
+
+    let collateral_amount = 100 * 100_000_000;
+    deployment
+        .mint_or_transfer_to("USDG", &client.payer(), 2 * collateral_amount)
+        .await?;
+
+    // A size that is not a multiple of the increment is rejected.
+    let size = 150 * MARKET_USD_UNIT;
+    let (rpc, order) = client
+        .market_increase(store, market_token, false, collateral_amount, true, size)
+        .build_with_address()
+        .await?;
+    let signature = rpc.send().await?;
+    tracing::info!(%order, %signature, %size, "created an increase position order");
+
+    let mut builder = keeper.execute_order(store, oracle, &order, false)?;
+    let err = deployment
+        .execute_with_pyth(&mut builder, None, false, false)
+        .await
+        .expect_err("should throw an error when the size is not a multiple of the increment");
+    assert_eq!(
+        err.anchor_error_code(),
+        Some(CoreError::PositionSizeBelowIncrement.into())
+    );
+
+    let signature = client.close_order(&order)?.build().await?.send().await?;
+    tracing::info!(%order, %signature, "closed the rejected order");
+
+    // A multiple of the increment is accepted.
+    let size = 200 * MARKET_USD_UNIT;
+    let (rpc, order) = client
+        .market_increase(store, market_token, false, collateral_amount, true, size)
+        .build_with_address()
+        .await?;
+    let signature = rpc.send().await?;
+    tracing::info!(%order, %signature, %size, "created an increase position order");
+
+    let mut builder = keeper.execute_order(store, oracle, &order, false)?;
+    deployment
+        .execute_with_pyth(&mut builder, None, true, true)
+        .instrument(tracing::info_span!("execute", order=%order))
+        .await?;
+
+    // Fully closing the position is always allowed.
+    let (rpc, order) = client
+        .market_decrease(store, market_token, false, 0, true, size)
+        .build_with_address()
+        .await?;
+    let signature = rpc.send().await?;
+    tracing::info!(%order, %signature, %size, "created a decrease position order");
+
+    let mut builder = keeper.execute_order(store, oracle, &order, false)?;
+    deployment
+        .execute_with_pyth(&mut builder, None, true, true)
+        .instrument(tracing::info_span!("execute", order=%order))
+        .await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn max_trade_size() -> eyre::Result<()> {
+    let deployment = current_deployment().await?;
+    let _guard = deployment.use_accounts().await?;
+    let span = tracing::info_span!("max_trade_size");
+    let _enter = span.enter();
+
+    let store = &deployment.store;
+    let oracle = &deployment.oracle();
+    let keeper = deployment.user_client(Deployment::DEFAULT_KEEPER)?;
+    let client = deployment.locked_user_client().await?;
+
+    let long_token_amount = 60_000_000_000_011;
+    let short_token_amount = 60_000_000_000_013;
+    let market_token = deployment
+        .prepare_market(
+            Deployment::SELECT_MAX_TRADE_SIZE_MARKET,
+            long_token_amount,
+            short_token_amount,
+            true,
+        )
+        .await?;
+
+    let max_trade_size = 10_000 * MARKET_USD_UNIT;
+    let signature = keeper
+        .set_max_trade_size(store, market_token, &max_trade_size)?
+        .send_without_preflight()
+        .await?;
+    tracing::info!(%signature, %max_trade_size, "set max trade size");
+
+    let collateral_amount = 1_000 * 100_000_000;
+    deployment
+        .mint_or_transfer_to("USDG", &client.payer(), 3 * collateral_amount)
+        .await?;
+
+    // A trade larger than the max trade size is rejected.
+    let size = 15_000 * MARKET_USD_UNIT;
+    let (rpc, order) = client
+        .market_increase(store, market_token, false, collateral_amount, true, size)
+        .build_with_address()
+        .await?;
+    let signature = rpc.send().await?;
+    tracing::info!(%order, %signature, %size, "created an increase position order");
+
+    let mut builder = keeper.execute_order(store, oracle, &order, false)?;
+    let err = deployment
+        .execute_with_pyth(&mut builder, None, false, false)
+        .await
+        .expect_err("should throw an error when the size exceeds the max trade size");
+    assert_eq!(
+        err.anchor_error_code(),
+        Some(CoreError::TradeSizeExceedsLimit.into())
+    );
+
+    let signature = client.close_order(&order)?.build().await?.send().await?;
+    tracing::info!(%order, %signature, "closed the rejected order");
+
+    // Two trades within the max trade size are accepted.
+    let size = 7_500 * MARKET_USD_UNIT;
+    let mut last_state = None;
+    for _ in 0..2 {
+        let (rpc, order, address) = client
+            .market_increase(store, market_token, false, collateral_amount, true, size)
+            .build_with_addresses()
+            .await?;
+        let address = address.expect("must have position");
+        let signature = rpc.send().await?;
+        tracing::info!(%order, %signature, %size, "created an increase position order");
+
+        let mut builder = keeper.execute_order(store, oracle, &order, false)?;
+        deployment
+            .execute_with_pyth(&mut builder, None, true, true)
+            .instrument(tracing::info_span!("execute", order=%order))
+            .await?;
+
+        let account = client.position(&address).await?;
+        if let Some((size_in_usd, size_in_tokens)) = last_state {
+            // The second increase builds on the first one.
+            assert_eq!(account.state.size_in_usd, size_in_usd + size);
+            assert!(account.state.size_in_tokens > size_in_tokens);
+        } else {
+            assert_eq!(account.state.size_in_usd, size);
+        }
+        last_state = Some((account.state.size_in_usd, account.state.size_in_tokens));
+    }
+
+    // Decreases are limited too, so the position must be closed in two orders.
+    let (rpc, order) = client
+        .market_decrease(store, market_token, false, 0, true, 2 * size)
+        .build_with_address()
+        .await?;
+    let signature = rpc.send().await?;
+    tracing::info!(%order, %signature, "created a decrease position order");
+
+    let mut builder = keeper.execute_order(store, oracle, &order, false)?;
+    let err = deployment
+        .execute_with_pyth(&mut builder, None, false, false)
+        .await
+        .expect_err("should throw an error when the size exceeds the max trade size");
+    assert_eq!(
+        err.anchor_error_code(),
+        Some(CoreError::TradeSizeExceedsLimit.into())
+    );
+
+    let signature = client.close_order(&order)?.build().await?.send().await?;
+    tracing::info!(%order, %signature, "closed the rejected order");
+
+    for _ in 0..2 {
+        let (rpc, order) = client
+            .market_decrease(store, market_token, false, 0, true, size)
+            .build_with_address()
+            .await?;
+        let signature = rpc.send().await?;
+        tracing::info!(%order, %signature, %size, "created a decrease position order");
+
+        let mut builder = keeper.execute_order(store, oracle, &order, false)?;
+        deployment
+            .execute_with_pyth(&mut builder, None, true, true)
+            .instrument(tracing::info_span!("execute", order=%order))
+            .await?;
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn market_analytics() -> eyre::Result<()> {
+    use anchor_client::solana_sdk::{
+        account::from_account,
+        sysvar::{self, clock::Clock},
+    };
+    use gmsol_store::states::MarketAnalytics;
+
+    let deployment = current_deployment().await?;
+    let _guard = deployment.use_accounts().await?;
+    let span = tracing::info_span!("market_analytics");
+    let _enter = span.enter();
+
+    let store = &deployment.store;
+    let oracle = &deployment.oracle();
+    let keeper = deployment.user_client(Deployment::DEFAULT_KEEPER)?;
+    let client = deployment.locked_user_client().await?;
+
+    let long_token_amount = 1_000_031;
+    let short_token_amount = 6_000_000_000_037;
+    let market_token = deployment
+        .prepare_market(
+            ["fBTC", "fBTC", "USDG"],
+            long_token_amount,
+            short_token_amount,
+            true,
+        )
+        .await?;
+
+    let collateral_amount = 100 * 100_000_000;
+    deployment
+        .mint_or_transfer_to("USDG", &client.payer(), 3 * collateral_amount)
+        .await?;
+
+    let chain_time = || async {
+        let account = client
+            .store_program()
+            .rpc()
+            .get_account(&sysvar::clock::ID)
+            .await?;
+        let clock = from_account::<Clock, _>(&account).ok_or(gmsol::Error::NotFound)?;
+        eyre::Ok(clock.unix_timestamp)
+    };
+
+    // Initialize the analytics account of the current period.
+    let period_secs = 30 * 24 * 3600;
+    let period_key = MarketAnalytics::period_key(chain_time().await?, period_secs)?;
+    let period_start = period_key * period_secs as i64;
+    let analytics =
+        client.find_market_analytics_address(store, market_token, period_secs, period_key);
+    if client
+        .account::<MarketAnalytics>(&analytics)
+        .await?
+        .is_none()
+    {
+        let (rpc, address) = client
+            .initialize_market_analytics(store, market_token, period_secs, period_key)
+            .swap_output(());
+        let signature = rpc.send().await?;
+        assert_eq!(address, analytics);
+        tracing::info!(%signature, %analytics, "initialized market analytics");
+    }
+    let mut expected = client.ohlcv_period(store, &analytics, period_start).await?;
+    assert_eq!(expected.market_token, *market_token);
+    assert_eq!(expected.period_secs, period_secs);
+
+    // Execute 5 trades in the period.
+    let trades = [
+        (true, 500),
+        (true, 300),
+        (false, 200),
+        (true, 100),
+        (false, 700),
+    ];
+    for (is_increase, size) in trades {
+        let size = size * MARKET_USD_UNIT;
+        let (rpc, order) = if is_increase {
+            client
+                .market_increase(store, market_token, false, collateral_amount, true, size)
+                .build_with_address()
+                .await?
+        } else {
+            client
+                .market_decrease(store, market_token, false, 0, true, size)
+                .build_with_address()
+                .await?
+        };
+        let signature = rpc.send().await?;
+        tracing::info!(%order, %signature, %size, %is_increase, "created an order");
+
+        let mut builder = keeper.execute_order(store, oracle, &order, false)?;
+        builder.add_market_analytics(analytics);
+        deployment
+            .execute_with_pyth(&mut builder, None, true, true)
+            .instrument(tracing::info_span!("execute", order=%order))
+            .await?;
+
+        let data = client.ohlcv_period(store, &analytics, period_start).await?;
+        if expected.trade_count == 0 {
+            expected.open = data.close;
+            expected.high = data.close;
+            expected.low = data.close;
+        }
+        expected.high = expected.high.max(data.close);
+        expected.low = expected.low.min(data.close);
+        expected.close = data.close;
+        expected.volume_usd += size;
+        expected.trade_count += 1;
+        assert_ne!(data.close, 0);
+        assert_eq!(data, expected);
+    }
+
+    // A new period creates a fresh account.
+    let period_secs = 10;
+    let mut now = chain_time().await?;
+    while now.rem_euclid(period_secs as i64) >= 3 {
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        now = chain_time().await?;
+    }
+    let period_key = MarketAnalytics::period_key(now, period_secs)?;
+    let (rpc, previous) = client
+        .initialize_market_analytics(store, market_token, period_secs, period_key)
+        .swap_output(());
+    let signature = rpc.send().await?;
+    tracing::info!(%signature, %previous, "initialized market analytics");
+
+    while MarketAnalytics::period_key(chain_time().await?, period_secs)? == period_key {
+        tokio::time::sleep(Duration::from_secs(1)).await;
+    }
+    let (rpc, current) = client
+        .initialize_market_analytics(store, market_token, period_secs, period_key + 1)
+        .swap_output(());
+    let signature = rpc.send().await?;
+    tracing::info!(%signature, %current, "initialized market analytics of the next period");
+    assert_ne!(current, previous);
+
+    let current_start = (period_key + 1) * period_secs as i64;
+    let data = client.ohlcv_period(store, &current, current_start).await?;
+    assert_eq!(data.trade_count, 0);
+    assert_eq!(data.volume_usd, 0);
+
+    // Trades are only recorded in the account of the current period.
+    let size = 100 * MARKET_USD_UNIT;
+    let (rpc, order) = client
+        .market_increase(store, market_token, false, collateral_amount, true, size)
+        .build_with_address()
+        .await?;
+    let signature = rpc.send().await?;
+    tracing::info!(%order, %signature, %size, "created an order");
+    let mut builder = keeper.execute_order(store, oracle, &order, false)?;
+    builder
+        .add_market_analytics(previous)
+        .add_market_analytics(current);
+    deployment
+        .execute_with_pyth(&mut builder, None, true, true)
+        .instrument(tracing::info_span!("execute", order=%order))
+        .await?;
+
+    let data = client.ohlcv_period(store, &current, current_start).await?;
+    assert_eq!(data.trade_count, 1);
+    assert_eq!(data.volume_usd, size);
+    assert_eq!(data.open, data.close);
+    let data = client
+        .ohlcv_period(store, &previous, current_start - period_secs as i64)
+        .await?;
+    assert_eq!(data.trade_count, 0);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn decrease_order_at_market_depth_limit() -> eyre::Result<()> {
+    let deployment = current_deployment().await?;
+    let _guard = deployment.use_accounts().await?;
+    let span = tracing::info_span!("decrease_order_at_market_depth_limit");
+    let _enter = span.enter();
+
+    let store = &deployment.store;
+    let oracle = &deployment.oracle();
+    let keeper = deployment.user_client(Deployment::DEFAULT_KEEPER)?;
+    let client = deployment.locked_user_client().await?;
+
+    let long_token_amount = 1_000_041;
+    let short_token_amount = 6_000_000_000_041;
+    let market_token = deployment
+        .prepare_market(
+            ["fBTC", "fBTC", "USDG"],
+            long_token_amount,
+            short_token_amount,
+            true,
+        )
+        .await?;
+
+    let collateral_amount = 100 * 100_000_000;
+    let size = 500 * MARKET_USD_UNIT;
+    deployment
+        .mint_or_transfer_to("USDG", &client.payer(), collateral_amount * 3)
+        .await?;
+
+    // Open a position.
+    let (rpc, order) = client
+        .market_increase(store, market_token, false, collateral_amount, true, size)
+        .build_with_address()
+        .await?;
+    let signature = rpc.send().await?;
+    tracing::info!(%order, %signature, %size, "created an increase position order");
+
+    let mut builder = keeper.execute_order(store, oracle, &order, false)?;
+    deployment
+        .execute_with_pyth(&mut builder, None, true, true)
+        .instrument(tracing::info_span!("execute", order=%order))
+        .await?;
+
+    // Allow only 1 more pending order in the market.
+    let pending = client
+        .market_pending_counts(store, market_token)
+        .await?
+        .orders;
+    let limit = u64::from(pending) + 1;
+    let signature = keeper
+        .insert_global_amount_by_key(store, AmountKey::MaxPendingOrders, &limit)
+        .send_without_preflight()
+        .await?;
+    tracing::info!(%signature, %limit, "set max pending orders");
+
+    // Fill the market with a limit order that will never be triggered.
+    let (rpc, limit_order) = client
+        .limit_increase(store, market_token, true, size, 1, false, collateral_amount)
+        .build_with_address()
+        .await?;
+    let signature = rpc.send().await?;
+    tracing::info!(order=%limit_order, %signature, "created a limit increase order");
+
+    let (rpc, _order) = client
+        .limit_increase(store, market_token, true, size, 1, false, collateral_amount)
+        .build_with_address()
+        .await?;
+    let err = rpc
+        .send()
+        .await
+        .expect_err("should exceed the market depth limit");
+    assert_eq!(
+        gmsol::Error::from(err).anchor_error_code(),
+        Some(CoreError::MarketDepthLimitExceeded.into())
+    );
+
+    // The position can still be closed.
+    let (rpc, order) = client
+        .market_decrease(store, market_token, false, 0, true, size)
+        .build_with_address()
+        .await?;
+    let signature = rpc.send().await?;
+    tracing::info!(%order, %signature, "created a decrease position order at the limit");
+    assert_eq!(
+        client
+            .market_pending_counts(store, market_token)
+            .await?
+            .orders,
+        pending + 1
+    );
+
+    let mut builder = keeper.execute_order(store, oracle, &order, false)?;
+    deployment
+        .execute_with_pyth(&mut builder, None, true, true)
+        .instrument(tracing::info_span!("execute", order=%order))
+        .await?;
+
+    // Cleanup.
+    let limit = 0;
+    let signature = keeper
+        .insert_global_amount_by_key(store, AmountKey::MaxPendingOrders, &limit)
+        .send_without_preflight()
+        .await?;
+    tracing::info!(%signature, "removed the limit of pending orders");
+
+    let signature = client
+        .close_order(&limit_order)?
+        .build()
+        .await?
+        .send()
+        .await?;
+    tracing::info!(order=%limit_order, %signature, "cancelled the limit order");
+
+    Ok(())
+}
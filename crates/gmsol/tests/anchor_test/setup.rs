@@ -1,5 +1,5 @@
 use std::{
-    collections::{hash_map::Entry, HashMap},
+    collections::{hash_map::Entry, HashMap, HashSet},
     fmt,
     future::Future,
     sync::{
@@ -124,6 +124,9 @@ impl Deployment {
     /// User 1.
     pub const USER_1: &'static str = "user_1";
 
+    /// User 2, only used for onboarding a referred user.
+    pub const USER_2: &'static str = "user_2";
+
     /// Default keeper.
     pub const DEFAULT_KEEPER: &'static str = "keeper";
 
@@ -136,6 +139,43 @@ impl Deployment {
     /// Market selector for first deposit test.
     pub const SELECT_FIRST_DEPOSIT_MARKET: [&'static str; 3] = ["fBTC", "USDG", "WSOL"];
 
+    /// Market selector for market token map test.
+    pub const SELECT_TOKEN_MAP_MARKET: [&'static str; 3] = ["fBTC", "WSOL", "fBTC"];
+
+    /// Market selector for close market test.
+    pub const SELECT_CLOSE_MARKET: [&'static str; 3] = ["SOL", "USDG", "WSOL"];
+
+    /// Market selector for close market with residual balance test.
+    pub const SELECT_CLOSE_MARKET_WITH_RESIDUAL: [&'static str; 3] = ["fBTC", "WSOL", "WSOL"];
+
+    /// Market selector for GLV batch shift test.
+    pub const SELECT_GLV_BATCH_SHIFT_MARKET: [&'static str; 3] = ["WSOL", "fBTC", "USDG"];
+
+    /// Market selector for position size increment test.
+    pub const SELECT_POSITION_SIZE_INCREMENT_MARKET: [&'static str; 3] = ["fBTC", "USDG", "USDG"];
+
+    /// Market selector for max trade size test.
+    pub const SELECT_MAX_TRADE_SIZE_MARKET: [&'static str; 3] = ["SOL", "USDG", "USDG"];
+
+    /// Market selector for market OI caps test.
+    pub const SELECT_OI_CAPS_MARKET: [&'static str; 3] = ["fBTC", "fBTC", "WSOL"];
+
+    /// Market selector for market token supply verification test.
+    pub const SELECT_SUPPLY_VERIFICATION_MARKET: [&'static str; 3] = ["WSOL", "fBTC", "fBTC"];
+
+    /// Market selector for the tests running on an isolated store.
+    pub const SELECT_ISOLATED_STORE_MARKET: [&'static str; 3] = ["fBTC", "fBTC", "USDG"];
+
+    /// Market selectors for batch market OI caps update test.
+    pub const SELECT_BATCH_OI_CAPS_MARKETS: [[&'static str; 3]; 3] = [
+        ["SOL", "fBTC", "WSOL"],
+        ["WSOL", "WSOL", "USDG"],
+        ["WSOL", "USDG", "USDG"],
+    ];
+
+    /// The index of the trusted Chainlink verifier entry for the deployment.
+    pub const TRUSTED_CHAINLINK_VERIFIER_INDEX: u8 = 0;
+
     const SOL_PYTH_FEED_ID: [u8; 32] = [
         0xef, 0x0d, 0x8b, 0x6f, 0xda, 0x2c, 0xeb, 0xa4, 0x1d, 0xa1, 0x5d, 0x40, 0x95, 0xd1, 0xda,
         0x39, 0x2a, 0x0d, 0x2f, 0x8e, 0xd0, 0xc6, 0xc7, 0xbc, 0x0f, 0x4c, 0xfa, 0xc8, 0xc2, 0x80,
@@ -293,6 +333,25 @@ impl Deployment {
             Self::SELECT_ADL_MARKET,
             // For first deposit test only
             Self::SELECT_FIRST_DEPOSIT_MARKET,
+            // For market token map test only
+            Self::SELECT_TOKEN_MAP_MARKET,
+            // For close market tests only
+            Self::SELECT_CLOSE_MARKET,
+            Self::SELECT_CLOSE_MARKET_WITH_RESIDUAL,
+            // For GLV batch shift test only
+            Self::SELECT_GLV_BATCH_SHIFT_MARKET,
+            // For position size increment test only
+            Self::SELECT_POSITION_SIZE_INCREMENT_MARKET,
+            // For max trade size test only
+            Self::SELECT_MAX_TRADE_SIZE_MARKET,
+            // For market OI caps test only
+            Self::SELECT_OI_CAPS_MARKET,
+            // For batch market OI caps update test only
+            Self::SELECT_BATCH_OI_CAPS_MARKETS[0],
+            Self::SELECT_BATCH_OI_CAPS_MARKETS[1],
+            Self::SELECT_BATCH_OI_CAPS_MARKETS[2],
+            // For market token supply verification test only
+            Self::SELECT_SUPPLY_VERIFICATION_MARKET,
             ["fETH", "fETH", "USDH"],
         ])
         .await?;
@@ -410,6 +469,7 @@ impl Deployment {
         self.users.add_user(Self::DEFAULT_USER, &mut self.rng)?;
         self.users.add_user(Self::USER_1, &mut self.rng)?;
         self.users.add_user(Self::DEFAULT_KEEPER, &mut self.rng)?;
+        self.users.add_user(Self::USER_2, &mut self.rng)?;
 
         Ok(())
     }
@@ -581,6 +641,7 @@ impl Deployment {
                     RoleKey::PRICE_KEEPER,
                     RoleKey::FEATURE_KEEPER,
                     RoleKey::CONFIG_KEEPER,
+                    RoleKey::ORACLE_CONTROLLER,
                 ]
                 .iter()
                 .map(|role| client.enable_role(store, role)),
@@ -591,7 +652,13 @@ impl Deployment {
             .push(client.grant_role(store, &keeper, RoleKey::PRICE_KEEPER))?
             .push(client.grant_role(store, &keeper, RoleKey::FEATURE_KEEPER))?
             .push(client.grant_role(store, &keeper, RoleKey::CONFIG_KEEPER))?
-            .push(client.grant_role(store, &keeper, RoleKey::GT_CONTROLLER))?;
+            .push(client.grant_role(store, &keeper, RoleKey::GT_CONTROLLER))?
+            .push(client.grant_role(store, &keeper, RoleKey::ORACLE_CONTROLLER))?
+            .push(client.set_trusted_chainlink_verifier(
+                store,
+                Self::TRUSTED_CHAINLINK_VERIFIER_INDEX,
+                &self.chainlink_verifier_program,
+            ))?;
 
         _ = builder
             .send_all(false)
@@ -813,8 +880,13 @@ impl Deployment {
         let market_tokens = self
             .market_tokens
             .iter()
-            .filter_map(|([_, long, short], address)| {
-                if long == long_token && short == short_token {
+            .filter_map(|(name, address)| {
+                let [_, long, short] = name;
+                // Keep the market of the GLV batch shift test out of the default GLV.
+                if long == long_token
+                    && short == short_token
+                    && *name != Self::SELECT_GLV_BATCH_SHIFT_MARKET
+                {
                     Some(*address)
                 } else {
                     None
@@ -1332,6 +1404,149 @@ impl Deployment {
 
         Ok(Arc::new(oracle))
     }
+
+    /// Create a store with its own token map, oracle and the selected market,
+    /// for the tests that change the store-wide state (e.g. pausing the store).
+    pub(crate) async fn create_isolated_store(
+        &self,
+        selector: [&str; 3],
+    ) -> eyre::Result<IsolatedStore> {
+        use rand::{distributions::Alphanumeric, thread_rng, Rng};
+
+        let store_key = thread_rng()
+            .sample_iter(Alphanumeric)
+            .take(16)
+            .collect::<String>();
+        let client = &self.client;
+        let store = client.find_store_address(&store_key);
+        let keeper = self.user_client(Self::DEFAULT_KEEPER)?;
+
+        let [index, long, short] = selector;
+        let index_token = self
+            .synthetic_tokens
+            .get(index)
+            .map(|token| (index, token, true))
+            .or_else(|| self.tokens.get(index).map(|token| (index, token, false)));
+        let tokens = [
+            index_token,
+            self.tokens.get(long).map(|token| (long, token, false)),
+            self.tokens.get(short).map(|token| (short, token, false)),
+        ]
+        .into_iter()
+        .map(|token| token.ok_or_eyre("token not found"))
+        .collect::<eyre::Result<Vec<_>>>()?;
+
+        let roles = [
+            RoleKey::MARKET_KEEPER,
+            RoleKey::ORDER_KEEPER,
+            RoleKey::PRICE_KEEPER,
+        ];
+        let mut builder = client.bundle();
+        builder
+            .push(client.initialize_store::<Keypair>(&store_key, None, None, None))?
+            .push_many(
+                roles.iter().map(|role| client.enable_role(&store, role)),
+                false,
+            )?
+            .push_many(
+                roles
+                    .iter()
+                    .map(|role| client.grant_role(&store, &keeper.payer(), role)),
+                false,
+            )?;
+        builder.send_all(false).await.map_err(|(_, err)| err)?;
+
+        let token_map = Keypair::new();
+        let oracle = Keypair::new();
+        let (rpc, token_map_address) = keeper.initialize_token_map(&store, &token_map);
+        let mut builder = keeper.bundle();
+        builder
+            .push(rpc)?
+            .push(keeper.set_token_map(&store, &token_map_address))?;
+        let mut inserted = HashSet::new();
+        for (name, token, synthetic) in tokens.iter() {
+            if !inserted.insert(token.address) {
+                continue;
+            }
+            let config = UpdateTokenConfigParams::default()
+                .update_price_feed(&token.config.provider, token.config.feed_id, None)?
+                .with_expected_provider(token.config.provider)
+                .with_precision(token.config.precision);
+            let rpc = if *synthetic {
+                keeper.insert_synthetic_token_config(
+                    &store,
+                    &token_map_address,
+                    name,
+                    &token.address,
+                    token.config.decimals,
+                    config,
+                    true,
+                    true,
+                )
+            } else {
+                keeper.insert_token_config(
+                    &store,
+                    &token_map_address,
+                    name,
+                    &token.address,
+                    config,
+                    true,
+                    true,
+                )
+            };
+            builder.push(rpc)?;
+        }
+        builder.push(keeper.initialize_oracle(&store, &oracle, None).await?.0)?;
+        builder.send_all(false).await.map_err(|(_, err)| err)?;
+
+        let [index_token, long_token, short_token] = [
+            tokens[0].1.address,
+            tokens[1].1.address,
+            tokens[2].1.address,
+        ];
+        let (rpc, market_token) = keeper
+            .create_market(
+                &store,
+                &format!("{index}/USD[{long}-{short}]"),
+                &index_token,
+                &long_token,
+                &short_token,
+                true,
+                Some(&token_map_address),
+            )
+            .await?;
+        let mut builder = keeper.bundle();
+        builder
+            .push(rpc)?
+            .push(keeper.update_market_config_by_key(
+                &store,
+                &market_token,
+                MarketConfigKey::MaxPoolAmountForLongToken,
+                &1_000_000_000_000_000_000,
+            )?)?
+            .push(keeper.update_market_config_by_key(
+                &store,
+                &market_token,
+                MarketConfigKey::MaxPoolAmountForShortToken,
+                &1_000_000_000_000_000_000,
+            )?)?;
+        let signatures = builder.send_all(false).await.map_err(|(_, err)| err)?;
+        tracing::info!(%store, %market_token, "created an isolated store with txns: {signatures:#?}");
+
+        Ok(IsolatedStore {
+            store,
+            oracle: oracle.pubkey(),
+            market_token,
+        })
+    }
+}
+
+/// A store created for a single test.
+#[derive(Debug)]
+pub(crate) struct IsolatedStore {
+    pub(crate) store: Pubkey,
+    pub(crate) oracle: Pubkey,
+    pub(crate) market_token: Pubkey,
 }
 
 /// Users.
@@ -0,0 +1,239 @@
+use std::time::Duration;
+
+use anchor_client::solana_sdk::pubkey::Pubkey;
+use anchor_spl::token::TokenAccount;
+use futures_util::TryStreamExt;
+use gmsol::{
+    exchange::ExchangeOps,
+    store::{events::StoreCPIEvent, market::VaultOps, store_ops::StoreOps},
+    utils::SignerRef,
+};
+use gmsol_store::{constants::TEST_EMERGENCY_MODE_MIN_PAUSED_DURATION, CoreError};
+use spl_associated_token_account::get_associated_token_address;
+
+use crate::anchor_test::setup::{current_deployment, Deployment, IsolatedStore};
+
+#[tokio::test]
+async fn pause_store() -> eyre::Result<()> {
+    let deployment = current_deployment().await?;
+    let _guard = deployment.use_accounts().await?;
+    let span = tracing::info_span!("pause_store");
+    let _enter = span.enter();
+
+    // Pausing affects the whole store, so the test runs on its own store.
+    let IsolatedStore {
+        store,
+        oracle,
+        market_token,
+    } = deployment
+        .create_isolated_store(Deployment::SELECT_ISOLATED_STORE_MARKET)
+        .await?;
+
+    let client = deployment.user_client(Deployment::DEFAULT_USER)?;
+    let keeper = deployment.user_client(Deployment::DEFAULT_KEEPER)?;
+
+    let [_, long_token, _] = Deployment::SELECT_ISOLATED_STORE_MARKET;
+    let long_token_address = deployment.token(long_token).expect("must exist").address;
+    let amount = 1_000_017;
+    deployment
+        .mint_or_transfer_to_user(long_token, Deployment::DEFAULT_USER, amount * 2)
+        .await?;
+
+    let (rpc, deposit) = client
+        .create_deposit(&store, &market_token)
+        .long_token(amount, None, None)
+        .build_with_address()
+        .await?;
+    let signature = rpc.send_without_preflight().await?;
+    tracing::info!(%signature, %deposit, "created a deposit");
+
+    let signature = deployment
+        .client
+        .set_store_paused(&store, true)
+        .send_without_preflight()
+        .await?;
+    tracing::info!(%signature, %store, "paused the store");
+
+    // Creation is blocked.
+    let (rpc, _) = client
+        .create_deposit(&store, &market_token)
+        .long_token(amount, None, None)
+        .build_with_address()
+        .await?;
+    let err = rpc
+        .send()
+        .await
+        .expect_err("should throw an error when creating a deposit while paused");
+    assert_eq!(
+        gmsol::Error::from(err).anchor_error_code(),
+        Some(CoreError::FeatureDisabled.into())
+    );
+
+    // Execution is blocked.
+    let mut builder = keeper.execute_deposit(&store, &oracle, &deposit, false);
+    let err = deployment
+        .execute_with_pyth(&mut builder, None, false, false)
+        .await
+        .expect_err("should throw an error when executing a deposit while paused");
+    assert_eq!(
+        err.anchor_error_code(),
+        Some(CoreError::FeatureDisabled.into())
+    );
+
+    // Closing is still allowed so that users can exit.
+    let signature = client
+        .close_deposit(&store, &deposit)
+        .build()
+        .await?
+        .send()
+        .await?;
+    tracing::info!(%signature, %deposit, "closed the deposit");
+    assert!(deployment
+        .get_ata_amount(&long_token_address, &deposit)
+        .await?
+        .is_none());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn emergency_vault_withdraw() -> eyre::Result<()> {
+    let deployment = current_deployment().await?;
+    let _guard = deployment.use_accounts().await?;
+    let span = tracing::info_span!("emergency_vault_withdraw");
+    let _enter = span.enter();
+
+    // The emergency mode requires pausing the whole store, so the test runs on its own store.
+    let IsolatedStore {
+        store,
+        oracle,
+        market_token,
+    } = deployment
+        .create_isolated_store(Deployment::SELECT_ISOLATED_STORE_MARKET)
+        .await?;
+
+    let client = deployment.user_client(Deployment::DEFAULT_USER)?;
+    let keeper = deployment.user_client(Deployment::DEFAULT_KEEPER)?;
+    let admin = &deployment.client;
+
+    let [_, long_token, _] = Deployment::SELECT_ISOLATED_STORE_MARKET;
+    let long_token_address = deployment.token(long_token).expect("must exist").address;
+    let amount = 2_000_023;
+    deployment
+        .mint_or_transfer_to_user(long_token, Deployment::DEFAULT_USER, amount)
+        .await?;
+
+    let (rpc, deposit) = client
+        .create_deposit(&store, &market_token)
+        .long_token(amount, None, None)
+        .build_with_address()
+        .await?;
+    let signature = rpc.send_without_preflight().await?;
+    tracing::info!(%signature, %deposit, "created a deposit");
+    let mut builder = keeper.execute_deposit(&store, &oracle, &deposit, false);
+    deployment
+        .execute_with_pyth(&mut builder, None, true, true)
+        .await?;
+
+    let signature = admin
+        .set_store_paused(&store, true)
+        .send_without_preflight()
+        .await?;
+    tracing::info!(%signature, %store, "paused the store");
+
+    // The store must have been paused for long enough.
+    let err = admin
+        .enable_emergency_mode(&store)
+        .send()
+        .await
+        .expect_err("should throw an error when the store has just been paused");
+    assert_eq!(
+        gmsol::Error::from(err).anchor_error_code(),
+        Some(CoreError::PreconditionsAreNotMet.into())
+    );
+
+    // The program is built with the `test-only` feature for the anchor tests.
+    tokio::time::sleep(Duration::from_secs(
+        TEST_EMERGENCY_MODE_MIN_PAUSED_DURATION as u64 + 2,
+    ))
+    .await;
+    let signature = admin.enable_emergency_mode(&store).send().await?;
+    tracing::info!(%signature, %store, "enabled the emergency mode");
+
+    let market_address = admin.find_market_address(&store, &market_token);
+    let vault = admin.find_market_vault_address(&store, &long_token_address);
+    let destination = get_associated_token_address(&client.payer(), &long_token_address);
+
+    let balance_before = admin
+        .market(&market_address)
+        .await?
+        .state()
+        .long_token_balance_raw();
+    let vault_before = get_token_amount(admin, &vault).await?;
+    let destination_before = get_token_amount(admin, &destination).await?;
+    assert!(balance_before >= amount);
+
+    let withdrawn = amount / 2;
+    let signature = admin
+        .emergency_vault_withdraw(
+            &store,
+            &market_token,
+            &long_token_address,
+            &destination,
+            withdrawn,
+        )
+        .send()
+        .await?;
+    tracing::info!(%signature, %withdrawn, "withdrew from the market vault");
+
+    let balance_after = admin
+        .market(&market_address)
+        .await?
+        .state()
+        .long_token_balance_raw();
+    assert_eq!(balance_after + withdrawn, balance_before);
+    assert_eq!(
+        get_token_amount(admin, &vault).await? + withdrawn,
+        vault_before
+    );
+    assert_eq!(
+        get_token_amount(admin, &destination).await?,
+        destination_before + withdrawn
+    );
+
+    let withdrawal = {
+        let events = admin.historical_store_cpi_events(&vault, None).await?;
+        futures_util::pin_mut!(events);
+        let mut withdrawal = None;
+        while let Some(events) = events.try_next().await? {
+            withdrawal = events
+                .into_value()
+                .into_iter()
+                .find_map(|event| match event {
+                    StoreCPIEvent::EmergencyWithdrawal(event) => Some(event),
+                    _ => None,
+                });
+            if withdrawal.is_some() {
+                break;
+            }
+        }
+        withdrawal.ok_or(gmsol::Error::NotFound)?
+    };
+    assert_eq!(withdrawal.market_token, market_token);
+    assert_eq!(withdrawal.token, long_token_address);
+    assert_eq!(withdrawal.amount, withdrawn);
+    assert_eq!(withdrawal.destination, destination);
+
+    Ok(())
+}
+
+async fn get_token_amount(
+    client: &gmsol::Client<SignerRef>,
+    address: &Pubkey,
+) -> eyre::Result<u64> {
+    Ok(client
+        .account::<TokenAccount>(address)
+        .await?
+        .ok_or(gmsol::Error::NotFound)?
+        .amount)
+}
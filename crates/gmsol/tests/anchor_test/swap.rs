@@ -105,3 +105,67 @@ async fn basic_swap() -> eyre::Result<()> {
 
     Ok(())
 }
+
+#[tokio::test]
+async fn required_feeds() -> eyre::Result<()> {
+    use std::collections::BTreeSet;
+
+    use gmsol_store::states::common::{token_with_feeds::token_records, TokensWithFeed};
+
+    let deployment = current_deployment().await?;
+    let _guard = deployment.use_accounts().await?;
+    let span = tracing::info_span!("required_feeds");
+    let _enter = span.enter();
+
+    let client = deployment.user_client(Deployment::DEFAULT_USER)?;
+    let store = &deployment.store;
+    let fbtc = deployment.token("fBTC").expect("must exist");
+    let usdg = deployment.token("USDG").expect("must exist");
+
+    let long_token_amount = 1_000_031;
+    let short_token_amount = 6_000_000_000_037;
+
+    let market_token = deployment
+        .prepare_market(
+            ["fBTC", "fBTC", "USDG"],
+            long_token_amount,
+            short_token_amount,
+            true,
+        )
+        .await?;
+
+    let swap_in_amount = 100 * 100_000_000;
+    deployment
+        .mint_or_transfer_to_user("USDG", Deployment::DEFAULT_USER, swap_in_amount)
+        .await?;
+
+    let (rpc, order) = client
+        .market_swap(
+            store,
+            market_token,
+            true,
+            &usdg.address,
+            swap_in_amount,
+            [market_token],
+        )
+        .build_with_address()
+        .await?;
+    let signature = rpc.send().await?;
+    tracing::info!(%order, %signature, "created a swap order");
+
+    let feeds = client.required_feeds(store, &order).await?;
+
+    let token_map = client.authorized_token_map(store).await?;
+    let tokens = BTreeSet::from([fbtc.address, usdg.address]);
+    let expected = TokensWithFeed::try_from_records(token_records(&token_map, &tokens)?)?;
+
+    assert_eq!(feeds.tokens, expected.tokens);
+    assert_eq!(feeds.feeds, expected.feeds);
+    assert_eq!(feeds.providers, expected.providers);
+    assert_eq!(feeds.nums, expected.nums);
+
+    let signature = client.close_order(&order)?.build().await?.send().await?;
+    tracing::info!(%order, %signature, "cancelled the swap order");
+
+    Ok(())
+}
@@ -1,4 +1,16 @@
-use gmsol::{constants::MARKET_USD_UNIT, exchange::ExchangeOps};
+use std::time::SystemTime;
+
+use anchor_client::solana_sdk::{pubkey::Pubkey, signature::Keypair};
+use gmsol::{
+    constants::MARKET_USD_UNIT,
+    exchange::ExchangeOps,
+    store::{
+        token::{ClaimableAccountToClose, TokenAccountOps},
+        token_config::TokenConfigOps,
+    },
+    types::{AmountKey, UpdateTokenConfigParams},
+};
+use gmsol_store::CoreError;
 use tracing::Instrument;
 
 use crate::anchor_test::setup::{current_deployment, Deployment};
@@ -90,3 +102,293 @@ async fn unwrap_native_token_with_swap_path() -> eyre::Result<()> {
 
     Ok(())
 }
+
+#[tokio::test]
+async fn close_empty_claimable_accounts() -> eyre::Result<()> {
+    let deployment = current_deployment().await?;
+    let _guard = deployment.use_accounts().await?;
+    let span = tracing::info_span!("close_empty_claimable_accounts");
+    let _enter = span.enter();
+
+    let store = &deployment.store;
+    let keeper = deployment.user_client(Deployment::DEFAULT_KEEPER)?;
+    let usdg = deployment.token("USDG").unwrap();
+
+    let timestamp = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)?
+        .as_secs()
+        .try_into()?;
+    let time_key = keeper.store(store).await?.claimable_time_key(timestamp)?;
+
+    let claimable_accounts = (0..3)
+        .map(|_| {
+            let owner = Pubkey::new_unique();
+            let account =
+                keeper.find_claimable_account_address(store, &usdg.address, &owner, &time_key);
+            ClaimableAccountToClose {
+                mint: usdg.address,
+                owner,
+                timestamp,
+                account,
+            }
+        })
+        .collect::<Vec<_>>();
+
+    // Prepare the claimable accounts.
+    for claimable in claimable_accounts.iter() {
+        let signature = keeper
+            .use_claimable_account(
+                store,
+                &claimable.mint,
+                &claimable.owner,
+                timestamp,
+                &claimable.account,
+                0,
+            )
+            .send()
+            .await?;
+        tracing::info!(%signature, account=%claimable.account, "prepared claimable account");
+    }
+
+    // Make the second claimable account non-empty.
+    let non_empty = &claimable_accounts[1];
+    let signature = deployment
+        .client
+        .store_transaction()
+        .pre_instruction(spl_token::instruction::mint_to_checked(
+            &anchor_spl::token::ID,
+            &usdg.address,
+            &non_empty.account,
+            &deployment.client.payer(),
+            &[],
+            1,
+            usdg.config.decimals,
+        )?)
+        .send()
+        .await?;
+    tracing::info!(%signature, account=%non_empty.account, "minted to claimable account");
+
+    let signature = keeper
+        .close_empty_claimable_accounts(store, &claimable_accounts)
+        .send()
+        .await?;
+    tracing::info!(%signature, "closed empty claimable accounts");
+
+    let rpc = keeper.store_program().rpc();
+    for (idx, claimable) in claimable_accounts.iter().enumerate() {
+        let account = rpc
+            .get_account_with_commitment(&claimable.account, keeper.commitment())
+            .await?
+            .value;
+        if idx == 1 {
+            assert!(account.is_some(), "non-empty account must not be closed");
+        } else {
+            assert!(account.is_none(), "empty account must be closed");
+        }
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn recover_stuck_claimable_account() -> eyre::Result<()> {
+    let deployment = current_deployment().await?;
+    let _guard = deployment.use_accounts().await?;
+    let span = tracing::info_span!("recover_stuck_claimable_account");
+    let _enter = span.enter();
+
+    let store = &deployment.store;
+    let admin = &deployment.client;
+    let keeper = deployment.user_client(Deployment::DEFAULT_KEEPER)?;
+    let usdg = deployment.token("USDG").unwrap();
+    let market_token = deployment
+        .market_token("fBTC", "fBTC", "USDG")
+        .expect("must exist");
+
+    let store_account = keeper.store(store).await?;
+    let window = i64::try_from(*store_account.get_amount_by_key(AmountKey::ClaimableTimeWindow))?;
+    let lifetime =
+        i64::try_from(*store_account.get_amount_by_key(AmountKey::MaxClaimableAccountLifetime))?;
+    let now: i64 = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)?
+        .as_secs()
+        .try_into()?;
+
+    let prepare = |timestamp: i64| {
+        let keeper = &keeper;
+        async move {
+            let time_key = keeper.store(store).await?.claimable_time_key(timestamp)?;
+            let owner = Pubkey::new_unique();
+            let account =
+                keeper.find_claimable_account_address(store, &usdg.address, &owner, &time_key);
+            let signature = keeper
+                .use_claimable_account(store, &usdg.address, &owner, timestamp, &account, 0)
+                .send()
+                .await?;
+            tracing::info!(%signature, %account, "prepared claimable account");
+            eyre::Ok(ClaimableAccountToClose {
+                mint: usdg.address,
+                owner,
+                timestamp,
+                account,
+            })
+        }
+    };
+
+    // Claimable accounts that are still in use cannot be recovered.
+    let recent = prepare(now).await?;
+    let err = admin
+        .recover_stuck_claimable_account(store, market_token, &recent, None)
+        .send()
+        .await
+        .expect_err("should throw an error when the claimable account is not dormant");
+    assert_eq!(
+        gmsol::Error::from(err).anchor_error_code(),
+        Some(CoreError::PreconditionsAreNotMet.into())
+    );
+
+    // Claimable accounts that have been dormant for too long can be recovered,
+    // even if they are non-empty.
+    let dormant = prepare(now - lifetime - 2 * window).await?;
+    let signature = admin
+        .store_transaction()
+        .pre_instruction(spl_token::instruction::mint_to_checked(
+            &anchor_spl::token::ID,
+            &usdg.address,
+            &dormant.account,
+            &admin.payer(),
+            &[],
+            1,
+            usdg.config.decimals,
+        )?)
+        .send()
+        .await?;
+    tracing::info!(%signature, account=%dormant.account, "minted to claimable account");
+
+    let rpc = keeper.store_program().rpc();
+    let rent = rpc
+        .get_account_with_commitment(&dormant.account, keeper.commitment())
+        .await?
+        .value
+        .expect("must exist")
+        .lamports;
+    let receiver = Pubkey::new_unique();
+    let signature = admin
+        .recover_stuck_claimable_account(store, market_token, &dormant, Some(&receiver))
+        .send()
+        .await?;
+    tracing::info!(%signature, account=%dormant.account, "recovered claimable account");
+
+    let account = rpc
+        .get_account_with_commitment(&dormant.account, keeper.commitment())
+        .await?
+        .value;
+    assert!(account.is_none(), "the recovered account must be closed");
+    assert_eq!(rpc.get_balance(&receiver).await?, rent);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn disabled_tokens() -> eyre::Result<()> {
+    let deployment = current_deployment().await?;
+    let _guard = deployment.use_accounts().await?;
+    let span = tracing::info_span!("disabled_tokens");
+    let _enter = span.enter();
+
+    let store = &deployment.store;
+    let keeper = deployment.user_client(Deployment::DEFAULT_KEEPER)?;
+
+    // Use a dedicated token map to avoid affecting other tests.
+    let token_map_keypair = Keypair::new();
+    let (rpc, token_map) = keeper.initialize_token_map(store, &token_map_keypair);
+    let mut builder = keeper.bundle();
+    builder.push(rpc)?;
+    let names = ["fBTC", "USDG", "WSOL"];
+    for name in names {
+        let token = deployment.token(name).expect("must exist");
+        let config = UpdateTokenConfigParams::default()
+            .update_price_feed(&token.config.provider, token.config.feed_id, None)?
+            .with_expected_provider(token.config.provider)
+            .with_precision(token.config.precision);
+        builder.push(keeper.insert_token_config(
+            store,
+            &token_map,
+            name,
+            &token.address,
+            config,
+            true,
+            true,
+        ))?;
+    }
+    builder.send_all(true).await.map_err(|(_, err)| err)?;
+    tracing::info!(%token_map, "initialized a token map");
+
+    assert!(keeper.disabled_tokens(&token_map).await?.is_empty());
+
+    let fbtc = deployment.token("fBTC").expect("must exist").address;
+    let wsol = deployment.token("WSOL").expect("must exist").address;
+    for token in [&fbtc, &wsol] {
+        let signature = keeper
+            .toggle_token_config(store, &token_map, token, false)
+            .send_without_preflight()
+            .await?;
+        tracing::info!(%signature, %token, "disabled token config");
+    }
+
+    let mut disabled = keeper.disabled_tokens(&token_map).await?;
+    disabled.sort();
+    let mut expected = vec![fbtc, wsol];
+    expected.sort();
+    assert_eq!(disabled, expected);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn get_token_config() -> eyre::Result<()> {
+    let deployment = current_deployment().await?;
+    let _guard = deployment.use_accounts().await?;
+    let span = tracing::info_span!("get_token_config");
+    let _enter = span.enter();
+
+    let store = &deployment.store;
+    let keeper = deployment.user_client(Deployment::DEFAULT_KEEPER)?;
+
+    // Use a dedicated token map to avoid affecting other tests.
+    let token_map_keypair = Keypair::new();
+    let (rpc, token_map) = keeper.initialize_token_map(store, &token_map_keypair);
+    let token = deployment.token("WSOL").expect("must exist");
+    let config = UpdateTokenConfigParams::default()
+        .update_price_feed(&token.config.provider, token.config.feed_id, Some(3))?
+        .with_expected_provider(token.config.provider)
+        .with_precision(token.config.precision);
+    let signature = rpc
+        .merge(keeper.insert_token_config(
+            store,
+            &token_map,
+            "WSOL",
+            &token.address,
+            config,
+            true,
+            true,
+        ))
+        .send()
+        .await?;
+    tracing::info!(%signature, %token_map, "initialized a token map");
+
+    let fetched = keeper.token_config(&token_map, &token.address).await?;
+    assert_eq!(fetched.name(), "WSOL");
+    assert!(fetched.is_enabled());
+    assert_eq!(fetched.decimals(), token.config.decimals);
+    assert_eq!(fetched.precision(), token.config.precision);
+    assert_eq!(fetched.expected_provider(), token.config.provider);
+
+    let feeds = fetched.feeds();
+    assert_eq!(feeds.len(), 1);
+    assert_eq!(feeds[0].provider, token.config.provider as u8);
+    assert_eq!(feeds[0].feed, token.config.feed_id);
+    assert_eq!(feeds[0].timestamp_adjustment, 3);
+
+    Ok(())
+}
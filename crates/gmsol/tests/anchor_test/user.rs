@@ -1,4 +1,10 @@
-use gmsol::{store::user::UserOps, types::user::ReferralCodeV2};
+use gmsol::{
+    constants::MARKET_USD_UNIT,
+    exchange::ExchangeOps,
+    store::{glv::GlvOps, user::UserOps},
+    types::user::{ReferralCodeV2, ReferralRewardVault, UserHeader},
+    utils::ZeroCopy,
+};
 use gmsol_store::CoreError;
 
 use crate::anchor_test::setup::{current_deployment, Deployment};
@@ -37,6 +43,13 @@ async fn referral() -> eyre::Result<()> {
         .await?;
     tracing::info!(%signature, "set the referrer of user 2 to user 1");
 
+    let stats = client.referral_stats(store, &client.payer()).await?;
+    assert_eq!(
+        stats.code,
+        Some(client.find_referral_code_address(store, code))
+    );
+    assert_eq!(stats.referee_count, 1);
+
     // Self-referral.
     let err = client
         .set_referrer(store, code, None)
@@ -49,14 +62,54 @@ async fn referral() -> eyre::Result<()> {
         Some(CoreError::SelfReferral.into())
     );
 
+    // Re-submitting the same referral code is a no-op.
+    let signature = client.initialize_referral_code(store, code)?.send().await?;
+    tracing::info!(%signature, "re-submitted the referral code for user 1");
+
+    // A different referral code is rejected.
+    let err = client
+        .initialize_referral_code(store, ReferralCodeV2::decode("gmso2")?)?
+        .send()
+        .await
+        .expect_err("should throw an error when the user already has a referral code");
+    assert_eq!(
+        gmsol::Error::from(err).anchor_error_code(),
+        Some(CoreError::ReferralCodeHasBeenSet.into())
+    );
+
     // Referral code is exclusive.
-    client
+    let err = client2
         .initialize_referral_code(store, code)?
         .send()
         .await
         .expect_err(
             "should throw an error when the referral code has already been set by someone else",
         );
+    assert_eq!(
+        gmsol::Error::from(err).anchor_error_code(),
+        Some(CoreError::ReferralCodeMismatched.into())
+    );
+
+    // Prepare the referral reward vault.
+    let admin = &deployment.client;
+    let reward = 100_000_000;
+    let reward_vault = admin.find_referral_reward_vault_address(store);
+    if admin
+        .account::<ZeroCopy<ReferralRewardVault>>(&reward_vault)
+        .await?
+        .is_none()
+    {
+        let signature = admin
+            .initialize_referral_reward_vault(store, 10 * reward)
+            .send_without_preflight()
+            .await?;
+        tracing::info!(%signature, %reward_vault, "initialized referral reward vault");
+    }
+    let signature = admin
+        .set_referral_transfer_reward(store, reward, 0)
+        .send_without_preflight()
+        .await?;
+    tracing::info!(%signature, %reward, "set referral transfer reward");
 
     let signature = client
         .transfer_referral_code(store, &client2.payer(), None)
@@ -65,12 +118,28 @@ async fn referral() -> eyre::Result<()> {
         .await?;
     tracing::info!(%signature, "requested to referral code to user 2");
 
+    let rpc = client2.store_program().rpc();
+    let balance_before = rpc.get_balance(&client2.payer()).await?;
     let signature = client2
         .accept_referral_code(store, code, None)
         .await?
         .send_without_preflight()
         .await?;
     tracing::info!(%signature, "user 2 accepted the referral code");
+    let balance_after = rpc.get_balance(&client2.payer()).await?;
+
+    // The reward is paid to user 2, minus the transaction fee.
+    const MAX_FEE: u64 = 100_000;
+    assert!(balance_after <= balance_before + reward);
+    assert!(balance_after + MAX_FEE >= balance_before + reward);
+
+    let referral_code = client
+        .account::<ZeroCopy<ReferralCodeV2>>(&client.find_referral_code_address(store, code))
+        .await?
+        .expect("must exist")
+        .0;
+    assert_eq!(referral_code.owner, client2.payer());
+    assert!(referral_code.is_transfer_reward_claimed());
 
     // Mutual-referral.
     let err = client
@@ -86,3 +155,311 @@ async fn referral() -> eyre::Result<()> {
 
     Ok(())
 }
+
+#[tokio::test]
+async fn onboard_referred_user() -> eyre::Result<()> {
+    let deployment = current_deployment().await?;
+    let _guard = deployment.use_accounts().await?;
+    let span = tracing::info_span!("onboard_referred_user");
+    let _enter = span.enter();
+
+    let referrer = deployment.user_client(Deployment::DEFAULT_USER)?;
+    let client = deployment.user_client(Deployment::USER_2)?;
+    let store = &deployment.store;
+
+    let code = ReferralCodeV2::decode("gmso3")?;
+    let signature = referrer
+        .prepare_user(store)?
+        .merge(referrer.initialize_referral_code(store, code)?)
+        .send_without_preflight()
+        .await?;
+    tracing::info!(%signature, "initialized referral code for the referrer");
+
+    let market_token = deployment
+        .market_token("SOL", "fBTC", "USDG")
+        .expect("must exist");
+    let amount = 1_000_000;
+    deployment
+        .mint_or_transfer_to_user("USDG", Deployment::USER_2, 2 * amount)
+        .await?;
+
+    // Prepare the user, set the referrer and create the deposit in one transaction.
+    let (rpc, deposit) = client
+        .create_deposit(store, market_token)
+        .short_token(amount, None, None)
+        .referral_code(Some(code))
+        .build_with_address()
+        .await?;
+    let signature = rpc.send().await?;
+    tracing::info!(%signature, %deposit, "onboarded a referred user with a deposit");
+
+    let user = client
+        .account::<ZeroCopy<UserHeader>>(&client.find_user_address(store, &client.payer()))
+        .await?
+        .expect("must exist")
+        .0;
+    assert_eq!(
+        user.referral().referrer(),
+        Some(&referrer.find_user_address(store, &referrer.payer()))
+    );
+
+    // The referral code is ignored once the referrer has been set.
+    let (with_code, _) = client
+        .create_deposit(store, market_token)
+        .short_token(amount, None, None)
+        .referral_code(Some(code))
+        .build_with_address()
+        .await?;
+    let (without_code, _) = client
+        .create_deposit(store, market_token)
+        .short_token(amount, None, None)
+        .build_with_address()
+        .await?;
+    assert_eq!(
+        with_code.instructions().len(),
+        without_code.instructions().len()
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn freeze_user_account() -> eyre::Result<()> {
+    let deployment = current_deployment().await?;
+    let _guard = deployment.use_accounts().await?;
+    let span = tracing::info_span!("freeze_user_account");
+    let _enter = span.enter();
+
+    let admin = &deployment.client;
+    let keeper = deployment.user_client(Deployment::DEFAULT_KEEPER)?;
+    let client = deployment.user_client(Deployment::USER_1)?;
+    let store = &deployment.store;
+    let oracle = &deployment.oracle();
+    let owner = client.payer();
+
+    let market_token = deployment
+        .prepare_market(["fBTC", "fBTC", "USDG"], 1_000_007, 6_000_000_000_007, true)
+        .await?;
+
+    let collateral_amount = 100 * 100_000_000;
+    deployment
+        .mint_or_transfer_to_user("USDG", Deployment::USER_1, collateral_amount * 2)
+        .await?;
+
+    let size = 5_000 * 100_000_000_000_000_000_000;
+
+    // Open a position before freezing.
+    let (rpc, order) = client
+        .market_increase(store, market_token, false, collateral_amount, true, size)
+        .build_with_address()
+        .await?;
+    let signature = rpc.send().await?;
+    tracing::info!(%order, %signature, "created an increase position order");
+
+    let mut builder = keeper.execute_order(store, oracle, &order, false)?;
+    deployment
+        .execute_with_pyth(
+            builder
+                .add_alt(deployment.common_alt().clone())
+                .add_alt(deployment.market_alt().clone()),
+            None,
+            true,
+            true,
+        )
+        .await?;
+
+    let signature = admin
+        .freeze_user_account(store, &owner, [1; 32])
+        .send_without_preflight()
+        .await?;
+    tracing::info!(%signature, "froze the user account");
+
+    // Frozen users cannot create increase orders.
+    let err = client
+        .market_increase(store, market_token, false, collateral_amount, true, size)
+        .build_with_address()
+        .await?
+        .0
+        .send()
+        .await
+        .expect_err("should throw an error when the user account is frozen");
+    assert_eq!(
+        gmsol::Error::from(err).anchor_error_code(),
+        Some(CoreError::UserAccountFrozen.into())
+    );
+
+    // Nor GLV deposits.
+    let err = client
+        .create_glv_deposit(store, &deployment.glv_token, market_token)
+        .short_token_deposit(collateral_amount, None, None)
+        .build_with_address()
+        .await?
+        .0
+        .send()
+        .await
+        .expect_err("should throw an error when the user account is frozen");
+    assert_eq!(
+        gmsol::Error::from(err).anchor_error_code(),
+        Some(CoreError::UserAccountFrozen.into())
+    );
+
+    // But are still allowed to close existing positions.
+    let (rpc, order) = client
+        .market_decrease(store, market_token, false, 0, true, size)
+        .build_with_address()
+        .await?;
+    let signature = rpc.send().await?;
+    tracing::info!(%order, %signature, "created a decrease position order while frozen");
+
+    let mut builder = keeper.execute_order(store, oracle, &order, false)?;
+    deployment
+        .execute_with_pyth(
+            builder
+                .add_alt(deployment.common_alt().clone())
+                .add_alt(deployment.market_alt().clone()),
+            None,
+            true,
+            true,
+        )
+        .await?;
+
+    let signature = admin
+        .unfreeze_user_account(store, &owner)
+        .send_without_preflight()
+        .await?;
+    tracing::info!(%signature, "unfroze the user account");
+
+    let (rpc, order) = client
+        .market_increase(store, market_token, false, collateral_amount, true, size)
+        .build_with_address()
+        .await?;
+    let signature = rpc.send().await?;
+    tracing::info!(%order, %signature, "created an increase position order after unfreezing");
+
+    let mut builder = keeper.execute_order(store, oracle, &order, false)?;
+    deployment
+        .execute_with_pyth(
+            builder
+                .add_alt(deployment.common_alt().clone())
+                .add_alt(deployment.market_alt().clone()),
+            None,
+            true,
+            true,
+        )
+        .await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn validate_referral_reward_factors() -> eyre::Result<()> {
+    let deployment = current_deployment().await?;
+    let _guard = deployment.use_accounts().await?;
+    let span = tracing::info_span!("validate_referral_reward_factors");
+    let _enter = span.enter();
+
+    let client = &deployment.client;
+    let store = &deployment.store;
+    let factors = |bases: &[u128]| {
+        bases
+            .iter()
+            .map(|base| *base * MARKET_USD_UNIT / 100)
+            .collect::<Vec<_>>()
+    };
+
+    // The factors used in the deployment are valid.
+    let problems = client
+        .referral_reward_factors_problems(store, factors(&[5, 10, 11, 12, 13, 15, 18, 23, 31, 44]))
+        .await?;
+    assert!(problems.is_empty());
+
+    // Unsorted and out of bounds.
+    let problems = client
+        .referral_reward_factors_problems(store, factors(&[5, 10, 9, 12, 13, 15, 18, 23, 31, 101]))
+        .await?;
+    tracing::info!(?problems, "unsorted and out of bounds");
+    let ranks = problems
+        .iter()
+        .map(|problem| problem.rank)
+        .collect::<Vec<_>>();
+    assert_eq!(ranks, [Some(9), Some(2)]);
+
+    // Rank count mismatched.
+    let problems = client
+        .referral_reward_factors_problems(store, factors(&[5, 10]))
+        .await?;
+    tracing::info!(?problems, "rank count mismatched");
+    assert_eq!(problems.len(), 1);
+    assert_eq!(problems[0].rank, None);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn user_activity() -> eyre::Result<()> {
+    let deployment = current_deployment().await?;
+    let _guard = deployment.use_accounts().await?;
+    let span = tracing::info_span!("user_activity");
+    let _enter = span.enter();
+
+    let keeper = deployment.user_client(Deployment::DEFAULT_KEEPER)?;
+    let client = deployment.locked_user_client().await?;
+    let store = &deployment.store;
+    let oracle = &deployment.oracle();
+    let owner = client.payer();
+    let market_token = deployment
+        .market_token("SOL", "WSOL", "WSOL")
+        .expect("must exist");
+
+    let amount = 1_000_000;
+    deployment
+        .mint_or_transfer_to("WSOL", &owner, 3 * amount)
+        .await?;
+
+    let mut before = None;
+    for idx in 0..3 {
+        let (rpc, deposit) = client
+            .create_deposit(store, market_token)
+            .long_token(amount, None, None)
+            .build_with_address()
+            .await?;
+        let signature = rpc.send().await?;
+        tracing::info!(%deposit, %signature, "created a deposit");
+
+        let mut builder = keeper.execute_deposit(store, oracle, &deposit, true);
+        deployment
+            .execute_with_pyth(&mut builder, None, true, true)
+            .await?;
+
+        // The user account is prepared by the first deposit.
+        if idx == 0 {
+            before = Some(client.user_activity(store, &owner).await?);
+        }
+    }
+    let before = before.expect("must exist");
+    assert!(before.deposit_count >= 1);
+
+    let market_token_amount = deployment
+        .get_user_ata_amount(market_token, None)
+        .await?
+        .expect("must exist");
+    let (rpc, withdrawal) = client
+        .create_withdrawal(store, market_token, market_token_amount)
+        .build_with_address()
+        .await?;
+    let signature = rpc.send().await?;
+    tracing::info!(%withdrawal, %signature, "created a withdrawal");
+
+    let mut builder = keeper.execute_withdrawal(store, oracle, &withdrawal, true);
+    deployment
+        .execute_with_pyth(&mut builder, None, true, true)
+        .await?;
+
+    let after = client.user_activity(store, &owner).await?;
+    assert_eq!(after.deposit_count, before.deposit_count + 2);
+    assert_eq!(after.withdrawal_count, before.withdrawal_count + 1);
+    assert_eq!(after.shift_count, before.shift_count);
+    assert!(after.last_action_ts >= before.last_action_ts);
+
+    Ok(())
+}
@@ -1,3 +1,4 @@
+use anchor_client::solana_sdk::{signature::Keypair, signer::Signer};
 use gmsol::exchange::ExchangeOps;
 
 use crate::anchor_test::setup::{current_deployment, Deployment};
@@ -163,3 +164,90 @@ async fn balanced_pool_withdrawal() -> eyre::Result<()> {
     }
     Ok(())
 }
+
+#[tokio::test]
+async fn withdrawal_to_third_party_receiver() -> eyre::Result<()> {
+    let deployment = current_deployment().await?;
+    let _guard = deployment.use_accounts().await?;
+    let span = tracing::info_span!("withdrawal_to_third_party_receiver");
+    let _enter = span.enter();
+
+    let keeper = deployment.user_client(Deployment::DEFAULT_KEEPER)?;
+    let store = &deployment.store;
+    let oracle = &deployment.oracle();
+    let market_token = deployment
+        .market_token("fBTC", "fBTC", "USDG")
+        .expect("must exist");
+    let fbtc = deployment.token("fBTC").expect("must exist").address;
+    let usdg = deployment.token("USDG").expect("must exist").address;
+
+    let client = deployment.locked_user_client().await?;
+    let owner = client.payer();
+    let amount = 1_000_000;
+    deployment
+        .mint_or_transfer_to("fBTC", &owner, amount)
+        .await?;
+
+    let (rpc, deposit) = client
+        .create_deposit(store, market_token)
+        .long_token(amount, None, None)
+        .build_with_address()
+        .await?;
+    let signature = rpc.send().await?;
+    tracing::info!(%deposit, %signature, "created a deposit");
+
+    let mut builder = keeper.execute_deposit(store, oracle, &deposit, false);
+    deployment
+        .execute_with_pyth(&mut builder, None, true, true)
+        .await?;
+
+    let market_token_amount = deployment
+        .get_ata_amount(market_token, &owner)
+        .await?
+        .expect("must exist");
+    let owner_fbtc_before = deployment.get_ata_amount(&fbtc, &owner).await?;
+    let owner_usdg_before = deployment.get_ata_amount(&usdg, &owner).await?;
+
+    // Withdraw to a third-party receiver.
+    let receiver = Keypair::new().pubkey();
+    let (rpc, withdrawal) = client
+        .create_withdrawal(store, market_token, market_token_amount)
+        .receiver(receiver)
+        .build_with_address()
+        .await?;
+    let signature = rpc.send().await?;
+    tracing::info!(%withdrawal, %signature, %receiver, "created a withdrawal with receiver");
+
+    let mut builder = keeper.execute_withdrawal(store, oracle, &withdrawal, false);
+    deployment
+        .execute_with_pyth(&mut builder, None, true, true)
+        .await?;
+
+    // The output funds are sent to the receiver.
+    let received_fbtc = deployment
+        .get_ata_amount(&fbtc, &receiver)
+        .await?
+        .unwrap_or(0);
+    let received_usdg = deployment
+        .get_ata_amount(&usdg, &receiver)
+        .await?
+        .unwrap_or(0);
+    tracing::info!(%received_fbtc, %received_usdg, "received by the third-party receiver");
+    assert!(received_fbtc != 0 || received_usdg != 0);
+
+    // The market tokens are burned and the owner ATAs for the output tokens are untouched.
+    assert_eq!(
+        deployment.get_ata_amount(market_token, &owner).await?,
+        Some(0)
+    );
+    assert_eq!(
+        deployment.get_ata_amount(&fbtc, &owner).await?,
+        owner_fbtc_before
+    );
+    assert_eq!(
+        deployment.get_ata_amount(&usdg, &owner).await?,
+        owner_usdg_before
+    );
+
+    Ok(())
+}
@@ -3,8 +3,8 @@ use std::{collections::HashSet, ops::Deref};
 use futures_util::TryStreamExt;
 use solana_client::{nonblocking::rpc_client::RpcClient, rpc_config::RpcSendTransactionConfig};
 use solana_sdk::{
-    commitment_config::CommitmentConfig, packet::PACKET_DATA_SIZE, signature::Signature,
-    signer::Signer, transaction::VersionedTransaction,
+    commitment_config::CommitmentConfig, packet::PACKET_DATA_SIZE, pubkey::Pubkey,
+    signature::Signature, signer::Signer, transaction::VersionedTransaction,
 };
 
 use crate::{
@@ -237,6 +237,19 @@ impl<'a, C: Deref<Target = impl Signer> + Clone> BundleBuilder<'a, C> {
         Ok(self)
     }
 
+    /// Push [`TransactionBuilder`]s, each paired with its `new_transaction` flag,
+    /// in the order given by [`optimize_packing`].
+    pub fn push_many_optimized(
+        &mut self,
+        txns: impl IntoIterator<Item = (TransactionBuilder<'a, C>, bool)>,
+    ) -> crate::Result<&mut Self> {
+        for (txn, new_transaction) in optimize_packing(txns) {
+            self.try_push_with_opts(txn, new_transaction)
+                .map_err(|(_, err)| err)?;
+        }
+        Ok(self)
+    }
+
     /// Get back all collected [`TransactionBuilder`]s.
     pub fn into_builders(self) -> Vec<TransactionBuilder<'a, C>> {
         self.builders
@@ -358,6 +371,130 @@ impl<'a, C: Deref<Target = impl Signer> + Clone> BundleBuilder<'a, C> {
     }
 }
 
+/// Reorder [`TransactionBuilder`]s, each paired with its `new_transaction` flag, so that
+/// the ones using the same accounts are next to each other.
+///
+/// Fewer distinct accounts per transaction means more instructions can be merged into one
+/// transaction when pushing them in the returned order.
+///
+/// ## Notes
+/// - Builders are only reordered within the runs delimited by `new_transaction` flags and
+///   [ordered](TransactionBuilder::ordered) builders. The first builder of each run takes
+///   the `new_transaction` flag of the run, and ordered builders keep their positions.
+/// - Builders writing an account used by another builder of the same run keep their
+///   relative order.
+pub fn optimize_packing<'a, C: Deref<Target = impl Signer> + Clone>(
+    txns: impl IntoIterator<Item = (TransactionBuilder<'a, C>, bool)>,
+) -> Vec<(TransactionBuilder<'a, C>, bool)> {
+    let txns = txns.into_iter();
+    let mut packed = Vec::with_capacity(txns.size_hint().0);
+    let mut run = Vec::new();
+    for (txn, new_transaction) in txns {
+        if new_transaction || txn.is_ordered() {
+            packed.extend(group_by_conflicts(std::mem::take(&mut run)));
+        }
+        if txn.is_ordered() {
+            packed.push((txn, new_transaction));
+        } else {
+            run.push((txn, new_transaction));
+        }
+    }
+    packed.extend(group_by_conflicts(run));
+    packed
+}
+
+#[derive(Default)]
+struct ConflictGroup {
+    writable: HashSet<Pubkey>,
+    accounts: HashSet<Pubkey>,
+    members: Vec<usize>,
+}
+
+impl ConflictGroup {
+    fn conflicts_with(&self, other: &Self) -> bool {
+        !self.writable.is_disjoint(&other.accounts) || !other.writable.is_disjoint(&self.accounts)
+    }
+
+    fn absorb(&mut self, other: Self) {
+        self.writable.extend(other.writable);
+        self.accounts.extend(other.accounts);
+        self.members.extend(other.members);
+    }
+}
+
+fn group_by_conflicts<'a, C: Deref<Target = impl Signer> + Clone>(
+    run: Vec<(TransactionBuilder<'a, C>, bool)>,
+) -> Vec<(TransactionBuilder<'a, C>, bool)> {
+    let Some(new_transaction) = run.first().map(|(_, new_transaction)| *new_transaction) else {
+        return run;
+    };
+
+    // Groups are kept sorted by their first members.
+    let mut groups = Vec::<ConflictGroup>::new();
+    for (idx, (txn, _)) in run.iter().enumerate() {
+        // The payer is shared by all the builders, so it is not considered a conflict.
+        let payer = txn.get_payer();
+        let mut group = ConflictGroup {
+            members: vec![idx],
+            ..Default::default()
+        };
+        for meta in txn
+            .instructions_with_options(true, None)
+            .iter()
+            .flat_map(|ix| ix.accounts.iter())
+            .filter(|meta| meta.pubkey != payer)
+        {
+            if meta.is_writable {
+                group.writable.insert(meta.pubkey);
+            }
+            group.accounts.insert(meta.pubkey);
+        }
+
+        // Merge all the groups conflicting with the new builder into the earliest of them.
+        let mut target = None;
+        let mut pos = 0;
+        while pos < groups.len() {
+            if !groups[pos].conflicts_with(&group) {
+                pos += 1;
+                continue;
+            }
+            match target {
+                None => {
+                    target = Some(pos);
+                    pos += 1;
+                }
+                Some(target) => {
+                    let other = groups.remove(pos);
+                    groups[target].absorb(other);
+                }
+            }
+        }
+        match target {
+            Some(target) => groups[target].absorb(group),
+            None => groups.push(group),
+        }
+    }
+
+    let mut slots = run
+        .into_iter()
+        .map(|(txn, _)| Some(txn))
+        .collect::<Vec<_>>();
+    groups
+        .into_iter()
+        .flat_map(|mut group| {
+            group.members.sort_unstable();
+            group.members
+        })
+        .enumerate()
+        .map(|(pos, idx)| {
+            let txn = slots[idx]
+                .take()
+                .expect("each builder must belong to exactly one group");
+            (txn, pos == 0 && new_transaction)
+        })
+        .collect()
+}
+
 async fn send_all_txs(
     client: &RpcClient,
     txs: impl IntoIterator<Item = VersionedTransaction>,
@@ -427,3 +564,109 @@ impl<'a, C> IntoIterator for BundleBuilder<'a, C> {
         self.builders.into_iter()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use solana_sdk::{instruction::AccountMeta, signature::Keypair};
+
+    use crate::transaction_builder::Config;
+
+    use super::*;
+
+    type TestBuilder<'a> = TransactionBuilder<'a, Arc<Keypair>>;
+
+    fn test_config() -> Config<Arc<Keypair>> {
+        Config::new(
+            Cluster::Localnet,
+            Arc::new(Keypair::new()),
+            CommitmentConfig::confirmed(),
+        )
+    }
+
+    fn new_market() -> Vec<Pubkey> {
+        (0..16).map(|_| Pubkey::new_unique()).collect()
+    }
+
+    /// An execution of an order of the given market, tagged by its instruction data.
+    fn execute_order<'a>(
+        cfg: &'a Config<Arc<Keypair>>,
+        market: &[Pubkey],
+        tag: u8,
+    ) -> TestBuilder<'a> {
+        let mut accounts = vec![
+            AccountMeta::new(cfg.payer(), true),
+            AccountMeta::new(Pubkey::new_unique(), false),
+        ];
+        accounts.extend(market.iter().map(|pubkey| AccountMeta::new(*pubkey, false)));
+        TransactionBuilder::new(solana_sdk::system_program::ID, cfg)
+            .accounts(accounts)
+            .args(vec![tag; 8])
+    }
+
+    fn tags(txns: &[(TestBuilder<'_>, bool)]) -> Vec<(u8, bool)> {
+        txns.iter()
+            .map(|(txn, new_transaction)| {
+                let tag = txn.instructions().last().expect("must exist").data[0];
+                (tag, *new_transaction)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn optimize_packing_reduces_transactions() -> crate::Result<()> {
+        let cfg = test_config();
+        let markets = [new_market(), new_market()];
+        // A keeper batch executing orders of two markets in the order they were created.
+        let batch = || {
+            (0..12)
+                .map(|idx| (execute_order(&cfg, &markets[idx % 2], idx as u8), false))
+                .collect::<Vec<_>>()
+        };
+
+        let mut naive = BundleBuilder::new(Cluster::Localnet);
+        naive.push_many(batch().into_iter().map(|(txn, _)| txn), false)?;
+
+        let mut optimized = BundleBuilder::new(Cluster::Localnet);
+        optimized.push_many_optimized(batch())?;
+
+        // Orders of different markets cannot fit into one transaction.
+        assert_eq!(naive.into_builders().len(), 12);
+        assert_eq!(optimized.into_builders().len(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn optimize_packing_preserves_boundaries() {
+        let cfg = test_config();
+        let [a, b] = [new_market(), new_market()];
+        let txns = vec![
+            (execute_order(&cfg, &a, 0), false),
+            (execute_order(&cfg, &b, 1), false),
+            (execute_order(&cfg, &a, 2), false),
+            (execute_order(&cfg, &b, 3).ordered(true), false),
+            (execute_order(&cfg, &a, 4), false),
+            (execute_order(&cfg, &b, 5), false),
+            (execute_order(&cfg, &a, 6), false),
+            (execute_order(&cfg, &b, 7), true),
+            (execute_order(&cfg, &a, 8), false),
+            (execute_order(&cfg, &b, 9), false),
+        ];
+        assert_eq!(
+            tags(&optimize_packing(txns)),
+            [
+                (0, false),
+                (2, false),
+                (1, false),
+                (3, false),
+                (4, false),
+                (6, false),
+                (5, false),
+                (7, true),
+                (9, false),
+                (8, false),
+            ]
+        );
+    }
+}
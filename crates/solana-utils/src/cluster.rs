@@ -1,7 +1,7 @@
 // Taken from:
 // https://github.com/coral-xyz/anchor/blob/55d74c620d30fc3c088df71895a1956336825de4/client/src/cluster.rs
 
-use std::str::FromStr;
+use std::{str::FromStr, time::Duration};
 
 use solana_client::nonblocking::rpc_client::RpcClient;
 use solana_sdk::commitment_config::CommitmentConfig;
@@ -79,6 +79,9 @@ impl std::fmt::Display for Cluster {
 }
 
 impl Cluster {
+    /// Default timeout for RPC requests.
+    pub const DEFAULT_RPC_TIMEOUT: Duration = Duration::from_secs(30);
+
     /// Get RPC url.
     pub fn url(&self) -> &str {
         match self {
@@ -107,6 +110,20 @@ impl Cluster {
     pub fn rpc(&self, commitment: CommitmentConfig) -> RpcClient {
         RpcClient::new_with_commitment(self.url().to_string(), commitment)
     }
+
+    /// Create a Solana RPC Client with the given initial timeout for confirming transactions.
+    pub fn rpc_with_confirm_timeout(
+        &self,
+        commitment: CommitmentConfig,
+        confirm_timeout: Duration,
+    ) -> RpcClient {
+        RpcClient::new_with_timeouts_and_commitment(
+            self.url().to_string(),
+            Self::DEFAULT_RPC_TIMEOUT,
+            commitment,
+            confirm_timeout,
+        )
+    }
 }
 
 #[cfg(feature = "anchor")]
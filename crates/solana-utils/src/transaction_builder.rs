@@ -1,6 +1,7 @@
 use std::{
     collections::{HashMap, HashSet},
     ops::Deref,
+    time::Duration,
 };
 
 use solana_client::{nonblocking::rpc_client::RpcClient, rpc_config::RpcSendTransactionConfig};
@@ -34,6 +35,8 @@ pub struct Config<C> {
     cluster: Cluster,
     payer: C,
     options: CommitmentConfig,
+    confirm_timeout: Option<Duration>,
+    compute_unit_price_micro_lamports: Option<u64>,
 }
 
 impl<C> Config<C> {
@@ -43,6 +46,8 @@ impl<C> Config<C> {
             cluster,
             payer,
             options,
+            confirm_timeout: None,
+            compute_unit_price_micro_lamports: None,
         }
     }
 
@@ -56,9 +61,24 @@ impl<C> Config<C> {
         &self.options
     }
 
+    /// Get the initial timeout for confirming transactions.
+    /// `None` means the default of the RPC client is used.
+    pub fn confirm_timeout(&self) -> Option<Duration> {
+        self.confirm_timeout
+    }
+
+    /// Get the default compute unit price in micro lamports.
+    /// `None` means the default of [`ComputeBudget`] is used.
+    pub fn compute_unit_price_micro_lamports(&self) -> Option<u64> {
+        self.compute_unit_price_micro_lamports
+    }
+
     /// Create a Solana RPC Client.
     pub fn rpc(&self) -> RpcClient {
-        self.cluster.rpc(self.options)
+        match self.confirm_timeout {
+            Some(timeout) => self.cluster.rpc_with_confirm_timeout(self.options, timeout),
+            None => self.cluster.rpc(self.options),
+        }
     }
 
     /// Set payer.
@@ -67,6 +87,8 @@ impl<C> Config<C> {
             cluster: self.cluster,
             payer,
             options: self.options,
+            confirm_timeout: self.confirm_timeout,
+            compute_unit_price_micro_lamports: self.compute_unit_price_micro_lamports,
         }
     }
 
@@ -81,6 +103,19 @@ impl<C> Config<C> {
         self.options = options;
         self
     }
+
+    /// Set the initial timeout for confirming transactions.
+    pub fn set_confirm_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.confirm_timeout = timeout;
+        self
+    }
+
+    /// Set the default compute unit price in micro lamports for the
+    /// [`TransactionBuilder`]s created with this config.
+    pub fn set_compute_unit_price_micro_lamports(mut self, micro_lamports: Option<u64>) -> Self {
+        self.compute_unit_price_micro_lamports = micro_lamports;
+        self
+    }
 }
 
 impl<C: Deref<Target = impl Signer>> Config<C> {
@@ -104,6 +139,7 @@ pub struct TransactionBuilder<'a, C, T = ()> {
     instruction_data: Option<Vec<u8>>,
     compute_budget: ComputeBudget,
     luts: HashMap<Pubkey, Vec<Pubkey>>,
+    ordered: bool,
 }
 
 impl<'a, C: Deref<Target = impl Signer> + Clone> TransactionBuilder<'a, C> {
@@ -118,8 +154,12 @@ impl<'a, C: Deref<Target = impl Signer> + Clone> TransactionBuilder<'a, C> {
             pre_instructions: Default::default(),
             accounts: Default::default(),
             instruction_data: None,
-            compute_budget: ComputeBudget::default(),
+            compute_budget: cfg
+                .compute_unit_price_micro_lamports()
+                .map(|price| ComputeBudget::default().with_price(price))
+                .unwrap_or_default(),
             luts: Default::default(),
+            ordered: false,
         }
     }
 
@@ -172,6 +212,9 @@ impl<'a, C: Deref<Target = impl Signer> + Clone> TransactionBuilder<'a, C> {
 
         // Merge LUTs.
         self.luts.extend(other.luts.drain());
+
+        // Merge ordering flags.
+        self.ordered |= other.ordered;
         Ok(())
     }
 
@@ -209,6 +252,7 @@ impl<'a, C: Deref<Target = impl Signer> + Clone, T> TransactionBuilder<'a, C, T>
             instruction_data: self.instruction_data,
             compute_budget: self.compute_budget,
             luts: self.luts,
+            ordered: self.ordered,
         }
     }
 
@@ -282,6 +326,20 @@ impl<'a, C: Deref<Target = impl Signer> + Clone, T> TransactionBuilder<'a, C, T>
         &mut self.compute_budget
     }
 
+    /// Mark whether the instructions have ordering dependencies on the instructions around them.
+    ///
+    /// [`optimize_packing`](crate::bundle_builder::optimize_packing) never moves other
+    /// [`TransactionBuilder`]s across an ordered one.
+    pub fn ordered(mut self, ordered: bool) -> Self {
+        self.ordered = ordered;
+        self
+    }
+
+    /// Returns whether the instructions have ordering dependencies.
+    pub fn is_ordered(&self) -> bool {
+        self.ordered
+    }
+
     fn get_compute_budget_instructions(
         &self,
         compute_unit_price_micro_lamports: Option<u64>,
@@ -351,6 +409,7 @@ impl<'a, C: Deref<Target = impl Signer> + Clone, T> TransactionBuilder<'a, C, T>
             instruction_data,
             compute_budget,
             luts,
+            ordered,
         } = self;
 
         (
@@ -365,6 +424,7 @@ impl<'a, C: Deref<Target = impl Signer> + Clone, T> TransactionBuilder<'a, C, T>
                 instruction_data,
                 compute_budget,
                 luts,
+                ordered,
             },
             previous,
         )
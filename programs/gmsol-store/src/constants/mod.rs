@@ -28,6 +28,12 @@ pub const CLAIMABLE_ACCOUNT_SEED: &[u8] = b"claimable_account";
 /// Escrow Account Seed.
 pub const ESCROW_ACCOUNT_SEED: &[u8] = b"escrow_account";
 
+/// Liquidity Mining Vault Seed.
+pub const LIQUIDITY_MINING_VAULT_SEED: &[u8] = b"liquidity_mining_vault";
+
+/// Order Callback Authority Seed.
+pub const ORDER_CALLBACK_AUTHORITY_SEED: &[u8] = b"order_callback_authority";
+
 /// Decimals of a market token.
 pub const MARKET_TOKEN_DECIMALS: u8 = 9;
 
@@ -65,8 +71,39 @@ pub const DEFAULT_ORACLE_MAX_FUTURE_TIMESTAMP_EXCESS: Amount = 0;
 /// Default max ADL prices staleness (in seconds).
 pub const DEFAULT_ADL_PRICES_MAX_STALENESS: Amount = 0;
 
+/// Default keeper assignment timeout (in seconds).
+pub const DEFAULT_KEEPER_ASSIGNMENT_TIMEOUT: Amount = 30;
+
+/// Default max claimable account lifetime (in seconds).
+pub const DEFAULT_MAX_CLAIMABLE_ACCOUNT_LIFETIME: Amount = 30 * 24 * 3600;
+
+/// Default oracle price deviation window (in seconds).
+pub const DEFAULT_ORACLE_PRICE_DEVIATION_WINDOW: Amount = 60;
+
+/// Default max compute units for an order callback.
+pub const DEFAULT_MAX_ORDER_CALLBACK_COMPUTE_UNITS: Amount = 100_000;
+
 /// Default oracle ref price deviation.
 pub const DEFAULT_ORACLE_REF_PRICE_DEVIATION: Factor = 1_000_000_000_000_000;
 
+/// Default supply tolerance factor (0.01%).
+pub const DEFAULT_SUPPLY_TOLERANCE_FACTOR: Factor = MARKET_USD_UNIT / 10_000;
+
+/// The minimum duration (in seconds) the store must have been paused for
+/// before the emergency mode can be enabled.
+#[cfg(not(feature = "test-only"))]
+pub const EMERGENCY_MODE_MIN_PAUSED_DURATION: i64 = 24 * 60 * 60;
+
+/// The minimum duration (in seconds) the store must have been paused for
+/// before the emergency mode can be enabled.
+///
+/// Shortened to [`TEST_EMERGENCY_MODE_MIN_PAUSED_DURATION`] with the `test-only` feature.
+#[cfg(feature = "test-only")]
+pub const EMERGENCY_MODE_MIN_PAUSED_DURATION: i64 = TEST_EMERGENCY_MODE_MIN_PAUSED_DURATION;
+
+/// The minimum paused duration (in seconds) used with the `test-only` feature,
+/// so that the emergency mode can be exercised by the integration tests.
+pub const TEST_EMERGENCY_MODE_MIN_PAUSED_DURATION: i64 = 5;
+
 /// Default GT vault time window size.
 pub const DEFAULT_GT_VAULT_TIME_WINDOW: u32 = 24 * 60 * 60;
@@ -117,3 +117,20 @@ impl InitSpace for DepositRemoved {
 }
 
 impl Event for DepositRemoved {}
+
+/// Dust deposit cancelled event.
+#[event]
+#[cfg_attr(feature = "debug", derive(Debug))]
+#[derive(Clone, InitSpace)]
+pub struct DustDepositCancelled {
+    /// Deposit.
+    pub deposit: Pubkey,
+    /// The total value of the deposit in USD.
+    pub value_usd: u128,
+}
+
+impl InitSpace for DustDepositCancelled {
+    const INIT_SPACE: usize = <Self as Space>::INIT_SPACE;
+}
+
+impl Event for DustDepositCancelled {}
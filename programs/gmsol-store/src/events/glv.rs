@@ -164,6 +164,25 @@ impl InitSpace for GlvPricing {
 
 impl Event for GlvPricing {}
 
+/// GLV batch shift created event.
+#[event]
+#[cfg_attr(feature = "debug", derive(Debug))]
+#[derive(Clone, InitSpace)]
+pub struct GlvBatchShiftCreated {
+    /// GLV token.
+    pub glv_token: Pubkey,
+    /// The batch nonce used to derive the nonces of the GLV shifts.
+    pub batch_nonce: u64,
+    /// The number of GLV shifts created.
+    pub count: u8,
+}
+
+impl InitSpace for GlvBatchShiftCreated {
+    const INIT_SPACE: usize = <Self as Space>::INIT_SPACE;
+}
+
+impl Event for GlvBatchShiftCreated {}
+
 /// Pricing kind.
 #[cfg_attr(feature = "debug", derive(Debug))]
 #[derive(Clone, InitSpace, AnchorSerialize, AnchorDeserialize)]
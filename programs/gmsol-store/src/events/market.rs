@@ -94,6 +94,214 @@ impl BorrowingFeesUpdated {
     }
 }
 
+/// Emergency withdrawal event.
+#[event]
+#[cfg_attr(feature = "debug", derive(Debug))]
+#[derive(InitSpace)]
+pub struct EmergencyWithdrawal {
+    /// Market token.
+    pub market_token: Pubkey,
+    /// The token withdrawn.
+    pub token: Pubkey,
+    /// Amount.
+    pub amount: u64,
+    /// Destination token account.
+    pub destination: Pubkey,
+}
+
+impl gmsol_utils::InitSpace for EmergencyWithdrawal {
+    const INIT_SPACE: usize = <Self as Space>::INIT_SPACE;
+}
+
+impl Event for EmergencyWithdrawal {}
+
+/// Stuck claimable account recovered event.
+#[event]
+#[cfg_attr(feature = "debug", derive(Debug))]
+#[derive(InitSpace)]
+pub struct StuckClaimableAccountRecovered {
+    /// The recovered claimable account.
+    pub account: Pubkey,
+    /// The balance returned to the market vault.
+    pub balance_returned: u64,
+    /// Seconds elapsed since the end of the time window of the claimable account.
+    pub dormant_secs: i64,
+}
+
+impl gmsol_utils::InitSpace for StuckClaimableAccountRecovered {
+    const INIT_SPACE: usize = <Self as Space>::INIT_SPACE;
+}
+
+impl Event for StuckClaimableAccountRecovered {}
+
+/// Batch OI caps updated event.
+#[event]
+#[cfg_attr(feature = "debug", derive(Debug))]
+#[derive(InitSpace)]
+pub struct BatchOiCapUpdated {
+    /// The number of markets updated.
+    pub markets_updated: u16,
+}
+
+impl gmsol_utils::InitSpace for BatchOiCapUpdated {
+    const INIT_SPACE: usize = <Self as Space>::INIT_SPACE;
+}
+
+impl Event for BatchOiCapUpdated {}
+
+/// Fees claimed with split event.
+#[event]
+#[cfg_attr(feature = "debug", derive(Debug))]
+#[derive(InitSpace)]
+pub struct FeesClaimedWithSplit {
+    /// Market token.
+    pub market_token: Pubkey,
+    /// The token claimed.
+    pub token: Pubkey,
+    /// Amount sent to the treasury receiver.
+    pub treasury_amount: u64,
+    /// Amount sent to the GT buyback address.
+    pub buyback_amount: u64,
+}
+
+impl gmsol_utils::InitSpace for FeesClaimedWithSplit {
+    const INIT_SPACE: usize = <Self as Space>::INIT_SPACE;
+}
+
+impl Event for FeesClaimedWithSplit {}
+
+/// Market name updated event.
+#[event]
+#[cfg_attr(feature = "debug", derive(Debug))]
+#[derive(InitSpace)]
+pub struct MarketNameUpdated {
+    /// Market token.
+    pub market_token: Pubkey,
+    /// The previous name.
+    #[max_len(64)]
+    pub old_name: String,
+    /// The new name.
+    #[max_len(64)]
+    pub new_name: String,
+}
+
+impl gmsol_utils::InitSpace for MarketNameUpdated {
+    const INIT_SPACE: usize = <Self as Space>::INIT_SPACE;
+}
+
+impl Event for MarketNameUpdated {}
+
+/// Market supply discrepancy event.
+#[event]
+#[cfg_attr(feature = "debug", derive(Debug))]
+#[derive(InitSpace)]
+pub struct MarketSupplyDiscrepancy {
+    /// Market token.
+    pub market_token: Pubkey,
+    /// The current supply of the market token.
+    pub current_supply: u64,
+    /// The supply estimated from the market balances.
+    pub expected_supply_estimate: u64,
+    /// The difference between the estimate and the current supply.
+    pub delta: i64,
+}
+
+impl gmsol_utils::InitSpace for MarketSupplyDiscrepancy {
+    const INIT_SPACE: usize = <Self as Space>::INIT_SPACE;
+}
+
+impl Event for MarketSupplyDiscrepancy {}
+
+/// Keeper tip paid event.
+#[event]
+#[cfg_attr(feature = "debug", derive(Debug))]
+#[derive(InitSpace)]
+pub struct KeeperTipPaid {
+    /// Market token.
+    pub market_token: Pubkey,
+    /// The keeper who executed the action.
+    pub keeper: Pubkey,
+    /// The token of the tip.
+    pub token: Pubkey,
+    /// Amount.
+    pub amount: u64,
+    /// The fees of the action that the tip was computed from.
+    pub fee_amount: u128,
+}
+
+impl gmsol_utils::InitSpace for KeeperTipPaid {
+    const INIT_SPACE: usize = <Self as Space>::INIT_SPACE;
+}
+
+impl Event for KeeperTipPaid {}
+
+/// Market state compressed event.
+#[event]
+#[cfg_attr(feature = "debug", derive(Debug))]
+#[derive(InitSpace)]
+pub struct MarketStateCompressed {
+    /// Market token.
+    pub market_token: Pubkey,
+    /// The number of non-zero reserved bytes that have been zeroed.
+    pub bytes_zeroed: u32,
+}
+
+impl gmsol_utils::InitSpace for MarketStateCompressed {
+    const INIT_SPACE: usize = <Self as Space>::INIT_SPACE;
+}
+
+impl Event for MarketStateCompressed {}
+
+/// Market state export event.
+#[event]
+#[cfg_attr(feature = "debug", derive(Debug))]
+pub struct MarketStateExport {
+    /// Market token.
+    pub market_token: Pubkey,
+    /// The serialized state of the market, i.e., the account data without the discriminator.
+    pub state: Vec<u8>,
+    /// The BLAKE3 hash of `state`.
+    pub state_hash: [u8; 32],
+}
+
+impl MarketStateExport {
+    const fn space(state_len: usize) -> usize {
+        32 + (4 + state_len) + 32
+    }
+}
+
+/// This is a cheaper variant of [`MarketStateExport`] event, sharing the same format
+/// for serialization.
+#[derive(BorshSerialize)]
+pub(crate) struct MarketStateExportRef<'a> {
+    /// Market token.
+    market_token: Pubkey,
+    /// The serialized state of the market.
+    state: &'a [u8],
+    /// The BLAKE3 hash of `state`.
+    state_hash: [u8; 32],
+}
+
+impl<'a> MarketStateExportRef<'a> {
+    pub(crate) fn new(market_token: Pubkey, state: &'a [u8], state_hash: [u8; 32]) -> Self {
+        Self {
+            market_token,
+            state,
+            state_hash,
+        }
+    }
+
+    pub(crate) fn space(&self) -> usize {
+        MarketStateExport::space(self.state.len())
+    }
+}
+
+impl anchor_lang::Discriminator for MarketStateExportRef<'_> {
+    const DISCRIMINATOR: [u8; 8] = MarketStateExport::DISCRIMINATOR;
+}
+
+impl Event for MarketStateExportRef<'_> {}
+
 /// A pool for market.
 #[cfg_attr(feature = "debug", derive(derive_more::Debug))]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
@@ -25,14 +25,22 @@ mod market;
 /// GT events.
 mod gt;
 
+/// User events.
+mod user;
+
+/// Oracle events.
+mod oracle;
+
 pub use deposit::*;
 pub use glv::*;
 pub use gt::*;
 pub use market::*;
+pub use oracle::*;
 pub use order::*;
 pub use shift::*;
 pub use swap::*;
 pub use trade::*;
+pub use user::*;
 pub use withdrawal::*;
 
 use anchor_lang::prelude::*;
@@ -0,0 +1,40 @@
+use anchor_lang::prelude::*;
+
+use gmsol_utils::InitSpace;
+
+use super::Event;
+
+/// Oracle price band breached event.
+///
+/// Since the transaction is rejected when the price band is breached,
+/// this event is emitted through program logs instead of CPI.
+#[event]
+#[cfg_attr(feature = "debug", derive(Debug))]
+#[derive(InitSpace)]
+pub struct OraclePriceBandBreached {
+    /// Token.
+    pub token: Pubkey,
+    /// The unit price that breached the band.
+    pub price: u128,
+    /// The lower bound of the band in unit price.
+    pub min_band: u128,
+    /// The upper bound of the band in unit price.
+    pub max_band: u128,
+}
+
+/// Oracle rotated event.
+#[event]
+#[cfg_attr(feature = "debug", derive(Debug))]
+#[derive(Clone, InitSpace)]
+pub struct OracleRotated {
+    /// The decommissioned oracle.
+    pub old_oracle: Pubkey,
+    /// The oracle replacing the old one.
+    pub new_oracle: Pubkey,
+}
+
+impl InitSpace for OracleRotated {
+    const INIT_SPACE: usize = <Self as Space>::INIT_SPACE;
+}
+
+impl Event for OracleRotated {}
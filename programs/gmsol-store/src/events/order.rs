@@ -6,7 +6,10 @@ use gmsol_model::action::{
 };
 use gmsol_utils::InitSpace;
 
-use crate::states::{common::action::ActionState, order::OrderKind};
+use crate::states::{
+    common::action::ActionState,
+    order::{OrderKind, TransferOut},
+};
 
 use super::Event;
 
@@ -159,3 +162,46 @@ impl InitSpace for OrderRemoved {
 }
 
 impl Event for OrderRemoved {}
+
+/// Order callback pending event.
+///
+/// Emitted after an order with a callback is executed. The callback can then be
+/// invoked with the `transfer_out` by the `invoke_order_callback` instruction.
+#[event]
+#[cfg_attr(feature = "debug", derive(Debug))]
+#[derive(Clone, InitSpace)]
+pub struct OrderCallbackPending {
+    /// Store.
+    pub store: Pubkey,
+    /// Order.
+    pub order: Pubkey,
+    /// Kind.
+    pub kind: OrderKind,
+    /// The callback program.
+    pub program: Pubkey,
+    /// The account passed to the callback program.
+    pub account: Pubkey,
+    /// The execution result to invoke the callback with.
+    pub transfer_out: TransferOut,
+}
+
+impl InitSpace for OrderCallbackPending {
+    const INIT_SPACE: usize = <Self as Space>::INIT_SPACE;
+}
+
+impl Event for OrderCallbackPending {}
+
+/// Batch positions prepared event.
+#[event]
+#[cfg_attr(feature = "debug", derive(Debug))]
+#[derive(Clone, InitSpace)]
+pub struct BatchPositionsPrepared {
+    /// The number of positions prepared.
+    pub count: u8,
+}
+
+impl InitSpace for BatchPositionsPrepared {
+    const INIT_SPACE: usize = <Self as Space>::INIT_SPACE;
+}
+
+impl Event for BatchPositionsPrepared {}
@@ -206,7 +206,7 @@ impl TradeEvent {
 
     /// Create position from this event.
     pub fn to_position(&self, meta: &impl crate::states::HasMarketMeta) -> Position {
-        use crate::states::position::PositionKind;
+        use crate::states::position::{MarginMode, PositionKind};
 
         let mut position = Position::default();
 
@@ -225,6 +225,8 @@ impl TradeEvent {
         position
             .try_init(
                 kind,
+                // Note: the margin mode is not recorded in the event.
+                MarginMode::default(),
                 // Note: there's no need to provide a correct bump here for now.
                 0,
                 self.store,
@@ -0,0 +1,35 @@
+use anchor_lang::prelude::*;
+
+use super::Event;
+
+/// User account frozen event.
+#[event]
+#[cfg_attr(feature = "debug", derive(Debug))]
+#[derive(InitSpace)]
+pub struct UserAccountFrozen {
+    /// The owner of the frozen user account.
+    pub user_address: Pubkey,
+    /// The hash of the freeze reason.
+    pub reason_hash: [u8; 32],
+}
+
+impl gmsol_utils::InitSpace for UserAccountFrozen {
+    const INIT_SPACE: usize = <Self as Space>::INIT_SPACE;
+}
+
+impl Event for UserAccountFrozen {}
+
+/// User account unfrozen event.
+#[event]
+#[cfg_attr(feature = "debug", derive(Debug))]
+#[derive(InitSpace)]
+pub struct UserAccountUnfrozen {
+    /// The owner of the unfrozen user account.
+    pub user_address: Pubkey,
+}
+
+impl gmsol_utils::InitSpace for UserAccountUnfrozen {
+    const INIT_SPACE: usize = <Self as Space>::INIT_SPACE;
+}
+
+impl Event for UserAccountUnfrozen {}
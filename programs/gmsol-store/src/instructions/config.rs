@@ -1,7 +1,10 @@
-use crate::states::{Amount, Factor};
+use crate::states::{
+    config_snapshot::{ConfigDiff, ConfigSnapshot},
+    Amount, Factor, FactorKey, GlobalConfigSnapshot, InitSpace, Seed,
+};
 use anchor_lang::prelude::*;
 
-use crate::{states::Store, utils::internal};
+use crate::{states::Store, utils::internal, CoreError};
 
 /// The accounts definition of instructions for updating configs.
 #[derive(Accounts)]
@@ -48,6 +51,18 @@ pub(crate) fn unchecked_insert_factor(
     Ok(())
 }
 
+/// CHECK: only CONFIG_KEEPER is allowed to invoke.
+pub(crate) fn unchecked_set_deposit_dust_threshold(
+    ctx: Context<InsertConfig>,
+    min_long_usd: Factor,
+    min_short_usd: Factor,
+) -> Result<()> {
+    let mut store = ctx.accounts.store.load_mut()?;
+    *store.get_factor_mut(&FactorKey::MinDepositLongUsd.to_string())? = min_long_usd;
+    *store.get_factor_mut(&FactorKey::MinDepositShortUsd.to_string())? = min_short_usd;
+    Ok(())
+}
+
 /// CHECK: only CONFIG_KEEPER is allowed to invoke.
 pub(crate) fn unchecked_insert_address(
     ctx: Context<InsertConfig>,
@@ -57,3 +72,136 @@ pub(crate) fn unchecked_insert_address(
     *ctx.accounts.store.load_mut()?.get_address_mut(key)? = address;
     Ok(())
 }
+
+/// CHECK: only ADMIN is allowed to invoke.
+pub(crate) fn unchecked_set_trusted_chainlink_verifier(
+    ctx: Context<InsertConfig>,
+    index: u8,
+    verifier: &Pubkey,
+) -> Result<()> {
+    ctx.accounts
+        .store
+        .load_mut()?
+        .set_trusted_chainlink_verifier(index, verifier)?;
+    msg!(
+        "[Config] trusted chainlink verifier {} is set to {}",
+        index,
+        verifier
+    );
+    Ok(())
+}
+
+/// The accounts definition for [`take_config_snapshot`](crate::gmsol_store::take_config_snapshot).
+#[derive(Accounts)]
+pub struct TakeConfigSnapshot<'info> {
+    /// Authority.
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    /// Store.
+    #[account(mut)]
+    pub store: AccountLoader<'info, Store>,
+    /// The snapshot account to write, which is the ring buffer slot of the next snapshot.
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + GlobalConfigSnapshot::INIT_SPACE,
+        seeds = [
+            GlobalConfigSnapshot::SEED,
+            store.key().as_ref(),
+            &GlobalConfigSnapshot::slot_seed(store.load()?.next_config_snapshot_index()),
+        ],
+        bump,
+    )]
+    pub snapshot: AccountLoader<'info, GlobalConfigSnapshot>,
+    /// The system program.
+    pub system_program: Program<'info, System>,
+}
+
+/// Take a snapshot of the global config.
+/// CHECK: only CONFIG_KEEPER is allowed to invoke.
+pub(crate) fn unchecked_take_config_snapshot(ctx: Context<TakeConfigSnapshot>) -> Result<()> {
+    let store = ctx.accounts.store.key();
+    match ctx.accounts.snapshot.load_init() {
+        Ok(mut snapshot) => {
+            snapshot.init(ctx.bumps.snapshot, &store);
+        }
+        Err(Error::AnchorError(err)) => {
+            if err.error_code_number != ErrorCode::AccountDiscriminatorAlreadySet as u32 {
+                return Err(Error::AnchorError(err));
+            }
+        }
+        Err(err) => {
+            return Err(err);
+        }
+    }
+    ctx.accounts.snapshot.exit(&crate::ID)?;
+
+    let mut snapshot = ctx.accounts.snapshot.load_mut()?;
+    require_keys_eq!(snapshot.store, store, CoreError::StoreMismatched);
+
+    let mut store = ctx.accounts.store.load_mut()?;
+    let index = store.next_config_snapshot()?;
+    snapshot.capture(index, &store, &Clock::get()?);
+    msg!(
+        "[Config] took config snapshot {} at slot {}",
+        index,
+        snapshot.snapshot_slot
+    );
+    Ok(())
+}
+
+impl<'info> internal::Authentication<'info> for TakeConfigSnapshot<'info> {
+    fn authority(&self) -> &Signer<'info> {
+        &self.authority
+    }
+
+    fn store(&self) -> &AccountLoader<'info, Store> {
+        &self.store
+    }
+}
+
+/// The accounts definition for [`get_config_snapshot`](crate::gmsol_store::get_config_snapshot).
+#[derive(Accounts)]
+pub struct ReadConfigSnapshot<'info> {
+    /// Store.
+    pub store: AccountLoader<'info, Store>,
+    /// The snapshot account.
+    #[account(has_one = store)]
+    pub snapshot: AccountLoader<'info, GlobalConfigSnapshot>,
+}
+
+/// Get the config snapshot with the given index.
+pub(crate) fn get_config_snapshot(
+    ctx: Context<ReadConfigSnapshot>,
+    index: u64,
+) -> Result<ConfigSnapshot> {
+    let snapshot = ctx.accounts.snapshot.load()?;
+    require_eq!(snapshot.index, index, CoreError::ConfigSnapshotNotFound);
+    Ok(snapshot.to_config_snapshot())
+}
+
+/// The accounts definition for [`compare_config_snapshots`](crate::gmsol_store::compare_config_snapshots).
+#[derive(Accounts)]
+pub struct CompareConfigSnapshots<'info> {
+    /// Store.
+    pub store: AccountLoader<'info, Store>,
+    /// The snapshot account of `index_a`.
+    #[account(has_one = store)]
+    pub snapshot_a: AccountLoader<'info, GlobalConfigSnapshot>,
+    /// The snapshot account of `index_b`.
+    #[account(has_one = store)]
+    pub snapshot_b: AccountLoader<'info, GlobalConfigSnapshot>,
+}
+
+/// Get the config entries that changed from snapshot `index_a` to snapshot `index_b`.
+pub(crate) fn compare_config_snapshots(
+    ctx: Context<CompareConfigSnapshots>,
+    index_a: u64,
+    index_b: u64,
+) -> Result<Vec<ConfigDiff>> {
+    let snapshot_a = ctx.accounts.snapshot_a.load()?;
+    require_eq!(snapshot_a.index, index_a, CoreError::ConfigSnapshotNotFound);
+    let snapshot_b = ctx.accounts.snapshot_b.load()?;
+    require_eq!(snapshot_b.index, index_b, CoreError::ConfigSnapshotNotFound);
+    Ok(snapshot_a.diff(&snapshot_b))
+}
@@ -7,11 +7,13 @@ use gmsol_utils::InitSpace;
 
 use crate::{
     events::{DepositCreated, EventEmitter},
+    instructions::user::initialize_user_if_needed,
     ops::deposit::{CreateDepositOperation, CreateDepositParams},
     states::{
         common::action::{Action, ActionExt},
         feature::{ActionDisabledFlag, DomainDisabledFlag},
-        Deposit, Market, NonceBytes, RoleKey, Seed, Store, StoreWalletSigner,
+        user::{UserActionKind, UserHeader},
+        Deposit, Market, NonceBytes, PendingActionKind, RoleKey, Seed, Store, StoreWalletSigner,
     },
     utils::{
         internal,
@@ -43,6 +45,15 @@ pub struct CreateDeposit<'info> {
     /// Market.
     #[account(mut, has_one = store)]
     pub market: AccountLoader<'info, Market>,
+    /// User Account. It will be initialized if needed.
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = 8 + UserHeader::space(0),
+        seeds = [UserHeader::SEED, store.key().as_ref(), owner.key().as_ref()],
+        bump,
+    )]
+    pub user: AccountLoader<'info, UserHeader>,
     /// The deposit to be created.
     #[account(
         init,
@@ -132,7 +143,17 @@ impl<'info> internal::Create<'info, Deposit> for CreateDeposit<'info> {
         bumps: &Self::Bumps,
         remaining_accounts: &'info [AccountInfo<'info>],
     ) -> Result<()> {
+        initialize_user_if_needed(&self.user, &self.store.key(), self.owner.key, bumps.user)?;
+        self.user.load()?.validate_not_frozen()?;
+        let clock = Clock::get()?;
+        let max_trades_per_block = self.store.load()?.max_trades_per_block_per_user();
+        self.user
+            .load_mut()?
+            .record_trade_in_block(clock.slot, max_trades_per_block)?;
         self.transfer_tokens(params)?;
+        self.user
+            .load_mut()?
+            .record_action(UserActionKind::Deposit, clock.unix_timestamp);
         CreateDepositOperation::builder()
             .deposit(self.deposit.clone())
             .market(self.market.clone())
@@ -148,6 +169,15 @@ impl<'info> internal::Create<'info, Deposit> for CreateDeposit<'info> {
             .swap_paths(remaining_accounts)
             .build()
             .execute()?;
+        let max_pending = self
+            .store
+            .load()?
+            .max_pending_actions(PendingActionKind::Deposit);
+        self.market.load_mut()?.indexer_mut().occupy_pending_slot(
+            PendingActionKind::Deposit,
+            max_pending,
+            &mut self.deposit.load_mut()?.header,
+        )?;
         emit!(DepositCreated::new(self.store.key(), self.deposit.key())?);
         Ok(())
     }
@@ -349,6 +379,14 @@ impl<'info> internal::Close<'info, Deposit> for CloseDeposit<'info> {
         bumps.store_wallet
     }
 
+    fn release_pending_slot(&self, remaining_accounts: &'info [AccountInfo<'info>]) -> Result<()> {
+        internal::release_pending_slot(
+            PendingActionKind::Deposit,
+            &mut self.deposit.load_mut()?.header,
+            remaining_accounts,
+        )
+    }
+
     fn validate(&self) -> Result<()> {
         let deposit = self.deposit.load()?;
         if deposit.header.action_state()?.is_pending() {
@@ -7,12 +7,16 @@ use crate::{
     ops::{
         deposit::ExecuteDepositOperation,
         execution_fee::PayExecutionFeeOperation,
-        market::{MarketTransferInOperation, MarketTransferOutOperation},
+        market::{
+            ClaimableFeeSnapshot, MarketTransferInOperation, MarketTransferOutOperation,
+            PayKeeperTipOperation,
+        },
     },
     states::{
         common::action::{ActionExt, ActionSigner},
         feature::{ActionDisabledFlag, DomainDisabledFlag},
-        Chainlink, Deposit, Market, Oracle, Seed, Store, TokenMapHeader, TokenMapLoader,
+        Chainlink, Deposit, Market, Oracle, PendingActionKind, Seed, Store, TokenMapHeader,
+        TokenMapLoader,
     },
     utils::internal,
     CoreError,
@@ -32,10 +36,12 @@ pub struct ExecuteDeposit<'info> {
     /// Authority.
     pub authority: Signer<'info>,
     /// Store.
-    #[account(has_one = token_map)]
     pub store: AccountLoader<'info, Store>,
     /// Token Map.
-    #[account(has_one = store)]
+    #[account(
+        has_one = store,
+        constraint = market.load()?.resolve_token_map(&*store.load()?) == Some(&token_map.key()) @ CoreError::TokenMapMismatched,
+    )]
     pub token_map: AccountLoader<'info, TokenMapHeader>,
     /// Oracle buffer to use.
     #[account(mut, has_one = store)]
@@ -121,6 +127,12 @@ pub struct ExecuteDeposit<'info> {
     pub system_program: Program<'info, System>,
     /// Chainlink Program.
     pub chainlink_program: Option<Program<'info, Chainlink>>,
+    /// The token account for receiving the keeper tip in long token.
+    #[account(mut)]
+    pub keeper_long_token_account: Option<Box<Account<'info, TokenAccount>>>,
+    /// The token account for receiving the keeper tip in short token.
+    #[account(mut)]
+    pub keeper_short_token_account: Option<Box<Account<'info, TokenAccount>>>,
 }
 
 /// CHECK: only ORDER_KEEPER can invoke this instruction.
@@ -146,15 +158,25 @@ pub(crate) fn unchecked_execute_deposit<'info>(
 
     accounts.transfer_tokens_in(&signer, remaining_accounts, &event_emitter)?;
 
+    let claimable_fees = ClaimableFeeSnapshot::take(&accounts.market)?;
     let executed =
         accounts.perform_execution(remaining_accounts, throw_on_execution_error, &event_emitter)?;
 
     if executed {
         accounts.deposit.load_mut()?.header.completed()?;
+        accounts.pay_keeper_tip(claimable_fees, &event_emitter)?;
     } else {
         accounts.deposit.load_mut()?.header.cancelled()?;
         accounts.transfer_tokens_out(remaining_accounts, &event_emitter)?;
     }
+    accounts
+        .market
+        .load_mut()?
+        .indexer_mut()
+        .release_pending_slot(
+            PendingActionKind::Deposit,
+            &mut accounts.deposit.load_mut()?.header,
+        );
 
     // It must be placed at the end to be executed correctly.
     accounts.pay_execution_fee(execution_fee)?;
@@ -173,6 +195,40 @@ impl<'info> internal::Authentication<'info> for ExecuteDeposit<'info> {
 }
 
 impl<'info> ExecuteDeposit<'info> {
+    #[inline(never)]
+    fn pay_keeper_tip(
+        &self,
+        claimable_fees: ClaimableFeeSnapshot,
+        event_emitter: &EventEmitter<'_, 'info>,
+    ) -> Result<()> {
+        let vaults = [
+            (
+                self.initial_long_token.as_deref(),
+                self.initial_long_token_vault.as_deref(),
+            ),
+            (
+                self.initial_short_token.as_deref(),
+                self.initial_short_token_vault.as_deref(),
+            ),
+        ]
+        .into_iter()
+        .filter_map(|(token, vault)| token.zip(vault))
+        .collect();
+        PayKeeperTipOperation::builder()
+            .store(&self.store)
+            .market(&self.market)
+            .keeper(self.authority.key)
+            .snapshot(claimable_fees)
+            .long_token_receiver(self.keeper_long_token_account.as_deref())
+            .short_token_receiver(self.keeper_short_token_account.as_deref())
+            .vaults(vaults)
+            .token_program(self.token_program.to_account_info())
+            .event_emitter(*event_emitter)
+            .build()
+            .execute()?;
+        Ok(())
+    }
+
     #[inline(never)]
     fn pay_execution_fee(&self, execution_fee: u64) -> Result<()> {
         let execution_lamports = self.deposit.load()?.execution_lamports(execution_fee);
@@ -340,8 +396,9 @@ impl<'info> ExecuteDeposit<'info> {
             .throw_on_execution_error(throw_on_execution_error)
             .event_emitter(*event_emitter);
 
-        let executed = self.oracle.load_mut()?.with_prices(
+        let executed = self.oracle.load_mut()?.with_market_prices(
             &self.store,
+            &self.market,
             &self.token_map,
             &feeds.tokens,
             remaining_accounts,
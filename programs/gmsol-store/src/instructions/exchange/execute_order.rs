@@ -8,19 +8,24 @@ use crate::{
     events::{EventEmitter, TradeData, TradeEventRef},
     ops::{
         execution_fee::PayExecutionFeeOperation,
-        market::{MarketTransferInOperation, MarketTransferOutOperation},
+        market::{
+            ClaimableFeeSnapshot, MarketTransferInOperation, MarketTransferOutOperation,
+            PayKeeperTipOperation,
+        },
         order::{
-            ExecuteOrderOperation, ProcessTransferOutOperation, RemovePosition,
-            ShouldSendTradeEvent,
+            ExecuteOrderOperation, InvokeOrderCallbackOperation, PrepareOrderCallbackOperation,
+            ProcessTransferOutOperation, RemovePosition, ShouldSendTradeEvent,
         },
     },
     states::{
         common::action::{ActionExt, ActionSigner},
         feature::ActionDisabledFlag,
+        keeper::KeeperDeadlineExtension,
         order::{Order, TransferOut},
         position::Position,
         user::UserHeader,
-        Chainlink, Market, Oracle, Seed, Store, TokenMapHeader, TokenMapLoader,
+        AmountKey, Chainlink, Market, MarketAnalytics, Oracle, PendingActionKind, Seed, Store,
+        TokenMapHeader, TokenMapLoader, VirtualPool,
     },
     utils::{internal, pubkey::DEFAULT_PUBKEY},
     CoreError,
@@ -106,10 +111,23 @@ pub(crate) fn check_delegation(account: &TokenAccount, target: Pubkey) -> Result
     Ok(is_matched)
 }
 
-pub(crate) fn validated_recent_timestamp(config: &Store, timestamp: i64) -> Result<i64> {
-    let recent_time_window = config.amount.recent_time_window;
-    let expiration_time = timestamp.saturating_add_unsigned(recent_time_window);
+pub(crate) fn validated_recent_timestamp(
+    config: &Store,
+    timestamp: i64,
+    deadline_extension: Option<&AccountLoader<KeeperDeadlineExtension>>,
+) -> Result<i64> {
     let clock = Clock::get()?;
+    let extension_secs = match deadline_extension {
+        Some(extension) => extension
+            .load()?
+            .effective_extension_secs(clock.unix_timestamp),
+        None => 0,
+    };
+    let recent_time_window = config
+        .amount
+        .recent_time_window
+        .saturating_add(u64::from(extension_secs));
+    let expiration_time = timestamp.saturating_add_unsigned(recent_time_window);
     if timestamp <= clock.unix_timestamp && clock.unix_timestamp <= expiration_time {
         Ok(timestamp)
     } else {
@@ -117,6 +135,48 @@ pub(crate) fn validated_recent_timestamp(config: &Store, timestamp: i64) -> Resu
     }
 }
 
+/// Split the trailing [`MarketAnalytics`] accounts from the remaining accounts.
+fn split_market_analytics<'info>(
+    remaining_accounts: &'info [AccountInfo<'info>],
+) -> (&'info [AccountInfo<'info>], &'info [AccountInfo<'info>]) {
+    let len = remaining_accounts
+        .iter()
+        .rev()
+        .take_while(|info| MarketAnalytics::is_market_analytics(info))
+        .count();
+    remaining_accounts.split_at(remaining_accounts.len() - len)
+}
+
+/// Record the executed trade in the given [`MarketAnalytics`] accounts.
+///
+/// The execution price is used as the price and the size delta as the volume.
+/// Accounts of other periods are skipped.
+fn record_trade_in_analytics<'info>(
+    accounts: &'info [AccountInfo<'info>],
+    event: &TradeData,
+) -> Result<()> {
+    let volume_usd = event.after.size_in_usd.abs_diff(event.before.size_in_usd);
+    for info in accounts {
+        let loader = AccountLoader::<MarketAnalytics>::try_from(info)?;
+        let mut analytics = loader.load_mut()?;
+        require_keys_eq!(analytics.store, event.store, CoreError::StoreMismatched);
+        require_keys_eq!(
+            analytics.market_token,
+            event.market_token,
+            CoreError::MarketTokenMintMismatched
+        );
+        if !analytics.contains(event.ts) {
+            msg!(
+                "[Analytics] skipped {} since the trade is not in its period",
+                info.key
+            );
+            continue;
+        }
+        analytics.record_trade(event.execution_price, volume_usd)?;
+    }
+    Ok(())
+}
+
 /// The accounts definition for [`execute_increase_or_swap_order`](crate::gmsol_store::execute_increase_or_swap_order) instruction.
 ///
 /// Remaining accounts expected by this instruction:
@@ -124,6 +184,11 @@ pub(crate) fn validated_recent_timestamp(config: &Store, timestamp: i64) -> Resu
 ///     swap params.
 ///   - M..M+N. `[writable]` N market accounts, where N represents the total number of unique
 ///     markets excluding the current market in the swap params.
+///   - M+N..M+N+P. `[]` P position accounts, required only if the position is in cross margin
+///     mode, where P is the number of the other positions of the owner in the market, in the
+///     order of [`Position::find_cross_margin_sibling_addresses`].
+///   - M+N+P..M+N+P+K. `[writable]` K optional [`MarketAnalytics`] accounts of the current market
+///     to record the trade in.
 #[event_cpi]
 #[derive(Accounts)]
 #[instruction(recent_timestamp: i64)]
@@ -131,10 +196,13 @@ pub struct ExecuteIncreaseOrSwapOrder<'info> {
     /// Authority.
     pub authority: Signer<'info>,
     /// Store.
-    #[account(mut, has_one = token_map)]
+    #[account(mut)]
     pub store: AccountLoader<'info, Store>,
     /// Token Map.
-    #[account(has_one = store)]
+    #[account(
+        has_one = store,
+        constraint = market.load()?.resolve_token_map(&*store.load()?) == Some(&token_map.key()) @ CoreError::TokenMapMismatched,
+    )]
     pub token_map: AccountLoader<'info, TokenMapHeader>,
     /// Oracle buffer to use.
     #[account(mut, has_one = store)]
@@ -281,6 +349,20 @@ pub struct ExecuteIncreaseOrSwapOrder<'info> {
     pub system_program: Program<'info, System>,
     /// Chainlink Program.
     pub chainlink_program: Option<Program<'info, Chainlink>>,
+    /// The virtual pool of the market, required if the market has enabled the virtual pool.
+    #[account(
+        mut,
+        has_one = store,
+        seeds = [VirtualPool::SEED, store.key().as_ref(), virtual_pool.load()?.key().as_ref()],
+        bump = virtual_pool.load()?.bump,
+    )]
+    pub virtual_pool: Option<AccountLoader<'info, VirtualPool>>,
+    /// The token account for receiving the keeper tip in long token.
+    #[account(mut)]
+    pub keeper_long_token_account: Option<Box<Account<'info, TokenAccount>>>,
+    /// The token account for receiving the keeper tip in short token.
+    #[account(mut)]
+    pub keeper_short_token_account: Option<Box<Account<'info, TokenAccount>>>,
 }
 
 #[inline(never)]
@@ -306,7 +388,18 @@ pub(crate) fn unchecked_execute_increase_or_swap_order<'info>(
         .load()?
         .validate_feature_enabled(kind.try_into()?, ActionDisabledFlag::Execute)?;
 
-    let remaining_accounts = ctx.remaining_accounts;
+    // Validate the executing keeper.
+    let keeper_assignment_timeout = *accounts
+        .store
+        .load()?
+        .get_amount_by_key(AmountKey::KeeperAssignmentTimeout);
+    accounts.order.load()?.validate_executing_keeper(
+        accounts.authority.key,
+        keeper_assignment_timeout,
+        Clock::get()?.unix_timestamp,
+    )?;
+
+    let (remaining_accounts, analytics) = split_market_analytics(ctx.remaining_accounts);
     let signer = accounts.order.load()?.signer();
 
     let event_authority = accounts.event_authority.clone();
@@ -314,6 +407,7 @@ pub(crate) fn unchecked_execute_increase_or_swap_order<'info>(
 
     accounts.transfer_tokens_in(&signer, remaining_accounts, &event_emitter)?;
 
+    let claimable_fees = ClaimableFeeSnapshot::take(&accounts.market)?;
     let (is_position_removed, transfer_out, should_send_trade_event) =
         accounts.perform_execution(remaining_accounts, throw_on_execution_error, &event_emitter)?;
 
@@ -324,6 +418,19 @@ pub(crate) fn unchecked_execute_increase_or_swap_order<'info>(
         accounts.order.load_mut()?.header.cancelled()?;
         accounts.transfer_tokens_out(remaining_accounts, &event_emitter)?;
     }
+    accounts
+        .market
+        .load_mut()?
+        .indexer_mut()
+        .release_pending_slot(
+            PendingActionKind::Order,
+            &mut accounts.order.load_mut()?.header,
+        );
+
+    if transfer_out.executed() {
+        accounts.pay_keeper_tip(claimable_fees, &event_emitter)?;
+        accounts.prepare_callback(&transfer_out, &event_emitter)?;
+    }
 
     if should_send_trade_event {
         let event_loader = accounts.event.clone();
@@ -331,6 +438,7 @@ pub(crate) fn unchecked_execute_increase_or_swap_order<'info>(
             .as_ref()
             .ok_or_else(|| error!(CoreError::EventBufferNotProvided))?
             .load()?;
+        record_trade_in_analytics(analytics, &event)?;
         let event = TradeEventRef::from(&*event);
         event_emitter.emit_cpi(&event)?;
     }
@@ -454,10 +562,12 @@ impl<'info> ExecuteIncreaseOrSwapOrder<'info> {
             .event(self.event.as_ref())
             .throw_on_execution_error(throw_on_execution_error)
             .executor(self.authority.to_account_info())
+            .virtual_pool(self.virtual_pool.as_ref())
             .event_emitter(*event_emitter);
 
-        self.oracle.load_mut()?.with_prices(
+        self.oracle.load_mut()?.with_market_prices(
             &self.store,
+            &self.market,
             &self.token_map,
             &feeds.tokens,
             remaining_accounts,
@@ -518,6 +628,52 @@ impl<'info> ExecuteIncreaseOrSwapOrder<'info> {
         Ok(())
     }
 
+    #[inline(never)]
+    fn prepare_callback(
+        &self,
+        transfer_out: &TransferOut,
+        event_emitter: &EventEmitter<'_, 'info>,
+    ) -> Result<()> {
+        PrepareOrderCallbackOperation::builder()
+            .store(&self.store)
+            .order(&self.order)
+            .transfer_out(transfer_out)
+            .event_emitter(*event_emitter)
+            .build()
+            .execute()
+    }
+
+    #[inline(never)]
+    fn pay_keeper_tip(
+        &self,
+        claimable_fees: ClaimableFeeSnapshot,
+        event_emitter: &EventEmitter<'_, 'info>,
+    ) -> Result<()> {
+        let vaults = [
+            (self.long_token.as_deref(), self.long_token_vault.as_deref()),
+            (
+                self.short_token.as_deref(),
+                self.short_token_vault.as_deref(),
+            ),
+        ]
+        .into_iter()
+        .filter_map(|(token, vault)| token.zip(vault))
+        .collect();
+        PayKeeperTipOperation::builder()
+            .store(&self.store)
+            .market(&self.market)
+            .keeper(self.authority.key)
+            .snapshot(claimable_fees)
+            .long_token_receiver(self.keeper_long_token_account.as_deref())
+            .short_token_receiver(self.keeper_short_token_account.as_deref())
+            .vaults(vaults)
+            .token_program(self.token_program.to_account_info())
+            .event_emitter(*event_emitter)
+            .build()
+            .execute()?;
+        Ok(())
+    }
+
     #[inline(never)]
     fn pay_execution_fee(&self, execution_fee: u64) -> Result<()> {
         let execution_lamports = self.order.load()?.execution_lamports(execution_fee);
@@ -539,6 +695,11 @@ impl<'info> ExecuteIncreaseOrSwapOrder<'info> {
 ///     swap params.
 ///   - M..M+N. `[writable]` N market accounts, where N represents the total number of unique
 ///     markets excluding the current market in the swap params.
+///   - M+N..M+N+P. `[]` P position accounts, required only if the position is in cross margin
+///     mode, where P is the number of the other positions of the owner in the market, in the
+///     order of [`Position::find_cross_margin_sibling_addresses`].
+///   - M+N+P..M+N+P+K. `[writable]` K optional [`MarketAnalytics`] accounts of the current market
+///     to record the trade in.
 #[event_cpi]
 #[derive(Accounts)]
 #[instruction(recent_timestamp: i64)]
@@ -546,10 +707,13 @@ pub struct ExecuteDecreaseOrder<'info> {
     /// Authority.
     pub authority: Signer<'info>,
     /// Store.
-    #[account(mut, has_one = token_map)]
+    #[account(mut)]
     pub store: AccountLoader<'info, Store>,
     /// Token Map.
-    #[account(has_one = store)]
+    #[account(
+        has_one = store,
+        constraint = market.load()?.resolve_token_map(&*store.load()?) == Some(&token_map.key()) @ CoreError::TokenMapMismatched,
+    )]
     pub token_map: AccountLoader<'info, TokenMapHeader>,
     /// Oracle buffer to use.
     #[account(mut, has_one = store)]
@@ -677,7 +841,7 @@ pub struct ExecuteDecreaseOrder<'info> {
             store.key().as_ref(),
             market.load()?.meta().long_token_mint.as_ref(),
             order.load()?.header.owner.as_ref(),
-            &store.load()?.claimable_time_key(validated_recent_timestamp(store.load()?.deref(), recent_timestamp)?)?,
+            &store.load()?.claimable_time_key(validated_recent_timestamp(store.load()?.deref(), recent_timestamp, deadline_extension.as_ref())?)?,
         ],
         bump,
     )]
@@ -692,7 +856,7 @@ pub struct ExecuteDecreaseOrder<'info> {
             store.key().as_ref(),
             market.load()?.meta().short_token_mint.as_ref(),
             order.load()?.header.owner.as_ref(),
-            &store.load()?.claimable_time_key(validated_recent_timestamp(store.load()?.deref(), recent_timestamp)?)?,
+            &store.load()?.claimable_time_key(validated_recent_timestamp(store.load()?.deref(), recent_timestamp, deadline_extension.as_ref())?)?,
         ],
         bump,
     )]
@@ -707,7 +871,7 @@ pub struct ExecuteDecreaseOrder<'info> {
             store.key().as_ref(),
             get_pnl_token(&Some(position.clone()), market.load()?.deref())?.as_ref(),
             store.load()?.address.holding.as_ref(),
-            &store.load()?.claimable_time_key(validated_recent_timestamp(store.load()?.deref(), recent_timestamp)?)?,
+            &store.load()?.claimable_time_key(validated_recent_timestamp(store.load()?.deref(), recent_timestamp, deadline_extension.as_ref())?)?,
         ],
         bump,
     )]
@@ -718,6 +882,27 @@ pub struct ExecuteDecreaseOrder<'info> {
     pub system_program: Program<'info, System>,
     /// Chainlink Program.
     pub chainlink_program: Option<Program<'info, Chainlink>>,
+    /// The keeper deadline extension of the store, if any.
+    #[account(
+        has_one = store,
+        seeds = [KeeperDeadlineExtension::SEED, store.key().as_ref()],
+        bump = deadline_extension.load()?.bump,
+    )]
+    pub deadline_extension: Option<AccountLoader<'info, KeeperDeadlineExtension>>,
+    /// The virtual pool of the market, required if the market has enabled the virtual pool.
+    #[account(
+        mut,
+        has_one = store,
+        seeds = [VirtualPool::SEED, store.key().as_ref(), virtual_pool.load()?.key().as_ref()],
+        bump = virtual_pool.load()?.bump,
+    )]
+    pub virtual_pool: Option<AccountLoader<'info, VirtualPool>>,
+    /// The token account for receiving the keeper tip in long token.
+    #[account(mut)]
+    pub keeper_long_token_account: Option<Box<Account<'info, TokenAccount>>>,
+    /// The token account for receiving the keeper tip in short token.
+    #[account(mut)]
+    pub keeper_short_token_account: Option<Box<Account<'info, TokenAccount>>>,
 }
 
 pub(crate) fn unchecked_execute_decrease_order<'info>(
@@ -727,7 +912,7 @@ pub(crate) fn unchecked_execute_decrease_order<'info>(
     throw_on_execution_error: bool,
 ) -> Result<()> {
     let accounts = &mut ctx.accounts;
-    let remaining_accounts = ctx.remaining_accounts;
+    let (remaining_accounts, analytics) = split_market_analytics(ctx.remaining_accounts);
 
     let kind = accounts.order.load()?.params().kind()?;
 
@@ -740,8 +925,20 @@ pub(crate) fn unchecked_execute_decrease_order<'info>(
         .load()?
         .validate_feature_enabled(kind.try_into()?, ActionDisabledFlag::Execute)?;
 
+    // Validate the executing keeper.
+    let keeper_assignment_timeout = *accounts
+        .store
+        .load()?
+        .get_amount_by_key(AmountKey::KeeperAssignmentTimeout);
+    accounts.order.load()?.validate_executing_keeper(
+        accounts.authority.key,
+        keeper_assignment_timeout,
+        Clock::get()?.unix_timestamp,
+    )?;
+
     let event_authority = accounts.event_authority.clone();
     let event_emitter = EventEmitter::new(&event_authority, ctx.bumps.event_authority);
+    let claimable_fees = ClaimableFeeSnapshot::take(&accounts.market)?;
     let (is_position_removed, transfer_out, should_send_trade_event) =
         accounts.perform_execution(remaining_accounts, throw_on_execution_error, &event_emitter)?;
 
@@ -751,10 +948,24 @@ pub(crate) fn unchecked_execute_decrease_order<'info>(
     } else {
         accounts.order.load_mut()?.header.cancelled()?;
     }
+    accounts
+        .market
+        .load_mut()?
+        .indexer_mut()
+        .release_pending_slot(
+            PendingActionKind::Order,
+            &mut accounts.order.load_mut()?.header,
+        );
+
+    if transfer_out.executed() {
+        accounts.pay_keeper_tip(claimable_fees, &event_emitter)?;
+        accounts.prepare_callback(&transfer_out, &event_emitter)?;
+    }
 
     if should_send_trade_event {
         let event_loader = accounts.event.clone();
         let event = event_loader.load()?;
+        record_trade_in_analytics(analytics, &event)?;
         let event = TradeEventRef::from(&*event);
         event_emitter.emit_cpi(&event)?;
     }
@@ -803,10 +1014,12 @@ impl<'info> ExecuteDecreaseOrder<'info> {
             .event(Some(&self.event))
             .throw_on_execution_error(throw_on_execution_error)
             .executor(self.authority.to_account_info())
+            .virtual_pool(self.virtual_pool.as_ref())
             .event_emitter(*event_emitter);
 
-        self.oracle.load_mut()?.with_prices(
+        self.oracle.load_mut()?.with_market_prices(
             &self.store,
+            &self.market,
             &self.token_map,
             &feeds.tokens,
             remaining_accounts,
@@ -868,6 +1081,45 @@ impl<'info> ExecuteDecreaseOrder<'info> {
         Ok(())
     }
 
+    #[inline(never)]
+    fn prepare_callback(
+        &self,
+        transfer_out: &TransferOut,
+        event_emitter: &EventEmitter<'_, 'info>,
+    ) -> Result<()> {
+        PrepareOrderCallbackOperation::builder()
+            .store(&self.store)
+            .order(&self.order)
+            .transfer_out(transfer_out)
+            .event_emitter(*event_emitter)
+            .build()
+            .execute()
+    }
+
+    #[inline(never)]
+    fn pay_keeper_tip(
+        &self,
+        claimable_fees: ClaimableFeeSnapshot,
+        event_emitter: &EventEmitter<'_, 'info>,
+    ) -> Result<()> {
+        PayKeeperTipOperation::builder()
+            .store(&self.store)
+            .market(&self.market)
+            .keeper(self.authority.key)
+            .snapshot(claimable_fees)
+            .long_token_receiver(self.keeper_long_token_account.as_deref())
+            .short_token_receiver(self.keeper_short_token_account.as_deref())
+            .vaults(vec![
+                (&*self.long_token, &*self.long_token_vault),
+                (&*self.short_token, &*self.short_token_vault),
+            ])
+            .token_program(self.token_program.to_account_info())
+            .event_emitter(*event_emitter)
+            .build()
+            .execute()?;
+        Ok(())
+    }
+
     #[inline(never)]
     fn pay_execution_fee(&self, execution_fee: u64) -> Result<()> {
         let execution_lamports = self.order.load()?.execution_lamports(execution_fee);
@@ -880,3 +1132,58 @@ impl<'info> ExecuteDecreaseOrder<'info> {
         Ok(())
     }
 }
+
+/// The accounts definition for the [`invoke_order_callback`](crate::gmsol_store::invoke_order_callback)
+/// instruction.
+#[derive(Accounts)]
+pub struct InvokeOrderCallback<'info> {
+    /// Authority.
+    pub authority: Signer<'info>,
+    /// Store.
+    pub store: AccountLoader<'info, Store>,
+    /// The executed order.
+    #[account(
+        mut,
+        constraint = order.load()?.header.store == store.key() @ CoreError::StoreMismatched,
+    )]
+    pub order: AccountLoader<'info, Order>,
+    /// The order callback authority.
+    /// CHECK: only used as the signer of the callback, checked in the handler.
+    pub callback_authority: UncheckedAccount<'info>,
+    /// The callback program.
+    /// CHECK: checked against the callback of the order in the handler.
+    pub callback_program: UncheckedAccount<'info>,
+    /// The account passed to the callback program.
+    /// CHECK: checked against the callback of the order in the handler.
+    #[account(mut)]
+    pub callback_account: UncheckedAccount<'info>,
+}
+
+/// Invoke the pending callback of an executed order.
+/// # CHECK
+/// Only [`ORDER_KEEPER`](crate::states::roles::RoleKey::ORDER_KEEPER) can use.
+pub(crate) fn unchecked_invoke_order_callback(
+    ctx: Context<InvokeOrderCallback>,
+    transfer_out: &TransferOut,
+) -> Result<()> {
+    let accounts = &ctx.accounts;
+    InvokeOrderCallbackOperation::builder()
+        .store(&accounts.store)
+        .order(&accounts.order)
+        .transfer_out(transfer_out)
+        .callback_authority(&accounts.callback_authority)
+        .callback_program(&accounts.callback_program)
+        .callback_account(&accounts.callback_account)
+        .build()
+        .execute()
+}
+
+impl<'info> internal::Authentication<'info> for InvokeOrderCallback<'info> {
+    fn authority(&self) -> &Signer<'info> {
+        &self.authority
+    }
+
+    fn store(&self) -> &AccountLoader<'info, Store> {
+        &self.store
+    }
+}
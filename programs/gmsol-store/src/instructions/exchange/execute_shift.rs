@@ -27,10 +27,13 @@ pub struct ExecuteShift<'info> {
     /// Authority.
     pub authority: Signer<'info>,
     /// Store.
-    #[account(has_one = token_map)]
     pub store: AccountLoader<'info, Store>,
     /// Token map.
-    #[account(has_one = store)]
+    #[account(
+        has_one = store,
+        constraint = from_market.load()?.resolve_token_map(&*store.load()?) == Some(&token_map.key()) @ CoreError::TokenMapMismatched,
+        constraint = to_market.load()?.resolve_token_map(&*store.load()?) == Some(&token_map.key()) @ CoreError::TokenMapMismatched,
+    )]
     pub token_map: AccountLoader<'info, TokenMapHeader>,
     /// Oracle buffer to use.
     #[account(mut, has_one = store)]
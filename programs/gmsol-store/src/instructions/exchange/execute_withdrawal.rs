@@ -5,14 +5,15 @@ use crate::{
     constants,
     events::EventEmitter,
     ops::{
-        execution_fee::PayExecutionFeeOperation, market::MarketTransferOutOperation,
+        execution_fee::PayExecutionFeeOperation,
+        market::{ClaimableFeeSnapshot, MarketTransferOutOperation, PayKeeperTipOperation},
         withdrawal::ExecuteWithdrawalOperation,
     },
     states::{
         common::action::{ActionExt, ActionSigner},
         feature::{ActionDisabledFlag, DomainDisabledFlag},
         withdrawal::Withdrawal,
-        Chainlink, Market, Oracle, Store, TokenMapHeader, TokenMapLoader,
+        Chainlink, Market, Oracle, PendingActionKind, Store, TokenMapHeader, TokenMapLoader,
     },
     utils::internal,
     CoreError,
@@ -33,10 +34,12 @@ pub struct ExecuteWithdrawal<'info> {
     /// Authority.
     pub authority: Signer<'info>,
     /// Store.
-    #[account(has_one = token_map)]
     pub store: AccountLoader<'info, Store>,
     /// Token map.
-    #[account(has_one = store)]
+    #[account(
+        has_one = store,
+        constraint = market.load()?.resolve_token_map(&*store.load()?) == Some(&token_map.key()) @ CoreError::TokenMapMismatched,
+    )]
     pub token_map: AccountLoader<'info, TokenMapHeader>,
     /// Oracle buffer to use.
     #[account(mut, has_one = store)]
@@ -131,6 +134,12 @@ pub struct ExecuteWithdrawal<'info> {
     pub system_program: Program<'info, System>,
     /// Chainlink Program.
     pub chainlink_program: Option<Program<'info, Chainlink>>,
+    /// The token account for receiving the keeper tip in long token.
+    #[account(mut)]
+    pub keeper_long_token_account: Option<Box<Account<'info, TokenAccount>>>,
+    /// The token account for receiving the keeper tip in short token.
+    #[account(mut)]
+    pub keeper_short_token_account: Option<Box<Account<'info, TokenAccount>>>,
 }
 
 /// CHECK only ORDER_KEEPER can invoke this instruction.
@@ -155,6 +164,7 @@ pub(crate) fn unchecked_execute_withdrawal<'info>(
 
     accounts.transfer_market_tokens_in(&signer)?;
 
+    let claimable_fees = ClaimableFeeSnapshot::take(&accounts.market)?;
     let executed =
         accounts.perform_execution(remaining_accounts, throw_on_execution_error, &event_emitter)?;
 
@@ -167,12 +177,21 @@ pub(crate) fn unchecked_execute_withdrawal<'info>(
                 final_short_token_amount,
                 &event_emitter,
             )?;
+            accounts.pay_keeper_tip(claimable_fees, &event_emitter)?;
         }
         None => {
             accounts.withdrawal.load_mut()?.header.cancelled()?;
             accounts.transfer_market_tokens_out()?;
         }
     }
+    accounts
+        .market
+        .load_mut()?
+        .indexer_mut()
+        .release_pending_slot(
+            PendingActionKind::Withdrawal,
+            &mut accounts.withdrawal.load_mut()?.header,
+        );
 
     // Is must be placed at the end to be executed correctly.
     accounts.pay_execution_fee(execution_fee)?;
@@ -191,6 +210,31 @@ impl<'info> internal::Authentication<'info> for ExecuteWithdrawal<'info> {
 }
 
 impl<'info> ExecuteWithdrawal<'info> {
+    #[inline(never)]
+    fn pay_keeper_tip(
+        &self,
+        claimable_fees: ClaimableFeeSnapshot,
+        event_emitter: &EventEmitter<'_, 'info>,
+    ) -> Result<()> {
+        let vaults = vec![
+            (&*self.final_long_token, &*self.final_long_token_vault),
+            (&*self.final_short_token, &*self.final_short_token_vault),
+        ];
+        PayKeeperTipOperation::builder()
+            .store(&self.store)
+            .market(&self.market)
+            .keeper(self.authority.key)
+            .snapshot(claimable_fees)
+            .long_token_receiver(self.keeper_long_token_account.as_deref())
+            .short_token_receiver(self.keeper_short_token_account.as_deref())
+            .vaults(vaults)
+            .token_program(self.token_program.to_account_info())
+            .event_emitter(*event_emitter)
+            .build()
+            .execute()?;
+        Ok(())
+    }
+
     fn perform_execution(
         &mut self,
         remaining_accounts: &'info [AccountInfo<'info>],
@@ -214,8 +258,9 @@ impl<'info> ExecuteWithdrawal<'info> {
             .throw_on_execution_error(throw_on_execution_error)
             .event_emitter(*event_emitter);
 
-        let executed = self.oracle.load_mut()?.with_prices(
+        let executed = self.oracle.load_mut()?.with_market_prices(
             &self.store,
+            &self.market,
             &self.token_map,
             &feeds.tokens,
             remaining_accounts,
@@ -19,6 +19,12 @@ pub mod execute_order;
 /// Update ADL state.
 pub mod update_adl;
 
+/// Pre-execution validation.
+pub mod validate_execution;
+
+/// Required feeds of actions.
+pub mod required_feeds;
+
 /// Position cut.
 pub mod position_cut;
 
@@ -35,8 +41,10 @@ pub use execute_shift::*;
 pub use execute_withdrawal::*;
 pub use order::*;
 pub use position_cut::*;
+pub use required_feeds::*;
 pub use shift::*;
 pub use update_adl::*;
+pub use validate_execution::*;
 pub use withdrawal::*;
 
 use crate::CoreError;
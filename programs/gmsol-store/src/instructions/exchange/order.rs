@@ -1,6 +1,6 @@
 use std::collections::HashSet;
 
-use anchor_lang::prelude::*;
+use anchor_lang::{prelude::*, system_program};
 use anchor_spl::{
     associated_token::AssociatedToken,
     token::{transfer_checked, Mint, Token, TokenAccount, TransferChecked},
@@ -10,7 +10,7 @@ use gmsol_utils::InitSpace;
 
 use crate::{
     constants,
-    events::{EventEmitter, GtUpdated, OrderCreated},
+    events::{BatchPositionsPrepared, EventEmitter, GtUpdated, OrderCreated},
     ops::{
         execution_fee::TransferExecutionFeeOperation,
         order::{CreateOrderOperation, CreateOrderParams},
@@ -20,10 +20,10 @@ use crate::{
         common::action::Action,
         feature::ActionDisabledFlag,
         order::{Order, OrderKind},
-        position::PositionKind,
+        position::{MarginMode, PositionKind, PositionSpec, MAX_BATCH_PREPARE_POSITIONS},
         user::UserHeader,
-        HasMarketMeta, Market, NonceBytes, Position, RoleKey, Seed, Store, StoreWalletSigner,
-        UpdateOrderParams,
+        HasMarketMeta, Market, NonceBytes, PendingActionKind, Position, RoleKey, Seed, Store,
+        StoreWalletSigner, UpdateOrderParams,
     },
     utils::{internal, token::is_associated_token_account_or_owner},
     CoreError,
@@ -74,6 +74,7 @@ pub(crate) fn prepare_position(
         &ctx.accounts.position,
         ctx.bumps.position,
         params.to_position_kind()?,
+        params.margin_mode,
         &ctx.accounts.owner,
         collateral_token,
         &market_token,
@@ -84,11 +85,146 @@ pub(crate) fn prepare_position(
     Ok(())
 }
 
+/// The accounts definition for the
+/// [`batch_prepare_positions`](crate::gmsol_store::batch_prepare_positions) instruction.
+///
+/// Remaining accounts expected by this instruction:
+///
+///   - 0..2N. `[market, position]` N pairs of market account and `[writable]` position
+///     account, one for each position spec in the same order, where N is the number of
+///     the specs.
+#[event_cpi]
+#[derive(Accounts)]
+pub struct BatchPreparePositions<'info> {
+    /// The owner of the positions.
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    /// Store.
+    pub store: AccountLoader<'info, Store>,
+    /// The system program.
+    pub system_program: Program<'info, System>,
+}
+
+pub(crate) fn batch_prepare_positions<'info>(
+    ctx: Context<'_, '_, 'info, 'info, BatchPreparePositions<'info>>,
+    specs: &[PositionSpec],
+) -> Result<()> {
+    require!(!specs.is_empty(), CoreError::InvalidArgument);
+    require_gte!(
+        MAX_BATCH_PREPARE_POSITIONS,
+        specs.len(),
+        CoreError::ExceedMaxLengthLimit
+    );
+    require_eq!(
+        ctx.remaining_accounts.len(),
+        specs.len() * 2,
+        CoreError::InvalidArgument
+    );
+
+    let accounts = &ctx.accounts;
+    let store = accounts.store.key();
+    for (spec, remaining) in specs.iter().zip(ctx.remaining_accounts.chunks_exact(2)) {
+        let market = AccountLoader::<Market>::try_from(&remaining[0])?;
+        let meta = *market.load()?.validated_meta(&store)?;
+        require_keys_eq!(
+            meta.market_token_mint,
+            spec.market_token,
+            CoreError::MarketTokenMintMismatched
+        );
+        require!(
+            meta.is_collateral_token(&spec.collateral_token),
+            CoreError::InvalidCollateralToken
+        );
+
+        let position = accounts.prepare_position_account(spec, &remaining[1])?;
+        validate_and_initialize_position_if_needed(
+            &position.loader,
+            position.bump,
+            spec.kind(),
+            None,
+            accounts.owner.as_ref(),
+            &spec.collateral_token,
+            &spec.market_token,
+            meta.is_pure(),
+            &store,
+            accounts.system_program.to_account_info(),
+        )?;
+    }
+
+    let event_emitter = EventEmitter::new(&ctx.accounts.event_authority, ctx.bumps.event_authority);
+    event_emitter.emit_cpi(&BatchPositionsPrepared {
+        count: specs.len() as u8,
+    })?;
+    Ok(())
+}
+
+struct PreparedPositionAccount<'info> {
+    loader: AccountLoader<'info, Position>,
+    bump: u8,
+}
+
+impl<'info> BatchPreparePositions<'info> {
+    /// Validate the address of the position account and create it if it does not exist.
+    fn prepare_position_account(
+        &self,
+        spec: &PositionSpec,
+        position: &'info AccountInfo<'info>,
+    ) -> Result<PreparedPositionAccount<'info>> {
+        let store = self.store.key();
+        let owner = self.owner.key();
+        let kind = [spec.kind() as u8];
+        let (address, bump) = Pubkey::find_program_address(
+            &[
+                Position::SEED,
+                store.as_ref(),
+                owner.as_ref(),
+                spec.market_token.as_ref(),
+                spec.collateral_token.as_ref(),
+                &kind,
+            ],
+            &crate::ID,
+        );
+        require_keys_eq!(position.key(), address, ErrorCode::ConstraintSeeds);
+        require!(position.is_writable, ErrorCode::ConstraintMut);
+
+        if *position.owner == system_program::ID {
+            let space = 8 + Position::INIT_SPACE;
+            system_program::create_account(
+                CpiContext::new_with_signer(
+                    self.system_program.to_account_info(),
+                    system_program::CreateAccount {
+                        from: self.owner.to_account_info(),
+                        to: position.clone(),
+                    },
+                    &[&[
+                        Position::SEED,
+                        store.as_ref(),
+                        owner.as_ref(),
+                        spec.market_token.as_ref(),
+                        spec.collateral_token.as_ref(),
+                        &kind,
+                        &[bump],
+                    ]],
+                ),
+                Rent::get()?.minimum_balance(space),
+                space as u64,
+                &crate::ID,
+            )?;
+        }
+
+        Ok(PreparedPositionAccount {
+            loader: AccountLoader::try_from_unchecked(&crate::ID, position)?,
+            bump,
+        })
+    }
+}
+
 #[allow(clippy::too_many_arguments)]
 fn validate_and_initialize_position_if_needed<'info>(
     position_loader: &AccountLoader<'info, Position>,
     bump: u8,
     kind: PositionKind,
+    margin_mode: Option<MarginMode>,
     owner: &AccountInfo<'info>,
     collateral_token: &Pubkey,
     market_token: &Pubkey,
@@ -103,6 +239,7 @@ fn validate_and_initialize_position_if_needed<'info>(
         Ok(mut position) => {
             position.try_init(
                 kind,
+                margin_mode.unwrap_or_default(),
                 bump,
                 *store,
                 owner_key,
@@ -132,6 +269,13 @@ fn validate_and_initialize_position_if_needed<'info>(
         store,
     )?;
 
+    // The margin mode of an existing position can only be changed while it is empty.
+    if let Some(margin_mode) = margin_mode {
+        position_loader
+            .load_mut()?
+            .update_margin_mode(margin_mode)?;
+    }
+
     if should_transfer_in {
         TransferExecutionFeeOperation::builder()
             .payment(position_loader.to_account_info())
@@ -311,6 +455,10 @@ impl<'info> internal::Create<'info, Order> for CreateOrder<'info> {
             .load()?
             .validate_not_restarted()?
             .validate_feature_enabled(params.kind.try_into()?, ActionDisabledFlag::Create)?;
+        // Frozen users are still allowed to close their positions.
+        if !params.kind.is_decrease_position() {
+            self.user.load()?.validate_not_frozen()?;
+        }
         Ok(())
     }
 
@@ -321,6 +469,10 @@ impl<'info> internal::Create<'info, Order> for CreateOrder<'info> {
         bumps: &Self::Bumps,
         remaining_accounts: &'info [AccountInfo<'info>],
     ) -> Result<()> {
+        let max_trades_per_block = self.store.load()?.max_trades_per_block_per_user();
+        self.user
+            .load_mut()?
+            .record_trade_in_block(Clock::get()?.slot, max_trades_per_block)?;
         self.transfer_tokens(params)?;
 
         let ops = CreateOrderOperation::builder()
@@ -406,6 +558,18 @@ impl<'info> internal::Create<'info, Order> for CreateOrder<'info> {
                 return err!(CoreError::OrderKindNotAllowed);
             }
         }
+        // Decrease orders are not counted so that positions can always be closed.
+        if !params.kind.is_decrease_position() {
+            let max_pending = self
+                .store
+                .load()?
+                .max_pending_actions(PendingActionKind::Order);
+            self.market.load_mut()?.indexer_mut().occupy_pending_slot(
+                PendingActionKind::Order,
+                max_pending,
+                &mut self.order.load_mut()?.header,
+            )?;
+        }
         emit!(OrderCreated::new(
             self.store.key(),
             self.order.key(),
@@ -611,6 +775,14 @@ impl<'info> internal::Close<'info, Order> for CloseOrder<'info> {
         self.rent_receiver.to_account_info()
     }
 
+    fn release_pending_slot(&self, remaining_accounts: &'info [AccountInfo<'info>]) -> Result<()> {
+        internal::release_pending_slot(
+            PendingActionKind::Order,
+            &mut self.order.load_mut()?.header,
+            remaining_accounts,
+        )
+    }
+
     fn validate(&self) -> Result<()> {
         let order = self.order.load()?;
         if order.header.action_state()?.is_pending() {
@@ -833,6 +1005,7 @@ impl<'info> CloseOrder<'info> {
             let mut referrer_user = referrer_user.load_mut()?;
 
             store.gt_mut().mint_to(&mut referrer_user, reward)?;
+            referrer_user.referral.add_reward(reward);
 
             event_emitter.emit_cpi(&GtUpdated::rewarded(
                 reward,
@@ -18,10 +18,11 @@ use crate::{
     states::{
         common::action::ActionExt,
         feature::{ActionDisabledFlag, DomainDisabledFlag},
+        keeper::KeeperDeadlineExtension,
         order::Order,
         user::UserHeader,
         Chainlink, HasMarketMeta, Market, NonceBytes, Oracle, Position, Seed, Store,
-        TokenMapHeader,
+        TokenMapHeader, VirtualPool,
     },
     utils::internal,
     validated_recent_timestamp, CoreError,
@@ -34,6 +35,9 @@ use crate::{
 ///
 ///   - 0..N. `[]` N feed accounts, where N represents the total number of unique tokens
 ///     in the market.
+///   - N..N+K. `[]` K position accounts, required only if the position is in cross margin mode,
+///     where K is the number of the other positions of the owner in the market, in the order of
+///     [`Position::find_cross_margin_sibling_addresses`](crate::states::Position::find_cross_margin_sibling_addresses).
 ///
 /// # Warnings
 /// Because token accounts can be frozen by token's
@@ -84,10 +88,13 @@ pub struct PositionCut<'info> {
     )]
     pub user: AccountLoader<'info, UserHeader>,
     /// Store.
-    #[account(mut, has_one = token_map)]
+    #[account(mut)]
     pub store: AccountLoader<'info, Store>,
     /// Token map.
-    #[account(has_one = store)]
+    #[account(
+        has_one = store,
+        constraint = market.load()?.resolve_token_map(&*store.load()?) == Some(&token_map.key()) @ CoreError::TokenMapMismatched,
+    )]
     pub token_map: AccountLoader<'info, TokenMapHeader>,
     /// Buffer for oracle prices.
     #[account(mut, has_one = store)]
@@ -176,7 +183,7 @@ pub struct PositionCut<'info> {
             store.key().as_ref(),
             market.load()?.meta().long_token_mint.as_ref(),
             position.load()?.owner.as_ref(),
-            &store.load()?.claimable_time_key(validated_recent_timestamp(store.load()?.deref(), recent_timestamp)?)?,
+            &store.load()?.claimable_time_key(validated_recent_timestamp(store.load()?.deref(), recent_timestamp, deadline_extension.as_ref())?)?,
         ],
         bump,
     )]
@@ -191,7 +198,7 @@ pub struct PositionCut<'info> {
             store.key().as_ref(),
             market.load()?.meta().short_token_mint.as_ref(),
             position.load()?.owner.as_ref(),
-            &store.load()?.claimable_time_key(validated_recent_timestamp(store.load()?.deref(), recent_timestamp)?)?,
+            &store.load()?.claimable_time_key(validated_recent_timestamp(store.load()?.deref(), recent_timestamp, deadline_extension.as_ref())?)?,
         ],
         bump,
     )]
@@ -206,7 +213,7 @@ pub struct PositionCut<'info> {
             store.key().as_ref(),
             get_pnl_token(&Some(position.clone()), market.load()?.deref())?.as_ref(),
             store.load()?.address.holding.as_ref(),
-            &store.load()?.claimable_time_key(validated_recent_timestamp(store.load()?.deref(), recent_timestamp)?)?,
+            &store.load()?.claimable_time_key(validated_recent_timestamp(store.load()?.deref(), recent_timestamp, deadline_extension.as_ref())?)?,
         ],
         bump,
     )]
@@ -220,6 +227,21 @@ pub struct PositionCut<'info> {
     pub associated_token_program: Program<'info, AssociatedToken>,
     /// Chainlink Program.
     pub chainlink_program: Option<Program<'info, Chainlink>>,
+    /// The keeper deadline extension of the store, if any.
+    #[account(
+        has_one = store,
+        seeds = [KeeperDeadlineExtension::SEED, store.key().as_ref()],
+        bump = deadline_extension.load()?.bump,
+    )]
+    pub deadline_extension: Option<AccountLoader<'info, KeeperDeadlineExtension>>,
+    /// The virtual pool of the market, required if the market has enabled the virtual pool.
+    #[account(
+        mut,
+        has_one = store,
+        seeds = [VirtualPool::SEED, store.key().as_ref(), virtual_pool.load()?.key().as_ref()],
+        bump = virtual_pool.load()?.bump,
+    )]
+    pub virtual_pool: Option<AccountLoader<'info, VirtualPool>>,
 }
 
 /// CHECK: only ORDER_KEEPER is allowed to use this instrcution.
@@ -301,15 +323,22 @@ pub(crate) fn unchecked_process_position_cut<'info>(
         .executor(accounts.authority.to_account_info())
         .refund(refund)
         .should_unwrap_native_token(should_unwrap_native_token)
+        .virtual_pool(accounts.virtual_pool.as_ref())
         .event_emitter(event_emitter);
 
-    let should_send_trade_event = accounts.oracle.load_mut()?.with_prices(
+    let should_send_trade_event = accounts.oracle.load_mut()?.with_market_prices(
         &accounts.store,
+        &accounts.market,
         &accounts.token_map,
         &tokens,
         remaining_accounts,
         accounts.chainlink_program.as_ref(),
-        |oracle, _remaining_accounts| ops.oracle(oracle).build().execute(),
+        |oracle, remaining_accounts| {
+            ops.oracle(oracle)
+                .cross_margin_positions(remaining_accounts)
+                .build()
+                .execute()
+        },
     )?;
 
     if should_send_trade_event {
@@ -0,0 +1,102 @@
+use anchor_lang::{prelude::*, Discriminator};
+
+use crate::{
+    states::{
+        common::{
+            action::ActionHeader,
+            token_with_feeds::{token_records, TokensWithFeed},
+        },
+        Deposit, Market, Order, Shift, Store, TokenMapHeader, TokenMapLoader, Withdrawal,
+    },
+    CoreError,
+};
+
+use super::{execute_shift::ordered_tokens, validate_execution::load_action};
+
+/// The accounts definition for [`get_required_feeds`](crate::gmsol_store::get_required_feeds).
+///
+/// *[See also the documentation for the instruction.](crate::gmsol_store::get_required_feeds)*
+#[derive(Accounts)]
+pub struct GetRequiredFeeds<'info> {
+    /// Store.
+    pub store: AccountLoader<'info, Store>,
+    /// Token map.
+    #[account(
+        has_one = store,
+        constraint = market.load()?.resolve_token_map(&*store.load()?) == Some(&token_map.key()) @ CoreError::TokenMapMismatched,
+    )]
+    pub token_map: AccountLoader<'info, TokenMapHeader>,
+    /// The market of the action, which is the `from_market` for shifts.
+    #[account(has_one = store)]
+    pub market: AccountLoader<'info, Market>,
+    /// The `to_market` of the shift, required only for shifts.
+    #[account(
+        has_one = store,
+        constraint = to_market.load()?.resolve_token_map(&*store.load()?) == Some(&token_map.key()) @ CoreError::TokenMapMismatched,
+    )]
+    pub to_market: Option<AccountLoader<'info, Market>>,
+    /// The action, must be a deposit, withdrawal, order or shift.
+    /// CHECK: the owner and discriminator are validated in the instruction.
+    pub action: UncheckedAccount<'info>,
+}
+
+pub(crate) fn get_required_feeds(ctx: Context<GetRequiredFeeds>) -> Result<TokensWithFeed> {
+    let accounts = &ctx.accounts;
+    let action = accounts.action.to_account_info();
+    require_keys_eq!(*action.owner, crate::ID, CoreError::InvalidArgument);
+
+    let data = action.try_borrow_data()?;
+    require_gte!(data.len(), 8, CoreError::InvalidArgument);
+    let (discriminator, data) = data.split_at(8);
+
+    let token_map = accounts.token_map.load_token_map()?;
+    let feeds = match discriminator {
+        disc if disc == Deposit::DISCRIMINATOR => {
+            let deposit = load_action::<Deposit>(data)?;
+            accounts.validate_header(&deposit.header)?;
+            deposit.swap().to_feeds(&token_map)?
+        }
+        disc if disc == Withdrawal::DISCRIMINATOR => {
+            let withdrawal = load_action::<Withdrawal>(data)?;
+            accounts.validate_header(&withdrawal.header)?;
+            withdrawal.swap().to_feeds(&token_map)?
+        }
+        disc if disc == Order::DISCRIMINATOR => {
+            let order = load_action::<Order>(data)?;
+            accounts.validate_header(&order.header)?;
+            order.swap().to_feeds(&token_map)?
+        }
+        disc if disc == Shift::DISCRIMINATOR => {
+            let shift = load_action::<Shift>(data)?;
+            accounts.validate_header(&shift.header)?;
+            let to_market = accounts
+                .to_market
+                .as_ref()
+                .ok_or_else(|| error!(CoreError::MarketMismatched))?;
+            require_keys_eq!(
+                to_market.load()?.meta().market_token_mint,
+                shift.tokens.to_market_token(),
+                CoreError::MarketMismatched
+            );
+            let from = *accounts.market.load()?.meta();
+            let to = *to_market.load()?.meta();
+            let records = token_records(&token_map, &ordered_tokens(&from, &to))?;
+            TokensWithFeed::try_from_records(records)?
+        }
+        _ => return err!(CoreError::InvalidArgument),
+    };
+
+    Ok(feeds)
+}
+
+impl GetRequiredFeeds<'_> {
+    fn validate_header(&self, header: &ActionHeader) -> Result<()> {
+        require_keys_eq!(header.store, self.store.key(), CoreError::StoreMismatched);
+        require_keys_eq!(
+            header.market,
+            self.market.key(),
+            CoreError::MarketMismatched
+        );
+        Ok(())
+    }
+}
@@ -7,10 +7,12 @@ use gmsol_utils::InitSpace;
 
 use crate::{
     events::EventEmitter,
+    instructions::user::initialize_user_if_needed,
     ops::shift::{CreateShiftOperation, CreateShiftParams},
     states::{
         common::action::{Action, ActionExt},
         feature::{ActionDisabledFlag, DomainDisabledFlag},
+        user::{UserActionKind, UserHeader},
         Market, NonceBytes, RoleKey, Seed, Shift, Store, StoreWalletSigner,
     },
     utils::{internal, token::is_associated_token_account},
@@ -38,6 +40,15 @@ pub struct CreateShift<'info> {
         constraint = from_market.load()?.validate_shiftable(&*to_market.load()?).is_ok() @ CoreError::TokenMintMismatched,
     )]
     pub to_market: AccountLoader<'info, Market>,
+    /// User Account. It will be initialized if needed.
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = 8 + UserHeader::space(0),
+        seeds = [UserHeader::SEED, store.key().as_ref(), owner.key().as_ref()],
+        bump,
+    )]
+    pub user: AccountLoader<'info, UserHeader>,
     /// Shift.
     #[account(
         init,
@@ -117,7 +128,12 @@ impl<'info> internal::Create<'info, Shift> for CreateShift<'info> {
         bumps: &Self::Bumps,
         _remaining_accounts: &'info [AccountInfo<'info>],
     ) -> Result<()> {
+        initialize_user_if_needed(&self.user, &self.store.key(), self.owner.key, bumps.user)?;
+        self.user.load()?.validate_not_frozen()?;
         self.transfer_tokens(params)?;
+        self.user
+            .load_mut()?
+            .record_action(UserActionKind::Shift, Clock::get()?.unix_timestamp);
         CreateShiftOperation::builder()
             .store(&self.store)
             .owner(&self.owner)
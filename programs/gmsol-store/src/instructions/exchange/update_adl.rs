@@ -18,10 +18,12 @@ pub struct UpdateAdlState<'info> {
     /// The address authorized to execute this instruction.
     pub authority: Signer<'info>,
     /// The store that owns the market.
-    #[account(has_one = token_map)]
     pub store: AccountLoader<'info, Store>,
     /// Token map.
-    #[account(has_one = store)]
+    #[account(
+        has_one = store,
+        constraint = market.load()?.resolve_token_map(&*store.load()?) == Some(&token_map.key()) @ CoreError::TokenMapMismatched,
+    )]
     pub token_map: AccountLoader<'info, TokenMapHeader>,
     /// The oracle buffer to use.
     #[account(mut, has_one = store)]
@@ -38,20 +40,30 @@ pub(crate) fn unchecked_update_adl_state<'info>(
     ctx: Context<'_, '_, 'info, 'info, UpdateAdlState<'info>>,
     is_long: bool,
 ) -> Result<()> {
-    let mut market = ctx.accounts.market.load_mut()?;
-    let tokens = market
+    ctx.accounts.store.load()?.validate_not_paused()?;
+
+    let tokens = ctx
+        .accounts
+        .market
+        .load()?
         .meta()
         .ordered_tokens()
         .into_iter()
         .collect::<Vec<_>>();
 
-    ctx.accounts.oracle.load_mut()?.with_prices(
+    ctx.accounts.oracle.load_mut()?.with_market_prices(
         &ctx.accounts.store,
+        &ctx.accounts.market,
         &ctx.accounts.token_map,
         &tokens,
         ctx.remaining_accounts,
         ctx.accounts.chainlink_program.as_ref(),
-        |oracle, _remaining_accounts| market.update_adl_state(oracle, is_long),
+        |oracle, _remaining_accounts| {
+            ctx.accounts
+                .market
+                .load_mut()?
+                .update_adl_state(oracle, is_long)
+        },
     )?;
 
     Ok(())
@@ -0,0 +1,330 @@
+use anchor_lang::{prelude::*, Discriminator};
+use anchor_spl::token::TokenAccount;
+use gmsol_utils::InitSpace;
+
+use crate::{
+    states::{
+        common::action::ActionHeader,
+        feature::{ActionDisabledFlag, DomainDisabledFlag},
+        order::OrderKind,
+        Deposit, Market, Oracle, Order, Store, ValidateOracleTime, Withdrawal,
+    },
+    CoreError, CoreResult,
+};
+
+/// The base fee (in lamports) of a transaction with one signature.
+const LAMPORTS_PER_SIGNATURE: u64 = 5_000;
+
+/// The compute unit price (in micro-lamports) assumed for estimating the gas.
+const ESTIMATED_COMPUTE_UNIT_PRICE_MICRO_LAMPORTS: u64 = 100_000;
+
+/// Estimated compute units for executing a deposit.
+const EXECUTE_DEPOSIT_COMPUTE_UNITS: u64 = 400_000;
+
+/// Estimated compute units for executing a withdrawal.
+const EXECUTE_WITHDRAWAL_COMPUTE_UNITS: u64 = 400_000;
+
+/// Estimated compute units for executing a swap or increase order.
+const EXECUTE_INCREASE_OR_SWAP_ORDER_COMPUTE_UNITS: u64 = 400_000;
+
+/// Estimated compute units for executing a decrease order.
+const EXECUTE_DECREASE_ORDER_COMPUTE_UNITS: u64 = 400_000;
+
+/// The accounts definition for [`validate_execution_params`](crate::gmsol_store::validate_execution_params).
+///
+/// *[See also the documentation for the instruction.](crate::gmsol_store::validate_execution_params)*
+///
+/// Remaining accounts expected by this instruction:
+///
+///   - 0..N. `[]` N escrow accounts of the action, used to check whether the
+///     escrows are funded.
+#[derive(Accounts)]
+pub struct ValidateExecutionParams<'info> {
+    /// Store.
+    pub store: AccountLoader<'info, Store>,
+    /// The oracle buffer to check.
+    #[account(has_one = store)]
+    pub oracle: AccountLoader<'info, Oracle>,
+    /// The market of the action.
+    #[account(has_one = store)]
+    pub market: AccountLoader<'info, Market>,
+    /// The action to validate, must be a deposit, withdrawal or order.
+    /// CHECK: the owner and discriminator are validated in the instruction.
+    pub action: UncheckedAccount<'info>,
+}
+
+/// Report of the pre-execution checks.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+#[cfg_attr(feature = "debug", derive(Debug))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ValidationReport {
+    /// Whether the prices in the oracle buffer are valid for the action.
+    pub oracle_freshness_ok: bool,
+    /// Whether the claimable execution fee covers the estimated gas.
+    pub execution_fee_sufficient: bool,
+    /// Whether the market is enabled.
+    pub market_enabled: bool,
+    /// Whether the escrow accounts hold enough tokens.
+    pub escrow_funded: bool,
+    /// Estimated gas in lamports for executing the action.
+    pub estimated_gas_lamports: u64,
+    /// Whether the execution is expected to pass the pre-execution checks.
+    pub would_succeed: bool,
+}
+
+pub(crate) fn validate_execution_params<'info>(
+    ctx: Context<'_, '_, 'info, 'info, ValidateExecutionParams<'info>>,
+    execution_fee: u64,
+) -> Result<ValidationReport> {
+    let accounts = &ctx.accounts;
+    let action = accounts.action.to_account_info();
+    require_keys_eq!(*action.owner, crate::ID, CoreError::InvalidArgument);
+
+    let data = action.try_borrow_data()?;
+    require_gte!(data.len(), 8, CoreError::InvalidArgument);
+    let (discriminator, data) = data.split_at(8);
+
+    let report = match discriminator {
+        disc if disc == Deposit::DISCRIMINATOR => {
+            let deposit = load_action::<Deposit>(data)?;
+            let escrows = [
+                (
+                    deposit.tokens.initial_long_token.account(),
+                    deposit.params.initial_long_token_amount,
+                ),
+                (
+                    deposit.tokens.initial_short_token.account(),
+                    deposit.params.initial_short_token_amount,
+                ),
+            ];
+            accounts.validate(
+                &deposit.header,
+                Deposit::INIT_SPACE,
+                execution_fee,
+                DomainDisabledFlag::Deposit,
+                EXECUTE_DEPOSIT_COMPUTE_UNITS,
+                &OracleTime::for_action(&accounts.store, &deposit.header, None, true)?,
+                &escrows,
+                ctx.remaining_accounts,
+            )?
+        }
+        disc if disc == Withdrawal::DISCRIMINATOR => {
+            let withdrawal = load_action::<Withdrawal>(data)?;
+            let escrows = [(
+                withdrawal.tokens.market_token.account(),
+                withdrawal.params.market_token_amount,
+            )];
+            accounts.validate(
+                &withdrawal.header,
+                Withdrawal::INIT_SPACE,
+                execution_fee,
+                DomainDisabledFlag::Withdrawal,
+                EXECUTE_WITHDRAWAL_COMPUTE_UNITS,
+                &OracleTime::for_action(&accounts.store, &withdrawal.header, None, true)?,
+                &escrows,
+                ctx.remaining_accounts,
+            )?
+        }
+        disc if disc == Order::DISCRIMINATOR => {
+            let order = load_action::<Order>(data)?;
+            let kind = order.params.kind()?;
+            let compute_units = if kind.is_decrease_position() {
+                EXECUTE_DECREASE_ORDER_COMPUTE_UNITS
+            } else {
+                EXECUTE_INCREASE_OR_SWAP_ORDER_COMPUTE_UNITS
+            };
+            let valid_from_ts = match kind {
+                OrderKind::LimitSwap
+                | OrderKind::LimitIncrease
+                | OrderKind::LimitDecrease
+                | OrderKind::StopLossDecrease => Some(order.params.valid_from_ts),
+                _ => None,
+            };
+            let escrows = [(
+                order.tokens.initial_collateral.account(),
+                order.params.initial_collateral_delta_amount,
+            )];
+            accounts.validate(
+                &order.header,
+                Order::INIT_SPACE,
+                execution_fee,
+                kind.try_into()?,
+                compute_units,
+                &OracleTime::for_action(
+                    &accounts.store,
+                    &order.header,
+                    valid_from_ts,
+                    kind.is_market(),
+                )?,
+                &escrows,
+                ctx.remaining_accounts,
+            )?
+        }
+        _ => return err!(CoreError::InvalidArgument),
+    };
+
+    Ok(report)
+}
+
+pub(super) fn load_action<T: bytemuck::Pod>(data: &[u8]) -> Result<&T> {
+    let data = data
+        .get(..std::mem::size_of::<T>())
+        .ok_or_else(|| error!(CoreError::InvalidArgument))?;
+    bytemuck::try_from_bytes(data).map_err(|_| error!(CoreError::InvalidArgument))
+}
+
+impl ValidateExecutionParams<'_> {
+    #[allow(clippy::too_many_arguments)]
+    fn validate<'info>(
+        &self,
+        header: &ActionHeader,
+        space: usize,
+        execution_fee: u64,
+        domain: DomainDisabledFlag,
+        compute_units: u64,
+        oracle_time: &OracleTime,
+        escrows: &[(Option<Pubkey>, u64)],
+        remaining_accounts: &'info [AccountInfo<'info>],
+    ) -> Result<ValidationReport> {
+        require_keys_eq!(header.store, self.store.key(), CoreError::StoreMismatched);
+        require_keys_eq!(
+            header.market,
+            self.market.key(),
+            CoreError::MarketMismatched
+        );
+
+        let oracle_freshness_ok = {
+            let oracle = self.oracle.load()?;
+            !oracle.is_cleared() && oracle.validate_time(oracle_time).is_ok()
+        };
+
+        // The keeper can claim at most `max_execution_lamports` from the action,
+        // and the action account must remain rent-exempt after paying.
+        let estimated_gas_lamports = estimate_gas_lamports(compute_units);
+        let claimable = execution_fee.min(header.max_execution_lamports());
+        let balance = self.action.lamports().saturating_sub(claimable);
+        let execution_fee_sufficient =
+            claimable >= estimated_gas_lamports && Rent::get()?.is_exempt(balance, 8 + space);
+
+        let market_enabled = self.market.load()?.is_enabled();
+
+        let escrow_funded = escrows
+            .iter()
+            .filter_map(|(account, amount)| account.map(|account| (account, *amount)))
+            .all(|(account, amount)| {
+                remaining_accounts
+                    .iter()
+                    .find(|info| info.key() == account)
+                    .and_then(|info| Account::<TokenAccount>::try_from(info).ok())
+                    .map(|escrow| escrow.amount >= amount)
+                    .unwrap_or(false)
+            });
+
+        let is_executable = header.action_state()?.is_pending()
+            && !self
+                .store
+                .load()?
+                .is_feature_disabled(domain, ActionDisabledFlag::Execute);
+
+        Ok(ValidationReport {
+            oracle_freshness_ok,
+            execution_fee_sufficient,
+            market_enabled,
+            escrow_funded,
+            estimated_gas_lamports,
+            would_succeed: oracle_freshness_ok
+                && execution_fee_sufficient
+                && market_enabled
+                && escrow_funded
+                && is_executable,
+        })
+    }
+}
+
+fn estimate_gas_lamports(compute_units: u64) -> u64 {
+    LAMPORTS_PER_SIGNATURE.saturating_add(
+        compute_units.saturating_mul(ESTIMATED_COMPUTE_UNIT_PRICE_MICRO_LAMPORTS) / 1_000_000,
+    )
+}
+
+/// Oracle time requirements of an action.
+///
+/// Position-related requirements of orders are not taken into account.
+struct OracleTime {
+    after: i64,
+    before: Option<i64>,
+    after_slot: u64,
+}
+
+impl OracleTime {
+    fn for_action(
+        store: &AccountLoader<Store>,
+        header: &ActionHeader,
+        valid_from_ts: Option<i64>,
+        expirable: bool,
+    ) -> Result<Self> {
+        let updated_at = header.updated_at();
+        let before = if expirable {
+            Some(store.load()?.request_expiration_at(updated_at)?)
+        } else {
+            None
+        };
+        Ok(Self {
+            after: valid_from_ts.map_or(updated_at, |ts| ts.max(updated_at)),
+            before,
+            after_slot: header.updated_at_slot(),
+        })
+    }
+}
+
+impl ValidateOracleTime for OracleTime {
+    fn oracle_updated_after(&self) -> CoreResult<Option<i64>> {
+        Ok(Some(self.after))
+    }
+
+    fn oracle_updated_before(&self) -> CoreResult<Option<i64>> {
+        Ok(self.before)
+    }
+
+    fn oracle_updated_after_slot(&self) -> CoreResult<Option<u64>> {
+        Ok(Some(self.after_slot))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytemuck::Zeroable;
+
+    use super::*;
+
+    #[test]
+    fn estimate_gas_lamports_for_compute_units() {
+        assert_eq!(estimate_gas_lamports(0), LAMPORTS_PER_SIGNATURE);
+        assert_eq!(
+            estimate_gas_lamports(EXECUTE_DEPOSIT_COMPUTE_UNITS),
+            LAMPORTS_PER_SIGNATURE + 40_000
+        );
+        assert_eq!(
+            estimate_gas_lamports(u64::MAX),
+            LAMPORTS_PER_SIGNATURE + u64::MAX / 1_000_000
+        );
+    }
+
+    #[test]
+    fn load_action_from_data() {
+        let mut deposit = Deposit::zeroed();
+        deposit.header.id = 42;
+        deposit.header.store = Pubkey::new_unique();
+        deposit.header.max_execution_lamports = 1_000;
+
+        // Trailing bytes are ignored.
+        let deposits = [deposit, Deposit::zeroed()];
+        let data: &[u8] = bytemuck::cast_slice(&deposits);
+        let loaded = load_action::<Deposit>(data).unwrap();
+        assert_eq!(loaded.header.id, 42);
+        assert_eq!(loaded.header.store, deposit.header.store);
+        assert_eq!(loaded.header.max_execution_lamports(), 1_000);
+
+        assert!(load_action::<Deposit>(&data[..std::mem::size_of::<Deposit>() - 1]).is_err());
+    }
+}
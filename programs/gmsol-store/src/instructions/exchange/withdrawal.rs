@@ -7,12 +7,14 @@ use gmsol_utils::InitSpace;
 
 use crate::{
     events::{EventEmitter, WithdrawalCreated},
+    instructions::user::initialize_user_if_needed,
     ops::withdrawal::{CreateWithdrawalOperation, CreateWithdrawalParams},
     states::{
         common::action::{Action, ActionExt},
         feature::{ActionDisabledFlag, DomainDisabledFlag},
+        user::{UserActionKind, UserHeader},
         withdrawal::Withdrawal,
-        Market, NonceBytes, RoleKey, Seed, Store, StoreWalletSigner,
+        Market, NonceBytes, PendingActionKind, RoleKey, Seed, Store, StoreWalletSigner,
     },
     utils::{
         internal,
@@ -44,6 +46,15 @@ pub struct CreateWithdrawal<'info> {
     /// Market.
     #[account(mut, has_one = store)]
     pub market: AccountLoader<'info, Market>,
+    /// User Account. It will be initialized if needed.
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = 8 + UserHeader::space(0),
+        seeds = [UserHeader::SEED, store.key().as_ref(), owner.key().as_ref()],
+        bump,
+    )]
+    pub user: AccountLoader<'info, UserHeader>,
     /// The withdrawal to be created.
     #[account(
         init,
@@ -125,7 +136,17 @@ impl<'info> internal::Create<'info, Withdrawal> for CreateWithdrawal<'info> {
         bumps: &Self::Bumps,
         remaining_accounts: &'info [AccountInfo<'info>],
     ) -> Result<()> {
+        initialize_user_if_needed(&self.user, &self.store.key(), self.owner.key, bumps.user)?;
+        self.user.load()?.validate_not_frozen()?;
+        let clock = Clock::get()?;
+        let max_trades_per_block = self.store.load()?.max_trades_per_block_per_user();
+        self.user
+            .load_mut()?
+            .record_trade_in_block(clock.slot, max_trades_per_block)?;
         self.transfer_tokens(params)?;
+        self.user
+            .load_mut()?
+            .record_action(UserActionKind::Withdrawal, clock.unix_timestamp);
         CreateWithdrawalOperation::builder()
             .withdrawal(self.withdrawal.clone())
             .market(self.market.clone())
@@ -141,6 +162,15 @@ impl<'info> internal::Create<'info, Withdrawal> for CreateWithdrawal<'info> {
             .swap_paths(remaining_accounts)
             .build()
             .execute()?;
+        let max_pending = self
+            .store
+            .load()?
+            .max_pending_actions(PendingActionKind::Withdrawal);
+        self.market.load_mut()?.indexer_mut().occupy_pending_slot(
+            PendingActionKind::Withdrawal,
+            max_pending,
+            &mut self.withdrawal.load_mut()?.header,
+        )?;
         emit!(WithdrawalCreated::new(
             self.store.key(),
             self.withdrawal.key(),
@@ -296,6 +326,14 @@ impl<'info> internal::Close<'info, Withdrawal> for CloseWithdrawal<'info> {
         bumps.store_wallet
     }
 
+    fn release_pending_slot(&self, remaining_accounts: &'info [AccountInfo<'info>]) -> Result<()> {
+        internal::release_pending_slot(
+            PendingActionKind::Withdrawal,
+            &mut self.withdrawal.load_mut()?.header,
+            remaining_accounts,
+        )
+    }
+
     fn validate(&self) -> Result<()> {
         let withdrawal = self.withdrawal.load()?;
         if withdrawal.header.action_state()?.is_pending() {
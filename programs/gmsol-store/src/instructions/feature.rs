@@ -1,11 +1,14 @@
 use anchor_lang::prelude::*;
+use gmsol_utils::InitSpace;
 
 use crate::{
     states::{
         feature::{ActionDisabledFlag, DomainDisabledFlag},
-        Store,
+        keeper::{KeeperDeadlineExtension, KeeperPriority},
+        Seed, Store,
     },
     utils::internal,
+    CoreError,
 };
 
 /// The accounts definition for [`toggle_feature`](crate::gmsol_store::toggle_feature).
@@ -42,3 +45,179 @@ impl<'info> internal::Authentication<'info> for ToggleFeature<'info> {
         &self.store
     }
 }
+
+/// The accounts definition for [`grant_keeper_deadline_extension`](crate::gmsol_store::grant_keeper_deadline_extension).
+#[derive(Accounts)]
+pub struct GrantKeeperDeadlineExtension<'info> {
+    /// Authority.
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    /// Store.
+    pub store: AccountLoader<'info, Store>,
+    /// The deadline extension account of the store.
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + KeeperDeadlineExtension::INIT_SPACE,
+        seeds = [KeeperDeadlineExtension::SEED, store.key().as_ref()],
+        bump,
+    )]
+    pub deadline_extension: AccountLoader<'info, KeeperDeadlineExtension>,
+    /// The system program.
+    pub system_program: Program<'info, System>,
+}
+
+/// Grant or update the keeper deadline extension.
+/// CHECK: only `FEATURE_KEEPER` can use this instruction.
+pub(crate) fn unchecked_grant_keeper_deadline_extension(
+    ctx: Context<GrantKeeperDeadlineExtension>,
+    extension_secs: u32,
+    duration_secs: u32,
+) -> Result<()> {
+    let store = ctx.accounts.store.key();
+    match ctx.accounts.deadline_extension.load_init() {
+        Ok(mut extension) => {
+            extension.init(ctx.bumps.deadline_extension, &store);
+        }
+        Err(Error::AnchorError(err)) => {
+            if err.error_code_number != ErrorCode::AccountDiscriminatorAlreadySet as u32 {
+                return Err(Error::AnchorError(err));
+            }
+        }
+        Err(err) => {
+            return Err(err);
+        }
+    }
+    ctx.accounts.deadline_extension.exit(&crate::ID)?;
+
+    let mut extension = ctx.accounts.deadline_extension.load_mut()?;
+    require_keys_eq!(extension.store, store, CoreError::StoreMismatched);
+    extension.grant(
+        ctx.accounts.authority.key,
+        extension_secs,
+        duration_secs,
+        Clock::get()?.unix_timestamp,
+    )?;
+    msg!(
+        "[Keeper] granted a deadline extension of {}s, valid until {}",
+        extension_secs,
+        extension.valid_until()
+    );
+    Ok(())
+}
+
+impl<'info> internal::Authentication<'info> for GrantKeeperDeadlineExtension<'info> {
+    fn authority(&self) -> &Signer<'info> {
+        &self.authority
+    }
+
+    fn store(&self) -> &AccountLoader<'info, Store> {
+        &self.store
+    }
+}
+
+/// The accounts definition for [`revoke_keeper_deadline_extension`](crate::gmsol_store::revoke_keeper_deadline_extension).
+#[derive(Accounts)]
+pub struct RevokeKeeperDeadlineExtension<'info> {
+    /// Authority.
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    /// Store.
+    pub store: AccountLoader<'info, Store>,
+    /// The deadline extension account to close.
+    #[account(
+        mut,
+        close = authority,
+        has_one = store,
+        seeds = [KeeperDeadlineExtension::SEED, store.key().as_ref()],
+        bump = deadline_extension.load()?.bump,
+    )]
+    pub deadline_extension: AccountLoader<'info, KeeperDeadlineExtension>,
+}
+
+/// Revoke the keeper deadline extension.
+/// CHECK: only `FEATURE_KEEPER` can use this instruction.
+pub(crate) fn unchecked_revoke_keeper_deadline_extension(
+    _ctx: Context<RevokeKeeperDeadlineExtension>,
+) -> Result<()> {
+    Ok(())
+}
+
+impl<'info> internal::Authentication<'info> for RevokeKeeperDeadlineExtension<'info> {
+    fn authority(&self) -> &Signer<'info> {
+        &self.authority
+    }
+
+    fn store(&self) -> &AccountLoader<'info, Store> {
+        &self.store
+    }
+}
+
+/// The accounts definition for [`set_keeper_priority`](crate::gmsol_store::set_keeper_priority).
+#[derive(Accounts)]
+#[instruction(keeper: Pubkey)]
+pub struct SetKeeperPriority<'info> {
+    /// Authority.
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    /// Store.
+    pub store: AccountLoader<'info, Store>,
+    /// The priority account of the keeper.
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + KeeperPriority::INIT_SPACE,
+        seeds = [KeeperPriority::SEED, store.key().as_ref(), keeper.as_ref()],
+        bump,
+    )]
+    pub keeper_priority: AccountLoader<'info, KeeperPriority>,
+    /// The system program.
+    pub system_program: Program<'info, System>,
+}
+
+/// Create or update the priority config of the given keeper.
+/// CHECK: only ADMIN can use this instruction.
+pub(crate) fn unchecked_set_keeper_priority(
+    ctx: Context<SetKeeperPriority>,
+    keeper: Pubkey,
+    priority: u8,
+    max_concurrent_executions: u16,
+    order_kinds: Vec<u8>,
+) -> Result<()> {
+    let store = ctx.accounts.store.key();
+    match ctx.accounts.keeper_priority.load_init() {
+        Ok(mut keeper_priority) => {
+            keeper_priority.init(ctx.bumps.keeper_priority, &store, &keeper);
+        }
+        Err(Error::AnchorError(err)) => {
+            if err.error_code_number != ErrorCode::AccountDiscriminatorAlreadySet as u32 {
+                return Err(Error::AnchorError(err));
+            }
+        }
+        Err(err) => {
+            return Err(err);
+        }
+    }
+    ctx.accounts.keeper_priority.exit(&crate::ID)?;
+
+    let mut keeper_priority = ctx.accounts.keeper_priority.load_mut()?;
+    require_keys_eq!(keeper_priority.store, store, CoreError::StoreMismatched);
+    require_keys_eq!(keeper_priority.keeper, keeper, CoreError::InvalidArgument);
+    keeper_priority.set(priority, max_concurrent_executions, &order_kinds)?;
+    msg!(
+        "[Keeper] set priority of keeper {} to {}",
+        keeper,
+        keeper_priority.priority
+    );
+    Ok(())
+}
+
+impl<'info> internal::Authentication<'info> for SetKeeperPriority<'info> {
+    fn authority(&self) -> &Signer<'info> {
+        &self.authority
+    }
+
+    fn store(&self) -> &AccountLoader<'info, Store> {
+        &self.store
+    }
+}
@@ -1,6 +1,6 @@
 use anchor_lang::prelude::*;
 use anchor_spl::{
-    associated_token::AssociatedToken,
+    associated_token::{get_associated_token_address_with_program_id, AssociatedToken},
     token::{Mint, Token, TokenAccount},
     token_2022::Token2022,
     token_interface,
@@ -10,6 +10,7 @@ use gmsol_utils::InitSpace;
 use crate::{
     constants,
     events::EventEmitter,
+    instructions::user::initialize_user_if_needed,
     ops::{
         execution_fee::PayExecutionFeeOperation,
         glv::{CreateGlvDepositOperation, CreateGlvDepositParams, ExecuteGlvDepositOperation},
@@ -19,8 +20,8 @@ use crate::{
         common::action::{Action, ActionExt, ActionSigner},
         feature::{ActionDisabledFlag, DomainDisabledFlag},
         glv::{GlvMarketFlag, SplitAccountsForGlv},
-        Chainlink, Glv, GlvDeposit, Market, NonceBytes, Oracle, RoleKey, Seed, Store,
-        StoreWalletSigner, TokenMapHeader, TokenMapLoader,
+        Chainlink, Glv, GlvDeposit, GlvDepositCooldown, Market, NonceBytes, Oracle, RoleKey, Seed,
+        Store, StoreWalletSigner, TokenMapHeader, TokenMapLoader, UserHeader,
     },
     utils::{
         internal,
@@ -51,6 +52,15 @@ pub struct CreateGlvDeposit<'info> {
         constraint = market.load()?.meta().market_token_mint == market_token.key() @ CoreError::MarketTokenMintMismatched,
     )]
     pub market: AccountLoader<'info, Market>,
+    /// User Account. It will be initialized if needed.
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = 8 + UserHeader::space(0),
+        seeds = [UserHeader::SEED, store.key().as_ref(), owner.key().as_ref()],
+        bump,
+    )]
+    pub user: AccountLoader<'info, UserHeader>,
     /// GLV.
     #[account(
         has_one = store,
@@ -67,6 +77,21 @@ pub struct CreateGlvDeposit<'info> {
         bump,
     )]
     pub glv_deposit: AccountLoader<'info, GlvDeposit>,
+    /// The GLV token ATA of the owner.
+    /// CHECK: only the address is used.
+    #[account(
+        constraint = is_associated_token_account_with_program_id(glv_token_ata.key, owner.key, &glv_token.key(), &glv_token_program.key()) @ CoreError::NotAnATA,
+    )]
+    pub glv_token_ata: UncheckedAccount<'info>,
+    /// The deposit cooldown of the GLV token ATA of the owner.
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = 8 + GlvDepositCooldown::INIT_SPACE,
+        seeds = [GlvDepositCooldown::SEED, glv.key().as_ref(), glv_token_ata.key().as_ref()],
+        bump,
+    )]
+    pub glv_deposit_cooldown: AccountLoader<'info, GlvDepositCooldown>,
     /// GLV Token.
     pub glv_token: Box<InterfaceAccount<'info, token_interface::Mint>>,
     /// Market token.
@@ -161,7 +186,10 @@ impl<'info> internal::Create<'info, GlvDeposit> for CreateGlvDeposit<'info> {
         bumps: &Self::Bumps,
         remaining_accounts: &'info [AccountInfo<'info>],
     ) -> Result<()> {
+        initialize_user_if_needed(&self.user, &self.store.key(), self.owner.key, bumps.user)?;
+        self.user.load()?.validate_not_frozen()?;
         self.transfer_tokens(params)?;
+        self.initialize_glv_deposit_cooldown_if_needed(bumps.glv_deposit_cooldown)?;
         CreateGlvDepositOperation::builder()
             .glv_deposit(self.glv_deposit.clone())
             .market(self.market.clone())
@@ -183,6 +211,32 @@ impl<'info> internal::Create<'info, GlvDeposit> for CreateGlvDeposit<'info> {
 }
 
 impl CreateGlvDeposit<'_> {
+    /// Initialize the deposit cooldown of the GLV token ATA of the owner if needed.
+    ///
+    /// The deposit time is recorded on execution.
+    fn initialize_glv_deposit_cooldown_if_needed(&self, bump: u8) -> Result<()> {
+        match self.glv_deposit_cooldown.load_init() {
+            Ok(mut cooldown) => {
+                cooldown.init(
+                    bump,
+                    &self.store.key(),
+                    &self.glv.key(),
+                    self.glv_token_ata.key,
+                );
+            }
+            Err(Error::AnchorError(err)) => {
+                if err.error_code_number != ErrorCode::AccountDiscriminatorAlreadySet as u32 {
+                    return Err(Error::AnchorError(err));
+                }
+            }
+            Err(err) => {
+                return Err(err);
+            }
+        }
+        self.glv_deposit_cooldown.exit(&crate::ID)?;
+        Ok(())
+    }
+
     fn transfer_tokens(&mut self, params: &CreateGlvDepositParams) -> Result<()> {
         use anchor_spl::token::{transfer_checked, TransferChecked};
 
@@ -588,10 +642,12 @@ pub struct ExecuteGlvDeposit<'info> {
     /// Authority.
     pub authority: Signer<'info>,
     /// Store.
-    #[account(has_one = token_map)]
     pub store: AccountLoader<'info, Store>,
     /// Token Map.
-    #[account(has_one = store)]
+    #[account(
+        has_one = store,
+        constraint = market.load()?.resolve_token_map(&*store.load()?) == Some(&token_map.key()) @ CoreError::TokenMapMismatched,
+    )]
     pub token_map: AccountLoader<'info, TokenMapHeader>,
     /// Oracle buffer to use.
     #[account(mut, has_one = store)]
@@ -699,6 +755,22 @@ pub struct ExecuteGlvDeposit<'info> {
         associated_token::authority = glv,
     )]
     pub market_token_vault: Box<Account<'info, TokenAccount>>,
+    /// The deposit cooldown of the GLV token ATA of the owner.
+    /// CHECK: it is only updated if it has been initialized.
+    #[account(
+        mut,
+        seeds = [
+            GlvDepositCooldown::SEED,
+            glv.key().as_ref(),
+            get_associated_token_address_with_program_id(
+                &glv_deposit.load()?.header.owner,
+                &glv_token.key(),
+                &glv_token_program.key(),
+            ).as_ref(),
+        ],
+        bump,
+    )]
+    pub glv_deposit_cooldown: UncheckedAccount<'info>,
     /// The token program.
     pub token_program: Program<'info, Token>,
     /// The token program for GLV token.
@@ -757,6 +829,7 @@ pub(crate) fn unchecked_execute_glv_deposit<'info>(
 
     if executed {
         accounts.glv_deposit.load_mut()?.header.completed()?;
+        accounts.record_deposit()?;
     } else {
         accounts.glv_deposit.load_mut()?.header.cancelled()?;
         accounts.transfer_tokens_out(remaining_accounts, &event_emitter)?;
@@ -778,6 +851,22 @@ impl<'info> internal::Authentication<'info> for ExecuteGlvDeposit<'info> {
 }
 
 impl<'info> ExecuteGlvDeposit<'info> {
+    /// Record the deposit time for the GLV token ATA of the owner if the GLV tokens
+    /// are delivered to the owner.
+    ///
+    /// The cooldown account is not initialized for deposits that are not created by
+    /// `create_glv_deposit` (e.g., the rebalance deposits), in which case nothing is recorded.
+    fn record_deposit(&self) -> Result<()> {
+        let glv_deposit = self.glv_deposit.load()?;
+        if glv_deposit.header.receiver() == glv_deposit.header.owner {
+            GlvDepositCooldown::record_deposit_of(
+                &self.glv_deposit_cooldown,
+                Clock::get()?.unix_timestamp,
+            )?;
+        }
+        Ok(())
+    }
+
     #[inline(never)]
     fn pay_execution_fee(&self, execution_fee: u64) -> Result<()> {
         let execution_lamports = self.glv_deposit.load()?.execution_lamports(execution_fee);
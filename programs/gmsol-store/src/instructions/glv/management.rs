@@ -6,10 +6,12 @@ use anchor_spl::{
     token_2022::Token2022,
     token_interface::{self, Mint},
 };
+use gmsol_model::price::Prices;
 use gmsol_utils::InitSpace;
 
 use crate::{
     constants,
+    ops::glv::get_glv_value_for_market,
     states::{
         glv::{Glv, UpdateGlvParams},
         Market, Seed, Store,
@@ -231,6 +233,19 @@ pub(crate) fn unchecked_update_glv_market_config(
     Ok(())
 }
 
+/// Set the min amount of the given market.
+///
+/// # CHECK
+/// - Only MARKET_KEEPER is allowed to call this function.
+pub(crate) fn unchecked_set_glv_market_min_amount(
+    ctx: Context<UpdateGlvMarketConfig>,
+    min_amount: u64,
+) -> Result<()> {
+    let mut glv = ctx.accounts.glv.load_mut()?;
+    glv.set_market_min_amount(&ctx.accounts.market_token.key(), min_amount)?;
+    Ok(())
+}
+
 /// Toggle flag of the given market.
 ///
 /// # CHECK
@@ -475,3 +490,96 @@ impl<'info> internal::Authentication<'info> for RemoveGlvMarket<'info> {
         &self.store
     }
 }
+
+/// The accounts definition for read-only instructions for GLV.
+///
+/// Remaining accounts expected by this instruction:
+///
+///   - 0..N. `[]` N market accounts, where N represents the total number of markets managed
+///     by the given GLV.
+///   - N..2N. `[]` N market token accounts (see above for the definition of N), sorted by
+///     addresses.
+#[derive(Accounts)]
+pub struct ReadGlv<'info> {
+    /// GLV.
+    #[account(has_one = glv_token)]
+    pub glv: AccountLoader<'info, Glv>,
+    /// GLV token.
+    pub glv_token: InterfaceAccount<'info, token_interface::Mint>,
+}
+
+/// Get GLV token price.
+pub(crate) fn get_glv_token_price<'info>(
+    ctx: Context<'_, '_, 'info, 'info, ReadGlv<'info>>,
+    prices: &[Prices<u128>],
+    maximize: bool,
+) -> Result<u128> {
+    let glv = ctx.accounts.glv.load()?;
+    let store = glv.store;
+    let len = glv.num_markets();
+
+    require_eq!(prices.len(), len, CoreError::InvalidArgument);
+    require_gte!(
+        ctx.remaining_accounts.len(),
+        len * 2,
+        CoreError::InvalidArgument
+    );
+
+    let markets = &ctx.remaining_accounts[0..len];
+    let market_tokens = &ctx.remaining_accounts[len..(len * 2)];
+
+    let mut value = 0u128;
+    for (idx, expected_market_token) in glv.market_tokens().enumerate() {
+        let market_token = &market_tokens[idx];
+        require_keys_eq!(
+            market_token.key(),
+            expected_market_token,
+            CoreError::MarketTokenMintMismatched
+        );
+
+        let balance = u128::from(
+            glv.market_config(&expected_market_token)
+                .ok_or_else(|| error!(CoreError::NotFound))?
+                .balance(),
+        );
+
+        // Markets with zero balance contribute nothing to the GLV value.
+        if balance == 0 {
+            continue;
+        }
+
+        let market = AccountLoader::<Market>::try_from(&markets[idx])?;
+        let market = market.load()?;
+        let meta = market.validated_meta(&store)?;
+        require_keys_eq!(
+            meta.market_token_mint,
+            expected_market_token,
+            CoreError::MarketTokenMintMismatched
+        );
+        let mint = Account::<anchor_spl::token::Mint>::try_from(market_token)?;
+
+        let (value_for_market, _, _) = get_glv_value_for_market(
+            &prices[idx],
+            &market.as_liquidity_market(&mint),
+            balance,
+            maximize,
+        )?;
+
+        value = value
+            .checked_add(value_for_market)
+            .ok_or_else(|| error!(CoreError::ValueOverflow))?;
+    }
+
+    let supply = u128::from(ctx.accounts.glv_token.supply);
+
+    // The price of the first mint is one USD per GLV token.
+    if supply == 0 {
+        return Ok(constants::MARKET_USD_UNIT);
+    }
+
+    let one = constants::MARKET_USD_UNIT / constants::MARKET_USD_TO_AMOUNT_DIVISOR;
+    let price = gmsol_model::utils::market_token_amount_to_usd(&one, &value, &supply)
+        .ok_or_else(|| error!(CoreError::ValueOverflow))?;
+
+    Ok(price)
+}
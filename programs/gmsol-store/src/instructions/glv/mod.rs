@@ -1,9 +1,11 @@
 mod deposit;
 mod management;
+mod rebalance;
 mod shift;
 mod withdrawal;
 
 pub use deposit::*;
 pub use management::*;
+pub use rebalance::*;
 pub use shift::*;
 pub use withdrawal::*;
@@ -0,0 +1,472 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{transfer_checked, Mint, Token, TokenAccount, TransferChecked},
+    token_2022::Token2022,
+    token_interface,
+};
+use gmsol_utils::InitSpace;
+
+use crate::{
+    ops::{
+        execution_fee::TransferExecutionFeeOperation,
+        glv::{CreateGlvDepositOperation, CreateGlvDepositParams},
+    },
+    states::{
+        common::action::Action,
+        feature::{ActionDisabledFlag, DomainDisabledFlag},
+        glv::GlvMarketFlag,
+        Chainlink, Glv, GlvDeposit, GlvTreasury, Market, Oracle, Seed, Store, TokenMapHeader,
+    },
+    utils::internal,
+    CoreError,
+};
+
+/// The accounts definition for [`initialize_glv_treasury`](crate::gmsol_store::initialize_glv_treasury)
+/// instruction.
+#[derive(Accounts)]
+pub struct InitializeGlvTreasury<'info> {
+    /// Authority.
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    /// Store.
+    pub store: AccountLoader<'info, Store>,
+    /// GLV.
+    #[account(has_one = store)]
+    pub glv: AccountLoader<'info, Glv>,
+    /// The GLV treasury to initialize.
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + GlvTreasury::INIT_SPACE,
+        seeds = [GlvTreasury::SEED, store.key().as_ref(), glv.key().as_ref()],
+        bump,
+    )]
+    pub glv_treasury: AccountLoader<'info, GlvTreasury>,
+    /// The system program.
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> internal::Authentication<'info> for InitializeGlvTreasury<'info> {
+    fn authority(&self) -> &Signer<'info> {
+        &self.authority
+    }
+
+    fn store(&self) -> &AccountLoader<'info, Store> {
+        &self.store
+    }
+}
+
+/// Initialize the GLV treasury.
+///
+/// # CHECK
+/// - Only MARKET_KEEPER is allowed to call this function.
+pub(crate) fn unchecked_initialize_glv_treasury(
+    ctx: Context<InitializeGlvTreasury>,
+    max_rebalance_value_per_day: u128,
+) -> Result<()> {
+    ctx.accounts.glv_treasury.load_init()?.init(
+        ctx.bumps.glv_treasury,
+        ctx.accounts.store.key(),
+        ctx.accounts.glv.key(),
+        max_rebalance_value_per_day,
+    );
+    Ok(())
+}
+
+/// The accounts definition for [`update_glv_treasury`](crate::gmsol_store::update_glv_treasury)
+/// instruction.
+#[derive(Accounts)]
+pub struct UpdateGlvTreasury<'info> {
+    /// Authority.
+    pub authority: Signer<'info>,
+    /// Store.
+    pub store: AccountLoader<'info, Store>,
+    /// The GLV treasury to update.
+    #[account(mut, has_one = store)]
+    pub glv_treasury: AccountLoader<'info, GlvTreasury>,
+}
+
+impl<'info> internal::Authentication<'info> for UpdateGlvTreasury<'info> {
+    fn authority(&self) -> &Signer<'info> {
+        &self.authority
+    }
+
+    fn store(&self) -> &AccountLoader<'info, Store> {
+        &self.store
+    }
+}
+
+/// Update the GLV treasury.
+///
+/// # CHECK
+/// - Only MARKET_KEEPER is allowed to call this function.
+pub(crate) fn unchecked_update_glv_treasury(
+    ctx: Context<UpdateGlvTreasury>,
+    max_rebalance_value_per_day: u128,
+) -> Result<()> {
+    ctx.accounts
+        .glv_treasury
+        .load_mut()?
+        .set_max_rebalance_value_per_day(max_rebalance_value_per_day);
+    Ok(())
+}
+
+/// The accounts definition for [`withdraw_from_glv_treasury`](crate::gmsol_store::withdraw_from_glv_treasury)
+/// instruction.
+#[derive(Accounts)]
+pub struct WithdrawFromGlvTreasury<'info> {
+    /// Authority.
+    pub authority: Signer<'info>,
+    /// Store.
+    pub store: AccountLoader<'info, Store>,
+    /// The GLV treasury to withdraw from.
+    #[account(has_one = store)]
+    pub glv_treasury: AccountLoader<'info, GlvTreasury>,
+    /// The token to withdraw.
+    pub token: InterfaceAccount<'info, token_interface::Mint>,
+    /// The treasury vault for the token.
+    #[account(
+        mut,
+        associated_token::mint = token,
+        associated_token::authority = glv_treasury,
+        associated_token::token_program = token_program,
+    )]
+    pub vault: InterfaceAccount<'info, token_interface::TokenAccount>,
+    /// The token account to receive the tokens.
+    #[account(mut, token::mint = token, token::token_program = token_program)]
+    pub target: InterfaceAccount<'info, token_interface::TokenAccount>,
+    /// The token program.
+    pub token_program: Interface<'info, token_interface::TokenInterface>,
+}
+
+impl<'info> internal::Authentication<'info> for WithdrawFromGlvTreasury<'info> {
+    fn authority(&self) -> &Signer<'info> {
+        &self.authority
+    }
+
+    fn store(&self) -> &AccountLoader<'info, Store> {
+        &self.store
+    }
+}
+
+/// Withdraw tokens from the GLV treasury.
+///
+/// # CHECK
+/// - Only ADMIN is allowed to call this function.
+pub(crate) fn unchecked_withdraw_from_glv_treasury(
+    ctx: Context<WithdrawFromGlvTreasury>,
+    amount: u64,
+) -> Result<()> {
+    require_neq!(amount, 0, CoreError::InvalidArgument);
+    require_keys_neq!(
+        ctx.accounts.vault.key(),
+        ctx.accounts.target.key(),
+        CoreError::InvalidArgument
+    );
+
+    let treasury = ctx.accounts.glv_treasury.load()?;
+    let seeds = treasury.signer_seeds();
+    token_interface::transfer_checked(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            token_interface::TransferChecked {
+                from: ctx.accounts.vault.to_account_info(),
+                mint: ctx.accounts.token.to_account_info(),
+                to: ctx.accounts.target.to_account_info(),
+                authority: ctx.accounts.glv_treasury.to_account_info(),
+            },
+        )
+        .with_signer(&[&seeds]),
+        amount,
+        ctx.accounts.token.decimals,
+    )?;
+
+    Ok(())
+}
+
+/// The accounts definition for
+/// [`trigger_automatic_rebalance_deposit`](crate::gmsol_store::trigger_automatic_rebalance_deposit)
+/// instruction.
+///
+/// The created GLV deposit is owned by the [`treasury_account`](Self::treasury_account),
+/// so the minted GLV tokens and any refunds are sent back to the treasury.
+///
+/// Remaining accounts expected by this instruction:
+///
+///   - 0. `[]` The feed account for the initial token.
+#[derive(Accounts)]
+pub struct AutoRebalanceDeposit<'info> {
+    /// Authority.
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    /// Store.
+    pub store: AccountLoader<'info, Store>,
+    /// Token map.
+    #[account(
+        has_one = store,
+        constraint = market.load()?.resolve_token_map(&*store.load()?) == Some(&token_map.key()) @ CoreError::TokenMapMismatched,
+    )]
+    pub token_map: AccountLoader<'info, TokenMapHeader>,
+    /// The oracle buffer to use.
+    #[account(mut, has_one = store)]
+    pub oracle: AccountLoader<'info, Oracle>,
+    /// Market.
+    #[account(
+        mut,
+        has_one = store,
+        constraint = market.load()?.meta().market_token_mint == market_token.key() @ CoreError::MarketTokenMintMismatched,
+    )]
+    pub market: AccountLoader<'info, Market>,
+    /// GLV.
+    #[account(
+        has_one = store,
+        constraint = glv.load()?.glv_token == glv_token.key() @ CoreError::TokenMintMismatched,
+        constraint = glv.load()?.contains(&market_token.key()) @ CoreError::InvalidArgument,
+    )]
+    pub glv: AccountLoader<'info, Glv>,
+    /// The GLV treasury.
+    #[account(
+        mut,
+        has_one = store,
+        has_one = glv,
+        seeds = [GlvTreasury::SEED, store.key().as_ref(), glv.key().as_ref()],
+        bump = treasury_account.load()?.bump,
+    )]
+    pub treasury_account: AccountLoader<'info, GlvTreasury>,
+    /// GLV deposit.
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + GlvDeposit::INIT_SPACE,
+        seeds = [
+            GlvDeposit::SEED,
+            store.key().as_ref(),
+            treasury_account.key().as_ref(),
+            &treasury_account.load()?.next_rebalance_deposit_nonce(),
+        ],
+        bump,
+    )]
+    pub glv_deposit: AccountLoader<'info, GlvDeposit>,
+    /// GLV token.
+    pub glv_token: Box<InterfaceAccount<'info, token_interface::Mint>>,
+    /// Market token.
+    pub market_token: Box<Account<'info, Mint>>,
+    /// The token to deposit, must be the long token or the short token of the market.
+    pub initial_token: Box<Account<'info, Mint>>,
+    /// The treasury vault for the initial token.
+    #[account(
+        mut,
+        associated_token::mint = initial_token,
+        associated_token::authority = treasury_account,
+    )]
+    pub treasury_vault: Box<Account<'info, TokenAccount>>,
+    /// The escrow account for GLV tokens.
+    #[account(
+        mut,
+        associated_token::mint = glv_token,
+        associated_token::authority = glv_deposit,
+        associated_token::token_program = glv_token_program,
+    )]
+    pub glv_token_escrow: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
+    /// The escrow account for market tokens.
+    #[account(
+        mut,
+        associated_token::mint = market_token,
+        associated_token::authority = glv_deposit,
+    )]
+    pub market_token_escrow: Box<Account<'info, TokenAccount>>,
+    /// The escrow account for the initial token.
+    #[account(
+        mut,
+        associated_token::mint = initial_token,
+        associated_token::authority = glv_deposit,
+    )]
+    pub initial_token_escrow: Box<Account<'info, TokenAccount>>,
+    /// The system program.
+    pub system_program: Program<'info, System>,
+    /// The token program.
+    pub token_program: Program<'info, Token>,
+    /// The token program for GLV token.
+    pub glv_token_program: Program<'info, Token2022>,
+    /// The associated token program.
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    /// Chainlink Program.
+    pub chainlink_program: Option<Program<'info, Chainlink>>,
+}
+
+impl<'info> internal::Authentication<'info> for AutoRebalanceDeposit<'info> {
+    fn authority(&self) -> &Signer<'info> {
+        &self.authority
+    }
+
+    fn store(&self) -> &AccountLoader<'info, Store> {
+        &self.store
+    }
+}
+
+/// Trigger an automatic rebalance deposit.
+///
+/// # CHECK
+/// - Only ORDER_KEEPER is allowed to call this function.
+pub(crate) fn unchecked_trigger_automatic_rebalance_deposit<'info>(
+    ctx: Context<'_, '_, 'info, 'info, AutoRebalanceDeposit<'info>>,
+    market_token: &Pubkey,
+    amount: u64,
+    min_market_token_amount: u64,
+    min_glv_token_amount: u64,
+) -> Result<()> {
+    let accounts = ctx.accounts;
+    require_keys_eq!(
+        accounts.market_token.key(),
+        *market_token,
+        CoreError::MarketTokenMintMismatched
+    );
+    require_neq!(amount, 0, CoreError::EmptyDeposit);
+    accounts.validate()?;
+
+    let value = accounts.rebalance_value(amount, ctx.remaining_accounts)?;
+
+    let nonce = {
+        let mut treasury = accounts.treasury_account.load_mut()?;
+        let nonce = treasury.next_rebalance_deposit_nonce();
+        treasury.record_rebalance(value, Clock::get()?.unix_timestamp)?;
+        nonce
+    };
+
+    let is_long = {
+        let market = accounts.market.load()?;
+        let meta = market.validated_meta(&accounts.store.key())?;
+        let initial_token = accounts.initial_token.key();
+        if initial_token == meta.long_token_mint {
+            true
+        } else if initial_token == meta.short_token_mint {
+            false
+        } else {
+            return err!(CoreError::TokenMintMismatched);
+        }
+    };
+
+    TransferExecutionFeeOperation::builder()
+        .payment(accounts.glv_deposit.to_account_info())
+        .payer(accounts.authority.to_account_info())
+        .execution_lamports(GlvDeposit::MIN_EXECUTION_LAMPORTS)
+        .system_program(accounts.system_program.to_account_info())
+        .build()
+        .execute()?;
+
+    accounts.transfer_from_treasury(amount)?;
+
+    let params = CreateGlvDepositParams {
+        execution_lamports: GlvDeposit::MIN_EXECUTION_LAMPORTS,
+        long_token_swap_length: 0,
+        short_token_swap_length: 0,
+        initial_long_token_amount: if is_long { amount } else { 0 },
+        initial_short_token_amount: if is_long { 0 } else { amount },
+        market_token_amount: 0,
+        min_market_token_amount,
+        min_glv_token_amount,
+        should_unwrap_native_token: false,
+    };
+    let (initial_long_token, initial_short_token) = if is_long {
+        (Some(&*accounts.initial_token_escrow), None)
+    } else {
+        (None, Some(&*accounts.initial_token_escrow))
+    };
+
+    CreateGlvDepositOperation::builder()
+        .glv_deposit(accounts.glv_deposit.clone())
+        .market(accounts.market.clone())
+        .store(accounts.store.clone())
+        .owner(accounts.treasury_account.as_ref())
+        .receiver(accounts.treasury_account.as_ref())
+        .nonce(&nonce)
+        .bump(ctx.bumps.glv_deposit)
+        .initial_long_token(initial_long_token)
+        .initial_short_token(initial_short_token)
+        .market_token(&accounts.market_token_escrow)
+        .glv_token(&accounts.glv_token_escrow)
+        .params(&params)
+        .swap_paths(&[])
+        .build()
+        .unchecked_execute()?;
+
+    msg!(
+        "[GLV] triggered rebalance deposit of {} into market {}",
+        amount,
+        market_token
+    );
+
+    Ok(())
+}
+
+impl<'info> AutoRebalanceDeposit<'info> {
+    /// Get the value of `amount` initial tokens, valued with the max price.
+    fn rebalance_value(
+        &self,
+        amount: u64,
+        remaining_accounts: &'info [AccountInfo<'info>],
+    ) -> Result<u128> {
+        let initial_token = self.initial_token.key();
+        self.oracle.load_mut()?.with_market_prices(
+            &self.store,
+            &self.market,
+            &self.token_map,
+            &[initial_token],
+            remaining_accounts,
+            self.chainlink_program.as_ref(),
+            |oracle, _remaining_accounts| {
+                let price = oracle.get_primary_price(&initial_token, false)?;
+                u128::from(amount)
+                    .checked_mul(price.max)
+                    .ok_or_else(|| error!(CoreError::ValueOverflow))
+            },
+        )
+    }
+
+    fn validate(&self) -> Result<()> {
+        self.store
+            .load()?
+            .validate_not_restarted()?
+            .validate_feature_enabled(DomainDisabledFlag::GlvDeposit, ActionDisabledFlag::Create)?;
+        let glv = self.glv.load()?;
+        let config = glv
+            .market_config(&self.market_token.key())
+            .ok_or_else(|| error!(CoreError::Internal))?;
+        require!(
+            config.get_flag(GlvMarketFlag::IsDepositAllowed),
+            CoreError::GlvDepositIsNotAllowed
+        );
+        require!(
+            config.is_below_min_amount(),
+            CoreError::GlvMarketNotBelowMinAllocation
+        );
+        Ok(())
+    }
+
+    fn transfer_from_treasury(&mut self, amount: u64) -> Result<()> {
+        {
+            let treasury = self.treasury_account.load()?;
+            let seeds = treasury.signer_seeds();
+            transfer_checked(
+                CpiContext::new(
+                    self.token_program.to_account_info(),
+                    TransferChecked {
+                        from: self.treasury_vault.to_account_info(),
+                        mint: self.initial_token.to_account_info(),
+                        to: self.initial_token_escrow.to_account_info(),
+                        authority: self.treasury_account.to_account_info(),
+                    },
+                )
+                .with_signer(&[&seeds]),
+                amount,
+                self.initial_token.decimals,
+            )?;
+        }
+
+        // Make sure the data for the escrow account is up-to-date.
+        self.initial_token_escrow.reload()?;
+
+        Ok(())
+    }
+}
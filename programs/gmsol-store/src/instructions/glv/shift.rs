@@ -1,4 +1,4 @@
-use anchor_lang::prelude::*;
+use anchor_lang::{prelude::*, system_program};
 use anchor_spl::{
     associated_token::AssociatedToken,
     token::{Mint, Token, TokenAccount},
@@ -7,9 +7,9 @@ use gmsol_utils::InitSpace;
 
 use crate::{
     constants,
-    events::EventEmitter,
+    events::{EventEmitter, GlvBatchShiftCreated},
     ops::{
-        execution_fee::PayExecutionFeeOperation,
+        execution_fee::{PayExecutionFeeOperation, TransferExecutionFeeOperation},
         glv::ExecuteGlvShiftOperation,
         shift::{CreateShiftOperation, CreateShiftParams},
     },
@@ -21,7 +21,7 @@ use crate::{
         Chainlink, Glv, Market, NonceBytes, Oracle, RoleKey, Seed, Store, StoreWalletSigner,
         TokenMapHeader,
     },
-    utils::internal,
+    utils::{internal, token::is_associated_token_account},
     CoreError,
 };
 
@@ -171,6 +171,214 @@ impl<'info> internal::Authentication<'info> for CreateGlvShift<'info> {
     }
 }
 
+/// The number of remaining accounts required by each GLV shift in a batch.
+const BATCH_SHIFT_ACCOUNTS_LEN: usize = 5;
+
+/// The accounts definition for [`batch_create_glv_shifts`](crate::batch_create_glv_shifts)
+/// instruction.
+///
+/// Remaining accounts expected by this instruction, for each GLV shift in the batch:
+///
+///   - 0. `[writable]` The GLV shift account to create, which must be a PDA derived from
+///     the SEED of [`GlvShift`], `store`, `authority` and the nonce given by
+///     [`Glv::batch_shift_nonce`].
+///   - 1. `[writable]` From market.
+///   - 2. `[]` To market.
+///   - 3. `[]` The vault of the GLV for from market tokens.
+///   - 4. `[]` The vault of the GLV for to market tokens.
+#[event_cpi]
+#[derive(Accounts)]
+pub struct BatchCreateGlvShifts<'info> {
+    /// Authority.
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    /// Store.
+    pub store: AccountLoader<'info, Store>,
+    /// GLV.
+    #[account(mut, has_one = store)]
+    pub glv: AccountLoader<'info, Glv>,
+    /// The system program.
+    pub system_program: Program<'info, System>,
+}
+
+/// Create a batch of GLV shifts.
+///
+/// # CHECK
+/// - Only ORDER_KEEPER is allowed to create GLV shifts.
+pub fn unchecked_batch_create_glv_shifts<'info>(
+    ctx: Context<'_, '_, 'info, 'info, BatchCreateGlvShifts<'info>>,
+    params: &[CreateShiftParams],
+) -> Result<()> {
+    let accounts = ctx.accounts;
+    let remaining_accounts = ctx.remaining_accounts;
+
+    require!(!params.is_empty(), CoreError::InvalidArgument);
+    require_gte!(
+        Glv::MAX_SHIFTS_PER_BATCH,
+        params.len(),
+        CoreError::ExceedMaxLengthLimit
+    );
+    require_eq!(
+        remaining_accounts.len(),
+        params.len() * BATCH_SHIFT_ACCOUNTS_LEN,
+        CoreError::InvalidArgument
+    );
+
+    accounts
+        .store
+        .load()?
+        .validate_not_restarted()?
+        .validate_feature_enabled(DomainDisabledFlag::GlvShift, ActionDisabledFlag::Create)?;
+    accounts.glv.load()?.validate_shift_interval()?;
+
+    let batch_nonce = accounts.glv.load_mut()?.next_batch_nonce()?;
+    let glv = accounts.glv.key();
+
+    for (index, (params, shift_accounts)) in params
+        .iter()
+        .zip(remaining_accounts.chunks_exact(BATCH_SHIFT_ACCOUNTS_LEN))
+        .enumerate()
+    {
+        // `index` is less than `MAX_SHIFTS_PER_BATCH`, so the conversion will not fail.
+        let nonce = Glv::batch_shift_nonce(&glv, batch_nonce, index as u8);
+        accounts.create_glv_shift(&nonce, params, shift_accounts)?;
+    }
+
+    let glv_token = accounts.glv.load()?.glv_token;
+    let event_emitter = EventEmitter::new(&accounts.event_authority, ctx.bumps.event_authority);
+    event_emitter.emit_cpi(&GlvBatchShiftCreated {
+        glv_token,
+        batch_nonce,
+        count: params.len() as u8,
+    })?;
+
+    Ok(())
+}
+
+impl<'info> internal::Authentication<'info> for BatchCreateGlvShifts<'info> {
+    fn authority(&self) -> &Signer<'info> {
+        &self.authority
+    }
+
+    fn store(&self) -> &AccountLoader<'info, Store> {
+        &self.store
+    }
+}
+
+impl<'info> BatchCreateGlvShifts<'info> {
+    #[inline(never)]
+    fn create_glv_shift(
+        &self,
+        nonce: &NonceBytes,
+        params: &CreateShiftParams,
+        shift_accounts: &'info [AccountInfo<'info>],
+    ) -> Result<()> {
+        let [glv_shift, from_market, to_market, from_market_token_vault, to_market_token_vault] =
+            shift_accounts
+        else {
+            return err!(CoreError::InvalidArgument);
+        };
+
+        let from_market = AccountLoader::<Market>::try_from(from_market)?;
+        let to_market = AccountLoader::<Market>::try_from(to_market)?;
+        let from_market_token_vault = Account::<TokenAccount>::try_from(from_market_token_vault)?;
+        let to_market_token_vault = Account::<TokenAccount>::try_from(to_market_token_vault)?;
+
+        // Validate the shift pair.
+        {
+            let glv_address = self.glv.key();
+            let glv = self.glv.load()?;
+            for vault in [&from_market_token_vault, &to_market_token_vault] {
+                require!(glv.contains(&vault.mint), CoreError::InvalidArgument);
+                require!(
+                    is_associated_token_account(&vault.key(), &glv_address, &vault.mint),
+                    CoreError::InvalidArgument
+                );
+            }
+            require_keys_neq!(
+                from_market_token_vault.mint,
+                to_market_token_vault.mint,
+                CoreError::InvalidShiftMarkets
+            );
+            let is_deposit_allowed = glv
+                .market_config(&to_market_token_vault.mint)
+                .ok_or_else(|| error!(CoreError::Internal))?
+                .get_flag(GlvMarketFlag::IsDepositAllowed);
+            require!(is_deposit_allowed, CoreError::GlvDepositIsNotAllowed);
+        }
+
+        // Initialize the GLV shift account.
+        let store = self.store.key();
+        let authority = self.authority.key();
+        let (address, bump) = Pubkey::find_program_address(
+            &[GlvShift::SEED, store.as_ref(), authority.as_ref(), nonce],
+            &crate::ID,
+        );
+        require_keys_eq!(glv_shift.key(), address, CoreError::InvalidArgument);
+        let space = 8 + GlvShift::INIT_SPACE;
+        system_program::create_account(
+            CpiContext::new_with_signer(
+                self.system_program.to_account_info(),
+                system_program::CreateAccount {
+                    from: self.authority.to_account_info(),
+                    to: glv_shift.clone(),
+                },
+                &[&[
+                    GlvShift::SEED,
+                    store.as_ref(),
+                    authority.as_ref(),
+                    nonce,
+                    &[bump],
+                ]],
+            ),
+            Rent::get()?.minimum_balance(space),
+            space as u64,
+            &crate::ID,
+        )?;
+        let glv_shift = AccountLoader::<GlvShift>::try_from_unchecked(&crate::ID, glv_shift)?;
+
+        {
+            let payer_seeds = self.glv.load()?.vec_signer_seeds();
+            let payer_seeds = payer_seeds
+                .iter()
+                .map(|seed| seed.as_slice())
+                .collect::<Vec<_>>();
+            TransferExecutionFeeOperation::builder()
+                .payment(glv_shift.to_account_info())
+                .payer(self.glv.to_account_info())
+                .execution_lamports(params.execution_lamports)
+                .system_program(self.system_program.to_account_info())
+                .signer_seeds(Some(&payer_seeds))
+                .build()
+                .execute()?;
+        }
+
+        CreateShiftOperation::builder()
+            .store(&self.store)
+            .owner(self.glv.as_ref())
+            .receiver(self.glv.as_ref())
+            .shift(&glv_shift)
+            .from_market(&from_market)
+            .from_market_token_account(&from_market_token_vault)
+            .to_market(&to_market)
+            .to_market_token_account(&to_market_token_vault)
+            .nonce(nonce)
+            .bump(bump)
+            .params(params)
+            .build()
+            .execute()?;
+
+        // Set the funder of the GLV shift.
+        glv_shift.exit(&crate::ID)?;
+        glv_shift
+            .load_mut()?
+            .header_mut()
+            .set_rent_receiver(authority);
+
+        Ok(())
+    }
+}
+
 /// The accounts definition for [`close_glv_shift`](crate::close_glv_shift) instruction.
 #[event_cpi]
 #[derive(Accounts)]
@@ -297,10 +505,13 @@ pub struct ExecuteGlvShift<'info> {
     /// Authority.
     pub authority: Signer<'info>,
     /// Store.
-    #[account(has_one = token_map)]
     pub store: AccountLoader<'info, Store>,
     /// Token Map.
-    #[account(has_one = store)]
+    #[account(
+        has_one = store,
+        constraint = from_market.load()?.resolve_token_map(&*store.load()?) == Some(&token_map.key()) @ CoreError::TokenMapMismatched,
+        constraint = to_market.load()?.resolve_token_map(&*store.load()?) == Some(&token_map.key()) @ CoreError::TokenMapMismatched,
+    )]
     pub token_map: AccountLoader<'info, TokenMapHeader>,
     /// Oracle buffer to use.
     #[account(mut, has_one = store)]
@@ -10,6 +10,7 @@ use gmsol_utils::InitSpace;
 use crate::{
     constants,
     events::EventEmitter,
+    instructions::user::initialize_user_if_needed,
     ops::{
         execution_fee::PayExecutionFeeOperation,
         glv::{
@@ -21,8 +22,8 @@ use crate::{
         common::action::{Action, ActionExt},
         feature::{ActionDisabledFlag, DomainDisabledFlag},
         glv::{GlvWithdrawal, SplitAccountsForGlv},
-        Chainlink, Glv, Market, NonceBytes, Oracle, RoleKey, Seed, Store, StoreWalletSigner,
-        TokenMapHeader, TokenMapLoader,
+        Chainlink, Glv, GlvDepositCooldown, Market, NonceBytes, Oracle, RoleKey, Seed, Store,
+        StoreWalletSigner, TokenMapHeader, TokenMapLoader, UserHeader,
     },
     utils::{
         internal,
@@ -53,6 +54,15 @@ pub struct CreateGlvWithdrawal<'info> {
         constraint = market.load()?.meta().market_token_mint == market_token.key() @ CoreError::MarketTokenMintMismatched,
     )]
     pub market: AccountLoader<'info, Market>,
+    /// User Account. It will be initialized if needed.
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = 8 + UserHeader::space(0),
+        seeds = [UserHeader::SEED, store.key().as_ref(), owner.key().as_ref()],
+        bump,
+    )]
+    pub user: AccountLoader<'info, UserHeader>,
     /// GLV.
     #[account(
         has_one = store,
@@ -80,6 +90,14 @@ pub struct CreateGlvWithdrawal<'info> {
     /// The source GLV token account.
     #[account(mut, token::mint = glv_token)]
     pub glv_token_source: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
+    /// The deposit cooldown of the source GLV token account.
+    /// CHECK: it is uninitialized if no GLV deposit has been made to the source
+    /// GLV token account, and is checked during the validation.
+    #[account(
+        seeds = [GlvDepositCooldown::SEED, glv.key().as_ref(), glv_token_source.key().as_ref()],
+        bump,
+    )]
+    pub glv_deposit_cooldown: UncheckedAccount<'info>,
     /// The escrow account for GLV tokens.
     #[account(
         mut,
@@ -142,6 +160,13 @@ impl<'info> internal::Create<'info, GlvWithdrawal> for CreateGlvWithdrawal<'info
                 DomainDisabledFlag::GlvWithdrawal,
                 ActionDisabledFlag::Create,
             )?;
+        if let Some(last_deposit_at) =
+            GlvDepositCooldown::last_deposit_at_of(&self.glv_deposit_cooldown)?
+        {
+            self.glv
+                .load()?
+                .validate_withdrawal_cooldown(last_deposit_at, Clock::get()?.unix_timestamp)?;
+        }
         Ok(())
     }
 
@@ -152,6 +177,8 @@ impl<'info> internal::Create<'info, GlvWithdrawal> for CreateGlvWithdrawal<'info
         bumps: &Self::Bumps,
         remaining_accounts: &'info [AccountInfo<'info>],
     ) -> Result<()> {
+        initialize_user_if_needed(&self.user, &self.store.key(), self.owner.key, bumps.user)?;
+        self.user.load()?.validate_not_frozen()?;
         self.transfer_glv_tokens(params)?;
         CreateGlvWithdrawalOperation::builder()
             .glv_withdrawal(self.glv_withdrawal.clone())
@@ -507,10 +534,12 @@ pub struct ExecuteGlvWithdrawal<'info> {
     /// Authority.
     pub authority: Signer<'info>,
     /// Store.
-    #[account(has_one = token_map)]
     pub store: AccountLoader<'info, Store>,
     /// Token Map.
-    #[account(has_one = store)]
+    #[account(
+        has_one = store,
+        constraint = market.load()?.resolve_token_map(&*store.load()?) == Some(&token_map.key()) @ CoreError::TokenMapMismatched,
+    )]
     pub token_map: AccountLoader<'info, TokenMapHeader>,
     /// Oracle buffer to use.
     #[account(mut, has_one = store)]
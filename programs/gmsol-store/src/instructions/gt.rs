@@ -3,8 +3,9 @@ use gmsol_utils::InitSpace;
 
 use crate::{
     events::{EventEmitter, GtUpdated},
+    instructions::ReadStore,
     states::{
-        gt::{GtExchange, GtExchangeVault},
+        gt::{GtExchange, GtExchangeVault, ReferralRewardFactorsProblem},
         user::UserHeader,
         Seed, Store,
     },
@@ -121,6 +122,19 @@ pub(crate) fn unchecked_gt_set_referral_reward_factors(
         .set_referral_reward_factors(factors)
 }
 
+/// Validate the referral reward factors without setting them.
+pub(crate) fn validate_referral_reward_factors(
+    ctx: Context<ReadStore>,
+    factors: &[u128],
+) -> Result<Vec<ReferralRewardFactorsProblem>> {
+    let store = ctx.accounts.store.load()?;
+    require!(
+        store.gt().is_initialized(),
+        CoreError::PreconditionsAreNotMet
+    );
+    Ok(store.gt().validate_referral_reward_factors(factors))
+}
+
 /// CHECK: only GT_CONTROLLER is authorized to use this instruction.
 #[cfg(feature = "test-only")]
 pub(crate) fn unchecked_gt_set_exchange_time_window(
@@ -253,6 +267,8 @@ pub struct RequestGtExchange<'info> {
 pub(crate) fn request_gt_exchange(ctx: Context<RequestGtExchange>, amount: u64) -> Result<()> {
     let accounts = ctx.accounts;
 
+    accounts.store.load()?.validate_not_paused()?;
+    accounts.user.load()?.validate_not_frozen()?;
     accounts.validate_and_init_exchange_if_needed(ctx.bumps.exchange)?;
 
     let mut store = accounts.store.load_mut()?;
@@ -282,6 +298,7 @@ impl RequestGtExchange<'_> {
                 )?;
                 drop(exchange);
                 self.exchange.exit(&crate::ID)?;
+                self.vault.load_mut()?.add_pending_exchange()?;
             }
             Err(Error::AnchorError(err)) => {
                 if err.error_code_number != ErrorCode::AccountDiscriminatorAlreadySet as u32 {
@@ -342,6 +359,7 @@ pub(crate) fn unchecked_confirm_gt_exchange_vault(
     ctx: Context<ConfirmGtExchangeVault>,
 ) -> Result<()> {
     let mut store = ctx.accounts.store.load_mut()?;
+    store.validate_not_paused()?;
     let mut vault = ctx.accounts.vault.load_mut()?;
     store
         .gt_mut()
@@ -371,8 +389,7 @@ pub struct CloseGtExchange<'info> {
         constraint = store.load()?.gt().is_initialized() @ CoreError::PreconditionsAreNotMet,
     )]
     pub store: AccountLoader<'info, Store>,
-    /// CHECK: only used to receive the funds.
-    #[account(mut)]
+    /// CHECK: only used as an identifier.
     pub owner: UncheckedAccount<'info>,
     #[account(
         mut,
@@ -383,8 +400,8 @@ pub struct CloseGtExchange<'info> {
     pub vault: AccountLoader<'info, GtExchangeVault>,
     #[account(
         mut,
-        close = owner,
         constraint = exchange.load()?.is_initialized() @ CoreError::InvalidArgument,
+        constraint = !exchange.load()?.is_completed() @ CoreError::PreconditionsAreNotMet,
         has_one = store,
         has_one = owner,
         has_one = vault,
@@ -396,10 +413,11 @@ pub struct CloseGtExchange<'info> {
 
 /// CHECK: only GT_CONTROLLER is allowed to use this instruction.
 pub(crate) fn unchecked_close_gt_exchange(ctx: Context<CloseGtExchange>) -> Result<()> {
-    let vault = ctx.accounts.vault.load()?;
-    let exchange = ctx.accounts.exchange.load()?;
+    let mut vault = ctx.accounts.vault.load_mut()?;
+    let mut exchange = ctx.accounts.exchange.load_mut()?;
+    exchange.complete(&mut vault)?;
     msg!(
-        "[GT] Closing confirmed exchange: vault_index = {}, vault = {}, owner = {}, amount = {}",
+        "[GT] Completed confirmed exchange: vault_index = {}, vault = {}, owner = {}, amount = {}",
         vault.time_window_index(),
         exchange.vault(),
         exchange.owner(),
@@ -417,3 +435,96 @@ impl<'info> internal::Authentication<'info> for CloseGtExchange<'info> {
         &self.store
     }
 }
+
+/// The accounts definition for [`reclaim_gt_exchange_rent`](crate::gmsol_store::reclaim_gt_exchange_rent) instruction.
+///
+/// Remaining accounts expected by this instruction:
+///
+///   - 0..N. `[writable]` N GT exchange vaults, where N is the `vaults` argument.
+///   - N..N+2M. `[writable]` M pairs of `[exchange, owner]`, where the `owner` is the
+///     owner of the `exchange` and will receive its rent.
+#[derive(Accounts)]
+pub struct ReclaimGtExchangeRent<'info> {
+    /// Authority.
+    pub authority: Signer<'info>,
+    /// Store.
+    #[account(
+        constraint = store.load()?.gt().is_initialized() @ CoreError::PreconditionsAreNotMet,
+    )]
+    pub store: AccountLoader<'info, Store>,
+    /// The receiver of the rent of the closed vaults.
+    /// CHECK: only used to receive the funds.
+    #[account(mut)]
+    pub receiver: UncheckedAccount<'info>,
+}
+
+/// CHECK: only GT_CONTROLLER is allowed to use this instruction.
+pub(crate) fn unchecked_reclaim_gt_exchange_rent<'info>(
+    ctx: Context<'_, '_, 'info, 'info, ReclaimGtExchangeRent<'info>>,
+    vaults: u8,
+) -> Result<()> {
+    let vaults = usize::from(vaults);
+    require_gte!(
+        ctx.remaining_accounts.len(),
+        vaults,
+        ErrorCode::AccountNotEnoughKeys
+    );
+    let (vaults, exchanges) = ctx.remaining_accounts.split_at(vaults);
+    require!(exchanges.len() % 2 == 0, CoreError::InvalidArgument);
+
+    let store = ctx.accounts.store.key();
+
+    let mut closed_exchanges = 0;
+    for pair in exchanges.chunks_exact(2) {
+        let (info, owner) = (&pair[0], &pair[1]);
+        let loader = AccountLoader::<GtExchange>::try_from(info)?;
+        {
+            let exchange = loader.load()?;
+            require!(exchange.is_initialized(), CoreError::InvalidArgument);
+            require_keys_eq!(*exchange.store(), store, CoreError::StoreMismatched);
+            require_keys_eq!(*exchange.owner(), owner.key(), CoreError::OwnerMismatched);
+            if !exchange.is_completed() {
+                msg!("[GT] Skipping uncompleted exchange: {}", info.key());
+                continue;
+            }
+        }
+        loader.close(owner.clone())?;
+        closed_exchanges += 1;
+    }
+
+    let mut closed_vaults = 0;
+    for info in vaults {
+        let loader = AccountLoader::<GtExchangeVault>::try_from(info)?;
+        {
+            let vault = loader.load()?;
+            require!(vault.is_initialized(), CoreError::InvalidArgument);
+            require_keys_eq!(vault.store, store, CoreError::StoreMismatched);
+            if !vault.is_closable() {
+                msg!(
+                    "[GT] Skipping unconfirmed vault or vault with pending exchanges: {}",
+                    info.key()
+                );
+                continue;
+            }
+        }
+        loader.close(ctx.accounts.receiver.to_account_info())?;
+        closed_vaults += 1;
+    }
+
+    msg!(
+        "[GT] Reclaimed rent: closed_vaults = {}, closed_exchanges = {}",
+        closed_vaults,
+        closed_exchanges
+    );
+    Ok(())
+}
+
+impl<'info> internal::Authentication<'info> for ReclaimGtExchangeRent<'info> {
+    fn authority(&self) -> &Signer<'info> {
+        &self.authority
+    }
+
+    fn store(&self) -> &AccountLoader<'info, Store> {
+        &self.store
+    }
+}
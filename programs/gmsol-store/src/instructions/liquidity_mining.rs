@@ -0,0 +1,395 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, Token, TokenAccount};
+use gmsol_utils::InitSpace;
+
+use crate::{
+    constants,
+    states::{
+        liquidity_mining::{CampaignParams, LiquidityMiningCampaign, LpBalanceCheckpoint},
+        Market, Seed, Store,
+    },
+    utils::internal,
+    CoreError,
+};
+
+/// The accounts definition for [`initialize_liquidity_mining_campaign`](crate::gmsol_store::initialize_liquidity_mining_campaign).
+///
+/// *[See also the documentation for the instruction.](crate::gmsol_store::initialize_liquidity_mining_campaign)*
+#[derive(Accounts)]
+#[instruction(campaign_index: u8)]
+pub struct InitializeLiquidityMiningCampaign<'info> {
+    /// Authority.
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    /// Store.
+    pub store: AccountLoader<'info, Store>,
+    /// The market of the market token.
+    #[account(
+        has_one = store,
+        constraint = market.load()?.meta.market_token_mint == market_token.key() @ CoreError::MarketTokenMintMismatched,
+    )]
+    pub market: AccountLoader<'info, Market>,
+    /// Market token.
+    pub market_token: Box<Account<'info, Mint>>,
+    /// Reward token.
+    #[account(constraint = reward_token.key() != market_token.key() @ CoreError::InvalidArgument)]
+    pub reward_token: Box<Account<'info, Mint>>,
+    /// The campaign to create.
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + LiquidityMiningCampaign::INIT_SPACE,
+        seeds = [LiquidityMiningCampaign::SEED, store.key().as_ref(), &[campaign_index]],
+        bump,
+    )]
+    pub campaign: AccountLoader<'info, LiquidityMiningCampaign>,
+    /// The vault for the staked market tokens.
+    #[account(
+        init,
+        payer = authority,
+        token::mint = market_token,
+        // We use the store as the authority of the token account.
+        token::authority = store,
+        seeds = [
+            constants::LIQUIDITY_MINING_VAULT_SEED,
+            campaign.key().as_ref(),
+            market_token.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub stake_vault: Box<Account<'info, TokenAccount>>,
+    /// The vault for the reward tokens.
+    #[account(
+        init,
+        payer = authority,
+        token::mint = reward_token,
+        // We use the store as the authority of the token account.
+        token::authority = store,
+        seeds = [
+            constants::LIQUIDITY_MINING_VAULT_SEED,
+            campaign.key().as_ref(),
+            reward_token.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub reward_vault: Box<Account<'info, TokenAccount>>,
+    /// The source of the reward tokens, owned by the authority.
+    #[account(mut, token::mint = reward_token)]
+    pub reward_source: Box<Account<'info, TokenAccount>>,
+    /// System Program.
+    pub system_program: Program<'info, System>,
+    /// Token Program.
+    pub token_program: Program<'info, Token>,
+}
+
+/// Initialize a liquidity mining campaign and deposit the rewards.
+///
+/// ## CHECK
+/// - Only ADMIN can initialize a liquidity mining campaign.
+pub(crate) fn unchecked_initialize_liquidity_mining_campaign(
+    ctx: Context<InitializeLiquidityMiningCampaign>,
+    campaign_index: u8,
+    params: &CampaignParams,
+) -> Result<()> {
+    ctx.accounts.campaign.load_init()?.init(
+        ctx.bumps.campaign,
+        campaign_index,
+        &ctx.accounts.store.key(),
+        &ctx.accounts.market_token.key(),
+        &ctx.accounts.reward_token.key(),
+        params,
+    )?;
+
+    token::transfer(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            token::Transfer {
+                from: ctx.accounts.reward_source.to_account_info(),
+                to: ctx.accounts.reward_vault.to_account_info(),
+                authority: ctx.accounts.authority.to_account_info(),
+            },
+        ),
+        params.total_reward,
+    )?;
+
+    msg!(
+        "[Liquidity Mining] initialized campaign {} for market token {}, from {} to {}",
+        campaign_index,
+        ctx.accounts.market_token.key(),
+        params.start_time,
+        params.end_time,
+    );
+    Ok(())
+}
+
+impl<'info> internal::Authentication<'info> for InitializeLiquidityMiningCampaign<'info> {
+    fn authority(&self) -> &Signer<'info> {
+        &self.authority
+    }
+
+    fn store(&self) -> &AccountLoader<'info, Store> {
+        &self.store
+    }
+}
+
+/// The accounts definition for [`stake_for_liquidity_mining`](crate::gmsol_store::stake_for_liquidity_mining).
+///
+/// *[See also the documentation for the instruction.](crate::gmsol_store::stake_for_liquidity_mining)*
+#[derive(Accounts)]
+#[instruction(campaign_index: u8)]
+pub struct StakeForLiquidityMining<'info> {
+    /// Owner.
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    /// Store.
+    pub store: AccountLoader<'info, Store>,
+    /// Campaign.
+    #[account(
+        mut,
+        has_one = store,
+        seeds = [LiquidityMiningCampaign::SEED, store.key().as_ref(), &[campaign_index]],
+        bump = campaign.load()?.bump,
+    )]
+    pub campaign: AccountLoader<'info, LiquidityMiningCampaign>,
+    /// The balance checkpoint of the owner.
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = 8 + LpBalanceCheckpoint::INIT_SPACE,
+        seeds = [LpBalanceCheckpoint::SEED, campaign.key().as_ref(), owner.key().as_ref()],
+        bump,
+    )]
+    pub checkpoint: AccountLoader<'info, LpBalanceCheckpoint>,
+    /// The source market token account.
+    #[account(mut, token::mint = stake_vault.mint)]
+    pub source: Account<'info, TokenAccount>,
+    /// The vault for the staked market tokens.
+    #[account(
+        mut,
+        constraint = stake_vault.mint == campaign.load()?.market_token @ CoreError::MarketTokenMintMismatched,
+        seeds = [
+            constants::LIQUIDITY_MINING_VAULT_SEED,
+            campaign.key().as_ref(),
+            stake_vault.mint.as_ref(),
+        ],
+        bump,
+    )]
+    pub stake_vault: Account<'info, TokenAccount>,
+    /// System Program.
+    pub system_program: Program<'info, System>,
+    /// Token Program.
+    pub token_program: Program<'info, Token>,
+}
+
+/// Stake market tokens into a liquidity mining campaign.
+pub(crate) fn stake_for_liquidity_mining(
+    ctx: Context<StakeForLiquidityMining>,
+    amount: u64,
+) -> Result<()> {
+    require_neq!(amount, 0, CoreError::InvalidArgument);
+
+    match ctx.accounts.checkpoint.load_init() {
+        Ok(mut checkpoint) => {
+            checkpoint.init(
+                ctx.bumps.checkpoint,
+                &ctx.accounts.campaign.key(),
+                ctx.accounts.owner.key,
+            );
+        }
+        Err(Error::AnchorError(err)) => {
+            if err.error_code_number != ErrorCode::AccountDiscriminatorAlreadySet as u32 {
+                return Err(Error::AnchorError(err));
+            }
+        }
+        Err(err) => {
+            return Err(err);
+        }
+    }
+    ctx.accounts.checkpoint.exit(&crate::ID)?;
+
+    {
+        let now = Clock::get()?.unix_timestamp;
+        let mut campaign = ctx.accounts.campaign.load_mut()?;
+        require!(
+            !campaign.is_ended_at(now),
+            CoreError::PreconditionsAreNotMet
+        );
+        let mut checkpoint = ctx.accounts.checkpoint.load_mut()?;
+        campaign.accrue(now)?;
+        checkpoint.update(&campaign)?;
+        checkpoint.stake(amount)?;
+        campaign.record_staked(amount)?;
+    }
+
+    token::transfer(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            token::Transfer {
+                from: ctx.accounts.source.to_account_info(),
+                to: ctx.accounts.stake_vault.to_account_info(),
+                authority: ctx.accounts.owner.to_account_info(),
+            },
+        ),
+        amount,
+    )?;
+    Ok(())
+}
+
+/// The accounts definition for [`unstake_from_liquidity_mining`](crate::gmsol_store::unstake_from_liquidity_mining).
+///
+/// *[See also the documentation for the instruction.](crate::gmsol_store::unstake_from_liquidity_mining)*
+#[derive(Accounts)]
+#[instruction(campaign_index: u8)]
+pub struct UnstakeFromLiquidityMining<'info> {
+    /// Owner.
+    pub owner: Signer<'info>,
+    /// Store.
+    pub store: AccountLoader<'info, Store>,
+    /// Campaign.
+    #[account(
+        mut,
+        has_one = store,
+        seeds = [LiquidityMiningCampaign::SEED, store.key().as_ref(), &[campaign_index]],
+        bump = campaign.load()?.bump,
+    )]
+    pub campaign: AccountLoader<'info, LiquidityMiningCampaign>,
+    /// The balance checkpoint of the owner.
+    #[account(
+        mut,
+        has_one = owner,
+        has_one = campaign,
+        seeds = [LpBalanceCheckpoint::SEED, campaign.key().as_ref(), owner.key().as_ref()],
+        bump = checkpoint.load()?.bump,
+    )]
+    pub checkpoint: AccountLoader<'info, LpBalanceCheckpoint>,
+    /// The destination market token account.
+    #[account(mut, token::mint = stake_vault.mint)]
+    pub destination: Account<'info, TokenAccount>,
+    /// The vault for the staked market tokens.
+    #[account(
+        mut,
+        constraint = stake_vault.mint == campaign.load()?.market_token @ CoreError::MarketTokenMintMismatched,
+        seeds = [
+            constants::LIQUIDITY_MINING_VAULT_SEED,
+            campaign.key().as_ref(),
+            stake_vault.mint.as_ref(),
+        ],
+        bump,
+    )]
+    pub stake_vault: Account<'info, TokenAccount>,
+    /// Token Program.
+    pub token_program: Program<'info, Token>,
+}
+
+/// Unstake market tokens from a liquidity mining campaign.
+pub(crate) fn unstake_from_liquidity_mining(
+    ctx: Context<UnstakeFromLiquidityMining>,
+    amount: u64,
+) -> Result<()> {
+    require_neq!(amount, 0, CoreError::InvalidArgument);
+
+    {
+        let now = Clock::get()?.unix_timestamp;
+        let mut campaign = ctx.accounts.campaign.load_mut()?;
+        let mut checkpoint = ctx.accounts.checkpoint.load_mut()?;
+        campaign.accrue(now)?;
+        checkpoint.update(&campaign)?;
+        checkpoint.unstake(amount)?;
+        campaign.record_unstaked(amount)?;
+    }
+
+    token::transfer(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            token::Transfer {
+                from: ctx.accounts.stake_vault.to_account_info(),
+                to: ctx.accounts.destination.to_account_info(),
+                authority: ctx.accounts.store.to_account_info(),
+            },
+            &[&ctx.accounts.store.load()?.signer_seeds()],
+        ),
+        amount,
+    )?;
+    Ok(())
+}
+
+/// The accounts definition for [`claim_liquidity_mining_rewards`](crate::gmsol_store::claim_liquidity_mining_rewards).
+///
+/// *[See also the documentation for the instruction.](crate::gmsol_store::claim_liquidity_mining_rewards)*
+#[derive(Accounts)]
+#[instruction(campaign_index: u8)]
+pub struct ClaimLiquidityMiningRewards<'info> {
+    /// Owner.
+    pub owner: Signer<'info>,
+    /// Store.
+    pub store: AccountLoader<'info, Store>,
+    /// Campaign.
+    #[account(
+        mut,
+        has_one = store,
+        seeds = [LiquidityMiningCampaign::SEED, store.key().as_ref(), &[campaign_index]],
+        bump = campaign.load()?.bump,
+    )]
+    pub campaign: AccountLoader<'info, LiquidityMiningCampaign>,
+    /// The balance checkpoint of the owner.
+    #[account(
+        mut,
+        has_one = owner,
+        has_one = campaign,
+        seeds = [LpBalanceCheckpoint::SEED, campaign.key().as_ref(), owner.key().as_ref()],
+        bump = checkpoint.load()?.bump,
+    )]
+    pub checkpoint: AccountLoader<'info, LpBalanceCheckpoint>,
+    /// The destination reward token account.
+    #[account(mut, token::mint = reward_vault.mint)]
+    pub destination: Account<'info, TokenAccount>,
+    /// The vault for the reward tokens.
+    #[account(
+        mut,
+        constraint = reward_vault.mint == campaign.load()?.reward_token @ CoreError::TokenMintMismatched,
+        seeds = [
+            constants::LIQUIDITY_MINING_VAULT_SEED,
+            campaign.key().as_ref(),
+            reward_vault.mint.as_ref(),
+        ],
+        bump,
+    )]
+    pub reward_vault: Account<'info, TokenAccount>,
+    /// Token Program.
+    pub token_program: Program<'info, Token>,
+}
+
+/// Claim the accrued rewards of a liquidity mining campaign.
+pub(crate) fn claim_liquidity_mining_rewards(
+    ctx: Context<ClaimLiquidityMiningRewards>,
+) -> Result<()> {
+    let amount = {
+        let now = Clock::get()?.unix_timestamp;
+        let mut campaign = ctx.accounts.campaign.load_mut()?;
+        let mut checkpoint = ctx.accounts.checkpoint.load_mut()?;
+        campaign.accrue(now)?;
+        checkpoint.update(&campaign)?;
+        let pending = checkpoint.pending_reward();
+        let amount = campaign.record_claimed(pending)?;
+        checkpoint.record_claimed(amount)?;
+        amount
+    };
+
+    if amount != 0 {
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                token::Transfer {
+                    from: ctx.accounts.reward_vault.to_account_info(),
+                    to: ctx.accounts.destination.to_account_info(),
+                    authority: ctx.accounts.store.to_account_info(),
+                },
+                &[&ctx.accounts.store.load()?.signer_seeds()],
+            ),
+            amount,
+        )?;
+    }
+
+    msg!("[Liquidity Mining] claimed {} reward tokens", amount);
+    Ok(())
+}
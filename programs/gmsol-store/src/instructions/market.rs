@@ -1,13 +1,22 @@
 use crate::{
-    events::EventEmitter,
+    events::{
+        BatchOiCapUpdated, EmergencyWithdrawal, EventEmitter, FeesClaimedWithSplit,
+        MarketNameUpdated, MarketStateCompressed, MarketStateExportRef, MarketSupplyDiscrepancy,
+    },
     ops::market::MarketTransferOutOperation,
     states::{
         market::{
             revertible::{Revertible, RevertibleMarket},
-            status::MarketStatus,
+            status::{
+                ArbitrageOpportunity, LpValueInfo, MarketExpectedValue, MarketStatus,
+                MarketTokenBacking, PriceImpactSensitivity, SupplyVerification, SwapFeeBreakdown,
+            },
             utils::ValidateMarketBalances,
         },
-        Factor, HasMarketMeta,
+        position::{
+            AdlEstimate, BreakEvenPrice, CollateralCoverage, LiquidationImpact, PositionInfo,
+        },
+        Factor, FactorKey, HasMarketMeta, Oracle, Position,
     },
     ModelError,
 };
@@ -15,16 +24,28 @@ use crate::{
 use anchor_lang::prelude::*;
 use anchor_spl::token::{Mint, Token, TokenAccount};
 use gmsol_model::{
-    num::Unsigned, price::Prices, BalanceExt, Bank, BaseMarketMut, LiquidityMarketExt,
-    PnlFactorKind, PoolExt,
+    num::Unsigned, price::Prices, Balance, BalanceExt, Bank, BaseMarketExt, BaseMarketMut,
+    LiquidityMarketExt, PnlFactorKind, PoolExt,
 };
 use gmsol_utils::InitSpace;
+use strum::IntoEnumIterator;
 
 use crate::{
     constants,
+    instructions::ReadStore,
     states::{
-        market::config::{EntryArgs, MarketConfigBuffer},
-        Market, Seed, Store, TokenMapAccess, TokenMapHeader, TokenMapLoader,
+        market::config::{
+            key_names_within_return_data, EntryArgs, MarketConfigBuffer, MarketConfigFlag,
+            MarketConfigProblem,
+        },
+        market::{
+            analytics::OhlcvData,
+            oi_caps::{OiCapUpdate, MAX_OI_CAP_UPDATES},
+            PendingCounts,
+        },
+        FeeStructureProposal, FeeStructureVote, Market, MarketAnalytics, MarketConfigKey,
+        MarketOiCaps, NonceBytes, Seed, Store, TokenMapAccess, TokenMapHeader, TokenMapLoader,
+        VirtualPool,
     },
     utils::internal,
     CoreError,
@@ -120,56 +141,12 @@ pub(crate) fn unchecked_initialize_market(
     name: &str,
     enable: bool,
 ) -> Result<()> {
-    {
-        let token_map = ctx.accounts.token_map.load_token_map()?;
-        require!(
-            token_map
-                .get(&index_token_mint)
-                .ok_or_else(|| error!(CoreError::NotFound))?
-                .is_enabled(),
-            CoreError::InvalidArgument
-        );
-
-        let long_token = &ctx.accounts.long_token_mint;
-        let long_token_config = token_map
-            .get(&long_token.key())
-            .ok_or_else(|| error!(CoreError::NotFound))?;
-        require!(
-            long_token_config.is_enabled(),
-            CoreError::TokenConfigDisabled
-        );
-        require!(
-            long_token_config.is_valid_pool_token_config(),
-            CoreError::InvalidArgument
-        );
-        // This is a redundant check to prevent the decimals in the token config from
-        // being inconsistent with the actual values.
-        require_eq!(
-            long_token_config.token_decimals(),
-            long_token.decimals,
-            CoreError::TokenDecimalsMismatched
-        );
-
-        let short_token = &ctx.accounts.short_token_mint;
-        let short_token_config = token_map
-            .get(&short_token.key())
-            .ok_or_else(|| error!(CoreError::NotFound))?;
-        require!(
-            short_token_config.is_enabled(),
-            CoreError::TokenConfigDisabled
-        );
-        require!(
-            short_token_config.is_valid_pool_token_config(),
-            CoreError::InvalidArgument
-        );
-        // This is a redundant check to prevent the decimals in the token config from
-        // being inconsistent with the actual values.
-        require_eq!(
-            short_token_config.token_decimals(),
-            short_token.decimals,
-            CoreError::TokenDecimalsMismatched
-        );
-    }
+    validate_market_tokens(
+        &ctx.accounts.token_map.load_token_map()?,
+        &index_token_mint,
+        &ctx.accounts.long_token_mint,
+        &ctx.accounts.short_token_mint,
+    )?;
     let market = &ctx.accounts.market;
     market.load_init()?.init(
         ctx.bumps.market,
@@ -194,6 +171,217 @@ impl<'info> internal::Authentication<'info> for InitializeMarket<'info> {
     }
 }
 
+/// Validate the token configs of the tokens of a market to be initialized.
+fn validate_market_tokens(
+    token_map: &impl TokenMapAccess,
+    index_token_mint: &Pubkey,
+    long_token: &Account<Mint>,
+    short_token: &Account<Mint>,
+) -> Result<()> {
+    require!(
+        token_map
+            .get(index_token_mint)
+            .ok_or_else(|| error!(CoreError::NotFound))?
+            .is_enabled(),
+        CoreError::InvalidArgument
+    );
+
+    let long_token_config = token_map
+        .get(&long_token.key())
+        .ok_or_else(|| error!(CoreError::NotFound))?;
+    require!(
+        long_token_config.is_enabled(),
+        CoreError::TokenConfigDisabled
+    );
+    require!(
+        long_token_config.is_valid_pool_token_config(),
+        CoreError::InvalidArgument
+    );
+    // This is a redundant check to prevent the decimals in the token config from
+    // being inconsistent with the actual values.
+    require_eq!(
+        long_token_config.token_decimals(),
+        long_token.decimals,
+        CoreError::TokenDecimalsMismatched
+    );
+
+    let short_token_config = token_map
+        .get(&short_token.key())
+        .ok_or_else(|| error!(CoreError::NotFound))?;
+    require!(
+        short_token_config.is_enabled(),
+        CoreError::TokenConfigDisabled
+    );
+    require!(
+        short_token_config.is_valid_pool_token_config(),
+        CoreError::InvalidArgument
+    );
+    // This is a redundant check to prevent the decimals in the token config from
+    // being inconsistent with the actual values.
+    require_eq!(
+        short_token_config.token_decimals(),
+        short_token.decimals,
+        CoreError::TokenDecimalsMismatched
+    );
+    Ok(())
+}
+
+/// Price cap config of a token.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+#[cfg_attr(feature = "debug", derive(Debug))]
+pub struct TokenPriceCapConfig {
+    /// The token to configure.
+    pub token: Pubkey,
+    /// Min price (in unit price), `0` means unbounded.
+    pub min_price: u32,
+    /// Max price (in unit price), `0` means unbounded.
+    pub max_price: u32,
+}
+
+/// Params for [`initialize_market_with_price_caps`](crate::gmsol_store::initialize_market_with_price_caps).
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+#[cfg_attr(feature = "debug", derive(Debug))]
+pub struct MarketWithPriceCapsParams {
+    /// The address of the index token.
+    pub index_token_mint: Pubkey,
+    /// The name of the market.
+    pub name: String,
+    /// Whether to enable the market after initialization.
+    pub enable: bool,
+    /// Price caps to set for the expected price feeds of the given tokens.
+    pub price_caps: Vec<TokenPriceCapConfig>,
+}
+
+/// The accounts definition for [`initialize_market_with_price_caps`](crate::gmsol_store::initialize_market_with_price_caps).
+///
+/// *[See also the documentation for the instruction.](crate::gmsol_store::initialize_market)*
+#[derive(Accounts)]
+#[instruction(params: MarketWithPriceCapsParams)]
+pub struct InitializeMarketWithPriceCaps<'info> {
+    /// The address authorized to execute this instruction.
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    /// The store account.
+    #[account(has_one = token_map)]
+    pub store: AccountLoader<'info, Store>,
+    /// Market token mint.
+    #[account(
+        init,
+        payer = authority,
+        mint::decimals = constants::MARKET_TOKEN_DECIMALS,
+        // We directly use the store as the authority.
+        mint::authority = store.key(),
+        seeds = [
+            constants::MAREKT_TOKEN_MINT_SEED,
+            store.key().as_ref(),
+            params.index_token_mint.as_ref(),
+            long_token_mint.key().as_ref(),
+            short_token_mint.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub market_token_mint: Account<'info, Mint>,
+    /// Long token.
+    pub long_token_mint: Account<'info, Mint>,
+    /// Short token.
+    pub short_token_mint: Account<'info, Mint>,
+    /// The market account.
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Market::INIT_SPACE,
+        seeds = [
+            Market::SEED,
+            store.key().as_ref(),
+            market_token_mint.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub market: AccountLoader<'info, Market>,
+    /// The token map account.
+    #[account(mut, has_one = store)]
+    pub token_map: AccountLoader<'info, TokenMapHeader>,
+    /// Long token vault must exist.
+    #[account(
+        token::mint = long_token_mint,
+        // We use the store as the authority of the token account.
+        token::authority = store,
+        seeds = [
+            constants::MARKET_VAULT_SEED,
+            store.key().as_ref(),
+            long_token_mint.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub long_token_vault: Account<'info, TokenAccount>,
+    /// Short token vault must exist.
+    #[account(
+        token::mint = short_token_mint,
+        // We use the store as the authority of the token account.
+        token::authority = store,
+        seeds = [
+            constants::MARKET_VAULT_SEED,
+            store.key().as_ref(),
+            short_token_mint.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub short_token_vault: Account<'info, TokenAccount>,
+    /// The system program.
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+}
+
+/// Initialize the account for [`Market`] and set the price caps of the given tokens.
+///
+/// ## CHECK
+/// - Only MARKET_KEEPER can create new market.
+pub(crate) fn unchecked_initialize_market_with_price_caps(
+    ctx: Context<InitializeMarketWithPriceCaps>,
+    params: &MarketWithPriceCapsParams,
+) -> Result<()> {
+    validate_market_tokens(
+        &ctx.accounts.token_map.load_token_map()?,
+        &params.index_token_mint,
+        &ctx.accounts.long_token_mint,
+        &ctx.accounts.short_token_mint,
+    )?;
+    let market = &ctx.accounts.market;
+    market.load_init()?.init(
+        ctx.bumps.market,
+        ctx.accounts.store.key(),
+        &params.name,
+        ctx.accounts.market_token_mint.key(),
+        params.index_token_mint,
+        ctx.accounts.long_token_mint.key(),
+        ctx.accounts.short_token_mint.key(),
+        params.enable,
+    )?;
+
+    let mut token_map = ctx.accounts.token_map.load_token_map_mut()?;
+    for cap in params.price_caps.iter() {
+        let config = token_map
+            .get_mut(&cap.token)
+            .ok_or_else(|| error!(CoreError::NotFound))?;
+        let provider = config.expected_provider()?;
+        let feed_config = config
+            .get_feed_config(&provider)?
+            .with_price_band(cap.min_price, cap.max_price)?;
+        config.set_feed_config(&provider, feed_config)?;
+    }
+    Ok(())
+}
+
+impl<'info> internal::Authentication<'info> for InitializeMarketWithPriceCaps<'info> {
+    fn authority(&self) -> &Signer<'info> {
+        &self.authority
+    }
+
+    fn store(&self) -> &AccountLoader<'info, Store> {
+        &self.store
+    }
+}
+
 /// The accounts definition for [`toggle_market`](crate::gmsol_store::toggle_market).
 ///
 /// *[See also the documentation for the instruction.](crate::gmsol_store::toggle_market)*
@@ -227,84 +415,61 @@ impl<'info> internal::Authentication<'info> for ToggleMarket<'info> {
     }
 }
 
-/// The accounts definition for [`market_transfer_in`](crate::gmsol_store::market_transfer_in).
-#[event_cpi]
+/// The accounts definition for [`set_market_token_map`](crate::gmsol_store::set_market_token_map).
+///
+/// *[See also the documentation for the instruction.](crate::gmsol_store::set_market_token_map)*
 #[derive(Accounts)]
-pub struct MarketTransferIn<'info> {
-    /// Authority.
+pub struct SetMarketTokenMap<'info> {
+    /// The caller.
     pub authority: Signer<'info>,
     /// Store.
     pub store: AccountLoader<'info, Store>,
-    /// The authority of the source account.
-    pub from_authority: Signer<'info>,
     /// Market.
     #[account(mut, has_one = store)]
     pub market: AccountLoader<'info, Market>,
-    /// The source account.
-    #[account(mut, token::mint = vault.mint, constraint = from.key() != vault.key())]
-    pub from: Account<'info, TokenAccount>,
-    /// The market vault.
-    #[account(
-        mut,
-        token::authority = store,
-        seeds = [
-            constants::MARKET_VAULT_SEED,
-            store.key().as_ref(),
-            vault.mint.as_ref(),
-        ],
-        bump,
-    )]
-    pub vault: Account<'info, TokenAccount>,
-    /// Token Program.
-    pub token_program: Program<'info, Token>,
+    /// The override token map to use.
+    /// The market will use the token map of the store if it is not provided.
+    #[account(has_one = store)]
+    pub token_map: Option<AccountLoader<'info, TokenMapHeader>>,
 }
 
-/// Transfer some tokens into the market.
+/// Set the override token map of the market.
 ///
 /// ## CHECK
-/// - Only MARKET_KEEPER can transfer in tokens with this method.
-pub(crate) fn unchecked_market_transfer_in(
-    ctx: Context<MarketTransferIn>,
-    amount: u64,
-) -> Result<()> {
-    use anchor_spl::token;
-
-    {
-        let is_collateral_token = ctx
-            .accounts
-            .market
-            .load()?
-            .validated_meta(&ctx.accounts.store.key())?
-            .is_collateral_token(&ctx.accounts.from.mint);
-        require!(is_collateral_token, CoreError::InvalidArgument);
-    }
-
-    if amount != 0 {
-        token::transfer(
-            CpiContext::new(
-                ctx.accounts.token_program.to_account_info(),
-                token::Transfer {
-                    from: ctx.accounts.from.to_account_info(),
-                    to: ctx.accounts.vault.to_account_info(),
-                    authority: ctx.accounts.from_authority.to_account_info(),
-                },
-            ),
-            amount,
-        )?;
-        let event_emitter =
-            EventEmitter::new(&ctx.accounts.event_authority, ctx.bumps.event_authority);
-        let token = &ctx.accounts.vault.mint;
-        let mut market = RevertibleMarket::new(&ctx.accounts.market, event_emitter)?;
-        market
-            .record_transferred_in_by_token(token, &amount)
-            .map_err(ModelError::from)?;
-        market.commit();
+/// - Only MARKET_KEEPER can set the token map of a market.
+pub(crate) fn unchecked_set_market_token_map(ctx: Context<SetMarketTokenMap>) -> Result<()> {
+    let mut market = ctx.accounts.market.load_mut()?;
+    match ctx.accounts.token_map.as_ref() {
+        Some(token_map) => {
+            // Make sure that the tokens of the market are all configured in the token map.
+            {
+                let map = token_map.load_token_map()?;
+                let meta = market.meta();
+                for token in [
+                    &meta.index_token_mint,
+                    &meta.long_token_mint,
+                    &meta.short_token_mint,
+                ] {
+                    require!(
+                        map.get(token)
+                            .ok_or_else(|| error!(CoreError::NotFound))?
+                            .is_enabled(),
+                        CoreError::TokenConfigDisabled
+                    );
+                }
+            }
+            market.set_token_map(&token_map.key());
+            msg!("[Market] the token map is now {}", token_map.key());
+        }
+        None => {
+            market.set_token_map(&Pubkey::default());
+            msg!("[Market] the token map is now the store default");
+        }
     }
-
     Ok(())
 }
 
-impl<'info> internal::Authentication<'info> for MarketTransferIn<'info> {
+impl<'info> internal::Authentication<'info> for SetMarketTokenMap<'info> {
     fn authority(&self) -> &Signer<'info> {
         &self.authority
     }
@@ -314,10 +479,12 @@ impl<'info> internal::Authentication<'info> for MarketTransferIn<'info> {
     }
 }
 
-/// The accounts definition for [`update_market_config`](crate::gmsol_store::update_market_config)
-/// and [`update_market_config_flag`](crate::gmsol_store::update_market_config_flag).
+/// The accounts definition for [`set_market_name`](crate::gmsol_store::set_market_name).
+///
+/// *[See also the documentation for the instruction.](crate::gmsol_store::set_market_name)*
+#[event_cpi]
 #[derive(Accounts)]
-pub struct UpdateMarketConfig<'info> {
+pub struct SetMarketName<'info> {
     /// The caller.
     pub authority: Signer<'info>,
     /// Store.
@@ -327,7 +494,34 @@ pub struct UpdateMarketConfig<'info> {
     pub market: AccountLoader<'info, Market>,
 }
 
-impl<'info> internal::Authentication<'info> for UpdateMarketConfig<'info> {
+/// Set the name of the market.
+///
+/// ## CHECK
+/// - Only MARKET_KEEPER can set the name of a market.
+pub(crate) fn unchecked_set_market_name(ctx: Context<SetMarketName>, name: &str) -> Result<()> {
+    let (market_token, old_name) = {
+        let mut market = ctx.accounts.market.load_mut()?;
+        let old_name = market.name()?.to_string();
+        market.set_name(name)?;
+        (market.meta().market_token_mint, old_name)
+    };
+
+    let event_emitter = EventEmitter::new(&ctx.accounts.event_authority, ctx.bumps.event_authority);
+    event_emitter.emit_cpi(&MarketNameUpdated {
+        market_token,
+        old_name,
+        new_name: name.to_string(),
+    })?;
+
+    msg!(
+        "[Market] the name of the {} market is now {}",
+        market_token,
+        name
+    );
+    Ok(())
+}
+
+impl<'info> internal::Authentication<'info> for SetMarketName<'info> {
     fn authority(&self) -> &Signer<'info> {
         &self.authority
     }
@@ -337,92 +531,1153 @@ impl<'info> internal::Authentication<'info> for UpdateMarketConfig<'info> {
     }
 }
 
-/// Update market config by key.
-///
-/// ## CHECK
-/// - Only MARKET_KEEPER can update the config of market.
-pub(crate) fn unchecked_update_market_config(
-    ctx: Context<UpdateMarketConfig>,
-    key: &str,
-    value: Factor,
-) -> Result<()> {
-    *ctx.accounts.market.load_mut()?.get_config_mut(key)? = value;
-    msg!(
-        "{}: set {} = {}",
-        ctx.accounts.market.load()?.meta.market_token_mint,
-        key,
-        value
-    );
-    Ok(())
-}
-
-/// Update market config flag by key.
+/// The accounts definition for [`close_market`](crate::gmsol_store::close_market).
 ///
-/// ## CHECK
-/// - Only MARKET_KEEPER can update the config of market.
-pub(crate) fn unchecked_update_market_config_flag(
+/// *[See also the documentation for the instruction.](crate::gmsol_store::close_market)*
+#[derive(Accounts)]
+pub struct CloseMarket<'info> {
+    /// The caller.
+    pub authority: Signer<'info>,
+    /// Store.
+    pub store: AccountLoader<'info, Store>,
+    /// Market.
+    #[account(
+        mut,
+        close = receiver,
+        has_one = store,
+        constraint = market.load()?.meta().market_token_mint == market_token_mint.key() @ CoreError::MarketTokenMintMismatched,
+    )]
+    pub market: AccountLoader<'info, Market>,
+    /// Market token mint.
+    pub market_token_mint: Account<'info, Mint>,
+    /// The vault for the market token.
+    /// It will also be closed if provided.
+    #[account(
+        mut,
+        token::mint = market_token_mint,
+        token::authority = store,
+        seeds = [
+            constants::MARKET_VAULT_SEED,
+            store.key().as_ref(),
+            market_token_mint.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub market_token_vault: Option<Account<'info, TokenAccount>>,
+    /// Receiver.
+    /// CHECK: Only used to receive funds after closing the accounts.
+    #[account(mut)]
+    pub receiver: UncheckedAccount<'info>,
+    /// The token program.
+    pub token_program: Program<'info, Token>,
+}
+
+/// Close the market.
+///
+/// The market must have no residual balances, which in practice means that
+/// only markets that have never been used can be closed.
+///
+/// ## CHECK
+/// - Only MARKET_KEEPER can close a market.
+pub(crate) fn unchecked_close_market(ctx: Context<CloseMarket>) -> Result<()> {
+    {
+        let market = ctx.accounts.market.load()?;
+        require!(!market.is_enabled(), CoreError::PreconditionsAreNotMet);
+        require!(market.is_drained(), CoreError::PreconditionsAreNotMet);
+        // Counted actions need the market to release their slots when closed.
+        let pending = market.indexer().pending_counts();
+        require!(
+            pending.deposits == 0 && pending.withdrawals == 0 && pending.orders == 0,
+            CoreError::PreconditionsAreNotMet
+        );
+    }
+    require_eq!(
+        ctx.accounts.market_token_mint.supply,
+        0,
+        CoreError::PreconditionsAreNotMet
+    );
+
+    if let Some(vault) = ctx.accounts.market_token_vault.as_ref() {
+        require_eq!(vault.amount, 0, CoreError::PreconditionsAreNotMet);
+        anchor_spl::token::close_account(CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            anchor_spl::token::CloseAccount {
+                account: vault.to_account_info(),
+                destination: ctx.accounts.receiver.to_account_info(),
+                authority: ctx.accounts.store.to_account_info(),
+            },
+            &[&ctx.accounts.store.load()?.signer_seeds()],
+        ))?;
+    }
+
+    msg!("[Market] closed market {}", ctx.accounts.market.key());
+    Ok(())
+}
+
+impl<'info> internal::Authentication<'info> for CloseMarket<'info> {
+    fn authority(&self) -> &Signer<'info> {
+        &self.authority
+    }
+
+    fn store(&self) -> &AccountLoader<'info, Store> {
+        &self.store
+    }
+}
+
+/// The accounts definition for [`market_transfer_in`](crate::gmsol_store::market_transfer_in).
+#[event_cpi]
+#[derive(Accounts)]
+pub struct MarketTransferIn<'info> {
+    /// Authority.
+    pub authority: Signer<'info>,
+    /// Store.
+    pub store: AccountLoader<'info, Store>,
+    /// The authority of the source account.
+    pub from_authority: Signer<'info>,
+    /// Market.
+    #[account(mut, has_one = store)]
+    pub market: AccountLoader<'info, Market>,
+    /// The source account.
+    #[account(mut, token::mint = vault.mint, constraint = from.key() != vault.key())]
+    pub from: Account<'info, TokenAccount>,
+    /// The market vault.
+    #[account(
+        mut,
+        token::authority = store,
+        seeds = [
+            constants::MARKET_VAULT_SEED,
+            store.key().as_ref(),
+            vault.mint.as_ref(),
+        ],
+        bump,
+    )]
+    pub vault: Account<'info, TokenAccount>,
+    /// Token Program.
+    pub token_program: Program<'info, Token>,
+}
+
+/// Transfer some tokens into the market.
+///
+/// ## CHECK
+/// - Only MARKET_KEEPER can transfer in tokens with this method.
+pub(crate) fn unchecked_market_transfer_in(
+    ctx: Context<MarketTransferIn>,
+    amount: u64,
+) -> Result<()> {
+    use anchor_spl::token;
+
+    {
+        let is_collateral_token = ctx
+            .accounts
+            .market
+            .load()?
+            .validated_meta(&ctx.accounts.store.key())?
+            .is_collateral_token(&ctx.accounts.from.mint);
+        require!(is_collateral_token, CoreError::InvalidArgument);
+    }
+
+    if amount != 0 {
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                token::Transfer {
+                    from: ctx.accounts.from.to_account_info(),
+                    to: ctx.accounts.vault.to_account_info(),
+                    authority: ctx.accounts.from_authority.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+        let event_emitter =
+            EventEmitter::new(&ctx.accounts.event_authority, ctx.bumps.event_authority);
+        let token = &ctx.accounts.vault.mint;
+        let mut market = RevertibleMarket::new(&ctx.accounts.market, event_emitter)?;
+        market
+            .record_transferred_in_by_token(token, &amount)
+            .map_err(ModelError::from)?;
+        market.commit();
+    }
+
+    Ok(())
+}
+
+impl<'info> internal::Authentication<'info> for MarketTransferIn<'info> {
+    fn authority(&self) -> &Signer<'info> {
+        &self.authority
+    }
+
+    fn store(&self) -> &AccountLoader<'info, Store> {
+        &self.store
+    }
+}
+
+/// The accounts definition for [`emergency_vault_withdraw`](crate::gmsol_store::emergency_vault_withdraw).
+///
+/// *[See also the documentation for the instruction.](crate::gmsol_store::emergency_vault_withdraw)*
+#[event_cpi]
+#[derive(Accounts)]
+pub struct EmergencyVaultWithdraw<'info> {
+    /// Authority.
+    pub authority: Signer<'info>,
+    /// Store.
+    pub store: AccountLoader<'info, Store>,
+    /// Market.
+    #[account(mut, has_one = store)]
+    pub market: AccountLoader<'info, Market>,
+    /// Token mint.
+    pub token_mint: InterfaceAccount<'info, anchor_spl::token_interface::Mint>,
+    /// The market vault.
+    #[account(
+        mut,
+        token::mint = token_mint,
+        token::authority = store,
+        token::token_program = token_program,
+        seeds = [
+            constants::MARKET_VAULT_SEED,
+            store.key().as_ref(),
+            token_mint.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub vault: InterfaceAccount<'info, anchor_spl::token_interface::TokenAccount>,
+    /// The destination account.
+    #[account(
+        mut,
+        token::mint = token_mint,
+        constraint = destination.key() != vault.key(),
+    )]
+    pub destination: InterfaceAccount<'info, anchor_spl::token_interface::TokenAccount>,
+    /// Token Program.
+    pub token_program: Interface<'info, anchor_spl::token_interface::TokenInterface>,
+}
+
+/// Withdraw tokens from the market vault in emergency mode.
+///
+/// ## CHECK
+/// - Only ADMIN can withdraw tokens with this method.
+pub(crate) fn unchecked_emergency_vault_withdraw(
+    ctx: Context<EmergencyVaultWithdraw>,
+    amount: u64,
+) -> Result<()> {
+    ctx.accounts
+        .store
+        .load()?
+        .validate_emergency_mode_enabled()?;
+
+    require_neq!(amount, 0, CoreError::InvalidArgument);
+
+    let event_emitter = EventEmitter::new(&ctx.accounts.event_authority, ctx.bumps.event_authority);
+    let token = &ctx.accounts.token_mint;
+    MarketTransferOutOperation::builder()
+        .store(&ctx.accounts.store)
+        .market(&ctx.accounts.market)
+        .amount(amount)
+        .decimals(token.decimals)
+        .to(ctx.accounts.destination.to_account_info())
+        .token_mint(token.to_account_info())
+        .vault(ctx.accounts.vault.to_account_info())
+        .token_program(ctx.accounts.token_program.to_account_info())
+        .event_emitter(event_emitter)
+        .build()
+        .execute()?;
+
+    let market_token = ctx.accounts.market.load()?.meta.market_token_mint;
+    event_emitter.emit_cpi(&EmergencyWithdrawal {
+        market_token,
+        token: token.key(),
+        amount,
+        destination: ctx.accounts.destination.key(),
+    })?;
+
+    msg!(
+        "[Emergency] withdrew `{}` {} from the {} market",
+        amount,
+        token.key(),
+        market_token,
+    );
+    Ok(())
+}
+
+impl<'info> internal::Authentication<'info> for EmergencyVaultWithdraw<'info> {
+    fn authority(&self) -> &Signer<'info> {
+        &self.authority
+    }
+
+    fn store(&self) -> &AccountLoader<'info, Store> {
+        &self.store
+    }
+}
+
+/// The accounts definition for [`update_market_config`](crate::gmsol_store::update_market_config)
+/// and [`update_market_config_flag`](crate::gmsol_store::update_market_config_flag).
+#[derive(Accounts)]
+pub struct UpdateMarketConfig<'info> {
+    /// The caller.
+    pub authority: Signer<'info>,
+    /// Store.
+    pub store: AccountLoader<'info, Store>,
+    /// Market.
+    #[account(mut, has_one = store)]
+    pub market: AccountLoader<'info, Market>,
+}
+
+impl<'info> internal::Authentication<'info> for UpdateMarketConfig<'info> {
+    fn authority(&self) -> &Signer<'info> {
+        &self.authority
+    }
+
+    fn store(&self) -> &AccountLoader<'info, Store> {
+        &self.store
+    }
+}
+
+/// Update market config by key.
+///
+/// ## CHECK
+/// - Only MARKET_KEEPER can update the config of market.
+pub(crate) fn unchecked_update_market_config(
     ctx: Context<UpdateMarketConfig>,
     key: &str,
-    value: bool,
+    value: Factor,
 ) -> Result<()> {
-    let previous = ctx
-        .accounts
-        .market
-        .load_mut()?
-        .set_config_flag(key, value)?;
+    *ctx.accounts.market.load_mut()?.get_config_mut(key)? = value;
     msg!(
-        "{}: set {} = {}, previous = {}",
+        "{}: set {} = {}",
         ctx.accounts.market.load()?.meta.market_token_mint,
         key,
-        value,
-        previous,
+        value
     );
     Ok(())
 }
 
-/// The accounts definition for [`update_market_config_with_buffer`](crate::gmsol_store::update_market_config_with_buffer).
+/// Update market config flag by key.
+///
+/// ## CHECK
+/// - Only MARKET_KEEPER can update the config of market.
+pub(crate) fn unchecked_update_market_config_flag(
+    ctx: Context<UpdateMarketConfig>,
+    key: &str,
+    value: bool,
+) -> Result<()> {
+    let previous = ctx
+        .accounts
+        .market
+        .load_mut()?
+        .set_config_flag(key, value)?;
+    msg!(
+        "{}: set {} = {}, previous = {}",
+        ctx.accounts.market.load()?.meta.market_token_mint,
+        key,
+        value,
+        previous,
+    );
+    Ok(())
+}
+
+/// The accounts definition for [`update_market_config_with_buffer`](crate::gmsol_store::update_market_config_with_buffer).
+///
+/// *[See also the documentation for the instruction.](crate::gmsol_store::update_market_config_with_buffer)*
+#[derive(Accounts)]
+pub struct UpdateMarketConfigWithBuffer<'info> {
+    /// The caller.
+    pub authority: Signer<'info>,
+    /// Store.
+    pub store: AccountLoader<'info, Store>,
+    /// Market.
+    #[account(mut, has_one = store)]
+    pub market: AccountLoader<'info, Market>,
+    /// The buffer to use.
+    #[account(mut, has_one = store, has_one = authority @ CoreError::PermissionDenied)]
+    pub buffer: Account<'info, MarketConfigBuffer>,
+}
+
+/// Update market config with buffer.
+///
+/// ## CHECK
+/// - Only MARKET_KEEPER can udpate the config of market.
+pub(crate) fn unchecked_update_market_config_with_buffer(
+    ctx: Context<UpdateMarketConfigWithBuffer>,
+) -> Result<()> {
+    let buffer = &ctx.accounts.buffer;
+    require_gt!(
+        buffer.expiry,
+        Clock::get()?.unix_timestamp,
+        CoreError::InvalidArgument
+    );
+    ctx.accounts
+        .market
+        .load_mut()?
+        .update_config_with_buffer(buffer)?;
+    msg!(
+        "{} updated with buffer {}",
+        ctx.accounts.market.load()?.description()?,
+        buffer.key()
+    );
+    Ok(())
+}
+
+impl<'info> internal::Authentication<'info> for UpdateMarketConfigWithBuffer<'info> {
+    fn authority(&self) -> &Signer<'info> {
+        &self.authority
+    }
+
+    fn store(&self) -> &AccountLoader<'info, Store> {
+        &self.store
+    }
+}
+
+/// The accounts definition for read-only instructions for market.
+#[derive(Accounts)]
+pub struct ReadMarket<'info> {
+    /// Market.
+    pub market: AccountLoader<'info, Market>,
+}
+
+/// Get market status.
+pub(crate) fn get_market_status(
+    ctx: Context<ReadMarket>,
+    prices: &Prices<u128>,
+    maximize_pnl: bool,
+    maximize_pool_value: bool,
+) -> Result<MarketStatus> {
+    let market = ctx.accounts.market.load()?;
+    let status = MarketStatus::from_market(&market, prices, maximize_pnl, maximize_pool_value)
+        .map_err(ModelError::from)?;
+    Ok(status)
+}
+
+/// Check the market config against the validity bounds.
+pub(crate) fn validate_market_config(ctx: Context<ReadMarket>) -> Result<Vec<MarketConfigProblem>> {
+    let market = ctx.accounts.market.load()?;
+    Ok(market
+        .validate_config()
+        .into_iter()
+        .map(MarketConfigProblem::from)
+        .collect())
+}
+
+/// Get the number of pending deposits, withdrawals and orders of the market.
+pub(crate) fn get_market_pending_counts(ctx: Context<ReadMarket>) -> Result<PendingCounts> {
+    Ok(ctx.accounts.market.load()?.indexer().pending_counts())
+}
+
+/// List the names of market config keys, starting from `start`.
+pub(crate) fn list_market_config_keys(_ctx: Context<ReadStore>, start: u16) -> Result<Vec<String>> {
+    Ok(key_names_within_return_data(MarketConfigKey::iter(), start))
+}
+
+/// List the names of market config flags, starting from `start`.
+pub(crate) fn list_market_config_flags(
+    _ctx: Context<ReadStore>,
+    start: u16,
+) -> Result<Vec<String>> {
+    Ok(key_names_within_return_data(
+        MarketConfigFlag::iter(),
+        start,
+    ))
+}
+
+/// Get market expected value.
+pub(crate) fn get_market_expected_value(
+    ctx: Context<ReadMarket>,
+    prices: &Prices<u128>,
+    trading_volume_usd: u128,
+    time_horizon_secs: u64,
+) -> Result<MarketExpectedValue> {
+    let market = ctx.accounts.market.load()?;
+    let value =
+        MarketExpectedValue::from_market(&market, prices, trading_volume_usd, time_horizon_secs)
+            .map_err(ModelError::from)?;
+    Ok(value)
+}
+
+/// Get the fee breakdown of swapping `amount` of `input_token` to `output_token`.
+pub(crate) fn get_market_swap_fees(
+    ctx: Context<ReadMarket>,
+    input_token: &Pubkey,
+    output_token: &Pubkey,
+    amount: u64,
+    prices: &Prices<u128>,
+) -> Result<SwapFeeBreakdown> {
+    let market = ctx.accounts.market.load()?;
+    let meta = market.meta();
+    require_keys_neq!(*input_token, *output_token, CoreError::InvalidArgument);
+    require_keys_eq!(
+        *meta.opposite_token(input_token)?,
+        *output_token,
+        CoreError::InvalidArgument
+    );
+    let is_token_in_long = meta.to_token_side(input_token)?;
+    let breakdown = SwapFeeBreakdown::from_market(&market, is_token_in_long, amount, prices)
+        .map_err(ModelError::from)?;
+    Ok(breakdown)
+}
+
+/// The accounts definition for
+/// [`get_market_price_impact_sensitivity`](crate::gmsol_store::get_market_price_impact_sensitivity).
+#[derive(Accounts)]
+pub struct GetMarketPriceImpactSensitivity<'info> {
+    /// Market.
+    pub market: AccountLoader<'info, Market>,
+    /// The oracle buffer of the store.
+    #[account(
+        constraint = oracle.load()?.store == market.load()?.store @ CoreError::StoreMismatched,
+    )]
+    pub oracle: AccountLoader<'info, Oracle>,
+}
+
+/// Get the price impact of opening positions of increasing sizes.
+pub(crate) fn get_market_price_impact_sensitivity(
+    ctx: Context<GetMarketPriceImpactSensitivity>,
+    prices: &Prices<u128>,
+    direction: i8,
+) -> Result<PriceImpactSensitivity> {
+    require_neq!(direction, 0, CoreError::InvalidArgument);
+    let market = ctx.accounts.market.load()?;
+    let sensitivity = PriceImpactSensitivity::from_market(&market, prices, direction > 0)
+        .map_err(ModelError::from)?;
+    Ok(sensitivity)
+}
+
+/// The accounts definition for
+/// [`get_swap_arbitrage_opportunity`](crate::gmsol_store::get_swap_arbitrage_opportunity).
+///
+/// Remaining accounts expected by this instruction:
+///
+///   - 0..N. `[]` N market accounts to search, where N represents the number of the provided
+///     prices, no more than [`ArbitrageOpportunity::MAX_MARKETS`].
+#[derive(Accounts)]
+pub struct GetSwapArbitrageOpportunity<'info> {
+    /// Store.
+    pub store: AccountLoader<'info, Store>,
+}
+
+/// Find the swap path with the highest output among the given markets.
+pub(crate) fn get_swap_arbitrage_opportunity<'info>(
+    ctx: Context<'_, '_, 'info, 'info, GetSwapArbitrageOpportunity<'info>>,
+    input_token: &Pubkey,
+    output_token: &Pubkey,
+    amount: u64,
+    prices: &[Prices<u128>],
+) -> Result<ArbitrageOpportunity> {
+    let len = prices.len();
+    require_gt!(len, 0, CoreError::InvalidArgument);
+    require_gte!(
+        ArbitrageOpportunity::MAX_MARKETS,
+        len,
+        CoreError::ExceedMaxLengthLimit
+    );
+    require_gte!(
+        ctx.remaining_accounts.len(),
+        len,
+        ErrorCode::AccountNotEnoughKeys
+    );
+    require!(amount != 0, CoreError::InvalidArgument);
+
+    let store = ctx.accounts.store.key();
+    let loaders = ctx.remaining_accounts[..len]
+        .iter()
+        .map(AccountLoader::<Market>::try_from)
+        .collect::<Result<Vec<_>>>()?;
+    let markets = loaders
+        .iter()
+        .map(|loader| loader.load())
+        .collect::<Result<Vec<_>>>()?;
+    for market in markets.iter() {
+        market.validate(&store)?;
+    }
+    let markets = markets
+        .iter()
+        .map(|market| &**market)
+        .zip(prices)
+        .collect::<Vec<_>>();
+
+    ArbitrageOpportunity::find(&markets, input_token, output_token, amount)
+        .map_err(ModelError::from)?
+        .ok_or_else(|| error!(CoreError::NotFound))
+}
+
+/// The accounts definition for read-only instructions for market.
+#[derive(Accounts)]
+pub struct ReadMarketWithToken<'info> {
+    /// Market.
+    #[account(
+        constraint = market.load()?.meta.market_token_mint == market_token.key() @ CoreError::InvalidArgument,
+    )]
+    pub market: AccountLoader<'info, Market>,
+    /// Market token.
+    pub market_token: Account<'info, Mint>,
+}
+
+/// Get market token price.
+pub(crate) fn get_market_token_price(
+    ctx: Context<ReadMarketWithToken>,
+    prices: &Prices<u128>,
+    pnl_factor: PnlFactorKind,
+    maximize: bool,
+) -> Result<u128> {
+    let market = ctx.accounts.market.load()?;
+    let liquidity_market = market.as_liquidity_market(&ctx.accounts.market_token);
+    let price = liquidity_market
+        .market_token_price(prices, pnl_factor, maximize)
+        .map_err(ModelError::from)?;
+    Ok(price)
+}
+
+/// Get the underlying tokens backing one unit of market token.
+pub(crate) fn get_market_token_backing(
+    ctx: Context<ReadMarketWithToken>,
+    prices: &Prices<u128>,
+) -> Result<MarketTokenBacking> {
+    let market = ctx.accounts.market.load()?;
+    let backing =
+        MarketTokenBacking::from_market(&market, ctx.accounts.market_token.supply, prices)
+            .map_err(ModelError::from)?;
+    Ok(backing)
+}
+
+/// The accounts definition for [`get_lp_value_per_token`](crate::gmsol_store::get_lp_value_per_token).
+///
+/// *[See also the documentation for the instruction.](crate::gmsol_store::get_lp_value_per_token)*
+#[derive(Accounts)]
+pub struct GetLpValuePerToken<'info> {
+    /// Market.
+    #[account(
+        constraint = market.load()?.meta.market_token_mint == market_token_mint.key() @ CoreError::MarketTokenMintMismatched,
+    )]
+    pub market: AccountLoader<'info, Market>,
+    /// Market token.
+    pub market_token_mint: Account<'info, Mint>,
+    /// The oracle buffer of the store, with the prices of the market tokens set.
+    #[account(
+        constraint = oracle.load()?.store == market.load()?.store @ CoreError::StoreMismatched,
+    )]
+    pub oracle: AccountLoader<'info, Oracle>,
+}
+
+/// Get the value of the liquidity pool and the market token with the prices in the oracle.
+pub(crate) fn get_lp_value_per_token(
+    ctx: Context<GetLpValuePerToken>,
+    maximize: bool,
+) -> Result<LpValueInfo> {
+    let market = ctx.accounts.market.load()?;
+    let prices = ctx.accounts.oracle.load()?.market_prices(&*market)?;
+    let info =
+        LpValueInfo::from_market(&market, &ctx.accounts.market_token_mint, &prices, maximize)
+            .map_err(ModelError::from)?;
+    Ok(info)
+}
+
+/// The accounts definition for [`verify_market_token_supply`](crate::gmsol_store::verify_market_token_supply).
+///
+/// *[See also the documentation for the instruction.](crate::gmsol_store::verify_market_token_supply)*
+#[event_cpi]
+#[derive(Accounts)]
+pub struct VerifyMarketTokenSupply<'info> {
+    /// Store.
+    pub store: AccountLoader<'info, Store>,
+    /// Market.
+    #[account(
+        has_one = store,
+        constraint = market.load()?.meta.market_token_mint == market_token.key() @ CoreError::MarketTokenMintMismatched,
+    )]
+    pub market: AccountLoader<'info, Market>,
+    /// Market token.
+    pub market_token: Account<'info, Mint>,
+}
+
+/// Verify the market token supply against the market balances.
+pub(crate) fn verify_market_token_supply(
+    ctx: Context<VerifyMarketTokenSupply>,
+    prices: &Prices<u128>,
+) -> Result<SupplyVerification> {
+    let tolerance_factor = *ctx
+        .accounts
+        .store
+        .load()?
+        .get_factor_by_key(FactorKey::SupplyToleranceFactor);
+    let market = ctx.accounts.market.load()?;
+    let verification = SupplyVerification::from_market(
+        &market,
+        ctx.accounts.market_token.supply,
+        prices,
+        tolerance_factor,
+    )
+    .map_err(ModelError::from)?;
+
+    if !verification.is_consistent {
+        let event_emitter =
+            EventEmitter::new(&ctx.accounts.event_authority, ctx.bumps.event_authority);
+        event_emitter.emit_cpi(&MarketSupplyDiscrepancy {
+            market_token: market.meta.market_token_mint,
+            current_supply: verification.current_supply,
+            expected_supply_estimate: verification.expected_supply_estimate,
+            delta: verification.delta,
+        })?;
+        msg!(
+            "[Market] supply discrepancy detected for the {} market: delta = {}",
+            market.meta.market_token_mint,
+            verification.delta
+        );
+    }
+
+    Ok(verification)
+}
+
+/// The accounts definition for read-only instructions for position.
+#[derive(Accounts)]
+pub struct ReadPosition<'info> {
+    /// Market.
+    pub market: AccountLoader<'info, Market>,
+    /// Position.
+    #[account(
+        constraint = position.load()?.store == market.load()?.store @ CoreError::StoreMismatched,
+        constraint = position.load()?.market_token == market.load()?.meta.market_token_mint @ CoreError::MarketTokenMintMismatched,
+    )]
+    pub position: AccountLoader<'info, Position>,
+}
+
+/// Get position info.
+pub(crate) fn get_position_info(
+    ctx: Context<ReadPosition>,
+    prices: &Prices<u128>,
+) -> Result<PositionInfo> {
+    let market = ctx.accounts.market.load()?;
+    let position = ctx.accounts.position.load()?;
+    let info = PositionInfo::from_position(&position.as_position(&market)?, prices)
+        .map_err(ModelError::from)?;
+    Ok(info)
+}
+
+/// Estimate the size delta to auto-deleverage for the position.
+pub(crate) fn estimate_adl_size_delta(
+    ctx: Context<ReadPosition>,
+    prices: &Prices<u128>,
+) -> Result<AdlEstimate> {
+    let market = ctx.accounts.market.load()?;
+    let position = ctx.accounts.position.load()?;
+    let estimate = AdlEstimate::from_position(&position.as_position(&market)?, prices)
+        .map_err(ModelError::from)?;
+    Ok(estimate)
+}
+
+/// Estimate the impact of liquidating the position.
+pub(crate) fn get_liquidation_impact(
+    ctx: Context<ReadPosition>,
+    prices: &Prices<u128>,
+) -> Result<LiquidationImpact> {
+    let market = ctx.accounts.market.load()?;
+    let position = ctx.accounts.position.load()?;
+    let impact = LiquidationImpact::from_position(&position.as_position(&market)?, prices)
+        .map_err(ModelError::from)?;
+    Ok(impact)
+}
+
+/// Calculate the collateral coverage of the position.
+pub(crate) fn get_collateral_coverage_ratio(
+    ctx: Context<ReadPosition>,
+    prices: &Prices<u128>,
+) -> Result<CollateralCoverage> {
+    let market = ctx.accounts.market.load()?;
+    let position = ctx.accounts.position.load()?;
+    let coverage = CollateralCoverage::from_position(&position.as_position(&market)?, prices)
+        .map_err(ModelError::from)?;
+    Ok(coverage)
+}
+
+/// Calculate the break-even price of the position.
+pub(crate) fn get_position_break_even_price(
+    ctx: Context<ReadPosition>,
+    prices: &Prices<u128>,
+) -> Result<BreakEvenPrice> {
+    let market = ctx.accounts.market.load()?;
+    let position = ctx.accounts.position.load()?;
+    let price = BreakEvenPrice::from_position(&position.as_position(&market)?, prices)
+        .map_err(ModelError::from)?;
+    Ok(price)
+}
+
+/// The accounts definition for [`initialize_market_config_buffer`](crate::gmsol_store::initialize_market_config_buffer).
+///
+/// *[See also the documentation for the instruction.](crate::gmsol_store::initialize_market_config_buffer)*
+#[derive(Accounts)]
+pub struct InitializeMarketConfigBuffer<'info> {
+    /// The caller.
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    /// Store.
+    pub store: AccountLoader<'info, Store>,
+    /// Buffer account to create.
+    #[account(init, payer = authority, space = 8 + MarketConfigBuffer::init_space(0))]
+    pub buffer: Account<'info, MarketConfigBuffer>,
+    /// System Program.
+    pub system_program: Program<'info, System>,
+}
+
+/// Initialize a market config buffer account.
+pub(crate) fn initialize_market_config_buffer(
+    ctx: Context<InitializeMarketConfigBuffer>,
+    expire_after_secs: u32,
+) -> Result<()> {
+    let buffer = &mut ctx.accounts.buffer;
+    buffer.authority = ctx.accounts.authority.key();
+    buffer.store = ctx.accounts.store.key();
+    buffer.expiry = Clock::get()?
+        .unix_timestamp
+        .saturating_add_unsigned(expire_after_secs as u64);
+    Ok(())
+}
+
+/// The accounts definition for [`set_market_config_buffer_authority`](crate::gmsol_store::set_market_config_buffer_authority).
+///
+/// *[See also the documentation for the instruction.](crate::gmsol_store::set_market_config_buffer_authority)*
+#[derive(Accounts)]
+pub struct SetMarketConfigBufferAuthority<'info> {
+    /// The authority.
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    /// Buffer.
+    #[account(mut, has_one = authority @ CoreError::PermissionDenied)]
+    pub buffer: Account<'info, MarketConfigBuffer>,
+}
+
+/// Set the authority of the buffer account.
+pub(crate) fn set_market_config_buffer_authority(
+    ctx: Context<SetMarketConfigBufferAuthority>,
+    new_authority: Pubkey,
+) -> Result<()> {
+    ctx.accounts.buffer.authority = new_authority;
+    Ok(())
+}
+
+/// The accounts definition for [`close_market_config_buffer`](crate::gmsol_store::close_market_config_buffer).
+///
+/// *[See also the documentation for the instruction.](crate::gmsol_store::close_market_config_buffer)*
+#[derive(Accounts)]
+pub struct CloseMarketConfigBuffer<'info> {
+    /// The authority.
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    /// Buffer.
+    #[account(mut, close = receiver, has_one = authority @ CoreError::PermissionDenied)]
+    pub buffer: Account<'info, MarketConfigBuffer>,
+    /// Receiver.
+    /// CHECK: Only used to receive funds after closing the buffer account.
+    #[account(mut)]
+    pub receiver: UncheckedAccount<'info>,
+}
+
+/// Close the buffer account.
+pub(crate) fn close_market_config_buffer(_ctx: Context<CloseMarketConfigBuffer>) -> Result<()> {
+    Ok(())
+}
+
+/// The accounts definition for [`push_to_market_config_buffer`](crate::gmsol_store::push_to_market_config_buffer).
+///
+/// *[See also the documentation for the instruction.](crate::gmsol_store::push_to_market_config_buffer)*
+#[derive(Accounts)]
+#[instruction(new_configs: Vec<(String, Factor)>)]
+pub struct PushToMarketConfigBuffer<'info> {
+    /// Authority.
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    /// Buffer.
+    #[account(
+        mut,
+        has_one = authority @ CoreError::PermissionDenied,
+        realloc = 8 + buffer.space_after_push(new_configs.len()),
+        realloc::payer = authority,
+        realloc::zero = false,
+    )]
+    pub buffer: Account<'info, MarketConfigBuffer>,
+    system_program: Program<'info, System>,
+}
+
+/// Push to the buffer account.
+pub(crate) fn push_to_market_config_buffer(
+    ctx: Context<PushToMarketConfigBuffer>,
+    new_configs: Vec<EntryArgs>,
+) -> Result<()> {
+    let buffer = &mut ctx.accounts.buffer;
+    for entry in new_configs {
+        buffer.push(entry.try_into()?);
+    }
+    Ok(())
+}
+
+/// The accounts definition for [`propose_fee_structure_change`](crate::gmsol_store::propose_fee_structure_change).
+///
+/// *[See also the documentation for the instruction.](crate::gmsol_store::propose_fee_structure_change)*
+#[derive(Accounts)]
+#[instruction(nonce: [u8; 32], changes: Vec<EntryArgs>)]
+pub struct ProposeFeeStructureChange<'info> {
+    /// The proposer.
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    /// Store.
+    pub store: AccountLoader<'info, Store>,
+    /// Market.
+    #[account(has_one = store)]
+    pub market: AccountLoader<'info, Market>,
+    /// The proposal to create.
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + FeeStructureProposal::init_space(changes.len()),
+        seeds = [
+            FeeStructureProposal::SEED,
+            store.key().as_ref(),
+            market.load()?.meta.market_token_mint.as_ref(),
+            &nonce,
+        ],
+        bump,
+    )]
+    pub proposal: Account<'info, FeeStructureProposal>,
+    /// The system program.
+    pub system_program: Program<'info, System>,
+}
+
+/// Propose a fee structure change.
+///
+/// ## CHECK
+/// - Only MARKET_KEEPER can propose a fee structure change.
+pub(crate) fn unchecked_propose_fee_structure_change(
+    ctx: Context<ProposeFeeStructureChange>,
+    nonce: NonceBytes,
+    changes: Vec<EntryArgs>,
+    voting_duration_secs: u32,
+) -> Result<()> {
+    let market_token = ctx.accounts.market.load()?.meta.market_token_mint;
+    let proposal = &mut ctx.accounts.proposal;
+    proposal.init(
+        ctx.bumps.proposal,
+        &ctx.accounts.store.key(),
+        &ctx.accounts.authority.key(),
+        &market_token,
+        &nonce,
+        changes,
+        voting_duration_secs,
+        Clock::get()?.unix_timestamp,
+    )?;
+    msg!(
+        "[Market] proposed fee structure change {} for the {} market, voting ends at {}",
+        proposal.key(),
+        market_token,
+        proposal.voting_ends_at
+    );
+    Ok(())
+}
+
+impl<'info> internal::Authentication<'info> for ProposeFeeStructureChange<'info> {
+    fn authority(&self) -> &Signer<'info> {
+        &self.authority
+    }
+
+    fn store(&self) -> &AccountLoader<'info, Store> {
+        &self.store
+    }
+}
+
+/// The accounts definition for [`vote_on_fee_structure`](crate::gmsol_store::vote_on_fee_structure).
+///
+/// *[See also the documentation for the instruction.](crate::gmsol_store::vote_on_fee_structure)*
+#[derive(Accounts)]
+pub struct VoteOnFeeStructure<'info> {
+    /// The voter.
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    /// Store.
+    pub store: AccountLoader<'info, Store>,
+    /// The proposal to vote on.
+    #[account(mut, has_one = store)]
+    pub proposal: Account<'info, FeeStructureProposal>,
+    /// The vote record to create, which can only be created once per voter.
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + FeeStructureVote::INIT_SPACE,
+        seeds = [
+            FeeStructureVote::SEED,
+            proposal.key().as_ref(),
+            authority.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub vote: Account<'info, FeeStructureVote>,
+    /// The system program.
+    pub system_program: Program<'info, System>,
+}
+
+/// Vote on a fee structure proposal.
+///
+/// ## CHECK
+/// - Only MARKET_KEEPER can vote on a fee structure proposal.
+pub(crate) fn unchecked_vote_on_fee_structure(
+    ctx: Context<VoteOnFeeStructure>,
+    vote_for: bool,
+) -> Result<()> {
+    let proposal = &mut ctx.accounts.proposal;
+    proposal.vote(vote_for, Clock::get()?.unix_timestamp)?;
+
+    let vote = &mut ctx.accounts.vote;
+    vote.bump = ctx.bumps.vote;
+    vote.vote_for = vote_for;
+    vote.proposal = proposal.key();
+    vote.voter = ctx.accounts.authority.key();
+
+    msg!(
+        "[Market] voted {} the fee structure proposal {}, for = {}, against = {}",
+        if vote_for { "for" } else { "against" },
+        proposal.key(),
+        proposal.votes_for,
+        proposal.votes_against
+    );
+    Ok(())
+}
+
+impl<'info> internal::Authentication<'info> for VoteOnFeeStructure<'info> {
+    fn authority(&self) -> &Signer<'info> {
+        &self.authority
+    }
+
+    fn store(&self) -> &AccountLoader<'info, Store> {
+        &self.store
+    }
+}
+
+/// The accounts definition for [`execute_fee_structure_proposal`](crate::gmsol_store::execute_fee_structure_proposal).
+///
+/// *[See also the documentation for the instruction.](crate::gmsol_store::execute_fee_structure_proposal)*
+#[derive(Accounts)]
+pub struct ExecuteFeeStructureProposal<'info> {
+    /// The caller.
+    pub authority: Signer<'info>,
+    /// Store.
+    pub store: AccountLoader<'info, Store>,
+    /// Market.
+    #[account(
+        mut,
+        has_one = store,
+        constraint = market.load()?.meta.market_token_mint == proposal.market_token @ CoreError::MarketTokenMintMismatched,
+    )]
+    pub market: AccountLoader<'info, Market>,
+    /// The proposal to execute.
+    #[account(mut, has_one = store)]
+    pub proposal: Account<'info, FeeStructureProposal>,
+}
+
+/// Execute a fee structure proposal.
+///
+/// ## CHECK
+/// - Only MARKET_KEEPER can execute a fee structure proposal.
+pub(crate) fn unchecked_execute_fee_structure_proposal(
+    ctx: Context<ExecuteFeeStructureProposal>,
+) -> Result<()> {
+    let proposal = &mut ctx.accounts.proposal;
+    proposal.validate_executable(Clock::get()?.unix_timestamp)?;
+    ctx.accounts
+        .market
+        .load_mut()?
+        .update_config_with_entries(proposal.fee_changes())?;
+    proposal.is_executed = true;
+    msg!(
+        "{} updated with fee structure proposal {}",
+        ctx.accounts.market.load()?.description()?,
+        proposal.key()
+    );
+    Ok(())
+}
+
+impl<'info> internal::Authentication<'info> for ExecuteFeeStructureProposal<'info> {
+    fn authority(&self) -> &Signer<'info> {
+        &self.authority
+    }
+
+    fn store(&self) -> &AccountLoader<'info, Store> {
+        &self.store
+    }
+}
+
+/// The accounts definition for [`toggle_gt_minting`](crate::gmsol_store::toggle_gt_minting).
+///
+/// *[See also the documentation for the instruction.](crate::gmsol_store::toggle_gt_minting)*
+#[derive(Accounts)]
+pub struct ToggleGTMinting<'info> {
+    /// The caller.
+    pub authority: Signer<'info>,
+    /// Store.
+    pub store: AccountLoader<'info, Store>,
+    /// Market.
+    #[account(mut, has_one = store)]
+    pub market: AccountLoader<'info, Market>,
+}
+
+/// Toggle GT Minting.
+///
+/// ## CHECK
+/// - Only MARKET_KEEPER can use this instruction.
+pub(crate) fn unchecked_toggle_gt_minting(
+    ctx: Context<ToggleGTMinting>,
+    enable: bool,
+) -> Result<()> {
+    ctx.accounts
+        .market
+        .load_mut()?
+        .set_is_gt_minting_enbaled(enable);
+    Ok(())
+}
+
+impl<'info> internal::Authentication<'info> for ToggleGTMinting<'info> {
+    fn authority(&self) -> &Signer<'info> {
+        &self.authority
+    }
+
+    fn store(&self) -> &AccountLoader<'info, Store> {
+        &self.store
+    }
+}
+
+/// The accounts definition for [`compress_pool_state`](crate::gmsol_store::compress_pool_state).
 ///
-/// *[See also the documentation for the instruction.](crate::gmsol_store::update_market_config_with_buffer)*
+/// *[See also the documentation for the instruction.](crate::gmsol_store::compress_pool_state)*
+#[event_cpi]
 #[derive(Accounts)]
-pub struct UpdateMarketConfigWithBuffer<'info> {
-    /// The caller.
+pub struct CompressPoolState<'info> {
+    /// Authority.
     pub authority: Signer<'info>,
     /// Store.
     pub store: AccountLoader<'info, Store>,
     /// Market.
     #[account(mut, has_one = store)]
     pub market: AccountLoader<'info, Market>,
-    /// The buffer to use.
-    #[account(mut, has_one = store, has_one = authority @ CoreError::PermissionDenied)]
-    pub buffer: Account<'info, MarketConfigBuffer>,
 }
 
-/// Update market config with buffer.
+/// Zero-fill the reserved ranges of the market.
 ///
 /// ## CHECK
-/// - Only MARKET_KEEPER can udpate the config of market.
-pub(crate) fn unchecked_update_market_config_with_buffer(
-    ctx: Context<UpdateMarketConfigWithBuffer>,
-) -> Result<()> {
-    let buffer = &ctx.accounts.buffer;
-    require_gt!(
-        buffer.expiry,
-        Clock::get()?.unix_timestamp,
-        CoreError::InvalidArgument
-    );
-    ctx.accounts
-        .market
-        .load_mut()?
-        .update_config_with_buffer(buffer)?;
+/// - Only ADMIN can compress the market state.
+pub(crate) fn unchecked_compress_pool_state(ctx: Context<CompressPoolState>) -> Result<()> {
+    let (market_token, bytes_zeroed) = {
+        let mut market = ctx.accounts.market.load_mut()?;
+        let bytes_zeroed = market.zero_reserved();
+        (market.meta.market_token_mint, bytes_zeroed)
+    };
+
+    let event_emitter = EventEmitter::new(&ctx.accounts.event_authority, ctx.bumps.event_authority);
+    event_emitter.emit_cpi(&MarketStateCompressed {
+        market_token,
+        bytes_zeroed,
+    })?;
+
     msg!(
-        "{} updated with buffer {}",
-        ctx.accounts.market.load()?.description()?,
-        buffer.key()
+        "[Market] zeroed {} reserved bytes of the {} market",
+        bytes_zeroed,
+        market_token
     );
     Ok(())
 }
 
-impl<'info> internal::Authentication<'info> for UpdateMarketConfigWithBuffer<'info> {
+impl<'info> internal::Authentication<'info> for CompressPoolState<'info> {
     fn authority(&self) -> &Signer<'info> {
         &self.authority
     }
@@ -432,191 +1687,490 @@ impl<'info> internal::Authentication<'info> for UpdateMarketConfigWithBuffer<'in
     }
 }
 
-/// The accounts definition for read-only instructions for market.
+/// The accounts definition for [`export_market_state`](crate::gmsol_store::export_market_state).
+///
+/// *[See also the documentation for the instruction.](crate::gmsol_store::export_market_state)*
+#[event_cpi]
 #[derive(Accounts)]
-pub struct ReadMarket<'info> {
+pub struct ExportMarketState<'info> {
+    /// Authority.
+    pub authority: Signer<'info>,
+    /// Store.
+    pub store: AccountLoader<'info, Store>,
     /// Market.
+    #[account(has_one = store)]
     pub market: AccountLoader<'info, Market>,
 }
 
-/// Get market status.
-pub(crate) fn get_market_status(
-    ctx: Context<ReadMarket>,
-    prices: &Prices<u128>,
-    maximize_pnl: bool,
-    maximize_pool_value: bool,
-) -> Result<MarketStatus> {
+/// Export the full state of the market.
+///
+/// ## CHECK
+/// - Only ORACLE_CONTROLLER can export the market state.
+pub(crate) fn unchecked_export_market_state(ctx: Context<ExportMarketState>) -> Result<[u8; 32]> {
     let market = ctx.accounts.market.load()?;
-    let status = MarketStatus::from_market(&market, prices, maximize_pnl, maximize_pool_value)
-        .map_err(ModelError::from)?;
-    Ok(status)
+    let market_token = market.meta.market_token_mint;
+    let state_hash = market.state_hash();
+
+    let event = MarketStateExportRef::new(market_token, market.state_bytes(), state_hash);
+    let event_emitter = EventEmitter::new(&ctx.accounts.event_authority, ctx.bumps.event_authority);
+    event_emitter.emit_cpi_with_space(&event, event.space())?;
+
+    msg!("[Market] exported the state of the {} market", market_token);
+    Ok(state_hash)
 }
 
-/// The accounts definition for read-only instructions for market.
+impl<'info> internal::Authentication<'info> for ExportMarketState<'info> {
+    fn authority(&self) -> &Signer<'info> {
+        &self.authority
+    }
+
+    fn store(&self) -> &AccountLoader<'info, Store> {
+        &self.store
+    }
+}
+
+/// The accounts definition for [`claim_fees_from_market`](crate::gmsol_store::claim_fees_from_market).
+///
+/// *[See also the documentation for the instruction.](crate::gmsol_store::claim_fees_from_market)*
+#[event_cpi]
 #[derive(Accounts)]
-pub struct ReadMarketWithToken<'info> {
-    /// Market.
+pub struct ClaimFeesFromMarket<'info> {
+    pub authority: Signer<'info>,
+    pub store: AccountLoader<'info, Store>,
+    #[account(mut, has_one = store)]
+    pub market: AccountLoader<'info, Market>,
+    pub token_mint: InterfaceAccount<'info, anchor_spl::token_interface::Mint>,
     #[account(
-        constraint = market.load()?.meta.market_token_mint == market_token.key() @ CoreError::InvalidArgument,
+        mut,
+        token::mint = token_mint,
+        token::authority = store,
+        token::token_program = token_program,
+        seeds = [
+            constants::MARKET_VAULT_SEED,
+            store.key().as_ref(),
+            token_mint.key().as_ref(),
+        ],
+        bump,
     )]
-    pub market: AccountLoader<'info, Market>,
-    /// Market token.
-    pub market_token: Account<'info, Mint>,
+    pub vault: InterfaceAccount<'info, anchor_spl::token_interface::TokenAccount>,
+    #[account(
+        mut,
+        token::mint = token_mint,
+    )]
+    pub target: InterfaceAccount<'info, anchor_spl::token_interface::TokenAccount>,
+    pub token_program: Interface<'info, anchor_spl::token_interface::TokenInterface>,
 }
 
-/// Get market token price.
-pub(crate) fn get_market_token_price(
-    ctx: Context<ReadMarketWithToken>,
-    prices: &Prices<u128>,
-    pnl_factor: PnlFactorKind,
-    maximize: bool,
-) -> Result<u128> {
-    let market = ctx.accounts.market.load()?;
-    let liquidity_market = market.as_liquidity_market(&ctx.accounts.market_token);
-    let price = liquidity_market
-        .market_token_price(prices, pnl_factor, maximize)
+/// Claim fees from the market.
+///
+/// # Errors
+/// - Only the receiver of treasury can claim fees.
+pub(crate) fn claim_fees_from_market(ctx: Context<ClaimFeesFromMarket>) -> Result<u64> {
+    // Validate the authority to be the receiver for the treasury.
+    ctx.accounts
+        .store
+        .load()?
+        .validate_not_restarted()?
+        .validate_claim_fees_address(ctx.accounts.authority.key)?;
+
+    let event_emitter = EventEmitter::new(&ctx.accounts.event_authority, ctx.bumps.event_authority);
+
+    let amount = claim_all_fees_from_pool(
+        &ctx.accounts.market,
+        &ctx.accounts.token_mint.key(),
+        event_emitter,
+    )?;
+
+    // Transfer out the tokens.
+    let token = &ctx.accounts.token_mint;
+    MarketTransferOutOperation::builder()
+        .store(&ctx.accounts.store)
+        .market(&ctx.accounts.market)
+        .amount(amount)
+        .decimals(token.decimals)
+        .to(ctx.accounts.target.to_account_info())
+        .token_mint(token.to_account_info())
+        .vault(ctx.accounts.vault.to_account_info())
+        .token_program(ctx.accounts.token_program.to_account_info())
+        .event_emitter(event_emitter)
+        .build()
+        .execute()?;
+
+    msg!(
+        "Claimed `{}` {} from the {} market",
+        amount,
+        token.key(),
+        ctx.accounts.market.load()?.meta.market_token_mint
+    );
+    Ok(amount)
+}
+
+/// Claim all fees of the given token from the claimable fee pool,
+/// and return the claimed amount.
+pub(crate) fn claim_all_fees_from_pool<'info>(
+    market: &AccountLoader<'info, Market>,
+    token: &Pubkey,
+    event_emitter: EventEmitter<'_, 'info>,
+) -> Result<u64> {
+    let mut market = RevertibleMarket::new(market, event_emitter)?;
+    let is_long_token = market.market_meta().to_token_side(token)?;
+    let the_opposite_side = !is_long_token;
+    let is_pure = market.market_meta().is_pure();
+    let pool = market.claimable_fee_pool_mut().map_err(ModelError::from)?;
+
+    let mut deltas = (0, 0);
+
+    // Saturating claim all fees from the pool.
+    let mut amount: u64 = pool
+        .amount(is_long_token)
+        .map_err(ModelError::from)?
+        .min(u128::from(u64::MAX))
+        .try_into()
+        .expect("must success");
+
+    deltas.0 = (u128::from(amount))
+        .to_opposite_signed()
         .map_err(ModelError::from)?;
-    Ok(price)
+
+    if is_pure {
+        let the_opposite_side_amount: u64 = pool
+            .amount(the_opposite_side)
+            .map_err(ModelError::from)?
+            .min(u128::from(u64::MAX))
+            .try_into()
+            .expect("must success");
+        deltas.1 = (u128::from(the_opposite_side_amount))
+            .to_opposite_signed()
+            .map_err(ModelError::from)?;
+        amount = amount
+            .checked_add(the_opposite_side_amount)
+            .ok_or_else(|| error!(CoreError::TokenAmountOverflow))?;
+    }
+
+    if deltas.0 != 0 {
+        pool.apply_delta_amount(is_long_token, &deltas.0)
+            .map_err(ModelError::from)?;
+    }
+
+    if deltas.1 != 0 {
+        pool.apply_delta_amount(the_opposite_side, &deltas.1)
+            .map_err(ModelError::from)?;
+    }
+
+    market
+        .validate_market_balance_for_the_given_token(token, amount)
+        .map_err(ModelError::from)?;
+    market.commit();
+
+    Ok(amount)
 }
 
-/// The accounts definition for [`initialize_market_config_buffer`](crate::gmsol_store::initialize_market_config_buffer).
+/// The accounts definition for [`claim_fees_split`](crate::gmsol_store::claim_fees_split).
 ///
-/// *[See also the documentation for the instruction.](crate::gmsol_store::initialize_market_config_buffer)*
+/// *[See also the documentation for the instruction.](crate::gmsol_store::claim_fees_split)*
+#[event_cpi]
 #[derive(Accounts)]
-pub struct InitializeMarketConfigBuffer<'info> {
-    /// The caller.
-    #[account(mut)]
+pub struct ClaimFeesSplit<'info> {
+    /// The treasury receiver.
     pub authority: Signer<'info>,
     /// Store.
     pub store: AccountLoader<'info, Store>,
-    /// Buffer account to create.
-    #[account(init, payer = authority, space = 8 + MarketConfigBuffer::init_space(0))]
-    pub buffer: Account<'info, MarketConfigBuffer>,
-    /// System Program.
-    pub system_program: Program<'info, System>,
+    /// Market.
+    #[account(mut, has_one = store)]
+    pub market: AccountLoader<'info, Market>,
+    /// The token to claim.
+    pub token_mint: InterfaceAccount<'info, anchor_spl::token_interface::Mint>,
+    /// The market vault for the token.
+    #[account(
+        mut,
+        token::mint = token_mint,
+        token::authority = store,
+        token::token_program = token_program,
+        seeds = [
+            constants::MARKET_VAULT_SEED,
+            store.key().as_ref(),
+            token_mint.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub vault: InterfaceAccount<'info, anchor_spl::token_interface::TokenAccount>,
+    /// The token account owned by the treasury receiver.
+    #[account(
+        mut,
+        token::mint = token_mint,
+    )]
+    pub treasury_target: InterfaceAccount<'info, anchor_spl::token_interface::TokenAccount>,
+    /// The token account owned by the GT buyback address.
+    #[account(
+        mut,
+        token::mint = token_mint,
+    )]
+    pub buyback_target: InterfaceAccount<'info, anchor_spl::token_interface::TokenAccount>,
+    /// The token program.
+    pub token_program: Interface<'info, anchor_spl::token_interface::TokenInterface>,
 }
 
-/// Initialize a market config buffer account.
-pub(crate) fn initialize_market_config_buffer(
-    ctx: Context<InitializeMarketConfigBuffer>,
-    expire_after_secs: u32,
-) -> Result<()> {
-    let buffer = &mut ctx.accounts.buffer;
-    buffer.authority = ctx.accounts.authority.key();
-    buffer.store = ctx.accounts.store.key();
-    buffer.expiry = Clock::get()?
-        .unix_timestamp
-        .saturating_add_unsigned(expire_after_secs as u64);
+/// Claim fees from the market and split them between the treasury receiver
+/// and the GT buyback address.
+///
+/// # Errors
+/// - Only the receiver of treasury can claim fees.
+/// - The targets must be owned by the treasury receiver and the GT buyback address respectively.
+/// - The GT buyback address must be set.
+pub(crate) fn claim_fees_split(ctx: Context<ClaimFeesSplit>) -> Result<()> {
+    let event_emitter = EventEmitter::new(&ctx.accounts.event_authority, ctx.bumps.event_authority);
+
+    let (treasury_amount, buyback_amount) = {
+        let store = ctx.accounts.store.load()?;
+        // Validate the authority to be the receiver for the treasury.
+        store
+            .validate_not_restarted()?
+            .validate_claim_fees_address(ctx.accounts.authority.key)?;
+        store.validate_claim_fees_address(&ctx.accounts.treasury_target.owner)?;
+        store.validate_buyback_address(&ctx.accounts.buyback_target.owner)?;
+
+        let amount = claim_all_fees_from_pool(
+            &ctx.accounts.market,
+            &ctx.accounts.token_mint.key(),
+            event_emitter,
+        )?;
+        store.split_fees(amount)?
+    };
+
+    let token = &ctx.accounts.token_mint;
+    for (amount, to) in [
+        (treasury_amount, &ctx.accounts.treasury_target),
+        (buyback_amount, &ctx.accounts.buyback_target),
+    ] {
+        MarketTransferOutOperation::builder()
+            .store(&ctx.accounts.store)
+            .market(&ctx.accounts.market)
+            .amount(amount)
+            .decimals(token.decimals)
+            .to(to.to_account_info())
+            .token_mint(token.to_account_info())
+            .vault(ctx.accounts.vault.to_account_info())
+            .token_program(ctx.accounts.token_program.to_account_info())
+            .event_emitter(event_emitter)
+            .build()
+            .execute()?;
+    }
+
+    let market_token = ctx.accounts.market.load()?.meta.market_token_mint;
+    event_emitter.emit_cpi(&FeesClaimedWithSplit {
+        market_token,
+        token: token.key(),
+        treasury_amount,
+        buyback_amount,
+    })?;
+
+    msg!(
+        "Claimed `{}` (treasury) and `{}` (buyback) {} from the {} market",
+        treasury_amount,
+        buyback_amount,
+        token.key(),
+        market_token,
+    );
     Ok(())
 }
 
-/// The accounts definition for [`set_market_config_buffer_authority`](crate::gmsol_store::set_market_config_buffer_authority).
+/// The accounts definition for [`initialize_virtual_pool`](crate::gmsol_store::initialize_virtual_pool).
 ///
-/// *[See also the documentation for the instruction.](crate::gmsol_store::set_market_config_buffer_authority)*
+/// *[See also the documentation for the instruction.](crate::gmsol_store::initialize_virtual_pool)*
 #[derive(Accounts)]
-pub struct SetMarketConfigBufferAuthority<'info> {
-    /// The authority.
+#[instruction(key: Pubkey)]
+pub struct InitializeVirtualPool<'info> {
+    /// The caller.
     #[account(mut)]
     pub authority: Signer<'info>,
-    /// Buffer.
-    #[account(mut, has_one = authority @ CoreError::PermissionDenied)]
-    pub buffer: Account<'info, MarketConfigBuffer>,
+    /// Store.
+    pub store: AccountLoader<'info, Store>,
+    /// The virtual pool to initialize.
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + VirtualPool::INIT_SPACE,
+        seeds = [VirtualPool::SEED, store.key().as_ref(), key.as_ref()],
+        bump,
+    )]
+    pub virtual_pool: AccountLoader<'info, VirtualPool>,
+    /// The system program.
+    pub system_program: Program<'info, System>,
 }
 
-/// Set the authority of the buffer account.
-pub(crate) fn set_market_config_buffer_authority(
-    ctx: Context<SetMarketConfigBufferAuthority>,
-    new_authority: Pubkey,
+/// Initialize a virtual pool.
+///
+/// ## CHECK
+/// - Only MARKET_KEEPER can use this instruction.
+pub(crate) fn unchecked_initialize_virtual_pool(
+    ctx: Context<InitializeVirtualPool>,
+    key: &Pubkey,
 ) -> Result<()> {
-    ctx.accounts.buffer.authority = new_authority;
+    require!(
+        crate::utils::pubkey::optional_address(key).is_some(),
+        CoreError::InvalidArgument
+    );
+    ctx.accounts.virtual_pool.load_init()?.init(
+        ctx.bumps.virtual_pool,
+        &ctx.accounts.store.key(),
+        key,
+    );
     Ok(())
 }
 
-/// The accounts definition for [`close_market_config_buffer`](crate::gmsol_store::close_market_config_buffer).
-///
-/// *[See also the documentation for the instruction.](crate::gmsol_store::close_market_config_buffer)*
-#[derive(Accounts)]
-pub struct CloseMarketConfigBuffer<'info> {
-    /// The authority.
-    #[account(mut)]
-    pub authority: Signer<'info>,
-    /// Buffer.
-    #[account(mut, close = receiver, has_one = authority @ CoreError::PermissionDenied)]
-    pub buffer: Account<'info, MarketConfigBuffer>,
-    /// Receiver.
-    /// CHECK: Only used to receive funds after closing the buffer account.
-    #[account(mut)]
-    pub receiver: UncheckedAccount<'info>,
-}
+impl<'info> internal::Authentication<'info> for InitializeVirtualPool<'info> {
+    fn authority(&self) -> &Signer<'info> {
+        &self.authority
+    }
 
-/// Close the buffer account.
-pub(crate) fn close_market_config_buffer(_ctx: Context<CloseMarketConfigBuffer>) -> Result<()> {
-    Ok(())
+    fn store(&self) -> &AccountLoader<'info, Store> {
+        &self.store
+    }
 }
 
-/// The accounts definition for [`push_to_market_config_buffer`](crate::gmsol_store::push_to_market_config_buffer).
+/// The accounts definition for [`add_market_to_virtual_pool`](crate::gmsol_store::add_market_to_virtual_pool).
 ///
-/// *[See also the documentation for the instruction.](crate::gmsol_store::push_to_market_config_buffer)*
+/// *[See also the documentation for the instruction.](crate::gmsol_store::add_market_to_virtual_pool)*
 #[derive(Accounts)]
-#[instruction(new_configs: Vec<(String, Factor)>)]
-pub struct PushToMarketConfigBuffer<'info> {
-    /// Authority.
-    #[account(mut)]
+#[instruction(market_token: Pubkey, key: Pubkey)]
+pub struct AddMarketToVirtualPool<'info> {
+    /// The caller.
     pub authority: Signer<'info>,
-    /// Buffer.
+    /// Store.
+    pub store: AccountLoader<'info, Store>,
+    /// Market.
     #[account(
         mut,
-        has_one = authority @ CoreError::PermissionDenied,
-        realloc = 8 + buffer.space_after_push(new_configs.len()),
-        realloc::payer = authority,
-        realloc::zero = false,
+        has_one = store,
+        seeds = [Market::SEED, store.key().as_ref(), market_token.as_ref()],
+        bump = market.load()?.bump,
     )]
-    pub buffer: Account<'info, MarketConfigBuffer>,
-    system_program: Program<'info, System>,
+    pub market: AccountLoader<'info, Market>,
+    /// The virtual pool.
+    #[account(
+        mut,
+        has_one = store,
+        seeds = [VirtualPool::SEED, store.key().as_ref(), key.as_ref()],
+        bump = virtual_pool.load()?.bump,
+    )]
+    pub virtual_pool: AccountLoader<'info, VirtualPool>,
 }
 
-/// Push to the buffer account.
-pub(crate) fn push_to_market_config_buffer(
-    ctx: Context<PushToMarketConfigBuffer>,
-    new_configs: Vec<EntryArgs>,
+/// Add the market to the virtual pool.
+///
+/// ## CHECK
+/// - Only MARKET_KEEPER can use this instruction.
+pub(crate) fn unchecked_add_market_to_virtual_pool(
+    ctx: Context<AddMarketToVirtualPool>,
+    key: &Pubkey,
 ) -> Result<()> {
-    let buffer = &mut ctx.accounts.buffer;
-    for entry in new_configs {
-        buffer.push(entry.try_into()?);
-    }
+    let mut market = ctx.accounts.market.load_mut()?;
+    market.set_virtual_pool_key(key)?;
+
+    // Include the current open interest of the market in the virtual pool.
+    let (long_open_interest, short_open_interest) = {
+        let open_interest = market.open_interest().map_err(ModelError::from)?;
+        (
+            open_interest.long_amount().map_err(ModelError::from)?,
+            open_interest.short_amount().map_err(ModelError::from)?,
+        )
+    };
+    let mut virtual_pool = ctx.accounts.virtual_pool.load_mut()?;
+    virtual_pool.apply_delta(
+        true,
+        long_open_interest
+            .try_into()
+            .map_err(|_| error!(CoreError::ValueOverflow))?,
+    )?;
+    virtual_pool.apply_delta(
+        false,
+        short_open_interest
+            .try_into()
+            .map_err(|_| error!(CoreError::ValueOverflow))?,
+    )?;
+
+    msg!(
+        "[Virtual Pool] added market {} to virtual pool {}",
+        market.meta.market_token_mint,
+        key
+    );
     Ok(())
 }
 
-/// The accounts definition for [`toggle_gt_minting`](crate::gmsol_store::toggle_gt_minting).
+impl<'info> internal::Authentication<'info> for AddMarketToVirtualPool<'info> {
+    fn authority(&self) -> &Signer<'info> {
+        &self.authority
+    }
+
+    fn store(&self) -> &AccountLoader<'info, Store> {
+        &self.store
+    }
+}
+
+/// The accounts definition for [`initialize_market_oi_caps`](crate::gmsol_store::initialize_market_oi_caps).
 ///
-/// *[See also the documentation for the instruction.](crate::gmsol_store::toggle_gt_minting)*
+/// *[See also the documentation for the instruction.](crate::gmsol_store::initialize_market_oi_caps)*
 #[derive(Accounts)]
-pub struct ToggleGTMinting<'info> {
+pub struct InitializeMarketOiCaps<'info> {
     /// The caller.
+    #[account(mut)]
     pub authority: Signer<'info>,
     /// Store.
     pub store: AccountLoader<'info, Store>,
     /// Market.
     #[account(mut, has_one = store)]
     pub market: AccountLoader<'info, Market>,
+    /// The OI caps account to initialize.
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + MarketOiCaps::INIT_SPACE,
+        seeds = [
+            MarketOiCaps::SEED,
+            store.key().as_ref(),
+            market.load()?.meta().market_token_mint.as_ref(),
+        ],
+        bump,
+    )]
+    pub oi_caps: AccountLoader<'info, MarketOiCaps>,
+    /// The system program.
+    pub system_program: Program<'info, System>,
 }
 
-/// Toggle GT Minting.
+/// Initialize the OI caps account of the market.
 ///
 /// ## CHECK
 /// - Only MARKET_KEEPER can use this instruction.
-pub(crate) fn unchecked_toggle_gt_minting(
-    ctx: Context<ToggleGTMinting>,
-    enable: bool,
+pub(crate) fn unchecked_initialize_market_oi_caps(
+    ctx: Context<InitializeMarketOiCaps>,
+    max_long: Factor,
+    max_short: Factor,
+    adjustment_rate_limit: Factor,
 ) -> Result<()> {
-    ctx.accounts
-        .market
-        .load_mut()?
-        .set_is_gt_minting_enbaled(enable);
+    let now = Clock::get()?.unix_timestamp;
+    let mut market = ctx.accounts.market.load_mut()?;
+    let market_token = market.meta().market_token_mint;
+    ctx.accounts.oi_caps.load_init()?.init(
+        ctx.bumps.oi_caps,
+        &ctx.accounts.store.key(),
+        &market_token,
+        max_long,
+        max_short,
+        adjustment_rate_limit,
+        now,
+    );
+    market.set_max_open_interest(max_long, max_short);
+    msg!(
+        "[OI Caps] {}: initialized with max_long = {}, max_short = {}, rate_limit = {}",
+        market_token,
+        max_long,
+        max_short,
+        adjustment_rate_limit,
+    );
     Ok(())
 }
 
-impl<'info> internal::Authentication<'info> for ToggleGTMinting<'info> {
+impl<'info> internal::Authentication<'info> for InitializeMarketOiCaps<'info> {
     fn authority(&self) -> &Signer<'info> {
         &self.authority
     }
@@ -626,128 +2180,250 @@ impl<'info> internal::Authentication<'info> for ToggleGTMinting<'info> {
     }
 }
 
-/// The accounts definition for [`claim_fees_from_market`](crate::gmsol_store::claim_fees_from_market).
+/// The accounts definition for [`update_market_oi_caps`](crate::gmsol_store::update_market_oi_caps).
 ///
-/// *[See also the documentation for the instruction.](crate::gmsol_store::claim_fees_from_market)*
-#[event_cpi]
+/// *[See also the documentation for the instruction.](crate::gmsol_store::update_market_oi_caps)*
 #[derive(Accounts)]
-pub struct ClaimFeesFromMarket<'info> {
+pub struct UpdateMarketOiCaps<'info> {
+    /// The caller.
     pub authority: Signer<'info>,
+    /// Store.
     pub store: AccountLoader<'info, Store>,
+    /// Market.
     #[account(mut, has_one = store)]
     pub market: AccountLoader<'info, Market>,
-    pub token_mint: InterfaceAccount<'info, anchor_spl::token_interface::Mint>,
+    /// The OI caps account of the market.
     #[account(
         mut,
-        token::mint = token_mint,
-        token::authority = store,
-        token::token_program = token_program,
+        has_one = store,
         seeds = [
-            constants::MARKET_VAULT_SEED,
+            MarketOiCaps::SEED,
             store.key().as_ref(),
-            token_mint.key().as_ref(),
+            market.load()?.meta().market_token_mint.as_ref(),
         ],
-        bump,
+        bump = oi_caps.load()?.bump,
     )]
-    pub vault: InterfaceAccount<'info, anchor_spl::token_interface::TokenAccount>,
-    #[account(
-        mut,
-        token::mint = token_mint,
-    )]
-    pub target: InterfaceAccount<'info, anchor_spl::token_interface::TokenAccount>,
-    pub token_program: Interface<'info, anchor_spl::token_interface::TokenInterface>,
+    pub oi_caps: AccountLoader<'info, MarketOiCaps>,
 }
 
-/// Claim fees from the market.
+/// Update the OI caps of the market.
 ///
-/// # Errors
-/// - Only the receiver of treasury can claim fees.
-pub(crate) fn claim_fees_from_market(ctx: Context<ClaimFeesFromMarket>) -> Result<u64> {
-    // Validate the authority to be the receiver for the treasury.
-    ctx.accounts
-        .store
-        .load()?
-        .validate_not_restarted()?
-        .validate_claim_fees_address(ctx.accounts.authority.key)?;
+/// ## CHECK
+/// - Only CONFIG_KEEPER can use this instruction.
+pub(crate) fn unchecked_update_market_oi_caps(
+    ctx: Context<UpdateMarketOiCaps>,
+    new_max_long: Factor,
+    new_max_short: Factor,
+) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    update_oi_caps(
+        &mut *ctx.accounts.market.load_mut()?,
+        &mut *ctx.accounts.oi_caps.load_mut()?,
+        new_max_long,
+        new_max_short,
+        now,
+    )
+}
 
-    let event_emitter = EventEmitter::new(&ctx.accounts.event_authority, ctx.bumps.event_authority);
+fn update_oi_caps(
+    market: &mut Market,
+    oi_caps: &mut MarketOiCaps,
+    new_max_long: Factor,
+    new_max_short: Factor,
+    now: i64,
+) -> Result<()> {
+    oi_caps.adjust(new_max_long, new_max_short, now)?;
+    market.set_max_open_interest(new_max_long, new_max_short);
+    msg!(
+        "[OI Caps] {}: updated to max_long = {}, max_short = {}",
+        market.meta().market_token_mint,
+        new_max_long,
+        new_max_short,
+    );
+    Ok(())
+}
 
-    let amount = {
-        let token = ctx.accounts.token_mint.key();
+impl<'info> internal::Authentication<'info> for UpdateMarketOiCaps<'info> {
+    fn authority(&self) -> &Signer<'info> {
+        &self.authority
+    }
 
-        let mut market = RevertibleMarket::new(&ctx.accounts.market, event_emitter)?;
-        let is_long_token = market.market_meta().to_token_side(&token)?;
-        let the_opposite_side = !is_long_token;
-        let is_pure = market.market_meta().is_pure();
-        let pool = market.claimable_fee_pool_mut().map_err(ModelError::from)?;
+    fn store(&self) -> &AccountLoader<'info, Store> {
+        &self.store
+    }
+}
 
-        let mut deltas = (0, 0);
+/// The accounts definition for [`batch_update_market_oi_caps`](crate::gmsol_store::batch_update_market_oi_caps).
+///
+/// Remaining accounts expected by this instruction:
+///
+///   - 0..2N. `[writable]` N pairs of `[market, oi_caps]`, one for each update in the same order,
+///     where N is the number of the updates.
+#[event_cpi]
+#[derive(Accounts)]
+pub struct BatchUpdateMarketOiCaps<'info> {
+    /// The caller.
+    pub authority: Signer<'info>,
+    /// Store.
+    pub store: AccountLoader<'info, Store>,
+}
 
-        // Saturating claim all fees from the pool.
-        let mut amount: u64 = pool
-            .amount(is_long_token)
-            .map_err(ModelError::from)?
-            .min(u128::from(u64::MAX))
-            .try_into()
-            .expect("must success");
+/// Update the OI caps of multiple markets.
+///
+/// ## CHECK
+/// - Only CONFIG_KEEPER can use this instruction.
+pub(crate) fn unchecked_batch_update_market_oi_caps<'info>(
+    ctx: Context<'_, '_, 'info, 'info, BatchUpdateMarketOiCaps<'info>>,
+    updates: &[OiCapUpdate],
+) -> Result<()> {
+    require!(!updates.is_empty(), CoreError::InvalidArgument);
+    require_gte!(
+        MAX_OI_CAP_UPDATES,
+        updates.len(),
+        CoreError::ExceedMaxLengthLimit
+    );
+    require_eq!(
+        ctx.remaining_accounts.len(),
+        updates.len() * 2,
+        CoreError::InvalidArgument
+    );
 
-        deltas.0 = (u128::from(amount))
-            .to_opposite_signed()
-            .map_err(ModelError::from)?;
+    let store = ctx.accounts.store.key();
+    let now = Clock::get()?.unix_timestamp;
+    for (update, accounts) in updates.iter().zip(ctx.remaining_accounts.chunks_exact(2)) {
+        let market = AccountLoader::<Market>::try_from(&accounts[0])?;
+        let oi_caps = AccountLoader::<MarketOiCaps>::try_from(&accounts[1])?;
 
-        if is_pure {
-            let the_opposite_side_amount: u64 = pool
-                .amount(the_opposite_side)
-                .map_err(ModelError::from)?
-                .min(u128::from(u64::MAX))
-                .try_into()
-                .expect("must success");
-            deltas.1 = (u128::from(the_opposite_side_amount))
-                .to_opposite_signed()
-                .map_err(ModelError::from)?;
-            amount = amount
-                .checked_add(the_opposite_side_amount)
-                .ok_or_else(|| error!(CoreError::TokenAmountOverflow))?;
-        }
+        let mut market = market.load_mut()?;
+        require_keys_eq!(market.store, store, CoreError::StoreMismatched);
+        require_keys_eq!(
+            market.meta().market_token_mint,
+            update.market_token,
+            CoreError::MarketTokenMintMismatched
+        );
 
-        if deltas.0 != 0 {
-            pool.apply_delta_amount(is_long_token, &deltas.0)
-                .map_err(ModelError::from)?;
-        }
+        let mut oi_caps_data = oi_caps.load_mut()?;
+        require_keys_eq!(oi_caps_data.store, store, CoreError::StoreMismatched);
+        require_keys_eq!(
+            oi_caps_data.market_token,
+            update.market_token,
+            CoreError::MarketTokenMintMismatched
+        );
+        let expected = Pubkey::create_program_address(
+            &[
+                MarketOiCaps::SEED,
+                store.as_ref(),
+                update.market_token.as_ref(),
+                &[oi_caps_data.bump],
+            ],
+            &crate::ID,
+        )
+        .map_err(|_| error!(CoreError::InvalidArgument))?;
+        require_keys_eq!(expected, oi_caps.key(), ErrorCode::ConstraintSeeds);
 
-        if deltas.1 != 0 {
-            pool.apply_delta_amount(the_opposite_side, &deltas.1)
-                .map_err(ModelError::from)?;
-        }
+        update_oi_caps(
+            &mut market,
+            &mut oi_caps_data,
+            update.max_oi_long,
+            update.max_oi_short,
+            now,
+        )?;
+    }
 
-        market
-            .validate_market_balance_for_the_given_token(&token, amount)
-            .map_err(ModelError::from)?;
-        market.commit();
+    let event_emitter = EventEmitter::new(&ctx.accounts.event_authority, ctx.bumps.event_authority);
+    event_emitter.emit_cpi(&BatchOiCapUpdated {
+        markets_updated: updates.len() as u16,
+    })?;
+    Ok(())
+}
 
-        amount
-    };
+impl<'info> internal::Authentication<'info> for BatchUpdateMarketOiCaps<'info> {
+    fn authority(&self) -> &Signer<'info> {
+        &self.authority
+    }
 
-    // Transfer out the tokens.
-    let token = &ctx.accounts.token_mint;
-    MarketTransferOutOperation::builder()
-        .store(&ctx.accounts.store)
-        .market(&ctx.accounts.market)
-        .amount(amount)
-        .decimals(token.decimals)
-        .to(ctx.accounts.target.to_account_info())
-        .token_mint(token.to_account_info())
-        .vault(ctx.accounts.vault.to_account_info())
-        .token_program(ctx.accounts.token_program.to_account_info())
-        .event_emitter(event_emitter)
-        .build()
-        .execute()?;
+    fn store(&self) -> &AccountLoader<'info, Store> {
+        &self.store
+    }
+}
+
+/// The accounts definition for [`initialize_market_analytics`](crate::gmsol_store::initialize_market_analytics).
+///
+/// *[See also the documentation for the instruction.](crate::gmsol_store::initialize_market_analytics)*
+#[derive(Accounts)]
+#[instruction(period_secs: u64)]
+pub struct InitializeMarketAnalytics<'info> {
+    /// Payer.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// Store.
+    pub store: AccountLoader<'info, Store>,
+    /// Market.
+    #[account(has_one = store)]
+    pub market: AccountLoader<'info, Market>,
+    /// The analytics account of the current period to initialize.
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + MarketAnalytics::INIT_SPACE,
+        seeds = [
+            MarketAnalytics::SEED,
+            store.key().as_ref(),
+            market.load()?.meta().market_token_mint.as_ref(),
+            &period_secs.to_le_bytes(),
+            &MarketAnalytics::current_period_key(period_secs)?.to_le_bytes(),
+        ],
+        bump,
+    )]
+    pub analytics: AccountLoader<'info, MarketAnalytics>,
+    /// The system program.
+    pub system_program: Program<'info, System>,
+}
 
+pub(crate) fn initialize_market_analytics(
+    ctx: Context<InitializeMarketAnalytics>,
+    period_secs: u64,
+) -> Result<()> {
+    let market_token = ctx.accounts.market.load()?.meta().market_token_mint;
+    let period_key = MarketAnalytics::current_period_key(period_secs)?;
+    let mut analytics = ctx.accounts.analytics.load_init()?;
+    analytics.init(
+        ctx.bumps.analytics,
+        &ctx.accounts.store.key(),
+        &market_token,
+        period_secs,
+        period_key,
+    )?;
     msg!(
-        "Claimed `{}` {} from the {} market",
-        amount,
-        token.key(),
-        ctx.accounts.market.load()?.meta.market_token_mint
+        "[Analytics] {}: initialized the period starting at {} with length {}s",
+        market_token,
+        analytics.period_start(),
+        period_secs,
     );
-    Ok(amount)
+    Ok(())
+}
+
+/// The accounts definition for [`get_ohlcv_period`](crate::gmsol_store::get_ohlcv_period).
+///
+/// *[See also the documentation for the instruction.](crate::gmsol_store::get_ohlcv_period)*
+#[derive(Accounts)]
+pub struct GetOhlcvPeriod<'info> {
+    /// Store.
+    pub store: AccountLoader<'info, Store>,
+    /// The analytics account of the period.
+    #[account(has_one = store)]
+    pub analytics: AccountLoader<'info, MarketAnalytics>,
+}
+
+pub(crate) fn get_ohlcv_period(
+    ctx: Context<GetOhlcvPeriod>,
+    period_start: i64,
+) -> Result<OhlcvData> {
+    let analytics = ctx.accounts.analytics.load()?;
+    require_eq!(
+        analytics.period_start(),
+        period_start,
+        CoreError::InvalidArgument
+    );
+    Ok(analytics.ohlcv())
 }
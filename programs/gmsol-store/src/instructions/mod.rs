@@ -37,11 +37,15 @@ pub mod glv;
 /// Instructions for migrations.
 pub mod migration;
 
+/// Instructions for liquidity mining.
+pub mod liquidity_mining;
+
 pub use config::*;
 pub use exchange::*;
 pub use feature::*;
 pub use glv::*;
 pub use gt::*;
+pub use liquidity_mining::*;
 pub use market::*;
 pub use migration::*;
 pub use oracle::*;
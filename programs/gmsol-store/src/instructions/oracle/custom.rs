@@ -3,7 +3,10 @@ use gmsol_chainlink_datastreams::interface::ChainlinkDataStreamsInterface;
 use gmsol_utils::InitSpace;
 
 use crate::{
-    states::{AmountKey, PriceFeed, PriceFeedPrice, PriceProviderKind, Seed, Store},
+    states::{
+        AmountKey, PriceFeed, PriceFeedPrice, PriceFeedStatus, PriceProviderKind, Seed, Store,
+        TokenMapHeader, TokenMapLoader,
+    },
     utils::internal,
     CoreError,
 };
@@ -92,6 +95,7 @@ pub struct UpdatePriceFeedWithChainlink<'info> {
     #[account(mut, has_one = store, has_one = authority)]
     pub price_feed: AccountLoader<'info, PriceFeed>,
     /// Chainlink Data Streams Program.
+    /// Must be one of the trusted Chainlink verifiers of the store.
     pub chainlink: Interface<'info, ChainlinkDataStreamsInterface>,
 }
 
@@ -102,6 +106,11 @@ pub(crate) fn unchecked_update_price_feed_with_chainlink(
 ) -> Result<()> {
     let accounts = ctx.accounts;
 
+    accounts
+        .store
+        .load()?
+        .validate_trusted_chainlink_verifier(&accounts.chainlink.key())?;
+
     require_eq!(
         accounts.price_feed.load()?.provider()?,
         PriceProviderKind::ChainlinkDataStreams,
@@ -171,3 +180,32 @@ impl UpdatePriceFeedWithChainlink<'_> {
         Ok(())
     }
 }
+
+/// The accounts definition for [`get_price_feed_status`](crate::get_price_feed_status) instruction.
+#[derive(Accounts)]
+pub struct GetPriceFeedStatus<'info> {
+    /// Store.
+    #[account(has_one = token_map)]
+    pub store: AccountLoader<'info, Store>,
+    /// Token map.
+    #[account(has_one = store)]
+    pub token_map: AccountLoader<'info, TokenMapHeader>,
+    /// Price feed.
+    #[account(has_one = store)]
+    pub price_feed: AccountLoader<'info, PriceFeed>,
+}
+
+/// Get the status of the price feed.
+pub(crate) fn get_price_feed_status(ctx: Context<GetPriceFeedStatus>) -> Result<PriceFeedStatus> {
+    let max_age = *ctx
+        .accounts
+        .store
+        .load()?
+        .get_amount_by_key(AmountKey::OracleMaxAge);
+    let feed = ctx.accounts.price_feed.load()?;
+    let token_map = ctx.accounts.token_map.load_token_map()?;
+    let token_config = token_map
+        .get(&feed.token)
+        .ok_or_else(|| error!(CoreError::NotFound))?;
+    feed.status(token_config, max_age, Clock::get()?.unix_timestamp)
+}
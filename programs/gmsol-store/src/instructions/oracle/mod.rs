@@ -4,10 +4,17 @@ pub mod custom;
 use std::ops::Deref;
 
 use anchor_lang::prelude::*;
+use gmsol_utils::InitSpace;
 
 use crate::{
-    states::{Chainlink, Oracle, PriceValidator, Store, TokenMapHeader, TokenMapLoader},
+    events::{EventEmitter, OracleRotated},
+    states::{
+        oracle::adjustment::HOURS_PER_DAY, AmountKey, Chainlink, Factor, LastOraclePrice, Oracle,
+        PriceAdjustmentSchedule, PriceValidator, Seed, Store, TokenMapAccess, TokenMapHeader,
+        TokenMapLoader,
+    },
     utils::internal,
+    CoreError,
 };
 
 pub use self::custom::*;
@@ -58,7 +65,9 @@ pub struct ClearAllPrices<'info> {
 /// Clear all prices of the given oracle account.
 /// CHECK: only ORACLE_CONTROLLER is allowed to invoke.
 pub(crate) fn unchecked_clear_all_prices(ctx: Context<ClearAllPrices>) -> Result<()> {
-    ctx.accounts.oracle.load_mut()?.clear_all_prices();
+    let mut oracle = ctx.accounts.oracle.load_mut()?;
+    oracle.validate_not_decommissioned()?;
+    oracle.clear_all_prices();
     Ok(())
 }
 
@@ -77,6 +86,9 @@ impl<'info> internal::Authentication<'info> for ClearAllPrices<'info> {
 /// Remaining accounts expected by this instruction:
 ///
 ///   - 0..N. `[]` N feed accounts, where N represents the total number of tokens.
+///   - N..N+M. M [`PriceAdjustmentSchedule`] or `[mutable]` [`LastOraclePrice`] accounts
+///     of the tokens, in any order. The schedules are optional, while the last price
+///     account is required for each token with a non-zero max price deviation.
 #[derive(Accounts)]
 pub struct SetPricesFromPriceFeed<'info> {
     /// The caller.
@@ -104,18 +116,84 @@ pub(crate) fn unchecked_set_prices_from_price_feed<'info>(
     ctx: Context<'_, '_, 'info, 'info, SetPricesFromPriceFeed<'info>>,
     tokens: Vec<Pubkey>,
 ) -> Result<()> {
-    let validator = PriceValidator::try_from(ctx.accounts.store.load()?.deref())?;
-    let token_map = ctx.accounts.token_map.load_token_map()?;
-    ctx.accounts
-        .oracle
-        .load_mut()?
-        .set_prices_from_remaining_accounts(
-            validator,
-            &token_map,
-            &tokens,
-            ctx.remaining_accounts,
-            ctx.accounts.chainlink_program.as_ref(),
+    let (validator, window) = {
+        let store = ctx.accounts.store.load()?;
+        (
+            PriceValidator::try_from(store.deref())?,
+            *store.get_amount_by_key(AmountKey::OraclePriceDeviationWindow),
         )
+    };
+    let now = Clock::get()?.unix_timestamp;
+    let store = ctx.accounts.store.key();
+    let (price_band_factors, last_prices) = split_price_feed_extra_accounts(
+        &store,
+        ctx.remaining_accounts
+            .get(tokens.len()..)
+            .unwrap_or_default(),
+        now,
+    )?;
+    let token_map = ctx.accounts.token_map.load_token_map()?;
+    let mut oracle = ctx.accounts.oracle.load_mut()?;
+    oracle.set_prices_from_remaining_accounts(
+        validator,
+        &token_map,
+        &tokens,
+        ctx.remaining_accounts,
+        ctx.accounts.chainlink_program.as_ref(),
+        &price_band_factors,
+    )?;
+
+    // Reject prices deviating too much from the last recorded ones.
+    for token in tokens.iter() {
+        let max_deviation_bps = token_map
+            .get(token)
+            .ok_or_else(|| error!(CoreError::NotFound))?
+            .max_price_deviation_bps();
+        let Some((_, last_price)) = last_prices.iter().find(|(t, _)| t == token) else {
+            require_eq!(max_deviation_bps, 0, CoreError::LastOraclePriceNotProvided);
+            continue;
+        };
+        let price = oracle.get_primary_decimal_price(token)?;
+        last_price.load_mut()?.validate_deviation_and_record(
+            &price,
+            max_deviation_bps,
+            now,
+            window,
+        )?;
+    }
+    Ok(())
+}
+
+/// Split the extra accounts of [`SetPricesFromPriceFeed`] into the price band factors
+/// of the current hour from the [`PriceAdjustmentSchedule`] accounts and the
+/// [`LastOraclePrice`] accounts.
+#[allow(clippy::type_complexity)]
+fn split_price_feed_extra_accounts<'info>(
+    store: &Pubkey,
+    accounts: &'info [AccountInfo<'info>],
+    now: i64,
+) -> Result<(
+    Vec<(Pubkey, Factor)>,
+    Vec<(Pubkey, AccountLoader<'info, LastOraclePrice>)>,
+)> {
+    let mut factors = Vec::with_capacity(accounts.len());
+    let mut last_prices = Vec::with_capacity(accounts.len());
+    for info in accounts {
+        if let Ok(schedule) = AccountLoader::<PriceAdjustmentSchedule>::try_from(info) {
+            let schedule = schedule.load()?;
+            require_keys_eq!(schedule.store, *store, CoreError::StoreMismatched);
+            factors.push((schedule.token, schedule.factor_at(now)));
+        } else {
+            let last_price = AccountLoader::<LastOraclePrice>::try_from(info)?;
+            let token = {
+                let last_price = last_price.load()?;
+                require_keys_eq!(last_price.store, *store, CoreError::StoreMismatched);
+                last_price.token
+            };
+            last_prices.push((token, last_price));
+        }
+    }
+    Ok((factors, last_prices))
 }
 
 impl<'info> internal::Authentication<'info> for SetPricesFromPriceFeed<'info> {
@@ -127,3 +205,215 @@ impl<'info> internal::Authentication<'info> for SetPricesFromPriceFeed<'info> {
         &self.store
     }
 }
+
+/// The accounts definition for [`rotate_oracle`](crate::gmsol_store::rotate_oracle).
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RotateOracle<'info> {
+    /// The caller.
+    pub authority: Signer<'info>,
+    /// Store.
+    pub store: AccountLoader<'info, Store>,
+    /// The oracle to decommission.
+    #[account(mut, has_one = store)]
+    pub old_oracle: AccountLoader<'info, Oracle>,
+    /// The oracle to replace the old one.
+    #[account(
+        mut,
+        has_one = store,
+        constraint = new_oracle.key() != old_oracle.key() @ CoreError::InvalidArgument,
+    )]
+    pub new_oracle: AccountLoader<'info, Oracle>,
+}
+
+/// Rotate the oracle.
+/// CHECK: only ADMIN is allowed to invoke.
+pub(crate) fn unchecked_rotate_oracle(ctx: Context<RotateOracle>) -> Result<()> {
+    let accounts = &ctx.accounts;
+    accounts
+        .old_oracle
+        .load_mut()?
+        .rotate(&mut *accounts.new_oracle.load_mut()?)?;
+
+    let event_emitter = EventEmitter::new(&accounts.event_authority, ctx.bumps.event_authority);
+    event_emitter.emit_cpi(&OracleRotated {
+        old_oracle: accounts.old_oracle.key(),
+        new_oracle: accounts.new_oracle.key(),
+    })?;
+    Ok(())
+}
+
+impl<'info> internal::Authentication<'info> for RotateOracle<'info> {
+    fn authority(&self) -> &Signer<'info> {
+        &self.authority
+    }
+
+    fn store(&self) -> &AccountLoader<'info, Store> {
+        &self.store
+    }
+}
+
+/// The accounts definition for [`close_decommissioned_oracle`](crate::gmsol_store::close_decommissioned_oracle).
+#[derive(Accounts)]
+pub struct CloseDecommissionedOracle<'info> {
+    /// The caller.
+    pub authority: Signer<'info>,
+    /// Store.
+    pub store: AccountLoader<'info, Store>,
+    /// The decommissioned oracle to close.
+    #[account(
+        mut,
+        close = receiver,
+        has_one = store,
+    )]
+    pub oracle: AccountLoader<'info, Oracle>,
+    /// Receiver.
+    /// CHECK: Only used to receive funds after closing the oracle.
+    #[account(mut)]
+    pub receiver: UncheckedAccount<'info>,
+}
+
+/// Close a decommissioned oracle.
+/// CHECK: only ADMIN is allowed to invoke.
+pub(crate) fn unchecked_close_decommissioned_oracle(
+    ctx: Context<CloseDecommissionedOracle>,
+) -> Result<()> {
+    require!(
+        ctx.accounts.oracle.load()?.is_decommissioned(),
+        CoreError::PreconditionsAreNotMet
+    );
+    Ok(())
+}
+
+impl<'info> internal::Authentication<'info> for CloseDecommissionedOracle<'info> {
+    fn authority(&self) -> &Signer<'info> {
+        &self.authority
+    }
+
+    fn store(&self) -> &AccountLoader<'info, Store> {
+        &self.store
+    }
+}
+
+/// The accounts definition for [`initialize_price_adjustment_schedule`](crate::gmsol_store::initialize_price_adjustment_schedule).
+///
+/// *[See also the documentation for the instruction.](crate::gmsol_store::initialize_price_adjustment_schedule)*
+#[derive(Accounts)]
+#[instruction(token: Pubkey)]
+pub struct InitializePriceAdjustmentSchedule<'info> {
+    /// The caller.
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    /// Store.
+    #[account(has_one = token_map)]
+    pub store: AccountLoader<'info, Store>,
+    /// Token map.
+    #[account(has_one = store)]
+    pub token_map: AccountLoader<'info, TokenMapHeader>,
+    /// The price adjustment schedule account to initialize.
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + PriceAdjustmentSchedule::INIT_SPACE,
+        seeds = [
+            PriceAdjustmentSchedule::SEED,
+            store.key().as_ref(),
+            token.as_ref(),
+        ],
+        bump,
+    )]
+    pub schedule: AccountLoader<'info, PriceAdjustmentSchedule>,
+    /// The system program.
+    pub system_program: Program<'info, System>,
+}
+
+/// Initialize the price adjustment schedule of the token.
+///
+/// ## CHECK
+/// - Only MARKET_KEEPER can use this instruction.
+pub(crate) fn unchecked_initialize_price_adjustment_schedule(
+    ctx: Context<InitializePriceAdjustmentSchedule>,
+    token: &Pubkey,
+    factors: &[Factor; HOURS_PER_DAY],
+) -> Result<()> {
+    require!(
+        ctx.accounts
+            .token_map
+            .load_token_map()?
+            .get(token)
+            .is_some(),
+        CoreError::NotFound
+    );
+    ctx.accounts.schedule.load_init()?.init(
+        ctx.bumps.schedule,
+        &ctx.accounts.store.key(),
+        token,
+        factors,
+    )?;
+    msg!("[Price Adjustment] {}: initialized", token);
+    Ok(())
+}
+
+impl<'info> internal::Authentication<'info> for InitializePriceAdjustmentSchedule<'info> {
+    fn authority(&self) -> &Signer<'info> {
+        &self.authority
+    }
+
+    fn store(&self) -> &AccountLoader<'info, Store> {
+        &self.store
+    }
+}
+
+/// The accounts definition for [`update_price_adjustment_schedule`](crate::gmsol_store::update_price_adjustment_schedule).
+///
+/// *[See also the documentation for the instruction.](crate::gmsol_store::update_price_adjustment_schedule)*
+#[derive(Accounts)]
+#[instruction(token: Pubkey)]
+pub struct UpdatePriceAdjustmentSchedule<'info> {
+    /// The caller.
+    pub authority: Signer<'info>,
+    /// Store.
+    pub store: AccountLoader<'info, Store>,
+    /// The price adjustment schedule account of the token.
+    #[account(
+        mut,
+        has_one = store,
+        seeds = [
+            PriceAdjustmentSchedule::SEED,
+            store.key().as_ref(),
+            token.as_ref(),
+        ],
+        bump = schedule.load()?.bump,
+    )]
+    pub schedule: AccountLoader<'info, PriceAdjustmentSchedule>,
+}
+
+/// Update the factor of the given hour in the price adjustment schedule.
+///
+/// ## CHECK
+/// - Only MARKET_KEEPER can use this instruction.
+pub(crate) fn unchecked_update_price_adjustment_schedule(
+    ctx: Context<UpdatePriceAdjustmentSchedule>,
+    token: &Pubkey,
+    hour: u8,
+    factor: Factor,
+) -> Result<()> {
+    ctx.accounts.schedule.load_mut()?.update(hour, factor)?;
+    msg!(
+        "[Price Adjustment] {}: updated factor of hour {} to {}",
+        token,
+        hour,
+        factor
+    );
+    Ok(())
+}
+
+impl<'info> internal::Authentication<'info> for UpdatePriceAdjustmentSchedule<'info> {
+    fn authority(&self) -> &Signer<'info> {
+        &self.authority
+    }
+
+    fn store(&self) -> &AccountLoader<'info, Store> {
+        &self.store
+    }
+}
@@ -258,8 +258,72 @@ impl<'info> internal::Authentication<'info> for SetTokenMap<'info> {
     }
 }
 
+/// The accounts definition for [`set_store_paused`](crate::gmsol_store::set_store_paused).
+#[derive(Accounts)]
+pub struct SetStorePaused<'info> {
+    /// The caller of this instruction.
+    pub authority: Signer<'info>,
+    /// Store.
+    #[account(mut)]
+    pub store: AccountLoader<'info, Store>,
+}
+
+/// Pause or unpause the store.
+///
+/// ## CHECK
+/// - Only ADMIN can execute this instruction.
+pub(crate) fn unchecked_set_store_paused(ctx: Context<SetStorePaused>, paused: bool) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    ctx.accounts.store.load_mut()?.set_paused(paused, now)?;
+    msg!("[Store] paused = {}", paused);
+    Ok(())
+}
+
+impl<'info> internal::Authentication<'info> for SetStorePaused<'info> {
+    fn authority(&self) -> &Signer<'info> {
+        &self.authority
+    }
+
+    fn store(&self) -> &AccountLoader<'info, Store> {
+        &self.store
+    }
+}
+
+/// The accounts definition for [`enable_emergency_mode`](crate::gmsol_store::enable_emergency_mode).
+#[derive(Accounts)]
+pub struct EnableEmergencyMode<'info> {
+    /// The caller of this instruction.
+    pub authority: Signer<'info>,
+    /// Store.
+    #[account(mut)]
+    pub store: AccountLoader<'info, Store>,
+}
+
+/// Enable the emergency mode.
+///
+/// ## CHECK
+/// - Only ADMIN can execute this instruction.
+pub(crate) fn unchecked_enable_emergency_mode(ctx: Context<EnableEmergencyMode>) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    ctx.accounts.store.load_mut()?.enable_emergency_mode(now)?;
+    msg!("[Store] emergency mode enabled");
+    Ok(())
+}
+
+impl<'info> internal::Authentication<'info> for EnableEmergencyMode<'info> {
+    fn authority(&self) -> &Signer<'info> {
+        &self.authority
+    }
+
+    fn store(&self) -> &AccountLoader<'info, Store> {
+        &self.store
+    }
+}
+
+/// The accounts definition for read-only instructions for store.
 #[derive(Accounts)]
 pub struct ReadStore<'info> {
+    /// Store.
     pub store: AccountLoader<'info, Store>,
 }
 
@@ -7,8 +7,13 @@ use anchor_spl::{
 
 use crate::{
     constants,
-    states::Store,
+    events::{EventEmitter, StuckClaimableAccountRecovered},
+    states::{
+        market::revertible::{Revertible, RevertibleMarket},
+        AmountKey, Market, Store,
+    },
     utils::{internal, token::must_be_uninitialized},
+    CoreError, ModelError,
 };
 
 /// The accounts definition for [`initialize_market_vault`](crate::gmsol_store::initialize_market_vault).
@@ -206,6 +211,245 @@ impl<'info> internal::Authentication<'info> for CloseEmptyClaimableAccount<'info
     }
 }
 
+/// The accounts definition for [`close_empty_claimable_accounts`](crate::gmsol_store::close_empty_claimable_accounts).
+///
+/// Remaining accounts expected by this instruction:
+///
+///   - 0..3N. `[]` N tuples of `[mint, owner, account]`, where `account` is the claimable
+///     account (writable) of the `owner` for the `mint`.
+///
+/// *[See also the documentation for the instruction.](crate::gmsol_store::close_empty_claimable_accounts)*
+#[derive(Accounts)]
+pub struct CloseEmptyClaimableAccounts<'info> {
+    /// The caller.
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    /// Store.
+    pub store: AccountLoader<'info, Store>,
+    /// Token Program.
+    pub token_program: Program<'info, Token>,
+}
+
+/// Close the given claimable accounts if they are empty.
+///
+/// ## CHECK
+/// - Only ORDER_KEEPER can close claimable accounts.
+pub(crate) fn unchecked_close_empty_claimable_accounts<'info>(
+    ctx: Context<'_, '_, 'info, 'info, CloseEmptyClaimableAccounts<'info>>,
+    timestamps: &[i64],
+) -> Result<()> {
+    let remaining_accounts = ctx.remaining_accounts;
+    require_eq!(
+        remaining_accounts.len(),
+        timestamps.len() * 3,
+        ErrorCode::AccountNotEnoughKeys
+    );
+
+    let store = ctx.accounts.store.key();
+    let mut closed = 0;
+    for (timestamp, accounts) in timestamps.iter().zip(remaining_accounts.chunks_exact(3)) {
+        let [mint, owner, account] = accounts else {
+            unreachable!();
+        };
+        let time_key = ctx.accounts.store.load()?.claimable_time_key(*timestamp)?;
+        let (expected, _) = Pubkey::find_program_address(
+            &[
+                constants::CLAIMABLE_ACCOUNT_SEED,
+                store.as_ref(),
+                mint.key.as_ref(),
+                owner.key.as_ref(),
+                &time_key,
+            ],
+            &crate::ID,
+        );
+        require_keys_eq!(expected, account.key(), ErrorCode::ConstraintSeeds);
+        require!(account.is_writable, ErrorCode::ConstraintMut);
+
+        if *account.owner == anchor_lang::system_program::ID {
+            continue;
+        }
+        let amount = anchor_spl::token::accessor::amount(account)?;
+        if amount != 0 {
+            msg!(
+                "[Claimable] skipped non-empty claimable account {}",
+                account.key
+            );
+            continue;
+        }
+        anchor_spl::token::close_account(CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            anchor_spl::token::CloseAccount {
+                account: account.clone(),
+                destination: ctx.accounts.authority.to_account_info(),
+                authority: ctx.accounts.store.to_account_info(),
+            },
+            &[&ctx.accounts.store.load()?.signer_seeds()],
+        ))?;
+        closed += 1;
+    }
+
+    msg!(
+        "[Claimable] closed {} of {} claimable accounts",
+        closed,
+        timestamps.len()
+    );
+    Ok(())
+}
+
+impl<'info> internal::Authentication<'info> for CloseEmptyClaimableAccounts<'info> {
+    fn authority(&self) -> &Signer<'info> {
+        &self.authority
+    }
+
+    fn store(&self) -> &AccountLoader<'info, Store> {
+        &self.store
+    }
+}
+
+/// The accounts definition for [`recover_stuck_claimable_account`](crate::gmsol_store::recover_stuck_claimable_account).
+///
+/// *[See also the documentation for the instruction.](crate::gmsol_store::recover_stuck_claimable_account)*
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(timestamp: i64)]
+pub struct RecoverStuckClaimableAccount<'info> {
+    /// The caller.
+    pub authority: Signer<'info>,
+    /// Store.
+    pub store: AccountLoader<'info, Store>,
+    /// The market to which the remaining balance is returned.
+    #[account(mut, has_one = store)]
+    pub market: AccountLoader<'info, Market>,
+    /// Mint.
+    pub mint: Account<'info, Mint>,
+    /// CHECK: only use to reference the owner.
+    pub owner: UncheckedAccount<'info>,
+    /// The claimable account to recover.
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = store,
+        seeds = [
+            constants::CLAIMABLE_ACCOUNT_SEED,
+            store.key().as_ref(),
+            mint.key().as_ref(),
+            owner.key().as_ref(),
+            &store.load()?.claimable_time_key(timestamp)?,
+        ],
+        bump,
+    )]
+    pub account: Account<'info, TokenAccount>,
+    /// The market vault.
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = store,
+        seeds = [
+            constants::MARKET_VAULT_SEED,
+            store.key().as_ref(),
+            mint.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub vault: Account<'info, TokenAccount>,
+    /// CHECK: only use to receive the rent.
+    #[account(mut)]
+    pub receiver: UncheckedAccount<'info>,
+    /// Token Program.
+    pub token_program: Program<'info, Token>,
+}
+
+/// Recover a claimable account that has been dormant for too long.
+///
+/// ## CHECK
+/// - Only ADMIN can recover claimable accounts.
+pub(crate) fn unchecked_recover_stuck_claimable_account(
+    ctx: Context<RecoverStuckClaimableAccount>,
+    timestamp: i64,
+) -> Result<()> {
+    let dormant_secs = {
+        let store = ctx.accounts.store.load()?;
+        let max_lifetime = *store.get_amount_by_key(AmountKey::MaxClaimableAccountLifetime);
+        require_neq!(max_lifetime, 0, CoreError::PreconditionsAreNotMet);
+        let max_lifetime =
+            i64::try_from(max_lifetime).map_err(|_| error!(CoreError::InvalidArgument))?;
+        let dormant_secs = store.claimable_dormant_secs(timestamp, Clock::get()?.unix_timestamp)?;
+        require_gt!(
+            dormant_secs,
+            max_lifetime,
+            CoreError::PreconditionsAreNotMet
+        );
+        dormant_secs
+    };
+
+    let event_emitter = EventEmitter::new(&ctx.accounts.event_authority, ctx.bumps.event_authority);
+    let mint = ctx.accounts.mint.key();
+    let balance = ctx.accounts.account.amount;
+
+    // Return the remaining balance to the market.
+    if balance != 0 {
+        let is_collateral_token = ctx
+            .accounts
+            .market
+            .load()?
+            .validated_meta(&ctx.accounts.store.key())?
+            .is_collateral_token(&mint);
+        require!(is_collateral_token, CoreError::InvalidArgument);
+
+        anchor_spl::token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                anchor_spl::token::Transfer {
+                    from: ctx.accounts.account.to_account_info(),
+                    to: ctx.accounts.vault.to_account_info(),
+                    authority: ctx.accounts.store.to_account_info(),
+                },
+                &[&ctx.accounts.store.load()?.signer_seeds()],
+            ),
+            balance,
+        )?;
+        let mut market = RevertibleMarket::new(&ctx.accounts.market, event_emitter)?;
+        market
+            .record_transferred_in_by_token(&mint, &balance)
+            .map_err(ModelError::from)?;
+        market.commit();
+    }
+
+    anchor_spl::token::close_account(CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        anchor_spl::token::CloseAccount {
+            account: ctx.accounts.account.to_account_info(),
+            destination: ctx.accounts.receiver.to_account_info(),
+            authority: ctx.accounts.store.to_account_info(),
+        },
+        &[&ctx.accounts.store.load()?.signer_seeds()],
+    ))?;
+
+    let account = ctx.accounts.account.key();
+    event_emitter.emit_cpi(&StuckClaimableAccountRecovered {
+        account,
+        balance_returned: balance,
+        dormant_secs,
+    })?;
+    msg!(
+        "[Claimable] recovered claimable account {}, returned `{}` {} to the market",
+        account,
+        balance,
+        mint,
+    );
+    Ok(())
+}
+
+impl<'info> internal::Authentication<'info> for RecoverStuckClaimableAccount<'info> {
+    fn authority(&self) -> &Signer<'info> {
+        &self.authority
+    }
+
+    fn store(&self) -> &AccountLoader<'info, Store> {
+        &self.store
+    }
+}
+
 /// The accounts definition for [`prepare_associated_token_account`](crate::gmsol_store::prepare_associated_token_account).
 ///
 /// *[See also the documentation for the instruction.](crate::gmsol_store::prepare_associated_token_account)*
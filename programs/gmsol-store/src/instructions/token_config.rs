@@ -1,10 +1,12 @@
-use anchor_lang::prelude::*;
+use anchor_lang::{prelude::*, solana_program::program::MAX_RETURN_DATA};
 use anchor_spl::token::Mint;
+use gmsol_utils::InitSpace;
 
 use crate::{
     states::{
-        FeedConfig, PriceProviderKind, Store, TokenMapAccess, TokenMapAccessMut, TokenMapHeader,
-        TokenMapLoader, UpdateTokenConfigParams,
+        oracle::adjusted_timestamp_from_feed_account, BridgePremiumConfig, Chainlink, FeedConfig,
+        LastOraclePrice, PriceProviderKind, Seed, Store, TokenConfigInfo, TokenMapAccess,
+        TokenMapAccessMut, TokenMapHeader, TokenMapLoader, UpdateTokenConfigParams,
     },
     utils::internal,
     CoreError,
@@ -261,22 +263,38 @@ pub struct SetFeedConfig<'info> {
 ///
 /// ## CHECK
 /// - Only [`MARKET_KEEPER`](crate::states::RoleKey::MARKET_KEEPER) can perform this action.
+#[allow(clippy::too_many_arguments)]
 pub(crate) fn unchecked_set_feed_config(
     ctx: Context<SetFeedConfig>,
     token: Pubkey,
     provider: &PriceProviderKind,
     feed: Pubkey,
     timestamp_adjustment: u32,
+    min_price: u32,
+    max_price: u32,
+    price_multiplier: u32,
+    price_multiplier_denominator: u32,
+    bridge_premium: Option<BridgePremiumConfig>,
 ) -> Result<()> {
-    ctx.accounts
-        .token_map
-        .load_token_map_mut()?
+    let mut token_map = ctx.accounts.token_map.load_token_map_mut()?;
+
+    let config = token_map
         .get_mut(&token)
-        .ok_or_else(|| error!(CoreError::NotFound))?
-        .set_feed_config(
-            provider,
-            FeedConfig::new(feed).with_timestamp_adjustment(timestamp_adjustment),
-        )
+        .ok_or_else(|| error!(CoreError::NotFound))?;
+
+    config.set_feed_config(
+        provider,
+        FeedConfig::new(feed)
+            .with_timestamp_adjustment(timestamp_adjustment)
+            .with_price_band(min_price, max_price)?
+            .with_price_multiplier(price_multiplier, price_multiplier_denominator)?,
+    )?;
+
+    if let Some(bridge_premium) = bridge_premium {
+        config.set_bridge_premium(&bridge_premium)?;
+    }
+
+    Ok(())
 }
 
 impl<'info> internal::Authentication<'info> for SetFeedConfig<'info> {
@@ -289,6 +307,198 @@ impl<'info> internal::Authentication<'info> for SetFeedConfig<'info> {
     }
 }
 
+/// The accounts definition for [`update_bridge_premium`](crate::gmsol_store::update_bridge_premium).
+///
+/// [*See also the documentation for the instruction.*](crate::gmsol_store::update_bridge_premium)
+#[derive(Accounts)]
+pub struct UpdateBridgePremium<'info> {
+    /// The authority of the instruction.
+    pub authority: Signer<'info>,
+    /// The store that owns the token map.
+    pub store: AccountLoader<'info, Store>,
+    /// The token map to update.
+    #[account(mut, has_one = store)]
+    pub token_map: AccountLoader<'info, TokenMapHeader>,
+}
+
+/// Update the bridge premium factor of the given bridged token.
+///
+/// ## CHECK
+/// - Only [`ORACLE_CONTROLLER`](crate::states::RoleKey::ORACLE_CONTROLLER) can perform this action.
+pub(crate) fn unchecked_update_bridge_premium(
+    ctx: Context<UpdateBridgePremium>,
+    token: Pubkey,
+    factor: i128,
+) -> Result<()> {
+    let mut token_map = ctx.accounts.token_map.load_token_map_mut()?;
+
+    let config = token_map
+        .get_mut(&token)
+        .ok_or_else(|| error!(CoreError::NotFound))?;
+
+    require!(config.is_bridged(), CoreError::PreconditionsAreNotMet);
+
+    config.set_bridge_premium_factor(factor)
+}
+
+impl<'info> internal::Authentication<'info> for UpdateBridgePremium<'info> {
+    fn authority(&self) -> &Signer<'info> {
+        &self.authority
+    }
+
+    fn store(&self) -> &AccountLoader<'info, Store> {
+        &self.store
+    }
+}
+
+/// The accounts definition for [`set_max_price_deviation`](crate::gmsol_store::set_max_price_deviation).
+#[derive(Accounts)]
+pub struct SetMaxPriceDeviation<'info> {
+    /// The authority of the instruction.
+    pub authority: Signer<'info>,
+    /// The store that owns the token map.
+    pub store: AccountLoader<'info, Store>,
+    /// The token map to update.
+    #[account(mut, has_one = store)]
+    pub token_map: AccountLoader<'info, TokenMapHeader>,
+}
+
+/// Set the max deviation from the last recorded price of the given token.
+///
+/// ## CHECK
+/// - Only [`MARKET_KEEPER`](crate::states::RoleKey::MARKET_KEEPER) can perform this action.
+pub(crate) fn unchecked_set_max_price_deviation(
+    ctx: Context<SetMaxPriceDeviation>,
+    token: Pubkey,
+    max_price_deviation_bps: u16,
+) -> Result<()> {
+    let mut token_map = ctx.accounts.token_map.load_token_map_mut()?;
+
+    token_map
+        .get_mut(&token)
+        .ok_or_else(|| error!(CoreError::NotFound))?
+        .set_max_price_deviation_bps(max_price_deviation_bps)
+}
+
+impl<'info> internal::Authentication<'info> for SetMaxPriceDeviation<'info> {
+    fn authority(&self) -> &Signer<'info> {
+        &self.authority
+    }
+
+    fn store(&self) -> &AccountLoader<'info, Store> {
+        &self.store
+    }
+}
+
+/// The accounts definition for [`initialize_last_oracle_price`](crate::gmsol_store::initialize_last_oracle_price).
+#[derive(Accounts)]
+#[instruction(token: Pubkey)]
+pub struct InitializeLastOraclePrice<'info> {
+    /// The authority of the instruction.
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    /// The store that owns the token map.
+    #[account(has_one = token_map)]
+    pub store: AccountLoader<'info, Store>,
+    /// The token map.
+    #[account(has_one = store)]
+    pub token_map: AccountLoader<'info, TokenMapHeader>,
+    /// The last oracle price account to initialize.
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + LastOraclePrice::INIT_SPACE,
+        seeds = [
+            LastOraclePrice::SEED,
+            store.key().as_ref(),
+            token.as_ref(),
+        ],
+        bump,
+    )]
+    pub last_price: AccountLoader<'info, LastOraclePrice>,
+    /// The system program.
+    pub system_program: Program<'info, System>,
+}
+
+/// Initialize the last oracle price account of the given token.
+///
+/// ## CHECK
+/// - Only [`MARKET_KEEPER`](crate::states::RoleKey::MARKET_KEEPER) can perform this action.
+pub(crate) fn unchecked_initialize_last_oracle_price(
+    ctx: Context<InitializeLastOraclePrice>,
+    token: &Pubkey,
+) -> Result<()> {
+    require!(
+        ctx.accounts
+            .token_map
+            .load_token_map()?
+            .get(token)
+            .is_some(),
+        CoreError::NotFound
+    );
+    ctx.accounts.last_price.load_init()?.init(
+        ctx.bumps.last_price,
+        &ctx.accounts.store.key(),
+        token,
+    );
+    Ok(())
+}
+
+impl<'info> internal::Authentication<'info> for InitializeLastOraclePrice<'info> {
+    fn authority(&self) -> &Signer<'info> {
+        &self.authority
+    }
+
+    fn store(&self) -> &AccountLoader<'info, Store> {
+        &self.store
+    }
+}
+
+/// The accounts definition for [`clear_last_oracle_price`](crate::gmsol_store::clear_last_oracle_price).
+#[derive(Accounts)]
+#[instruction(token: Pubkey)]
+pub struct ClearLastOraclePrice<'info> {
+    /// The authority of the instruction.
+    pub authority: Signer<'info>,
+    /// The store.
+    pub store: AccountLoader<'info, Store>,
+    /// The last oracle price account of the token.
+    #[account(
+        mut,
+        has_one = store,
+        seeds = [
+            LastOraclePrice::SEED,
+            store.key().as_ref(),
+            token.as_ref(),
+        ],
+        bump = last_price.load()?.bump,
+    )]
+    pub last_price: AccountLoader<'info, LastOraclePrice>,
+}
+
+/// Clear the last recorded price of the given token.
+///
+/// ## CHECK
+/// - Only [`ADMIN`](crate::states::RoleKey::ADMIN) can perform this action.
+pub(crate) fn unchecked_clear_last_oracle_price(
+    ctx: Context<ClearLastOraclePrice>,
+    token: Pubkey,
+) -> Result<()> {
+    ctx.accounts.last_price.load_mut()?.clear();
+    msg!("[Oracle] cleared the last recorded price of {}", token);
+    Ok(())
+}
+
+impl<'info> internal::Authentication<'info> for ClearLastOraclePrice<'info> {
+    fn authority(&self) -> &Signer<'info> {
+        &self.authority
+    }
+
+    fn store(&self) -> &AccountLoader<'info, Store> {
+        &self.store
+    }
+}
+
 /// The accounts definition of the instructions to read token map.
 #[derive(Accounts)]
 pub struct ReadTokenMap<'info> {
@@ -306,6 +516,28 @@ pub(crate) fn is_token_config_enabled(ctx: Context<ReadTokenMap>, token: &Pubkey
         .ok_or_else(|| error!(CoreError::NotFound))
 }
 
+/// The max number of disabled tokens that fits in the return data.
+const MAX_DISABLED_TOKENS: usize = (MAX_RETURN_DATA - 4) / 32;
+
+/// Get the disabled tokens of the token map.
+pub(crate) fn get_disabled_tokens(ctx: Context<ReadTokenMap>) -> Result<Vec<Pubkey>> {
+    let token_map = ctx.accounts.token_map.load_token_map()?;
+    let disabled = token_map
+        .tokens()
+        .filter(|token| {
+            token_map
+                .get(token)
+                .is_some_and(|config| !config.is_enabled())
+        })
+        .collect::<Vec<_>>();
+    require_gte!(
+        MAX_DISABLED_TOKENS,
+        disabled.len(),
+        CoreError::ExceedMaxLengthLimit
+    );
+    Ok(disabled)
+}
+
 /// Get expected provider for the given token.
 pub(crate) fn token_expected_provider(
     ctx: Context<ReadTokenMap>,
@@ -347,6 +579,41 @@ pub(crate) fn token_timestamp_adjustment(
         .timestamp_adjustment(provider)
 }
 
+/// The accounts definition for [`get_adjusted_timestamp`](crate::gmsol_store::get_adjusted_timestamp).
+///
+/// Remaining accounts expected by this instruction:
+///
+///   - 0. `[]` The price feed account of the token.
+#[derive(Accounts)]
+pub struct ReadTokenFeed<'info> {
+    /// Token map.
+    pub token_map: AccountLoader<'info, TokenMapHeader>,
+    /// Chainlink Program.
+    pub chainlink_program: Option<Program<'info, Chainlink>>,
+}
+
+/// Get the timestamp of the price reported by the feed, with the timestamp adjustment applied.
+pub(crate) fn get_adjusted_timestamp<'info>(
+    ctx: Context<'_, '_, 'info, 'info, ReadTokenFeed<'info>>,
+    token: &Pubkey,
+    provider: &PriceProviderKind,
+) -> Result<i64> {
+    let feed = ctx
+        .remaining_accounts
+        .first()
+        .ok_or_else(|| error!(ErrorCode::AccountNotEnoughKeys))?;
+    let token_map = ctx.accounts.token_map.load_token_map()?;
+    let token_config = token_map
+        .get(token)
+        .ok_or_else(|| error!(CoreError::NotFound))?;
+    adjusted_timestamp_from_feed_account(
+        token_config,
+        provider,
+        ctx.accounts.chainlink_program.as_ref(),
+        feed,
+    )
+}
+
 /// Get the name of the given token.
 pub(crate) fn token_name(ctx: Context<ReadTokenMap>, token: &Pubkey) -> Result<String> {
     ctx.accounts
@@ -380,6 +647,18 @@ pub(crate) fn token_precision(ctx: Context<ReadTokenMap>, token: &Pubkey) -> Res
         .precision())
 }
 
+/// Get the full config of the given token.
+pub(crate) fn get_token_config(
+    ctx: Context<ReadTokenMap>,
+    token: &Pubkey,
+) -> Result<TokenConfigInfo> {
+    let token_map = ctx.accounts.token_map.load_token_map()?;
+    let config = token_map
+        .get(token)
+        .ok_or_else(|| error!(CoreError::NotFound))?;
+    TokenConfigInfo::try_from(config)
+}
+
 #[allow(clippy::too_many_arguments)]
 fn do_push_token_map<'info>(
     authority: AccountInfo<'info>,
@@ -1,14 +1,28 @@
-use anchor_lang::prelude::*;
+use anchor_lang::{prelude::*, system_program};
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::spl_token::native_mint,
+    token_interface::{self, Mint, TokenAccount, TokenInterface},
+};
 use gmsol_utils::InitSpace;
 
 use crate::{
+    constants,
+    events::{EventEmitter, UserAccountFrozen, UserAccountUnfrozen},
+    ops::market::MarketTransferOutOperation,
     states::{
-        user::{ReferralCodeBytes, ReferralCodeV2, UserHeader},
-        Seed, Store,
+        user::{
+            ReferralCodeBytes, ReferralCodeV2, ReferralRewardVault, ReferralStats, UserActivity,
+            UserHeader,
+        },
+        Market, Seed, Store,
     },
+    utils::internal,
     CoreError,
 };
 
+use super::market::claim_all_fees_from_pool;
+
 /// The accounts definitions for [`prepare_user`](crate::gmsol_store::prepare_user) instruction.
 #[derive(Accounts)]
 pub struct PrepareUser<'info> {
@@ -30,30 +44,43 @@ pub struct PrepareUser<'info> {
 }
 
 pub(crate) fn prepare_user(ctx: Context<PrepareUser>) -> Result<()> {
-    let store = ctx.accounts.store.key();
-    let owner = ctx.accounts.owner.key;
-    {
-        match ctx.accounts.user.load_init() {
-            Ok(mut user) => {
-                user.init(&store, owner, ctx.bumps.user)?;
-            }
-            Err(Error::AnchorError(err)) => {
-                if err.error_code_number != ErrorCode::AccountDiscriminatorAlreadySet as u32 {
-                    return Err(Error::AnchorError(err));
-                }
-            }
-            Err(err) => {
-                return Err(err);
+    initialize_user_if_needed(
+        &ctx.accounts.user,
+        &ctx.accounts.store.key(),
+        ctx.accounts.owner.key,
+        ctx.bumps.user,
+    )
+}
+
+/// Initialize the user account if needed and validate it.
+///
+/// The user account must have been created with the `init_if_needed` constraint.
+pub(crate) fn initialize_user_if_needed(
+    user: &AccountLoader<UserHeader>,
+    store: &Pubkey,
+    owner: &Pubkey,
+    bump: u8,
+) -> Result<()> {
+    match user.load_init() {
+        Ok(mut user) => {
+            user.init(store, owner, bump)?;
+        }
+        Err(Error::AnchorError(err)) => {
+            if err.error_code_number != ErrorCode::AccountDiscriminatorAlreadySet as u32 {
+                return Err(Error::AnchorError(err));
             }
         }
+        Err(err) => {
+            return Err(err);
+        }
     }
-    ctx.accounts.user.exit(&crate::ID)?;
+    user.exit(&crate::ID)?;
     {
-        let user = ctx.accounts.user.load()?;
+        let user = user.load()?;
         require!(user.is_initialized(), CoreError::InvalidUserAccount);
-        require_keys_eq!(user.store, store, CoreError::InvalidUserAccount);
+        require_keys_eq!(user.store, *store, CoreError::InvalidUserAccount);
         require_keys_eq!(user.owner, *owner, CoreError::InvalidUserAccount);
-        require_eq!(user.bump, ctx.bumps.user, CoreError::InvalidUserAccount);
+        require_eq!(user.bump, bump, CoreError::InvalidUserAccount);
     }
     Ok(())
 }
@@ -69,8 +96,11 @@ pub struct InitializeReferralCode<'info> {
     /// Store.
     pub store: AccountLoader<'info, Store>,
     /// Referral Code Account.
+    ///
+    /// It can be already initialized only if it is the referral code of the user,
+    /// in which case the instruction is a no-op.
     #[account(
-        init,
+        init_if_needed,
         payer = owner,
         space = 8 + ReferralCodeV2::INIT_SPACE,
         seeds = [ReferralCodeV2::SEED, store.key().as_ref(), &code],
@@ -100,12 +130,38 @@ pub(crate) fn initialize_referral_code(
     );
 
     // Initialize Referral Code Account.
-    ctx.accounts.referral_code.load_init()?.init(
-        ctx.bumps.referral_code,
-        code,
-        &ctx.accounts.store.key(),
-        ctx.accounts.owner.key,
-    );
+    match ctx.accounts.referral_code.load_init() {
+        Ok(mut referral_code) => {
+            referral_code.init(
+                ctx.bumps.referral_code,
+                code,
+                &ctx.accounts.store.key(),
+                ctx.accounts.owner.key,
+            );
+        }
+        Err(Error::AnchorError(err)) => {
+            if err.error_code_number != ErrorCode::AccountDiscriminatorAlreadySet as u32 {
+                return Err(Error::AnchorError(err));
+            }
+            // The referral code has been initialized. Succeed as a no-op only if it is
+            // already the referral code of the user, so that retries are safe.
+            let referral_code = ctx.accounts.referral_code.load()?;
+            require_keys_eq!(
+                referral_code.owner,
+                ctx.accounts.owner.key(),
+                CoreError::ReferralCodeMismatched
+            );
+            require_keys_eq!(
+                ctx.accounts.user.load()?.referral.code,
+                ctx.accounts.referral_code.key(),
+                CoreError::ReferralCodeMismatched
+            );
+            return Ok(());
+        }
+        Err(err) => {
+            return Err(err);
+        }
+    }
 
     // Set referral code address.
     ctx.accounts
@@ -171,6 +227,7 @@ pub(crate) fn set_referrer(ctx: Context<SetReferrer>, _code: ReferralCodeBytes)
 /// The accounts definitions for [`accept_referral_code`](crate::gmsol_store::accept_referral_code) instruction.
 #[derive(Accounts)]
 pub struct AcceptReferralCode<'info> {
+    #[account(mut)]
     pub next_owner: Signer<'info>,
     pub store: AccountLoader<'info, Store>,
     /// User Account.
@@ -201,6 +258,14 @@ pub struct AcceptReferralCode<'info> {
         bump = receiver_user.load()?.bump,
     )]
     pub receiver_user: AccountLoader<'info, UserHeader>,
+    /// The referral reward vault to claim the transfer reward from.
+    #[account(
+        mut,
+        has_one = store,
+        seeds = [ReferralRewardVault::SEED, store.key().as_ref()],
+        bump = reward_vault.load()?.bump,
+    )]
+    pub reward_vault: Option<AccountLoader<'info, ReferralRewardVault>>,
 }
 
 pub(crate) fn accept_referral_code(ctx: Context<AcceptReferralCode>) -> Result<()> {
@@ -217,9 +282,49 @@ pub(crate) fn accept_referral_code(ctx: Context<AcceptReferralCode>) -> Result<(
         code.owner,
     );
 
+    if let Some(reward_vault) = ctx.accounts.reward_vault.as_ref() {
+        let is_eligible = reward_vault.load()?.is_eligible(&code, &receiver_user);
+        if is_eligible {
+            code.claim_transfer_reward();
+            receiver_user.set_referral_transfer_reward_claimed();
+            ctx.accounts.pay_transfer_reward(reward_vault)?;
+        }
+    }
+
     Ok(())
 }
 
+impl<'info> AcceptReferralCode<'info> {
+    fn pay_transfer_reward(
+        &self,
+        reward_vault: &AccountLoader<'info, ReferralRewardVault>,
+    ) -> Result<()> {
+        let reward = reward_vault.load()?.referral_code_transfer_reward();
+        if reward == 0 {
+            return Ok(());
+        }
+
+        let vault = reward_vault.to_account_info();
+        let rent = Rent::get()?.minimum_balance(vault.data_len());
+        let available = vault.lamports().saturating_sub(rent);
+        require_gte!(
+            available,
+            reward,
+            CoreError::InsufficientReferralRewardVaultBalance
+        );
+
+        vault.sub_lamports(reward)?;
+        self.next_owner.add_lamports(reward)?;
+
+        msg!(
+            "[Referral] paid {} lamports of transfer reward to {}",
+            reward,
+            self.next_owner.key(),
+        );
+        Ok(())
+    }
+}
+
 /// The accounts definitions for [`transfer_referral_code`](crate::gmsol_store::transfer_referral_code) instruction.
 #[derive(Accounts)]
 pub struct TransferReferralCode<'info> {
@@ -308,3 +413,332 @@ pub(crate) fn cancel_referral_code_transfer(
 
     Ok(())
 }
+
+/// The accounts definition for read-only instructions for user accounts.
+#[derive(Accounts)]
+#[instruction(owner: Pubkey)]
+pub struct ReadUser<'info> {
+    /// Store.
+    pub store: AccountLoader<'info, Store>,
+    /// User Account.
+    #[account(
+        has_one = store,
+        seeds = [UserHeader::SEED, store.key().as_ref(), owner.as_ref()],
+        bump = user.load()?.bump,
+    )]
+    pub user: AccountLoader<'info, UserHeader>,
+}
+
+/// Get referral stats.
+pub(crate) fn get_referral_stats(ctx: Context<ReadUser>, owner: &Pubkey) -> Result<ReferralStats> {
+    let user = ctx.accounts.user.load()?;
+    require!(user.is_initialized(), CoreError::InvalidUserAccount);
+    require_keys_eq!(user.owner, *owner, CoreError::OwnerMismatched);
+    Ok(user.referral().stats())
+}
+
+/// Get user activity.
+pub(crate) fn get_user_activity(ctx: Context<ReadUser>, owner: &Pubkey) -> Result<UserActivity> {
+    let user = ctx.accounts.user.load()?;
+    require!(user.is_initialized(), CoreError::InvalidUserAccount);
+    require_keys_eq!(user.owner, *owner, CoreError::OwnerMismatched);
+    Ok(*user.activity())
+}
+
+/// The accounts definitions for [`freeze_user_account`](crate::gmsol_store::freeze_user_account)
+/// and [`unfreeze_user_account`](crate::gmsol_store::unfreeze_user_account) instructions.
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(user_address: Pubkey)]
+pub struct FreezeUserAccount<'info> {
+    /// Authority.
+    pub authority: Signer<'info>,
+    /// Store.
+    pub store: AccountLoader<'info, Store>,
+    /// User Account.
+    #[account(
+        mut,
+        has_one = store,
+        constraint = user.load()?.owner == user_address @ CoreError::OwnerMismatched,
+        seeds = [UserHeader::SEED, store.key().as_ref(), user_address.as_ref()],
+        bump = user.load()?.bump,
+    )]
+    pub user: AccountLoader<'info, UserHeader>,
+}
+
+/// Freeze the user account.
+///
+/// ## CHECK
+/// - Only ADMIN can freeze user accounts.
+pub(crate) fn unchecked_freeze_user_account(
+    ctx: Context<FreezeUserAccount>,
+    user_address: Pubkey,
+    reason_hash: [u8; 32],
+) -> Result<()> {
+    ctx.accounts.user.load_mut()?.freeze(reason_hash)?;
+
+    let event_emitter = EventEmitter::new(&ctx.accounts.event_authority, ctx.bumps.event_authority);
+    event_emitter.emit_cpi(&UserAccountFrozen {
+        user_address,
+        reason_hash,
+    })?;
+
+    msg!("[User] the user account of {} is now frozen", user_address);
+    Ok(())
+}
+
+/// Unfreeze the user account.
+///
+/// ## CHECK
+/// - Only ADMIN can unfreeze user accounts.
+pub(crate) fn unchecked_unfreeze_user_account(
+    ctx: Context<FreezeUserAccount>,
+    user_address: Pubkey,
+) -> Result<()> {
+    ctx.accounts.user.load_mut()?.unfreeze()?;
+
+    let event_emitter = EventEmitter::new(&ctx.accounts.event_authority, ctx.bumps.event_authority);
+    event_emitter.emit_cpi(&UserAccountUnfrozen { user_address })?;
+
+    msg!(
+        "[User] the user account of {} is now unfrozen",
+        user_address
+    );
+    Ok(())
+}
+
+impl<'info> internal::Authentication<'info> for FreezeUserAccount<'info> {
+    fn authority(&self) -> &Signer<'info> {
+        &self.authority
+    }
+
+    fn store(&self) -> &AccountLoader<'info, Store> {
+        &self.store
+    }
+}
+
+/// The accounts definition for [`initialize_referral_reward_vault`](crate::gmsol_store::initialize_referral_reward_vault)
+/// instruction.
+#[derive(Accounts)]
+pub struct InitializeReferralRewardVault<'info> {
+    /// Authority.
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    /// Store.
+    pub store: AccountLoader<'info, Store>,
+    /// Referral reward vault.
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + ReferralRewardVault::INIT_SPACE,
+        seeds = [ReferralRewardVault::SEED, store.key().as_ref()],
+        bump,
+    )]
+    pub reward_vault: AccountLoader<'info, ReferralRewardVault>,
+    /// The system program.
+    pub system_program: Program<'info, System>,
+}
+
+/// Initialize the referral reward vault and fund it with the given amount of lamports.
+///
+/// ## CHECK
+/// - Only ADMIN can initialize the referral reward vault.
+pub(crate) fn unchecked_initialize_referral_reward_vault(
+    ctx: Context<InitializeReferralRewardVault>,
+    amount: u64,
+) -> Result<()> {
+    ctx.accounts
+        .reward_vault
+        .load_init()?
+        .init(ctx.bumps.reward_vault, &ctx.accounts.store.key());
+
+    if amount != 0 {
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.authority.to_account_info(),
+                    to: ctx.accounts.reward_vault.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+    }
+
+    Ok(())
+}
+
+impl<'info> internal::Authentication<'info> for InitializeReferralRewardVault<'info> {
+    fn authority(&self) -> &Signer<'info> {
+        &self.authority
+    }
+
+    fn store(&self) -> &AccountLoader<'info, Store> {
+        &self.store
+    }
+}
+
+/// The accounts definition for [`set_referral_transfer_reward`](crate::gmsol_store::set_referral_transfer_reward)
+/// instruction.
+#[derive(Accounts)]
+pub struct SetReferralTransferReward<'info> {
+    /// Authority.
+    pub authority: Signer<'info>,
+    /// Store.
+    pub store: AccountLoader<'info, Store>,
+    /// Referral reward vault.
+    #[account(
+        mut,
+        has_one = store,
+        seeds = [ReferralRewardVault::SEED, store.key().as_ref()],
+        bump = reward_vault.load()?.bump,
+    )]
+    pub reward_vault: AccountLoader<'info, ReferralRewardVault>,
+}
+
+/// Set the reward for accepting a referral code transfer.
+///
+/// ## CHECK
+/// - Only ADMIN can set the referral transfer reward.
+pub(crate) fn unchecked_set_referral_transfer_reward(
+    ctx: Context<SetReferralTransferReward>,
+    amount: u64,
+    min_paid_fee_value: u128,
+) -> Result<()> {
+    ctx.accounts
+        .reward_vault
+        .load_mut()?
+        .set_referral_code_transfer_reward(amount, min_paid_fee_value);
+    msg!(
+        "[Referral] the referral code transfer reward is now {}, with min paid fee value {}",
+        amount,
+        min_paid_fee_value,
+    );
+    Ok(())
+}
+
+impl<'info> internal::Authentication<'info> for SetReferralTransferReward<'info> {
+    fn authority(&self) -> &Signer<'info> {
+        &self.authority
+    }
+
+    fn store(&self) -> &AccountLoader<'info, Store> {
+        &self.store
+    }
+}
+
+/// The accounts definition for
+/// [`fund_referral_reward_vault_from_fees`](crate::gmsol_store::fund_referral_reward_vault_from_fees)
+/// instruction.
+#[event_cpi]
+#[derive(Accounts)]
+pub struct FundReferralRewardVaultFromFees<'info> {
+    /// Authority.
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    /// Store.
+    pub store: AccountLoader<'info, Store>,
+    /// Referral reward vault.
+    #[account(
+        mut,
+        has_one = store,
+        seeds = [ReferralRewardVault::SEED, store.key().as_ref()],
+        bump = reward_vault.load()?.bump,
+    )]
+    pub reward_vault: AccountLoader<'info, ReferralRewardVault>,
+    /// The market to claim the fees from.
+    #[account(mut, has_one = store)]
+    pub market: AccountLoader<'info, Market>,
+    /// The wrapped native token.
+    #[account(address = native_mint::ID)]
+    pub token_mint: InterfaceAccount<'info, Mint>,
+    /// The market vault for the wrapped native token.
+    #[account(
+        mut,
+        token::mint = token_mint,
+        token::authority = store,
+        token::token_program = token_program,
+        seeds = [
+            constants::MARKET_VAULT_SEED,
+            store.key().as_ref(),
+            token_mint.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+    /// The escrow account used to unwrap the claimed fees.
+    /// It is closed at the end of the instruction.
+    #[account(
+        init_if_needed,
+        payer = authority,
+        associated_token::mint = token_mint,
+        associated_token::authority = reward_vault,
+        associated_token::token_program = token_program,
+    )]
+    pub escrow: InterfaceAccount<'info, TokenAccount>,
+    /// The system program.
+    pub system_program: Program<'info, System>,
+    /// The token program.
+    pub token_program: Interface<'info, TokenInterface>,
+    /// The associated token program.
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+/// Claim the fees in wrapped native token from the market and fund the referral
+/// reward vault with them.
+///
+/// # Errors
+/// - Only the receiver of treasury can fund the referral reward vault with the fees.
+pub(crate) fn fund_referral_reward_vault_from_fees(
+    ctx: Context<FundReferralRewardVaultFromFees>,
+) -> Result<u64> {
+    // Validate the authority to be the receiver for the treasury.
+    ctx.accounts
+        .store
+        .load()?
+        .validate_not_restarted()?
+        .validate_claim_fees_address(ctx.accounts.authority.key)?;
+
+    let event_emitter = EventEmitter::new(&ctx.accounts.event_authority, ctx.bumps.event_authority);
+
+    let token = &ctx.accounts.token_mint;
+    let amount = claim_all_fees_from_pool(&ctx.accounts.market, &token.key(), event_emitter)?;
+
+    MarketTransferOutOperation::builder()
+        .store(&ctx.accounts.store)
+        .market(&ctx.accounts.market)
+        .amount(amount)
+        .decimals(token.decimals)
+        .to(ctx.accounts.escrow.to_account_info())
+        .token_mint(token.to_account_info())
+        .vault(ctx.accounts.vault.to_account_info())
+        .token_program(ctx.accounts.token_program.to_account_info())
+        .event_emitter(event_emitter)
+        .build()
+        .execute()?;
+
+    // Unwrap the claimed fees into the reward vault. The rent of the escrow account
+    // is also transferred to the reward vault.
+    {
+        let reward_vault = ctx.accounts.reward_vault.load()?;
+        let seeds = reward_vault.signer_seeds();
+        token_interface::close_account(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                token_interface::CloseAccount {
+                    account: ctx.accounts.escrow.to_account_info(),
+                    destination: ctx.accounts.reward_vault.to_account_info(),
+                    authority: ctx.accounts.reward_vault.to_account_info(),
+                },
+            )
+            .with_signer(&[&seeds]),
+        )?;
+    }
+
+    msg!(
+        "[Referral] funded the referral reward vault with {} lamports of fees from the {} market",
+        amount,
+        ctx.accounts.market.load()?.meta().market_token_mint,
+    );
+    Ok(amount)
+}
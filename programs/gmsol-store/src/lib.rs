@@ -37,6 +37,8 @@
 //! - [`transfer_store_authority`]: Transfer the authority of the given store to a new authority.
 //! - [`transfer_receiver`](gmsol_store::transfer_receiver): Set the claimable fee receiver address.
 //! - [`set_token_map`]: Set the token map account to use.
+//! - [`set_store_paused`]: Pause or unpause the store.
+//! - [`enable_emergency_mode`]: Enable the emergency mode after the store has been paused long enough.
 //!
 //! #### Instructions for Config Management
 //! - [`insert_amount`]: Insert an amount to the global config.
@@ -44,9 +46,16 @@
 //! - [`insert_address`]: Insert an address to the global config.
 //! - [`insert_order_fee_discount_for_referred_user`]:
 //!   Insert order fee discount for referred user factor to the global config.
+//! - [`set_deposit_dust_threshold`]: Set the minimum USD values of deposits.
+//! - [`take_config_snapshot`]: Take a snapshot of the global config.
+//! - [`get_config_snapshot`]: Get a historical snapshot of the global config.
+//! - [`compare_config_snapshots`]: Get the config entries changed between two snapshots.
 //!
 //! #### Instructions for Feature Management
 //! - [`toggle_feature`]: Enable or disable the given feature.
+//! - [`grant_keeper_deadline_extension`]: Grant or update the deadline extension for keepers.
+//! - [`revoke_keeper_deadline_extension`]: Revoke the deadline extension for keepers.
+//! - [`set_keeper_priority`]: Create or update the priority config of a keeper.
 //!
 //! ## Role-based Permission Management
 //!
@@ -82,22 +91,35 @@
 //! - [`toggle_token_config`]: Enable or disable a token config of the given token map.
 //! - [`set_expected_provider`]: Set the expected provider for the given token.
 //! - [`set_feed_config`]: Set the feed config of the given provider for the given token.
+//! - [`update_bridge_premium`]: Update the bridge premium factor of the given bridged token.
+//! - [`set_max_price_deviation`]: Set the max deviation from the last recorded price of the given token.
+//! - [`initialize_last_oracle_price`]: Initialize the account recording the last price of the given token.
+//! - [`clear_last_oracle_price`]: Clear the last recorded price of the given token.
 //! - [`is_token_config_enabled`](gmsol_store::is_token_config_enabled): Check if the config for the given token is enabled.
+//! - [`get_disabled_tokens`](gmsol_store::get_disabled_tokens): Get the tokens whose configs are disabled in the given token map.
 //! - [`token_expected_provider`](gmsol_store::token_expected_provider): Get the expected provider set for the given token.
 //! - [`token_feed`](gmsol_store::token_feed): Get the feed address of the given provider set for the given token.
 //! - [`token_timestamp_adjustment`](gmsol_store::token_timestamp_adjustment): Get the timestamp adjustment of the given
 //!   provider for the give token.
+//! - [`get_adjusted_timestamp`](gmsol_store::get_adjusted_timestamp): Get the timestamp of the price reported by the
+//!   given feed with the timestamp adjustment applied.
 //! - [`token_name`](gmsol_store::token_name): Get the name of the given token.
 //! - [`token_decimals`](gmsol_store::token_decimals): Get the token decimals of the given token.
 //! - [`token_precision`](gmsol_store::token_precision): Get the price precision of the given token.
+//! - [`get_token_config`](gmsol_store::get_token_config): Get the full config of the given token.
 //!
 //! #### Instructions for [`Oracle`](states::Oracle) accounts
 //! - [`initialize_oracle`](gmsol_store::initialize_oracle): Initialize a new [`Oracle`](states::Oracle) account.
 //! - [`clear_all_prices`]: Clear the prices of the given oracle account.
+//! - [`rotate_oracle`]: Replace an oracle account with a new one.
+//! - [`close_decommissioned_oracle`]: Close a decommissioned oracle account.
 //! - [`set_prices_from_price_feed`]: Validate and set prices parsed from the
 //!   provided price feed accounts.
+//! - [`initialize_price_adjustment_schedule`]: Initialize the hourly price band adjustment schedule of a token.
+//! - [`update_price_adjustment_schedule`]: Update the factor of an hour in the price adjustment schedule.
 //! - [`initialize_price_feed`]: Initialize a custom price feed.
 //! - [`update_price_feed_with_chainlink`]: Update a custom Chainlink price feed with Chainlink Data Streams report.
+//! - [`get_price_feed_status`](gmsol_store::get_price_feed_status): Get the status of a custom price feed.
 //!
 //! ## Market Management
 //!
@@ -105,14 +127,57 @@
 //!
 //! #### Instructions for [`Market`](states::Market) management
 //! - [`initialize_market`]: Initialize a [`Market`](states::Market) account.
+//! - [`initialize_market_with_price_caps`]: Initialize a [`Market`](states::Market) account and
+//!   set the price caps of the given tokens.
 //! - [`toggle_market`]: Enable or disable the given market.
+//! - [`set_market_token_map`]: Set the override token map of the given market.
+//! - [`set_market_name`]: Set the name of the given market.
+//! - [`close_market`]: Close a disabled and drained market.
 //! - [`market_transfer_in`]: Transfer tokens into the market and record the amount in its balance.
+//! - [`emergency_vault_withdraw`]: Withdraw tokens from a market vault in emergency mode.
+//! - [`compress_pool_state`]: Zero-fill the reserved ranges of a market account.
+//! - [`export_market_state`]: Emit the full state of a market with its hash.
 //! - [`update_market_config`]: Update an item in the market config.
 //! - [`update_market_config_with_buffer`]: Update the market config with the given
 //!   [`MarketConfigBuffer`](states::market::config::MarketConfigBuffer) account.
 //! - [`get_market_status`](gmsol_store::get_market_status): Calculate the market status with the given prices.
+//! - [`validate_market_config`](gmsol_store::validate_market_config): Report the out-of-range configs of the market.
+//! - [`get_market_pending_counts`](gmsol_store::get_market_pending_counts): Get the number of pending
+//!   deposits, withdrawals and orders of the market.
+//! - [`list_market_config_keys`](gmsol_store::list_market_config_keys): List the names of the supported market config keys.
+//! - [`list_market_config_flags`](gmsol_store::list_market_config_flags): List the names of the supported market config flags.
+//! - [`get_market_expected_value`](gmsol_store::get_market_expected_value): Estimate the expected value of
+//!   providing liquidity to the market.
+//! - [`get_market_swap_fees`](gmsol_store::get_market_swap_fees): Simulate a swap in the market and return
+//!   the fee breakdown.
+//! - [`get_market_price_impact_sensitivity`](gmsol_store::get_market_price_impact_sensitivity): Simulate
+//!   opening positions of increasing sizes and return the price impacts.
+//! - [`get_swap_arbitrage_opportunity`](gmsol_store::get_swap_arbitrage_opportunity): Find the swap path
+//!   with the highest output among the given markets.
 //! - [`get_market_token_price`](gmsol_store::get_market_token_price): Calculate the market token price the given prices.
+//! - [`get_market_token_backing`](gmsol_store::get_market_token_backing): Calculate the underlying tokens
+//!   backing one unit of market token with the given prices.
+//! - [`get_lp_value_per_token`](gmsol_store::get_lp_value_per_token): Calculate the value of the
+//!   liquidity pool and the market token with the prices in the oracle.
+//! - [`verify_market_token_supply`](gmsol_store::verify_market_token_supply): Verify the market token
+//!   supply against the market balances with the given prices.
+//! - [`get_position_info`](gmsol_store::get_position_info): Calculate the health metrics of a position with the given prices.
+//! - [`estimate_adl_size_delta`](gmsol_store::estimate_adl_size_delta): Estimate the size delta to
+//!   auto-deleverage for a position with the given prices.
+//! - [`get_collateral_coverage_ratio`](gmsol_store::get_collateral_coverage_ratio): Calculate the
+//!   collateral coverage of a position with the given prices.
+//! - [`get_position_break_even_price`](gmsol_store::get_position_break_even_price): Calculate the
+//!   break-even price of a position with the given prices.
 //! - [`toggle_gt_minting`]: Enable or disable GT minting for the given market.
+//! - [`initialize_virtual_pool`]: Initialize a virtual pool shared by correlated markets.
+//! - [`add_market_to_virtual_pool`]: Add the given market to a virtual pool.
+//! - [`initialize_market_oi_caps`]: Initialize the OI caps account of the given market.
+//! - [`update_market_oi_caps`]: Adjust the OI caps of the given market, subject to the daily rate limit.
+//! - [`batch_update_market_oi_caps`](gmsol_store::batch_update_market_oi_caps): Adjust the OI caps of
+//!   multiple markets atomically.
+//! - [`initialize_market_analytics`]: Initialize the analytics account of the given market for
+//!   the current period, which accumulates the OHLCV data of the trades.
+//! - [`get_ohlcv_period`]: Get the OHLCV data of the given period.
 //!
 //! #### Instructions for [`MarketConfigBuffer`](states::market::config::MarketConfigBuffer) accounts
 //! - [`initialize_market_config_buffer`](gmsol_store::initialize_market_config_buffer): Initialize a market config buffer account.
@@ -122,10 +187,19 @@
 //! - [`push_to_market_config_buffer`](gmsol_store::push_to_market_config_buffer): Push config items to the given market config
 //!   buffer account.
 //!
+//! #### Instructions for [`FeeStructureProposal`](states::FeeStructureProposal) accounts
+//! - [`propose_fee_structure_change`](gmsol_store::propose_fee_structure_change): Propose changes to the fee
+//!   parameters of the given market.
+//! - [`vote_on_fee_structure`](gmsol_store::vote_on_fee_structure): Vote for or against a fee structure proposal.
+//! - [`execute_fee_structure_proposal`](gmsol_store::execute_fee_structure_proposal): Apply the changes of an
+//!   approved fee structure proposal to the market.
+//!
 //! #### Instructions for token accounts
 //! - [`initialize_market_vault`]: Initialize the market vault for the given token.
 //! - [`use_claimable_account`]: Prepare a claimable account to receive tokens during the order execution.
 //! - [`close_empty_claimable_account`]: Close a empty claimble account.
+//! - [`close_empty_claimable_accounts`]: Close the empty ones of the given claimable accounts.
+//! - [`recover_stuck_claimable_account`]: Recover a claimable account that has been dormant for too long.
 //! - [`prepare_associated_token_account`](gmsol_store::prepare_associated_token_account): Prepare an ATA.
 //!
 //! ## Exchange
@@ -148,16 +222,23 @@
 //!
 //! #### Instructions for [`Order`](states::Order) and [`Position`](states::Position)
 //! - [`prepare_position`](gmsol_store::prepare_position): Prepare the position account for orders.
+//! - [`batch_prepare_positions`](gmsol_store::batch_prepare_positions): Prepare multiple position
+//!   accounts for orders at once.
 //! - [`prepare_trade_event_buffer`](gmsol_store::prepare_trade_event_buffer): Prepare trade event buffer.
 //! - [`create_order`]: Create an order by the owner.
 //! - [`update_order`](gmsol_store::update_order): Update an order by the owner.
 //! - [`execute_increase_or_swap_order`](gmsol_store::execute_increase_or_swap_order()): Execute an order by keepers.
 //! - [`execute_decrease_order`]: Execute a decrease order by keepers.
+//! - [`invoke_order_callback`]: Invoke the pending callback of an executed order by keepers.
 //! - [`close_order`]: Close an order, either by the owner or by keepers.
 //! - [`cancel_order_if_no_position`]: Cancel an order if the position does not exist.
 //! - [`liquidate`]: Perform a liquidation by keepers.
 //! - [`auto_deleverage`]: Perform an ADL by keepers.
 //! - [`update_adl_state`]: Update the ADL state of the market.
+//! - [`validate_execution_params`]: Dry-run the pre-execution checks of a deposit, withdrawal
+//!   or order for keepers.
+//! - [`get_required_feeds`]: Get the tokens and feeds required for executing a deposit,
+//!   withdrawal, order or shift.
 //!
 //! ## GLV (GMX Liquidity Vault) Pools
 //! The instructions for providing functionalities for GLV are as follows:
@@ -166,9 +247,11 @@
 //! - [`initialize_glv`]: Initialize a GLV.
 //! - [`update_glv_market_config`]: Update GLV market config.
 //! - [`toggle_glv_market_flag`]: Toggle flags of GLV market.
+//! - [`set_glv_market_min_amount`]: Set the min allocation of a GLV market.
 //! - [`update_glv_config`]: Update GLV global config.
 //! - [`insert_glv_market`]: Insert a new market to the GLV.
 //! - [`remove_glv_market`]: Remove a market from the GLV.
+//! - [`get_glv_token_price`](gmsol_store::get_glv_token_price): Calculate the GLV token price with the given prices.
 //!
 //! #### Instructions for [`GlvDeposit`](states::GlvDeposit)
 //! - [`create_glv_deposit`]: Create a GLV deposit by the owner.
@@ -185,6 +268,13 @@
 //! - [`execute_glv_shift`]: Execute a GLV shift by keepers.
 //! - [`close_glv_shift`]: Close a shift by keepers.
 //!
+//! #### Instructions for [`GlvTreasury`](states::glv::GlvTreasury)
+//! - [`initialize_glv_treasury`]: Initialize the treasury of a GLV.
+//! - [`update_glv_treasury`]: Update the treasury of a GLV.
+//! - [`withdraw_from_glv_treasury`]: Withdraw tokens from the treasury of a GLV.
+//! - [`trigger_automatic_rebalance_deposit`]: Rebalance a GLV market below its min allocation
+//!   with a deposit funded by the GLV treasury.
+//!
 //! ## User Accounts and Referrals
 //! The instructions for user accounts and referrals are as follows:
 //! - [`prepare_user`](gmsol_store::prepare_user): Prepare a user account.
@@ -193,6 +283,20 @@
 //! - [`transfer_referral_code`](gmsol_store::transfer_referral_code): Transfer the referral code to others.
 //! - [`cancel_referral_code_transfer`](gmsol_store::cancel_referral_code_transfer): Cancel the referral code transfer.
 //! - [`accept_referral_code`](gmsol_store::accept_referral_code): Complete the referral code transfer.
+//! - [`initialize_referral_reward_vault`](gmsol_store::initialize_referral_reward_vault): Initialize and fund the referral reward vault.
+//! - [`set_referral_transfer_reward`](gmsol_store::set_referral_transfer_reward): Set the reward for accepting a referral code transfer.
+//! - [`fund_referral_reward_vault_from_fees`](gmsol_store::fund_referral_reward_vault_from_fees): Fund the referral reward vault with protocol fees.
+//! - [`get_referral_stats`](gmsol_store::get_referral_stats): Get the referral stats of a user.
+//! - [`get_user_activity`](gmsol_store::get_user_activity): Get the deposit, withdrawal and shift activity of a user.
+//! - [`freeze_user_account`](gmsol_store::freeze_user_account): Freeze a user account.
+//! - [`unfreeze_user_account`](gmsol_store::unfreeze_user_account): Unfreeze a user account.
+//!
+//! ## Liquidity Mining
+//! The instructions for liquidity mining campaigns are as follows:
+//! - [`initialize_liquidity_mining_campaign`](gmsol_store::initialize_liquidity_mining_campaign): Initialize a liquidity mining campaign.
+//! - [`stake_for_liquidity_mining`](gmsol_store::stake_for_liquidity_mining): Stake market tokens into a campaign.
+//! - [`unstake_from_liquidity_mining`](gmsol_store::unstake_from_liquidity_mining): Unstake market tokens from a campaign.
+//! - [`claim_liquidity_mining_rewards`](gmsol_store::claim_liquidity_mining_rewards): Claim the accrued rewards of a campaign.
 //!
 //! ## GT Model
 //!
@@ -202,11 +306,14 @@
 //! - [`initialize_gt`]: Initialize the GT state.
 //! - [`gt_set_order_fee_discount_factors`]: Set order fee discount factors.
 //! - [`gt_set_referral_reward_factors`]: Set referral reward factors.
+//! - [`validate_referral_reward_factors`](gmsol_store::validate_referral_reward_factors): Validate referral reward factors.
 //! - [`gt_set_exchange_time_window`]: Set GT exchange time window.
 //! - [`prepare_gt_exchange_vault`](gmsol_store::prepare_gt_exchange_vault): Prepare current GT exchange vault.
 //! - [`confirm_gt_exchange_vault`]: Confirm GT exchange vault.
 //! - [`request_gt_exchange`](gmsol_store::request_gt_exchange): Request a GT exchange.
-//! - [`close_gt_exchange`]: Close a confirmed GT exchange.
+//! - [`close_gt_exchange`]: Mark a confirmed GT exchange as completed.
+//! - [`reclaim_gt_exchange_rent`](gmsol_store::reclaim_gt_exchange_rent): Close completed GT exchanges and fully completed
+//!   GT exchange vaults in bulk.
 
 /// Instructions.
 pub mod instructions;
@@ -236,10 +343,28 @@ use self::{
         withdrawal::CreateWithdrawalParams,
     },
     states::{
+        common::TokensWithFeed,
+        config_snapshot::{ConfigDiff, ConfigSnapshot},
         glv::UpdateGlvParams,
-        market::{config::EntryArgs, status::MarketStatus},
-        order::UpdateOrderParams,
-        token_config::UpdateTokenConfigParams,
+        gt::ReferralRewardFactorsProblem,
+        liquidity_mining::CampaignParams,
+        market::{
+            analytics::OhlcvData,
+            config::{EntryArgs, MarketConfigProblem},
+            oi_caps::OiCapUpdate,
+            status::{
+                ArbitrageOpportunity, LpValueInfo, MarketExpectedValue, MarketStatus,
+                MarketTokenBacking, PriceImpactSensitivity, SupplyVerification, SwapFeeBreakdown,
+            },
+            PendingCounts,
+        },
+        oracle::PriceFeedStatus,
+        order::{TransferOut, UpdateOrderParams},
+        position::{
+            AdlEstimate, BreakEvenPrice, CollateralCoverage, LiquidationImpact, PositionInfo,
+            PositionSpec,
+        },
+        token_config::{BridgePremiumConfig, TokenConfigInfo, UpdateTokenConfigParams},
         FactorKey, PriceProviderKind,
     },
     utils::internal,
@@ -390,6 +515,52 @@ pub mod gmsol_store {
         instructions::unchecked_set_token_map(ctx)
     }
 
+    /// Pause or unpause the store.
+    ///
+    /// While the store is paused, creating, updating and executing actions (including
+    /// the GLV and GT actions) fail with [`CoreError::FeatureDisabled`]. Closing actions
+    /// and views are still permitted so that users can exit.
+    ///
+    /// Unpausing the store also disables the emergency mode.
+    ///
+    /// # Accounts
+    /// *[See the documentation for the accounts.](SetStorePaused).*
+    ///
+    /// # Arguments
+    /// - `paused`: Whether to pause (`true`) or unpause (`false`) the store.
+    ///
+    /// # Errors
+    /// - The [`authority`](SetStorePaused::authority) must be a signer and the current
+    ///   admin of the store.
+    /// - The [`store`](SetStorePaused::store) must be an initialized store account
+    ///   owned by the store program.
+    /// - The `paused` flag must differ from the current state.
+    #[access_control(internal::Authenticate::only_admin(&ctx))]
+    pub fn set_store_paused(ctx: Context<SetStorePaused>, paused: bool) -> Result<()> {
+        instructions::unchecked_set_store_paused(ctx, paused)
+    }
+
+    /// Enable the emergency mode of the store.
+    ///
+    /// The emergency mode allows the admin to withdraw tokens from market vaults with
+    /// [`emergency_vault_withdraw`].
+    ///
+    /// # Accounts
+    /// *[See the documentation for the accounts.](EnableEmergencyMode).*
+    ///
+    /// # Errors
+    /// - The [`authority`](EnableEmergencyMode::authority) must be a signer and the current
+    ///   admin of the store.
+    /// - The [`store`](EnableEmergencyMode::store) must be an initialized store account
+    ///   owned by the store program.
+    /// - The store must have been paused for at least
+    ///   [`EMERGENCY_MODE_MIN_PAUSED_DURATION`](constants::EMERGENCY_MODE_MIN_PAUSED_DURATION) seconds.
+    /// - The emergency mode must not have been enabled.
+    #[access_control(internal::Authenticate::only_admin(&ctx))]
+    pub fn enable_emergency_mode(ctx: Context<EnableEmergencyMode>) -> Result<()> {
+        instructions::unchecked_enable_emergency_mode(ctx)
+    }
+
     // ===========================================
     //      Role-based Permission Management
     // ===========================================
@@ -665,6 +836,130 @@ pub mod gmsol_store {
         instructions::unchecked_insert_factor(ctx, &key.to_string(), factor)
     }
 
+    /// Set the minimum USD values of the long and short sides of deposits.
+    ///
+    /// Deposits and GLV deposits with any side valued below the corresponding minimum
+    /// are cancelled on execution, or fail if `throw_on_execution_error` is set.
+    ///
+    /// # Accounts
+    /// *[See the documentation for the accounts.](InsertConfig).*
+    ///
+    /// # Arguments
+    /// - `min_long_usd`: The minimum total USD value of a deposit providing long tokens.
+    ///   `0` to disable.
+    /// - `min_short_usd`: The minimum total USD value of a deposit providing short tokens.
+    ///   `0` to disable.
+    ///
+    /// # Errors
+    /// - The [`authority`](InsertConfig::authority) must be a signer and have the CONFIG_KEEPER role
+    ///   in the store.
+    /// - The store must be initialized and owned by this program.
+    ///
+    /// # Notes
+    /// - The combined value of both sides of a deposit is compared against the lowest
+    ///   minimum of the sides it provides.
+    /// - The values are stored under the [`FactorKey::MinDepositLongUsd`] and
+    ///   [`FactorKey::MinDepositShortUsd`] keys.
+    #[access_control(internal::Authenticate::only_config_keeper(&ctx))]
+    pub fn set_deposit_dust_threshold(
+        ctx: Context<InsertConfig>,
+        min_long_usd: u128,
+        min_short_usd: u128,
+    ) -> Result<()> {
+        instructions::unchecked_set_deposit_dust_threshold(ctx, min_long_usd, min_short_usd)
+    }
+
+    /// Set the trusted Chainlink verifier entry at the given index.
+    ///
+    /// Only the Chainlink verifier programs in the trusted list can be used to update
+    /// custom price feeds with [`update_price_feed_with_chainlink`].
+    ///
+    /// # Accounts
+    /// *[See the documentation for the accounts.](InsertConfig).*
+    ///
+    /// # Arguments
+    /// - `index`: The index of the entry to update.
+    /// - `verifier`: The program ID of the verifier. Set to the default pubkey to remove the entry.
+    ///
+    /// # Errors
+    /// - The [`authority`](InsertConfig::authority) must be a signer and the current admin of the
+    ///   store.
+    /// - The store must be initialized and owned by this program.
+    /// - The `index` must be less than
+    ///   [`MAX_TRUSTED_CHAINLINK_VERIFIERS`](states::Store::MAX_TRUSTED_CHAINLINK_VERIFIERS).
+    #[access_control(internal::Authenticate::only_admin(&ctx))]
+    pub fn set_trusted_chainlink_verifier(
+        ctx: Context<InsertConfig>,
+        index: u8,
+        verifier: Pubkey,
+    ) -> Result<()> {
+        instructions::unchecked_set_trusted_chainlink_verifier(ctx, index, &verifier)
+    }
+
+    /// Take a snapshot of the global config.
+    ///
+    /// The snapshot is written to the ring buffer slot of the next snapshot index, overwriting
+    /// the oldest snapshot once [`MAX_SNAPSHOTS`](states::GlobalConfigSnapshot::MAX_SNAPSHOTS)
+    /// snapshots have been taken.
+    ///
+    /// # Accounts
+    /// *[See the documentation for the accounts.](TakeConfigSnapshot).*
+    ///
+    /// # Errors
+    /// - The [`authority`](TakeConfigSnapshot::authority) must be a signer and have the
+    ///   CONFIG_KEEPER role in the store.
+    /// - The store must be initialized and owned by this program.
+    /// - The [`snapshot`](TakeConfigSnapshot::snapshot) must be the PDA of the ring buffer slot
+    ///   of the next snapshot index.
+    #[access_control(internal::Authenticate::only_config_keeper(&ctx))]
+    pub fn take_config_snapshot(ctx: Context<TakeConfigSnapshot>) -> Result<()> {
+        instructions::unchecked_take_config_snapshot(ctx)
+    }
+
+    /// Get a historical snapshot of the global config.
+    ///
+    /// # Accounts
+    /// *[See the documentation for the accounts.](ReadConfigSnapshot).*
+    ///
+    /// # Arguments
+    /// - `index`: The index of the snapshot.
+    ///
+    /// # Return
+    /// Returns the [`ConfigSnapshot`](states::config_snapshot::ConfigSnapshot) of the defined keys.
+    ///
+    /// # Errors
+    /// - The [`snapshot`](ReadConfigSnapshot::snapshot) must be a snapshot account of the `store`.
+    /// - The snapshot with the given `index` must not have been evicted.
+    pub fn get_config_snapshot(
+        ctx: Context<ReadConfigSnapshot>,
+        index: u64,
+    ) -> Result<ConfigSnapshot> {
+        instructions::get_config_snapshot(ctx, index)
+    }
+
+    /// Get the config entries changed between two snapshots of the global config.
+    ///
+    /// # Accounts
+    /// *[See the documentation for the accounts.](CompareConfigSnapshots).*
+    ///
+    /// # Arguments
+    /// - `index_a`: The index of the earlier snapshot.
+    /// - `index_b`: The index of the later snapshot.
+    ///
+    /// # Return
+    /// Returns the list of [`ConfigDiff`](states::config_snapshot::ConfigDiff).
+    ///
+    /// # Errors
+    /// - The snapshot accounts must be snapshot accounts of the `store`.
+    /// - The snapshots with the given indexes must not have been evicted.
+    pub fn compare_config_snapshots(
+        ctx: Context<CompareConfigSnapshots>,
+        index_a: u64,
+        index_b: u64,
+    ) -> Result<Vec<ConfigDiff>> {
+        instructions::compare_config_snapshots(ctx, index_a, index_b)
+    }
+
     // ===========================================
     //             Feature Management
     // ===========================================
@@ -719,6 +1014,86 @@ pub mod gmsol_store {
         instructions::unchecked_toggle_feature(ctx, domain, action, enable)
     }
 
+    /// Grant (or update) a deadline extension for keepers.
+    ///
+    /// During network congestion, keepers may fail to execute actions within the recent time
+    /// window. This instruction allows a FEATURE_KEEPER to temporarily extend the window used to
+    /// validate the `recent_timestamp` argument of the order execution instructions.
+    ///
+    /// # Accounts
+    /// *[See the documentation for the accounts.](GrantKeeperDeadlineExtension).*
+    ///
+    /// # Arguments
+    /// - `extension_secs`: The extension (in seconds) added to the recent time window.
+    /// - `duration_secs`: The extension will be valid for `duration_secs` seconds from now.
+    ///
+    /// # Errors
+    /// - The [`authority`](GrantKeeperDeadlineExtension::authority) must be a signer and have the
+    ///   FEATURE_KEEPER role in the store.
+    /// - Both `extension_secs` and `duration_secs` must be non-zero.
+    #[access_control(internal::Authenticate::only_feature_keeper(&ctx))]
+    pub fn grant_keeper_deadline_extension(
+        ctx: Context<GrantKeeperDeadlineExtension>,
+        extension_secs: u32,
+        duration_secs: u32,
+    ) -> Result<()> {
+        instructions::unchecked_grant_keeper_deadline_extension(ctx, extension_secs, duration_secs)
+    }
+
+    /// Revoke the deadline extension for keepers by closing the extension account.
+    ///
+    /// # Accounts
+    /// *[See the documentation for the accounts.](RevokeKeeperDeadlineExtension).*
+    ///
+    /// # Errors
+    /// - The [`authority`](RevokeKeeperDeadlineExtension::authority) must be a signer and have the
+    ///   FEATURE_KEEPER role in the store.
+    /// - The [`deadline_extension`](RevokeKeeperDeadlineExtension::deadline_extension) must be
+    ///   the initialized extension account of the store.
+    #[access_control(internal::Authenticate::only_feature_keeper(&ctx))]
+    pub fn revoke_keeper_deadline_extension(
+        ctx: Context<RevokeKeeperDeadlineExtension>,
+    ) -> Result<()> {
+        instructions::unchecked_revoke_keeper_deadline_extension(ctx)
+    }
+
+    /// Create or update the priority config of the given keeper.
+    ///
+    /// The priority config is a routing hint for off-chain keepers: high-priority orders of the
+    /// reserved kinds should be routed to keepers with higher priority. Order owners can
+    /// additionally assign their orders to a specific keeper at creation time, see
+    /// [`CreateOrderParams::assigned_keeper`](crate::ops::order::CreateOrderParams::assigned_keeper).
+    ///
+    /// # Accounts
+    /// *[See the documentation for the accounts.](SetKeeperPriority).*
+    ///
+    /// # Arguments
+    /// - `keeper`: The address of the keeper.
+    /// - `priority`: The priority of the keeper.
+    /// - `max_concurrent_executions`: The max number of concurrent executions of the keeper.
+    /// - `order_kinds`: The kinds of orders the keeper is reserved for.
+    ///
+    /// # Errors
+    /// - The [`authority`](SetKeeperPriority::authority) must be a signer and the ADMIN of the
+    ///   store.
+    /// - Each of the `order_kinds` must be a valid [`OrderKind`](states::order::OrderKind).
+    #[access_control(internal::Authenticate::only_admin(&ctx))]
+    pub fn set_keeper_priority(
+        ctx: Context<SetKeeperPriority>,
+        keeper: Pubkey,
+        priority: u8,
+        max_concurrent_executions: u16,
+        order_kinds: Vec<u8>,
+    ) -> Result<()> {
+        instructions::unchecked_set_keeper_priority(
+            ctx,
+            keeper,
+            priority,
+            max_concurrent_executions,
+            order_kinds,
+        )
+    }
+
     // ===========================================
     //           Token Config Management
     // ===========================================
@@ -889,6 +1264,22 @@ pub mod gmsol_store {
     ///   Must be a valid [`PriceProviderKind`] value.
     /// - `feed`: The new feed address.
     /// - `timestamp_adjustment`: The new timestamp adjustment in seconds.
+    /// - `min_price`: The lower bound of the price band, `0` to disable.
+    /// - `max_price`: The upper bound of the price band, `0` to disable.
+    /// - `price_multiplier`: The numerator of the ratio that prices from the feed are scaled by.
+    /// - `price_multiplier_denominator`: The denominator of the ratio that prices from the
+    ///   feed are scaled by.
+    /// - `bridge_premium`: The new bridge premium config of the token, or `None` to keep the
+    ///   current one.
+    ///
+    /// Prices from the feed are multiplied by `price_multiplier / price_multiplier_denominator`
+    /// right after parsing, before the price band is checked.
+    ///
+    /// The bounds of the price band are prices of one whole token in USD with the precision of
+    /// the token. Prices from the feed outside the band are rejected when setting prices.
+    ///
+    /// For a bridged token, prices from the feed are multiplied by
+    /// `1 + bridge_premium_factor / MARKET_USD_UNIT` when setting prices.
     ///
     /// # Errors
     /// - The [`authority`](SetFeedConfig::authority) must be a signer
@@ -899,6 +1290,11 @@ pub mod gmsol_store {
     ///   owned by the `store`.
     /// - The given `token` must exist in the token map.
     /// - The `provider` index must correspond to a valid [`PriceProviderKind`].
+    /// - `min_price` must not be greater than `max_price` if both are set.
+    /// - `price_multiplier` and `price_multiplier_denominator` must be non-zero, and their
+    ///   ratio must be within `[1 / MAX_PRICE_MULTIPLIER_RATIO, MAX_PRICE_MULTIPLIER_RATIO]`.
+    /// - The bridge premium factor must be greater than `-MARKET_USD_UNIT` if provided.
+    #[allow(clippy::too_many_arguments)]
     #[access_control(internal::Authenticate::only_market_keeper(&ctx))]
     pub fn set_feed_config(
         ctx: Context<SetFeedConfig>,
@@ -906,6 +1302,11 @@ pub mod gmsol_store {
         provider: u8,
         feed: Pubkey,
         timestamp_adjustment: u32,
+        min_price: u32,
+        max_price: u32,
+        price_multiplier: u32,
+        price_multiplier_denominator: u32,
+        bridge_premium: Option<BridgePremiumConfig>,
     ) -> Result<()> {
         instructions::unchecked_set_feed_config(
             ctx,
@@ -914,9 +1315,132 @@ pub mod gmsol_store {
                 .map_err(|_| CoreError::InvalidProviderKindIndex)?,
             feed,
             timestamp_adjustment,
+            min_price,
+            max_price,
+            price_multiplier,
+            price_multiplier_denominator,
+            bridge_premium,
         )
     }
 
+    /// Update the bridge premium factor of the given bridged token.
+    ///
+    /// # Accounts
+    /// [*See the documentation for the accounts*](UpdateBridgePremium).
+    ///
+    /// # Arguments
+    /// - `token`: The bridged token whose config will be updated.
+    /// - `factor`: The new bridge premium factor, positive for a premium and negative for
+    ///   a discount, with the unit of `MARKET_USD_UNIT`.
+    ///
+    /// # Errors
+    /// - The [`authority`](UpdateBridgePremium::authority) must be a signer
+    ///   and an ORACLE_CONTROLLER in the given store.
+    /// - The [`store`](UpdateBridgePremium::store) must be an initialized [`Store`](states::Store)
+    ///   account owned by the store program.
+    /// - The [`token_map`](UpdateBridgePremium::token_map) must be an initialized token map account
+    ///   owned by the `store`.
+    /// - The given `token` must exist in the token map and be marked as bridged.
+    /// - The `factor` must be greater than `-MARKET_USD_UNIT`.
+    #[access_control(internal::Authenticate::only_oracle_controller(&ctx))]
+    pub fn update_bridge_premium(
+        ctx: Context<UpdateBridgePremium>,
+        token: Pubkey,
+        factor: i128,
+    ) -> Result<()> {
+        instructions::unchecked_update_bridge_premium(ctx, token, factor)
+    }
+
+    /// Set the max deviation of the prices from the price feed from the last recorded price
+    /// of the given token.
+    ///
+    /// When setting prices from price feeds, a new price is rejected if it deviates from
+    /// the last recorded price by more than this threshold, provided that the last price
+    /// was recorded within the `oracle_price_deviation_window` of the store.
+    ///
+    /// The [`LastOraclePrice`](states::LastOraclePrice) account of the token must be
+    /// initialized with [`initialize_last_oracle_price`] before enabling the check, since it
+    /// is required by [`set_prices_from_price_feed`] afterwards.
+    ///
+    /// # Accounts
+    /// [*See the documentation for the accounts*](SetMaxPriceDeviation).
+    ///
+    /// # Arguments
+    /// - `token`: The token whose config will be updated.
+    /// - `max_price_deviation_bps`: The max deviation in basis points, `0` to disable.
+    ///
+    /// # Errors
+    /// - The [`authority`](SetMaxPriceDeviation::authority) must be a signer
+    ///   and a MARKET_KEEPER in the given store.
+    /// - The [`store`](SetMaxPriceDeviation::store) must be an initialized [`Store`](states::Store)
+    ///   account owned by the store program.
+    /// - The [`token_map`](SetMaxPriceDeviation::token_map) must be an initialized token map account
+    ///   owned by the `store`.
+    /// - The given `token` must exist in the token map.
+    /// - The `max_price_deviation_bps` must not be greater than `10_000`.
+    #[access_control(internal::Authenticate::only_market_keeper(&ctx))]
+    pub fn set_max_price_deviation(
+        ctx: Context<SetMaxPriceDeviation>,
+        token: Pubkey,
+        max_price_deviation_bps: u16,
+    ) -> Result<()> {
+        instructions::unchecked_set_max_price_deviation(ctx, token, max_price_deviation_bps)
+    }
+
+    /// Initialize the [`LastOraclePrice`](states::LastOraclePrice) account of the given token.
+    ///
+    /// The account records the last price of the token set from the price feeds, so that the
+    /// price deviation can be validated without writing to the token map.
+    ///
+    /// # Accounts
+    /// [*See the documentation for the accounts*](InitializeLastOraclePrice).
+    ///
+    /// # Arguments
+    /// - `token`: The token to record the last price for.
+    ///
+    /// # Errors
+    /// - The [`authority`](InitializeLastOraclePrice::authority) must be a signer
+    ///   and a MARKET_KEEPER in the given store.
+    /// - The [`store`](InitializeLastOraclePrice::store) must be an initialized [`Store`](states::Store)
+    ///   account owned by the store program.
+    /// - The [`token_map`](InitializeLastOraclePrice::token_map) must be the token map of the store,
+    ///   and the `token` must be configured in it.
+    /// - The [`last_price`](InitializeLastOraclePrice::last_price) must be uninitialized and be
+    ///   the PDA derived from the store and the token.
+    #[access_control(internal::Authenticate::only_market_keeper(&ctx))]
+    pub fn initialize_last_oracle_price(
+        ctx: Context<InitializeLastOraclePrice>,
+        token: Pubkey,
+    ) -> Result<()> {
+        instructions::unchecked_initialize_last_oracle_price(ctx, &token)
+    }
+
+    /// Clear the last recorded price of the given token.
+    ///
+    /// This allows the next price from the price feed to be accepted without the deviation
+    /// check, e.g. after a legitimate price gap.
+    ///
+    /// # Accounts
+    /// [*See the documentation for the accounts*](ClearLastOraclePrice).
+    ///
+    /// # Arguments
+    /// - `token`: The token whose last recorded price will be cleared.
+    ///
+    /// # Errors
+    /// - The [`authority`](ClearLastOraclePrice::authority) must be a signer
+    ///   and the ADMIN of the given store.
+    /// - The [`store`](ClearLastOraclePrice::store) must be an initialized [`Store`](states::Store)
+    ///   account owned by the store program.
+    /// - The [`last_price`](ClearLastOraclePrice::last_price) must be the initialized
+    ///   [`LastOraclePrice`](states::LastOraclePrice) account of the given `token`.
+    #[access_control(internal::Authenticate::only_admin(&ctx))]
+    pub fn clear_last_oracle_price(
+        ctx: Context<ClearLastOraclePrice>,
+        token: Pubkey,
+    ) -> Result<()> {
+        instructions::unchecked_clear_last_oracle_price(ctx, token)
+    }
+
     /// Return whether the token config is enabled.
     ///
     /// # Accounts
@@ -936,6 +1460,23 @@ pub mod gmsol_store {
         instructions::is_token_config_enabled(ctx, &token)
     }
 
+    /// Get the tokens whose configs are disabled in the token map.
+    ///
+    /// # Accounts
+    /// [*See the documentation for the accounts*](ReadTokenMap).
+    ///
+    /// # Errors
+    /// - The [`token_map`](ReadTokenMap::token_map) must be an initialized token map account
+    ///   owned by the store program.
+    /// - The number of disabled tokens must fit in the return data, otherwise returns
+    ///   [`CoreError::ExceedMaxLengthLimit`].
+    ///
+    /// # Returns
+    /// Returns the addresses of the disabled tokens.
+    pub fn get_disabled_tokens(ctx: Context<ReadTokenMap>) -> Result<Vec<Pubkey>> {
+        instructions::get_disabled_tokens(ctx)
+    }
+
     /// Get the expected provider of the given token.
     ///
     /// # Accounts
@@ -1015,6 +1556,45 @@ pub mod gmsol_store {
         )
     }
 
+    /// Get the timestamp of the price reported by the given feed, with the configured timestamp
+    /// adjustment of the given token for the provider applied.
+    ///
+    /// This is the timestamp used in the oracle timestamp validation, which helps diagnose
+    /// [`CoreError::OracleTimestampsAreLargerThanRequired`] and
+    /// [`CoreError::OracleTimestampsAreSmallerThanRequired`] failures.
+    ///
+    /// # Accounts
+    /// [*See the documentation for the accounts*](ReadTokenFeed).
+    ///
+    /// # Arguments
+    /// - `token`: The address of the token to query for.
+    /// - `provider`: The index of provider to query for. Must be a valid index defined in
+    ///   [`PriceProviderKind`].
+    ///
+    /// # Errors
+    /// - The [`token_map`](ReadTokenFeed::token_map) must be an initialized token map account
+    ///   owned by the `store`.
+    /// - The given `token` must exist in the token map.
+    /// - The `provider` must be a valid index defined in [`PriceProviderKind`], otherwise
+    ///   returns [`CoreError::InvalidProviderKindIndex`].
+    /// - The feed account must be provided as the first remaining account, and must be a valid
+    ///   feed of the `provider`, which must be the expected provider of the token.
+    ///
+    /// # Returns
+    /// Returns the adjusted timestamp of the price reported by the feed.
+    pub fn get_adjusted_timestamp<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ReadTokenFeed<'info>>,
+        token: Pubkey,
+        provider: u8,
+    ) -> Result<i64> {
+        instructions::get_adjusted_timestamp(
+            ctx,
+            &token,
+            &PriceProviderKind::try_from(provider)
+                .map_err(|_| CoreError::InvalidProviderKindIndex)?,
+        )
+    }
+
     /// Get the name of the token.
     ///
     /// # Accounts
@@ -1072,6 +1652,28 @@ pub mod gmsol_store {
         instructions::token_precision(ctx, &token)
     }
 
+    /// Get the full config of the token.
+    ///
+    /// This is the read counterpart of [`push_to_token_map`], returning the name, flags,
+    /// decimals, precision, expected provider and all the configured feeds in one call.
+    ///
+    /// # Accounts
+    /// [*See the documentation for the accounts*](ReadTokenMap).
+    ///
+    /// # Arguments
+    /// - `token`: The address of the token to query for.
+    ///
+    /// # Errors
+    /// - The [`token_map`](ReadTokenMap::token_map) must be an initialized token map account
+    ///   owned by the store program.
+    /// - The given `token` must exist in the token map.
+    ///
+    /// # Returns
+    /// Returns the config of the given token.
+    pub fn get_token_config(ctx: Context<ReadTokenMap>, token: Pubkey) -> Result<TokenConfigInfo> {
+        instructions::get_token_config(ctx, &token)
+    }
+
     // ===========================================
     //              Oracle Management
     // ===========================================
@@ -1107,12 +1709,53 @@ pub mod gmsol_store {
     /// - The [`store`](ClearAllPrices::store) must be an initialized store account owned by the
     ///   store program.
     /// - The [`oracle`](ClearAllPrices::oracle) must be an initialized oracle account owned by
-    ///   the given store.
+    ///   the given store. It must not be decommissioned.
     #[access_control(internal::Authenticate::only_oracle_controller(&ctx))]
     pub fn clear_all_prices(ctx: Context<ClearAllPrices>) -> Result<()> {
         instructions::unchecked_clear_all_prices(ctx)
     }
 
+    /// Replace an existing oracle account with a new one.
+    ///
+    /// The authority of the old oracle is copied to the new oracle, and the old oracle is marked
+    /// as decommissioned, so prices can no longer be cleared or set with it.
+    ///
+    /// # Accounts
+    /// *[See the documentation for the accounts.](RotateOracle)*
+    ///
+    /// # Errors
+    /// - The [`authority`](RotateOracle::authority) must be a signer and the current admin of the
+    ///   store.
+    /// - The [`store`](RotateOracle::store) must be an initialized store account owned by the
+    ///   store program.
+    /// - The [`old_oracle`](RotateOracle::old_oracle) must be an initialized oracle account owned
+    ///   by the given store. It must be in the cleared state and not decommissioned.
+    /// - The [`new_oracle`](RotateOracle::new_oracle) must be an initialized oracle account owned
+    ///   by the given store and different from the `old_oracle`. It must be in the cleared state
+    ///   and not decommissioned.
+    #[access_control(internal::Authenticate::only_admin(&ctx))]
+    pub fn rotate_oracle(ctx: Context<RotateOracle>) -> Result<()> {
+        instructions::unchecked_rotate_oracle(ctx)
+    }
+
+    /// Close a decommissioned oracle account and reclaim its rent.
+    ///
+    /// # Accounts
+    /// *[See the documentation for the accounts.](CloseDecommissionedOracle)*
+    ///
+    /// # Errors
+    /// - The [`authority`](CloseDecommissionedOracle::authority) must be a signer and the current
+    ///   admin of the store.
+    /// - The [`store`](CloseDecommissionedOracle::store) must be an initialized store account owned
+    ///   by the store program.
+    /// - The [`oracle`](CloseDecommissionedOracle::oracle) must be an initialized oracle account
+    ///   owned by the given store. It must have been decommissioned by
+    ///   [`rotate_oracle`](gmsol_store::rotate_oracle).
+    #[access_control(internal::Authenticate::only_admin(&ctx))]
+    pub fn close_decommissioned_oracle(ctx: Context<CloseDecommissionedOracle>) -> Result<()> {
+        instructions::unchecked_close_decommissioned_oracle(ctx)
+    }
+
     /// Set prices from the provided price feeds.
     ///
     /// This instruction updates token prices in the oracle account using data from configured price feeds.
@@ -1132,12 +1775,22 @@ pub mod gmsol_store {
     /// - The [`store`](SetPricesFromPriceFeed::store) must be an initialized store account owned by
     ///   the store program.
     /// - The [`oracle`](SetPricesFromPriceFeed::oracle) must be an initialized oracle account owned
-    ///   by the given store. It must not have any prices set and be in the cleared state.
+    ///   by the given store. It must not have any prices set, be in the cleared state and not be
+    ///   decommissioned.
     /// - The [`token_map`](SetPricesFromPriceFeed::token_map) must be an initialized token map account
     ///   that is owned and authorized by the store.
     /// - The number of tokens provided cannot exceed [`MAX_TOKENS`](crate::states::oracle::price_map::PriceMap::MAX_TOKENS).
     /// - Each token in `tokens` must be configured and enabled in the token map.
     /// - For each token, there must be a valid corresponding price feed account included in the remaining accounts.
+    /// - Each remaining account after the price feed accounts must be either a
+    ///   [`PriceAdjustmentSchedule`](states::PriceAdjustmentSchedule) account or a writable
+    ///   [`LastOraclePrice`](states::LastOraclePrice) account owned by the store. The price band
+    ///   of the token of a schedule is adjusted by the factor of the current UTC hour.
+    /// - For each token with a price band configured, the price must be within the (adjusted) band.
+    /// - For each token with a max price deviation configured, its `LastOraclePrice` account must
+    ///   be provided, and the price must not deviate from the last recorded price by more than the
+    ///   threshold if the last price was recorded within the `oracle_price_deviation_window` of
+    ///   the store. The price is recorded in each provided `LastOraclePrice` account.
     #[access_control(internal::Authenticate::only_oracle_controller(&ctx))]
     pub fn set_prices_from_price_feed<'info>(
         ctx: Context<'_, '_, 'info, 'info, SetPricesFromPriceFeed<'info>>,
@@ -1146,9 +1799,72 @@ pub mod gmsol_store {
         instructions::unchecked_set_prices_from_price_feed(ctx, tokens)
     }
 
-    /// Initialize a custom price feed account.
+    /// Initialize the price adjustment schedule of the given token.
     ///
-    /// Creates a new price feed account that can be used to provide custom price data for a token.
+    /// The schedule holds a factor for each UTC hour. When the schedule account is provided to
+    /// [`set_prices_from_price_feed`], the lower bound of the price band of the token is divided
+    /// by, and the upper bound multiplied by, the factor of the current hour, allowing wider
+    /// confidence intervals during high-volatility hours.
+    ///
+    /// # Accounts
+    /// [*See the documentation for the accounts.*](InitializePriceAdjustmentSchedule)
+    ///
+    /// # Arguments
+    /// - `token`: The token to adjust the price band for.
+    /// - `factors`: The adjustment factor of each UTC hour, where `MARKET_USD_UNIT` means no
+    ///   adjustment.
+    ///
+    /// # Errors
+    /// - The [`authority`](InitializePriceAdjustmentSchedule::authority) must be a signer and be a
+    ///   MARKET_KEEPER in the store.
+    /// - The [`store`](InitializePriceAdjustmentSchedule::store) must be an initialized store account.
+    /// - The [`token_map`](InitializePriceAdjustmentSchedule::token_map) must be the token map of
+    ///   the store, and the `token` must be configured in it.
+    /// - The [`schedule`](InitializePriceAdjustmentSchedule::schedule) must be uninitialized and be
+    ///   the PDA derived from the store and the token.
+    /// - Each factor must be non-zero and must not exceed
+    ///   [`MAX_PRICE_ADJUSTMENT_FACTOR`](states::oracle::adjustment::MAX_PRICE_ADJUSTMENT_FACTOR).
+    #[access_control(internal::Authenticate::only_market_keeper(&ctx))]
+    pub fn initialize_price_adjustment_schedule(
+        ctx: Context<InitializePriceAdjustmentSchedule>,
+        token: Pubkey,
+        factors: [u128; 24],
+    ) -> Result<()> {
+        instructions::unchecked_initialize_price_adjustment_schedule(ctx, &token, &factors)
+    }
+
+    /// Update the factor of the given UTC hour in the price adjustment schedule of the token.
+    ///
+    /// # Accounts
+    /// [*See the documentation for the accounts.*](UpdatePriceAdjustmentSchedule)
+    ///
+    /// # Arguments
+    /// - `token`: The token of the schedule.
+    /// - `hour`: The UTC hour to update, must be less than 24.
+    /// - `factor`: The new adjustment factor of the hour.
+    ///
+    /// # Errors
+    /// - The [`authority`](UpdatePriceAdjustmentSchedule::authority) must be a signer and be a
+    ///   MARKET_KEEPER in the store.
+    /// - The [`store`](UpdatePriceAdjustmentSchedule::store) must be an initialized store account.
+    /// - The [`schedule`](UpdatePriceAdjustmentSchedule::schedule) must be the initialized price
+    ///   adjustment schedule of the token.
+    /// - The `hour` must be less than 24.
+    /// - The `factor` must be non-zero and must not exceed
+    ///   [`MAX_PRICE_ADJUSTMENT_FACTOR`](states::oracle::adjustment::MAX_PRICE_ADJUSTMENT_FACTOR).
+    #[access_control(internal::Authenticate::only_market_keeper(&ctx))]
+    pub fn update_price_adjustment_schedule(
+        ctx: Context<UpdatePriceAdjustmentSchedule>,
+        token: Pubkey,
+        hour: u8,
+        factor: u128,
+    ) -> Result<()> {
+        instructions::unchecked_update_price_adjustment_schedule(ctx, &token, hour, factor)
+    }
+
+    /// Initialize a custom price feed account.
+    ///
+    /// Creates a new price feed account that can be used to provide custom price data for a token.
     /// The price feed is owned by the store and can only be updated by ORDER_KEEPERs.
     ///
     /// # Accounts
@@ -1202,7 +1918,9 @@ pub mod gmsol_store {
     /// - The [`price_feed`](UpdatePriceFeedWithChainlink::price_feed) must be initialized, owned by
     ///   the store, and authorized for the `authority`.
     /// - The [`chainlink`](UpdatePriceFeedWithChainlink::chainlink) program ID must be trusted in the
-    ///   definition of the [`ChainlinkDataStreamsInterface`](gmsol_chainlink_datastreams::interface::ChainlinkDataStreamsInterface).
+    ///   definition of the [`ChainlinkDataStreamsInterface`](gmsol_chainlink_datastreams::interface::ChainlinkDataStreamsInterface),
+    ///   and must be one of the trusted Chainlink verifiers of the store
+    ///   (see [`set_trusted_chainlink_verifier`]).
     /// - The price feed must be configured to use [`ChainlinkDataStreams`](PriceProviderKind::ChainlinkDataStreams)
     ///   as its provider.
     /// - The `signed_report` must be:
@@ -1220,6 +1938,27 @@ pub mod gmsol_store {
         instructions::unchecked_update_price_feed_with_chainlink(ctx, compressed_report)
     }
 
+    /// Get the status of a custom price feed.
+    ///
+    /// Returns the latest price of the feed, its age and whether it is considered stale,
+    /// i.e. would be rejected by the price validation under the current
+    /// [`OracleMaxAge`](states::AmountKey::OracleMaxAge) and the timestamp adjustment
+    /// configured for the token.
+    ///
+    /// # Accounts
+    /// *[See the documentation for the accounts.](GetPriceFeedStatus)*
+    ///
+    /// # Errors
+    /// - The [`store`](GetPriceFeedStatus::store) must be an initialized store account.
+    /// - The [`token_map`](GetPriceFeedStatus::token_map) must be the token map of the `store`.
+    /// - The [`price_feed`](GetPriceFeedStatus::price_feed) must be an initialized price feed
+    ///   account owned by the `store`.
+    /// - The token of the price feed must be configured in the token map, with a feed config
+    ///   for the provider of the price feed.
+    pub fn get_price_feed_status(ctx: Context<GetPriceFeedStatus>) -> Result<PriceFeedStatus> {
+        instructions::get_price_feed_status(ctx)
+    }
+
     // ===========================================
     //              Market Management
     // ===========================================
@@ -1258,6 +1997,36 @@ pub mod gmsol_store {
         instructions::unchecked_initialize_market(ctx, index_token_mint, &name, enable)
     }
 
+    /// Initialize a [`Market`](states::Market) account and set the price caps of the given tokens
+    /// in a single instruction.
+    ///
+    /// The price caps are applied to the feed configs of the expected providers of the tokens,
+    /// keeping the feed addresses and timestamp adjustments unchanged.
+    ///
+    /// # Accounts
+    /// [*See the documentation for the accounts.*](InitializeMarketWithPriceCaps)
+    ///
+    /// # Arguments
+    /// - `params`: The market initialization params and the price caps to set.
+    ///
+    /// # Errors
+    /// - The [`authority`](InitializeMarketWithPriceCaps::authority) must be a signer and have
+    ///   the MARKET_KEEPER role in the store.
+    /// - The [`token_map`](InitializeMarketWithPriceCaps::token_map) must be initialized, writable
+    ///   and owned and authorized by the `store`.
+    /// - All other accounts must satisfy the same requirements as in
+    ///   [`initialize_market`](gmsol_store::initialize_market).
+    /// - Each token in the price caps must exist in the token map and have a feed config for its
+    ///   expected provider.
+    /// - The min price of each cap must not be greater than the max price unless either is `0`.
+    #[access_control(internal::Authenticate::only_market_keeper(&ctx))]
+    pub fn initialize_market_with_price_caps(
+        ctx: Context<InitializeMarketWithPriceCaps>,
+        params: MarketWithPriceCapsParams,
+    ) -> Result<()> {
+        instructions::unchecked_initialize_market_with_price_caps(ctx, &params)
+    }
+
     /// Enable or disable the given market.
     ///
     /// This instruction allows a MARKET_KEEPER to toggle whether a market is enabled or disabled.
@@ -1278,6 +2047,80 @@ pub mod gmsol_store {
         instructions::unchecked_toggle_market(ctx, enable)
     }
 
+    /// Set the override token map of the given market.
+    ///
+    /// The execution of the market will use the override token map instead of the token
+    /// map of the store. If the `token_map` account is not provided, the override is removed
+    /// and the market falls back to the token map of the store.
+    ///
+    /// # Accounts
+    /// [*See the documentation for the accounts.*](SetMarketTokenMap)
+    ///
+    /// # Errors
+    /// - The [`authority`](SetMarketTokenMap::authority) must be a signer and have the
+    ///   MARKET_KEEPER role in the store.
+    /// - The [`store`](SetMarketTokenMap::store) must be initialized and owned by this program.
+    /// - The [`market`](SetMarketTokenMap::market) must be initialized and owned by the store.
+    /// - The [`token_map`](SetMarketTokenMap::token_map), if provided, must be an initialized
+    ///   token map account owned by the store, in which the index, long and short tokens of the
+    ///   market are configured and enabled.
+    #[access_control(internal::Authenticate::only_market_keeper(&ctx))]
+    pub fn set_market_token_map(ctx: Context<SetMarketTokenMap>) -> Result<()> {
+        instructions::unchecked_set_market_token_map(ctx)
+    }
+
+    /// Set the name of the given market.
+    ///
+    /// # Accounts
+    /// [*See the documentation for the accounts.*](SetMarketName)
+    ///
+    /// # Arguments
+    /// - `name`: The new name of the market.
+    ///
+    /// # Errors
+    /// - The [`authority`](SetMarketName::authority) must be a signer and have the
+    ///   MARKET_KEEPER role in the store.
+    /// - The [`store`](SetMarketName::store) must be initialized and owned by this program.
+    /// - The [`market`](SetMarketName::market) must be initialized and owned by the store.
+    /// - The length of the `name` must be less than the max name length of the market (64 bytes).
+    #[access_control(internal::Authenticate::only_market_keeper(&ctx))]
+    pub fn set_market_name(ctx: Context<SetMarketName>, name: String) -> Result<()> {
+        instructions::unchecked_set_market_name(ctx, &name)
+    }
+
+    /// Close the given market.
+    ///
+    /// This instruction allows a MARKET_KEEPER to decommission a market by closing the market
+    /// account and, if provided, the market token vault, returning the rent to the `receiver`.
+    ///
+    /// # Accounts
+    /// [*See the documentation for the accounts.*](CloseMarket)
+    ///
+    /// # Errors
+    /// - The [`authority`](CloseMarket::authority) must be a signer and have the
+    ///   MARKET_KEEPER role in the store.
+    /// - The [`store`](CloseMarket::store) must be initialized and owned by this program.
+    /// - The [`market`](CloseMarket::market) must be initialized and owned by the store.
+    /// - The [`market_token_mint`](CloseMarket::market_token_mint) must be the market token of the market.
+    /// - The [`market_token_vault`](CloseMarket::market_token_vault), if provided, must be the
+    ///   market vault of the market token.
+    /// - The market must be disabled, all of its pools must be drained, the supply of the market
+    ///   token must be zero, the market token vault must be empty and the market must have no
+    ///   pending deposits, withdrawals or orders. Otherwise, this instruction fails with
+    ///   [`CoreError::PreconditionsAreNotMet`].
+    ///
+    /// # Notes
+    /// - Residual balances are not handled by this instruction. Once a market has been used, its
+    ///   pools (e.g., the swap impact pool or the claimable fee pool) usually keep some dust that
+    ///   cannot be withdrawn, so in practice only markets that have never been used can be closed.
+    /// - The long token and short token vaults are shared by the markets of the store, so they are
+    ///   not closed by this instruction.
+    /// - The market token mint cannot be closed, so the market cannot be re-initialized.
+    #[access_control(internal::Authenticate::only_market_keeper(&ctx))]
+    pub fn close_market(ctx: Context<CloseMarket>) -> Result<()> {
+        instructions::unchecked_close_market(ctx)
+    }
+
     /// Transfer tokens into the market and record the amounts in its balance.
     ///
     /// This instruction allows a MARKET_KEEPER to transfer tokens from a source account into one of
@@ -1308,6 +2151,81 @@ pub mod gmsol_store {
         instructions::unchecked_market_transfer_in(ctx, amount)
     }
 
+    /// Withdraw tokens from a market vault in emergency mode.
+    ///
+    /// This instruction allows the ADMIN to transfer tokens out of one of the market vault
+    /// accounts, updating the market's internal balance tracking. An
+    /// [`EmergencyWithdrawal`](crate::events::EmergencyWithdrawal) event is emitted.
+    ///
+    /// # Accounts
+    /// [*See the documentation for the accounts.*](EmergencyVaultWithdraw)
+    ///
+    /// # Arguments
+    /// - `amount`: The amount of tokens to withdraw from the market vault.
+    ///
+    /// # Errors
+    /// - The [`authority`](EmergencyVaultWithdraw::authority) must be a signer and the current
+    ///   admin of the store.
+    /// - The [`store`](EmergencyVaultWithdraw::store) must be an initialized store account owned
+    ///   by this program, with the emergency mode enabled.
+    /// - The [`market`](EmergencyVaultWithdraw::market) must be an initialized market account owned
+    ///   by the store.
+    /// - The [`vault`](EmergencyVaultWithdraw::vault) must be an initialized and valid market vault
+    ///   token account owned by the store, and the token must be one of the market's pool tokens.
+    /// - The [`destination`](EmergencyVaultWithdraw::destination) must be an initialized token
+    ///   account of the same mint and cannot be the vault.
+    /// - The `amount` must be non-zero and must not exceed the market's balance of the token.
+    #[access_control(internal::Authenticate::only_admin(&ctx))]
+    pub fn emergency_vault_withdraw(
+        ctx: Context<EmergencyVaultWithdraw>,
+        amount: u64,
+    ) -> Result<()> {
+        instructions::unchecked_emergency_vault_withdraw(ctx, amount)
+    }
+
+    /// Zero-fill all the reserved ranges of a market account.
+    ///
+    /// Reserved ranges of existing market accounts may contain garbage left by older program
+    /// versions. This instruction zero-fills them so that they can be safely re-used by new
+    /// fields. It is safe since the reserved ranges are never read by the program. A
+    /// [`MarketStateCompressed`](crate::events::MarketStateCompressed) event is emitted.
+    ///
+    /// # Accounts
+    /// [*See the documentation for the accounts.*](CompressPoolState)
+    ///
+    /// # Errors
+    /// - The [`authority`](CompressPoolState::authority) must be a signer and the current
+    ///   admin of the store.
+    /// - The [`store`](CompressPoolState::store) must be an initialized store account owned
+    ///   by this program.
+    /// - The [`market`](CompressPoolState::market) must be an initialized market account owned
+    ///   by the store.
+    #[access_control(internal::Authenticate::only_admin(&ctx))]
+    pub fn compress_pool_state(ctx: Context<CompressPoolState>) -> Result<()> {
+        instructions::unchecked_compress_pool_state(ctx)
+    }
+
+    /// Export the full state of a market.
+    ///
+    /// A [`MarketStateExport`](crate::events::MarketStateExport) event is emitted, containing the
+    /// account data of the market (without the discriminator) and its BLAKE3 hash, so that
+    /// off-chain systems can sync and verify the market state. The hash is also returned.
+    ///
+    /// # Accounts
+    /// [*See the documentation for the accounts.*](ExportMarketState)
+    ///
+    /// # Errors
+    /// - The [`authority`](ExportMarketState::authority) must be a signer and have the
+    ///   ORACLE_CONTROLLER role in the store.
+    /// - The [`store`](ExportMarketState::store) must be an initialized store account owned
+    ///   by this program.
+    /// - The [`market`](ExportMarketState::market) must be an initialized market account owned
+    ///   by the store.
+    #[access_control(internal::Authenticate::only_oracle_controller(&ctx))]
+    pub fn export_market_state(ctx: Context<ExportMarketState>) -> Result<[u8; 32]> {
+        instructions::unchecked_export_market_state(ctx)
+    }
+
     /// Update an item in the market config.
     ///
     /// This instruction allows a MARKET_KEEPER to update a single configuration value in the market's
@@ -1394,6 +2312,73 @@ pub mod gmsol_store {
         instructions::unchecked_update_market_config_with_buffer(ctx)
     }
 
+    /// Check every market config against its validity bounds.
+    ///
+    /// Returns the out-of-range configs, each with a human-readable description of the
+    /// problem, e.g., an exponent out of range or a factor too large. An empty result means
+    /// that the market config is safe to use.
+    ///
+    /// # Accounts
+    /// [*See the documentation for the accounts.*](ReadMarket)
+    ///
+    /// # Errors
+    /// - The [`market`](ReadMarket::market) account must be properly initialized.
+    pub fn validate_market_config(ctx: Context<ReadMarket>) -> Result<Vec<MarketConfigProblem>> {
+        instructions::validate_market_config(ctx)
+    }
+
+    /// Get the number of pending deposits, withdrawals and orders of the market.
+    ///
+    /// The limits of these numbers are configured by the
+    /// [`MaxPendingDeposits`](states::AmountKey::MaxPendingDeposits),
+    /// [`MaxPendingWithdrawals`](states::AmountKey::MaxPendingWithdrawals) and
+    /// [`MaxPendingOrders`](states::AmountKey::MaxPendingOrders) amounts of the store.
+    ///
+    /// # Accounts
+    /// [*See the documentation for the accounts.*](ReadMarket)
+    ///
+    /// # Errors
+    /// - The [`market`](ReadMarket::market) account must be properly initialized.
+    pub fn get_market_pending_counts(ctx: Context<ReadMarket>) -> Result<PendingCounts> {
+        instructions::get_market_pending_counts(ctx)
+    }
+
+    /// List the names of the supported market config keys.
+    ///
+    /// The names can be used as the `key` of [`update_market_config`] and the entries of the
+    /// market config buffer. Since the return data is limited in size, the names are returned
+    /// in pages: callers should keep advancing `start` by the number of names returned until
+    /// an empty list is returned.
+    ///
+    /// # Accounts
+    /// [*See the documentation for the accounts.*](ReadStore)
+    ///
+    /// # Arguments
+    /// - `start`: The index of the first key to return.
+    ///
+    /// # Errors
+    /// - The [`store`](ReadStore::store) must be initialized.
+    pub fn list_market_config_keys(ctx: Context<ReadStore>, start: u16) -> Result<Vec<String>> {
+        instructions::list_market_config_keys(ctx, start)
+    }
+
+    /// List the names of the supported market config flags.
+    ///
+    /// The names can be used as the `key` of [`update_market_config_flag`]. The names are
+    /// returned in pages in the same way as [`list_market_config_keys`].
+    ///
+    /// # Accounts
+    /// [*See the documentation for the accounts.*](ReadStore)
+    ///
+    /// # Arguments
+    /// - `start`: The index of the first flag to return.
+    ///
+    /// # Errors
+    /// - The [`store`](ReadStore::store) must be initialized.
+    pub fn list_market_config_flags(ctx: Context<ReadStore>, start: u16) -> Result<Vec<String>> {
+        instructions::list_market_config_flags(ctx, start)
+    }
+
     /// Calculate the current market status.
     ///
     /// This instruction calculates and returns the current status of a market, including metrics like
@@ -1423,6 +2408,140 @@ pub mod gmsol_store {
         instructions::get_market_status(ctx, &prices, maximize_pnl, maximize_pool_value)
     }
 
+    /// Estimate the expected value of providing liquidity to the market.
+    ///
+    /// The projection assumes that the current open interest and borrowing rates stay
+    /// unchanged during the time horizon, and that the current pending PnL of the traders
+    /// is realized against the pool.
+    ///
+    /// # Accounts
+    /// [*See the documentation for the accounts.*](ReadMarket)
+    ///
+    /// # Arguments
+    /// - `prices`: The current unit prices of tokens in the market, used for calculations.
+    /// - `trading_volume_usd`: The expected trading volume during the time horizon.
+    /// - `time_horizon_secs`: The time horizon of the projection in seconds.
+    ///
+    /// # Errors
+    /// - The [`market`](ReadMarket::market) account must be properly initialized.
+    /// - The provided prices must be non-zero.
+    /// - The `time_horizon_secs` must be positive.
+    /// - Any calculation errors.
+    pub fn get_market_expected_value(
+        ctx: Context<ReadMarket>,
+        prices: Prices<u128>,
+        trading_volume_usd: u128,
+        time_horizon_secs: u64,
+    ) -> Result<MarketExpectedValue> {
+        instructions::get_market_expected_value(ctx, &prices, trading_volume_usd, time_horizon_secs)
+    }
+
+    /// Simulate a swap in the market and return the fee breakdown.
+    ///
+    /// The swap is simulated with the same calculation as the execution of swaps, without
+    /// modifying the market.
+    ///
+    /// # Accounts
+    /// [*See the documentation for the accounts.*](ReadMarket)
+    ///
+    /// # Arguments
+    /// - `input_token`: The token to swap in.
+    /// - `output_token`: The token to swap out.
+    /// - `amount`: The amount of `input_token` to swap.
+    /// - `prices`: The current unit prices of tokens in the market, used for calculations.
+    ///
+    /// # Errors
+    /// - The [`market`](ReadMarket::market) account must be properly initialized.
+    /// - The `input_token` and `output_token` must be the two different collateral tokens of
+    ///   the market.
+    /// - The `amount` must be non-zero.
+    /// - The provided prices must be non-zero.
+    /// - Any calculation errors, including the validations of the swap.
+    pub fn get_market_swap_fees(
+        ctx: Context<ReadMarket>,
+        input_token: Pubkey,
+        output_token: Pubkey,
+        amount: u64,
+        prices: Prices<u128>,
+    ) -> Result<SwapFeeBreakdown> {
+        instructions::get_market_swap_fees(ctx, &input_token, &output_token, amount, &prices)
+    }
+
+    /// Simulate opening positions of increasing sizes in the market and return the
+    /// price impact sensitivity.
+    ///
+    /// The price impacts are calculated with the same formulas as the execution of
+    /// increase orders, without modifying the market. Note that positive impact values
+    /// in the returned [`PriceImpactSensitivity`] are against the trader.
+    ///
+    /// # Accounts
+    /// [*See the documentation for the accounts.*](GetMarketPriceImpactSensitivity)
+    ///
+    /// # Arguments
+    /// - `prices`: The current unit prices of tokens in the market, used for calculations.
+    /// - `direction`: Positive for long positions, negative for short positions.
+    ///
+    /// # Errors
+    /// - The [`market`](GetMarketPriceImpactSensitivity::market) account must be properly
+    ///   initialized.
+    /// - The [`oracle`](GetMarketPriceImpactSensitivity::oracle) must be an initialized oracle
+    ///   account of the same store as the `market`.
+    /// - The `direction` must be non-zero.
+    /// - The provided prices must be non-zero.
+    /// - Any calculation errors.
+    pub fn get_market_price_impact_sensitivity(
+        ctx: Context<GetMarketPriceImpactSensitivity>,
+        prices: Prices<u128>,
+        direction: i8,
+    ) -> Result<PriceImpactSensitivity> {
+        instructions::get_market_price_impact_sensitivity(ctx, &prices, direction)
+    }
+
+    /// Find the swap path with the highest output among the given markets.
+    ///
+    /// Every path of at most [`ArbitrageOpportunity::MAX_PATH_LENGTH`] markets from the
+    /// `input_token` to the `output_token` is simulated with the same calculation as the
+    /// execution of swaps, and the one with the highest output amount is returned. The
+    /// `input_token` and `output_token` can be the same to search for circular paths.
+    /// Markets that cannot perform a swap, e.g., due to insufficient liquidity, are skipped.
+    ///
+    /// A path is profitable if the returned `expected_profit_usd` is positive, i.e., the
+    /// price differential covers the swap fees and the price impact.
+    ///
+    /// # Accounts
+    /// [*See the documentation for the accounts.*](GetSwapArbitrageOpportunity)
+    ///
+    /// # Arguments
+    /// - `input_token`: The token to swap in.
+    /// - `output_token`: The token to swap out.
+    /// - `amount`: The amount of `input_token` to swap.
+    /// - `prices`: The current unit prices of tokens for each market, in the same order as
+    ///   the markets in the remaining accounts.
+    ///
+    /// # Errors
+    /// - The [`store`](GetSwapArbitrageOpportunity::store) must be an initialized store account.
+    /// - The number of `prices` must be non-zero and no more than
+    ///   [`ArbitrageOpportunity::MAX_MARKETS`].
+    /// - The remaining accounts must be enabled markets of the store, one for each of the `prices`.
+    /// - The `amount` must be non-zero.
+    /// - There must be at least one path from `input_token` to `output_token`.
+    /// - Any calculation errors.
+    pub fn get_swap_arbitrage_opportunity<'info>(
+        ctx: Context<'_, '_, 'info, 'info, GetSwapArbitrageOpportunity<'info>>,
+        input_token: Pubkey,
+        output_token: Pubkey,
+        amount: u64,
+        prices: Vec<Prices<u128>>,
+    ) -> Result<ArbitrageOpportunity> {
+        instructions::get_swap_arbitrage_opportunity(
+            ctx,
+            &input_token,
+            &output_token,
+            amount,
+            &prices,
+        )
+    }
+
     /// Get the current market token price based on the provided token prices and PnL factor.
     ///
     /// This instruction calculates and returns the current price of the market token, taking into
@@ -1460,6 +2579,218 @@ pub mod gmsol_store {
         )
     }
 
+    /// Get the underlying tokens backing one unit of market token.
+    ///
+    /// Returns the amounts of long token and short token, and their value (without pnl),
+    /// backing one unit (`10^9` base units) of market token, calculated from the pool
+    /// balances and the market token supply. For a pure market, the whole backing is
+    /// reported as `long_amount`.
+    ///
+    /// # Accounts
+    /// [*See the documentation for the accounts.*](ReadMarketWithToken)
+    ///
+    /// # Arguments
+    /// - `prices`: The current unit prices of tokens in the market, used for calculations.
+    ///
+    /// # Errors
+    /// - The [`market`](ReadMarketWithToken::market) must be an initialized market account.
+    /// - The provided prices must be non-zero.
+    /// - Any calculation errors.
+    pub fn get_market_token_backing(
+        ctx: Context<ReadMarketWithToken>,
+        prices: Prices<u128>,
+    ) -> Result<MarketTokenBacking> {
+        instructions::get_market_token_backing(ctx, &prices)
+    }
+
+    /// Get the value of the liquidity pool and the market token with the prices in the oracle.
+    ///
+    /// Unlike [`get_market_token_price`](gmsol_store::get_market_token_price), the prices are
+    /// read from the given oracle account, which must have been set by
+    /// [`set_prices_from_price_feed`](gmsol_store::set_prices_from_price_feed). The pool value
+    /// and the NAV per token are calculated with the [`MaxForTrader`](gmsol_model::PnlFactorKind::MaxForTrader)
+    /// PnL factor.
+    ///
+    /// # Accounts
+    /// [*See the documentation for the accounts.*](GetLpValuePerToken)
+    ///
+    /// # Arguments
+    /// - `maximize`: If true, uses the maximum possible values in calculations.
+    ///   If false, uses minimum values.
+    ///
+    /// # Errors
+    /// - The [`market`](GetLpValuePerToken::market) must be an initialized market account
+    ///   with the given market token.
+    /// - The [`oracle`](GetLpValuePerToken::oracle) must be an initialized oracle account
+    ///   owned by the store of the market, and it must have the prices of all the tokens
+    ///   of the market. Otherwise, returns [`CoreError::MissingOraclePrice`].
+    /// - The pool value must not be negative.
+    /// - Any calculation errors.
+    pub fn get_lp_value_per_token(
+        ctx: Context<GetLpValuePerToken>,
+        maximize: bool,
+    ) -> Result<LpValueInfo> {
+        instructions::get_lp_value_per_token(ctx, maximize)
+    }
+
+    /// Verify the market token supply against the market balances.
+    ///
+    /// The value of the market balances owned by the liquidity providers (the recorded token
+    /// balances minus the claimable fees, the swap impact pool and the position collaterals) is
+    /// divided by the NAV per token derived from the liquidity pool to estimate the supply.
+    /// The estimate matches the current supply unless the market balances and the pools have
+    /// diverged, e.g. tokens have been transferred into the market without minting market tokens.
+    ///
+    /// If the difference exceeds the [`SupplyToleranceFactor`](crate::states::FactorKey::SupplyToleranceFactor)
+    /// of the store relative to the current supply, the result is marked as inconsistent and a
+    /// [`MarketSupplyDiscrepancy`](crate::events::MarketSupplyDiscrepancy) event is emitted.
+    ///
+    /// # Accounts
+    /// [*See the documentation for the accounts.*](VerifyMarketTokenSupply)
+    ///
+    /// # Arguments
+    /// - `prices`: The current unit prices of tokens in the market, used for calculations.
+    ///
+    /// # Errors
+    /// - The [`store`](VerifyMarketTokenSupply::store) must be an initialized store account.
+    /// - The [`market`](VerifyMarketTokenSupply::market) must be an initialized market account
+    ///   owned by the store, and its market token must be the
+    ///   [`market_token`](VerifyMarketTokenSupply::market_token).
+    /// - The provided prices must be non-zero.
+    /// - Any calculation errors.
+    pub fn verify_market_token_supply(
+        ctx: Context<VerifyMarketTokenSupply>,
+        prices: Prices<u128>,
+    ) -> Result<SupplyVerification> {
+        instructions::verify_market_token_supply(ctx, &prices)
+    }
+
+    /// Calculate the health metrics of a position.
+    ///
+    /// This instruction calculates and returns the size, collateral, PnL, pending fees, net value,
+    /// leverage and whether the position is liquidatable, using the same model code as the
+    /// execution.
+    ///
+    /// # Accounts
+    /// [*See the documentation for the accounts.*](ReadPosition)
+    ///
+    /// # Arguments
+    /// - `prices`: The current unit prices of tokens in the market, used for calculations.
+    ///
+    /// # Errors
+    /// - The [`market`](ReadPosition::market) must be an initialized market account.
+    /// - The [`position`](ReadPosition::position) must be an initialized position account of
+    ///   the `market`.
+    /// - The provided prices must be non-zero.
+    /// - Any calculation errors.
+    pub fn get_position_info(
+        ctx: Context<ReadPosition>,
+        prices: Prices<u128>,
+    ) -> Result<PositionInfo> {
+        instructions::get_position_info(ctx, &prices)
+    }
+
+    /// Estimate the size delta to auto-deleverage for a position.
+    ///
+    /// This instruction calculates the minimum `size_delta_in_usd` to pass to
+    /// [`auto_deleverage`] for the position to restore the pnl factor of its side to the
+    /// min pnl factor after ADL, without over-deleveraging.
+    ///
+    /// # Accounts
+    /// [*See the documentation for the accounts.*](ReadPosition)
+    ///
+    /// # Arguments
+    /// - `prices`: The current unit prices of tokens in the market, used for calculations.
+    ///
+    /// # Errors
+    /// - The [`market`](ReadPosition::market) must be an initialized market account.
+    /// - The [`position`](ReadPosition::position) must be an initialized position account of
+    ///   the `market`.
+    /// - The provided prices must be non-zero.
+    /// - Any calculation errors.
+    pub fn estimate_adl_size_delta(
+        ctx: Context<ReadPosition>,
+        prices: Prices<u128>,
+    ) -> Result<AdlEstimate> {
+        instructions::estimate_adl_size_delta(ctx, &prices)
+    }
+
+    /// Estimate the impact on the market of liquidating a position.
+    ///
+    /// This instruction calculates the price impact, the remaining collateral and the
+    /// liquidation fee of closing the whole position with the same model code as the
+    /// liquidation, and whether the pool would be worse-off than its current state.
+    ///
+    /// # Accounts
+    /// [*See the documentation for the accounts.*](ReadPosition)
+    ///
+    /// # Arguments
+    /// - `prices`: The current unit prices of tokens in the market, used for calculations.
+    ///
+    /// # Errors
+    /// - The [`market`](ReadPosition::market) must be an initialized market account.
+    /// - The [`position`](ReadPosition::position) must be an initialized position account of
+    ///   the `market`.
+    /// - The provided prices must be non-zero.
+    /// - Any calculation errors.
+    pub fn get_liquidation_impact(
+        ctx: Context<ReadPosition>,
+        prices: Prices<u128>,
+    ) -> Result<LiquidationImpact> {
+        instructions::get_liquidation_impact(ctx, &prices)
+    }
+
+    /// Calculate the collateral coverage of a position.
+    ///
+    /// This instruction calculates the remaining collateral value, the maintenance and
+    /// initial margins, the coverage ratio against the maintenance margin, and the index
+    /// token price at which the position is margin called, using the same model code as
+    /// the liquidation check.
+    ///
+    /// # Accounts
+    /// [*See the documentation for the accounts.*](ReadPosition)
+    ///
+    /// # Arguments
+    /// - `prices`: The current unit prices of tokens in the market, used for calculations.
+    ///
+    /// # Errors
+    /// - The [`market`](ReadPosition::market) must be an initialized market account.
+    /// - The [`position`](ReadPosition::position) must be an initialized position account of
+    ///   the `market`.
+    /// - The provided prices must be non-zero.
+    /// - Any calculation errors.
+    pub fn get_collateral_coverage_ratio(
+        ctx: Context<ReadPosition>,
+        prices: Prices<u128>,
+    ) -> Result<CollateralCoverage> {
+        instructions::get_collateral_coverage_ratio(ctx, &prices)
+    }
+
+    /// Calculate the break-even price of a position.
+    ///
+    /// This instruction calculates the entry price of the position and the index token
+    /// prices at which its PnL offsets the accumulated fees (opening fee, pending borrowing
+    /// and funding fees) and additionally the projected closing fee.
+    ///
+    /// # Accounts
+    /// [*See the documentation for the accounts.*](ReadPosition)
+    ///
+    /// # Arguments
+    /// - `prices`: The current unit prices of tokens in the market, used for calculations.
+    ///
+    /// # Errors
+    /// - The [`market`](ReadPosition::market) must be an initialized market account.
+    /// - The [`position`](ReadPosition::position) must be an initialized, non-empty
+    ///   position account of the `market`.
+    /// - The provided prices must be non-zero.
+    /// - Any calculation errors.
+    pub fn get_position_break_even_price(
+        ctx: Context<ReadPosition>,
+        prices: Prices<u128>,
+    ) -> Result<BreakEvenPrice> {
+        instructions::get_position_break_even_price(ctx, &prices)
+    }
+
     /// Initialize a market config buffer account.
     ///
     /// This instruction creates a new market config buffer account that can be used to stage market
@@ -1557,6 +2888,92 @@ pub mod gmsol_store {
         instructions::push_to_market_config_buffer(ctx, new_configs)
     }
 
+    /// Propose changes to the fee parameters of the given market.
+    ///
+    /// This instruction allows a MARKET_KEEPER to create a [`FeeStructureProposal`](states::FeeStructureProposal)
+    /// account with the given market config changes. Other MARKET_KEEPERs can then vote on it
+    /// until the voting ends.
+    ///
+    /// # Accounts
+    /// [*See the documentation for the accounts.*](ProposeFeeStructureChange)
+    ///
+    /// # Arguments
+    /// - `nonce`: The nonce used to derive the proposal address.
+    /// - `changes`: The market config changes to propose. Each item consists of a string key
+    ///   and a factor value.
+    /// - `voting_duration_secs`: The duration of the voting in seconds.
+    ///
+    /// # Errors
+    /// - The [`authority`](ProposeFeeStructureChange::authority) must be a signer and be a
+    ///   MARKET_KEEPER in the store.
+    /// - The [`store`](ProposeFeeStructureChange::store) must be an initialized store account.
+    /// - The [`market`](ProposeFeeStructureChange::market) must be an initialized market account
+    ///   owned by the store.
+    /// - The [`proposal`](ProposeFeeStructureChange::proposal) must be uninitialized and be the
+    ///   PDA derived from the store, the market token and the `nonce`.
+    /// - The `changes` must not be empty and must not exceed
+    ///   [`MAX_FEE_STRUCTURE_CHANGES`](states::market::proposal::MAX_FEE_STRUCTURE_CHANGES) items.
+    /// - The keys in `changes` must be valid [`MarketConfigKey`](states::market::config::MarketConfigKey).
+    /// - The `voting_duration_secs` must not be zero.
+    #[access_control(internal::Authenticate::only_market_keeper(&ctx))]
+    pub fn propose_fee_structure_change(
+        ctx: Context<ProposeFeeStructureChange>,
+        nonce: [u8; 32],
+        changes: Vec<EntryArgs>,
+        voting_duration_secs: u32,
+    ) -> Result<()> {
+        instructions::unchecked_propose_fee_structure_change(
+            ctx,
+            nonce,
+            changes,
+            voting_duration_secs,
+        )
+    }
+
+    /// Vote for or against a fee structure proposal.
+    ///
+    /// Each MARKET_KEEPER can only vote once on a proposal, which is enforced by creating a
+    /// [`FeeStructureVote`](states::FeeStructureVote) account for the voter.
+    ///
+    /// # Accounts
+    /// [*See the documentation for the accounts.*](VoteOnFeeStructure)
+    ///
+    /// # Arguments
+    /// - `vote_for`: Whether to vote for (`true`) or against (`false`) the proposal.
+    ///
+    /// # Errors
+    /// - The [`authority`](VoteOnFeeStructure::authority) must be a signer and be a
+    ///   MARKET_KEEPER in the store.
+    /// - The [`store`](VoteOnFeeStructure::store) must be an initialized store account.
+    /// - The [`proposal`](VoteOnFeeStructure::proposal) must be an initialized fee structure
+    ///   proposal owned by the store. It must not have been executed and its voting must not
+    ///   have ended.
+    /// - The [`vote`](VoteOnFeeStructure::vote) must be uninitialized, i.e., the authority
+    ///   must not have voted on the proposal.
+    #[access_control(internal::Authenticate::only_market_keeper(&ctx))]
+    pub fn vote_on_fee_structure(ctx: Context<VoteOnFeeStructure>, vote_for: bool) -> Result<()> {
+        instructions::unchecked_vote_on_fee_structure(ctx, vote_for)
+    }
+
+    /// Apply the changes of an approved fee structure proposal to the market.
+    ///
+    /// # Accounts
+    /// [*See the documentation for the accounts.*](ExecuteFeeStructureProposal)
+    ///
+    /// # Errors
+    /// - The [`authority`](ExecuteFeeStructureProposal::authority) must be a signer and be a
+    ///   MARKET_KEEPER in the store.
+    /// - The [`store`](ExecuteFeeStructureProposal::store) must be an initialized store account.
+    /// - The [`market`](ExecuteFeeStructureProposal::market) must be an initialized market account
+    ///   owned by the store and be the market of the proposal.
+    /// - The [`proposal`](ExecuteFeeStructureProposal::proposal) must be an initialized fee
+    ///   structure proposal owned by the store that has not been executed.
+    /// - The voting of the proposal must have ended with more votes for than against.
+    #[access_control(internal::Authenticate::only_market_keeper(&ctx))]
+    pub fn execute_fee_structure_proposal(ctx: Context<ExecuteFeeStructureProposal>) -> Result<()> {
+        instructions::unchecked_execute_fee_structure_proposal(ctx)
+    }
+
     /// Enable or disable GT minting for the given market.
     ///
     /// This instruction allows a MARKET_KEEPER to control whether GT minting is enabled for the
@@ -1579,6 +2996,203 @@ pub mod gmsol_store {
         instructions::unchecked_toggle_gt_minting(ctx, enable)
     }
 
+    /// Initialize a virtual pool.
+    ///
+    /// A virtual pool tracks the open interest shared by a group of correlated markets.
+    /// Markets that have been added to the virtual pool and have the `virtual_pool_enabled`
+    /// config flag set use it in place of their own open interest to calculate the
+    /// position price impact.
+    ///
+    /// # Accounts
+    /// [*See the documentation for the accounts.*](InitializeVirtualPool)
+    ///
+    /// # Arguments
+    /// - `key`: The key designating the group of markets.
+    ///
+    /// # Errors
+    /// - The [`authority`](InitializeVirtualPool::authority) must be a signer and be a
+    ///   MARKET_KEEPER in the store.
+    /// - The [`store`](InitializeVirtualPool::store) must be an initialized store account.
+    /// - The [`virtual_pool`](InitializeVirtualPool::virtual_pool) must be uninitialized and be
+    ///   the PDA derived from the store and the `key`.
+    /// - The `key` must not be the default pubkey.
+    #[access_control(internal::Authenticate::only_market_keeper(&ctx))]
+    pub fn initialize_virtual_pool(ctx: Context<InitializeVirtualPool>, key: Pubkey) -> Result<()> {
+        instructions::unchecked_initialize_virtual_pool(ctx, &key)
+    }
+
+    /// Add the given market to a virtual pool.
+    ///
+    /// The current open interest of the market is included in the virtual pool.
+    ///
+    /// # Accounts
+    /// [*See the documentation for the accounts.*](AddMarketToVirtualPool)
+    ///
+    /// # Arguments
+    /// - `market_token`: The market token of the market.
+    /// - `key`: The key of the virtual pool.
+    ///
+    /// # Errors
+    /// - The [`authority`](AddMarketToVirtualPool::authority) must be a signer and be a
+    ///   MARKET_KEEPER in the store.
+    /// - The [`store`](AddMarketToVirtualPool::store) must be an initialized store account.
+    /// - The [`market`](AddMarketToVirtualPool::market) must be an initialized market account
+    ///   owned by the store, and must not have been added to a virtual pool.
+    /// - The [`virtual_pool`](AddMarketToVirtualPool::virtual_pool) must be an initialized
+    ///   virtual pool owned by the store with the given `key`.
+    #[access_control(internal::Authenticate::only_market_keeper(&ctx))]
+    pub fn add_market_to_virtual_pool(
+        ctx: Context<AddMarketToVirtualPool>,
+        market_token: Pubkey,
+        key: Pubkey,
+    ) -> Result<()> {
+        let _ = market_token;
+        instructions::unchecked_add_market_to_virtual_pool(ctx, &key)
+    }
+
+    /// Initialize the OI caps account of the given market.
+    ///
+    /// The OI caps account maintains the max open interest of the market, which can then be
+    /// adjusted by a CONFIG_KEEPER with [`update_market_oi_caps`] without going through the
+    /// market config buffer flow. The caps are applied to the max open interest configs of
+    /// the market, so they take precedence over the values set previously.
+    ///
+    /// # Accounts
+    /// [*See the documentation for the accounts.*](InitializeMarketOiCaps)
+    ///
+    /// # Arguments
+    /// - `max_long`: The max open interest for long.
+    /// - `max_short`: The max open interest for short.
+    /// - `adjustment_rate_limit`: The maximum total change of each cap within 24 hours.
+    ///
+    /// # Errors
+    /// - The [`authority`](InitializeMarketOiCaps::authority) must be a signer and be a
+    ///   MARKET_KEEPER in the store.
+    /// - The [`store`](InitializeMarketOiCaps::store) must be an initialized store account.
+    /// - The [`market`](InitializeMarketOiCaps::market) must be an initialized market account
+    ///   owned by the store.
+    /// - The [`oi_caps`](InitializeMarketOiCaps::oi_caps) must be uninitialized and be the PDA
+    ///   derived from the store and the market token.
+    #[access_control(internal::Authenticate::only_market_keeper(&ctx))]
+    pub fn initialize_market_oi_caps(
+        ctx: Context<InitializeMarketOiCaps>,
+        max_long: u128,
+        max_short: u128,
+        adjustment_rate_limit: u128,
+    ) -> Result<()> {
+        instructions::unchecked_initialize_market_oi_caps(
+            ctx,
+            max_long,
+            max_short,
+            adjustment_rate_limit,
+        )
+    }
+
+    /// Adjust the OI caps of the given market.
+    ///
+    /// The total change of each cap within 24 hours cannot exceed the adjustment rate limit.
+    /// The new caps are applied to the max open interest configs of the market.
+    ///
+    /// # Accounts
+    /// [*See the documentation for the accounts.*](UpdateMarketOiCaps)
+    ///
+    /// # Arguments
+    /// - `new_max_long`: The new max open interest for long.
+    /// - `new_max_short`: The new max open interest for short.
+    ///
+    /// # Errors
+    /// - The [`authority`](UpdateMarketOiCaps::authority) must be a signer and be a
+    ///   CONFIG_KEEPER in the store.
+    /// - The [`store`](UpdateMarketOiCaps::store) must be an initialized store account.
+    /// - The [`market`](UpdateMarketOiCaps::market) must be an initialized market account
+    ///   owned by the store.
+    /// - The [`oi_caps`](UpdateMarketOiCaps::oi_caps) must be the initialized OI caps account
+    ///   of the market.
+    /// - The total change of each cap within the current 24-hour window must not exceed the
+    ///   adjustment rate limit.
+    #[access_control(internal::Authenticate::only_config_keeper(&ctx))]
+    pub fn update_market_oi_caps(
+        ctx: Context<UpdateMarketOiCaps>,
+        new_max_long: u128,
+        new_max_short: u128,
+    ) -> Result<()> {
+        instructions::unchecked_update_market_oi_caps(ctx, new_max_long, new_max_short)
+    }
+
+    /// Adjust the OI caps of multiple markets.
+    ///
+    /// Each update is subject to the rate limit of its market. The updates are applied
+    /// atomically: the whole batch is rejected if any of them fails.
+    ///
+    /// # Accounts
+    /// [*See the documentation for the accounts.*](BatchUpdateMarketOiCaps)
+    ///
+    /// # Arguments
+    /// - `updates`: The new OI caps of the markets.
+    ///
+    /// # Errors
+    /// - The [`authority`](BatchUpdateMarketOiCaps::authority) must be a signer and be a
+    ///   CONFIG_KEEPER in the store.
+    /// - The [`store`](BatchUpdateMarketOiCaps::store) must be an initialized store account.
+    /// - The number of `updates` must be in `1..=MAX_OI_CAP_UPDATES`.
+    /// - The remaining accounts must be the `[market, oi_caps]` pairs of the `updates`, where
+    ///   each market must be an initialized market account owned by the store, and each
+    ///   `oi_caps` must be the initialized OI caps account of the market.
+    /// - Each update must satisfy the rate limit of its market.
+    #[access_control(internal::Authenticate::only_config_keeper(&ctx))]
+    pub fn batch_update_market_oi_caps<'info>(
+        ctx: Context<'_, '_, 'info, 'info, BatchUpdateMarketOiCaps<'info>>,
+        updates: Vec<OiCapUpdate>,
+    ) -> Result<()> {
+        instructions::unchecked_batch_update_market_oi_caps(ctx, &updates)
+    }
+
+    /// Initialize the analytics account of the given market for the current period.
+    ///
+    /// The period is identified by `period_key = unix_timestamp / period_secs`. Once
+    /// initialized, the OHLCV data of the trades in the period are accumulated into the
+    /// account when it is provided to [`execute_increase_or_swap_order`] or
+    /// [`execute_decrease_order`], so price charts can be built without off-chain indexers.
+    /// Anyone can initialize the analytics accounts by paying the rent.
+    ///
+    /// # Accounts
+    /// [*See the documentation for the accounts.*](InitializeMarketAnalytics)
+    ///
+    /// # Arguments
+    /// - `period_secs`: The length of the period in seconds.
+    ///
+    /// # Errors
+    /// - The [`payer`](InitializeMarketAnalytics::payer) must be a signer.
+    /// - The [`store`](InitializeMarketAnalytics::store) must be an initialized store account.
+    /// - The [`market`](InitializeMarketAnalytics::market) must be an initialized market account
+    ///   owned by the store.
+    /// - The [`analytics`](InitializeMarketAnalytics::analytics) must be uninitialized and be the
+    ///   PDA derived from the store, the market token, the `period_secs` and the current period key.
+    /// - The `period_secs` must be non-zero and not greater than `i64::MAX`.
+    pub fn initialize_market_analytics(
+        ctx: Context<InitializeMarketAnalytics>,
+        period_secs: u64,
+    ) -> Result<()> {
+        instructions::initialize_market_analytics(ctx, period_secs)
+    }
+
+    /// Get the OHLCV data of the given period.
+    ///
+    /// # Accounts
+    /// [*See the documentation for the accounts.*](GetOhlcvPeriod)
+    ///
+    /// # Arguments
+    /// - `period_start`: The start timestamp of the period.
+    ///
+    /// # Errors
+    /// - The [`store`](GetOhlcvPeriod::store) must be an initialized store account.
+    /// - The [`analytics`](GetOhlcvPeriod::analytics) must be an initialized analytics account
+    ///   owned by the store.
+    /// - The `period_start` must match the start of the period of the `analytics` account.
+    pub fn get_ohlcv_period(ctx: Context<GetOhlcvPeriod>, period_start: i64) -> Result<OhlcvData> {
+        instructions::get_ohlcv_period(ctx, period_start)
+    }
+
     /// Claim fees from the given market.
     ///
     /// # Accounts
@@ -1602,6 +3216,34 @@ pub mod gmsol_store {
         Ok(claimed)
     }
 
+    /// Claim fees from the given market and split them between the treasury receiver
+    /// and the GT buyback address.
+    ///
+    /// The fraction of the claimed fees sent to the [`Buyback`](crate::states::AddressKey::Buyback)
+    /// address is given by the [`BuybackFeeFactor`](crate::states::FactorKey::BuybackFeeFactor)
+    /// of the store, while the remainder is sent to the treasury receiver.
+    /// A [`FeesClaimedWithSplit`](crate::events::FeesClaimedWithSplit) event is emitted
+    /// with both amounts.
+    ///
+    /// # Accounts
+    /// [*See the documentation for the accounts.*](ClaimFeesSplit)
+    ///
+    /// # Errors
+    /// - The [`authority`](ClaimFeesSplit::authority) must be a signer and be the designated
+    ///   fee receiver in the given store.
+    /// - The [`market`](ClaimFeesSplit::market) must be an initialized [`Market`](crate::states::Market)
+    ///   account owned by this program and associated with the given store.
+    /// - The [`treasury_target`](ClaimFeesSplit::treasury_target) must be owned by the treasury
+    ///   receiver.
+    /// - The [`buyback_target`](ClaimFeesSplit::buyback_target) must be owned by the GT buyback
+    ///   address, which must have been set.
+    /// - The buyback fee factor must not be greater than `1`.
+    /// - The token being claimed must be one of the market's configured collateral tokens.
+    /// - The market must maintain valid balance requirements after the claim.
+    pub fn claim_fees_split(ctx: Context<ClaimFeesSplit>) -> Result<()> {
+        instructions::claim_fees_split(ctx)
+    }
+
     /// Initialize a new market vault for a specific token.
     ///
     /// This instruction creates a new vault account that will be used to store tokens for a market.
@@ -1674,6 +3316,64 @@ pub mod gmsol_store {
         instructions::unchecked_close_empty_claimable_account(ctx, timestamp)
     }
 
+    /// Close the given claimable accounts if they are empty.
+    ///
+    /// Unlike [`close_empty_claimable_account`], claimable accounts that are uninitialized
+    /// or have a non-zero balance are skipped instead of failing the instruction.
+    ///
+    /// # Accounts
+    /// [*See the documentation for the accounts.*](CloseEmptyClaimableAccounts)
+    ///
+    /// # Arguments
+    /// - `timestamps`: The timestamps for which the claimable accounts were created, one for
+    ///   each tuple of the remaining accounts.
+    ///
+    /// # Errors
+    /// - The [`authority`](CloseEmptyClaimableAccounts::authority) must be a signer and have
+    ///   ORDER_KEEPER permissions in the store.
+    /// - The [`store`](CloseEmptyClaimableAccounts::store) must be initialized.
+    /// - The number of remaining accounts must be three times the length of `timestamps`.
+    /// - Each claimable account must be writable and a PDA derived from its claimable timestamp
+    ///   and other expected seeds.
+    #[access_control(internal::Authenticate::only_order_keeper(&ctx))]
+    pub fn close_empty_claimable_accounts<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CloseEmptyClaimableAccounts<'info>>,
+        timestamps: Vec<i64>,
+    ) -> Result<()> {
+        instructions::unchecked_close_empty_claimable_accounts(ctx, &timestamps)
+    }
+
+    /// Recover a claimable account that cannot be closed normally.
+    ///
+    /// The remaining balance of the claimable account, if any, is returned to the market
+    /// vault and credited to the given market, then the account is closed with the rent
+    /// sent to the receiver.
+    ///
+    /// # Accounts
+    /// [*See the documentation for the accounts.*](RecoverStuckClaimableAccount)
+    ///
+    /// # Arguments
+    /// - `timestamp`: The timestamp for which the claimable account was created.
+    ///
+    /// # Errors
+    /// - The [`authority`](RecoverStuckClaimableAccount::authority) must be a signer and the
+    ///   ADMIN of the store.
+    /// - The [`store`](RecoverStuckClaimableAccount::store) must be initialized.
+    /// - The [`market`](RecoverStuckClaimableAccount::market) must be initialized and owned by
+    ///   the store. It must have the mint as a collateral token if the balance is non-zero.
+    /// - The [`account`](RecoverStuckClaimableAccount::account) must be a store-owned claimable
+    ///   account derived from the claimable timestamp and other expected seeds.
+    /// - The [`vault`](RecoverStuckClaimableAccount::vault) must be the market vault of the mint.
+    /// - The `max_claimable_account_lifetime` amount of the store must be non-zero, and more
+    ///   than that many seconds must have elapsed since the end of the claimable time window.
+    #[access_control(internal::Authenticate::only_admin(&ctx))]
+    pub fn recover_stuck_claimable_account(
+        ctx: Context<RecoverStuckClaimableAccount>,
+        timestamp: i64,
+    ) -> Result<()> {
+        instructions::unchecked_recover_stuck_claimable_account(ctx, timestamp)
+    }
+
     /// Prepare an associated token account.
     ///
     /// # Accounts
@@ -1714,6 +3414,10 @@ pub mod gmsol_store {
     /// - The [`store`](CreateDeposit::store) is not properly initialized.
     /// - The [`market`](CreateDeposit::market) is not initialized, not owned by the store,
     ///   or is disabled.
+    /// - The [`user`](CreateDeposit::user) is not the PDA of the owner's user account, or is frozen.
+    ///   It will be initialized if needed.
+    /// - The owner has already created the max number of trades allowed in the current slot,
+    ///   as configured by [`MaxTradesPerBlockPerUser`](states::AmountKey::MaxTradesPerBlockPerUser).
     /// - The [`deposit`](CreateDeposit::deposit) account is already initialized or is not
     ///   a valid PDA derived from the provided nonce and other expected seeds.
     /// - The [`market_token`](CreateDeposit::market_token) is not the market token of `market`.
@@ -1749,6 +3453,8 @@ pub mod gmsol_store {
     /// - Any escrow account is not owned by the `deposit` or does not match the `deposit` records.
     /// - Any associated token account address is invalid.
     /// - The deposit is not in a cancelled or completed state when closed by a non-owner.
+    /// - The deposit is counted in the pending count of its market, but the market is not
+    ///   provided (writable) as the first remaining account.
     pub fn close_deposit<'info>(
         ctx: Context<'_, '_, 'info, 'info, CloseDeposit<'info>>,
         reason: String,
@@ -1771,8 +3477,8 @@ pub mod gmsol_store {
     /// - The [`authority`](ExecuteDeposit::authority) is not a signer or is not an ORDER_KEEPER
     ///   in the store.
     /// - The [`store`](ExecuteDeposit::store) is not properly initialized.
-    /// - The [`token_map`](ExecuteDeposit::token_map) is not initialized or not authorized by
-    ///   the store.
+    /// - The [`token_map`](ExecuteDeposit::token_map) is not initialized or not the token map
+    ///   to use for the `market`.
     /// - The [`oracle`](ExecuteDeposit::oracle) is not initialized, cleared and owned by the
     ///   store.
     /// - The [`market`](ExecuteDeposit::market) is not initialized, is disabled, not owned by
@@ -1786,6 +3492,9 @@ pub mod gmsol_store {
     /// - Any market accounts in the remaining accounts are disabled, not owned by the store,
     ///   or do not match the swap parameters.
     /// - Any oracle prices from the feed accounts are incomplete or invalid.
+    /// - The [`keeper_long_token_account`](ExecuteDeposit::keeper_long_token_account) or
+    ///   [`keeper_short_token_account`](ExecuteDeposit::keeper_short_token_account) is provided for
+    ///   receiving the keeper tip, but is not a token account of the corresponding token of the `market`.
     /// - The execution fails and `throw_on_execution_error` is set to `true`.
     #[access_control(internal::Authenticate::only_order_keeper(&ctx))]
     pub fn execute_deposit<'info>(
@@ -1816,6 +3525,10 @@ pub mod gmsol_store {
     /// - The [`store`](CreateWithdrawal::store) is not properly initialized.
     /// - The [`market`](CreateWithdrawal::market) is not initialized, is disabled, or not owned
     ///   by the store.
+    /// - The [`user`](CreateWithdrawal::user) is not the PDA of the owner's user account, or is frozen.
+    ///   It will be initialized if needed.
+    /// - The owner has already created the max number of trades allowed in the current slot,
+    ///   as configured by [`MaxTradesPerBlockPerUser`](states::AmountKey::MaxTradesPerBlockPerUser).
     /// - The [`withdrawal`](CreateWithdrawal::withdrawal) is already initialized or is not a valid
     ///   PDA derived from the provided `nonce` and expected seeds.
     /// - The [`market_token`](CreateWithdrawal::market_token) does not match the market token
@@ -1853,6 +3566,8 @@ pub mod gmsol_store {
     /// - Any required escrow accounts are not properly initialized or not owned by the `withdrawal`.
     /// - Any associated token accounts have invalid addresses.
     /// - The withdrawal is not in a cancelled or completed state when the executor is not the owner
+    /// - The withdrawal is counted in the pending count of its market, but the market is not
+    ///   provided (writable) as the first remaining account.
     pub fn close_withdrawal<'info>(
         ctx: Context<'_, '_, 'info, 'info, CloseWithdrawal<'info>>,
         reason: String,
@@ -1875,8 +3590,8 @@ pub mod gmsol_store {
     /// - The [`authority`](ExecuteWithdrawal::authority) is not a signer or is not an ORDER_KEEPER
     ///   in the store.
     /// - The [`store`](ExecuteWithdrawal::store) is not properly initialized.
-    /// - The [`token_map`](ExecuteWithdrawal::token_map) is not initialized or not authorized by
-    ///   the store.
+    /// - The [`token_map`](ExecuteWithdrawal::token_map) is not initialized or not the token map
+    ///   to use for the `market`.
     /// - The [`oracle`](ExecuteWithdrawal::oracle) is not initialized, cleared and owned by the
     ///   store.
     /// - The [`market`](ExecuteWithdrawal::market) is not initialized, is disabled, not owned by
@@ -1890,6 +3605,9 @@ pub mod gmsol_store {
     /// - Any market accounts in the remaining accounts are disabled, not owned by the store, or do
     ///   not match the swap parameters.
     /// - Any oracle prices from the feed accounts are incomplete or invalid.
+    /// - The [`keeper_long_token_account`](ExecuteWithdrawal::keeper_long_token_account) or
+    ///   [`keeper_short_token_account`](ExecuteWithdrawal::keeper_short_token_account) is provided for
+    ///   receiving the keeper tip, but is not a token account of the corresponding token of the `market`.
     /// - The execution fails and `throw_on_execution_error` is set to true.
     #[access_control(internal::Authenticate::only_order_keeper(&ctx))]
     pub fn execute_withdrawal<'info>(
@@ -1923,6 +3641,8 @@ pub mod gmsol_store {
     ///   `owner` and expected seeds.
     /// - The position account is neither uninitialized nor validly initialized with `store` as the
     ///   store and `owner` as the owner.
+    /// - The `margin_mode` in `params` is provided and differs from the margin mode of the existing
+    ///   position while the position is not empty.
     pub fn prepare_position(
         ctx: Context<PreparePosition>,
         params: CreateOrderParams,
@@ -1930,6 +3650,36 @@ pub mod gmsol_store {
         instructions::prepare_position(ctx, &params)
     }
 
+    /// Prepare multiple position accounts for orders at once.
+    ///
+    /// Each position is derived with the same seeds as in [`prepare_position`], and is
+    /// created if it does not exist. The whole batch is rejected if any of the positions
+    /// fails to be prepared.
+    ///
+    /// # Accounts
+    /// *[See the documentation for the accounts.](BatchPreparePositions)*
+    ///
+    /// # Arguments
+    /// - `specs`: The specifications of the positions to prepare.
+    ///
+    /// # Errors
+    /// This instruction will fail if:
+    /// - The [`owner`](BatchPreparePositions::owner) is not a signer or has insufficient balance
+    ///   for the rent.
+    /// - The [`store`](BatchPreparePositions::store) is not properly initialized.
+    /// - The number of `specs` is not in `1..=MAX_BATCH_PREPARE_POSITIONS`.
+    /// - The remaining accounts are not the `[market, position]` pairs of the `specs`, where
+    ///   each market must be an enabled market owned by the `store` with the market token of
+    ///   the spec, and each position must be the writable PDA derived from the `owner` and the spec.
+    /// - The collateral token of any spec is not a collateral token of its market.
+    /// - Any of the positions is already initialized with different parameters.
+    pub fn batch_prepare_positions<'info>(
+        ctx: Context<'_, '_, 'info, 'info, BatchPreparePositions<'info>>,
+        specs: Vec<PositionSpec>,
+    ) -> Result<()> {
+        instructions::batch_prepare_positions(ctx, &specs)
+    }
+
     /// Create an order by the owner.
     ///
     /// # Accounts
@@ -1948,11 +3698,16 @@ pub mod gmsol_store {
     ///   the `store`.
     /// - The [`user`](CreateOrder::user) is not initialized or does not correspond to the owner.
     ///   The address must be a valid PDA derived from the `owner` and expected seeds.
+    /// - The [`user`](CreateOrder::user) is frozen and the order is not a decrease order.
+    /// - The owner has already created the max number of trades allowed in the current slot,
+    ///   as configured by [`MaxTradesPerBlockPerUser`](states::AmountKey::MaxTradesPerBlockPerUser).
     /// - The [`order`](CreateOrder::order) is not uninitialized or the address is not a valid
     ///   PDA derived from the `owner`, `nonce` and expected seeds.
     /// - For increase/decrease orders:
     ///   - The [`position`](CreateOrder::position) is missing, not validly initialized, or not
     ///     owned by both the `owner` and `store`.
+    ///   - The `margin_mode` in `params` is provided and does not match the margin mode of the
+    ///     [`position`](CreateOrder::position).
     ///   - The [`long_token`](CreateOrder::long_token) or [`short_token`](CreateOrder::short_token)
     ///     are missing, or do not match the those defined in the [`market`](CreateOrder::market).
     ///   - The [`long_token_escrow`](CreateOrder::long_token_escrow) or
@@ -1973,6 +3728,7 @@ pub mod gmsol_store {
     /// - The feature for creating this kind of order is not enabled.
     /// - The remaining market accounts do not match the swap parameters, not all enabled or owned
     ///   by the `store`.
+    /// - The callback is provided partially, or the callback program is the store program itself.
     pub fn create_order<'info>(
         mut ctx: Context<'_, '_, 'info, 'info, CreateOrder<'info>>,
         nonce: [u8; 32],
@@ -2004,6 +3760,8 @@ pub mod gmsol_store {
     /// - The addresses of the ATAs must be valid.
     /// - The `order` must be cancelled or completed if the `executor` is not the owner.
     /// - The feature must be enabled for closing the given kind of `order`.
+    /// - If the `order` is counted in the pending count of its market, the market must be
+    ///   provided (writable) as the first remaining account.
     pub fn close_order<'info>(
         ctx: Context<'_, '_, 'info, 'info, CloseOrder<'info>>,
         reason: String,
@@ -2078,6 +3836,10 @@ pub mod gmsol_store {
 
     /// Execute an increase/swap order by keepers.
     ///
+    /// If the `order` has a callback and order callbacks are enabled, the callback becomes
+    /// pending after the order is executed, and an [`OrderCallbackPending`](events::OrderCallbackPending)
+    /// event is emitted. See [`invoke_order_callback`] for details.
+    ///
     /// # Accounts
     /// *[See the documentation for the accounts.](ExecuteIncreaseOrSwapOrder)*
     ///
@@ -2091,8 +3853,8 @@ pub mod gmsol_store {
     /// - The [`authority`](ExecuteIncreaseOrSwapOrder::authority) must be a signer and have the
     ///   ORDER_KEEPER role in the `store`.
     /// - The [`store`](ExecuteIncreaseOrSwapOrder::store) must be initialized.
-    /// - The [`token_map`](ExecuteIncreaseOrSwapOrder::token_map) must be initialized and authorized
-    ///   by the `store`.
+    /// - The [`token_map`](ExecuteIncreaseOrSwapOrder::token_map) must be initialized and be the
+    ///   token map to use for the `market`.
     /// - The [`oracle`](ExecuteIncreaseOrSwapOrder::oracle) must be initialized, cleared and owned
     ///   by the `store`.
     /// - The [`market`](ExecuteIncreaseOrSwapOrder::market) must be initialized, enabled and owned
@@ -2125,6 +3887,16 @@ pub mod gmsol_store {
     ///   details.
     /// - The feature for executing this order type must be enabled in the `store`.
     /// - If `throw_on_execution_error` is true, any execution failure will throw an error
+    /// - If the position is in cross margin mode, all the other positions of the owner in the
+    ///   `market` must be provided in the remaining accounts. The position is then checked by the
+    ///   total remaining collateral value of the ones in cross margin mode against their total
+    ///   required collateral value, while it must still be able to cover its own losses.
+    /// - The optional trailing [`MarketAnalytics`](states::MarketAnalytics) accounts must be
+    ///   owned by the `store` and be of the `market`. Position trades are recorded in the ones
+    ///   whose period covers the execution time.
+    /// - The [`keeper_long_token_account`](ExecuteIncreaseOrSwapOrder::keeper_long_token_account) or
+    ///   [`keeper_short_token_account`](ExecuteIncreaseOrSwapOrder::keeper_short_token_account) is provided for
+    ///   receiving the keeper tip, but is not a token account of the corresponding token of the `market`.
     // Note: There is a false positive lint for the doc link of `event`.
     #[allow(rustdoc::broken_intra_doc_links)]
     #[access_control(internal::Authenticate::only_order_keeper(&ctx))]
@@ -2144,6 +3916,10 @@ pub mod gmsol_store {
 
     /// Execute a decrease order by keepers.
     ///
+    /// If the `order` has a callback and order callbacks are enabled, the callback becomes
+    /// pending after the order is executed, and an [`OrderCallbackPending`](events::OrderCallbackPending)
+    /// event is emitted. See [`invoke_order_callback`] for details.
+    ///
     /// # Accounts
     /// *[See the documentation for the accounts.](ExecuteDecreaseOrder)*
     ///
@@ -2157,8 +3933,8 @@ pub mod gmsol_store {
     /// - The [`authority`](ExecuteDecreaseOrder::authority) must be a signer with the ORDER_KEEPER
     ///   role in the `store`.
     /// - The [`store`](ExecuteDecreaseOrder::store) must be initialized.
-    /// - The [`token_map`](ExecuteDecreaseOrder::token_map) must be initialized and authorized
-    ///   by the `store`.
+    /// - The [`token_map`](ExecuteDecreaseOrder::token_map) must be initialized and be the
+    ///   token map to use for the `market`.
     /// - The [`oracle`](ExecuteDecreaseOrder::oracle) must be initialized, cleared and owned
     ///   by the `store`.
     /// - The [`market`](ExecuteDecreaseOrder::market) must be initialized, enabled and owned
@@ -2182,6 +3958,16 @@ pub mod gmsol_store {
     ///   details.
     /// - The feature for executing decrease orders must be enabled in the `store`.
     /// - If `throw_on_execution_error` is true, any execution failure will throw an error.
+    /// - If the position is in cross margin mode, all the other positions of the owner in the
+    ///   `market` must be provided in the remaining accounts. The position is then checked by the
+    ///   total remaining collateral value of the ones in cross margin mode against their total
+    ///   required collateral value, while it must still be able to cover its own losses.
+    /// - The optional trailing [`MarketAnalytics`](states::MarketAnalytics) accounts must be
+    ///   owned by the `store` and be of the `market`. Position trades are recorded in the ones
+    ///   whose period covers the execution time.
+    /// - The [`keeper_long_token_account`](ExecuteDecreaseOrder::keeper_long_token_account) or
+    ///   [`keeper_short_token_account`](ExecuteDecreaseOrder::keeper_short_token_account) is provided for
+    ///   receiving the keeper tip, but is not a token account of the corresponding token of the `market`.
     // Note: There is a false positive lint for the doc link of `event`.
     #[allow(rustdoc::broken_intra_doc_links)]
     #[access_control(internal::Authenticate::only_order_keeper(&ctx))]
@@ -2199,6 +3985,40 @@ pub mod gmsol_store {
         )
     }
 
+    /// Invoke the pending callback of an executed order by keepers.
+    ///
+    /// The callback is invoked in this separate instruction, so that a failing callback never
+    /// affects the execution of the order. It is best-effort: the keeper may give up invoking a
+    /// failing callback and close the order.
+    ///
+    /// # Accounts
+    /// *[See the documentation for the accounts.](InvokeOrderCallback)*
+    ///
+    /// # Arguments
+    /// - `transfer_out`: The execution result, as emitted in the
+    ///   [`OrderCallbackPending`](events::OrderCallbackPending) event.
+    ///
+    /// # Errors
+    /// - The [`authority`](InvokeOrderCallback::authority) must be a signer with the ORDER_KEEPER
+    ///   role in the `store`.
+    /// - The [`store`](InvokeOrderCallback::store) must be initialized, with order callbacks
+    ///   enabled, i.e., the max order callback compute units must not be zero.
+    /// - The [`order`](InvokeOrderCallback::order) must be owned by the `store` and have a
+    ///   pending callback.
+    /// - The `transfer_out` must match the execution result recorded in the `order`.
+    /// - The [`callback_authority`](InvokeOrderCallback::callback_authority),
+    ///   [`callback_program`](InvokeOrderCallback::callback_program) and
+    ///   [`callback_account`](InvokeOrderCallback::callback_account) must match the `order`.
+    /// - The callback must succeed within the max order callback compute units configured in
+    ///   the `store`.
+    #[access_control(internal::Authenticate::only_order_keeper(&ctx))]
+    pub fn invoke_order_callback(
+        ctx: Context<InvokeOrderCallback>,
+        transfer_out: TransferOut,
+    ) -> Result<()> {
+        instructions::unchecked_invoke_order_callback(ctx, &transfer_out)
+    }
+
     /// Perform a liquidation by keepers.
     ///
     /// # Accounts
@@ -2216,8 +4036,8 @@ pub mod gmsol_store {
     /// - The [`user`](PositionCut::user) must be an initialized user account corresponding to the
     ///   `owner`.
     /// - The [`store`](PositionCut::store) must be initialized.
-    /// - The [`token_map`](PositionCut::token_map) must be initialized and authorized by the
-    ///   `store`.
+    /// - The [`token_map`](PositionCut::token_map) must be initialized and be the token map to
+    ///   use for the `market`.
     /// - The [`oracle`](PositionCut::oracle) must be initialized, cleared and owned by the `store`.
     /// - The [`market`](PositionCut::market) must be:
     ///   - Initialized and enabled
@@ -2248,6 +4068,8 @@ pub mod gmsol_store {
     /// - Price feed accounts must be:
     ///   - Valid and complete
     ///   - Provided in order matching the market's sorted token list
+    /// - If the position is in cross margin mode, the other positions of the owner in the `market`
+    ///   must be provided after the price feed accounts.
     /// - The liquidation feature must be enabled in the `store`.
     /// - Oracle prices must be valid and complete.
     // Note: There is a false positive lint for the doc link of `event`.
@@ -2295,6 +4117,61 @@ pub mod gmsol_store {
         instructions::unchecked_update_adl_state(ctx, is_long)
     }
 
+    /// Dry-run the pre-execution checks of an action for keepers.
+    ///
+    /// This instruction is read-only and does not perform the execution itself. It is intended
+    /// to be simulated by keepers to avoid sending executions that are going to fail or to be
+    /// unprofitable.
+    ///
+    /// # Accounts
+    /// *[See the documentation for the accounts.](ValidateExecutionParams)*
+    ///
+    /// # Arguments
+    /// - `execution_fee`: The execution fee to be claimed by the keeper.
+    ///
+    /// # Return
+    /// Returns a [`ValidationReport`] describing the result of each check.
+    ///
+    /// # Errors
+    /// - The [`oracle`](ValidateExecutionParams::oracle) must be an initialized
+    ///   [`Oracle`](states::Oracle) account owned by the `store`.
+    /// - The [`market`](ValidateExecutionParams::market) must be owned by the `store`.
+    /// - The [`action`](ValidateExecutionParams::action) must be a [`Deposit`](states::Deposit),
+    ///   [`Withdrawal`](states::Withdrawal) or [`Order`](states::Order) account owned by the
+    ///   `store` and matching the `market`.
+    pub fn validate_execution_params<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ValidateExecutionParams<'info>>,
+        execution_fee: u64,
+    ) -> Result<ValidationReport> {
+        instructions::validate_execution_params(ctx, execution_fee)
+    }
+
+    /// Get the tokens and their feeds required for executing the given action.
+    ///
+    /// The returned tokens are in the same order as the feed accounts expected by the
+    /// execution instruction of the action.
+    ///
+    /// # Accounts
+    /// *[See the documentation for the accounts.](GetRequiredFeeds)*
+    ///
+    /// # Return
+    /// Returns a [`TokensWithFeed`](states::common::TokensWithFeed) containing the tokens,
+    /// their feeds and providers.
+    ///
+    /// # Errors
+    /// - The [`token_map`](GetRequiredFeeds::token_map) must be the token map resolved for
+    ///   the markets.
+    /// - The [`market`](GetRequiredFeeds::market) must be owned by the `store`.
+    /// - The [`to_market`](GetRequiredFeeds::to_market) must be provided for shifts, be owned
+    ///   by the `store` and match the shift.
+    /// - The [`action`](GetRequiredFeeds::action) must be a [`Deposit`](states::Deposit),
+    ///   [`Withdrawal`](states::Withdrawal), [`Order`](states::Order) or
+    ///   [`Shift`](states::Shift) account owned by the `store` and matching the `market`.
+    /// - All the required tokens must be configured in the token map.
+    pub fn get_required_feeds(ctx: Context<GetRequiredFeeds>) -> Result<TokensWithFeed> {
+        instructions::get_required_feeds(ctx)
+    }
+
     /// Perform an ADL (Auto-Deleveraging) by keepers.
     ///
     /// # Accounts
@@ -2310,7 +4187,8 @@ pub mod gmsol_store {
     /// - The [`owner`](PositionCut::owner) must be the position owner.
     /// - The [`user`](PositionCut::user) must be initialized and corresponding to the `owner`.
     /// - The [`store`](PositionCut::store) must be initialized.
-    /// - The [`token_map`](PositionCut::token_map) must be initialized and authorized by the store.
+    /// - The [`token_map`](PositionCut::token_map) must be initialized and be the token map to
+    ///   use for the `market`.
     /// - The [`oracle`](PositionCut::oracle) must be initialized, cleared and store-owned.
     /// - The [`market`](PositionCut::market) must be initialized, enabled, store-owned and match
     ///   the position's market. The market must be in ADL state.
@@ -2335,6 +4213,8 @@ pub mod gmsol_store {
     /// - The [`claimable_pnl_token_account_for_holding`](PositionCut::claimable_pnl_token_account_for_holding)
     ///   must be a store-owned, holding-delegated claimable account for PnL token.
     /// - Price feed accounts must be valid and provided in the market's sorted token list order.
+    /// - If the position is in cross margin mode, the other positions of the owner in the `market`
+    ///   must be provided after the price feed accounts.
     /// - The ADL feature must be enabled in the `store`.
     /// - Oracle prices must be valid and complete.
     /// - Execution must complete successfully.
@@ -2381,6 +4261,8 @@ pub mod gmsol_store {
     ///   and store-owned.
     /// - The [`from_market`](CreateShift::from_market) must be shiftable to the
     ///   [`to_market`](CreateShift::to_market).
+    /// - The [`user`](CreateShift::user) must be the PDA of the user account of the `owner`
+    ///   and not be frozen. It will be initialized if needed.
     /// - The [`shift`](CreateShift::shift) must be uninitialized. Its address must
     ///   match the PDA derived from the expected seeds.
     /// - The [`from_market_token`](CreateShift::from_market_token) must be the market
@@ -2420,7 +4302,8 @@ pub mod gmsol_store {
     /// - The [`authority`](ExecuteShift::authority) must be a signer and have the ORDER_KEEPER role
     ///   in the store.
     /// - The [`store`](ExecuteShift::store) must be initialized.
-    /// - The [`token_map`](ExecuteShift::token_map) must be initialized and authorized by the store.
+    /// - The [`token_map`](ExecuteShift::token_map) must be initialized and be the token map to
+    ///   use for both the `from_market` and the `to_market`.
     /// - The [`oracle`](ExecuteShift::oracle) must be initialized, cleared and store-owned.
     /// - The [`from_market`](ExecuteShift::from_market) must be initialized, enabled and store-owned.
     ///   It must be the from market of the [`shift`](ExecuteShift::shift).
@@ -2573,6 +4456,28 @@ pub mod gmsol_store {
         instructions::unchecked_gt_set_referral_reward_factors(ctx, &factors)
     }
 
+    /// Validate referral reward factors without setting them.
+    ///
+    /// This instruction applies the same checks as [`gt_set_referral_reward_factors`] and
+    /// returns all the problems found, so that the factors can be validated before proposing
+    /// the change. The factors are valid if the returned list is empty.
+    ///
+    /// # Accounts
+    /// *[See the documentation for the accounts.](ReadStore)*
+    ///
+    /// # Arguments
+    /// - `factors`: The referral reward factors for each user rank.
+    ///
+    /// # Errors
+    /// - The [`store`](ReadStore::store) must be initialized.
+    /// - The GT state of the `store` must be initialized.
+    pub fn validate_referral_reward_factors(
+        ctx: Context<ReadStore>,
+        factors: Vec<u128>,
+    ) -> Result<Vec<ReferralRewardFactorsProblem>> {
+        instructions::validate_referral_reward_factors(ctx, &factors)
+    }
+
     /// Set GT exchange time window (in seconds).
     ///
     /// # Accounts
@@ -2651,7 +4556,8 @@ pub mod gmsol_store {
     /// # Errors
     /// - The [`owner`](RequestGtExchange::owner) must be a signer.
     /// - The [`store`](RequestGtExchange::store) must be properly initialized with an initialized GT state.
-    /// - The [`user`](RequestGtExchange::user) must be properly initialized and correspond to the `owner`.
+    /// - The [`user`](RequestGtExchange::user) must be properly initialized, correspond to the `owner`
+    ///   and not be frozen.
     /// - The [`vault`](RequestGtExchange::vault) must be properly initialized, owned by the `store`,
     ///   and currently accepting deposits (not yet confirmed).
     /// - The [`exchange`](RequestGtExchange::exchange) must be either:
@@ -2664,7 +4570,9 @@ pub mod gmsol_store {
         instructions::request_gt_exchange(ctx, amount)
     }
 
-    /// Close a confirmed GT exchange.
+    /// Mark a confirmed GT exchange as completed.
+    ///
+    /// The exchange account is kept until its rent is reclaimed with [`reclaim_gt_exchange_rent`].
     ///
     /// # Accounts
     /// *[See the documentation for the accounts.](CloseGtExchange)*
@@ -2674,13 +4582,42 @@ pub mod gmsol_store {
     /// - The [`store`](CloseGtExchange::store) must be properly initialized with an initialized GT state.
     /// - The [`vault`](CloseGtExchange::vault) must be properly initialized, owned by the `store`,
     ///   and confirmed.
-    /// - The [`exchange`](CloseGtExchange::exchange) must be properly initialized and owned by both
-    ///   the `owner` and `vault`.
+    /// - The [`exchange`](CloseGtExchange::exchange) must be properly initialized, owned by both
+    ///   the `owner` and `vault`, and not completed.
     #[access_control(internal::Authenticate::only_gt_controller(&ctx))]
     pub fn close_gt_exchange(ctx: Context<CloseGtExchange>) -> Result<()> {
         instructions::unchecked_close_gt_exchange(ctx)
     }
 
+    /// Close completed GT exchanges and GT exchange vaults in bulk, refunding their rent.
+    ///
+    /// Exchanges that are not completed are skipped. Vaults are only closed once they are
+    /// confirmed and all their exchanges have been completed, so vaults created before the
+    /// pending exchanges are tracked are always skipped.
+    ///
+    /// # Accounts
+    /// *[See the documentation for the accounts.](ReclaimGtExchangeRent)*
+    ///
+    /// # Arguments
+    /// - `vaults`: The number of vaults in the remaining accounts.
+    ///
+    /// # Errors
+    /// - The [`authority`](ReclaimGtExchangeRent::authority) must be a signer and have the
+    ///   GT_CONTROLLER role in the `store`.
+    /// - The [`store`](ReclaimGtExchangeRent::store) must be properly initialized with an
+    ///   initialized GT state.
+    /// - Each vault in the remaining accounts must be a properly initialized GT exchange vault
+    ///   owned by the `store`.
+    /// - Each exchange in the remaining accounts must be properly initialized, owned by the `store`,
+    ///   and followed by its owner.
+    #[access_control(internal::Authenticate::only_gt_controller(&ctx))]
+    pub fn reclaim_gt_exchange_rent<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ReclaimGtExchangeRent<'info>>,
+        vaults: u8,
+    ) -> Result<()> {
+        instructions::unchecked_reclaim_gt_exchange_rent(ctx, vaults)
+    }
+
     // ===========================================
     //              User & Referral
     // ===========================================
@@ -2711,7 +4648,9 @@ pub mod gmsol_store {
     /// # Errors
     /// - The [`owner`](InitializeReferralCode::owner) must be a signer.
     /// - The [`store`](InitializeReferralCode::store) must be properly initialized.
-    /// - The [`referral_code`](InitializeReferralCode::referral_code) account must be uninitialized.
+    /// - The [`referral_code`](InitializeReferralCode::referral_code) account must be uninitialized,
+    ///   unless it is already the referral code of the `owner`, in which case the instruction
+    ///   succeeds without doing anything.
     /// - The [`user`](InitializeReferralCode::user) account must be:
     ///   - Properly initialized
     ///   - Correspond to the `owner`
@@ -2815,10 +4754,292 @@ pub mod gmsol_store {
     ///   - Properly initialized
     ///   - Not have an associated referral code
     ///   - Correspond to the `next_owner`
+    /// - The [`reward_vault`](AcceptReferralCode::reward_vault) is optional. If it is provided,
+    ///   the transfer reward has been claimed neither for this referral code nor by the
+    ///   `next_owner`, and the `next_owner` has paid at least the min paid fee value of the vault,
+    ///   the reward is paid to the `next_owner`, and the vault must hold enough lamports to pay it.
     pub fn accept_referral_code(ctx: Context<AcceptReferralCode>) -> Result<()> {
         instructions::accept_referral_code(ctx)
     }
 
+    /// Initialize the referral reward vault and fund it with the given amount of lamports.
+    ///
+    /// The vault can be topped up later by transferring lamports to it directly.
+    ///
+    /// # Accounts
+    /// *[See the documentation for the accounts.](InitializeReferralRewardVault)*
+    ///
+    /// # Arguments
+    /// - `amount`: The amount of lamports to fund the vault with.
+    ///
+    /// # Errors
+    /// - The [`authority`](InitializeReferralRewardVault::authority) must be a signer and the
+    ///   current admin of the store.
+    /// - The [`store`](InitializeReferralRewardVault::store) must be properly initialized.
+    /// - The [`reward_vault`](InitializeReferralRewardVault::reward_vault) must not have been
+    ///   initialized.
+    #[access_control(internal::Authenticate::only_admin(&ctx))]
+    pub fn initialize_referral_reward_vault(
+        ctx: Context<InitializeReferralRewardVault>,
+        amount: u64,
+    ) -> Result<()> {
+        instructions::unchecked_initialize_referral_reward_vault(ctx, amount)
+    }
+
+    /// Set the reward (in lamports) paid for accepting a referral code transfer.
+    ///
+    /// # Accounts
+    /// *[See the documentation for the accounts.](SetReferralTransferReward)*
+    ///
+    /// # Arguments
+    /// - `amount`: The reward in lamports.
+    /// - `min_paid_fee_value`: The min value (in USD) of the trading fees paid by the new owner
+    ///   to be eligible for the reward.
+    ///
+    /// # Errors
+    /// - The [`authority`](SetReferralTransferReward::authority) must be a signer and the
+    ///   current admin of the store.
+    /// - The [`store`](SetReferralTransferReward::store) must be properly initialized.
+    /// - The [`reward_vault`](SetReferralTransferReward::reward_vault) must be properly
+    ///   initialized.
+    #[access_control(internal::Authenticate::only_admin(&ctx))]
+    pub fn set_referral_transfer_reward(
+        ctx: Context<SetReferralTransferReward>,
+        amount: u64,
+        min_paid_fee_value: u128,
+    ) -> Result<()> {
+        instructions::unchecked_set_referral_transfer_reward(ctx, amount, min_paid_fee_value)
+    }
+
+    /// Claim the fees in wrapped native token from the given market and unwrap them
+    /// into the referral reward vault.
+    ///
+    /// # Accounts
+    /// *[See the documentation for the accounts.](FundReferralRewardVaultFromFees)*
+    ///
+    /// # Return
+    /// - Returns the claimed amount in lamports.
+    ///
+    /// # Errors
+    /// - The [`authority`](FundReferralRewardVaultFromFees::authority) must be a signer and be the
+    ///   designated fee receiver in the given store.
+    /// - The [`store`](FundReferralRewardVaultFromFees::store) must be properly initialized.
+    /// - The [`reward_vault`](FundReferralRewardVaultFromFees::reward_vault) must be properly
+    ///   initialized.
+    /// - The [`market`](FundReferralRewardVaultFromFees::market) must be owned by the `store`
+    ///   and use the wrapped native token as one of its collateral tokens.
+    /// - The [`vault`](FundReferralRewardVaultFromFees::vault) must be the market vault for
+    ///   the wrapped native token.
+    /// - The [`escrow`](FundReferralRewardVaultFromFees::escrow) must be the ATA of the
+    ///   `reward_vault` for the wrapped native token.
+    pub fn fund_referral_reward_vault_from_fees(
+        ctx: Context<FundReferralRewardVaultFromFees>,
+    ) -> Result<u64> {
+        instructions::fund_referral_reward_vault_from_fees(ctx)
+    }
+
+    /// Get the referral stats of a user.
+    ///
+    /// # Accounts
+    /// *[See the documentation for the accounts.](ReadUser)*
+    ///
+    /// # Arguments
+    /// - `owner`: The owner of the user account.
+    ///
+    /// # Return
+    /// Returns the [`ReferralStats`](states::user::ReferralStats) of the user, including
+    /// the referral code address, the number of referees and the cumulative GT rewards
+    /// received as a referrer.
+    ///
+    /// # Errors
+    /// - The [`store`](ReadUser::store) must be properly initialized.
+    /// - The [`user`](ReadUser::user) account must be properly initialized and correspond
+    ///   to the `owner`.
+    pub fn get_referral_stats(
+        ctx: Context<ReadUser>,
+        owner: Pubkey,
+    ) -> Result<states::user::ReferralStats> {
+        instructions::get_referral_stats(ctx, &owner)
+    }
+
+    /// Get the activity of a user.
+    ///
+    /// # Accounts
+    /// *[See the documentation for the accounts.](ReadUser)*
+    ///
+    /// # Arguments
+    /// - `owner`: The owner of the user account.
+    ///
+    /// # Return
+    /// Returns the [`UserActivity`](states::user::UserActivity) of the user, including the
+    /// number of deposits, withdrawals and shifts created by the user and the timestamp of
+    /// the last one.
+    ///
+    /// # Errors
+    /// - The [`store`](ReadUser::store) must be properly initialized.
+    /// - The [`user`](ReadUser::user) account must be properly initialized and correspond
+    ///   to the `owner`.
+    pub fn get_user_activity(
+        ctx: Context<ReadUser>,
+        owner: Pubkey,
+    ) -> Result<states::user::UserActivity> {
+        instructions::get_user_activity(ctx, &owner)
+    }
+
+    /// Freeze a user account, preventing the owner from creating new actions,
+    /// including GLV deposits and withdrawals.
+    ///
+    /// Decrease orders can still be created to close existing positions.
+    ///
+    /// # Accounts
+    /// *[See the documentation for the accounts.](FreezeUserAccount)*
+    ///
+    /// # Arguments
+    /// - `user_address`: The owner of the user account to freeze.
+    /// - `reason_hash`: The hash of the freeze reason.
+    ///
+    /// # Errors
+    /// - The [`authority`](FreezeUserAccount::authority) must be a signer and the current
+    ///   admin of the store.
+    /// - The [`store`](FreezeUserAccount::store) must be properly initialized.
+    /// - The [`user`](FreezeUserAccount::user) account must be:
+    ///   - Properly initialized
+    ///   - Correspond to the `user_address`
+    ///   - Not frozen
+    #[access_control(internal::Authenticate::only_admin(&ctx))]
+    pub fn freeze_user_account(
+        ctx: Context<FreezeUserAccount>,
+        user_address: Pubkey,
+        reason_hash: [u8; 32],
+    ) -> Result<()> {
+        instructions::unchecked_freeze_user_account(ctx, user_address, reason_hash)
+    }
+
+    /// Unfreeze a user account.
+    ///
+    /// # Accounts
+    /// *[See the documentation for the accounts.](FreezeUserAccount)*
+    ///
+    /// # Arguments
+    /// - `user_address`: The owner of the user account to unfreeze.
+    ///
+    /// # Errors
+    /// - The [`authority`](FreezeUserAccount::authority) must be a signer and the current
+    ///   admin of the store.
+    /// - The [`store`](FreezeUserAccount::store) must be properly initialized.
+    /// - The [`user`](FreezeUserAccount::user) account must be:
+    ///   - Correspond to the `user_address`
+    ///   - Frozen
+    #[access_control(internal::Authenticate::only_admin(&ctx))]
+    pub fn unfreeze_user_account(
+        ctx: Context<FreezeUserAccount>,
+        user_address: Pubkey,
+    ) -> Result<()> {
+        instructions::unchecked_unfreeze_user_account(ctx, user_address)
+    }
+
+    // ===========================================
+    //              Liquidity Mining
+    // ===========================================
+
+    /// Initialize a liquidity mining campaign for a market token and deposit the rewards.
+    ///
+    /// # Accounts
+    /// *[See the documentation for the accounts.](InitializeLiquidityMiningCampaign)*
+    ///
+    /// # Arguments
+    /// - `campaign_index`: The index of the campaign. Used to derive the campaign address.
+    /// - `params`: The parameters of the campaign.
+    ///
+    /// # Errors
+    /// - The [`authority`](InitializeLiquidityMiningCampaign::authority) must be a signer and
+    ///   have the ADMIN role in the `store`.
+    /// - The [`market`](InitializeLiquidityMiningCampaign::market) must belong to the `store`
+    ///   and use the [`market_token`](InitializeLiquidityMiningCampaign::market_token).
+    /// - The [`reward_token`](InitializeLiquidityMiningCampaign::reward_token) must not be
+    ///   the market token.
+    /// - The [`campaign`](InitializeLiquidityMiningCampaign::campaign) must be uninitialized.
+    /// - The `end_time` must be greater than the `start_time`, and `total_reward` must be non-zero.
+    /// - The [`reward_source`](InitializeLiquidityMiningCampaign::reward_source) must be owned by
+    ///   the `authority` and hold at least `total_reward` reward tokens.
+    #[access_control(internal::Authenticate::only_admin(&ctx))]
+    pub fn initialize_liquidity_mining_campaign(
+        ctx: Context<InitializeLiquidityMiningCampaign>,
+        campaign_index: u8,
+        params: CampaignParams,
+    ) -> Result<()> {
+        instructions::unchecked_initialize_liquidity_mining_campaign(ctx, campaign_index, &params)
+    }
+
+    /// Stake market tokens into a liquidity mining campaign.
+    ///
+    /// # Accounts
+    /// *[See the documentation for the accounts.](StakeForLiquidityMining)*
+    ///
+    /// # Arguments
+    /// - `campaign_index`: The index of the campaign.
+    /// - `amount`: The amount of market tokens to stake.
+    ///
+    /// # Errors
+    /// - The [`owner`](StakeForLiquidityMining::owner) must be a signer.
+    /// - The [`campaign`](StakeForLiquidityMining::campaign) must belong to the `store` and
+    ///   must not have ended.
+    /// - The `amount` must be non-zero and the [`source`](StakeForLiquidityMining::source)
+    ///   must hold enough market tokens.
+    pub fn stake_for_liquidity_mining(
+        ctx: Context<StakeForLiquidityMining>,
+        _campaign_index: u8,
+        amount: u64,
+    ) -> Result<()> {
+        instructions::stake_for_liquidity_mining(ctx, amount)
+    }
+
+    /// Unstake market tokens from a liquidity mining campaign.
+    ///
+    /// # Accounts
+    /// *[See the documentation for the accounts.](UnstakeFromLiquidityMining)*
+    ///
+    /// # Arguments
+    /// - `campaign_index`: The index of the campaign.
+    /// - `amount`: The amount of market tokens to unstake.
+    ///
+    /// # Errors
+    /// - The [`owner`](UnstakeFromLiquidityMining::owner) must be a signer.
+    /// - The [`checkpoint`](UnstakeFromLiquidityMining::checkpoint) must belong to the `owner`
+    ///   and the `campaign`.
+    /// - The `amount` must be non-zero and must not exceed the staked balance.
+    pub fn unstake_from_liquidity_mining(
+        ctx: Context<UnstakeFromLiquidityMining>,
+        _campaign_index: u8,
+        amount: u64,
+    ) -> Result<()> {
+        instructions::unstake_from_liquidity_mining(ctx, amount)
+    }
+
+    /// Claim the accrued rewards of a liquidity mining campaign.
+    ///
+    /// The rewards are accrued proportionally to the staked balance and the time staked
+    /// within the campaign period, and are capped by the unclaimed rewards of the campaign.
+    ///
+    /// # Accounts
+    /// *[See the documentation for the accounts.](ClaimLiquidityMiningRewards)*
+    ///
+    /// # Arguments
+    /// - `campaign_index`: The index of the campaign.
+    ///
+    /// # Errors
+    /// - The [`owner`](ClaimLiquidityMiningRewards::owner) must be a signer.
+    /// - The [`checkpoint`](ClaimLiquidityMiningRewards::checkpoint) must belong to the `owner`
+    ///   and the `campaign`.
+    /// - The [`reward_vault`](ClaimLiquidityMiningRewards::reward_vault) must be the reward
+    ///   vault of the `campaign`.
+    pub fn claim_liquidity_mining_rewards(
+        ctx: Context<ClaimLiquidityMiningRewards>,
+        _campaign_index: u8,
+    ) -> Result<()> {
+        instructions::claim_liquidity_mining_rewards(ctx)
+    }
+
     // ===========================================
     //                GLV Operations
     // ===========================================
@@ -2919,6 +5140,31 @@ pub mod gmsol_store {
         instructions::unchecked_toggle_glv_market_flag(ctx, &flag, enable)
     }
 
+    /// Set the min amount of a market token the given GLV is targeted to hold.
+    ///
+    /// # Accounts
+    /// *[See the documentation for the accounts.](UpdateGlvMarketConfig)*
+    ///
+    /// # Arguments
+    /// - `min_amount`: The min amount of the market token. Zero means no min allocation.
+    ///
+    /// # Errors
+    /// - The [`authority`](UpdateGlvMarketConfig::authority) must be:
+    ///   - A signer
+    ///   - Have MARKET_KEEPER role in the `store`
+    /// - The [`store`](UpdateGlvMarketConfig::store) must be properly initialized.
+    /// - The [`glv`](UpdateGlvMarketConfig::glv) must be:
+    ///   - Properly initialized
+    ///   - Owned by the `store`
+    ///   - Have the market token in its list of market tokens
+    #[access_control(internal::Authenticate::only_market_keeper(&ctx))]
+    pub fn set_glv_market_min_amount(
+        ctx: Context<UpdateGlvMarketConfig>,
+        min_amount: u64,
+    ) -> Result<()> {
+        instructions::unchecked_set_glv_market_min_amount(ctx, min_amount)
+    }
+
     /// Update GLV config.
     ///
     /// # Accounts
@@ -2997,6 +5243,42 @@ pub mod gmsol_store {
         instructions::unchecked_remove_glv_market(ctx)
     }
 
+    /// Get the current GLV token price based on the provided token prices.
+    ///
+    /// This instruction calculates and returns the current price of the GLV token, which is the
+    /// total value of the market tokens held by the GLV divided by the supply of the GLV token.
+    /// The value of each market token balance is calculated in the same way as the execution
+    /// of GLV actions.
+    ///
+    /// # Accounts
+    /// [*See the documentation for the accounts.*](ReadGlv)
+    ///
+    /// # Arguments
+    /// - `prices`: The current unit prices of tokens for each market in the GLV, in the same
+    ///   order as the market tokens sorted by addresses.
+    /// - `maximize`: If true, uses the maximum possible values in calculations.
+    ///   If false, uses minimum values.
+    ///
+    /// # Errors
+    /// - The [`glv`](ReadGlv::glv) must be an initialized GLV account.
+    /// - The [`glv_token`](ReadGlv::glv_token) must be the GLV token of the `glv`.
+    /// - The remaining accounts must be the markets and market tokens of the `glv`.
+    ///   See the documentation of [`ReadGlv`] for details.
+    /// - The number of `prices` must match the number of markets in the `glv`.
+    /// - The provided prices must be non-zero for the markets with non-zero balance.
+    /// - Any calculation errors.
+    ///
+    /// # Returns
+    /// Returns the price of one GLV token. Returns one USD if the supply of the GLV
+    /// token is zero.
+    pub fn get_glv_token_price<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ReadGlv<'info>>,
+        prices: Vec<Prices<u128>>,
+        maximize: bool,
+    ) -> Result<u128> {
+        instructions::get_glv_token_price(ctx, &prices, maximize)
+    }
+
     /// Create GLV deposit.
     ///
     /// # Accounts
@@ -3014,6 +5296,8 @@ pub mod gmsol_store {
     ///   - Properly initialized
     ///   - Owned by the `store`
     ///   - Listed in the [`glv`](CreateGlvDeposit::glv)
+    /// - The [`user`](CreateGlvDeposit::user) must be the PDA of the user account of the `owner`
+    ///   and not be frozen. It will be initialized if needed.
     /// - The [`glv`](CreateGlvDeposit::glv) must be:
     ///   - Properly initialized
     ///   - Owned by the `store`
@@ -3021,6 +5305,11 @@ pub mod gmsol_store {
     ///   - Uninitialized
     ///   - Address must be PDA derived from the SEED of [`GlvDeposit`](states::GlvDeposit),
     ///     [`store`](CreateGlvDeposit::store), [`owner`](CreateGlvDeposit::owner) and `nonce`
+    /// - The [`glv_token_ata`](CreateGlvDeposit::glv_token_ata) must be the GLV token ATA
+    ///   of the `owner`.
+    /// - The [`glv_deposit_cooldown`](CreateGlvDeposit::glv_deposit_cooldown) must be
+    ///   a PDA derived from the SEED of [`GlvDepositCooldown`](states::GlvDepositCooldown),
+    ///   the `glv` and the `glv_token_ata`. It will be initialized if needed.
     /// - The [`glv_token`](CreateGlvDeposit::glv_token) must be:
     ///   - Properly initialized
     ///   - Correspond to the provided [`glv`](CreateGlvDeposit::glv)
@@ -3097,7 +5386,7 @@ pub mod gmsol_store {
     /// - The [`store`](ExecuteGlvDeposit::store) must be properly initialized
     /// - The [`token_map`](ExecuteGlvDeposit::token_map) must be:
     ///   - Properly initialized
-    ///   - Authorized by the `store`
+    ///   - The token map to use for the `market`
     /// - The [`oracle`](ExecuteGlvDeposit::oracle) must be:
     ///   - Cleared
     ///   - Owned by the `store`
@@ -3114,6 +5403,9 @@ pub mod gmsol_store {
     ///   - Properly initialized
     ///   - Owned by the `store`
     ///   - In pending state
+    /// - The [`glv_deposit_cooldown`](ExecuteGlvDeposit::glv_deposit_cooldown) must be
+    ///   a PDA derived from the SEED of [`GlvDepositCooldown`](states::GlvDepositCooldown),
+    ///   the `glv` and the GLV token ATA of the owner of the `glv_deposit`.
     /// - Token requirements:
     ///   - All tokens must be valid and recorded in the [`glv_deposit`](ExecuteGlvDeposit::glv_deposit)
     ///   - [`glv_token`](ExecuteGlvDeposit::glv_token) must be the GLV token of the [`glv`](ExecuteGlvDeposit::glv)
@@ -3166,9 +5458,16 @@ pub mod gmsol_store {
     ///   - Enabled
     ///   - Owned by the `store`
     ///   - One of the markets in the [`glv`](CreateGlvWithdrawal::glv)
+    /// - The [`user`](CreateGlvWithdrawal::user) must be the PDA of the user account of the `owner`
+    ///   and not be frozen. It will be initialized if needed.
     /// - The [`glv`](CreateGlvWithdrawal::glv) must be:
     ///   - Properly initialized
     ///   - Owned by the `store`
+    /// - The [`glv_deposit_cooldown`](CreateGlvWithdrawal::glv_deposit_cooldown) must be
+    ///   a PDA derived from the SEED of [`GlvDepositCooldown`](states::GlvDepositCooldown),
+    ///   the `glv` and the `glv_token_source`.
+    /// - The withdrawal cooldown of the `glv` must have passed since the last GLV deposit
+    ///   recorded for the `glv_token_source`.
     /// - The [`glv_withdrawal`](CreateGlvWithdrawal::glv_withdrawal) must be:
     ///   - Uninitialized
     ///   - A PDA derived from:
@@ -3194,6 +5493,11 @@ pub mod gmsol_store {
     ///   - Must correspond to their respective tokens
     ///   - Must be owned by the [`glv_withdrawal`](CreateGlvWithdrawal::glv_withdrawal)
     /// - All token programs must match their corresponding token accounts
+    ///
+    /// # Notes
+    /// - The withdrawal cooldown is only recorded for the GLV token ATA of the owner when a GLV
+    ///   deposit made by the owner to itself is executed. GLV tokens delivered to other receivers
+    ///   or transferred to other token accounts can be withdrawn without waiting for the cooldown.
     pub fn create_glv_withdrawal<'info>(
         mut ctx: Context<'_, '_, 'info, 'info, CreateGlvWithdrawal<'info>>,
         nonce: [u8; 32],
@@ -3254,7 +5558,7 @@ pub mod gmsol_store {
     /// - The [`store`](ExecuteGlvWithdrawal::store) must be properly initialized
     /// - The [`token_map`](ExecuteGlvWithdrawal::token_map) must be:
     ///   - Properly initialized
-    ///   - Authorized by the `store`
+    ///   - The token map to use for the `market`
     /// - The [`oracle`](ExecuteGlvWithdrawal::oracle) must be:
     ///   - Cleared
     ///   - Owned by the `store`
@@ -3353,6 +5657,42 @@ pub mod gmsol_store {
         internal::Create::create(&mut ctx, &nonce, &params)
     }
 
+    /// Create a batch of GLV shifts in one instruction.
+    ///
+    /// The nonces of the GLV shifts are derived from the batch nonce stored in the GLV,
+    /// which is advanced after each call. See
+    /// [`Glv::batch_shift_nonce`](states::Glv::batch_shift_nonce) for details.
+    ///
+    /// # Accounts
+    /// *[See the documentation for the accounts.](BatchCreateGlvShifts)*
+    ///
+    /// # Arguments
+    /// - `params`: The parameters for creating each GLV shift, in the same order as
+    ///   the groups of remaining accounts.
+    ///
+    /// # Errors
+    /// - The [`authority`](BatchCreateGlvShifts::authority) must be:
+    ///   - A signer
+    ///   - A `ORDER_KEEPER` in the `store`
+    /// - The [`store`](BatchCreateGlvShifts::store) must be properly initialized
+    /// - The [`glv`](BatchCreateGlvShifts::glv) must be:
+    ///   - Properly initialized
+    ///   - Owned by the `store`
+    /// - The number of GLV shifts must be non-zero and must not exceed
+    ///   [`MAX_SHIFTS_PER_BATCH`](states::Glv::MAX_SHIFTS_PER_BATCH).
+    /// - For each GLV shift:
+    ///   - The from and to markets must be different markets of the GLV.
+    ///   - The vaults must be the market token vaults owned by the GLV.
+    ///   - The GLV shift account must be the uninitialized PDA derived from the batch nonce.
+    ///   - The `from_market_token_amount` must be non-zero.
+    #[access_control(internal::Authenticate::only_order_keeper(&ctx))]
+    pub fn batch_create_glv_shifts<'info>(
+        ctx: Context<'_, '_, 'info, 'info, BatchCreateGlvShifts<'info>>,
+        params: Vec<CreateShiftParams>,
+    ) -> Result<()> {
+        instructions::unchecked_batch_create_glv_shifts(ctx, &params)
+    }
+
     /// Close a GLV shift.
     ///
     /// # Accounts
@@ -3403,7 +5743,7 @@ pub mod gmsol_store {
     /// - The [`store`](ExecuteGlvShift::store) must be properly initialized
     /// - The [`token_map`](ExecuteGlvShift::token_map) must be:
     ///   - Properly initialized
-    ///   - Authorized by the `store`
+    ///   - The token map to use for both the `from_market` and the `to_market`
     /// - The [`oracle`](ExecuteGlvShift::oracle) must be:
     ///   - Cleared
     ///   - Owned by the `store`
@@ -3451,6 +5791,132 @@ pub mod gmsol_store {
         instructions::unchecked_execute_glv_shift(ctx, execution_lamports, throw_on_execution_error)
     }
 
+    /// Initialize the treasury of a GLV.
+    ///
+    /// # Accounts
+    /// *[See the documentation for the accounts.](InitializeGlvTreasury)*
+    ///
+    /// # Arguments
+    /// - `max_rebalance_value_per_day`: The max value (in USD) of tokens that can be used
+    ///   for automatic rebalancing in a day.
+    ///
+    /// # Errors
+    /// - The [`authority`](InitializeGlvTreasury::authority) must be a signer and have
+    ///   MARKET_KEEPER role in the `store`.
+    /// - The [`store`](InitializeGlvTreasury::store) must be properly initialized.
+    /// - The [`glv`](InitializeGlvTreasury::glv) must be owned by the `store`.
+    /// - The [`glv_treasury`](InitializeGlvTreasury::glv_treasury) must be uninitialized
+    ///   and derived from the `store` and the `glv`.
+    #[access_control(internal::Authenticate::only_market_keeper(&ctx))]
+    pub fn initialize_glv_treasury(
+        ctx: Context<InitializeGlvTreasury>,
+        max_rebalance_value_per_day: u128,
+    ) -> Result<()> {
+        instructions::unchecked_initialize_glv_treasury(ctx, max_rebalance_value_per_day)
+    }
+
+    /// Update the treasury of a GLV.
+    ///
+    /// # Accounts
+    /// *[See the documentation for the accounts.](UpdateGlvTreasury)*
+    ///
+    /// # Arguments
+    /// - `max_rebalance_value_per_day`: The max value (in USD) of tokens that can be used
+    ///   for automatic rebalancing in a day.
+    ///
+    /// # Errors
+    /// - The [`authority`](UpdateGlvTreasury::authority) must be a signer and have
+    ///   MARKET_KEEPER role in the `store`.
+    /// - The [`store`](UpdateGlvTreasury::store) must be properly initialized.
+    /// - The [`glv_treasury`](UpdateGlvTreasury::glv_treasury) must be owned by the `store`.
+    #[access_control(internal::Authenticate::only_market_keeper(&ctx))]
+    pub fn update_glv_treasury(
+        ctx: Context<UpdateGlvTreasury>,
+        max_rebalance_value_per_day: u128,
+    ) -> Result<()> {
+        instructions::unchecked_update_glv_treasury(ctx, max_rebalance_value_per_day)
+    }
+
+    /// Withdraw tokens from the treasury of a GLV.
+    ///
+    /// # Accounts
+    /// *[See the documentation for the accounts.](WithdrawFromGlvTreasury)*
+    ///
+    /// # Arguments
+    /// - `amount`: The amount of tokens to withdraw.
+    ///
+    /// # Errors
+    /// - The [`authority`](WithdrawFromGlvTreasury::authority) must be a signer and be
+    ///   the ADMIN of the `store`.
+    /// - The [`store`](WithdrawFromGlvTreasury::store) must be properly initialized.
+    /// - The [`glv_treasury`](WithdrawFromGlvTreasury::glv_treasury) must be owned by the `store`.
+    /// - The [`vault`](WithdrawFromGlvTreasury::vault) must be the ATA of the `glv_treasury`
+    ///   for the [`token`](WithdrawFromGlvTreasury::token) and have enough tokens.
+    /// - The [`target`](WithdrawFromGlvTreasury::target) must be a token account of the `token`
+    ///   different from the `vault`.
+    /// - `amount` must not be zero.
+    #[access_control(internal::Authenticate::only_admin(&ctx))]
+    pub fn withdraw_from_glv_treasury(
+        ctx: Context<WithdrawFromGlvTreasury>,
+        amount: u64,
+    ) -> Result<()> {
+        instructions::unchecked_withdraw_from_glv_treasury(ctx, amount)
+    }
+
+    /// Trigger a deposit funded by the GLV treasury into a GLV market that is
+    /// below its min allocation.
+    ///
+    /// The created GLV deposit is owned by the treasury and is executed
+    /// with [`execute_glv_deposit`], which credits the minted market tokens to the GLV.
+    ///
+    /// # Accounts
+    /// *[See the documentation for the accounts.](AutoRebalanceDeposit)*
+    ///
+    /// # Arguments
+    /// - `market_token`: The market token of the market to rebalance.
+    /// - `amount`: The amount of initial tokens to deposit.
+    /// - `min_market_token_amount`: The min amount of market tokens to receive.
+    /// - `min_glv_token_amount`: The min amount of GLV tokens to receive.
+    ///
+    /// # Errors
+    /// - The [`authority`](AutoRebalanceDeposit::authority) must be a signer and have
+    ///   ORDER_KEEPER role in the `store`.
+    /// - The [`store`](AutoRebalanceDeposit::store) must be properly initialized,
+    ///   and GLV deposit creation must be enabled.
+    /// - The [`token_map`](AutoRebalanceDeposit::token_map) must be resolved from the `market`.
+    /// - The [`oracle`](AutoRebalanceDeposit::oracle) must be owned by the `store` and cleared.
+    /// - The [`market`](AutoRebalanceDeposit::market) must be enabled, owned by the `store`
+    ///   and use `market_token` as its market token.
+    /// - The [`glv`](AutoRebalanceDeposit::glv) must be owned by the `store` and contain the market,
+    ///   whose balance must be below its min amount and deposits must be allowed.
+    /// - The [`treasury_account`](AutoRebalanceDeposit::treasury_account) must be the treasury
+    ///   of the `glv`, and the value of `amount` (valued with the max price of the initial token)
+    ///   must not exceed its remaining daily limit.
+    /// - The [`initial_token`](AutoRebalanceDeposit::initial_token) must be the long token or
+    ///   the short token of the market.
+    /// - The [`treasury_vault`](AutoRebalanceDeposit::treasury_vault) must have enough tokens.
+    /// - The [`glv_deposit`](AutoRebalanceDeposit::glv_deposit) must be uninitialized and derived
+    ///   from the `treasury_account` and its next rebalance deposit nonce.
+    /// - The escrow accounts must be the ATAs owned by the `glv_deposit`.
+    /// - The feed account for the initial token must be provided in the remaining accounts
+    ///   and be valid.
+    #[access_control(internal::Authenticate::only_order_keeper(&ctx))]
+    pub fn trigger_automatic_rebalance_deposit<'info>(
+        ctx: Context<'_, '_, 'info, 'info, AutoRebalanceDeposit<'info>>,
+        market_token: Pubkey,
+        amount: u64,
+        min_market_token_amount: u64,
+        min_glv_token_amount: u64,
+    ) -> Result<()> {
+        instructions::unchecked_trigger_automatic_rebalance_deposit(
+            ctx,
+            &market_token,
+            amount,
+            min_market_token_amount,
+            min_glv_token_amount,
+        )
+    }
+
     #[access_control(internal::Authenticate::only_migration_keeper(&ctx))]
     pub fn migrate_referral_code<'info>(
         ctx: Context<'_, '_, 'info, 'info, MigrateReferralCode<'info>>,
@@ -3469,6 +5935,7 @@ pub mod gmsol_store {
 pub type CoreResult<T> = std::result::Result<T, CoreError>;
 
 #[error_code]
+#[derive(num_enum::TryFromPrimitive)]
 pub enum CoreError {
     // ===========================================
     //                Common Errors
@@ -3879,6 +6346,93 @@ pub enum CoreError {
     /// Price is stale.
     #[msg("Price is stale")]
     PriceIsStale,
+    /// Emergency mode is not enabled.
+    #[msg("emergency mode is not enabled")]
+    EmergencyModeNotEnabled,
+    /// User account is frozen.
+    #[msg("user account is frozen")]
+    UserAccountFrozen,
+    /// Insufficient balance of the referral reward vault.
+    #[msg("insufficient balance of the referral reward vault")]
+    InsufficientReferralRewardVaultBalance,
+    /// Withdrawal cooldown not yet passed.
+    #[msg("GLV: withdrawal cooldown not yet passed")]
+    GlvWithdrawalCooldownNotPassed,
+    /// Invalid virtual pool.
+    #[msg("virtual pool is not provided or does not match the market")]
+    InvalidVirtualPool,
+    /// Token map mismatched.
+    #[msg("token map mismatched")]
+    TokenMapMismatched,
+    /// Deposit value is below the minimum.
+    #[msg("deposit value is below the minimum")]
+    DepositValueTooSmall,
+    /// Config snapshot not found.
+    #[msg("config snapshot not found or evicted")]
+    ConfigSnapshotNotFound,
+    /// Oracle price is outside the configured price band.
+    #[msg("oracle price is outside the configured price band")]
+    OraclePriceOutsideBand,
+    /// Swap impact pool withdrawal factor exceeded.
+    #[msg("the swap impact pool exceeds the allowed factor of the remaining liquidity")]
+    SwapImpactPoolWithdrawalFactorExceeded,
+    /// Untrusted Chainlink verifier.
+    #[msg("the chainlink verifier program is not trusted by the store")]
+    UntrustedChainlinkVerifier,
+    /// Oracle is decommissioned.
+    #[msg("the oracle is decommissioned")]
+    OracleDecommissioned,
+    /// Position size delta is not a multiple of the position size increment.
+    #[msg("the size delta is not a multiple of the position size increment")]
+    PositionSizeBelowIncrement,
+    /// The order is assigned to another keeper.
+    #[msg("the order is assigned to another keeper")]
+    OrderAssignedToAnotherKeeper,
+    /// The size delta exceeds the max trade size of the market.
+    #[msg("the size delta exceeds the max trade size of the market")]
+    TradeSizeExceedsLimit,
+    /// The adjustment of the OI caps exceeds the rate limit.
+    #[msg("the adjustment of the OI caps exceeds the daily rate limit")]
+    OpenInterestCapAdjustmentRateLimited,
+    /// Oracle price deviates too much from the last recorded price.
+    #[msg("oracle price deviates too much from the last recorded price")]
+    OraclePriceDeviationExceeded,
+    /// The GLV market is not below its min allocation.
+    #[msg("GLV: the market is not below its min allocation")]
+    GlvMarketNotBelowMinAllocation,
+    /// The rebalance value exceeds the daily limit of the GLV treasury.
+    #[msg("GLV: the rebalance value exceeds the daily limit")]
+    GlvRebalanceAmountExceedsDailyLimit,
+    /// The accounts required by the order callback are not provided or mismatched.
+    #[msg("order callback accounts are not provided or mismatched")]
+    OrderCallbackAccountsNotProvided,
+    /// Not enough compute units left for the order callback.
+    #[msg("not enough compute units left for the order callback")]
+    NotEnoughComputeUnitsForOrderCallback,
+    /// The order callback consumed more compute units than allowed.
+    #[msg("the order callback consumed more compute units than allowed")]
+    OrderCallbackComputeUnitsExceeded,
+    /// Invalid margin mode.
+    #[msg("invalid margin mode")]
+    InvalidMarginMode,
+    /// The margin mode does not match the position's.
+    #[msg("the margin mode does not match the position's")]
+    MarginModeMismatched,
+    /// The user has created too many actions in the current slot.
+    #[msg("user trade rate limit exceeded")]
+    UserTradeRateLimitExceeded,
+    /// The market has too many pending actions.
+    #[msg("market depth limit exceeded")]
+    MarketDepthLimitExceeded,
+    /// The last oracle price account of the token is not provided.
+    #[msg("the last oracle price account of the token is not provided")]
+    LastOraclePriceNotProvided,
+    /// The order callback is not pending.
+    #[msg("the order callback is not pending")]
+    OrderCallbackNotPending,
+    /// The provided execution result does not match the pending order callback.
+    #[msg("the execution result does not match the pending order callback")]
+    OrderCallbackResultMismatched,
 }
 
 impl CoreError {
@@ -3897,4 +6451,8 @@ impl CoreError {
     pub(crate) const fn invalid_position_kind(_kind: u8) -> Self {
         Self::InvalidPositionKind
     }
+
+    pub(crate) const fn invalid_margin_mode(_mode: u8) -> Self {
+        Self::InvalidMarginMode
+    }
 }
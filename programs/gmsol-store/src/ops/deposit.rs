@@ -3,12 +3,13 @@ use anchor_spl::token::{Mint, TokenAccount};
 use typed_builder::TypedBuilder;
 
 use crate::{
-    events::EventEmitter,
+    events::{DustDepositCancelled, EventEmitter},
     ops::market::RevertibleLiquidityMarketOperation,
     states::{
         common::action::{Action, ActionExt, ActionParams},
+        deposit::DepositActionParams,
         market::revertible::Revertible,
-        Deposit, Market, NonceBytes, Oracle, Store, ValidateOracleTime,
+        Deposit, FactorKey, Market, NonceBytes, Oracle, Store, ValidateOracleTime,
     },
     CoreError, CoreResult,
 };
@@ -60,7 +61,11 @@ pub(crate) struct CreateDepositOperation<'a, 'info> {
 impl CreateDepositOperation<'_, '_> {
     /// Execute.
     pub(crate) fn execute(self) -> Result<()> {
-        self.market.load()?.validate(&self.store.key())?;
+        {
+            let market = self.market.load()?;
+            market.validate(&self.store.key())?;
+            market.validate_trading_window(Clock::get()?.unix_timestamp)?;
+        }
         self.validate_params_excluding_swap()?;
 
         let Self {
@@ -182,6 +187,52 @@ impl CreateDepositOperation<'_, '_> {
     }
 }
 
+/// Returns the total value of the deposit in USD if it is below the minimum
+/// configured in the store, or `None` otherwise.
+///
+/// The minimum of a deposit is the lowest of the minimums of the sides it
+/// provides, and is compared against the combined value of both sides.
+pub(crate) fn dust_deposit_value(
+    store: &Store,
+    oracle: &Oracle,
+    params: &DepositActionParams,
+    (initial_long_token, initial_short_token): (Option<Pubkey>, Option<Pubkey>),
+) -> Result<Option<u128>> {
+    let sides = [
+        (
+            params.initial_long_token_amount,
+            initial_long_token,
+            FactorKey::MinDepositLongUsd,
+        ),
+        (
+            params.initial_short_token_amount,
+            initial_short_token,
+            FactorKey::MinDepositShortUsd,
+        ),
+    ];
+
+    let mut value_usd = 0u128;
+    let mut threshold: Option<u128> = None;
+    for (amount, token, key) in sides {
+        if amount == 0 {
+            continue;
+        }
+        let token = token.ok_or_else(|| error!(CoreError::TokenAccountNotProvided))?;
+        let price = oracle.get_primary_price(&token, false)?.min;
+        let value = u128::from(amount)
+            .checked_mul(price)
+            .ok_or_else(|| error!(CoreError::ValueOverflow))?;
+        value_usd = value_usd
+            .checked_add(value)
+            .ok_or_else(|| error!(CoreError::ValueOverflow))?;
+        let min_value = *store.get_factor_by_key(key);
+        threshold = Some(threshold.map_or(min_value, |threshold| threshold.min(min_value)));
+    }
+
+    let is_dust = threshold.is_some_and(|threshold| value_usd < threshold);
+    Ok(is_dust.then_some(value_usd))
+}
+
 /// Operation for executing a deposit.
 #[derive(TypedBuilder)]
 pub(crate) struct ExecuteDepositOperation<'a, 'info> {
@@ -217,6 +268,17 @@ impl ExecuteDepositOperation<'_, '_> {
                 return Err(error!(err));
             }
         }
+        if let Some(value_usd) = self.dust_value()? {
+            if throw_on_execution_error {
+                return err!(CoreError::DepositValueTooSmall);
+            }
+            msg!("Deposit value is below the minimum: {}", value_usd);
+            self.event_emitter.emit_cpi(&DustDepositCancelled {
+                deposit: self.deposit.key(),
+                value_usd,
+            })?;
+            return Ok(false);
+        }
         match self.perfrom_deposit() {
             Ok(()) => Ok(true),
             Err(err) if !throw_on_execution_error => {
@@ -231,6 +293,19 @@ impl ExecuteDepositOperation<'_, '_> {
         self.oracle.validate_time(self)
     }
 
+    fn dust_value(&self) -> Result<Option<u128>> {
+        let deposit = self.deposit.load()?;
+        dust_deposit_value(
+            &*self.store.load()?,
+            self.oracle,
+            &deposit.params,
+            (
+                deposit.tokens.initial_long_token.token(),
+                deposit.tokens.initial_short_token.token(),
+            ),
+        )
+    }
+
     fn validate_before_execution(&self) -> Result<()> {
         let market = self.market.load()?;
         market.validate(&self.store.key())?;
@@ -10,7 +10,7 @@ use typed_builder::TypedBuilder;
 
 use crate::{
     constants,
-    events::{EventEmitter, GlvPricing, GlvPricingKind},
+    events::{DustDepositCancelled, EventEmitter, GlvPricing, GlvPricingKind},
     states::{
         common::action::{Action, ActionExt, ActionParams, ActionSigner},
         glv::{GlvShift, GlvWithdrawal},
@@ -277,6 +277,17 @@ impl ExecuteGlvDepositOperation<'_, '_> {
                 return Err(error!(err));
             }
         }
+        if let Some(value_usd) = self.dust_value()? {
+            if throw_on_execution_error {
+                return err!(CoreError::DepositValueTooSmall);
+            }
+            msg!("GLV deposit value is below the minimum: {}", value_usd);
+            self.event_emitter.emit_cpi(&DustDepositCancelled {
+                deposit: self.glv_deposit.key(),
+                value_usd,
+            })?;
+            return Ok(false);
+        }
         let executed = match self.perform_glv_deposit() {
             Ok(()) => true,
             Err(err) if !throw_on_execution_error => {
@@ -295,6 +306,19 @@ impl ExecuteGlvDepositOperation<'_, '_> {
         self.oracle.validate_time(self)
     }
 
+    fn dust_value(&self) -> Result<Option<u128>> {
+        let glv_deposit = self.glv_deposit.load()?;
+        super::deposit::dust_deposit_value(
+            &*self.store.load()?,
+            self.oracle,
+            &glv_deposit.params.deposit,
+            (
+                glv_deposit.tokens.initial_long_token.token(),
+                glv_deposit.tokens.initial_short_token.token(),
+            ),
+        )
+    }
+
     fn validate_before_execution(&self) -> Result<()> {
         let market = self.market.load()?;
         market.validate(&self.store.key())?;
@@ -1100,7 +1124,7 @@ where
     get_glv_value_for_market(prices, market, balance, maximize)
 }
 
-fn get_glv_value_for_market<M>(
+pub(crate) fn get_glv_value_for_market<M>(
     prices: &Prices<u128>,
     market: &M,
     balance: u128,
@@ -1,13 +1,13 @@
 use anchor_lang::prelude::*;
 use anchor_spl::token::{Mint, TokenAccount};
 use gmsol_model::{
-    price::Prices, Bank, BorrowingFeeMarketMutExt, LiquidityMarketMutExt, MarketAction,
-    PerpMarketMutExt, PositionImpactMarketMutExt,
+    price::Prices, Balance, BalanceExt, Bank, BaseMarket, BorrowingFeeMarketMutExt,
+    LiquidityMarketMutExt, MarketAction, PerpMarketMutExt, PositionImpactMarketMutExt,
 };
 use typed_builder::TypedBuilder;
 
 use crate::{
-    events::{DepositExecuted, EventEmitter, MarketFeesUpdated, WithdrawalExecuted},
+    events::{DepositExecuted, EventEmitter, KeeperTipPaid, MarketFeesUpdated, WithdrawalExecuted},
     states::{
         common::swap::SwapActionParams,
         deposit::DepositActionParams,
@@ -123,6 +123,151 @@ impl MarketTransferOutOperation<'_, '_> {
     }
 }
 
+/// The amounts of the claimable fee pool of a market, taken before the execution
+/// of an action to compute the fees of the action.
+#[derive(Clone, Copy)]
+pub(crate) struct ClaimableFeeSnapshot {
+    long_amount: u128,
+    short_amount: u128,
+}
+
+impl ClaimableFeeSnapshot {
+    /// Take a snapshot of the claimable fee pool of the given market.
+    pub(crate) fn take(market: &AccountLoader<Market>) -> Result<Self> {
+        let market = market.load()?;
+        let pool = market.claimable_fee_pool().map_err(ModelError::from)?;
+        Ok(Self {
+            long_amount: pool.long_amount().map_err(ModelError::from)?,
+            short_amount: pool.short_amount().map_err(ModelError::from)?,
+        })
+    }
+
+    fn amount(&self, is_long_token: bool) -> u128 {
+        if is_long_token {
+            self.long_amount
+        } else {
+            self.short_amount
+        }
+    }
+}
+
+/// Operation for paying the keeper tip from the claimable fee pool.
+///
+/// The tip of each side is a fraction of the fees collected by the claimable fee pool
+/// since the [`ClaimableFeeSnapshot`] was taken. It is only paid if the receiver for
+/// the side and the market vault of the token are both provided.
+#[derive(TypedBuilder)]
+pub(crate) struct PayKeeperTipOperation<'a, 'info> {
+    store: &'a AccountLoader<'info, Store>,
+    market: &'a AccountLoader<'info, Market>,
+    keeper: &'a Pubkey,
+    snapshot: ClaimableFeeSnapshot,
+    long_token_receiver: Option<&'a Account<'info, TokenAccount>>,
+    short_token_receiver: Option<&'a Account<'info, TokenAccount>>,
+    /// Market vaults that can be used to pay the tips, with their token mints.
+    vaults: Vec<(&'a Account<'info, Mint>, &'a Account<'info, TokenAccount>)>,
+    token_program: AccountInfo<'info>,
+    #[builder(setter(into))]
+    event_emitter: EventEmitter<'a, 'info>,
+}
+
+impl<'a, 'info> PayKeeperTipOperation<'a, 'info> {
+    /// Pay the keeper tips, and return the tip amounts for the long token and short token.
+    pub(crate) fn execute(self) -> Result<(u64, u64)> {
+        let long_tip = self.pay(true, self.long_token_receiver)?;
+        let short_tip = self.pay(false, self.short_token_receiver)?;
+        Ok((long_tip, short_tip))
+    }
+
+    fn pay(
+        &self,
+        is_long_token: bool,
+        receiver: Option<&'a Account<'info, TokenAccount>>,
+    ) -> Result<u64> {
+        use gmsol_model::{num::Unsigned, BaseMarketMut, Pool};
+
+        let Some(receiver) = receiver else {
+            return Ok(0);
+        };
+
+        let (market_token, token, fee_amount, tip) = {
+            let market = self.market.load()?;
+            let meta = market.validated_meta(&self.store.key())?;
+            let token = if is_long_token {
+                meta.long_token_mint
+            } else {
+                meta.short_token_mint
+            };
+            require_keys_eq!(receiver.mint, token, CoreError::TokenMintMismatched);
+            let fee_amount = market
+                .claimable_fee_pool()
+                .and_then(|pool| pool.amount(is_long_token))
+                .map_err(ModelError::from)?
+                .saturating_sub(self.snapshot.amount(is_long_token));
+            let tip: u64 = market
+                .keeper_tip(fee_amount)?
+                .min(u128::from(u64::MAX))
+                .try_into()
+                .expect("must success");
+            (meta.market_token_mint, token, fee_amount, tip)
+        };
+
+        if tip == 0 {
+            return Ok(0);
+        }
+
+        let Some((token_mint, vault)) = self
+            .vaults
+            .iter()
+            .find(|(mint, vault)| mint.key() == token && vault.mint == token)
+        else {
+            return Ok(0);
+        };
+
+        {
+            let mut market = RevertibleMarket::new(self.market, self.event_emitter)?;
+            let delta = u128::from(tip)
+                .to_opposite_signed()
+                .map_err(ModelError::from)?;
+            market
+                .claimable_fee_pool_mut()
+                .and_then(|pool| pool.apply_delta_amount(is_long_token, &delta))
+                .map_err(ModelError::from)?;
+            market.commit();
+        }
+
+        MarketTransferOutOperation::builder()
+            .store(self.store)
+            .market(self.market)
+            .amount(tip)
+            .decimals(token_mint.decimals)
+            .to(receiver.to_account_info())
+            .token_mint(token_mint.to_account_info())
+            .vault(vault.to_account_info())
+            .token_program(self.token_program.clone())
+            .event_emitter(self.event_emitter)
+            .build()
+            .execute()?;
+
+        self.event_emitter.emit_cpi(&KeeperTipPaid {
+            market_token,
+            keeper: *self.keeper,
+            token,
+            amount: tip,
+            fee_amount,
+        })?;
+
+        msg!(
+            "[Keeper Tip] paid `{}` {} to the keeper {}",
+            tip,
+            token,
+            self.keeper
+        );
+
+        Ok(tip)
+    }
+}
+
 /// Revertible Liquidity Market Operation.
 pub struct RevertibleLiquidityMarketOperation<'a, 'info> {
     store: &'a AccountLoader<'info, Store>,
@@ -419,6 +564,9 @@ impl<'a, 'info, T> Execute<'a, 'info, T> {
             // Validate current market.
             self.market
                 .validate_market_balances(long_amount, short_amount)?;
+            self.market
+                .base()
+                .validate_swap_impact_pool_for_withdrawal()?;
 
             self.event_emitter
                 .emit_cpi(&WithdrawalExecuted::from_report(
@@ -11,7 +11,11 @@ use gmsol_model::{
 use typed_builder::TypedBuilder;
 
 use crate::{
-    events::{EventEmitter, MarketFeesUpdated, PositionDecreased, PositionIncreased, TradeData},
+    constants,
+    events::{
+        EventEmitter, MarketFeesUpdated, OrderCallbackPending, PositionDecreased,
+        PositionIncreased, TradeData,
+    },
     states::{
         common::action::{Action, ActionExt, ActionParams},
         market::{
@@ -23,10 +27,13 @@ use crate::{
             },
             utils::{Adl, ValidateMarketBalances},
         },
-        order::{Order, OrderActionParams, OrderKind, OrderTokenAccounts, TransferOut},
-        position::PositionKind,
+        order::{
+            Order, OrderActionParams, OrderCallbackArgs, OrderKind, OrderTokenAccounts, TransferOut,
+        },
+        position::{MarginMode, PositionKind},
         user::UserHeader,
         AmountKey, HasMarketMeta, Market, NonceBytes, Oracle, Position, Store, ValidateOracleTime,
+        VirtualPool,
     },
     CoreError, ModelError,
 };
@@ -63,6 +70,31 @@ pub struct CreateOrderParams {
     pub should_unwrap_native_token: bool,
     /// Valid from timestamp.
     pub valid_from_ts: Option<i64>,
+    /// The keeper assigned to execute the order.
+    ///
+    /// Other keepers can only execute the order after the keeper assignment
+    /// timeout has passed.
+    pub assigned_keeper: Option<Pubkey>,
+    /// The number of seconds after which the order can be executed as a market order
+    /// even if it has not been triggered (limit increase / limit decrease orders only).
+    ///
+    /// The window starts at the last update of the order (or `valid_from_ts` if later).
+    /// An acceptable price must be provided when it is set.
+    pub fallback_to_market_after_secs: Option<u32>,
+    /// The program to invoke after the order is executed successfully.
+    ///
+    /// See [`OrderCallbackArgs`](crate::states::order::OrderCallbackArgs) for the
+    /// callback interface.
+    pub callback_program: Option<Pubkey>,
+    /// The account passed to the callback program. Required if `callback_program` is set.
+    pub callback_account: Option<Pubkey>,
+    /// The data passed to the callback program.
+    pub callback_data: Option<[u8; 32]>,
+    /// The margin mode of the position.
+    ///
+    /// Defaults to the margin mode of the position, or [`MarginMode::Isolated`]
+    /// for a new position.
+    pub margin_mode: Option<MarginMode>,
 }
 
 impl ActionParams for CreateOrderParams {
@@ -146,7 +178,9 @@ impl<'a, 'info> CreateOrderOperation<'a, 'info> {
     }
 
     fn validate(&self) -> Result<()> {
-        self.market.load()?.validate(&self.store.key())?;
+        let market = self.market.load()?;
+        market.validate(&self.store.key())?;
+        market.validate_trading_window(Clock::get()?.unix_timestamp)?;
         ActionExt::validate_balance(&self.order, self.params.execution_lamports)?;
         Ok(())
     }
@@ -168,6 +202,7 @@ impl<'a, 'info> CreateOrderOperation<'a, 'info> {
                 tokens,
                 params,
                 swap,
+                callback,
                 ..
             } = &mut *order;
 
@@ -191,6 +226,48 @@ impl<'a, 'info> CreateOrderOperation<'a, 'info> {
 
             let (from, to) = (f)(self.params, tokens, params)?;
 
+            if let Some(keeper) = self.params.assigned_keeper {
+                params.assigned_keeper = keeper;
+            }
+
+            if let Some(secs) = self.params.fallback_to_market_after_secs {
+                require!(
+                    matches!(
+                        self.params.kind,
+                        OrderKind::LimitIncrease | OrderKind::LimitDecrease
+                    ),
+                    CoreError::InvalidArgument
+                );
+                require!(secs != 0, CoreError::InvalidArgument);
+                require!(
+                    self.params.acceptable_price.is_some(),
+                    CoreError::InvalidArgument
+                );
+                params.fallback_to_market_after_secs = secs;
+            }
+
+            match self.params.callback_program {
+                Some(program) => {
+                    require_keys_neq!(program, crate::ID, CoreError::InvalidArgument);
+                    let account = self
+                        .params
+                        .callback_account
+                        .ok_or_else(|| error!(CoreError::InvalidArgument))?;
+                    callback.init(
+                        program,
+                        account,
+                        self.params.callback_data.unwrap_or_default(),
+                    );
+                }
+                None => {
+                    require!(
+                        self.params.callback_account.is_none()
+                            && self.params.callback_data.is_none(),
+                        CoreError::InvalidArgument
+                    );
+                }
+            }
+
             let market = self.market.load()?;
             let meta = market.meta();
             let swap_path = self.swap_path;
@@ -333,10 +410,16 @@ impl CreateIncreaseOrderOperation<'_, '_> {
                 self.short_token.mint,
                 CoreError::TokenMintMismatched
             );
-            self.position
-                .load()?
+            let position = self.position.load()?;
+            position
                 .validate_for_market(&market)
                 .map_err(ModelError::from)?;
+            if let Some(mode) = self.common.params.margin_mode {
+                require!(
+                    position.margin_mode()? == mode,
+                    CoreError::MarginModeMismatched
+                );
+            }
         }
 
         Ok(())
@@ -413,10 +496,16 @@ impl CreateDecreaseOrderOperation<'_, '_> {
                 self.short_token.mint,
                 CoreError::TokenMintMismatched
             );
-            self.position
-                .load()?
+            let position = self.position.load()?;
+            position
                 .validate_for_market(&market)
                 .map_err(ModelError::from)?;
+            if let Some(mode) = self.common.params.margin_mode {
+                require!(
+                    position.margin_mode()? == mode,
+                    CoreError::MarginModeMismatched
+                );
+            }
         }
         Ok(())
     }
@@ -757,6 +846,8 @@ pub(crate) struct ExecuteOrderOperation<'a, 'info> {
     position: Option<&'a AccountLoader<'info, Position>>,
     event: Option<&'a AccountLoader<'info, TradeData>>,
     oracle: &'a Oracle,
+    #[builder(default)]
+    virtual_pool: Option<&'a AccountLoader<'info, VirtualPool>>,
     remaining_accounts: &'info [AccountInfo<'info>],
     throw_on_execution_error: bool,
     #[builder(default)]
@@ -773,7 +864,7 @@ enum SecondaryOrderType {
     AutoDeleveraging,
 }
 
-impl ExecuteOrderOperation<'_, '_> {
+impl<'info> ExecuteOrderOperation<'_, 'info> {
     #[inline(never)]
     pub(crate) fn execute(
         self,
@@ -869,16 +960,20 @@ impl ExecuteOrderOperation<'_, '_> {
         self.validate_market()?;
         self.validate_order(should_throw_error, &prices)?;
 
+        let (remaining_accounts, cross_margin_collateral_value) =
+            self.unpack_cross_margin_positions(&prices)?;
+
         // Prepare execution context.
         let gt_minting_enabled = self.market.load()?.is_gt_minting_enabled();
         let mut market = RevertibleMarket::new(self.market, self.event_emitter)?
-            .with_order_fee_discount_factor(order_fee_discount_factor);
+            .with_order_fee_discount_factor(order_fee_discount_factor)
+            .with_virtual_pool(self.virtual_pool)?;
         let current_market_token = market.market_meta().market_token_mint;
         let loaders = self
             .order
             .load()?
             .swap
-            .unpack_markets_for_swap(&current_market_token, self.remaining_accounts)?;
+            .unpack_markets_for_swap(&current_market_token, remaining_accounts)?;
         let mut swap_markets = SwapMarkets::new(
             &self.store.key(),
             &loaders,
@@ -968,7 +1063,8 @@ impl ExecuteOrderOperation<'_, '_> {
                     )?;
                     should_send_trade_event = true;
                 }
-                let mut position = RevertiblePosition::new(market, position_loader)?;
+                let mut position = RevertiblePosition::new(market, position_loader)?
+                    .with_cross_margin_collateral_value(cross_margin_collateral_value);
 
                 position.on_validate().map_err(ModelError::from)?;
 
@@ -1061,6 +1157,65 @@ impl ExecuteOrderOperation<'_, '_> {
         ))
     }
 
+    /// Split the other positions of the owner in the same market off from the end of
+    /// the remaining accounts and sum up their excess collateral values.
+    ///
+    /// The positions are required only if the position to execute is in cross margin mode,
+    /// and all of them must be provided in the order of
+    /// [`Position::find_cross_margin_sibling_addresses`]. Positions that do not exist
+    /// or are not in cross margin mode are skipped. The excess values are not capped at
+    /// zero, so the position is checked by the total remaining collateral value of the
+    /// cross margin positions against their total required collateral value.
+    #[inline(never)]
+    fn unpack_cross_margin_positions(
+        &self,
+        prices: &Prices<u128>,
+    ) -> Result<(&'info [AccountInfo<'info>], i128)> {
+        let Some(position_loader) = self.position else {
+            return Ok((self.remaining_accounts, 0));
+        };
+        let position = position_loader.load()?;
+        if !position.is_cross_margin()? {
+            return Ok((self.remaining_accounts, 0));
+        }
+
+        let market = self.market.load()?;
+        let meta = market.meta();
+        let siblings = position.find_cross_margin_sibling_addresses(
+            &meta.long_token_mint,
+            &meta.short_token_mint,
+            &crate::ID,
+        )?;
+        let split_at = self
+            .remaining_accounts
+            .len()
+            .checked_sub(siblings.len())
+            .ok_or_else(|| error!(ErrorCode::AccountNotEnoughKeys))?;
+        let (remaining_accounts, sibling_accounts) = self.remaining_accounts.split_at(split_at);
+
+        let mut value = 0i128;
+        for (info, expected) in sibling_accounts.iter().zip(siblings.iter()) {
+            require_keys_eq!(info.key(), *expected, CoreError::PositionMismatched);
+            if *info.owner != crate::ID {
+                continue;
+            }
+            let sibling = AccountLoader::<Position>::try_from(info)?;
+            let sibling = sibling.load()?;
+            if !sibling.is_cross_margin()? || sibling.state.is_empty() {
+                continue;
+            }
+            let excess = sibling
+                .excess_collateral_value(&market, prices)
+                .map_err(ModelError::from)?;
+            value = value
+                .checked_add(excess)
+                .ok_or_else(|| error!(CoreError::ValueOverflow))?;
+        }
+        msg!("[Position] cross margin collateral value: {}", value);
+
+        Ok((remaining_accounts, value))
+    }
+
     fn close_position(&self) -> Result<()> {
         let Some(position) = self.position else {
             return err!(CoreError::PositionIsRequired);
@@ -1174,9 +1329,12 @@ impl ExecuteOrderOperation<'_, '_> {
     }
 
     fn validate_trigger_price(&self, prices: &Prices<u128>) -> Result<()> {
-        self.order
-            .load()?
-            .validate_trigger_price(&prices.index_token_price)
+        let order = self.order.load()?;
+        if order.should_fallback_to_market(Clock::get()?.unix_timestamp) {
+            msg!("[Order] fallback window elapsed, executing as a market order");
+            return Ok(());
+        }
+        order.validate_trigger_price(&prices.index_token_price)
     }
 }
 
@@ -1363,6 +1521,14 @@ fn execute_increase_position(
     let (long_amount, short_amount, paid_order_fee_value) = {
         let size_delta_usd = params.size_delta_value;
         let acceptable_price = params.acceptable_price;
+        position
+            .market()
+            .as_ref()
+            .validate_position_size_increment(size_delta_usd)?;
+        position
+            .market()
+            .as_ref()
+            .validate_max_trade_size(size_delta_usd)?;
         let report = position
             .increase(
                 prices,
@@ -1448,6 +1614,22 @@ fn execute_decrease_position(
             );
         }
 
+        // Validate the size delta unless the position is fully closed.
+        if !is_liquidation_order && size_delta_usd < *position.size_in_usd() {
+            position
+                .market()
+                .as_ref()
+                .validate_position_size_increment(size_delta_usd)?;
+        }
+
+        // Liquidation and ADL orders are not limited by the max trade size.
+        if !is_liquidation_order && !is_adl_order {
+            position
+                .market()
+                .as_ref()
+                .validate_max_trade_size(size_delta_usd.min(*position.size_in_usd()))?;
+        }
+
         // Validate that ADL is required.
         if is_adl_order {
             let Some(pnl_factor) = position
@@ -1642,6 +1824,12 @@ pub struct PositionCutOperation<'a, 'info> {
     market: &'a AccountLoader<'info, Market>,
     store: &'a AccountLoader<'info, Store>,
     oracle: &'a Oracle,
+    #[builder(default)]
+    virtual_pool: Option<&'a AccountLoader<'info, VirtualPool>>,
+    /// The other positions of the owner in the same market,
+    /// required only if the position is in cross margin mode.
+    #[builder(default)]
+    cross_margin_positions: &'info [AccountInfo<'info>],
     owner: AccountInfo<'info>,
     user: &'a AccountLoader<'info, UserHeader>,
     nonce: &'a NonceBytes,
@@ -1673,10 +1861,19 @@ pub enum PositionCutKind {
 }
 
 impl PositionCutKind {
-    fn size_delta_usd(&self, size_in_usd: u128) -> u128 {
+    /// Get the size delta of the order to create.
+    ///
+    /// The size delta of a partial ADL is rounded down to the position size increment
+    /// of the market.
+    fn size_delta_usd(&self, size_in_usd: u128, market: &Market) -> Result<u128> {
         match self {
-            Self::Liquidate => size_in_usd,
-            Self::AutoDeleverage(delta) => size_in_usd.min(*delta),
+            Self::Liquidate => Ok(size_in_usd),
+            Self::AutoDeleverage(delta) if *delta >= size_in_usd => Ok(size_in_usd),
+            Self::AutoDeleverage(delta) => {
+                let size_delta_usd = market.round_down_to_position_size_increment(*delta);
+                require_neq!(size_delta_usd, 0, CoreError::PositionSizeBelowIncrement);
+                Ok(size_delta_usd)
+            }
         }
     }
 
@@ -1739,7 +1936,9 @@ impl PositionCutOperation<'_, '_> {
             execution_lamports: Order::MIN_EXECUTION_LAMPORTS,
             swap_path_length: 0,
             initial_collateral_delta_amount: 0,
-            size_delta_value: self.kind.size_delta_usd(size_in_usd),
+            size_delta_value: self
+                .kind
+                .size_delta_usd(size_in_usd, &*self.market.load()?)?,
             is_long,
             is_collateral_long,
             min_output: None,
@@ -1747,6 +1946,12 @@ impl PositionCutOperation<'_, '_> {
             acceptable_price: None,
             should_unwrap_native_token: self.should_unwrap_native_token,
             valid_from_ts: None,
+            assigned_keeper: None,
+            fallback_to_market_after_secs: None,
+            callback_program: None,
+            callback_account: None,
+            callback_data: None,
+            margin_mode: None,
         };
         let output_token_account = if is_collateral_long {
             self.long_token_account
@@ -1788,7 +1993,8 @@ impl PositionCutOperation<'_, '_> {
             .position(Some(self.position))
             .event(Some(self.event))
             .oracle(self.oracle)
-            .remaining_accounts(&[])
+            .virtual_pool(self.virtual_pool)
+            .remaining_accounts(self.cross_margin_positions)
             .throw_on_execution_error(true)
             .refund(self.refund)
             .executor(self.executor.clone())
@@ -1848,3 +2054,161 @@ impl PositionCutOperation<'_, '_> {
         Ok(())
     }
 }
+
+/// Mark the callback of an executed order as pending and emit the execution result.
+///
+/// Does nothing if the order has no callback or the order callbacks are disabled.
+#[derive(TypedBuilder)]
+pub(crate) struct PrepareOrderCallbackOperation<'a, 'info> {
+    store: &'a AccountLoader<'info, Store>,
+    order: &'a AccountLoader<'info, Order>,
+    transfer_out: &'a TransferOut,
+    event_emitter: EventEmitter<'a, 'info>,
+}
+
+impl PrepareOrderCallbackOperation<'_, '_> {
+    pub(crate) fn execute(self) -> Result<()> {
+        let max_compute_units = *self
+            .store
+            .load()?
+            .get_amount_by_key(AmountKey::MaxOrderCallbackComputeUnits);
+
+        let event = {
+            let mut order = self.order.load_mut()?;
+            let kind = order.params().kind()?;
+            let Some(callback) = order.callback_mut() else {
+                return Ok(());
+            };
+            if max_compute_units == 0 {
+                msg!("[Callback] order callbacks are disabled, skipped");
+                return Ok(());
+            }
+            callback.set_pending(self.transfer_out)?;
+            OrderCallbackPending {
+                store: self.store.key(),
+                order: self.order.key(),
+                kind,
+                program: *callback.program().expect("must exist"),
+                account: *callback.account(),
+                transfer_out: *self.transfer_out,
+            }
+        };
+
+        self.event_emitter.emit_cpi(&event)?;
+        Ok(())
+    }
+}
+
+/// Invoke the pending callback of an executed order.
+#[derive(TypedBuilder)]
+pub(crate) struct InvokeOrderCallbackOperation<'a, 'info> {
+    store: &'a AccountLoader<'info, Store>,
+    order: &'a AccountLoader<'info, Order>,
+    transfer_out: &'a TransferOut,
+    callback_authority: &'a UncheckedAccount<'info>,
+    callback_program: &'a UncheckedAccount<'info>,
+    callback_account: &'a UncheckedAccount<'info>,
+}
+
+impl InvokeOrderCallbackOperation<'_, '_> {
+    pub(crate) fn execute(self) -> Result<()> {
+        use anchor_lang::solana_program::{
+            compute_units::sol_remaining_compute_units,
+            instruction::{AccountMeta, Instruction},
+            program::invoke_signed,
+        };
+
+        let max_compute_units = *self
+            .store
+            .load()?
+            .get_amount_by_key(AmountKey::MaxOrderCallbackComputeUnits);
+        require_neq!(max_compute_units, 0, CoreError::FeatureDisabled);
+
+        let (callback, kind) = {
+            let mut order = self.order.load_mut()?;
+            let kind = order.params().kind()?;
+            let callback = order
+                .callback_mut()
+                .ok_or_else(|| error!(CoreError::OrderCallbackNotPending))?;
+            callback.take_pending(self.transfer_out)?;
+            (*callback, kind)
+        };
+
+        let store = self.store.key();
+        let (authority_address, bump) = Pubkey::find_program_address(
+            &[constants::ORDER_CALLBACK_AUTHORITY_SEED, store.as_ref()],
+            &crate::ID,
+        );
+        let authority = self.callback_authority;
+        require_keys_eq!(
+            authority.key(),
+            authority_address,
+            CoreError::OrderCallbackAccountsNotProvided
+        );
+        let program = self.callback_program;
+        require_keys_eq!(
+            program.key(),
+            *callback.program().expect("must exist"),
+            CoreError::OrderCallbackAccountsNotProvided
+        );
+        let account = self.callback_account;
+        require_keys_eq!(
+            account.key(),
+            *callback.account(),
+            CoreError::OrderCallbackAccountsNotProvided
+        );
+
+        let args = OrderCallbackArgs {
+            kind,
+            data: *callback.data(),
+            transfer_out: *self.transfer_out,
+        };
+        let mut data = OrderCallbackArgs::DISCRIMINATOR.to_vec();
+        args.serialize(&mut data)?;
+        let instruction = Instruction {
+            program_id: program.key(),
+            accounts: vec![
+                AccountMeta::new_readonly(authority.key(), true),
+                AccountMeta::new_readonly(self.order.key(), false),
+                AccountMeta::new(account.key(), false),
+            ],
+            data,
+        };
+
+        // The compute units consumed by the callback cannot be limited directly,
+        // so we make sure that enough compute units are reserved for it and check
+        // the consumption afterwards.
+        let remaining = sol_remaining_compute_units();
+        require_gte!(
+            remaining,
+            max_compute_units,
+            CoreError::NotEnoughComputeUnitsForOrderCallback
+        );
+        invoke_signed(
+            &instruction,
+            &[
+                authority.to_account_info(),
+                self.order.to_account_info(),
+                account.to_account_info(),
+                program.to_account_info(),
+            ],
+            &[&[
+                constants::ORDER_CALLBACK_AUTHORITY_SEED,
+                store.as_ref(),
+                &[bump],
+            ]],
+        )?;
+        let consumed = remaining.saturating_sub(sol_remaining_compute_units());
+        require_gte!(
+            max_compute_units,
+            consumed,
+            CoreError::OrderCallbackComputeUnitsExceeded
+        );
+
+        msg!(
+            "[Callback] invoked order callback, consumed {} CUs",
+            consumed
+        );
+        Ok(())
+    }
+}
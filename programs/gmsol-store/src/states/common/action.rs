@@ -129,6 +129,8 @@ impl ActionState {
 pub enum ActionFlag {
     /// Should unwrap native token.
     ShouldUnwrapNativeToken,
+    /// Counted in the pending count of the market.
+    IsPendingCounted,
     // CHECK: should have no more than `MAX_FLAGS` of flags.
 }
 
@@ -297,6 +299,18 @@ impl ActionHeader {
         self.flags
             .set_flag(ActionFlag::ShouldUnwrapNativeToken, should_unwrap)
     }
+
+    /// Returns whether the action is counted in the pending count of the market.
+    pub fn is_pending_counted(&self) -> bool {
+        self.flags.get_flag(ActionFlag::IsPendingCounted)
+    }
+
+    /// Set whether the action is counted in the pending count of the market.
+    ///
+    /// Returns the previous value.
+    pub(crate) fn set_pending_counted(&mut self, counted: bool) -> bool {
+        self.flags.set_flag(ActionFlag::IsPendingCounted, counted)
+    }
 }
 
 /// Action Signer.
@@ -0,0 +1,216 @@
+use anchor_lang::prelude::*;
+use strum::IntoEnumIterator;
+
+use super::{
+    AddressKey, Addresses, Amount, AmountKey, Amounts, Factor, FactorKey, Factors, InitSpace, Seed,
+    Store,
+};
+
+/// Global Config Snapshot.
+///
+/// A copy of the global config of a store taken at a given slot. Snapshots are stored in a
+/// ring buffer of [`MAX_SNAPSHOTS`](GlobalConfigSnapshot::MAX_SNAPSHOTS) accounts per store,
+/// so the oldest snapshot is overwritten once the buffer is full.
+#[account(zero_copy)]
+#[cfg_attr(feature = "debug", derive(derive_more::Debug))]
+pub struct GlobalConfigSnapshot {
+    version: u8,
+    /// Bump seed.
+    pub bump: u8,
+    #[cfg_attr(feature = "debug", debug(skip))]
+    padding_0: [u8; 6],
+    /// Store.
+    pub store: Pubkey,
+    /// The index of the snapshot.
+    pub index: u64,
+    /// The slot at which the snapshot was taken.
+    pub snapshot_slot: u64,
+    /// The timestamp at which the snapshot was taken.
+    pub snapshot_timestamp: i64,
+    amount: Amounts,
+    #[cfg_attr(feature = "debug", debug(skip))]
+    padding_1: [u8; 8],
+    factor: Factors,
+    address: Addresses,
+    #[cfg_attr(feature = "debug", debug(skip))]
+    reserved: [u8; 256],
+}
+
+static_assertions::const_assert!(GlobalConfigSnapshot::INIT_SPACE + 8 <= 10240);
+
+impl InitSpace for GlobalConfigSnapshot {
+    const INIT_SPACE: usize = std::mem::size_of::<Self>();
+}
+
+impl Seed for GlobalConfigSnapshot {
+    const SEED: &'static [u8] = b"config_snapshot";
+}
+
+impl GlobalConfigSnapshot {
+    /// Max number of snapshots kept for a store.
+    pub const MAX_SNAPSHOTS: u64 = 100;
+
+    /// Get the seed of the ring buffer slot used by the snapshot with the given index.
+    pub fn slot_seed(index: u64) -> [u8; 8] {
+        (index % Self::MAX_SNAPSHOTS).to_le_bytes()
+    }
+
+    pub(crate) fn init(&mut self, bump: u8, store: &Pubkey) {
+        self.bump = bump;
+        self.store = *store;
+    }
+
+    /// Overwrite the snapshot with the current global config of the store.
+    pub(crate) fn capture(&mut self, index: u64, store: &Store, clock: &Clock) {
+        self.index = index;
+        self.snapshot_slot = clock.slot;
+        self.snapshot_timestamp = clock.unix_timestamp;
+        self.amount = store.amount;
+        self.factor = store.factor;
+        self.address = store.address;
+    }
+
+    /// Get amount by key.
+    pub fn get_amount_by_key(&self, key: AmountKey) -> &Amount {
+        self.amount.get(&key)
+    }
+
+    /// Get factor by key.
+    pub fn get_factor_by_key(&self, key: FactorKey) -> &Factor {
+        self.factor.get(&key)
+    }
+
+    /// Get address by key.
+    pub fn get_address_by_key(&self, key: AddressKey) -> &Pubkey {
+        self.address.get(&key)
+    }
+
+    /// Get all the config entries of the snapshot.
+    pub fn entries(&self) -> Vec<ConfigEntry> {
+        let amounts = AmountKey::iter().map(|key| ConfigEntry {
+            key: key.to_string(),
+            value: ConfigValue::Amount(*self.get_amount_by_key(key)),
+        });
+        let factors = FactorKey::iter().map(|key| ConfigEntry {
+            key: key.to_string(),
+            value: ConfigValue::Factor(*self.get_factor_by_key(key)),
+        });
+        let addresses = AddressKey::iter().map(|key| ConfigEntry {
+            key: key.to_string(),
+            value: ConfigValue::Address(*self.get_address_by_key(key)),
+        });
+        amounts.chain(factors).chain(addresses).collect()
+    }
+
+    /// Get the config entries that changed from `self` to `other`.
+    pub fn diff(&self, other: &Self) -> Vec<ConfigDiff> {
+        self.entries()
+            .into_iter()
+            .zip(other.entries())
+            .filter(|(previous, current)| previous.value != current.value)
+            .map(|(previous, current)| ConfigDiff {
+                key: previous.key,
+                previous: previous.value,
+                current: current.value,
+            })
+            .collect()
+    }
+
+    /// Convert to [`ConfigSnapshot`].
+    pub fn to_config_snapshot(&self) -> ConfigSnapshot {
+        ConfigSnapshot {
+            index: self.index,
+            snapshot_slot: self.snapshot_slot,
+            snapshot_timestamp: self.snapshot_timestamp,
+            entries: self.entries(),
+        }
+    }
+}
+
+/// Config Value.
+#[cfg_attr(feature = "debug", derive(Debug))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Copy, PartialEq, Eq, AnchorSerialize, AnchorDeserialize)]
+pub enum ConfigValue {
+    /// Amount.
+    Amount(Amount),
+    /// Factor.
+    Factor(Factor),
+    /// Address.
+    Address(Pubkey),
+}
+
+/// Config Entry.
+#[cfg_attr(feature = "debug", derive(Debug))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct ConfigEntry {
+    /// Config key.
+    pub key: String,
+    /// Config value.
+    pub value: ConfigValue,
+}
+
+/// Config Diff.
+#[cfg_attr(feature = "debug", derive(Debug))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct ConfigDiff {
+    /// Config key.
+    pub key: String,
+    /// The value in the earlier snapshot.
+    pub previous: ConfigValue,
+    /// The value in the later snapshot.
+    pub current: ConfigValue,
+}
+
+/// Config Snapshot.
+///
+/// The compact representation of a [`GlobalConfigSnapshot`] containing only the defined keys.
+#[cfg_attr(feature = "debug", derive(Debug))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct ConfigSnapshot {
+    /// The index of the snapshot.
+    pub index: u64,
+    /// The slot at which the snapshot was taken.
+    pub snapshot_slot: u64,
+    /// The timestamp at which the snapshot was taken.
+    pub snapshot_timestamp: i64,
+    /// Config entries.
+    pub entries: Vec<ConfigEntry>,
+}
+
+#[cfg(test)]
+mod tests {
+    use bytemuck::Zeroable;
+
+    use super::*;
+
+    #[test]
+    fn test_config_snapshot_diff() {
+        let mut previous = GlobalConfigSnapshot::zeroed();
+        let mut current = GlobalConfigSnapshot::zeroed();
+        assert!(previous.diff(&current).is_empty());
+
+        previous.factor.min_deposit_short_usd = 1;
+        current.factor.min_deposit_short_usd = 2;
+        let diff = previous.diff(&current);
+        assert_eq!(diff.len(), 1);
+        assert_eq!(diff[0].key, FactorKey::MinDepositShortUsd.to_string());
+        assert!(diff[0].previous == ConfigValue::Factor(1));
+        assert!(diff[0].current == ConfigValue::Factor(2));
+    }
+
+    #[test]
+    fn test_slot_seed() {
+        assert_eq!(
+            GlobalConfigSnapshot::slot_seed(1),
+            GlobalConfigSnapshot::slot_seed(GlobalConfigSnapshot::MAX_SNAPSHOTS + 1)
+        );
+        assert_ne!(
+            GlobalConfigSnapshot::slot_seed(0),
+            GlobalConfigSnapshot::slot_seed(1)
+        );
+    }
+}
@@ -21,7 +21,7 @@ use super::{
         token::{TokenAndAccount, TokensCollector},
     },
     deposit::DepositActionParams,
-    shift, Seed, Shift, TokenMapAccess,
+    shift, NonceBytes, Seed, Shift, TokenMapAccess,
 };
 
 const MAX_ALLOWED_NUMBER_OF_MARKETS: usize = 96;
@@ -51,8 +51,13 @@ pub struct Glv {
     padding_1: [u8; 4],
     shift_max_price_impact_factor: u128,
     shift_min_value: u128,
+    withdrawal_cooldown_secs: u32,
     #[cfg_attr(feature = "debug", debug(skip))]
-    reserved: [u8; 256],
+    padding_2: [u8; 4],
+    /// The nonce of the next batch of GLV shifts.
+    batch_nonce: u64,
+    #[cfg_attr(feature = "debug", debug(skip))]
+    reserved: [u8; 240],
     /// Market config map with market token addresses as keys.
     markets: GlvMarkets,
 }
@@ -81,6 +86,9 @@ impl Glv {
     /// Max allowed number of markets.
     pub const MAX_ALLOWED_NUMBER_OF_MARKETS: usize = MAX_ALLOWED_NUMBER_OF_MARKETS;
 
+    /// Max allowed number of GLV shifts in a batch.
+    pub const MAX_SHIFTS_PER_BATCH: usize = 8;
+
     /// Find GLV token address.
     pub fn find_glv_token_pda(store: &Pubkey, index: u16, program_id: &Pubkey) -> (Pubkey, u8) {
         Pubkey::find_program_address(
@@ -267,6 +275,15 @@ impl Glv {
             self.shift_min_value = value;
         }
 
+        if let Some(secs) = params.withdrawal_cooldown_secs {
+            require_neq!(
+                self.withdrawal_cooldown_secs,
+                secs,
+                CoreError::PreconditionsAreNotMet
+            );
+            self.withdrawal_cooldown_secs = secs;
+        }
+
         Ok(())
     }
 
@@ -355,6 +372,19 @@ impl Glv {
         Ok(())
     }
 
+    pub(crate) fn set_market_min_amount(
+        &mut self,
+        market_token: &Pubkey,
+        min_amount: u64,
+    ) -> Result<()> {
+        let config = self
+            .markets
+            .get_mut(market_token)
+            .ok_or_else(|| error!(CoreError::NotFound))?;
+        config.min_amount = min_amount;
+        Ok(())
+    }
+
     pub(crate) fn toggle_market_config_flag(
         &mut self,
         market_token: &Pubkey,
@@ -485,6 +515,25 @@ impl Glv {
         }
     }
 
+    /// Validate that the withdrawal cooldown has passed since the last GLV deposit
+    /// at `last_deposit_at`. Zero cooldown means disabled.
+    pub(crate) fn validate_withdrawal_cooldown(
+        &self,
+        last_deposit_at: i64,
+        current: i64,
+    ) -> Result<()> {
+        let cooldown = self.withdrawal_cooldown_secs;
+        if cooldown == 0 {
+            Ok(())
+        } else {
+            let after = last_deposit_at
+                .checked_add(i64::from(cooldown))
+                .ok_or_else(|| error!(CoreError::ValueOverflow))?;
+            require_gte!(current, after, CoreError::GlvWithdrawalCooldownNotPassed);
+            Ok(())
+        }
+    }
+
     pub(crate) fn validate_shift_price_impact(
         &self,
         from_market_token_value: u128,
@@ -526,6 +575,28 @@ impl Glv {
         self.shift_last_executed_at = clock.unix_timestamp;
         Ok(())
     }
+
+    /// Return the current batch nonce and advance the counter.
+    pub(crate) fn next_batch_nonce(&mut self) -> Result<u64> {
+        let batch_nonce = self.batch_nonce;
+        self.batch_nonce = batch_nonce
+            .checked_add(1)
+            .ok_or_else(|| error!(CoreError::ValueOverflow))?;
+        Ok(batch_nonce)
+    }
+
+    /// Derive the nonce of the GLV shift at `index` of the given batch.
+    ///
+    /// The GLV address is included so that batches of different GLVs
+    /// created by the same keeper never share a nonce.
+    pub fn batch_shift_nonce(glv: &Pubkey, batch_nonce: u64, index: u8) -> NonceBytes {
+        anchor_lang::solana_program::blake3::hashv(&[
+            glv.as_ref(),
+            &batch_nonce.to_le_bytes(),
+            &[index],
+        ])
+        .to_bytes()
+    }
 }
 
 #[cfg(feature = "utils")]
@@ -550,6 +621,16 @@ impl Glv {
         self.shift_min_value
     }
 
+    /// Get withdrawal cooldown.
+    pub fn withdrawal_cooldown_secs(&self) -> u32 {
+        self.withdrawal_cooldown_secs
+    }
+
+    /// Get the nonce of the next batch of GLV shifts.
+    pub fn batch_nonce(&self) -> u64 {
+        self.batch_nonce
+    }
+
     /// Get min tokens for first deposit.
     pub fn min_tokens_for_first_deposit(&self) -> u64 {
         self.min_tokens_for_first_deposit
@@ -568,6 +649,8 @@ pub struct UpdateGlvParams {
     pub shift_max_price_impact_factor: Option<u128>,
     /// Minimum shift value.
     pub shift_min_value: Option<u128>,
+    /// The cooldown (in seconds) after a GLV deposit before withdrawal is allowed.
+    pub withdrawal_cooldown_secs: Option<u32>,
 }
 
 impl UpdateGlvParams {
@@ -577,6 +660,7 @@ impl UpdateGlvParams {
             && self.shift_min_interval_secs.is_none()
             && self.shift_max_price_impact_factor.is_none()
             && self.shift_min_value.is_none()
+            && self.withdrawal_cooldown_secs.is_none()
     }
 
     pub(crate) fn validate(&self) -> Result<()> {
@@ -611,8 +695,7 @@ pub struct GlvMarketConfig {
     padding_0: [u8; 7],
     max_value: u128,
     balance: u64,
-    #[cfg_attr(feature = "debug", debug(skip))]
-    padding_1: [u8; 8],
+    min_amount: u64,
 }
 
 impl Default for GlvMarketConfig {
@@ -672,6 +755,18 @@ impl GlvMarketConfig {
         self.balance
     }
 
+    /// Get min amount of market tokens the GLV is targeted to hold.
+    ///
+    /// Zero means no min allocation.
+    pub fn min_amount(&self) -> u64 {
+        self.min_amount
+    }
+
+    /// Return whether the balance is below the min allocation.
+    pub fn is_below_min_amount(&self) -> bool {
+        self.balance < self.min_amount
+    }
+
     pub(crate) fn toggle_flag(&mut self, flag: GlvMarketFlag, enable: bool) -> Result<bool> {
         let current = self.flags.get_flag(flag);
         require_neq!(current, enable, CoreError::PreconditionsAreNotMet);
@@ -697,6 +792,265 @@ impl GlvMarketConfig {
     }
 }
 
+/// GLV Treasury.
+///
+/// Holds the protocol funds that keepers can use to rebalance the markets
+/// of a GLV through deposits.
+#[account(zero_copy)]
+#[cfg_attr(feature = "debug", derive(derive_more::Debug))]
+pub struct GlvTreasury {
+    version: u8,
+    /// Bump seed.
+    pub(crate) bump: u8,
+    bump_bytes: [u8; 1],
+    #[cfg_attr(feature = "debug", debug(skip))]
+    padding_0: [u8; 5],
+    /// Store.
+    pub(crate) store: Pubkey,
+    /// GLV.
+    pub(crate) glv: Pubkey,
+    rebalance_day: i64,
+    max_rebalance_value_per_day: u128,
+    rebalanced_value_today: u128,
+    /// The nonce of the next rebalance deposit.
+    deposit_nonce: u64,
+    #[cfg_attr(feature = "debug", debug(skip))]
+    padding_1: [u8; 8],
+    #[cfg_attr(feature = "debug", debug(skip))]
+    reserved: [u8; 128],
+}
+
+impl Seed for GlvTreasury {
+    const SEED: &'static [u8] = b"glv_treasury";
+}
+
+impl InitSpace for GlvTreasury {
+    const INIT_SPACE: usize = std::mem::size_of::<Self>();
+}
+
+impl GlvTreasury {
+    const SECONDS_PER_DAY: i64 = 86_400;
+
+    /// Find GLV treasury address.
+    pub fn find_glv_treasury_pda(
+        store: &Pubkey,
+        glv: &Pubkey,
+        program_id: &Pubkey,
+    ) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED, store.as_ref(), glv.as_ref()], program_id)
+    }
+
+    /// Derive the nonce of the rebalance deposit with the given deposit nonce.
+    pub fn rebalance_deposit_nonce(deposit_nonce: u64) -> NonceBytes {
+        let mut nonce = NonceBytes::default();
+        nonce[..8].copy_from_slice(&deposit_nonce.to_le_bytes());
+        nonce
+    }
+
+    pub(crate) fn signer_seeds(&self) -> [&[u8]; 4] {
+        [
+            Self::SEED,
+            self.store.as_ref(),
+            self.glv.as_ref(),
+            &self.bump_bytes,
+        ]
+    }
+
+    pub(crate) fn init(
+        &mut self,
+        bump: u8,
+        store: Pubkey,
+        glv: Pubkey,
+        max_rebalance_value_per_day: u128,
+    ) {
+        self.bump = bump;
+        self.bump_bytes = [bump];
+        self.store = store;
+        self.glv = glv;
+        self.max_rebalance_value_per_day = max_rebalance_value_per_day;
+    }
+
+    pub(crate) fn set_max_rebalance_value_per_day(&mut self, value: u128) {
+        self.max_rebalance_value_per_day = value;
+    }
+
+    /// Get the nonce of the next rebalance deposit.
+    pub fn next_rebalance_deposit_nonce(&self) -> NonceBytes {
+        Self::rebalance_deposit_nonce(self.deposit_nonce)
+    }
+
+    /// Record a rebalance of `value` (in USD) at `now` and advance the deposit nonce.
+    ///
+    /// # Errors
+    /// - The total value rebalanced in the current day must not exceed
+    ///   the max rebalance value per day.
+    pub(crate) fn record_rebalance(&mut self, value: u128, now: i64) -> Result<()> {
+        let day = now.div_euclid(Self::SECONDS_PER_DAY);
+        let rebalanced = if day == self.rebalance_day {
+            self.rebalanced_value_today
+        } else {
+            0
+        };
+        let rebalanced = rebalanced
+            .checked_add(value)
+            .ok_or_else(|| error!(CoreError::ValueOverflow))?;
+        require_gte!(
+            self.max_rebalance_value_per_day,
+            rebalanced,
+            CoreError::GlvRebalanceAmountExceedsDailyLimit
+        );
+        self.deposit_nonce = self
+            .deposit_nonce
+            .checked_add(1)
+            .ok_or_else(|| error!(CoreError::ValueOverflow))?;
+        self.rebalance_day = day;
+        self.rebalanced_value_today = rebalanced;
+        Ok(())
+    }
+}
+
+#[cfg(feature = "utils")]
+impl GlvTreasury {
+    /// Get the store address.
+    pub fn store(&self) -> &Pubkey {
+        &self.store
+    }
+
+    /// Get the GLV address.
+    pub fn glv(&self) -> &Pubkey {
+        &self.glv
+    }
+
+    /// Get max rebalance value per day.
+    pub fn max_rebalance_value_per_day(&self) -> u128 {
+        self.max_rebalance_value_per_day
+    }
+
+    /// Get the value rebalanced in the last recorded day.
+    pub fn rebalanced_value_today(&self) -> u128 {
+        self.rebalanced_value_today
+    }
+}
+
+/// GLV Deposit Cooldown.
+///
+/// Records the execution time of the last GLV deposit made by the owner of the
+/// given GLV token account (its ATA) to itself, so that the withdrawal cooldown
+/// applies to withdrawals from that account.
+///
+/// GLV tokens delivered to other receivers or transferred to other token accounts
+/// are not covered by the cooldown.
+#[account(zero_copy)]
+#[cfg_attr(feature = "debug", derive(derive_more::Debug))]
+pub struct GlvDepositCooldown {
+    version: u8,
+    /// Bump seed.
+    pub(crate) bump: u8,
+    #[cfg_attr(feature = "debug", debug(skip))]
+    padding_0: [u8; 6],
+    /// Store.
+    pub(crate) store: Pubkey,
+    /// GLV.
+    pub(crate) glv: Pubkey,
+    /// The GLV token account.
+    pub(crate) token_account: Pubkey,
+    last_deposit_at: i64,
+    #[cfg_attr(feature = "debug", debug(skip))]
+    reserved: [u8; 64],
+}
+
+impl Seed for GlvDepositCooldown {
+    const SEED: &'static [u8] = b"glv_deposit_cooldown";
+}
+
+impl InitSpace for GlvDepositCooldown {
+    const INIT_SPACE: usize = std::mem::size_of::<Self>();
+}
+
+impl GlvDepositCooldown {
+    /// Find GLV deposit cooldown address.
+    pub fn find_glv_deposit_cooldown_pda(
+        glv: &Pubkey,
+        token_account: &Pubkey,
+        program_id: &Pubkey,
+    ) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[Self::SEED, glv.as_ref(), token_account.as_ref()],
+            program_id,
+        )
+    }
+
+    pub(crate) fn init(&mut self, bump: u8, store: &Pubkey, glv: &Pubkey, token_account: &Pubkey) {
+        self.bump = bump;
+        self.store = *store;
+        self.glv = *glv;
+        self.token_account = *token_account;
+    }
+
+    /// Get the timestamp of the last GLV deposit.
+    pub fn last_deposit_at(&self) -> i64 {
+        self.last_deposit_at
+    }
+
+    pub(crate) fn record_deposit(&mut self, timestamp: i64) {
+        self.last_deposit_at = timestamp;
+    }
+
+    /// Get the timestamp of the last GLV deposit recorded in the given account.
+    ///
+    /// Returns `None` if the account has not been initialized, i.e., no GLV deposit
+    /// has ever been made to the GLV token account.
+    ///
+    /// # CHECK
+    /// The address of the account must have been validated.
+    pub(crate) fn last_deposit_at_of(info: &AccountInfo) -> Result<Option<i64>> {
+        if info.owner != &crate::ID {
+            return Ok(None);
+        }
+        let data = info.try_borrow_data()?;
+        Ok(Some(Self::from_data(&data)?.last_deposit_at))
+    }
+
+    /// Record a GLV deposit in the given account if it has been initialized.
+    ///
+    /// Returns whether the deposit is recorded.
+    ///
+    /// # CHECK
+    /// The address of the account must have been validated.
+    pub(crate) fn record_deposit_of(info: &AccountInfo, timestamp: i64) -> Result<bool> {
+        if info.owner != &crate::ID {
+            return Ok(false);
+        }
+        let mut data = info.try_borrow_mut_data()?;
+        Self::from_data_mut(&mut data)?.record_deposit(timestamp);
+        Ok(true)
+    }
+
+    fn from_data(data: &[u8]) -> Result<&Self> {
+        require!(
+            data.starts_with(&Self::DISCRIMINATOR),
+            ErrorCode::AccountDiscriminatorMismatch
+        );
+        bytemuck::try_from_bytes(
+            data.get(8..(8 + Self::INIT_SPACE))
+                .ok_or_else(|| error!(ErrorCode::AccountDidNotDeserialize))?,
+        )
+        .map_err(|_| error!(ErrorCode::AccountDidNotDeserialize))
+    }
+
+    fn from_data_mut(data: &mut [u8]) -> Result<&mut Self> {
+        require!(
+            data.starts_with(&Self::DISCRIMINATOR),
+            ErrorCode::AccountDiscriminatorMismatch
+        );
+        bytemuck::try_from_bytes_mut(
+            data.get_mut(8..(8 + Self::INIT_SPACE))
+                .ok_or_else(|| error!(ErrorCode::AccountDidNotDeserialize))?,
+        )
+        .map_err(|_| error!(ErrorCode::AccountDidNotDeserialize))
+    }
+}
+
 pub(crate) struct SplitAccountsForGlv<'info> {
     pub(crate) markets: &'info [AccountInfo<'info>],
     pub(crate) market_tokens: &'info [AccountInfo<'info>],
@@ -1132,6 +1486,11 @@ impl GlvShift {
         self.shift.tokens()
     }
 
+    /// Get shift params.
+    pub fn params(&self) -> &shift::ShiftActionParams {
+        self.shift.params()
+    }
+
     pub(crate) fn header_mut(&mut self) -> &mut ActionHeader {
         &mut self.shift.header
     }
@@ -148,22 +148,55 @@ impl GtState {
         Ok(())
     }
 
-    pub(crate) fn set_referral_reward_factors(&mut self, factors: &[u128]) -> Result<()> {
-        require_eq!(
-            factors.len(),
-            (self.max_rank + 1) as usize,
-            CoreError::InvalidArgument
-        );
+    /// Validate the referral reward factors, returning all the problems found.
+    ///
+    /// The factors are valid if the returned list is empty.
+    pub fn validate_referral_reward_factors(
+        &self,
+        factors: &[u128],
+    ) -> Vec<ReferralRewardFactorsProblem> {
+        let mut problems = Vec::new();
+
+        let expected = (self.max_rank + 1) as usize;
+        if factors.len() != expected {
+            problems.push(ReferralRewardFactorsProblem::new(
+                None,
+                format!(
+                    "expected {expected} factors (one per rank), got {}",
+                    factors.len()
+                ),
+            ));
+        }
 
-        // Factors must be storted.
-        require!(
-            factors.windows(2).all(|ab| {
-                if let [a, b] = &ab {
-                    a <= b
-                } else {
-                    false
+        for (rank, factor) in factors.iter().enumerate() {
+            let rank = u8::try_from(rank).ok();
+            if *factor > constants::MARKET_USD_UNIT {
+                problems.push(ReferralRewardFactorsProblem::new(
+                    rank,
+                    "the factor must not exceed 100%".to_string(),
+                ));
+            }
+        }
+
+        // Factors must be sorted.
+        for (rank, ab) in factors.windows(2).enumerate() {
+            if let [a, b] = ab {
+                if a > b {
+                    problems.push(ReferralRewardFactorsProblem::new(
+                        u8::try_from(rank + 1).ok(),
+                        "the factor must not be less than the factor of the previous rank"
+                            .to_string(),
+                    ));
                 }
-            }),
+            }
+        }
+
+        problems
+    }
+
+    pub(crate) fn set_referral_reward_factors(&mut self, factors: &[u128]) -> Result<()> {
+        require!(
+            self.validate_referral_reward_factors(factors).is_empty(),
             CoreError::InvalidArgument
         );
 
@@ -426,6 +459,23 @@ impl GtState {
     }
 }
 
+/// A problem found in the referral reward factors.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+#[cfg_attr(feature = "debug", derive(Debug))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ReferralRewardFactorsProblem {
+    /// The rank of the invalid factor, or `None` if the problem is about the whole list.
+    pub rank: Option<u8>,
+    /// The description of the problem.
+    pub problem: String,
+}
+
+impl ReferralRewardFactorsProblem {
+    fn new(rank: Option<u8>, problem: String) -> Self {
+        Self { rank, problem }
+    }
+}
+
 /// GT Exchange Vault Flags.
 #[repr(u8)]
 #[non_exhaustive]
@@ -435,6 +485,8 @@ pub enum GtExchangeVaultFlag {
     Intiailized,
     /// Confirmed.
     Comfirmed,
+    /// The number of pending exchanges is tracked.
+    PendingExchangesTracked,
     // CHECK: should have no more than `MAX_FLAGS` of flags.
 }
 
@@ -454,9 +506,11 @@ pub struct GtExchangeVault {
     amount: u64,
     /// Store.
     pub store: Pubkey,
+    /// The number of exchanges of this vault that are not completed.
+    pending_exchanges: u64,
     #[cfg_attr(feature = "debug", debug(skip))]
     #[cfg_attr(feature = "serde", serde(with = "serde_bytes"))]
-    reserved: [u8; 64],
+    reserved: [u8; 56],
 }
 
 impl GtExchangeVault {
@@ -486,11 +540,42 @@ impl GtExchangeVault {
         self.ts = clock.unix_timestamp;
         self.store = *store;
         self.flags.set_flag(GtExchangeVaultFlag::Intiailized, true);
+        self.flags
+            .set_flag(GtExchangeVaultFlag::PendingExchangesTracked, true);
         self.time_window = i64::from(time_window);
 
         Ok(())
     }
 
+    /// Get the number of exchanges of this vault that are not completed.
+    ///
+    /// Returns `None` if the vault was created before the number is tracked.
+    pub fn pending_exchanges(&self) -> Option<u64> {
+        self.flags
+            .get_flag(GtExchangeVaultFlag::PendingExchangesTracked)
+            .then_some(self.pending_exchanges)
+    }
+
+    /// Returns whether the vault can be closed, i.e., it is confirmed and
+    /// all its exchanges have been completed.
+    pub fn is_closable(&self) -> bool {
+        self.is_confirmed() && self.pending_exchanges() == Some(0)
+    }
+
+    pub(crate) fn add_pending_exchange(&mut self) -> Result<()> {
+        self.pending_exchanges = self
+            .pending_exchanges
+            .checked_add(1)
+            .ok_or_else(|| error!(CoreError::ValueOverflow))?;
+        Ok(())
+    }
+
+    fn remove_pending_exchange(&mut self) {
+        if self.pending_exchanges().is_some() {
+            self.pending_exchanges = self.pending_exchanges.saturating_sub(1);
+        }
+    }
+
     /// Get current time window index.
     pub fn time_window_index(&self) -> i64 {
         get_time_window_index(self.ts, self.time_window)
@@ -575,6 +660,8 @@ impl gmsol_utils::InitSpace for GtExchangeVault {
 pub enum GtExchangeFlag {
     /// Initialized.
     Intiailized,
+    /// Completed.
+    Completed,
     // CHECK: should have no more than `MAX_FLAGS` of flags.
 }
 
@@ -662,6 +749,24 @@ impl GtExchange {
     pub fn amount(&self) -> u64 {
         self.amount
     }
+
+    /// Get whether the exchange is completed.
+    pub fn is_completed(&self) -> bool {
+        self.flags.get_flag(GtExchangeFlag::Completed)
+    }
+
+    /// Mark the exchange as completed.
+    ///
+    /// # Errors
+    /// - The exchange must be initialized and not completed.
+    /// - The `vault` must be the vault of the exchange.
+    pub(crate) fn complete(&mut self, vault: &mut GtExchangeVault) -> Result<()> {
+        require!(self.is_initialized(), CoreError::PreconditionsAreNotMet);
+        require!(!self.is_completed(), CoreError::PreconditionsAreNotMet);
+        vault.remove_pending_exchange();
+        self.flags.set_flag(GtExchangeFlag::Completed, true);
+        Ok(())
+    }
 }
 
 impl gmsol_utils::InitSpace for GtExchange {
@@ -677,3 +782,49 @@ pub fn get_time_window_index(ts: i64, time_window: i64) -> i64 {
     debug_assert!(time_window > 0);
     ts / time_window
 }
+
+#[cfg(test)]
+mod tests {
+    use bytemuck::Zeroable;
+
+    use super::*;
+
+    #[test]
+    fn validate_referral_reward_factors() {
+        let mut gt = GtState::zeroed();
+        gt.max_rank = 2;
+        let unit = constants::MARKET_USD_UNIT;
+
+        assert!(gt
+            .validate_referral_reward_factors(&[0, unit / 10, unit])
+            .is_empty());
+        assert!(gt
+            .validate_referral_reward_factors(&[unit / 10, unit / 10, unit / 10])
+            .is_empty());
+
+        let problems = gt.validate_referral_reward_factors(&[0, unit / 10]);
+        assert_eq!(problems.len(), 1);
+        assert_eq!(problems[0].rank, None);
+
+        let problems = gt.validate_referral_reward_factors(&[0, unit + 1, unit]);
+        let ranks = problems
+            .iter()
+            .map(|problem| problem.rank)
+            .collect::<Vec<_>>();
+        assert_eq!(ranks, [Some(1), Some(2)]);
+
+        let problems = gt.validate_referral_reward_factors(&[unit / 2, unit / 10, unit / 5, 0]);
+        let ranks = problems
+            .iter()
+            .map(|problem| problem.rank)
+            .collect::<Vec<_>>();
+        assert_eq!(ranks, [None, Some(1), Some(3)]);
+
+        assert!(gt
+            .set_referral_reward_factors(&[0, unit + 1, unit])
+            .is_err());
+        assert!(gt
+            .set_referral_reward_factors(&[0, unit / 10, unit])
+            .is_ok());
+    }
+}
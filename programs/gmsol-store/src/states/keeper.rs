@@ -0,0 +1,228 @@
+use anchor_lang::prelude::*;
+
+use crate::CoreError;
+
+use super::{order::OrderKind, Seed};
+
+/// Keeper Deadline Extension.
+///
+/// An extension of the recent time window granted to keepers, used to relax
+/// the `recent_timestamp` validation during network congestion.
+#[account(zero_copy)]
+#[cfg_attr(feature = "debug", derive(derive_more::Debug))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct KeeperDeadlineExtension {
+    /// Bump seed.
+    pub bump: u8,
+    padding: [u8; 3],
+    extension_secs: u32,
+    valid_until: i64,
+    /// Store.
+    pub store: Pubkey,
+    granted_by: Pubkey,
+    #[cfg_attr(feature = "debug", debug(skip))]
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes"))]
+    reserved: [u8; 64],
+}
+
+impl KeeperDeadlineExtension {
+    /// Get the extension in seconds.
+    pub fn extension_secs(&self) -> u32 {
+        self.extension_secs
+    }
+
+    /// Get the timestamp until which the extension is valid.
+    pub fn valid_until(&self) -> i64 {
+        self.valid_until
+    }
+
+    /// Get the address that granted the extension.
+    pub fn granted_by(&self) -> &Pubkey {
+        &self.granted_by
+    }
+
+    /// Returns whether the extension is still valid at the given timestamp.
+    pub fn is_valid_at(&self, timestamp: i64) -> bool {
+        self.valid_until > timestamp
+    }
+
+    /// Get the extension (in seconds) effective at the given timestamp.
+    pub fn effective_extension_secs(&self, timestamp: i64) -> u32 {
+        if self.is_valid_at(timestamp) {
+            self.extension_secs
+        } else {
+            0
+        }
+    }
+
+    pub(crate) fn init(&mut self, bump: u8, store: &Pubkey) {
+        self.bump = bump;
+        self.store = *store;
+    }
+
+    pub(crate) fn grant(
+        &mut self,
+        granted_by: &Pubkey,
+        extension_secs: u32,
+        duration_secs: u32,
+        now: i64,
+    ) -> Result<()> {
+        require_neq!(extension_secs, 0, CoreError::InvalidArgument);
+        require_neq!(duration_secs, 0, CoreError::InvalidArgument);
+
+        self.valid_until = now
+            .checked_add(i64::from(duration_secs))
+            .ok_or_else(|| error!(CoreError::ValueOverflow))?;
+        self.extension_secs = extension_secs;
+        self.granted_by = *granted_by;
+        Ok(())
+    }
+}
+
+impl Seed for KeeperDeadlineExtension {
+    const SEED: &'static [u8] = b"keeper_deadline_extension";
+}
+
+impl gmsol_utils::InitSpace for KeeperDeadlineExtension {
+    const INIT_SPACE: usize = std::mem::size_of::<Self>();
+}
+
+/// Keeper Priority.
+///
+/// Routing configuration of a keeper, used by off-chain keepers to decide
+/// which orders to pick up.
+#[account(zero_copy)]
+#[cfg_attr(feature = "debug", derive(derive_more::Debug))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct KeeperPriority {
+    /// Bump seed.
+    pub bump: u8,
+    /// Priority.
+    pub priority: u8,
+    max_concurrent_executions: u16,
+    reserved_for_order_kinds: u32,
+    /// Store.
+    pub store: Pubkey,
+    /// Keeper.
+    pub keeper: Pubkey,
+    #[cfg_attr(feature = "debug", debug(skip))]
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes"))]
+    reserved: [u8; 64],
+}
+
+impl KeeperPriority {
+    /// Get the max number of concurrent executions.
+    pub fn max_concurrent_executions(&self) -> u16 {
+        self.max_concurrent_executions
+    }
+
+    /// Returns whether the keeper is reserved for the given order kind.
+    pub fn is_reserved_for(&self, kind: OrderKind) -> bool {
+        let bit = u8::from(kind);
+        self.reserved_for_order_kinds & (1 << bit) != 0
+    }
+
+    /// Get the order kinds that the keeper is reserved for.
+    pub fn reserved_for_order_kinds(&self) -> impl Iterator<Item = OrderKind> + '_ {
+        (0..u32::BITS)
+            .filter(|bit| self.reserved_for_order_kinds & (1 << bit) != 0)
+            .filter_map(|bit| OrderKind::try_from(bit as u8).ok())
+    }
+
+    pub(crate) fn init(&mut self, bump: u8, store: &Pubkey, keeper: &Pubkey) {
+        self.bump = bump;
+        self.store = *store;
+        self.keeper = *keeper;
+    }
+
+    pub(crate) fn set(
+        &mut self,
+        priority: u8,
+        max_concurrent_executions: u16,
+        order_kinds: &[u8],
+    ) -> Result<()> {
+        let mut reserved_for_order_kinds = 0u32;
+        for kind in order_kinds {
+            let kind = OrderKind::try_from(*kind)?;
+            reserved_for_order_kinds |= 1 << u8::from(kind);
+        }
+
+        self.priority = priority;
+        self.max_concurrent_executions = max_concurrent_executions;
+        self.reserved_for_order_kinds = reserved_for_order_kinds;
+        Ok(())
+    }
+}
+
+impl Seed for KeeperPriority {
+    const SEED: &'static [u8] = b"keeper_priority";
+}
+
+impl gmsol_utils::InitSpace for KeeperPriority {
+    const INIT_SPACE: usize = std::mem::size_of::<Self>();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_effective_extension_secs() {
+        let mut extension = KeeperDeadlineExtension {
+            bump: 0,
+            padding: [0; 3],
+            extension_secs: 0,
+            valid_until: 0,
+            store: Pubkey::default(),
+            granted_by: Pubkey::default(),
+            reserved: [0; 64],
+        };
+        assert_eq!(extension.effective_extension_secs(0), 0);
+
+        let keeper = Pubkey::new_unique();
+        extension.grant(&keeper, 60, 10, 100).unwrap();
+        assert_eq!(extension.granted_by(), &keeper);
+        assert_eq!(extension.valid_until(), 110);
+        assert_eq!(extension.effective_extension_secs(100), 60);
+        assert_eq!(extension.effective_extension_secs(109), 60);
+        assert_eq!(extension.effective_extension_secs(110), 0);
+
+        assert!(extension.grant(&keeper, 0, 10, 100).is_err());
+        assert!(extension.grant(&keeper, 60, 0, 100).is_err());
+        assert!(extension.grant(&keeper, 60, u32::MAX, i64::MAX).is_err());
+    }
+
+    #[test]
+    fn test_keeper_priority_order_kinds() {
+        let mut priority = KeeperPriority {
+            bump: 0,
+            priority: 0,
+            max_concurrent_executions: 0,
+            reserved_for_order_kinds: 0,
+            store: Pubkey::default(),
+            keeper: Pubkey::default(),
+            reserved: [0; 64],
+        };
+        assert!(!priority.is_reserved_for(OrderKind::MarketIncrease));
+
+        priority
+            .set(
+                1,
+                4,
+                &[
+                    OrderKind::MarketIncrease.into(),
+                    OrderKind::LimitDecrease.into(),
+                ],
+            )
+            .unwrap();
+        assert_eq!(priority.priority, 1);
+        assert_eq!(priority.max_concurrent_executions(), 4);
+        assert!(priority.is_reserved_for(OrderKind::MarketIncrease));
+        assert!(priority.is_reserved_for(OrderKind::LimitDecrease));
+        assert!(!priority.is_reserved_for(OrderKind::MarketDecrease));
+        assert_eq!(priority.reserved_for_order_kinds().count(), 2);
+
+        assert!(priority.set(1, 4, &[u8::MAX]).is_err());
+        assert!(priority.is_reserved_for(OrderKind::MarketIncrease));
+    }
+}
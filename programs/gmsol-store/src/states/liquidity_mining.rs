@@ -0,0 +1,426 @@
+use anchor_lang::prelude::*;
+use gmsol_model::num::MulDiv;
+
+use crate::{constants, CoreError};
+
+use super::{Factor, Seed};
+
+/// The parameters of a liquidity mining campaign.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+#[cfg_attr(feature = "debug", derive(Debug))]
+pub struct CampaignParams {
+    /// The start time of the campaign.
+    pub start_time: i64,
+    /// The end time of the campaign.
+    pub end_time: i64,
+    /// The total amount of reward tokens to deposit into the campaign,
+    /// which are released linearly during the campaign period.
+    pub total_reward: u64,
+}
+
+/// Liquidity Mining Campaign.
+///
+/// A time-bounded campaign rewarding the market tokens staked in it.
+///
+/// The rewards released in each period are shared among the stakers in proportion to
+/// their stakes, tracked by a cumulative reward per staked market token, so the total
+/// rewards can never exceed the deposited rewards.
+#[account(zero_copy)]
+#[cfg_attr(feature = "debug", derive(derive_more::Debug))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LiquidityMiningCampaign {
+    /// Bump seed.
+    pub bump: u8,
+    /// Campaign index.
+    pub index: u8,
+    padding: [u8; 6],
+    start_time: i64,
+    end_time: i64,
+    total_reward: u64,
+    total_claimed: u64,
+    total_staked: u64,
+    last_accrued_at: i64,
+    #[cfg_attr(feature = "debug", debug(skip))]
+    padding_1: [u8; 8],
+    cumulative_reward_per_market_token: Factor,
+    /// Store.
+    pub store: Pubkey,
+    /// Market token.
+    pub market_token: Pubkey,
+    /// Reward token.
+    pub reward_token: Pubkey,
+    #[cfg_attr(feature = "debug", debug(skip))]
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes"))]
+    reserved: [u8; 128],
+}
+
+impl LiquidityMiningCampaign {
+    /// Get the start time.
+    pub fn start_time(&self) -> i64 {
+        self.start_time
+    }
+
+    /// Get the end time.
+    pub fn end_time(&self) -> i64 {
+        self.end_time
+    }
+
+    /// Get the total amount of reward tokens deposited.
+    pub fn total_reward(&self) -> u64 {
+        self.total_reward
+    }
+
+    /// Get the total amount of reward tokens claimed.
+    pub fn total_claimed(&self) -> u64 {
+        self.total_claimed
+    }
+
+    /// Get the total amount of market tokens staked.
+    pub fn total_staked(&self) -> u64 {
+        self.total_staked
+    }
+
+    /// Get the last time the rewards were accrued.
+    pub fn last_accrued_at(&self) -> i64 {
+        self.last_accrued_at
+    }
+
+    /// Get the cumulative reward per staked market token, as a factor with
+    /// [`MARKET_DECIMALS`](constants::MARKET_DECIMALS).
+    pub fn cumulative_reward_per_market_token(&self) -> Factor {
+        self.cumulative_reward_per_market_token
+    }
+
+    /// Returns whether the campaign has ended at the given timestamp.
+    pub fn is_ended_at(&self, timestamp: i64) -> bool {
+        timestamp >= self.end_time
+    }
+
+    /// Get the cumulative reward per staked market token at the given timestamp.
+    pub fn cumulative_reward_per_market_token_at(&self, timestamp: i64) -> Result<Factor> {
+        let from = self.last_accrued_at.max(self.start_time);
+        let to = timestamp.min(self.end_time);
+        if to <= from || self.total_staked == 0 {
+            return Ok(self.cumulative_reward_per_market_token);
+        }
+        // `end_time` is always greater than `start_time`.
+        let duration = (self.end_time - self.start_time) as u128;
+        let elapsed = (to - from) as u128;
+        let delta = u128::from(self.total_reward)
+            .checked_mul_div(
+                &elapsed
+                    .checked_mul(constants::MARKET_USD_UNIT)
+                    .ok_or_else(|| error!(CoreError::ValueOverflow))?,
+                &duration
+                    .checked_mul(u128::from(self.total_staked))
+                    .ok_or_else(|| error!(CoreError::ValueOverflow))?,
+            )
+            .ok_or_else(|| error!(CoreError::ValueOverflow))?;
+        self.cumulative_reward_per_market_token
+            .checked_add(delta)
+            .ok_or_else(|| error!(CoreError::ValueOverflow))
+    }
+
+    /// Accrue the rewards released up to `now` to the stakers.
+    ///
+    /// Must be called before the total staked amount changes. The rewards released
+    /// while nothing is staked are not distributed.
+    pub(crate) fn accrue(&mut self, now: i64) -> Result<()> {
+        self.cumulative_reward_per_market_token =
+            self.cumulative_reward_per_market_token_at(now)?;
+        self.last_accrued_at = self.last_accrued_at.max(now);
+        Ok(())
+    }
+
+    pub(crate) fn init(
+        &mut self,
+        bump: u8,
+        index: u8,
+        store: &Pubkey,
+        market_token: &Pubkey,
+        reward_token: &Pubkey,
+        params: &CampaignParams,
+    ) -> Result<()> {
+        require_gt!(
+            params.end_time,
+            params.start_time,
+            CoreError::InvalidArgument
+        );
+        require_neq!(params.total_reward, 0, CoreError::InvalidArgument);
+
+        self.bump = bump;
+        self.index = index;
+        self.start_time = params.start_time;
+        self.end_time = params.end_time;
+        self.total_reward = params.total_reward;
+        self.store = *store;
+        self.market_token = *market_token;
+        self.reward_token = *reward_token;
+        Ok(())
+    }
+
+    pub(crate) fn record_staked(&mut self, amount: u64) -> Result<()> {
+        self.total_staked = self
+            .total_staked
+            .checked_add(amount)
+            .ok_or_else(|| error!(CoreError::ValueOverflow))?;
+        Ok(())
+    }
+
+    pub(crate) fn record_unstaked(&mut self, amount: u64) -> Result<()> {
+        self.total_staked = self
+            .total_staked
+            .checked_sub(amount)
+            .ok_or_else(|| error!(CoreError::ValueOverflow))?;
+        Ok(())
+    }
+
+    /// Record a claim of at most `amount` reward tokens, capped by the unclaimed
+    /// reward tokens of the campaign. Returns the amount actually claimed.
+    pub(crate) fn record_claimed(&mut self, amount: u64) -> Result<u64> {
+        let remaining = self
+            .total_reward
+            .checked_sub(self.total_claimed)
+            .ok_or_else(|| error!(CoreError::Internal))?;
+        let amount = amount.min(remaining);
+        self.total_claimed = self
+            .total_claimed
+            .checked_add(amount)
+            .ok_or_else(|| error!(CoreError::ValueOverflow))?;
+        Ok(amount)
+    }
+}
+
+impl Seed for LiquidityMiningCampaign {
+    const SEED: &'static [u8] = b"liquidity_mining_campaign";
+}
+
+impl gmsol_utils::InitSpace for LiquidityMiningCampaign {
+    const INIT_SPACE: usize = std::mem::size_of::<Self>();
+}
+
+/// LP Balance Checkpoint.
+///
+/// Tracks the market tokens staked by an LP in a [`LiquidityMiningCampaign`]
+/// and the rewards accrued to them.
+#[account(zero_copy)]
+#[cfg_attr(feature = "debug", derive(derive_more::Debug))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LpBalanceCheckpoint {
+    /// Bump seed.
+    pub bump: u8,
+    padding: [u8; 7],
+    balance: u64,
+    accrued_reward: u64,
+    claimed: u64,
+    reward_per_market_token_paid: Factor,
+    /// Campaign.
+    pub campaign: Pubkey,
+    /// Owner.
+    pub owner: Pubkey,
+    #[cfg_attr(feature = "debug", debug(skip))]
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes"))]
+    reserved: [u8; 64],
+}
+
+impl LpBalanceCheckpoint {
+    /// Get the staked balance.
+    pub fn balance(&self) -> u64 {
+        self.balance
+    }
+
+    /// Get the amount of reward tokens claimed.
+    pub fn claimed(&self) -> u64 {
+        self.claimed
+    }
+
+    /// Get the cumulative reward per staked market token as of the last update.
+    pub fn reward_per_market_token_paid(&self) -> Factor {
+        self.reward_per_market_token_paid
+    }
+
+    pub(crate) fn init(&mut self, bump: u8, campaign: &Pubkey, owner: &Pubkey) {
+        self.bump = bump;
+        self.campaign = *campaign;
+        self.owner = *owner;
+    }
+
+    fn reward_since_last_update(&self, cumulative_reward_per_market_token: Factor) -> Result<u64> {
+        let delta = cumulative_reward_per_market_token
+            .checked_sub(self.reward_per_market_token_paid)
+            .ok_or_else(|| error!(CoreError::Internal))?;
+        u128::from(self.balance)
+            .checked_mul_div(&delta, &constants::MARKET_USD_UNIT)
+            .and_then(|reward| reward.try_into().ok())
+            .ok_or_else(|| error!(CoreError::ValueOverflow))
+    }
+
+    /// Accrue the rewards with the cumulative reward per market token of the campaign,
+    /// which must be [accrued](LiquidityMiningCampaign::accrue) first.
+    pub(crate) fn update(&mut self, campaign: &LiquidityMiningCampaign) -> Result<()> {
+        let cumulative = campaign.cumulative_reward_per_market_token;
+        let reward = self.reward_since_last_update(cumulative)?;
+        self.accrued_reward = self
+            .accrued_reward
+            .checked_add(reward)
+            .ok_or_else(|| error!(CoreError::ValueOverflow))?;
+        self.reward_per_market_token_paid = cumulative;
+        Ok(())
+    }
+
+    pub(crate) fn stake(&mut self, amount: u64) -> Result<()> {
+        self.balance = self
+            .balance
+            .checked_add(amount)
+            .ok_or_else(|| error!(CoreError::ValueOverflow))?;
+        Ok(())
+    }
+
+    pub(crate) fn unstake(&mut self, amount: u64) -> Result<()> {
+        require_gte!(self.balance, amount, CoreError::NotEnoughTokenAmount);
+        self.balance -= amount;
+        Ok(())
+    }
+
+    /// Get the amount of reward tokens accrued but not yet claimed at the given timestamp.
+    pub fn pending_reward_at(
+        &self,
+        campaign: &LiquidityMiningCampaign,
+        timestamp: i64,
+    ) -> Result<u64> {
+        let cumulative = campaign.cumulative_reward_per_market_token_at(timestamp)?;
+        self.accrued_reward
+            .checked_add(self.reward_since_last_update(cumulative)?)
+            .ok_or_else(|| error!(CoreError::ValueOverflow))
+    }
+
+    /// Get the amount of reward tokens accrued but not yet claimed, as of the last update.
+    pub fn pending_reward(&self) -> u64 {
+        self.accrued_reward
+    }
+
+    pub(crate) fn record_claimed(&mut self, amount: u64) -> Result<()> {
+        self.accrued_reward = self
+            .accrued_reward
+            .checked_sub(amount)
+            .ok_or_else(|| error!(CoreError::Internal))?;
+        self.claimed = self
+            .claimed
+            .checked_add(amount)
+            .ok_or_else(|| error!(CoreError::ValueOverflow))?;
+        Ok(())
+    }
+}
+
+impl Seed for LpBalanceCheckpoint {
+    const SEED: &'static [u8] = b"lp_balance_checkpoint";
+}
+
+impl gmsol_utils::InitSpace for LpBalanceCheckpoint {
+    const INIT_SPACE: usize = std::mem::size_of::<Self>();
+}
+
+#[cfg(test)]
+mod tests {
+    use bytemuck::Zeroable;
+
+    use super::*;
+
+    fn campaign(start_time: i64, end_time: i64, total_reward: u64) -> LiquidityMiningCampaign {
+        let mut campaign = LiquidityMiningCampaign::zeroed();
+        campaign
+            .init(
+                0,
+                0,
+                &Pubkey::default(),
+                &Pubkey::default(),
+                &Pubkey::default(),
+                &CampaignParams {
+                    start_time,
+                    end_time,
+                    total_reward,
+                },
+            )
+            .unwrap();
+        campaign
+    }
+
+    fn stake(
+        campaign: &mut LiquidityMiningCampaign,
+        checkpoint: &mut LpBalanceCheckpoint,
+        amount: u64,
+        now: i64,
+    ) {
+        campaign.accrue(now).unwrap();
+        checkpoint.update(campaign).unwrap();
+        checkpoint.stake(amount).unwrap();
+        campaign.record_staked(amount).unwrap();
+    }
+
+    fn claim(
+        campaign: &mut LiquidityMiningCampaign,
+        checkpoint: &mut LpBalanceCheckpoint,
+        now: i64,
+    ) -> u64 {
+        campaign.accrue(now).unwrap();
+        checkpoint.update(campaign).unwrap();
+        let amount = campaign
+            .record_claimed(checkpoint.pending_reward())
+            .unwrap();
+        checkpoint.record_claimed(amount).unwrap();
+        amount
+    }
+
+    #[test]
+    fn accrue_within_campaign_period() {
+        let mut campaign = campaign(100, 200, 1_000);
+
+        let mut checkpoint = LpBalanceCheckpoint::zeroed();
+        // Staked before the start.
+        stake(&mut campaign, &mut checkpoint, 10, 50);
+        assert_eq!(checkpoint.pending_reward_at(&campaign, 150).unwrap(), 500);
+        assert_eq!(claim(&mut campaign, &mut checkpoint, 150), 500);
+
+        // Claimed after the end.
+        assert_eq!(claim(&mut campaign, &mut checkpoint, 300), 500);
+        assert_eq!(checkpoint.claimed(), 1_000);
+        assert_eq!(checkpoint.pending_reward_at(&campaign, 400).unwrap(), 0);
+    }
+
+    #[test]
+    fn rewards_are_shared_among_stakers() {
+        let mut campaign = campaign(0, 100, 1_000);
+
+        let mut alice = LpBalanceCheckpoint::zeroed();
+        let mut bob = LpBalanceCheckpoint::zeroed();
+        stake(&mut campaign, &mut alice, 1, 0);
+        // Bob stakes a much larger amount midway.
+        stake(&mut campaign, &mut bob, 999, 50);
+
+        let alice_reward = claim(&mut campaign, &mut alice, 100);
+        let bob_reward = claim(&mut campaign, &mut bob, 100);
+        assert_eq!(alice_reward, 500);
+        assert_eq!(bob_reward, 499);
+        assert!(alice_reward + bob_reward <= campaign.total_reward());
+        assert_eq!(campaign.total_claimed(), alice_reward + bob_reward);
+    }
+
+    #[test]
+    fn rewards_are_not_released_while_nothing_is_staked() {
+        let mut campaign = campaign(0, 100, 1_000);
+
+        let mut checkpoint = LpBalanceCheckpoint::zeroed();
+        stake(&mut campaign, &mut checkpoint, 10, 60);
+        assert_eq!(claim(&mut campaign, &mut checkpoint, 200), 400);
+    }
+
+    #[test]
+    fn claims_are_capped_by_total_reward() {
+        let mut campaign = LiquidityMiningCampaign::zeroed();
+        campaign.total_reward = 100;
+        assert_eq!(campaign.record_claimed(60).unwrap(), 60);
+        assert_eq!(campaign.record_claimed(60).unwrap(), 40);
+        assert_eq!(campaign.record_claimed(60).unwrap(), 0);
+        assert_eq!(campaign.total_claimed(), 100);
+    }
+}
@@ -0,0 +1,244 @@
+use anchor_lang::{prelude::*, Discriminator};
+
+use crate::{states::Seed, CoreError};
+
+use super::Factor;
+
+/// Market Analytics.
+///
+/// Accumulates the OHLCV data of the trades of a market within a period,
+/// where the period is identified by `period_key = unix_timestamp / period_secs`.
+#[account(zero_copy)]
+#[cfg_attr(feature = "debug", derive(derive_more::Debug))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MarketAnalytics {
+    /// Bump seed.
+    pub bump: u8,
+    #[cfg_attr(feature = "debug", debug(skip))]
+    padding_0: [u8; 3],
+    /// The number of trades in the period.
+    trade_count: u32,
+    /// Store.
+    pub store: Pubkey,
+    /// Market token.
+    pub market_token: Pubkey,
+    /// The length of the period in seconds.
+    period_secs: u64,
+    /// The start timestamp of the period.
+    period_start: i64,
+    #[cfg_attr(feature = "debug", debug(skip))]
+    padding_1: [u8; 8],
+    /// Open price.
+    open: Factor,
+    /// High price.
+    high: Factor,
+    /// Low price.
+    low: Factor,
+    /// Close price.
+    close: Factor,
+    /// Total trade volume in USD.
+    volume_usd: Factor,
+    #[cfg_attr(feature = "debug", debug(skip))]
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes"))]
+    reserved: [u8; 128],
+}
+
+impl MarketAnalytics {
+    /// Get the period key of the given timestamp.
+    pub fn period_key(ts: i64, period_secs: u64) -> Result<i64> {
+        let period_secs = Self::validated_period_secs(period_secs)?;
+        Ok(ts.div_euclid(period_secs))
+    }
+
+    /// Get the period key of the current timestamp.
+    pub fn current_period_key(period_secs: u64) -> Result<i64> {
+        Self::period_key(Clock::get()?.unix_timestamp, period_secs)
+    }
+
+    fn validated_period_secs(period_secs: u64) -> Result<i64> {
+        require_neq!(period_secs, 0, CoreError::InvalidArgument);
+        i64::try_from(period_secs).map_err(|_| error!(CoreError::InvalidArgument))
+    }
+
+    /// Find market analytics address.
+    pub fn find_market_analytics_pda(
+        store: &Pubkey,
+        market_token: &Pubkey,
+        period_secs: u64,
+        period_key: i64,
+        program_id: &Pubkey,
+    ) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[
+                Self::SEED,
+                store.as_ref(),
+                market_token.as_ref(),
+                &period_secs.to_le_bytes(),
+                &period_key.to_le_bytes(),
+            ],
+            program_id,
+        )
+    }
+
+    /// Returns whether the account info looks like a market analytics account.
+    pub(crate) fn is_market_analytics(info: &AccountInfo) -> bool {
+        info.owner == &crate::ID
+            && info
+                .try_borrow_data()
+                .map(|data| data.starts_with(&Self::DISCRIMINATOR))
+                .unwrap_or(false)
+    }
+
+    pub(crate) fn init(
+        &mut self,
+        bump: u8,
+        store: &Pubkey,
+        market_token: &Pubkey,
+        period_secs: u64,
+        period_key: i64,
+    ) -> Result<()> {
+        let period_start = period_key
+            .checked_mul(Self::validated_period_secs(period_secs)?)
+            .ok_or_else(|| error!(CoreError::ValueOverflow))?;
+        self.bump = bump;
+        self.store = *store;
+        self.market_token = *market_token;
+        self.period_secs = period_secs;
+        self.period_start = period_start;
+        Ok(())
+    }
+
+    /// Get the length of the period in seconds.
+    pub fn period_secs(&self) -> u64 {
+        self.period_secs
+    }
+
+    /// Get the start timestamp of the period.
+    pub fn period_start(&self) -> i64 {
+        self.period_start
+    }
+
+    /// Get the number of trades in the period.
+    pub fn trade_count(&self) -> u32 {
+        self.trade_count
+    }
+
+    /// Returns whether the given timestamp is within the period.
+    pub fn contains(&self, ts: i64) -> bool {
+        let Ok(period_secs) = i64::try_from(self.period_secs) else {
+            return false;
+        };
+        ts >= self.period_start && ts - self.period_start < period_secs
+    }
+
+    /// Record a trade.
+    pub(crate) fn record_trade(&mut self, price: Factor, volume_usd: Factor) -> Result<()> {
+        let volume_usd = self
+            .volume_usd
+            .checked_add(volume_usd)
+            .ok_or_else(|| error!(CoreError::ValueOverflow))?;
+        let trade_count = self
+            .trade_count
+            .checked_add(1)
+            .ok_or_else(|| error!(CoreError::ValueOverflow))?;
+
+        if self.trade_count == 0 {
+            self.open = price;
+            self.high = price;
+            self.low = price;
+        } else {
+            self.high = self.high.max(price);
+            self.low = self.low.min(price);
+        }
+        self.close = price;
+        self.volume_usd = volume_usd;
+        self.trade_count = trade_count;
+        Ok(())
+    }
+
+    /// Get the OHLCV data of the period.
+    pub fn ohlcv(&self) -> OhlcvData {
+        OhlcvData {
+            market_token: self.market_token,
+            period_secs: self.period_secs,
+            period_start: self.period_start,
+            open: self.open,
+            high: self.high,
+            low: self.low,
+            close: self.close,
+            volume_usd: self.volume_usd,
+            trade_count: self.trade_count,
+        }
+    }
+}
+
+impl Seed for MarketAnalytics {
+    const SEED: &'static [u8] = b"market_analytics";
+}
+
+impl gmsol_utils::InitSpace for MarketAnalytics {
+    const INIT_SPACE: usize = std::mem::size_of::<Self>();
+}
+
+/// OHLCV data of a period.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "debug", derive(Debug))]
+pub struct OhlcvData {
+    /// Market token.
+    pub market_token: Pubkey,
+    /// The length of the period in seconds.
+    pub period_secs: u64,
+    /// The start timestamp of the period.
+    pub period_start: i64,
+    /// Open price.
+    pub open: u128,
+    /// High price.
+    pub high: u128,
+    /// Low price.
+    pub low: u128,
+    /// Close price.
+    pub close: u128,
+    /// Total trade volume in USD.
+    pub volume_usd: u128,
+    /// The number of trades.
+    pub trade_count: u32,
+}
+
+#[cfg(test)]
+mod tests {
+    use bytemuck::Zeroable;
+
+    use super::*;
+
+    #[test]
+    fn period_keys() {
+        assert_eq!(MarketAnalytics::period_key(3_599, 3_600).unwrap(), 0);
+        assert_eq!(MarketAnalytics::period_key(3_600, 3_600).unwrap(), 1);
+        assert_eq!(MarketAnalytics::period_key(-1, 3_600).unwrap(), -1);
+        assert!(MarketAnalytics::period_key(3_600, 0).is_err());
+    }
+
+    #[test]
+    fn record_trades() {
+        let mut analytics = MarketAnalytics::zeroed();
+        analytics
+            .init(0, &Pubkey::default(), &Pubkey::default(), 60, 2)
+            .unwrap();
+        assert_eq!(analytics.period_start(), 120);
+        assert!(!analytics.contains(119));
+        assert!(analytics.contains(120));
+        assert!(analytics.contains(179));
+        assert!(!analytics.contains(180));
+
+        for (price, volume) in [(100, 10), (120, 20), (90, 30), (110, 40), (105, 50)] {
+            analytics.record_trade(price, volume).unwrap();
+        }
+        let ohlcv = analytics.ohlcv();
+        assert_eq!(ohlcv.open, 100);
+        assert_eq!(ohlcv.high, 120);
+        assert_eq!(ohlcv.low, 90);
+        assert_eq!(ohlcv.close, 105);
+        assert_eq!(ohlcv.volume_usd, 150);
+        assert_eq!(ohlcv.trade_count, 5);
+    }
+}
@@ -1,10 +1,25 @@
 use anchor_lang::prelude::*;
 
+use gmsol_model::utils::apply_factor;
+
 use crate::{constants, states::Factor, CoreError};
 
 /// Max number of config flags.
 pub const MAX_CONFIG_FLAGS: usize = 128;
 
+/// Number of seconds in a week.
+pub const SECONDS_PER_WEEK: i64 = 7 * 24 * 3600;
+
+/// The offset (in seconds) from the unix epoch (Thursday) to the start of a week (Monday).
+const UNIX_EPOCH_SECONDS_OF_WEEK: i64 = 3 * 24 * 3600;
+
+/// The max exponent factor considered valid by [`MarketConfig::validate`].
+const MAX_EXPONENT_FACTOR: Factor = 10 * constants::MARKET_USD_UNIT;
+
+/// The max keeper tip factor, i.e., at most 10% of the fees of an action
+/// can be paid to the keeper as a tip.
+pub const MAX_KEEPER_TIP_FACTOR: Factor = constants::MARKET_USD_UNIT / 10;
+
 /// Market Config.
 #[zero_copy]
 #[cfg_attr(feature = "debug", derive(Debug))]
@@ -86,10 +101,31 @@ pub struct MarketConfig {
     pub(super) max_open_interest_for_long: Factor,
     pub(super) max_open_interest_for_short: Factor,
     pub(super) min_tokens_for_first_deposit: Factor,
-    reserved: [Factor; 32],
+    // Trading window.
+    pub(super) trading_window_open: Factor,
+    pub(super) trading_window_close: Factor,
+    pub(super) trading_window_timezone_offset: Factor,
+    // Oracle.
+    pub(super) max_oracle_ts_range: Factor,
+    // Withdrawal.
+    pub(super) max_swap_impact_pool_withdrawal_factor: Factor,
+    // Position size.
+    pub(super) position_size_increment_usd: Factor,
+    pub(super) max_trade_size_usd: Factor,
+    // Borrowing fee kink model.
+    pub(super) borrowing_fee_min_factor_for_long: Factor,
+    pub(super) borrowing_fee_min_factor_for_short: Factor,
+    // Keeper tip.
+    pub(super) keeper_tip_factor: Factor,
+    reserved: [Factor; 22],
 }
 
 impl MarketConfig {
+    /// Zero-fill the reserved factors.
+    pub(super) fn zero_reserved(&mut self) -> u32 {
+        super::zero_fill(&mut self.reserved)
+    }
+
     pub(super) fn init(&mut self) {
         self.swap_impact_exponent = constants::DEFAULT_SWAP_IMPACT_EXPONENT;
         self.swap_impact_positive_factor = constants::DEFAULT_SWAP_IMPACT_POSITIVE_FACTOR;
@@ -320,6 +356,22 @@ impl MarketConfig {
             MarketConfigKey::MaxOpenInterestForLong => &self.max_open_interest_for_long,
             MarketConfigKey::MaxOpenInterestForShort => &self.max_open_interest_for_short,
             MarketConfigKey::MinTokensForFirstDeposit => &self.min_tokens_for_first_deposit,
+            MarketConfigKey::TradingWindowOpen => &self.trading_window_open,
+            MarketConfigKey::TradingWindowClose => &self.trading_window_close,
+            MarketConfigKey::TradingWindowTimezoneOffset => &self.trading_window_timezone_offset,
+            MarketConfigKey::MaxOracleTsRange => &self.max_oracle_ts_range,
+            MarketConfigKey::MaxSwapImpactPoolWithdrawalFactor => {
+                &self.max_swap_impact_pool_withdrawal_factor
+            }
+            MarketConfigKey::PositionSizeIncrementUsd => &self.position_size_increment_usd,
+            MarketConfigKey::MaxTradeSizeUsd => &self.max_trade_size_usd,
+            MarketConfigKey::BorrowingFeeMinFactorForLong => {
+                &self.borrowing_fee_min_factor_for_long
+            }
+            MarketConfigKey::BorrowingFeeMinFactorForShort => {
+                &self.borrowing_fee_min_factor_for_short
+            }
+            MarketConfigKey::KeeperTipFactor => &self.keeper_tip_factor,
         }
     }
 
@@ -459,7 +511,223 @@ impl MarketConfig {
             MarketConfigKey::MaxOpenInterestForLong => &mut self.max_open_interest_for_long,
             MarketConfigKey::MaxOpenInterestForShort => &mut self.max_open_interest_for_short,
             MarketConfigKey::MinTokensForFirstDeposit => &mut self.min_tokens_for_first_deposit,
+            MarketConfigKey::TradingWindowOpen => &mut self.trading_window_open,
+            MarketConfigKey::TradingWindowClose => &mut self.trading_window_close,
+            MarketConfigKey::TradingWindowTimezoneOffset => {
+                &mut self.trading_window_timezone_offset
+            }
+            MarketConfigKey::MaxOracleTsRange => &mut self.max_oracle_ts_range,
+            MarketConfigKey::MaxSwapImpactPoolWithdrawalFactor => {
+                &mut self.max_swap_impact_pool_withdrawal_factor
+            }
+            MarketConfigKey::PositionSizeIncrementUsd => &mut self.position_size_increment_usd,
+            MarketConfigKey::MaxTradeSizeUsd => &mut self.max_trade_size_usd,
+            MarketConfigKey::BorrowingFeeMinFactorForLong => {
+                &mut self.borrowing_fee_min_factor_for_long
+            }
+            MarketConfigKey::BorrowingFeeMinFactorForShort => {
+                &mut self.borrowing_fee_min_factor_for_short
+            }
+            MarketConfigKey::KeeperTipFactor => &mut self.keeper_tip_factor,
+        }
+    }
+
+    /// Check the configs against their validity bounds.
+    ///
+    /// Returns the keys of the out-of-range configs, each with a description of the problem.
+    /// An empty list means that all configs are in range.
+    pub fn validate(&self) -> Vec<(MarketConfigKey, &'static str)> {
+        use MarketConfigKey::*;
+
+        let unit = constants::MARKET_USD_UNIT;
+        let mut problems = Vec::new();
+
+        for key in [
+            SwapImpactExponent,
+            PositionImpactExponent,
+            BorrowingFeeExponentForLong,
+            BorrowingFeeExponentForShort,
+            FundingFeeExponent,
+        ] {
+            if !(unit..=MAX_EXPONENT_FACTOR).contains(self.get(key)) {
+                problems.push((key, "exponent out of range, must be between 1 and 10"));
+            }
+        }
+
+        for key in [
+            SwapFeeReceiverFactor,
+            SwapFeeFactorForPositiveImpact,
+            SwapFeeFactorForNegativeImpact,
+            MinCollateralFactor,
+            MaxPositivePositionImpactFactor,
+            MaxNegativePositionImpactFactor,
+            MaxPositionImpactFactorForLiquidations,
+            OrderFeeReceiverFactor,
+            OrderFeeFactorForPositiveImpact,
+            OrderFeeFactorForNegativeImpact,
+            LiquidationFeeReceiverFactor,
+            LiquidationFeeFactor,
+            PositionImpactDistributeFactor,
+            BorrowingFeeReceiverFactor,
+            BorrowingFeeOptimalUsageFactorForLong,
+            BorrowingFeeOptimalUsageFactorForShort,
+            FundingFeeThresholdForStableFunding,
+            FundingFeeThresholdForDecreaseFunding,
+            ReserveFactor,
+            OpenInterestReserveFactor,
+            MaxPnlFactorForLongDeposit,
+            MaxPnlFactorForShortDeposit,
+            MaxPnlFactorForLongWithdrawal,
+            MaxPnlFactorForShortWithdrawal,
+            MaxPnlFactorForLongTrader,
+            MaxPnlFactorForShortTrader,
+            MaxPnlFactorForLongAdl,
+            MaxPnlFactorForShortAdl,
+            MinPnlFactorAfterLongAdl,
+            MinPnlFactorAfterShortAdl,
+            MaxSwapImpactPoolWithdrawalFactor,
+        ] {
+            if *self.get(key) > unit {
+                problems.push((key, "factor too large, must not exceed 1"));
+            }
+        }
+
+        if self.funding_fee_min_factor_per_second > self.funding_fee_max_factor_per_second {
+            problems.push((
+                FundingFeeMinFactorPerSecond,
+                "must not exceed the max funding factor per second",
+            ));
+        }
+        if self.min_pnl_factor_after_long_adl > self.max_pnl_factor_for_long_adl {
+            problems.push((
+                MinPnlFactorAfterLongAdl,
+                "must not exceed the max pnl factor for ADL",
+            ));
+        }
+        if self.min_pnl_factor_after_short_adl > self.max_pnl_factor_for_short_adl {
+            problems.push((
+                MinPnlFactorAfterShortAdl,
+                "must not exceed the max pnl factor for ADL",
+            ));
+        }
+        if self.keeper_tip_factor > MAX_KEEPER_TIP_FACTOR {
+            problems.push((KeeperTipFactor, "factor too large, must not exceed 10%"));
+        }
+
+        problems
+    }
+
+    /// Returns whether the market is open for trading at the given unix timestamp.
+    ///
+    /// The trading window is unset (i.e., the market is open 24/7) if the open time
+    /// equals the close time. A window whose open time is greater than its close time
+    /// wraps around the end of the week.
+    pub(crate) fn is_open_at(&self, timestamp: i64) -> bool {
+        let open = self.trading_window_open % SECONDS_PER_WEEK as u128;
+        let close = self.trading_window_close % SECONDS_PER_WEEK as u128;
+        if open == close {
+            return true;
+        }
+        let offset = self.trading_window_timezone_offset % SECONDS_PER_WEEK as u128;
+        let seconds_of_week = (timestamp
+            .rem_euclid(SECONDS_PER_WEEK)
+            .saturating_add(UNIX_EPOCH_SECONDS_OF_WEEK)
+            .saturating_add(offset as i64))
+        .rem_euclid(SECONDS_PER_WEEK) as u128;
+        if open < close {
+            open <= seconds_of_week && seconds_of_week < close
+        } else {
+            seconds_of_week >= open || seconds_of_week < close
+        }
+    }
+
+    /// Validate that the swap impact pool amount of a token does not exceed the
+    /// allowed factor of the liquidity pool amount of the same token remaining
+    /// after a withdrawal.
+    ///
+    /// The validation is skipped if `max_swap_impact_pool_withdrawal_factor` is zero.
+    pub(crate) fn validate_swap_impact_pool_for_withdrawal(
+        &self,
+        swap_impact_pool_amount: u128,
+        liquidity_pool_amount: u128,
+    ) -> Result<()> {
+        let factor = self.max_swap_impact_pool_withdrawal_factor;
+        if factor == 0 {
+            return Ok(());
+        }
+        let max_swap_impact_pool_amount =
+            apply_factor::<_, { constants::MARKET_DECIMALS }>(&liquidity_pool_amount, &factor)
+                .ok_or_else(|| error!(CoreError::ValueOverflow))?;
+        require_gte!(
+            max_swap_impact_pool_amount,
+            swap_impact_pool_amount,
+            CoreError::SwapImpactPoolWithdrawalFactorExceeded
+        );
+        Ok(())
+    }
+
+    /// Validate that the given size delta (in USD) is a multiple of the position size increment.
+    ///
+    /// The validation is skipped if `position_size_increment_usd` is zero.
+    pub(crate) fn validate_position_size_increment(&self, size_delta_usd: u128) -> Result<()> {
+        let increment = self.position_size_increment_usd;
+        if increment == 0 {
+            return Ok(());
+        }
+        require_eq!(
+            size_delta_usd % increment,
+            0,
+            CoreError::PositionSizeBelowIncrement
+        );
+        Ok(())
+    }
+
+    /// Round down the given size delta (in USD) to the nearest multiple of the position size increment.
+    ///
+    /// Returns the size delta unchanged if `position_size_increment_usd` is zero.
+    pub(crate) fn round_down_to_position_size_increment(&self, size_delta_usd: u128) -> u128 {
+        let increment = self.position_size_increment_usd;
+        if increment == 0 {
+            size_delta_usd
+        } else {
+            size_delta_usd - size_delta_usd % increment
+        }
+    }
+
+    /// Validate that the given size delta (in USD) does not exceed the max trade size.
+    ///
+    /// The validation is skipped if `max_trade_size_usd` is zero.
+    pub(crate) fn validate_max_trade_size(&self, size_delta_usd: u128) -> Result<()> {
+        let max_trade_size = self.max_trade_size_usd;
+        if max_trade_size == 0 {
+            return Ok(());
+        }
+        require_gte!(
+            max_trade_size,
+            size_delta_usd,
+            CoreError::TradeSizeExceedsLimit
+        );
+        Ok(())
+    }
+
+    /// Get the keeper tip for the given amount of fees.
+    ///
+    /// The keeper tip factor is capped at [`MAX_KEEPER_TIP_FACTOR`].
+    pub(crate) fn keeper_tip(&self, fee_amount: u128) -> Result<u128> {
+        let factor = self.keeper_tip_factor.min(MAX_KEEPER_TIP_FACTOR);
+        if factor == 0 {
+            return Ok(0);
         }
+        apply_factor::<_, { constants::MARKET_DECIMALS }>(&fee_amount, &factor)
+            .ok_or_else(|| error!(CoreError::ValueOverflow))
+    }
+
+    /// Get the max oracle timestamps range (in seconds) of the market.
+    ///
+    /// Returns `None` if unset, i.e., the global one should be used.
+    pub(crate) fn max_oracle_ts_range(&self) -> Option<u64> {
+        (self.max_oracle_ts_range != 0)
+            .then(|| self.max_oracle_ts_range.try_into().unwrap_or(u64::MAX))
     }
 
     /// Get config flag.
@@ -479,6 +747,7 @@ impl MarketConfig {
 #[derive(
     strum::EnumString,
     strum::Display,
+    strum::EnumIter,
     Clone,
     Copy,
     PartialEq,
@@ -491,7 +760,6 @@ impl MarketConfig {
 )]
 #[strum(serialize_all = "snake_case")]
 #[cfg_attr(feature = "debug", derive(Debug))]
-#[cfg_attr(feature = "enum-iter", derive(strum::EnumIter))]
 #[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
 #[cfg_attr(feature = "clap", clap(rename_all = "snake_case"))]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
@@ -503,6 +771,8 @@ pub enum MarketConfigFlag {
     SkipBorrowingFeeForSmallerSide,
     /// Ignore open interest for usage factor.
     IgnoreOpenInterestForUsageFactor,
+    /// Use the virtual pool for position price impact.
+    VirtualPoolEnabled,
     // CHECK: cannot have more than `MAX_CONFIG_FLAGS` flags.
 }
 
@@ -512,6 +782,7 @@ gmsol_utils::flags!(MarketConfigFlag, MAX_CONFIG_FLAGS, u128);
 #[derive(
     strum::EnumString,
     strum::Display,
+    strum::EnumIter,
     Clone,
     Copy,
     PartialEq,
@@ -524,7 +795,6 @@ gmsol_utils::flags!(MarketConfigFlag, MAX_CONFIG_FLAGS, u128);
 )]
 #[strum(serialize_all = "snake_case")]
 #[cfg_attr(feature = "debug", derive(Debug))]
-#[cfg_attr(feature = "enum-iter", derive(strum::EnumIter))]
 #[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
 #[cfg_attr(feature = "clap", clap(rename_all = "snake_case"))]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
@@ -583,12 +853,24 @@ pub enum MarketConfigKey {
     /// Borrowing fee receiver factor.
     BorrowingFeeReceiverFactor,
     /// Borrowing fee factor for long.
+    ///
+    /// Deprecated: only used if the kink model is disabled, i.e., the optimal usage factor
+    /// is zero.
     BorrowingFeeFactorForLong,
     /// Borrowing fee factor for short.
+    ///
+    /// Deprecated: only used if the kink model is disabled, i.e., the optimal usage factor
+    /// is zero.
     BorrowingFeeFactorForShort,
     /// Borrowing fee exponent for long.
+    ///
+    /// Deprecated: only used if the kink model is disabled, i.e., the optimal usage factor
+    /// is zero.
     BorrowingFeeExponentForLong,
     /// Borrowing fee exponent for short.
+    ///
+    /// Deprecated: only used if the kink model is disabled, i.e., the optimal usage factor
+    /// is zero.
     BorrowingFeeExponentForShort,
     /// Borrowing fee optimal usage factor for long.
     BorrowingFeeOptimalUsageFactorForLong,
@@ -656,6 +938,55 @@ pub enum MarketConfigKey {
     MaxOpenInterestForShort,
     /// Min tokens for first deposit.
     MinTokensForFirstDeposit,
+    /// The open time of the trading window, in seconds since Monday 00:00 (local time).
+    TradingWindowOpen,
+    /// The close time of the trading window, in seconds since Monday 00:00 (local time).
+    TradingWindowClose,
+    /// The timezone offset of the trading window, in seconds east of UTC, taken modulo one week.
+    ///
+    /// A negative offset `-x` should be set as `SECONDS_PER_WEEK - x`.
+    TradingWindowTimezoneOffset,
+    /// The max range (in seconds) of the oracle timestamps allowed when executing
+    /// actions in this market. The global one is used if it is set to zero.
+    MaxOracleTsRange,
+    /// The max ratio of the swap impact pool amount to the liquidity pool amount of
+    /// the same token remaining after a withdrawal. Disabled if it is set to zero.
+    MaxSwapImpactPoolWithdrawalFactor,
+    /// The size delta (in USD) of position increases and decreases must be a multiple
+    /// of this increment. Disabled if it is set to zero.
+    PositionSizeIncrementUsd,
+    /// The max size delta (in USD) of a single position increase or decrease.
+    /// Disabled if it is set to zero.
+    MaxTradeSizeUsd,
+    /// Borrowing fee min factor for long, i.e., the borrowing factor per second of the
+    /// kink model at zero usage.
+    BorrowingFeeMinFactorForLong,
+    /// Borrowing fee min factor for short, i.e., the borrowing factor per second of the
+    /// kink model at zero usage.
+    BorrowingFeeMinFactorForShort,
+    /// The fraction of the fees of an action paid to the keeper as a tip on successful
+    /// execution, capped at [`MAX_KEEPER_TIP_FACTOR`]. Disabled if it is set to zero.
+    KeeperTipFactor,
+}
+
+/// A problem of the market config reported by [`MarketConfig::validate`].
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+#[cfg_attr(feature = "debug", derive(Debug))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MarketConfigProblem {
+    /// The config key, in snake case.
+    pub key: String,
+    /// The description of the problem.
+    pub problem: String,
+}
+
+impl From<(MarketConfigKey, &str)> for MarketConfigProblem {
+    fn from((key, problem): (MarketConfigKey, &str)) -> Self {
+        Self {
+            key: key.to_string(),
+            problem: problem.to_string(),
+        }
+    }
 }
 
 /// An entry of the config buffer.
@@ -753,3 +1084,208 @@ impl MarketConfigBuffer {
         self.entries.len()
     }
 }
+
+/// Collect the names of the given keys, starting from `start`, as many as fit in the
+/// return data.
+pub(crate) fn key_names_within_return_data<T: ToString>(
+    keys: impl IntoIterator<Item = T>,
+    start: u16,
+) -> Vec<String> {
+    use anchor_lang::solana_program::program::MAX_RETURN_DATA;
+
+    // Each name is serialized with a 4-byte length prefix, as is the list itself.
+    let mut size = 4;
+    keys.into_iter()
+        .skip(start.into())
+        .map(|key| key.to_string())
+        .take_while(|name| {
+            size += 4 + name.len();
+            size <= MAX_RETURN_DATA
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use strum::IntoEnumIterator;
+
+    use super::*;
+
+    const DAY: i64 = 24 * 3600;
+    const HOUR: i64 = 3600;
+
+    /// Unix timestamp of Monday, 1970-01-05 00:00:00 UTC.
+    const MONDAY: i64 = 4 * DAY;
+
+    fn config_with_window(open: i64, close: i64, offset: i64) -> MarketConfig {
+        let mut config: MarketConfig = bytemuck::Zeroable::zeroed();
+        config.trading_window_open = open as u128;
+        config.trading_window_close = close as u128;
+        config.trading_window_timezone_offset = offset.rem_euclid(SECONDS_PER_WEEK) as u128;
+        config
+    }
+
+    #[test]
+    fn test_swap_impact_pool_withdrawal_factor() {
+        let mut config: MarketConfig = bytemuck::Zeroable::zeroed();
+
+        // Disabled by default.
+        assert!(config
+            .validate_swap_impact_pool_for_withdrawal(u128::MAX, 0)
+            .is_ok());
+
+        // The swap impact pool must not exceed 50% of the remaining liquidity.
+        config.max_swap_impact_pool_withdrawal_factor = constants::MARKET_USD_UNIT / 2;
+        assert!(config
+            .validate_swap_impact_pool_for_withdrawal(500, 1_000)
+            .is_ok());
+        assert!(config
+            .validate_swap_impact_pool_for_withdrawal(501, 1_000)
+            .is_err());
+
+        // A withdrawal draining the liquidity pool of a heavily skewed market is rejected.
+        assert!(config
+            .validate_swap_impact_pool_for_withdrawal(1_000, 0)
+            .is_err());
+        assert!(config
+            .validate_swap_impact_pool_for_withdrawal(0, 0)
+            .is_ok());
+    }
+
+    #[test]
+    fn test_position_size_increment() {
+        let mut config: MarketConfig = bytemuck::Zeroable::zeroed();
+
+        // Disabled by default.
+        assert!(config.validate_position_size_increment(1).is_ok());
+        assert_eq!(config.round_down_to_position_size_increment(150), 150);
+
+        config.position_size_increment_usd = 100;
+        assert!(config.validate_position_size_increment(0).is_ok());
+        assert!(config.validate_position_size_increment(200).is_ok());
+        assert!(config.validate_position_size_increment(150).is_err());
+        assert!(config.validate_position_size_increment(u128::MAX).is_err());
+        assert_eq!(config.round_down_to_position_size_increment(150), 100);
+        assert_eq!(config.round_down_to_position_size_increment(99), 0);
+        assert_eq!(config.round_down_to_position_size_increment(200), 200);
+    }
+
+    #[test]
+    fn test_validate() {
+        let mut config: MarketConfig = bytemuck::Zeroable::zeroed();
+        config.init();
+        assert!(config.validate().is_empty());
+
+        config.position_impact_exponent = 0;
+        config.liquidation_fee_factor = constants::MARKET_USD_UNIT + 1;
+        config.funding_fee_min_factor_per_second = config.funding_fee_max_factor_per_second + 1;
+        let keys = config
+            .validate()
+            .into_iter()
+            .map(|(key, _)| key)
+            .collect::<Vec<_>>();
+        assert!(
+            keys == [
+                MarketConfigKey::PositionImpactExponent,
+                MarketConfigKey::LiquidationFeeFactor,
+                MarketConfigKey::FundingFeeMinFactorPerSecond,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_max_trade_size() {
+        let mut config: MarketConfig = bytemuck::Zeroable::zeroed();
+
+        // Disabled by default.
+        assert!(config.validate_max_trade_size(u128::MAX).is_ok());
+
+        config.max_trade_size_usd = 100;
+        assert!(config.validate_max_trade_size(0).is_ok());
+        assert!(config.validate_max_trade_size(100).is_ok());
+        assert!(config.validate_max_trade_size(101).is_err());
+    }
+
+    #[test]
+    fn test_keeper_tip() {
+        let mut config: MarketConfig = bytemuck::Zeroable::zeroed();
+        config.init();
+
+        // Disabled by default.
+        assert_eq!(config.keeper_tip(1_000).unwrap(), 0);
+
+        config.keeper_tip_factor = constants::MARKET_USD_UNIT / 20;
+        assert_eq!(config.keeper_tip(1_000).unwrap(), 50);
+        assert!(config.validate().is_empty());
+
+        // The factor is capped.
+        config.keeper_tip_factor = constants::MARKET_USD_UNIT;
+        assert_eq!(config.keeper_tip(1_000).unwrap(), 100);
+        assert!(config
+            .validate()
+            .iter()
+            .any(|(key, _)| *key == MarketConfigKey::KeeperTipFactor));
+    }
+
+    #[test]
+    fn test_unset_trading_window() {
+        let config: MarketConfig = bytemuck::Zeroable::zeroed();
+        for ts in [0, MONDAY, MONDAY + 5 * DAY + 13 * HOUR, i64::MAX, -DAY] {
+            assert!(config.is_open_at(ts));
+        }
+    }
+
+    #[test]
+    fn test_trading_window_boundaries() {
+        // Monday 09:30 to Friday 16:00 in UTC-5.
+        let config = config_with_window(9 * HOUR + 1800, 4 * DAY + 16 * HOUR, -5 * HOUR);
+
+        let open_at = MONDAY + 14 * HOUR + 1800;
+        assert!(!config.is_open_at(open_at - 1));
+        assert!(config.is_open_at(open_at));
+
+        let close_at = MONDAY + 4 * DAY + 21 * HOUR;
+        assert!(config.is_open_at(close_at - 1));
+        assert!(!config.is_open_at(close_at));
+
+        // Saturday is closed, and the window repeats weekly.
+        assert!(!config.is_open_at(MONDAY + 5 * DAY + 12 * HOUR));
+        assert!(config.is_open_at(open_at + SECONDS_PER_WEEK));
+        assert!(!config.is_open_at(close_at + SECONDS_PER_WEEK));
+    }
+
+    #[test]
+    fn test_wrapping_trading_window() {
+        // Sunday 00:00 to Tuesday 00:00 in UTC.
+        let config = config_with_window(6 * DAY, DAY, 0);
+
+        assert!(!config.is_open_at(MONDAY - DAY - 1));
+        assert!(config.is_open_at(MONDAY - DAY));
+        assert!(config.is_open_at(MONDAY));
+        assert!(config.is_open_at(MONDAY + DAY - 1));
+        assert!(!config.is_open_at(MONDAY + DAY));
+    }
+
+    #[test]
+    fn test_list_key_names() {
+        let mut names = Vec::new();
+        loop {
+            let page = key_names_within_return_data(MarketConfigKey::iter(), names.len() as u16);
+            if page.is_empty() {
+                break;
+            }
+            let size = 4 + page.iter().map(|name| 4 + name.len()).sum::<usize>();
+            assert!(size <= anchor_lang::solana_program::program::MAX_RETURN_DATA);
+            names.extend(page);
+        }
+        assert_eq!(names.len(), MarketConfigKey::iter().count());
+        for (name, key) in names.iter().zip(MarketConfigKey::iter()) {
+            assert!(MarketConfigKey::from_str(name).unwrap() == key);
+        }
+
+        let flags = key_names_within_return_data(MarketConfigFlag::iter(), 0);
+        assert_eq!(flags.len(), MarketConfigFlag::iter().count());
+    }
+}
@@ -39,10 +39,10 @@ use crate::{
     CoreError,
 };
 
-use super::{Factor, InitSpace, Oracle, Seed};
+use super::{common::action::ActionHeader, Factor, InitSpace, Oracle, Seed, Store};
 
 use self::{
-    config::{MarketConfig, MarketConfigBuffer, MarketConfigKey},
+    config::{Entry, MarketConfig, MarketConfigBuffer, MarketConfigKey},
     pool::{Pool, Pools},
 };
 
@@ -66,6 +66,18 @@ pub mod pool;
 /// Market Status.
 pub mod status;
 
+/// Virtual Pool.
+pub mod virtual_pool;
+
+/// Market OI Caps.
+pub mod oi_caps;
+
+/// Market Analytics.
+pub mod analytics;
+
+/// Fee Structure Proposal.
+pub mod proposal;
+
 mod model;
 
 /// Max number of flags.
@@ -92,8 +104,10 @@ pub struct Market {
     indexer: Indexer,
     state: State,
     buffer: RevertibleBuffer,
+    virtual_pool_key: Pubkey,
+    token_map: Pubkey,
     #[cfg_attr(feature = "serde", serde(with = "serde_bytes"))]
-    reserved: [u8; 256],
+    reserved: [u8; 192],
 }
 
 #[zero_copy]
@@ -108,6 +122,25 @@ struct State {
     reserved: [u8; 1024],
 }
 
+impl State {
+    /// Zero-fill all the reserved ranges of the state.
+    fn zero_reserved(&mut self) -> u32 {
+        zero_fill(&mut self.reserved)
+            + self.pools.zero_reserved()
+            + zero_fill(&mut self.clocks.reserved)
+            + zero_fill(&mut self.other.reserved)
+    }
+}
+
+/// Zero-fill the given value, returning the number of non-zero bytes.
+fn zero_fill<T: bytemuck::Pod>(value: &mut T) -> u32 {
+    let bytes = bytemuck::bytes_of_mut(value);
+    let count = bytes.iter().filter(|byte| **byte != 0).count();
+    bytes.fill(0);
+    // The size of the market account is far less than `u32::MAX`.
+    count as u32
+}
+
 impl Bump for Market {
     fn seed(&self) -> u8 {
         self.bump
@@ -122,6 +155,17 @@ impl InitSpace for Market {
     const INIT_SPACE: usize = std::mem::size_of::<Self>();
 }
 
+// Reference layout of the market account. Any change of these sizes breaks the
+// on-chain layout, so new fields must be carved out of the reserved ranges.
+static_assertions::const_assert_eq!(Market::INIT_SPACE, 9168);
+static_assertions::const_assert_eq!(std::mem::size_of::<MarketConfig>(), 1520);
+static_assertions::const_assert_eq!(std::mem::size_of::<Indexer>(), 192);
+static_assertions::const_assert_eq!(std::mem::size_of::<State>(), 3472);
+static_assertions::const_assert_eq!(std::mem::size_of::<Pools>(), 2048);
+static_assertions::const_assert_eq!(std::mem::size_of::<Clocks>(), 80);
+static_assertions::const_assert_eq!(std::mem::size_of::<OtherState>(), 320);
+static_assertions::const_assert_eq!(std::mem::size_of::<RevertibleBuffer>(), 3488);
+
 impl Default for Market {
     fn default() -> Self {
         use bytemuck::Zeroable;
@@ -196,6 +240,31 @@ impl Market {
         bytes_to_fixed_str(&self.name)
     }
 
+    /// Length of the header of the account data (without the discriminator),
+    /// i.e., the bytes up to and including the `store` field.
+    pub fn header_len() -> usize {
+        bytemuck::offset_of!(Market, store) + std::mem::size_of::<Pubkey>()
+    }
+
+    /// Decode the meta and the enabled flag from the header of the account data
+    /// (without the discriminator).
+    ///
+    /// Return `None` if the given bytes are shorter than [`header_len`](Self::header_len).
+    pub fn decode_header(header: &[u8]) -> Option<(MarketMeta, bool)> {
+        if header.len() < Self::header_len() {
+            return None;
+        }
+        let flags_offset = bytemuck::offset_of!(Market, flags);
+        let flags: MarketFlagContainer = bytemuck::pod_read_unaligned(
+            &header[flags_offset..(flags_offset + std::mem::size_of::<MarketFlagContainer>())],
+        );
+        let meta_offset = bytemuck::offset_of!(Market, meta);
+        let meta: MarketMeta = bytemuck::pod_read_unaligned(
+            &header[meta_offset..(meta_offset + std::mem::size_of::<MarketMeta>())],
+        );
+        Some((meta, flags.get_flag(MarketFlag::Enabled)))
+    }
+
     /// Description.
     pub fn description(&self) -> Result<String> {
         let name = self.name()?;
@@ -272,6 +341,33 @@ impl Market {
         self.state.pools.get(kind).map(|s| s.pool()).copied()
     }
 
+    /// Returns whether all the pools holding amounts are empty.
+    pub fn is_drained(&self) -> bool {
+        self.state.pools.is_drained()
+    }
+
+    /// Get the serialized state of the market, i.e., the account data without
+    /// the discriminator.
+    pub fn state_bytes(&self) -> &[u8] {
+        bytemuck::bytes_of(self)
+    }
+
+    /// Compute the BLAKE3 hash of the [serialized state](Self::state_bytes) of the market.
+    pub fn state_hash(&self) -> [u8; 32] {
+        anchor_lang::solana_program::blake3::hash(self.state_bytes()).to_bytes()
+    }
+
+    /// Zero-fill all the reserved ranges of the market, including those of the buffer.
+    ///
+    /// Returns the number of non-zero bytes that have been zeroed.
+    pub(crate) fn zero_reserved(&mut self) -> u32 {
+        zero_fill(&mut self.reserved)
+            + self.config.zero_reserved()
+            + zero_fill(&mut self.indexer.reserved)
+            + self.state.zero_reserved()
+            + self.buffer.zero_reserved()
+    }
+
     /// Try to get pool of the given kind.
     pub fn try_pool(&self, kind: PoolKind) -> gmsol_model::Result<&Pool> {
         Ok(self
@@ -298,6 +394,40 @@ impl Market {
         Ok(())
     }
 
+    /// Validate that the market is open for trading at the given unix timestamp.
+    pub fn validate_trading_window(&self, timestamp: i64) -> Result<()> {
+        require!(self.config.is_open_at(timestamp), CoreError::MarketNotOpen);
+        Ok(())
+    }
+
+    /// Validate that the given size delta (in USD) is a multiple of the position size increment.
+    pub fn validate_position_size_increment(&self, size_delta_usd: u128) -> Result<()> {
+        self.config.validate_position_size_increment(size_delta_usd)
+    }
+
+    /// Round down the given size delta (in USD) to the nearest multiple of the position size increment.
+    pub fn round_down_to_position_size_increment(&self, size_delta_usd: u128) -> u128 {
+        self.config
+            .round_down_to_position_size_increment(size_delta_usd)
+    }
+
+    /// Validate that the given size delta (in USD) does not exceed the max trade size.
+    pub fn validate_max_trade_size(&self, size_delta_usd: u128) -> Result<()> {
+        self.config.validate_max_trade_size(size_delta_usd)
+    }
+
+    /// Get the keeper tip for the given amount of fees.
+    pub fn keeper_tip(&self, fee_amount: u128) -> Result<u128> {
+        self.config.keeper_tip(fee_amount)
+    }
+
+    /// Get the max oracle timestamps range (in seconds) configured for the market.
+    ///
+    /// Returns `None` if it is not set for the market.
+    pub fn max_oracle_ts_range(&self) -> Option<u64> {
+        self.config.max_oracle_ts_range()
+    }
+
     /// Get config.
     pub fn get_config(&self, key: &str) -> Result<&Factor> {
         let key = MarketConfigKey::from_str(key)
@@ -311,6 +441,13 @@ impl Market {
         self.config.get(key)
     }
 
+    /// Check the market config against the validity bounds.
+    ///
+    /// See [`MarketConfig::validate`](config::MarketConfig::validate) for more details.
+    pub fn validate_config(&self) -> Vec<(MarketConfigKey, &'static str)> {
+        self.config.validate()
+    }
+
     /// Get config mutably.
     pub fn get_config_mut(&mut self, key: &str) -> Result<&mut Factor> {
         let key = MarketConfigKey::from_str(key)
@@ -340,6 +477,73 @@ impl Market {
         Ok(self.config.set_flag(key, value))
     }
 
+    /// Get the key of the virtual pool that this market belongs to.
+    ///
+    /// Returns `None` if the market has not been added to a virtual pool.
+    pub fn virtual_pool_key(&self) -> Option<&Pubkey> {
+        crate::utils::pubkey::optional_address(&self.virtual_pool_key)
+    }
+
+    /// Get the key of the virtual pool in use.
+    ///
+    /// Returns `None` if the virtual pool is not enabled or the market has not been
+    /// added to a virtual pool.
+    pub fn enabled_virtual_pool_key(&self) -> Option<&Pubkey> {
+        if self.get_config_flag_by_key(MarketConfigFlag::VirtualPoolEnabled) {
+            self.virtual_pool_key()
+        } else {
+            None
+        }
+    }
+
+    /// Set the virtual pool key.
+    pub(crate) fn set_virtual_pool_key(&mut self, key: &Pubkey) -> Result<()> {
+        require!(
+            self.virtual_pool_key().is_none(),
+            CoreError::PreconditionsAreNotMet
+        );
+        require!(
+            crate::utils::pubkey::optional_address(key).is_some(),
+            CoreError::InvalidArgument
+        );
+        self.virtual_pool_key = *key;
+        Ok(())
+    }
+
+    /// Set the max open interest of both sides.
+    pub(crate) fn set_max_open_interest(&mut self, for_long: Factor, for_short: Factor) {
+        self.config.max_open_interest_for_long = for_long;
+        self.config.max_open_interest_for_short = for_short;
+    }
+
+    /// Get the override token map of the market.
+    ///
+    /// Returns `None` if the market uses the token map of the store.
+    pub fn token_map(&self) -> Option<&Pubkey> {
+        crate::utils::pubkey::optional_address(&self.token_map)
+    }
+
+    /// Get the token map to use for the market, falling back to the
+    /// token map of the given store.
+    pub fn resolve_token_map<'a>(&'a self, store: &'a Store) -> Option<&'a Pubkey> {
+        self.token_map().or_else(|| store.token_map())
+    }
+
+    /// Set the name of the market.
+    pub(crate) fn set_name(&mut self, name: &str) -> Result<()> {
+        // A trailing zero byte is required to read the name back.
+        require_gt!(MAX_NAME_LEN, name.len(), CoreError::ExceedMaxLengthLimit);
+        self.name = fixed_str_to_bytes(name)?;
+        Ok(())
+    }
+
+    /// Set the override token map.
+    ///
+    /// Pass the default pubkey to use the token map of the store.
+    pub(crate) fn set_token_map(&mut self, token_map: &Pubkey) {
+        self.token_map = *token_map;
+    }
+
     /// Get other market state.
     pub fn state(&self) -> &OtherState {
         &self.state.other
@@ -357,7 +561,15 @@ impl Market {
 
     /// Update config with buffer.
     pub fn update_config_with_buffer(&mut self, buffer: &MarketConfigBuffer) -> Result<()> {
-        for entry in buffer.iter() {
+        self.update_config_with_entries(buffer.iter())
+    }
+
+    /// Update config with the given entries.
+    pub(crate) fn update_config_with_entries<'a>(
+        &mut self,
+        entries: impl IntoIterator<Item = &'a Entry>,
+    ) -> Result<()> {
+        for entry in entries {
             let key = entry.key()?;
             let current_value = self.config.get_mut(key);
             let new_value = entry.value();
@@ -642,9 +854,38 @@ pub struct Indexer {
     glv_withdrawal_count: u64,
     #[cfg_attr(feature = "debug", debug(skip))]
     padding_0: [u8; 8],
+    pending_deposit_count: u32,
+    pending_withdrawal_count: u32,
+    pending_order_count: u32,
+    #[cfg_attr(feature = "debug", debug(skip))]
+    padding_1: [u8; 4],
     #[cfg_attr(feature = "debug", debug(skip))]
     #[cfg_attr(feature = "serde", serde(with = "serde_bytes"))]
-    reserved: [u8; 128],
+    reserved: [u8; 112],
+}
+
+/// Kind of the actions whose pending count is tracked by the market.
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "debug", derive(Debug))]
+pub enum PendingActionKind {
+    /// Deposit.
+    Deposit,
+    /// Withdrawal.
+    Withdrawal,
+    /// Order.
+    Order,
+}
+
+/// The number of pending actions of a market.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "debug", derive(Debug))]
+pub struct PendingCounts {
+    /// The number of pending deposits.
+    pub deposits: u32,
+    /// The number of pending withdrawals.
+    pub withdrawals: u32,
+    /// The number of pending orders.
+    pub orders: u32,
 }
 
 impl Indexer {
@@ -678,6 +919,70 @@ impl Indexer {
         self.glv_withdrawal_count
     }
 
+    /// Get current number of pending actions of the given kind.
+    pub fn pending_count(&self, kind: PendingActionKind) -> u32 {
+        match kind {
+            PendingActionKind::Deposit => self.pending_deposit_count,
+            PendingActionKind::Withdrawal => self.pending_withdrawal_count,
+            PendingActionKind::Order => self.pending_order_count,
+        }
+    }
+
+    /// Get current number of pending actions of all kinds.
+    pub fn pending_counts(&self) -> PendingCounts {
+        PendingCounts {
+            deposits: self.pending_deposit_count,
+            withdrawals: self.pending_withdrawal_count,
+            orders: self.pending_order_count,
+        }
+    }
+
+    fn pending_count_mut(&mut self, kind: PendingActionKind) -> &mut u32 {
+        match kind {
+            PendingActionKind::Deposit => &mut self.pending_deposit_count,
+            PendingActionKind::Withdrawal => &mut self.pending_withdrawal_count,
+            PendingActionKind::Order => &mut self.pending_order_count,
+        }
+    }
+
+    /// Count the given newly created action as pending.
+    ///
+    /// Returns an error if the market already has `max_pending` pending actions
+    /// of the given kind. Zero `max_pending` means no limit, in which case the
+    /// action is not counted.
+    pub(crate) fn occupy_pending_slot(
+        &mut self,
+        kind: PendingActionKind,
+        max_pending: u32,
+        header: &mut ActionHeader,
+    ) -> Result<()> {
+        require!(!header.is_pending_counted(), CoreError::Internal);
+        if max_pending == 0 {
+            return Ok(());
+        }
+        let count = self.pending_count_mut(kind);
+        require_gt!(max_pending, *count, CoreError::MarketDepthLimitExceeded);
+        *count = count
+            .checked_add(1)
+            .ok_or_else(|| error!(CoreError::ValueOverflow))?;
+        header.set_pending_counted(true);
+        Ok(())
+    }
+
+    /// Release the pending slot occupied by the given action, if any.
+    ///
+    /// Must be called once the action leaves the pending state or is closed.
+    pub(crate) fn release_pending_slot(
+        &mut self,
+        kind: PendingActionKind,
+        header: &mut ActionHeader,
+    ) {
+        if header.set_pending_counted(false) {
+            let count = self.pending_count_mut(kind);
+            *count = count.saturating_sub(1);
+        }
+    }
+
     /// Next deposit id.
     pub fn next_deposit_id(&mut self) -> Result<u64> {
         let next_id = self
@@ -744,6 +1049,31 @@ mod tests {
     use super::*;
     use crate::events::{EventClocks, EventOtherState};
 
+    #[test]
+    fn test_decode_header() {
+        let mut market = Market::default();
+        market.meta.market_token_mint = Pubkey::new_unique();
+        market.meta.index_token_mint = Pubkey::new_unique();
+        market.meta.long_token_mint = Pubkey::new_unique();
+        market.meta.short_token_mint = Pubkey::new_unique();
+        market.store = Pubkey::new_unique();
+        market.set_enabled(true);
+
+        let data = bytemuck::bytes_of(&market);
+        let header = &data[..Market::header_len()];
+        assert_eq!(&header[(header.len() - 32)..], market.store.as_ref());
+        let (meta, is_enabled) = Market::decode_header(header).expect("must decode");
+        assert_eq!(bytemuck::bytes_of(&meta), bytemuck::bytes_of(&market.meta));
+        assert!(is_enabled);
+
+        market.set_enabled(false);
+        let data = bytemuck::bytes_of(&market);
+        let (_, is_enabled) =
+            Market::decode_header(&data[..Market::header_len()]).expect("must decode");
+        assert!(!is_enabled);
+        assert!(Market::decode_header(&data[..(Market::header_len() - 1)]).is_none());
+    }
+
     #[test]
     fn test_event_clocks() {
         let clocks = Clocks {
@@ -815,4 +1145,75 @@ mod tests {
 
         assert_eq!(data, event_data);
     }
+
+    #[test]
+    fn test_zero_reserved() {
+        const GARBAGE: u8 = 0xab;
+
+        let mut market = Market::default();
+        bytemuck::bytes_of_mut(&mut market).fill(GARBAGE);
+        let market_token = market.meta.market_token_mint;
+        let rev = market.state.other.rev;
+
+        // The reserved ranges of the market, including the cached state in the buffer.
+        let reserved_state = 1024 + 16 * std::mem::size_of::<pool::PoolStorage>() + 3 * 8 + 256;
+        let reserved = 192 + 28 * 16 + 128 + 2 * reserved_state;
+
+        let bytes_zeroed = market.zero_reserved();
+        assert_eq!(bytes_zeroed as usize, reserved);
+
+        assert!(market.reserved.iter().all(|byte| *byte == 0));
+        assert!(market.indexer.reserved.iter().all(|byte| *byte == 0));
+        assert!(market.state.reserved.iter().all(|byte| *byte == 0));
+        assert!(market.state.clocks.reserved.iter().all(|value| *value == 0));
+        assert!(market.state.other.reserved.iter().all(|byte| *byte == 0));
+
+        // Other fields must not be disturbed.
+        assert_eq!(market.meta.market_token_mint, market_token);
+        assert_eq!(market.state.other.rev, rev);
+        let bytes = bytemuck::bytes_of(&market);
+        assert_eq!(
+            bytes.iter().filter(|byte| **byte == GARBAGE).count(),
+            Market::INIT_SPACE - reserved
+        );
+
+        assert_eq!(market.zero_reserved(), 0);
+    }
+
+    #[test]
+    fn pending_slots() {
+        use bytemuck::Zeroable;
+
+        let mut indexer = Indexer::zeroed();
+        let mut headers = [ActionHeader::zeroed(); 3];
+        let kind = PendingActionKind::Deposit;
+
+        for header in headers.iter_mut().take(2) {
+            indexer.occupy_pending_slot(kind, 2, header).unwrap();
+        }
+        assert_eq!(indexer.pending_count(kind), 2);
+        assert!(indexer
+            .occupy_pending_slot(kind, 2, &mut headers[2])
+            .is_err());
+        assert!(!headers[2].is_pending_counted());
+
+        // Releasing an action twice only frees one slot.
+        indexer.release_pending_slot(kind, &mut headers[0]);
+        indexer.release_pending_slot(kind, &mut headers[0]);
+        assert_eq!(indexer.pending_count(kind), 1);
+        indexer
+            .occupy_pending_slot(kind, 2, &mut headers[2])
+            .unwrap();
+        assert_eq!(indexer.pending_counts().deposits, 2);
+
+        // Zero means no limit, and the action is not counted.
+        indexer
+            .occupy_pending_slot(kind, 0, &mut headers[0])
+            .unwrap();
+        assert!(!headers[0].is_pending_counted());
+        assert_eq!(indexer.pending_count(kind), 2);
+        indexer.release_pending_slot(kind, &mut headers[0]);
+        assert_eq!(indexer.pending_count(kind), 2);
+        assert_eq!(indexer.pending_count(PendingActionKind::Order), 0);
+    }
 }
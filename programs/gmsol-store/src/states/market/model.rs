@@ -210,6 +210,7 @@ impl gmsol_model::BorrowingFeeMarket<{ constants::MARKET_DECIMALS }> for Market
                         self.config
                             .borrowing_fee_above_optimal_usage_factor_for_long,
                     )
+                    .min_borrowing_factor(self.config.borrowing_fee_min_factor_for_long)
                     .build(),
             )
             .short(
@@ -220,6 +221,7 @@ impl gmsol_model::BorrowingFeeMarket<{ constants::MARKET_DECIMALS }> for Market
                         self.config
                             .borrowing_fee_above_optimal_usage_factor_for_short,
                     )
+                    .min_borrowing_factor(self.config.borrowing_fee_min_factor_for_short)
                     .build(),
             )
             .build())
@@ -0,0 +1,217 @@
+use anchor_lang::prelude::*;
+
+use crate::{states::Seed, CoreError};
+
+use super::Factor;
+
+/// The length of the window in which the OI cap adjustments are rate limited.
+pub const OI_CAPS_ADJUSTMENT_WINDOW: i64 = 24 * 60 * 60;
+
+/// Max number of updates in a batch OI caps update.
+pub const MAX_OI_CAP_UPDATES: usize = 16;
+
+/// An update of the OI caps of a market.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+#[cfg_attr(feature = "debug", derive(Debug))]
+pub struct OiCapUpdate {
+    /// Market token.
+    pub market_token: Pubkey,
+    /// New max open interest for long.
+    pub max_oi_long: Factor,
+    /// New max open interest for short.
+    pub max_oi_short: Factor,
+}
+
+/// Market OI Caps.
+///
+/// Maintains the max open interest of a market, which can be adjusted frequently
+/// by a CONFIG_KEEPER, subject to a daily rate limit.
+#[account(zero_copy)]
+#[cfg_attr(feature = "debug", derive(derive_more::Debug))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MarketOiCaps {
+    /// Bump seed.
+    pub bump: u8,
+    #[cfg_attr(feature = "debug", debug(skip))]
+    padding_0: [u8; 15],
+    /// Store.
+    pub store: Pubkey,
+    /// Market token.
+    pub market_token: Pubkey,
+    /// Max open interest for long.
+    max_oi_long: Factor,
+    /// Max open interest for short.
+    max_oi_short: Factor,
+    /// Maximum change of each cap in a window.
+    adjustment_rate_limit: Factor,
+    /// Total change of the long cap in the current window.
+    adjusted_long_in_window: Factor,
+    /// Total change of the short cap in the current window.
+    adjusted_short_in_window: Factor,
+    /// Last adjusted timestamp.
+    last_adjusted: i64,
+    /// The start of the current window.
+    window_start: i64,
+    #[cfg_attr(feature = "debug", debug(skip))]
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes"))]
+    reserved: [u8; 128],
+}
+
+impl MarketOiCaps {
+    /// Get the max open interest for long.
+    pub fn max_oi_long(&self) -> Factor {
+        self.max_oi_long
+    }
+
+    /// Get the max open interest for short.
+    pub fn max_oi_short(&self) -> Factor {
+        self.max_oi_short
+    }
+
+    /// Get the max open interest of the given side.
+    pub fn max_oi(&self, is_long: bool) -> Factor {
+        if is_long {
+            self.max_oi_long
+        } else {
+            self.max_oi_short
+        }
+    }
+
+    /// Get the maximum change of each cap in a window.
+    pub fn adjustment_rate_limit(&self) -> Factor {
+        self.adjustment_rate_limit
+    }
+
+    /// Get the last adjusted timestamp.
+    pub fn last_adjusted(&self) -> i64 {
+        self.last_adjusted
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn init(
+        &mut self,
+        bump: u8,
+        store: &Pubkey,
+        market_token: &Pubkey,
+        max_oi_long: Factor,
+        max_oi_short: Factor,
+        adjustment_rate_limit: Factor,
+        now: i64,
+    ) {
+        self.bump = bump;
+        self.store = *store;
+        self.market_token = *market_token;
+        self.max_oi_long = max_oi_long;
+        self.max_oi_short = max_oi_short;
+        self.adjustment_rate_limit = adjustment_rate_limit;
+        self.last_adjusted = now;
+        self.window_start = now;
+    }
+
+    /// Adjust the caps.
+    ///
+    /// # Errors
+    /// - The total change of each cap in the current window must not exceed
+    ///   the adjustment rate limit.
+    pub(crate) fn adjust(
+        &mut self,
+        new_max_long: Factor,
+        new_max_short: Factor,
+        now: i64,
+    ) -> Result<()> {
+        let window_expired = now.saturating_sub(self.window_start) >= OI_CAPS_ADJUSTMENT_WINDOW;
+        let (adjusted_long, adjusted_short) = if window_expired {
+            (0, 0)
+        } else {
+            (self.adjusted_long_in_window, self.adjusted_short_in_window)
+        };
+
+        let adjusted_long = adjusted_long
+            .checked_add(self.max_oi_long.abs_diff(new_max_long))
+            .ok_or_else(|| error!(CoreError::ValueOverflow))?;
+        let adjusted_short = adjusted_short
+            .checked_add(self.max_oi_short.abs_diff(new_max_short))
+            .ok_or_else(|| error!(CoreError::ValueOverflow))?;
+        require_gte!(
+            self.adjustment_rate_limit,
+            adjusted_long,
+            CoreError::OpenInterestCapAdjustmentRateLimited
+        );
+        require_gte!(
+            self.adjustment_rate_limit,
+            adjusted_short,
+            CoreError::OpenInterestCapAdjustmentRateLimited
+        );
+
+        if window_expired {
+            self.window_start = now;
+        }
+        self.adjusted_long_in_window = adjusted_long;
+        self.adjusted_short_in_window = adjusted_short;
+        self.max_oi_long = new_max_long;
+        self.max_oi_short = new_max_short;
+        self.last_adjusted = now;
+        Ok(())
+    }
+}
+
+impl Seed for MarketOiCaps {
+    const SEED: &'static [u8] = b"market_oi_caps";
+}
+
+impl gmsol_utils::InitSpace for MarketOiCaps {
+    const INIT_SPACE: usize = std::mem::size_of::<Self>();
+}
+
+#[cfg(test)]
+mod tests {
+    use bytemuck::Zeroable;
+
+    use super::*;
+
+    fn caps() -> MarketOiCaps {
+        let mut caps = MarketOiCaps::zeroed();
+        caps.init(
+            0,
+            &Pubkey::default(),
+            &Pubkey::default(),
+            1_000_000,
+            1_000_000,
+            500_000,
+            0,
+        );
+        caps
+    }
+
+    #[test]
+    fn adjustments_within_limit() {
+        let mut caps = caps();
+        caps.adjust(1_100_000, 900_000, 10).unwrap();
+        caps.adjust(1_500_000, 600_000, 20).unwrap();
+        assert_eq!(caps.max_oi_long(), 1_500_000);
+        assert_eq!(caps.max_oi_short(), 600_000);
+        assert_eq!(caps.last_adjusted(), 20);
+    }
+
+    #[test]
+    fn adjustments_exceeding_limit_are_rejected() {
+        let mut caps = caps();
+        caps.adjust(1_400_000, 1_000_000, 10).unwrap();
+        assert!(caps.adjust(1_200_000, 1_000_000, 20).is_err());
+        assert!(caps.adjust(3_000_000, 1_000_000, 20).is_err());
+        assert_eq!(caps.max_oi_long(), 1_400_000);
+        assert_eq!(caps.last_adjusted(), 10);
+    }
+
+    #[test]
+    fn limit_resets_after_window() {
+        let mut caps = caps();
+        caps.adjust(1_500_000, 1_000_000, 10).unwrap();
+        assert!(caps
+            .adjust(1_600_000, 1_000_000, OI_CAPS_ADJUSTMENT_WINDOW - 1)
+            .is_err());
+        caps.adjust(1_600_000, 1_000_000, OI_CAPS_ADJUSTMENT_WINDOW)
+            .unwrap();
+        assert_eq!(caps.max_oi_long(), 1_600_000);
+    }
+}
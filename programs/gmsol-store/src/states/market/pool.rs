@@ -60,6 +60,11 @@ impl Pool {
     fn is_pure(&self) -> bool {
         !matches!(self.is_pure, 0)
     }
+
+    /// Returns whether the pool is empty.
+    pub fn is_empty(&self) -> bool {
+        self.long_token_amount == 0 && self.short_token_amount == 0
+    }
 }
 
 impl gmsol_model::Balance for Pool {
@@ -177,6 +182,11 @@ pub struct Pools {
 }
 
 impl Pools {
+    /// Zero-fill the reserved pools.
+    pub(super) fn zero_reserved(&mut self) -> u32 {
+        super::zero_fill(&mut self.reserved)
+    }
+
     pub(super) fn init(&mut self, is_pure: bool) {
         self.primary.set_is_pure(is_pure);
         self.swap_impact.set_is_pure(is_pure);
@@ -201,6 +211,27 @@ impl Pools {
         self.total_borrowing.set_is_pure(false);
     }
 
+    /// Returns whether all the pools holding amounts are empty.
+    ///
+    /// The borrowing factor and funding pools are cumulative factors, so they are not checked.
+    pub(super) fn is_drained(&self) -> bool {
+        [
+            &self.primary,
+            &self.swap_impact,
+            &self.claimable_fee,
+            &self.open_interest_for_long,
+            &self.open_interest_for_short,
+            &self.open_interest_in_tokens_for_long,
+            &self.open_interest_in_tokens_for_short,
+            &self.position_impact,
+            &self.collateral_sum_for_long,
+            &self.collateral_sum_for_short,
+            &self.total_borrowing,
+        ]
+        .iter()
+        .all(|storage| storage.pool().is_empty())
+    }
+
     pub(super) fn get(&self, kind: PoolKind) -> Option<&PoolStorage> {
         let pool = match kind {
             PoolKind::Primary => &self.primary,
@@ -0,0 +1,237 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    states::{NonceBytes, Seed},
+    CoreError,
+};
+
+use super::config::{Entry, EntryArgs};
+
+/// Max number of config changes in a fee structure proposal.
+pub const MAX_FEE_STRUCTURE_CHANGES: usize = 16;
+
+/// Fee Structure Proposal.
+///
+/// A proposal to update the fee parameters (or any other configs) of a market,
+/// which can only be executed after the voting has ended with more votes for than against.
+#[account]
+#[cfg_attr(feature = "debug", derive(Debug))]
+pub struct FeeStructureProposal {
+    /// Bump seed.
+    pub bump: u8,
+    /// Whether the proposal has been executed.
+    pub is_executed: bool,
+    /// Store.
+    pub store: Pubkey,
+    /// Proposer.
+    pub proposer: Pubkey,
+    /// Market token.
+    pub market_token: Pubkey,
+    /// Nonce.
+    pub nonce: NonceBytes,
+    /// The timestamp at which the proposal was created.
+    pub proposed_at: i64,
+    /// The timestamp at which the voting ends.
+    pub voting_ends_at: i64,
+    /// The number of votes for.
+    pub votes_for: u32,
+    /// The number of votes against.
+    pub votes_against: u32,
+    fee_changes: Vec<Entry>,
+}
+
+impl Seed for FeeStructureProposal {
+    const SEED: &'static [u8] = b"fee_proposal";
+}
+
+impl FeeStructureProposal {
+    pub(crate) fn init_space(len: usize) -> usize {
+        1 + 1 + 32 + 32 + 32 + 32 + 8 + 8 + 4 + 4 + 4 + Entry::INIT_SPACE * len
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn init(
+        &mut self,
+        bump: u8,
+        store: &Pubkey,
+        proposer: &Pubkey,
+        market_token: &Pubkey,
+        nonce: &NonceBytes,
+        changes: Vec<EntryArgs>,
+        voting_duration_secs: u32,
+        now: i64,
+    ) -> Result<()> {
+        require!(!changes.is_empty(), CoreError::InvalidArgument);
+        require_gte!(
+            MAX_FEE_STRUCTURE_CHANGES,
+            changes.len(),
+            CoreError::ExceedMaxLengthLimit
+        );
+        require_neq!(voting_duration_secs, 0, CoreError::InvalidArgument);
+
+        self.bump = bump;
+        self.store = *store;
+        self.proposer = *proposer;
+        self.market_token = *market_token;
+        self.nonce = *nonce;
+        self.proposed_at = now;
+        self.voting_ends_at = now.saturating_add_unsigned(voting_duration_secs.into());
+        self.fee_changes = changes
+            .into_iter()
+            .map(Entry::try_from)
+            .collect::<Result<_>>()?;
+        Ok(())
+    }
+
+    /// Create an iterator of the config changes.
+    pub fn fee_changes(&self) -> impl Iterator<Item = &Entry> {
+        self.fee_changes.iter()
+    }
+
+    /// Returns whether the voting has ended at the given timestamp.
+    pub fn is_voting_ended(&self, now: i64) -> bool {
+        now > self.voting_ends_at
+    }
+
+    /// Returns whether the proposal has more votes for than against.
+    pub fn is_approved(&self) -> bool {
+        self.votes_for > self.votes_against
+    }
+
+    pub(crate) fn vote(&mut self, vote_for: bool, now: i64) -> Result<()> {
+        require!(!self.is_executed, CoreError::PreconditionsAreNotMet);
+        require!(
+            !self.is_voting_ended(now),
+            CoreError::PreconditionsAreNotMet
+        );
+        let votes = if vote_for {
+            &mut self.votes_for
+        } else {
+            &mut self.votes_against
+        };
+        *votes = votes
+            .checked_add(1)
+            .ok_or_else(|| error!(CoreError::ValueOverflow))?;
+        Ok(())
+    }
+
+    pub(crate) fn validate_executable(&self, now: i64) -> Result<()> {
+        require!(!self.is_executed, CoreError::PreconditionsAreNotMet);
+        require!(self.is_voting_ended(now), CoreError::PreconditionsAreNotMet);
+        require!(self.is_approved(), CoreError::PreconditionsAreNotMet);
+        Ok(())
+    }
+}
+
+/// Fee Structure Vote.
+///
+/// The vote of a keeper on a [`FeeStructureProposal`]. Its existence
+/// prevents the keeper from voting on the same proposal more than once.
+#[account]
+#[derive(InitSpace)]
+#[cfg_attr(feature = "debug", derive(Debug))]
+pub struct FeeStructureVote {
+    /// Bump seed.
+    pub bump: u8,
+    /// Whether the vote is for the proposal.
+    pub vote_for: bool,
+    /// Proposal.
+    pub proposal: Pubkey,
+    /// Voter.
+    pub voter: Pubkey,
+}
+
+impl Seed for FeeStructureVote {
+    const SEED: &'static [u8] = b"fee_vote";
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn proposal() -> FeeStructureProposal {
+        FeeStructureProposal {
+            bump: 0,
+            is_executed: false,
+            store: Pubkey::default(),
+            proposer: Pubkey::default(),
+            market_token: Pubkey::default(),
+            nonce: Default::default(),
+            proposed_at: 0,
+            voting_ends_at: 0,
+            votes_for: 0,
+            votes_against: 0,
+            fee_changes: vec![],
+        }
+    }
+
+    #[test]
+    fn voting() {
+        let mut proposal = proposal();
+        let changes = vec![EntryArgs {
+            key: "swap_fee_factor_for_positive_impact".to_string(),
+            value: 1,
+        }];
+        proposal
+            .init(
+                0,
+                &Pubkey::default(),
+                &Pubkey::default(),
+                &Pubkey::default(),
+                &Default::default(),
+                changes,
+                60,
+                100,
+            )
+            .unwrap();
+        assert_eq!(proposal.voting_ends_at, 160);
+        assert_eq!(proposal.fee_changes().count(), 1);
+
+        proposal.vote(true, 100).unwrap();
+        proposal.vote(true, 130).unwrap();
+        proposal.vote(false, 160).unwrap();
+        assert!(proposal.vote(true, 161).is_err());
+        assert_eq!((proposal.votes_for, proposal.votes_against), (2, 1));
+
+        assert!(proposal.validate_executable(160).is_err());
+        assert!(proposal.validate_executable(161).is_ok());
+    }
+
+    #[test]
+    fn rejected_proposal_is_not_executable() {
+        let mut proposal = proposal();
+        proposal.vote(true, 0).unwrap();
+        proposal.vote(false, 0).unwrap();
+        assert!(!proposal.is_approved());
+        assert!(proposal.validate_executable(1).is_err());
+    }
+
+    #[test]
+    fn invalid_changes() {
+        let init = |changes: Vec<EntryArgs>| {
+            proposal().init(
+                0,
+                &Pubkey::default(),
+                &Pubkey::default(),
+                &Pubkey::default(),
+                &Default::default(),
+                changes,
+                60,
+                0,
+            )
+        };
+        assert!(init(vec![]).is_err());
+        assert!(init(vec![EntryArgs {
+            key: "no_such_key".to_string(),
+            value: 1,
+        }])
+        .is_err());
+        let too_many = (0..=MAX_FEE_STRUCTURE_CHANGES)
+            .map(|_| EntryArgs {
+                key: "swap_fee_factor_for_positive_impact".to_string(),
+                value: 1,
+            })
+            .collect();
+        assert!(init(too_many).is_err());
+    }
+}
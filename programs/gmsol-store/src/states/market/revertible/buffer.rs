@@ -26,6 +26,11 @@ impl RevertibleBuffer {
         self.rev = 1;
     }
 
+    /// Zero-fill the reserved ranges of the cached state.
+    pub(crate) fn zero_reserved(&mut self) -> u32 {
+        self.state.zero_reserved()
+    }
+
     pub(super) fn pool<'a>(&'a self, kind: PoolKind, storage: &'a State) -> Option<&'a Pool> {
         let pool_storage = self.state.pools.get(kind)?;
         Some(
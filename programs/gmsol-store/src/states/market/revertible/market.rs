@@ -16,11 +16,12 @@ use crate::{
     states::{
         market::{
             clock::{AsClock, AsClockMut},
+            virtual_pool::VirtualPool,
             Clocks, Pool,
         },
         Factor, HasMarketMeta, Market, MarketMeta, OtherState,
     },
-    CoreError,
+    CoreError, ModelError,
 };
 
 use super::{Revertible, Revision};
@@ -44,6 +45,35 @@ pub struct RevertibleMarket<'a, 'info> {
     order_fee_discount_factor: u128,
     event_emitter: EventEmitter<'a, 'info>,
     swap_pricing: SwapPricingKind,
+    virtual_pool: Option<RevertibleVirtualPool<'a>>,
+}
+
+/// Revertible Virtual Pool.
+struct RevertibleVirtualPool<'a> {
+    storage: RefMut<'a, VirtualPool>,
+    state: VirtualPool,
+    open_interest: Pool,
+}
+
+impl<'a> RevertibleVirtualPool<'a> {
+    fn new(storage: RefMut<'a, VirtualPool>) -> Self {
+        let state = *storage;
+        Self {
+            open_interest: state.open_interest_pool(),
+            state,
+            storage,
+        }
+    }
+
+    fn apply_delta(&mut self, is_long: bool, delta: i128) -> Result<()> {
+        self.state.apply_delta(is_long, delta)?;
+        self.open_interest = self.state.open_interest_pool();
+        Ok(())
+    }
+
+    fn commit(mut self) {
+        *self.storage = self.state;
+    }
 }
 
 impl Key for RevertibleMarket<'_, '_> {
@@ -76,9 +106,34 @@ impl<'a, 'info> RevertibleMarket<'a, 'info> {
             order_fee_discount_factor: 0,
             event_emitter,
             swap_pricing: SwapPricingKind::Swap,
+            virtual_pool: None,
         })
     }
 
+    /// Use the given virtual pool if the market has enabled it.
+    ///
+    /// ## Errors
+    /// - The virtual pool must be provided and match the market if enabled.
+    pub(crate) fn with_virtual_pool(
+        mut self,
+        virtual_pool: Option<&'a AccountLoader<'info, VirtualPool>>,
+    ) -> Result<Self> {
+        let Some(key) = self.market.enabled_virtual_pool_key().copied() else {
+            return Ok(self);
+        };
+        let virtual_pool = virtual_pool
+            .ok_or_else(|| error!(CoreError::InvalidVirtualPool))?
+            .load_mut()?;
+        require_keys_eq!(
+            virtual_pool.store,
+            self.market.store,
+            CoreError::StoreMismatched
+        );
+        require_keys_eq!(*virtual_pool.key(), key, CoreError::InvalidVirtualPool);
+        self.virtual_pool = Some(RevertibleVirtualPool::new(virtual_pool));
+        Ok(self)
+    }
+
     pub(crate) fn with_order_fee_discount_factor(mut self, discount: u128) -> Self {
         self.order_fee_discount_factor = discount;
         self
@@ -222,6 +277,33 @@ impl<'a, 'info> RevertibleMarket<'a, 'info> {
         self.other_mut().trade_count = next_trade_id;
         Ok(next_trade_id)
     }
+
+    /// Validate the swap impact pool against the liquidity remaining after a withdrawal.
+    ///
+    /// # Errors
+    /// - The swap impact pool amount of each token must not exceed the configured
+    ///   factor of the remaining liquidity pool amount of the same token.
+    pub(crate) fn validate_swap_impact_pool_for_withdrawal(&self) -> Result<()> {
+        use gmsol_model::{Balance, BaseMarket};
+
+        let swap_impact_pool = self.swap_impact_pool().map_err(ModelError::from)?;
+        let liquidity_pool = self.liquidity_pool().map_err(ModelError::from)?;
+        for (swap_impact_pool_amount, liquidity_pool_amount) in [
+            (swap_impact_pool.long_amount(), liquidity_pool.long_amount()),
+            (
+                swap_impact_pool.short_amount(),
+                liquidity_pool.short_amount(),
+            ),
+        ] {
+            self.market
+                .config
+                .validate_swap_impact_pool_for_withdrawal(
+                    swap_impact_pool_amount.map_err(ModelError::from)?,
+                    liquidity_pool_amount.map_err(ModelError::from)?,
+                )?;
+        }
+        Ok(())
+    }
 }
 
 impl Revertible for RevertibleMarket<'_, '_> {
@@ -233,6 +315,9 @@ impl Revertible for RevertibleMarket<'_, '_> {
             ..
         } = &mut *self.market;
         buffer.commit_to_storage(state, &meta.market_token_mint, &self.event_emitter);
+        if let Some(virtual_pool) = self.virtual_pool.take() {
+            virtual_pool.commit();
+        }
         debug_msg!(
             "[Balance committed] {}: {},{}",
             meta.market_token_mint,
@@ -417,6 +502,13 @@ impl gmsol_model::PositionImpactMarket<{ constants::MARKET_DECIMALS }>
     fn passed_in_seconds_for_position_impact_distribution(&self) -> gmsol_model::Result<u64> {
         AsClock::from(&self.clocks().price_impact_distribution).passed_in_seconds()
     }
+
+    fn virtual_open_interest_pool(&self) -> gmsol_model::Result<Option<&Self::Pool>> {
+        Ok(self
+            .virtual_pool
+            .as_ref()
+            .map(|virtual_pool| &virtual_pool.open_interest))
+    }
 }
 
 impl gmsol_model::PositionImpactMarketMut<{ constants::MARKET_DECIMALS }>
@@ -586,4 +678,15 @@ impl gmsol_model::PerpMarketMut<{ constants::MARKET_DECIMALS }> for RevertibleMa
     fn total_borrowing_pool_mut(&mut self) -> gmsol_model::Result<&mut Self::Pool> {
         self.pool_mut(PoolKind::TotalBorrowing)
     }
+
+    fn apply_delta_to_virtual_open_interest(
+        &mut self,
+        is_long: bool,
+        delta: &Self::Signed,
+    ) -> gmsol_model::Result<()> {
+        if let Some(virtual_pool) = self.virtual_pool.as_mut() {
+            virtual_pool.apply_delta(is_long, *delta)?;
+        }
+        Ok(())
+    }
 }
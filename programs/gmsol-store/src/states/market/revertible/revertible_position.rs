@@ -1,7 +1,9 @@
 use std::cell::RefMut;
 
 use anchor_lang::prelude::*;
-use gmsol_model::{action::decrease_position::DecreasePositionSwapType, num::Unsigned};
+use gmsol_model::{
+    action::decrease_position::DecreasePositionSwapType, num::Unsigned, price::Prices,
+};
 
 use crate::{
     constants,
@@ -19,6 +21,7 @@ pub struct RevertiblePosition<'a, 'info> {
     state: PositionState,
     is_collateral_token_long: bool,
     is_long: bool,
+    cross_margin_collateral_value: i128,
 }
 
 impl<'a, 'info> RevertiblePosition<'a, 'info> {
@@ -44,9 +47,16 @@ impl<'a, 'info> RevertiblePosition<'a, 'info> {
             state: storage.state,
             market,
             storage,
+            cross_margin_collateral_value: 0,
         })
     }
 
+    /// Set the total excess collateral value of the other positions in cross margin mode.
+    pub(crate) fn with_cross_margin_collateral_value(mut self, value: i128) -> Self {
+        self.cross_margin_collateral_value = value;
+        self
+    }
+
     pub(crate) fn collateral_token(&self) -> &Pubkey {
         &self.storage.collateral_token
     }
@@ -122,6 +132,13 @@ impl<'a, 'info> gmsol_model::Position<{ constants::MARKET_DECIMALS }>
     fn on_validate(&self) -> gmsol_model::Result<()> {
         self.storage.validate_for_market(&self.market.market)
     }
+
+    fn cross_margin_collateral_value(
+        &self,
+        _prices: &Prices<Self::Num>,
+    ) -> gmsol_model::Result<Self::Signed> {
+        Ok(self.cross_margin_collateral_value)
+    }
 }
 
 impl gmsol_model::PositionMut<{ constants::MARKET_DECIMALS }> for RevertiblePosition<'_, '_> {
@@ -1,5 +1,14 @@
 use anchor_lang::prelude::*;
-use gmsol_model::{price::Prices, BaseMarketExt, BorrowingFeeMarketExt, PerpMarket};
+use anchor_spl::token::Mint;
+use gmsol_model::{
+    num::{MulDiv, Unsigned},
+    price::Prices,
+    utils::apply_factor,
+    Balance, BaseMarketExt, BorrowingFeeMarketExt, LiquidityMarketExt, PerpMarket, PerpMarketExt,
+    PnlFactorKind, PoolKind, PositionImpactMarketExt, SwapMarket, SwapMarketExt,
+};
+
+use crate::constants;
 
 use super::Market;
 
@@ -59,3 +68,611 @@ impl MarketStatus {
         })
     }
 }
+
+/// Expected value of providing liquidity to a market.
+#[cfg_attr(feature = "debug", derive(Debug))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct MarketExpectedValue {
+    /// Expected order fee revenue for the pool.
+    pub expected_fee_revenue_usd: u128,
+    /// Expected borrowing fee revenue for the pool.
+    pub expected_borrowing_revenue_usd: u128,
+    /// Expected PnL of the pool against the current positions.
+    pub expected_pnl_exposure_usd: i128,
+    /// Expected net value.
+    pub expected_net_value_usd: i128,
+    /// Annualized yield of the expected net value to the pool value, in basis points.
+    pub annualized_yield_bps: u32,
+}
+
+impl MarketExpectedValue {
+    /// Estimate from market and prices, assuming `trading_volume_usd` is traded
+    /// during `time_horizon_secs`.
+    pub fn from_market(
+        market: &Market,
+        prices: &Prices<u128>,
+        trading_volume_usd: u128,
+        time_horizon_secs: u64,
+    ) -> gmsol_model::Result<Self> {
+        let value = market.expected_value(prices, &trading_volume_usd, time_horizon_secs)?;
+        Ok(Self {
+            expected_fee_revenue_usd: value.fee_revenue,
+            expected_borrowing_revenue_usd: value.borrowing_revenue,
+            expected_pnl_exposure_usd: value.pnl_exposure,
+            expected_net_value_usd: value.net_value,
+            annualized_yield_bps: factor_to_bps(value.annualized_yield_factor),
+        })
+    }
+}
+
+/// The underlying tokens backing one unit of market token.
+#[cfg_attr(feature = "debug", derive(Debug))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct MarketTokenBacking {
+    /// The amount of long token backing one unit of market token.
+    /// For a pure market, this is the amount of the only token.
+    pub long_amount: u64,
+    /// The amount of short token backing one unit of market token.
+    /// Always zero for a pure market.
+    pub short_amount: u64,
+    /// The value of the backing tokens (without pnl), using min prices.
+    pub value: u128,
+}
+
+impl MarketTokenBacking {
+    /// Calculate from market, market token supply and prices.
+    ///
+    /// All fields are zero if the supply is zero.
+    pub fn from_market(
+        market: &Market,
+        supply: u64,
+        prices: &Prices<u128>,
+    ) -> gmsol_model::Result<Self> {
+        if supply == 0 {
+            return Ok(Self {
+                long_amount: 0,
+                short_amount: 0,
+                value: 0,
+            });
+        }
+
+        let supply = u128::from(supply);
+        let unit = 10u128.pow(constants::MARKET_TOKEN_DECIMALS as u32);
+        let per_unit = |amount: u128| -> gmsol_model::Result<u64> {
+            amount
+                .checked_mul_div(&unit, &supply)
+                .ok_or(gmsol_model::Error::Computation(
+                    "calculating backing amount",
+                ))?
+                .try_into()
+                .map_err(|_| gmsol_model::Error::Convert)
+        };
+
+        // For a pure market, the whole pool is kept as long token.
+        let pool = market.try_pool(PoolKind::Primary)?;
+        let long_amount = per_unit(pool.long_token_amount)?;
+        let short_amount = per_unit(pool.short_token_amount)?;
+
+        let pool_value = market
+            .pool_value_without_pnl_for_one_side(prices, true, false)?
+            .checked_add(market.pool_value_without_pnl_for_one_side(prices, false, false)?)
+            .ok_or(gmsol_model::Error::Computation("calculating pool value"))?;
+        let value = pool_value
+            .checked_mul_div(&unit, &supply)
+            .ok_or(gmsol_model::Error::Computation("calculating backing value"))?;
+
+        Ok(Self {
+            long_amount,
+            short_amount,
+            value,
+        })
+    }
+}
+
+/// The result of verifying the market token supply against the market balances.
+#[cfg_attr(feature = "debug", derive(Debug))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct SupplyVerification {
+    /// The current supply of the market token.
+    pub current_supply: u64,
+    /// The supply implied by the value of the market balances owned by the liquidity providers.
+    pub expected_supply_estimate: u64,
+    /// `expected_supply_estimate - current_supply`, saturating at the bounds of [`i64`].
+    pub delta: i64,
+    /// The value of the market balances owned by the liquidity providers, using min prices.
+    pub pool_value_usd: u128,
+    /// The value of the liquidity pool (without pnl) backing one unit of market token,
+    /// using min prices.
+    pub nav_per_token: u128,
+    /// Whether the absolute `delta` is within the tolerance.
+    pub is_consistent: bool,
+}
+
+impl SupplyVerification {
+    /// Verify the market token supply against the balances of the market with the given prices.
+    ///
+    /// The NAV per token is derived from the liquidity pool, while the pool value is derived
+    /// from the recorded token balances after deducting the amounts not owned by the liquidity
+    /// providers (claimable fees, swap impact pool and position collaterals). Therefore, the
+    /// estimate only departs from the supply when the balances have changed without updating
+    /// the pools, or the other way around.
+    pub fn from_market(
+        market: &Market,
+        supply: u64,
+        prices: &Prices<u128>,
+        tolerance_factor: u128,
+    ) -> gmsol_model::Result<Self> {
+        let nav_per_token = MarketTokenBacking::from_market(market, supply, prices)?.value;
+
+        let is_pure = market.is_pure();
+        let state = market.state();
+        let required_for_one_side = |is_long_side: bool| -> gmsol_model::Result<u128> {
+            market
+                .expected_min_token_balance_excluding_collateral_amount_for_one_token_side(
+                    is_long_side,
+                )?
+                .checked_add(market.total_collateral_amount_for_one_token_side(is_long_side)?)
+                .ok_or(gmsol_model::Error::Computation(
+                    "calculating required balance",
+                ))
+        };
+        let excess_value = |is_long_token: bool| -> gmsol_model::Result<i128> {
+            let mut required = required_for_one_side(is_long_token)?;
+            // Since both sides are kept as the same token in a pure market,
+            // we need to include the other side.
+            if is_pure {
+                required = required
+                    .checked_add(required_for_one_side(!is_long_token)?)
+                    .ok_or(gmsol_model::Error::Computation(
+                        "calculating required balance",
+                    ))?;
+            }
+            let (balance, price) = if is_long_token {
+                (state.long_token_balance_raw(), &prices.long_token_price)
+            } else {
+                (state.short_token_balance_raw(), &prices.short_token_price)
+            };
+            let balance = u128::from(balance);
+            balance
+                .to_signed()?
+                .checked_sub(required.to_signed()?)
+                .and_then(|excess| excess.checked_mul(price.pick_price(false).to_signed().ok()?))
+                .ok_or(gmsol_model::Error::Computation(
+                    "calculating excess balance value",
+                ))
+        };
+
+        let mut value = market
+            .pool_value_without_pnl_for_one_side(prices, true, false)?
+            .checked_add(market.pool_value_without_pnl_for_one_side(prices, false, false)?)
+            .ok_or(gmsol_model::Error::Computation("calculating pool value"))?
+            .to_signed()?
+            .checked_add(excess_value(true)?)
+            .ok_or(gmsol_model::Error::Computation("calculating pool value"))?;
+        if !is_pure {
+            value = value
+                .checked_add(excess_value(false)?)
+                .ok_or(gmsol_model::Error::Computation("calculating pool value"))?;
+        }
+        let pool_value_usd = value.max(0).unsigned_abs();
+
+        let expected_supply_estimate = if nav_per_token == 0 {
+            0
+        } else {
+            let unit = 10u128.pow(constants::MARKET_TOKEN_DECIMALS as u32);
+            pool_value_usd
+                .checked_mul_div(&unit, &nav_per_token)
+                .ok_or(gmsol_model::Error::Computation(
+                    "calculating expected supply",
+                ))?
+                .try_into()
+                .unwrap_or(u64::MAX)
+        };
+
+        let delta = (i128::from(expected_supply_estimate) - i128::from(supply))
+            .clamp(i64::MIN.into(), i64::MAX.into()) as i64;
+        let tolerance = apply_factor::<_, { constants::MARKET_DECIMALS }>(
+            &u128::from(supply),
+            &tolerance_factor,
+        )
+        .ok_or(gmsol_model::Error::Computation(
+            "calculating supply tolerance",
+        ))?;
+        let is_consistent = u128::from(delta.unsigned_abs()) <= tolerance
+            && (nav_per_token != 0 || pool_value_usd == 0);
+
+        Ok(Self {
+            current_supply: supply,
+            expected_supply_estimate,
+            delta,
+            pool_value_usd,
+            nav_per_token,
+            is_consistent,
+        })
+    }
+}
+
+/// The value of the liquidity pool of a market and its market token.
+#[cfg_attr(feature = "debug", derive(Debug))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct LpValueInfo {
+    /// The value of one unit of market token, i.e., the market token price.
+    pub nav_per_token: u128,
+    /// The value of the liquidity pool.
+    pub total_pool_value: u128,
+    /// The current supply of the market token.
+    pub outstanding_supply: u64,
+    /// The ratio of the pnl of long positions to the long pool value.
+    pub pnl_factor_long: i128,
+    /// The ratio of the pnl of short positions to the short pool value.
+    pub pnl_factor_short: i128,
+}
+
+impl LpValueInfo {
+    /// The PnL factor used to calculate the pool value.
+    pub const PNL_FACTOR: PnlFactorKind = PnlFactorKind::MaxForTrader;
+
+    /// Calculate from market, market token and prices.
+    ///
+    /// The NAV per token is the same as the market token price calculated with
+    /// [`PNL_FACTOR`](Self::PNL_FACTOR).
+    pub fn from_market(
+        market: &Market,
+        market_token: &Mint,
+        prices: &Prices<u128>,
+        maximize: bool,
+    ) -> gmsol_model::Result<Self> {
+        let liquidity_market = market.as_liquidity_market(market_token);
+        let nav_per_token =
+            liquidity_market.market_token_price(prices, Self::PNL_FACTOR, maximize)?;
+        let total_pool_value = liquidity_market.pool_value(prices, Self::PNL_FACTOR, maximize)?;
+        if total_pool_value.is_negative() {
+            return Err(gmsol_model::Error::InvalidPoolValue(
+                "the pool value is negative",
+            ));
+        }
+        Ok(Self {
+            nav_per_token,
+            total_pool_value: total_pool_value.unsigned_abs(),
+            outstanding_supply: market_token.supply,
+            pnl_factor_long: market.pnl_factor(prices, true, maximize)?,
+            pnl_factor_short: market.pnl_factor(prices, false, maximize)?,
+        })
+    }
+}
+
+/// Fee breakdown of a swap.
+#[cfg_attr(feature = "debug", derive(Debug))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct SwapFeeBreakdown {
+    /// The swap fee factor applied to the swap.
+    pub swap_fee_factor: u128,
+    /// The price impact relative to the value of the swap.
+    pub price_impact_factor: u128,
+    /// The price impact value. Positive impact is in favor of the user.
+    pub price_impact_usd: i128,
+    /// The value of the swap fee.
+    pub swap_fee_usd: u128,
+    /// The combined cost of the swap fee and the price impact relative to the
+    /// value of the swap, in basis points.
+    pub effective_fee_bps: u32,
+    /// The expected amount of output token.
+    pub output_amount: u64,
+}
+
+impl SwapFeeBreakdown {
+    /// Simulate a swap of `token_in_amount` in the market with the given prices.
+    pub fn from_market(
+        market: &Market,
+        is_token_in_long: bool,
+        token_in_amount: u64,
+        prices: &Prices<u128>,
+    ) -> gmsol_model::Result<Self> {
+        let report = market.simulate_swap(is_token_in_long, token_in_amount.into(), *prices)?;
+
+        let token_in_price = if is_token_in_long {
+            &prices.long_token_price
+        } else {
+            &prices.short_token_price
+        };
+        let to_value = |amount: u128| {
+            amount
+                .checked_mul(token_in_price.mid())
+                .ok_or(gmsol_model::Error::Computation("calculating swap value"))
+        };
+        let swap_value = to_value(token_in_amount.into())?;
+        let fees = report.token_in_fees();
+        let swap_fee_usd = to_value(
+            fees.fee_amount_for_receiver()
+                .checked_add(*fees.fee_amount_for_pool())
+                .ok_or(gmsol_model::Error::Computation(
+                    "calculating swap fee amount",
+                ))?,
+        )?;
+
+        let price_impact_usd = *report.price_impact();
+        let price_impact_factor = price_impact_usd
+            .unsigned_abs()
+            .checked_mul_div(&constants::MARKET_USD_UNIT, &swap_value)
+            .ok_or(gmsol_model::Error::Computation(
+                "calculating price impact factor",
+            ))?;
+
+        // Positive price impact offsets the swap fee.
+        let effective_fee_usd = i128::try_from(swap_fee_usd)
+            .map_err(|_| gmsol_model::Error::Convert)?
+            .saturating_sub(price_impact_usd)
+            .max(0)
+            .unsigned_abs();
+        let effective_fee_factor = effective_fee_usd
+            .checked_mul_div(&constants::MARKET_USD_UNIT, &swap_value)
+            .ok_or(gmsol_model::Error::Computation(
+                "calculating effective fee factor",
+            ))?;
+
+        Ok(Self {
+            swap_fee_factor: *market
+                .swap_fee_params()?
+                .factor(price_impact_usd.is_positive()),
+            price_impact_factor,
+            price_impact_usd,
+            swap_fee_usd,
+            effective_fee_bps: factor_to_bps(effective_fee_factor),
+            output_amount: (*report.token_out_amount())
+                .try_into()
+                .map_err(|_| gmsol_model::Error::Convert)?,
+        })
+    }
+}
+
+/// The best swap path found for a token pair among a set of markets.
+#[cfg_attr(feature = "debug", derive(Debug))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct ArbitrageOpportunity {
+    /// The market tokens of the markets in the path.
+    pub best_path: Vec<Pubkey>,
+    /// The value of the output minus the value of the input, using mid prices.
+    pub expected_profit_usd: i128,
+    /// The amount of input token.
+    pub input_amount: u64,
+    /// The expected amount of output token.
+    pub output_amount: u64,
+    /// The total price impact value of the swaps in the path.
+    /// Positive impact is in favor of the user.
+    pub price_impact_total: i128,
+}
+
+impl ArbitrageOpportunity {
+    /// Max number of markets in a path.
+    pub const MAX_PATH_LENGTH: usize = 4;
+
+    /// Max number of markets to search.
+    pub const MAX_MARKETS: usize = 32;
+
+    /// Find the path with the highest output amount for swapping `input_amount` of
+    /// `input_token` to `output_token` through the given markets.
+    ///
+    /// The `input_token` and `output_token` can be the same to find a circular path.
+    /// Returns `None` if there is no path.
+    pub fn find(
+        markets: &[(&Market, &Prices<u128>)],
+        input_token: &Pubkey,
+        output_token: &Pubkey,
+        input_amount: u64,
+    ) -> gmsol_model::Result<Option<Self>> {
+        if markets.len() > Self::MAX_MARKETS {
+            return Err(gmsol_model::Error::InvalidArgument("too many markets"));
+        }
+
+        let mut search = PathSearch {
+            markets,
+            output_token,
+            path: Vec::with_capacity(Self::MAX_PATH_LENGTH),
+            best: None,
+        };
+        search.search(input_token, input_amount.into(), 0)?;
+        let Some(best) = search.best else {
+            return Ok(None);
+        };
+
+        let token_price = |idx: usize, token: &Pubkey| {
+            let (market, prices) = markets[idx];
+            let is_long = market
+                .meta()
+                .to_token_side(token)
+                .map_err(|_| gmsol_model::Error::InvalidArgument("token not found"))?;
+            Ok(if is_long {
+                prices.long_token_price.mid()
+            } else {
+                prices.short_token_price.mid()
+            })
+        };
+        let to_value = |amount: u128, price: u128| {
+            amount
+                .checked_mul(price)
+                .and_then(|value| i128::try_from(value).ok())
+                .ok_or(gmsol_model::Error::Computation("calculating swap value"))
+        };
+        let first = *best.path.first().expect("must be non-empty");
+        let last = *best.path.last().expect("must be non-empty");
+        let input_value = to_value(input_amount.into(), token_price(first, input_token)?)?;
+        let output_value = to_value(best.output_amount, token_price(last, output_token)?)?;
+
+        Ok(Some(Self {
+            best_path: best
+                .path
+                .iter()
+                .map(|idx| markets[*idx].0.meta().market_token_mint)
+                .collect(),
+            expected_profit_usd: output_value
+                .checked_sub(input_value)
+                .ok_or(gmsol_model::Error::Computation("calculating profit"))?,
+            input_amount,
+            output_amount: best
+                .output_amount
+                .try_into()
+                .map_err(|_| gmsol_model::Error::Convert)?,
+            price_impact_total: best.price_impact,
+        }))
+    }
+}
+
+struct SearchedPath {
+    path: Vec<usize>,
+    output_amount: u128,
+    price_impact: i128,
+}
+
+/// Depth-first search over the swap paths.
+struct PathSearch<'a> {
+    markets: &'a [(&'a Market, &'a Prices<u128>)],
+    output_token: &'a Pubkey,
+    path: Vec<usize>,
+    best: Option<SearchedPath>,
+}
+
+impl PathSearch<'_> {
+    fn search(
+        &mut self,
+        token: &Pubkey,
+        amount: u128,
+        price_impact: i128,
+    ) -> gmsol_model::Result<()> {
+        let is_better = match &self.best {
+            Some(best) => amount > best.output_amount,
+            None => true,
+        };
+        if !self.path.is_empty() && token == self.output_token && is_better {
+            self.best = Some(SearchedPath {
+                path: self.path.clone(),
+                output_amount: amount,
+                price_impact,
+            });
+        }
+
+        if self.path.len() >= ArbitrageOpportunity::MAX_PATH_LENGTH {
+            return Ok(());
+        }
+
+        let markets = self.markets;
+        for (idx, (market, prices)) in markets.iter().enumerate() {
+            if market.is_pure() || self.path.contains(&idx) {
+                continue;
+            }
+            let meta = market.meta();
+            let (Ok(is_token_in_long), Ok(next_token)) =
+                (meta.to_token_side(token), meta.opposite_token(token))
+            else {
+                continue;
+            };
+            // Skip the markets that cannot perform the swap, e.g., due to insufficient liquidity.
+            let Ok(report) = market.simulate_swap(is_token_in_long, amount, **prices) else {
+                continue;
+            };
+            let next_amount = *report.token_out_amount();
+            if next_amount == 0 {
+                continue;
+            }
+            let next_price_impact = price_impact.checked_add(*report.price_impact()).ok_or(
+                gmsol_model::Error::Computation("calculating total price impact"),
+            )?;
+
+            self.path.push(idx);
+            self.search(next_token, next_amount, next_price_impact)?;
+            self.path.pop();
+        }
+
+        Ok(())
+    }
+}
+
+/// Price impact sensitivity of a market for opening positions of one side.
+///
+/// Unlike the other views, positive impact values are against the trader.
+#[cfg_attr(feature = "debug", derive(Debug))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct PriceImpactSensitivity {
+    /// The price impact of a position of 1M USD.
+    pub impact_at_1m_usd: i128,
+    /// The price impact of a position of 5M USD.
+    pub impact_at_5m_usd: i128,
+    /// The price impact of a position of 10M USD.
+    pub impact_at_10m_usd: i128,
+    /// The price impact of a position of 50M USD.
+    pub impact_at_50m_usd: i128,
+    /// The absolute price impact of a position of 1M USD relative to its size.
+    pub impact_factor_per_million: u128,
+    /// The position size that balances the open interest, at which the marginal
+    /// price impact is zero. [`u128::MAX`] if the open interest is balanced or
+    /// any position of the side increases the skew.
+    pub equilibrium_size_usd: u128,
+}
+
+impl PriceImpactSensitivity {
+    /// Simulate opening positions of increasing sizes in the market with the given prices.
+    pub fn from_market(
+        market: &Market,
+        prices: &Prices<u128>,
+        is_long: bool,
+    ) -> gmsol_model::Result<Self> {
+        let one_million_usd = 1_000_000 * constants::MARKET_USD_UNIT;
+        let impact_at = |millions: u128| -> gmsol_model::Result<i128> {
+            let size_delta_usd = millions
+                .checked_mul(one_million_usd)
+                .and_then(|size| i128::try_from(size).ok())
+                .ok_or(gmsol_model::Error::Computation("calculating size delta"))?;
+            let mut impact = market.position_price_impact(is_long, &size_delta_usd)?;
+            market.cap_positive_position_price_impact(
+                &prices.index_token_price,
+                &size_delta_usd,
+                &mut impact,
+            )?;
+            impact
+                .checked_neg()
+                .ok_or(gmsol_model::Error::Computation("negating price impact"))
+        };
+
+        let impact_at_1m_usd = impact_at(1)?;
+        let impact_factor_per_million = impact_at_1m_usd
+            .unsigned_abs()
+            .checked_mul_div(&constants::MARKET_USD_UNIT, &one_million_usd)
+            .ok_or(gmsol_model::Error::Computation(
+                "calculating impact factor per million",
+            ))?;
+
+        let open_interest = market.open_interest()?;
+        let (long_open_interest, short_open_interest) =
+            (open_interest.long_amount()?, open_interest.short_amount()?);
+        let (same_side, opposite_side) = if is_long {
+            (long_open_interest, short_open_interest)
+        } else {
+            (short_open_interest, long_open_interest)
+        };
+        let equilibrium_size_usd = opposite_side
+            .checked_sub(same_side)
+            .filter(|size| *size != 0)
+            .unwrap_or(u128::MAX);
+
+        Ok(Self {
+            impact_at_1m_usd,
+            impact_at_5m_usd: impact_at(5)?,
+            impact_at_10m_usd: impact_at(10)?,
+            impact_at_50m_usd: impact_at(50)?,
+            impact_factor_per_million,
+            equilibrium_size_usd,
+        })
+    }
+}
+
+/// Convert a factor to basis points, saturating at [`u32::MAX`].
+fn factor_to_bps(factor: u128) -> u32 {
+    let bps = factor.saturating_mul(10_000) / constants::MARKET_USD_UNIT;
+    u32::try_from(bps).unwrap_or(u32::MAX)
+}
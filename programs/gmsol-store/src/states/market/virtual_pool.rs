@@ -0,0 +1,86 @@
+use anchor_lang::prelude::*;
+use bytemuck::Zeroable;
+
+use crate::{states::Seed, CoreError};
+
+use super::pool::Pool;
+
+/// Virtual Pool.
+///
+/// Tracks the virtual open interest shared by a group of correlated markets,
+/// which is used in place of the local open interest to calculate the position
+/// price impact of the markets in the group.
+#[account(zero_copy)]
+#[cfg_attr(feature = "debug", derive(derive_more::Debug))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct VirtualPool {
+    /// Bump seed.
+    pub bump: u8,
+    #[cfg_attr(feature = "debug", debug(skip))]
+    padding_0: [u8; 15],
+    /// Store.
+    pub store: Pubkey,
+    /// The key designating the group of markets.
+    key: Pubkey,
+    /// Virtual long open interest.
+    virtual_long_tokens: i128,
+    /// Virtual short open interest.
+    virtual_short_tokens: i128,
+    #[cfg_attr(feature = "debug", debug(skip))]
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes"))]
+    reserved: [u8; 128],
+}
+
+impl VirtualPool {
+    /// Get the key of the virtual pool.
+    pub fn key(&self) -> &Pubkey {
+        &self.key
+    }
+
+    /// Get the virtual long open interest.
+    pub fn virtual_long_tokens(&self) -> i128 {
+        self.virtual_long_tokens
+    }
+
+    /// Get the virtual short open interest.
+    pub fn virtual_short_tokens(&self) -> i128 {
+        self.virtual_short_tokens
+    }
+
+    /// Get the virtual open interest as a [`Pool`].
+    ///
+    /// Negative amounts are treated as zero.
+    pub fn open_interest_pool(&self) -> Pool {
+        let mut pool = Pool::zeroed();
+        pool.long_token_amount = self.virtual_long_tokens.max(0).unsigned_abs();
+        pool.short_token_amount = self.virtual_short_tokens.max(0).unsigned_abs();
+        pool
+    }
+
+    pub(crate) fn init(&mut self, bump: u8, store: &Pubkey, key: &Pubkey) {
+        self.bump = bump;
+        self.store = *store;
+        self.key = *key;
+    }
+
+    /// Apply delta to the virtual open interest of the given side.
+    pub(crate) fn apply_delta(&mut self, is_long: bool, delta: i128) -> Result<()> {
+        let amount = if is_long {
+            &mut self.virtual_long_tokens
+        } else {
+            &mut self.virtual_short_tokens
+        };
+        *amount = amount
+            .checked_add(delta)
+            .ok_or_else(|| error!(CoreError::ValueOverflow))?;
+        Ok(())
+    }
+}
+
+impl Seed for VirtualPool {
+    const SEED: &'static [u8] = b"virtual_pool";
+}
+
+impl gmsol_utils::InitSpace for VirtualPool {
+    const INIT_SPACE: usize = std::mem::size_of::<Self>();
+}
@@ -43,10 +43,26 @@ pub mod glv;
 /// GT.
 pub mod gt;
 
+/// Keeper.
+pub mod keeper;
+
+/// Global config snapshot.
+pub mod config_snapshot;
+
+/// Liquidity mining.
+pub mod liquidity_mining;
+
+pub use config_snapshot::GlobalConfigSnapshot;
 pub use deposit::Deposit;
-pub use glv::{Glv, GlvDeposit, GlvShift, GlvWithdrawal};
+pub use glv::{Glv, GlvDeposit, GlvDepositCooldown, GlvShift, GlvTreasury, GlvWithdrawal};
 pub use market::{
-    config::MarketConfigKey, pool::PoolStorage, HasMarketMeta, Market, MarketMeta, OtherState,
+    analytics::MarketAnalytics,
+    config::MarketConfigKey,
+    oi_caps::MarketOiCaps,
+    pool::PoolStorage,
+    proposal::{FeeStructureProposal, FeeStructureVote},
+    virtual_pool::VirtualPool,
+    HasMarketMeta, Market, MarketMeta, OtherState, PendingActionKind,
 };
 pub use oracle::*;
 pub use order::{Order, OrderActionParams, UpdateOrderParams};
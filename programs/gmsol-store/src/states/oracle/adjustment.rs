@@ -0,0 +1,172 @@
+use anchor_lang::prelude::*;
+use gmsol_model::num::MulDiv;
+
+use crate::{
+    constants,
+    states::{Factor, Seed},
+    CoreError,
+};
+
+/// Number of hourly factors in a [`PriceAdjustmentSchedule`].
+pub const HOURS_PER_DAY: usize = 24;
+
+/// Max price adjustment factor.
+pub const MAX_PRICE_ADJUSTMENT_FACTOR: Factor = 10 * constants::MARKET_USD_UNIT;
+
+const SECONDS_PER_HOUR: i64 = 60 * 60;
+
+/// Price Adjustment Schedule.
+///
+/// Holds a factor for each UTC hour of the day, by which the price band of
+/// the token is widened (or narrowed) when setting prices from price feeds
+/// during that hour.
+#[account(zero_copy)]
+#[cfg_attr(feature = "debug", derive(derive_more::Debug))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PriceAdjustmentSchedule {
+    /// Bump seed.
+    pub bump: u8,
+    #[cfg_attr(feature = "debug", debug(skip))]
+    padding_0: [u8; 15],
+    /// Store.
+    pub store: Pubkey,
+    /// Token.
+    pub token: Pubkey,
+    /// Adjustment factor of each UTC hour.
+    hourly_adjustment_factors: [Factor; HOURS_PER_DAY],
+    #[cfg_attr(feature = "debug", debug(skip))]
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes"))]
+    reserved: [u8; 128],
+}
+
+impl PriceAdjustmentSchedule {
+    pub(crate) fn init(
+        &mut self,
+        bump: u8,
+        store: &Pubkey,
+        token: &Pubkey,
+        factors: &[Factor; HOURS_PER_DAY],
+    ) -> Result<()> {
+        for factor in factors {
+            validate_factor(*factor)?;
+        }
+        self.bump = bump;
+        self.store = *store;
+        self.token = *token;
+        self.hourly_adjustment_factors = *factors;
+        Ok(())
+    }
+
+    /// Get the adjustment factors of each UTC hour.
+    pub fn hourly_adjustment_factors(&self) -> &[Factor; HOURS_PER_DAY] {
+        &self.hourly_adjustment_factors
+    }
+
+    /// Get the adjustment factor at the given timestamp.
+    pub fn factor_at(&self, ts: i64) -> Factor {
+        let hour = ts.rem_euclid(SECONDS_PER_HOUR * HOURS_PER_DAY as i64) / SECONDS_PER_HOUR;
+        self.hourly_adjustment_factors[hour as usize]
+    }
+
+    pub(crate) fn update(&mut self, hour: u8, factor: Factor) -> Result<()> {
+        validate_factor(factor)?;
+        let entry = self
+            .hourly_adjustment_factors
+            .get_mut(usize::from(hour))
+            .ok_or_else(|| error!(CoreError::InvalidArgument))?;
+        *entry = factor;
+        Ok(())
+    }
+}
+
+impl Seed for PriceAdjustmentSchedule {
+    const SEED: &'static [u8] = b"price_adjustment";
+}
+
+impl gmsol_utils::InitSpace for PriceAdjustmentSchedule {
+    const INIT_SPACE: usize = std::mem::size_of::<Self>();
+}
+
+fn validate_factor(factor: Factor) -> Result<()> {
+    require!(
+        factor != 0 && factor <= MAX_PRICE_ADJUSTMENT_FACTOR,
+        CoreError::InvalidArgument
+    );
+    Ok(())
+}
+
+/// Adjust the price band (in unit price) with the given factor, i.e.,
+/// divide the lower bound and multiply the upper bound by the factor.
+///
+/// A disabled upper bound (`u128::MAX`) is kept unchanged.
+pub(crate) fn adjust_price_band(
+    (min_band, max_band): (u128, u128),
+    factor: Factor,
+) -> Result<(u128, u128)> {
+    let unit = constants::MARKET_USD_UNIT;
+    let min = min_band
+        .checked_mul_div(&unit, &factor)
+        .ok_or_else(|| error!(CoreError::ValueOverflow))?;
+    let max = if max_band == u128::MAX {
+        max_band
+    } else {
+        max_band
+            .checked_mul_div(&factor, &unit)
+            .unwrap_or(u128::MAX)
+    };
+    Ok((min, max))
+}
+
+#[cfg(test)]
+mod tests {
+    use bytemuck::Zeroable;
+
+    use super::*;
+
+    const UNIT: Factor = constants::MARKET_USD_UNIT;
+
+    #[test]
+    fn factor_of_each_hour() {
+        let mut schedule = PriceAdjustmentSchedule::zeroed();
+        schedule
+            .init(0, &Pubkey::default(), &Pubkey::default(), &[UNIT; 24])
+            .unwrap();
+        schedule.update(14, UNIT * 3 / 2).unwrap();
+
+        // 2024-01-01T14:30:00Z.
+        assert_eq!(schedule.factor_at(1_704_119_400), UNIT * 3 / 2);
+        // 2024-01-01T13:59:59Z.
+        assert_eq!(schedule.factor_at(1_704_117_599), UNIT);
+        // 2024-01-02T14:00:00Z.
+        assert_eq!(schedule.factor_at(1_704_204_000), UNIT * 3 / 2);
+    }
+
+    #[test]
+    fn invalid_factors() {
+        let mut schedule = PriceAdjustmentSchedule::zeroed();
+        let mut factors = [UNIT; 24];
+        factors[3] = 0;
+        assert!(schedule
+            .init(0, &Pubkey::default(), &Pubkey::default(), &factors)
+            .is_err());
+        factors[3] = MAX_PRICE_ADJUSTMENT_FACTOR + 1;
+        assert!(schedule
+            .init(0, &Pubkey::default(), &Pubkey::default(), &factors)
+            .is_err());
+        assert!(schedule.update(24, UNIT).is_err());
+        assert!(schedule.update(0, 0).is_err());
+    }
+
+    #[test]
+    fn adjusted_price_band() {
+        assert_eq!(
+            adjust_price_band((90, 110), UNIT * 3 / 2).unwrap(),
+            (60, 165)
+        );
+        assert_eq!(adjust_price_band((90, 110), UNIT).unwrap(), (90, 110));
+        assert_eq!(
+            adjust_price_band((90, u128::MAX), UNIT * 2).unwrap(),
+            (45, u128::MAX)
+        );
+    }
+}
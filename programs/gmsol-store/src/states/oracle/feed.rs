@@ -120,6 +120,16 @@ impl PriceFeed {
         &self.feed_id
     }
 
+    /// Get store.
+    pub fn store(&self) -> &Pubkey {
+        &self.store
+    }
+
+    /// Get token.
+    pub fn token(&self) -> &Pubkey {
+        &self.token
+    }
+
     pub(crate) fn check_and_get_price(
         &self,
         clock: &Clock,
@@ -146,6 +156,58 @@ impl PriceFeed {
     fn try_to_price(&self, token_config: &TokenConfig) -> Result<gmsol_utils::Price> {
         self.price().try_to_price(token_config)
     }
+
+    /// Get the status of the price feed at the given timestamp.
+    ///
+    /// The price is considered stale if it would be rejected by the price validation
+    /// with the given `max_age`, taking the timestamp adjustment of the token into account.
+    pub fn status(
+        &self,
+        token_config: &TokenConfig,
+        max_age: u64,
+        current_ts: i64,
+    ) -> Result<PriceFeedStatus> {
+        let provider = self.provider()?;
+        let timestamp_adjustment = token_config.timestamp_adjustment(&provider)?;
+        let expiration_ts = self
+            .price
+            .ts
+            .saturating_sub_unsigned(timestamp_adjustment.into())
+            .saturating_add_unsigned(max_age);
+        Ok(PriceFeedStatus {
+            token: self.token,
+            provider: self.provider,
+            current_price: self.price.price,
+            last_updated_timestamp: self.price.ts,
+            last_updated_slot: self.last_published_at_slot,
+            age_seconds: current_ts.saturating_sub(self.price.ts),
+            is_stale: expiration_ts < current_ts,
+            confidence_interval: self.price.max_price.saturating_sub(self.price.min_price) / 2,
+        })
+    }
+}
+
+/// Price Feed Status.
+#[cfg_attr(feature = "debug", derive(Debug))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct PriceFeedStatus {
+    /// Token.
+    pub token: Pubkey,
+    /// Provider (the representation of [`PriceProviderKind`]).
+    pub provider: u8,
+    /// The latest price (in the decimals of the feed).
+    pub current_price: u128,
+    /// The timestamp of the latest price.
+    pub last_updated_timestamp: i64,
+    /// The slot at which the latest price was published.
+    pub last_updated_slot: u64,
+    /// The age (in seconds) of the latest price.
+    pub age_seconds: i64,
+    /// Whether the latest price is too old to be used.
+    pub is_stale: bool,
+    /// Half of the spread between the max and min price.
+    pub confidence_interval: u128,
 }
 
 /// Price Feed Flags.
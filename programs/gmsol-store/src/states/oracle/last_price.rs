@@ -0,0 +1,167 @@
+use anchor_lang::prelude::*;
+
+use crate::{states::Seed, CoreError};
+
+/// Basis points in one.
+const MAX_BPS: u16 = 10_000;
+
+/// Last Oracle Price.
+///
+/// Records the last price of the token set from the price feeds, against which
+/// the deviation of the next price is validated. Each token has its own record,
+/// so that setting the prices of different tokens is not serialized.
+#[account(zero_copy)]
+#[cfg_attr(feature = "debug", derive(derive_more::Debug))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LastOraclePrice {
+    /// Bump seed.
+    pub bump: u8,
+    #[cfg_attr(feature = "debug", debug(skip))]
+    padding_0: [u8; 7],
+    /// The timestamp of the last recorded price.
+    ts: i64,
+    /// Store.
+    pub store: Pubkey,
+    /// Token.
+    pub token: Pubkey,
+    /// The last recorded mid unit price, `0` if there is no recorded price.
+    price: u128,
+    #[cfg_attr(feature = "debug", debug(skip))]
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes"))]
+    reserved: [u8; 64],
+}
+
+impl LastOraclePrice {
+    pub(crate) fn init(&mut self, bump: u8, store: &Pubkey, token: &Pubkey) {
+        self.bump = bump;
+        self.store = *store;
+        self.token = *token;
+        self.clear();
+    }
+
+    /// Get the last recorded mid unit price and its timestamp.
+    pub fn last_price(&self) -> Option<(u128, i64)> {
+        (self.price != 0).then_some((self.price, self.ts))
+    }
+
+    /// Clear the last recorded price, so that the next price is accepted without
+    /// the deviation check.
+    pub(crate) fn clear(&mut self) {
+        self.price = 0;
+        self.ts = 0;
+    }
+
+    /// Validate the deviation of the given price from the last recorded price, and
+    /// record it as the last price.
+    ///
+    /// The deviation is only checked if `max_deviation_bps` is not zero and the last
+    /// price was recorded within `window` seconds.
+    pub(crate) fn validate_deviation_and_record(
+        &mut self,
+        price: &gmsol_utils::Price,
+        max_deviation_bps: u16,
+        now: i64,
+        window: u64,
+    ) -> Result<()> {
+        let mid = price
+            .min
+            .to_unit_price()
+            .checked_add(price.max.to_unit_price())
+            .ok_or_else(|| error!(CoreError::ValueOverflow))?
+            / 2;
+        if let Some((last_price, last_ts)) = self.last_price() {
+            let is_recent =
+                now.saturating_sub(last_ts) <= i64::try_from(window).unwrap_or(i64::MAX);
+            if max_deviation_bps != 0 && is_recent {
+                let deviation_bps = mid
+                    .abs_diff(last_price)
+                    .checked_mul(u128::from(MAX_BPS))
+                    .ok_or_else(|| error!(CoreError::ValueOverflow))?
+                    / last_price;
+                require_gte!(
+                    u128::from(max_deviation_bps),
+                    deviation_bps,
+                    CoreError::OraclePriceDeviationExceeded
+                );
+            }
+        }
+        self.price = mid;
+        self.ts = now;
+        Ok(())
+    }
+}
+
+impl Seed for LastOraclePrice {
+    const SEED: &'static [u8] = b"last_oracle_price";
+}
+
+impl gmsol_utils::InitSpace for LastOraclePrice {
+    const INIT_SPACE: usize = std::mem::size_of::<Self>();
+}
+
+#[cfg(test)]
+mod tests {
+    use bytemuck::Zeroable;
+    use gmsol_utils::price::{Decimal, Price};
+
+    use super::*;
+
+    fn price(value: u32) -> Price {
+        let price = Decimal {
+            value,
+            decimal_multiplier: 7,
+        };
+        Price {
+            min: price,
+            max: price,
+        }
+    }
+
+    fn unit_price(value: u32) -> u128 {
+        price(value).min.to_unit_price()
+    }
+
+    #[test]
+    fn price_deviation() {
+        let window = 60;
+        let mut record = LastOraclePrice::zeroed();
+        record.init(0, &Pubkey::default(), &Pubkey::default());
+        assert_eq!(record.last_price(), None);
+
+        // Disabled, but the price is still recorded.
+        record
+            .validate_deviation_and_record(&price(1_000_000), 0, 0, window)
+            .unwrap();
+        record
+            .validate_deviation_and_record(&price(2_000_000), 0, 1, window)
+            .unwrap();
+        assert_eq!(record.last_price(), Some((unit_price(2_000_000), 1)));
+
+        // Max deviation of 5%.
+        record
+            .validate_deviation_and_record(&price(2_100_000), 500, 10, window)
+            .unwrap();
+        assert!(record
+            .validate_deviation_and_record(&price(2_210_000), 500, 20, window)
+            .is_err());
+        assert!(record
+            .validate_deviation_and_record(&price(1_990_000), 500, 20, window)
+            .is_err());
+        assert_eq!(record.last_price(), Some((unit_price(2_100_000), 10)));
+        record
+            .validate_deviation_and_record(&price(2_000_000), 500, 20, window)
+            .unwrap();
+
+        // The check is skipped if the last price is not recent.
+        record
+            .validate_deviation_and_record(&price(3_000_000), 500, 20 + window as i64 + 1, window)
+            .unwrap();
+
+        // The check is skipped after the last price is cleared.
+        record.clear();
+        record
+            .validate_deviation_and_record(&price(1_000_000), 500, 100, window)
+            .unwrap();
+        assert_eq!(record.last_price(), Some((unit_price(1_000_000), 100)));
+    }
+}
@@ -19,9 +19,16 @@ pub mod validator;
 /// Oracle time validation.
 pub mod time;
 
+/// Price adjustment schedule.
+pub mod adjustment;
+
+/// Last oracle price.
+pub mod last_price;
+
 use std::ops::Deref;
 
 use crate::{
+    events::OraclePriceBandBreached,
     states::{TokenMapAccess, TokenMapLoader},
     CoreError, CoreResult,
 };
@@ -29,11 +36,13 @@ use anchor_lang::prelude::*;
 use num_enum::{IntoPrimitive, TryFromPrimitive};
 
 use self::price_map::PriceMap;
-use super::{HasMarketMeta, Seed, Store, TokenConfig, TokenMapHeader, TokenMapRef};
+use super::{Factor, HasMarketMeta, Market, Seed, Store, TokenConfig, TokenMapHeader, TokenMapRef};
 
 pub use self::{
+    adjustment::PriceAdjustmentSchedule,
     chainlink::Chainlink,
-    feed::{PriceFeed, PriceFeedPrice},
+    feed::{PriceFeed, PriceFeedPrice, PriceFeedStatus},
+    last_price::LastOraclePrice,
     pyth::Pyth,
     switchboard::Switchboard,
     time::{ValidateOracleTime, ValidateOracleTimeExt},
@@ -48,6 +57,8 @@ const MAX_FLAGS: usize = 8;
 enum OracleFlag {
     /// Cleared.
     Cleared,
+    /// Decommissioned.
+    Decommissioned,
     // CHECK: should have no more than `MAX_FLAGS` of flags.
 }
 
@@ -97,6 +108,40 @@ impl Oracle {
         self.flags.get_flag(OracleFlag::Cleared)
     }
 
+    /// Return whether the oracle is decommissioned.
+    pub fn is_decommissioned(&self) -> bool {
+        self.flags.get_flag(OracleFlag::Decommissioned)
+    }
+
+    /// Validate that the oracle is not decommissioned.
+    pub fn validate_not_decommissioned(&self) -> Result<()> {
+        require!(!self.is_decommissioned(), CoreError::OracleDecommissioned);
+        Ok(())
+    }
+
+    /// Get the authority of the oracle.
+    pub fn authority(&self) -> &Pubkey {
+        &self.authority
+    }
+
+    /// Replace this oracle with the `new_oracle`.
+    ///
+    /// The authority of this oracle is copied to the `new_oracle`, and this oracle is
+    /// marked as decommissioned.
+    pub(crate) fn rotate(&mut self, new_oracle: &mut Self) -> Result<()> {
+        self.validate_not_decommissioned()?;
+        new_oracle.validate_not_decommissioned()?;
+        require_keys_eq!(self.store, new_oracle.store, CoreError::StoreMismatched);
+        require!(
+            self.is_cleared() && new_oracle.is_cleared(),
+            CoreError::PreconditionsAreNotMet
+        );
+
+        new_oracle.authority = self.authority;
+        self.flags.set_flag(OracleFlag::Decommissioned, true);
+        Ok(())
+    }
+
     /// Set prices from remaining accounts.
     pub(crate) fn set_prices_from_remaining_accounts<'info>(
         &mut self,
@@ -105,7 +150,9 @@ impl Oracle {
         tokens: &[Pubkey],
         remaining_accounts: &'info [AccountInfo<'info>],
         chainlink: Option<&Program<'info, Chainlink>>,
+        price_band_factors: &[(Pubkey, Factor)],
     ) -> Result<()> {
+        self.validate_not_decommissioned()?;
         require!(self.is_cleared(), CoreError::PricesAreAlreadySet);
         require!(self.primary.is_empty(), CoreError::PricesAreAlreadySet);
         require!(
@@ -124,13 +171,24 @@ impl Oracle {
 
             require!(token_config.is_enabled(), CoreError::TokenConfigDisabled);
 
-            let oracle_price = OraclePrice::parse_from_feed_account(
+            let mut oracle_price = OraclePrice::parse_from_feed_account(
                 validator.clock(),
                 token_config,
                 chainlink,
                 feed,
             )?;
 
+            let price_band_factor = price_band_factors
+                .iter()
+                .find_map(|(adjusted, factor)| (adjusted == token).then_some(*factor));
+            oracle_price.price = effective_price(
+                token,
+                token_config,
+                &oracle_price.provider,
+                &oracle_price.price,
+                price_band_factor,
+            )?;
+
             validator.validate_one(
                 token_config,
                 &oracle_price.provider,
@@ -199,6 +257,50 @@ impl Oracle {
         f: impl FnOnce(&mut Self, &'info [AccountInfo<'info>]) -> Result<T>,
     ) -> Result<T> {
         let validator = PriceValidator::try_from(store.load()?.deref())?;
+        self.with_validated_prices(
+            validator,
+            token_map,
+            tokens,
+            remaining_accounts,
+            chainlink,
+            f,
+        )
+    }
+
+    /// Same as [`with_prices`](Self::with_prices), but the max oracle timestamps range
+    /// configured for the given market is used in place of the global one if it is set.
+    #[inline(never)]
+    pub(crate) fn with_market_prices<'info, T>(
+        &mut self,
+        store: &AccountLoader<'info, Store>,
+        market: &AccountLoader<'info, Market>,
+        token_map: &AccountLoader<'info, TokenMapHeader>,
+        tokens: &[Pubkey],
+        remaining_accounts: &'info [AccountInfo<'info>],
+        chainlink: Option<&Program<'info, Chainlink>>,
+        f: impl FnOnce(&mut Self, &'info [AccountInfo<'info>]) -> Result<T>,
+    ) -> Result<T> {
+        let validator = PriceValidator::try_from(store.load()?.deref())?
+            .with_max_oracle_timestamp_range(market.load()?.max_oracle_ts_range());
+        self.with_validated_prices(
+            validator,
+            token_map,
+            tokens,
+            remaining_accounts,
+            chainlink,
+            f,
+        )
+    }
+
+    fn with_validated_prices<'info, T>(
+        &mut self,
+        validator: PriceValidator,
+        token_map: &AccountLoader<'info, TokenMapHeader>,
+        tokens: &[Pubkey],
+        remaining_accounts: &'info [AccountInfo<'info>],
+        chainlink: Option<&Program<'info, Chainlink>>,
+        f: impl FnOnce(&mut Self, &'info [AccountInfo<'info>]) -> Result<T>,
+    ) -> Result<T> {
         require_gte!(
             remaining_accounts.len(),
             tokens.len(),
@@ -208,7 +310,14 @@ impl Oracle {
         let remaining_accounts = &remaining_accounts[tokens.len()..];
         let res = {
             let token_map = token_map.load_token_map()?;
-            self.set_prices_from_remaining_accounts(validator, &token_map, tokens, feeds, chainlink)
+            self.set_prices_from_remaining_accounts(
+                validator,
+                &token_map,
+                tokens,
+                feeds,
+                chainlink,
+                &[],
+            )
         };
         match res {
             Ok(()) => {
@@ -235,6 +344,14 @@ impl Oracle {
         Ok(())
     }
 
+    /// Get primary price for the given token in decimal.
+    pub(crate) fn get_primary_decimal_price(&self, token: &Pubkey) -> Result<gmsol_utils::Price> {
+        self.primary
+            .get(token)
+            .ok_or_else(|| error!(CoreError::MissingOraclePrice))?
+            .to_price()
+    }
+
     /// Get primary price for the given token.
     pub fn get_primary_price(
         &self,
@@ -331,6 +448,82 @@ impl PriceProviderKind {
     }
 }
 
+/// Check that the price is within the price band of the given provider.
+///
+/// The band is adjusted by the given factor if provided.
+/// Get the effective price from the raw price reported by the feed,
+/// and validate it against the price band.
+fn effective_price(
+    token: &Pubkey,
+    token_config: &TokenConfig,
+    provider: &PriceProviderKind,
+    raw_price: &gmsol_utils::Price,
+    price_band_factor: Option<Factor>,
+) -> Result<gmsol_utils::Price> {
+    // Scale the raw price by the multiplier of the feed.
+    let price = token_config.apply_price_multiplier(provider, raw_price)?;
+
+    // Adjust the price of bridged tokens for the cross-chain premium (or discount).
+    let price = token_config.apply_bridge_premium(&price)?;
+
+    // The price band applies to the effective price.
+    check_price_band(token, token_config, provider, &price, price_band_factor)?;
+
+    Ok(price)
+}
+
+fn check_price_band(
+    token: &Pubkey,
+    token_config: &TokenConfig,
+    provider: &PriceProviderKind,
+    price: &gmsol_utils::Price,
+    factor: Option<Factor>,
+) -> Result<()> {
+    let Some(band) = token_config.price_band(provider)? else {
+        return Ok(());
+    };
+    let (min_band, max_band) = match factor {
+        Some(factor) => adjustment::adjust_price_band(band, factor)?,
+        None => band,
+    };
+    let min = price.min.to_unit_price();
+    let max = price.max.to_unit_price();
+    let breached = if min < min_band {
+        Some(min)
+    } else if max > max_band {
+        Some(max)
+    } else {
+        None
+    };
+    if let Some(price) = breached {
+        emit!(OraclePriceBandBreached {
+            token: *token,
+            price,
+            min_band,
+            max_band,
+        });
+        return err!(CoreError::OraclePriceOutsideBand);
+    }
+    Ok(())
+}
+
+/// Get the adjusted timestamp of the price reported by the given feed account.
+pub(crate) fn adjusted_timestamp_from_feed_account<'info>(
+    token_config: &TokenConfig,
+    provider: &PriceProviderKind,
+    chainlink: Option<&Program<'info, Chainlink>>,
+    feed: &'info AccountInfo<'info>,
+) -> Result<i64> {
+    let clock = Clock::get()?;
+    let oracle_price = OraclePrice::parse_from_feed_account(&clock, token_config, chainlink, feed)?;
+    require_eq!(
+        oracle_price.provider,
+        *provider,
+        CoreError::InvalidPriceFeedAccount
+    );
+    token_config.adjusted_timestamp(provider, oracle_price.oracle_ts)
+}
+
 struct OraclePrice {
     provider: PriceProviderKind,
     oracle_slot: u64,
@@ -395,3 +588,282 @@ impl OraclePrice {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use bytemuck::Zeroable;
+    use gmsol_utils::price::{Decimal, Price};
+
+    use crate::states::{FeedConfig, UpdateTokenConfigParams};
+
+    use super::*;
+
+    fn token_config(min_price: u32, max_price: u32) -> TokenConfig {
+        let mut config = TokenConfig::zeroed();
+        config
+            .update(
+                "TEST",
+                false,
+                9,
+                UpdateTokenConfigParams::default().with_expected_provider(PriceProviderKind::Pyth),
+                true,
+                true,
+            )
+            .unwrap();
+        config
+            .set_feed_config(
+                &PriceProviderKind::Pyth,
+                FeedConfig::new(Pubkey::new_unique())
+                    .with_price_band(min_price, max_price)
+                    .unwrap(),
+            )
+            .unwrap();
+        config
+    }
+
+    fn price(value: u32) -> Price {
+        // The decimal multiplier for a token with 9 decimals and the default precision.
+        let price = Decimal {
+            value,
+            decimal_multiplier: 7,
+        };
+        Price {
+            min: price,
+            max: price,
+        }
+    }
+
+    fn assert_price(a: &Price, b: &Price) {
+        assert_eq!(a.min, b.min);
+        assert_eq!(a.max, b.max);
+    }
+
+    #[test]
+    fn test_price_band() {
+        let token = Pubkey::new_unique();
+        let provider = PriceProviderKind::Pyth;
+
+        // Band of [$90, $110].
+        let config = token_config(900_000, 1_100_000);
+        assert!(check_price_band(&token, &config, &provider, &price(850_000), None).is_err());
+        assert!(check_price_band(&token, &config, &provider, &price(1_150_000), None).is_err());
+        assert!(check_price_band(&token, &config, &provider, &price(1_000_000), None).is_ok());
+        assert!(check_price_band(&token, &config, &provider, &price(900_000), None).is_ok());
+        assert!(check_price_band(&token, &config, &provider, &price(1_100_000), None).is_ok());
+
+        // Band of [0, 0] is disabled.
+        let config = token_config(0, 0);
+        assert!(config.price_band(&provider).unwrap().is_none());
+        assert!(check_price_band(&token, &config, &provider, &price(1), None).is_ok());
+        assert!(check_price_band(&token, &config, &provider, &price(u32::MAX), None).is_ok());
+
+        // Only the lower bound is set.
+        let config = token_config(900_000, 0);
+        assert!(check_price_band(&token, &config, &provider, &price(850_000), None).is_err());
+        assert!(check_price_band(&token, &config, &provider, &price(u32::MAX), None).is_ok());
+    }
+
+    #[test]
+    fn test_price_band_with_adjustment_schedule() {
+        use crate::constants::MARKET_USD_UNIT;
+
+        let token = Pubkey::new_unique();
+        let provider = PriceProviderKind::Pyth;
+        let mut schedule = PriceAdjustmentSchedule::zeroed();
+        schedule
+            .init(0, &Pubkey::default(), &token, &[MARKET_USD_UNIT; 24])
+            .unwrap();
+        schedule.update(14, MARKET_USD_UNIT * 3 / 2).unwrap();
+
+        // Band of [$90, $110], with a price of $100 ± $15.
+        let config = token_config(900_000, 1_100_000);
+        let price = Price {
+            min: Decimal {
+                value: 850_000,
+                decimal_multiplier: 7,
+            },
+            max: Decimal {
+                value: 1_150_000,
+                decimal_multiplier: 7,
+            },
+        };
+
+        // 2024-01-01T13:30:00Z.
+        let factor = schedule.factor_at(1_704_115_800);
+        assert!(check_price_band(&token, &config, &provider, &price, Some(factor)).is_err());
+        assert!(check_price_band(&token, &config, &provider, &price, None).is_err());
+
+        // 2024-01-01T14:30:00Z.
+        let factor = schedule.factor_at(1_704_119_400);
+        assert!(check_price_band(&token, &config, &provider, &price, Some(factor)).is_ok());
+    }
+
+    #[test]
+    fn test_bridge_premium() {
+        use crate::{constants::MARKET_USD_UNIT, states::BridgePremiumConfig};
+
+        let mut config = token_config(0, 0);
+
+        // $100 BTC.
+        let feed_price = price(1_000_000);
+        assert_price(
+            &config.apply_bridge_premium(&feed_price).unwrap(),
+            &feed_price,
+        );
+
+        // +0.5% premium.
+        config
+            .set_bridge_premium(&BridgePremiumConfig {
+                is_bridged: true,
+                bridge_premium_factor: (MARKET_USD_UNIT / 200) as i128,
+            })
+            .unwrap();
+        assert_price(
+            &config.apply_bridge_premium(&feed_price).unwrap(),
+            &price(1_005_000),
+        );
+
+        // -0.5% discount.
+        config
+            .set_bridge_premium_factor(-((MARKET_USD_UNIT / 200) as i128))
+            .unwrap();
+        assert_price(
+            &config.apply_bridge_premium(&feed_price).unwrap(),
+            &price(995_000),
+        );
+
+        // The premium is ignored if the token is not marked as bridged.
+        config
+            .set_bridge_premium(&BridgePremiumConfig {
+                is_bridged: false,
+                bridge_premium_factor: (MARKET_USD_UNIT / 200) as i128,
+            })
+            .unwrap();
+        assert_price(
+            &config.apply_bridge_premium(&feed_price).unwrap(),
+            &feed_price,
+        );
+
+        // A discount of 100% or more is not allowed.
+        assert!(config
+            .set_bridge_premium_factor(-(MARKET_USD_UNIT as i128))
+            .is_err());
+    }
+
+    #[test]
+    fn test_price_band_applies_to_bridged_price() {
+        use crate::{constants::MARKET_USD_UNIT, states::BridgePremiumConfig};
+
+        let token = Pubkey::new_unique();
+        let provider = PriceProviderKind::Pyth;
+
+        // Band of [$90, $100.20].
+        let mut config = token_config(900_000, 1_002_000);
+        let feed_price = price(1_000_000);
+        assert_price(
+            &effective_price(&token, &config, &provider, &feed_price, None).unwrap(),
+            &feed_price,
+        );
+
+        // The effective price of $100.50 is outside the band.
+        config
+            .set_bridge_premium(&BridgePremiumConfig {
+                is_bridged: true,
+                bridge_premium_factor: (MARKET_USD_UNIT / 200) as i128,
+            })
+            .unwrap();
+        assert!(effective_price(&token, &config, &provider, &feed_price, None).is_err());
+
+        // The effective price of $99.50 is inside the band.
+        config
+            .set_bridge_premium_factor(-((MARKET_USD_UNIT / 200) as i128))
+            .unwrap();
+        assert_price(
+            &effective_price(&token, &config, &provider, &feed_price, None).unwrap(),
+            &price(995_000),
+        );
+    }
+
+    #[test]
+    fn test_price_multiplier() {
+        let provider = PriceProviderKind::Pyth;
+        let mut config = token_config(0, 0);
+
+        // No scaling by default.
+        let feed_price = price(10_000);
+        assert_price(
+            &config
+                .apply_price_multiplier(&provider, &feed_price)
+                .unwrap(),
+            &feed_price,
+        );
+
+        // A raw price of $1 is scaled to $100 with a 100x multiplier.
+        let feed_config = config
+            .get_feed_config(&provider)
+            .unwrap()
+            .with_price_multiplier(100, 1)
+            .unwrap();
+        config.set_feed_config(&provider, feed_config).unwrap();
+        assert_price(
+            &config
+                .apply_price_multiplier(&provider, &feed_price)
+                .unwrap(),
+            &price(1_000_000),
+        );
+
+        // Scaling down.
+        let feed_config = feed_config.with_price_multiplier(1, 100).unwrap();
+        config.set_feed_config(&provider, feed_config).unwrap();
+        assert_price(
+            &config
+                .apply_price_multiplier(&provider, &feed_price)
+                .unwrap(),
+            &price(100),
+        );
+
+        // The multiplier is kept across token config updates.
+        config
+            .update(
+                "TEST",
+                false,
+                9,
+                UpdateTokenConfigParams::from(&config).with_heartbeat_duration(60),
+                true,
+                false,
+            )
+            .unwrap();
+        assert_eq!(
+            config
+                .get_feed_config(&provider)
+                .unwrap()
+                .price_multiplier(),
+            Some((1, 100))
+        );
+    }
+
+    #[test]
+    fn test_invalid_price_multiplier() {
+        let feed_config = FeedConfig::new(Pubkey::new_unique());
+        assert!(feed_config.with_price_multiplier(0, 1).is_err());
+        assert!(feed_config.with_price_multiplier(1, 0).is_err());
+        assert!(feed_config.with_price_multiplier(1_000_001, 1).is_err());
+        assert!(feed_config.with_price_multiplier(1, 1_000_001).is_err());
+        assert!(feed_config.with_price_multiplier(1_000_000, 1).is_ok());
+        assert!(feed_config.with_price_multiplier(1, 1_000_000).is_ok());
+    }
+
+    #[test]
+    fn test_invalid_max_price_deviation() {
+        let mut config = token_config(0, 0);
+        assert!(config.set_max_price_deviation_bps(10_001).is_err());
+        assert!(config.set_max_price_deviation_bps(10_000).is_ok());
+    }
+
+    #[test]
+    fn test_invalid_price_band() {
+        assert!(FeedConfig::new(Pubkey::new_unique())
+            .with_price_band(1_100_000, 900_000)
+            .is_err());
+    }
+}
@@ -27,6 +27,14 @@ impl PriceValidator {
         &self.clock
     }
 
+    /// Override the max oracle timestamps range if provided.
+    pub(crate) fn with_max_oracle_timestamp_range(mut self, range: Option<Amount>) -> Self {
+        if let Some(range) = range {
+            self.max_oracle_timestamp_range = range;
+        }
+        self
+    }
+
     pub(super) fn validate_one(
         &mut self,
         token_config: &TokenConfig,
@@ -35,10 +43,7 @@ impl PriceValidator {
         oracle_slot: u64,
         _price: &Price,
     ) -> Result<()> {
-        let timestamp_adjustment = token_config.timestamp_adjustment(provider)?.into();
-        let ts = oracle_ts
-            .checked_sub_unsigned(timestamp_adjustment)
-            .ok_or_else(|| error!(CoreError::TokenAmountOverflow))?;
+        let ts = token_config.adjusted_timestamp(provider, oracle_ts)?;
 
         let expiration_ts = ts
             .checked_add_unsigned(self.max_age)
@@ -114,3 +119,51 @@ impl<'a> TryFrom<&'a Store> for PriceValidator {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn validator(max_oracle_timestamp_range: Amount) -> PriceValidator {
+        PriceValidator {
+            clock: Clock::default(),
+            max_age: 60,
+            max_oracle_timestamp_range,
+            max_future_timestamp_excess: 60,
+            min_oracle_ts: i64::MAX,
+            max_oracle_ts: i64::MIN,
+            min_oracle_slot: None,
+        }
+    }
+
+    fn finish_with_range(validator: PriceValidator, min_ts: i64, max_ts: i64) -> Result<()> {
+        let mut validator = validator;
+        validator.merge_range(Some(1), min_ts, max_ts);
+        validator.finish().map(|_| ())
+    }
+
+    #[test]
+    fn test_global_max_oracle_timestamp_range() {
+        assert!(
+            finish_with_range(validator(5).with_max_oracle_timestamp_range(None), 100, 105).is_ok()
+        );
+        assert!(
+            finish_with_range(validator(5).with_max_oracle_timestamp_range(None), 100, 106)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_tight_market_max_oracle_timestamp_range() {
+        let tight = || validator(5).with_max_oracle_timestamp_range(Some(1));
+        assert!(finish_with_range(tight(), 100, 101).is_ok());
+        assert!(finish_with_range(tight(), 100, 102).is_err());
+    }
+
+    #[test]
+    fn test_loose_market_max_oracle_timestamp_range() {
+        let loose = || validator(5).with_max_oracle_timestamp_range(Some(30));
+        assert!(finish_with_range(loose(), 100, 130).is_ok());
+        assert!(finish_with_range(loose(), 100, 131).is_err());
+    }
+}
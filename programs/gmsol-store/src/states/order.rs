@@ -130,6 +130,107 @@ impl OrderKind {
     }
 }
 
+/// Order callback.
+///
+/// After the order is executed successfully, the callback becomes pending and the
+/// callback program can then be invoked with [`OrderCallbackArgs`] by a separate
+/// instruction, so that a failing callback never affects the execution.
+#[zero_copy]
+#[cfg_attr(feature = "debug", derive(derive_more::Debug))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OrderCallback {
+    /// The callback program.
+    program: Pubkey,
+    /// The account passed to the callback program, e.g., a state account of
+    /// the integrator.
+    account: Pubkey,
+    /// Data passed to the callback program.
+    data: [u8; 32],
+    /// The hash of the pending execution result, all zeros if no callback is pending.
+    #[cfg_attr(feature = "debug", debug(skip))]
+    pending_result_hash: [u8; 32],
+}
+
+impl OrderCallback {
+    pub(crate) fn init(&mut self, program: Pubkey, account: Pubkey, data: [u8; 32]) {
+        self.program = program;
+        self.account = account;
+        self.data = data;
+    }
+
+    /// Get the callback program.
+    pub fn program(&self) -> Option<&Pubkey> {
+        optional_address(&self.program)
+    }
+
+    /// Get the account passed to the callback program.
+    pub fn account(&self) -> &Pubkey {
+        &self.account
+    }
+
+    /// Get the data passed to the callback program.
+    pub fn data(&self) -> &[u8; 32] {
+        &self.data
+    }
+
+    /// Returns whether the callback is pending to be invoked.
+    pub fn is_pending(&self) -> bool {
+        self.pending_result_hash != [0; 32]
+    }
+
+    fn hash_result(transfer_out: &TransferOut) -> [u8; 32] {
+        anchor_lang::solana_program::hash::hash(bytemuck::bytes_of(transfer_out)).to_bytes()
+    }
+
+    /// Mark the callback as pending with the given execution result.
+    pub(crate) fn set_pending(&mut self, transfer_out: &TransferOut) -> Result<()> {
+        require!(!self.is_pending(), CoreError::PreconditionsAreNotMet);
+        self.pending_result_hash = Self::hash_result(transfer_out);
+        Ok(())
+    }
+
+    /// Take the pending callback, validating the given execution result against the
+    /// recorded one.
+    pub(crate) fn take_pending(&mut self, transfer_out: &TransferOut) -> Result<()> {
+        require!(self.is_pending(), CoreError::OrderCallbackNotPending);
+        require!(
+            self.pending_result_hash == Self::hash_result(transfer_out),
+            CoreError::OrderCallbackResultMismatched
+        );
+        self.pending_result_hash = [0; 32];
+        Ok(())
+    }
+}
+
+/// The arguments of the order callback instruction.
+///
+/// The callback instruction is invoked with the following accounts:
+///
+///   - 0. `[signer]` The order callback authority, i.e., the PDA derived from
+///     [`ORDER_CALLBACK_AUTHORITY_SEED`](crate::constants::ORDER_CALLBACK_AUTHORITY_SEED)
+///     and the store address.
+///   - 1. `[]` The executed order.
+///   - 2. `[writable]` The [callback account](OrderCallback::account).
+///
+/// It is invoked in a separate instruction after the execution, so the order may
+/// have been closed if the callback is never invoked.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+#[cfg_attr(feature = "debug", derive(Debug))]
+pub struct OrderCallbackArgs {
+    /// Order kind.
+    pub kind: OrderKind,
+    /// The data set on order creation.
+    pub data: [u8; 32],
+    /// The amounts transferred out.
+    pub transfer_out: TransferOut,
+}
+
+impl OrderCallbackArgs {
+    /// The discriminator of the callback instruction, which is the Anchor
+    /// discriminator of an instruction named `on_order_executed`.
+    pub const DISCRIMINATOR: [u8; 8] = [118, 92, 237, 152, 50, 143, 248, 212];
+}
+
 /// Transfer Out.
 #[zero_copy]
 #[cfg_attr(feature = "debug", derive(derive_more::Debug))]
@@ -399,9 +500,8 @@ pub struct Order {
     pub(crate) gt_reward: u64,
     #[cfg_attr(feature = "debug", debug(skip))]
     padding_1: [u8; 8],
-    #[cfg_attr(feature = "debug", debug(skip))]
-    #[cfg_attr(feature = "serde", serde(with = "serde_bytes"))]
-    reserved: [u8; 128],
+    /// Callback.
+    pub(crate) callback: OrderCallback,
 }
 
 impl Seed for Order {
@@ -460,6 +560,39 @@ impl Order {
         self.header.signer(Self::SEED)
     }
 
+    /// Validate that the order can be executed by the given keeper.
+    ///
+    /// An order assigned to a keeper can only be executed by that keeper, unless `timeout`
+    /// seconds have passed since the last update of the order.
+    pub fn validate_executing_keeper(&self, keeper: &Pubkey, timeout: u64, now: i64) -> Result<()> {
+        let Some(assigned_keeper) = self.params.assigned_keeper() else {
+            return Ok(());
+        };
+        if assigned_keeper == keeper {
+            return Ok(());
+        }
+        let expires_at = self
+            .header
+            .updated_at
+            .saturating_add(timeout.try_into().unwrap_or(i64::MAX));
+        require_gte!(now, expires_at, CoreError::OrderAssignedToAnotherKeeper);
+        Ok(())
+    }
+
+    /// Returns whether the fallback window of the order has elapsed, i.e. the order
+    /// can be executed as a market order without being triggered.
+    pub fn should_fallback_to_market(&self, now: i64) -> bool {
+        let Some(secs) = self.params.fallback_to_market_after_secs() else {
+            return false;
+        };
+        let fallback_at = self
+            .header
+            .updated_at
+            .max(self.params.valid_from_ts)
+            .saturating_add(i64::from(secs));
+        now >= fallback_at
+    }
+
     /// Validate that current timestamp >= `valid_from_ts`.
     pub fn validate_valid_from_ts(&self) -> Result<()> {
         if self.params.kind()?.is_market() {
@@ -588,6 +721,20 @@ impl Order {
         &self.params
     }
 
+    /// Get the callback to invoke after the order is executed, if any.
+    pub fn callback(&self) -> Option<&OrderCallback> {
+        self.callback.program().map(|_| &self.callback)
+    }
+
+    /// Get the callback mutably, if any.
+    pub(crate) fn callback_mut(&mut self) -> Option<&mut OrderCallback> {
+        if self.callback.program().is_some() {
+            Some(&mut self.callback)
+        } else {
+            None
+        }
+    }
+
     /// Get swap params.
     pub fn swap(&self) -> &SwapActionParams {
         &self.swap
@@ -771,9 +918,15 @@ pub struct OrderActionParams {
     pub(crate) valid_from_ts: i64,
     #[cfg_attr(feature = "debug", debug(skip))]
     padding_2: [u8; 8],
+    /// The keeper assigned to execute the order.
+    pub(crate) assigned_keeper: Pubkey,
+    /// The number of seconds after which the order can be executed as a market order.
+    pub(crate) fallback_to_market_after_secs: u32,
+    #[cfg_attr(feature = "debug", debug(skip))]
+    padding_3: [u8; 4],
     #[cfg_attr(feature = "debug", debug(skip))]
     #[cfg_attr(feature = "serde", serde(with = "serde_bytes"))]
-    reserved: [u8; 64],
+    reserved: [u8; 24],
 }
 
 impl OrderActionParams {
@@ -993,6 +1146,24 @@ impl OrderActionParams {
     pub fn valid_from_ts(&self) -> i64 {
         self.valid_from_ts
     }
+
+    /// Get the assigned keeper.
+    pub fn assigned_keeper(&self) -> Option<&Pubkey> {
+        if self.assigned_keeper == Pubkey::default() {
+            None
+        } else {
+            Some(&self.assigned_keeper)
+        }
+    }
+
+    /// Get the number of seconds after which the order can be executed as a market order.
+    pub fn fallback_to_market_after_secs(&self) -> Option<u32> {
+        if self.fallback_to_market_after_secs == 0 {
+            None
+        } else {
+            Some(self.fallback_to_market_after_secs)
+        }
+    }
 }
 
 /// Order side.
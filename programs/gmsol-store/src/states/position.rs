@@ -1,6 +1,13 @@
 use crate::{constants, CoreError};
 use anchor_lang::prelude::*;
 use borsh::{BorshDeserialize, BorshSerialize};
+use gmsol_model::{
+    num::MulDiv,
+    price::Prices,
+    utils::{apply_factor, div_to_factor},
+    BaseMarket, BaseMarketExt, PerpMarket, PerpMarketExt, PnlFactorKind, PositionExt,
+    PositionStateExt,
+};
 use num_enum::TryFromPrimitive;
 
 use super::{Market, Seed};
@@ -17,9 +24,11 @@ pub struct Position {
     pub store: Pubkey,
     /// Position kind (the representation of [`PositionKind`]).
     pub kind: u8,
+    /// Margin mode (the representation of [`MarginMode`]).
+    pub margin_mode: u8,
     /// Padding.
     #[cfg_attr(feature = "debug", debug(skip))]
-    pub padding_0: [u8; 13],
+    pub padding_0: [u8; 12],
     /// Owner.
     pub owner: Pubkey,
     /// The market token of the position market.
@@ -74,14 +83,84 @@ impl Position {
         Ok(matches!(self.kind()?, PositionKind::Long))
     }
 
+    /// Get margin mode.
+    pub fn margin_mode(&self) -> Result<MarginMode> {
+        let mode = MarginMode::try_from_primitive(self.margin_mode)?;
+        Ok(mode)
+    }
+
+    /// Returns whether the position is in cross margin mode.
+    pub fn is_cross_margin(&self) -> Result<bool> {
+        Ok(matches!(self.margin_mode()?, MarginMode::Cross))
+    }
+
+    /// Set the margin mode of the position.
+    ///
+    /// The margin mode can only be changed while the position is empty.
+    pub(crate) fn update_margin_mode(&mut self, mode: MarginMode) -> Result<()> {
+        if self.margin_mode()? == mode {
+            return Ok(());
+        }
+        require!(self.state.is_empty(), CoreError::MarginModeMismatched);
+        self.margin_mode = mode.into();
+        Ok(())
+    }
+
+    /// Find the addresses of the positions that can share collateral with this position
+    /// in cross margin mode, i.e., the other positions of the same owner in the same market.
+    ///
+    /// The addresses are ordered by position kind (long first), then by collateral token
+    /// (long token first).
+    pub fn find_cross_margin_sibling_addresses(
+        &self,
+        long_token: &Pubkey,
+        short_token: &Pubkey,
+        program_id: &Pubkey,
+    ) -> Result<Vec<Pubkey>> {
+        let kind = self.kind()?;
+        let collateral_tokens = if long_token == short_token {
+            vec![long_token]
+        } else {
+            vec![long_token, short_token]
+        };
+        let addresses = [PositionKind::Long, PositionKind::Short]
+            .into_iter()
+            .flat_map(|sibling_kind| {
+                collateral_tokens
+                    .iter()
+                    .map(move |sibling_collateral| (sibling_kind, *sibling_collateral))
+            })
+            .filter(|(sibling_kind, sibling_collateral)| {
+                *sibling_kind != kind || **sibling_collateral != self.collateral_token
+            })
+            .map(|(sibling_kind, sibling_collateral)| {
+                Pubkey::find_program_address(
+                    &[
+                        Self::SEED,
+                        self.store.as_ref(),
+                        self.owner.as_ref(),
+                        self.market_token.as_ref(),
+                        sibling_collateral.as_ref(),
+                        &[sibling_kind as u8],
+                    ],
+                    program_id,
+                )
+                .0
+            })
+            .collect();
+        Ok(addresses)
+    }
+
     /// Initialize the position state.
     ///
     /// Returns error if
     /// - `kind` is `Unitialized`.
     /// - The kind of the position is not `Uninitialized`.
+    #[allow(clippy::too_many_arguments)]
     pub fn try_init(
         &mut self,
         kind: PositionKind,
+        margin_mode: MarginMode,
         bump: u8,
         store: Pubkey,
         owner: &Pubkey,
@@ -95,6 +174,7 @@ impl Position {
             return err!(CoreError::InvalidPosition);
         }
         self.kind = kind as u8;
+        self.margin_mode = margin_mode.into();
         self.bump = bump;
         self.store = store;
         self.owner = *owner;
@@ -108,6 +188,18 @@ impl Position {
         AsPosition::try_new(self, market)
     }
 
+    /// Get the excess collateral value of the position that is shared with
+    /// the other positions in cross margin mode, which can be negative.
+    ///
+    /// See [`PositionExt::cross_margin_excess_value`] for more details.
+    pub fn excess_collateral_value(
+        &self,
+        market: &Market,
+        prices: &Prices<u128>,
+    ) -> gmsol_model::Result<i128> {
+        AsPosition::try_new(self, market)?.cross_margin_excess_value(prices)
+    }
+
     pub(crate) fn validate_for_market(&self, market: &Market) -> gmsol_model::Result<()> {
         let meta = market
             .validated_meta(&self.store)
@@ -298,6 +390,63 @@ pub enum PositionKind {
     Short,
 }
 
+/// Margin Mode.
+#[non_exhaustive]
+#[repr(u8)]
+#[derive(
+    AnchorSerialize,
+    AnchorDeserialize,
+    Clone,
+    Copy,
+    Default,
+    num_enum::IntoPrimitive,
+    num_enum::TryFromPrimitive,
+    PartialEq,
+    Eq,
+    strum::EnumString,
+    strum::Display,
+    InitSpace,
+    Debug,
+)]
+#[strum(serialize_all = "snake_case")]
+#[num_enum(error_type(name = CoreError, constructor = CoreError::invalid_margin_mode))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum MarginMode {
+    /// Isolated margin, the position is backed by its own collateral only.
+    #[default]
+    Isolated,
+    /// Cross margin, the excess collateral of the owner's other cross margin
+    /// positions in the same market is shared with the position.
+    Cross,
+}
+
+/// Max number of positions in a batch position preparation.
+pub const MAX_BATCH_PREPARE_POSITIONS: usize = 8;
+
+/// The specification of a position to prepare.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+#[cfg_attr(feature = "debug", derive(Debug))]
+pub struct PositionSpec {
+    /// Market token.
+    pub market_token: Pubkey,
+    /// Whether the position is long.
+    pub is_long: bool,
+    /// Collateral token.
+    pub collateral_token: Pubkey,
+}
+
+impl PositionSpec {
+    /// Get the position kind.
+    pub fn kind(&self) -> PositionKind {
+        if self.is_long {
+            PositionKind::Long
+        } else {
+            PositionKind::Short
+        }
+    }
+}
+
 /// A helper type that implements the [`Position`](gmsol_model::Position) trait.
 pub struct AsPosition<'a> {
     is_long: bool,
@@ -373,3 +522,522 @@ impl gmsol_model::Position<{ constants::MARKET_DECIMALS }> for AsPosition<'_> {
         self.position.validate_for_market(self.market)
     }
 }
+
+/// Position Info.
+///
+/// The health metrics of a position calculated with the given prices.
+#[cfg_attr(feature = "debug", derive(Debug))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct PositionInfo {
+    /// Size in USD.
+    pub size_in_usd: u128,
+    /// Size in tokens.
+    pub size_in_tokens: u128,
+    /// Collateral amount.
+    pub collateral_amount: u128,
+    /// Collateral value.
+    pub collateral_value: u128,
+    /// PnL value if the position were closed, capped by the max PnL factor for traders.
+    pub pnl: i128,
+    /// Uncapped PnL value.
+    pub uncapped_pnl: i128,
+    /// Pending borrowing fee value.
+    pub pending_borrowing_fee_value: u128,
+    /// Pending funding fee amount (in collateral token).
+    pub pending_funding_fee_amount: u128,
+    /// Claimable funding fee amount in long token.
+    pub claimable_funding_fee_amount_for_long_token: u128,
+    /// Claimable funding fee amount in short token.
+    pub claimable_funding_fee_amount_for_short_token: u128,
+    /// Net value, i.e., the collateral value plus the PnL minus the total cost
+    /// (including the closing fees) of closing the position.
+    pub net_value: i128,
+    /// Leverage (as a factor), i.e., the size in USD divided by the net value.
+    /// `None` if the net value is not positive.
+    pub leverage: Option<u128>,
+    /// Whether the position is liquidatable.
+    pub is_liquidatable: bool,
+}
+
+impl PositionInfo {
+    /// Create from position and prices.
+    ///
+    /// Note that the pending fees are calculated with the market state as of its last update.
+    pub fn from_position<P>(position: &P, prices: &Prices<u128>) -> gmsol_model::Result<Self>
+    where
+        P: gmsol_model::Position<{ constants::MARKET_DECIMALS }, Num = u128, Signed = i128>,
+    {
+        let size_in_usd = *position.size_in_usd();
+        let (pnl, uncapped_pnl, _) = position.pnl_value(prices, &size_in_usd)?;
+        let collateral_value = position.collateral_value(prices)?;
+        let collateral_price = position.collateral_price(prices);
+        let fees = position.position_fees(collateral_price, &size_in_usd, false, false)?;
+        let total_cost_value = fees
+            .total_cost_amount()?
+            .checked_mul(*collateral_price.pick_price(false))
+            .ok_or(gmsol_model::Error::Computation(
+                "overflow calculating total cost value",
+            ))?;
+        let net_value = i128::try_from(collateral_value)
+            .ok()
+            .and_then(|value| value.checked_add(pnl))
+            .and_then(|value| value.checked_sub(total_cost_value.try_into().ok()?))
+            .ok_or(gmsol_model::Error::Computation("calculating net value"))?;
+        let leverage = if net_value.is_positive() {
+            Some(
+                size_in_usd
+                    .checked_mul_div(&constants::MARKET_USD_UNIT, &net_value.unsigned_abs())
+                    .ok_or(gmsol_model::Error::Computation("calculating leverage"))?,
+            )
+        } else {
+            None
+        };
+        let funding_fees = fees.funding_fees();
+        Ok(Self {
+            size_in_usd,
+            size_in_tokens: *position.size_in_tokens(),
+            collateral_amount: *position.collateral_amount(),
+            collateral_value,
+            pnl,
+            uncapped_pnl,
+            pending_borrowing_fee_value: position.pending_borrowing_fee_value()?,
+            pending_funding_fee_amount: *funding_fees.amount(),
+            claimable_funding_fee_amount_for_long_token: *funding_fees
+                .claimable_long_token_amount(),
+            claimable_funding_fee_amount_for_short_token: *funding_fees
+                .claimable_short_token_amount(),
+            net_value,
+            leverage,
+            is_liquidatable: position.check_liquidatable(prices, true)?.is_some(),
+        })
+    }
+}
+
+/// ADL Estimate.
+///
+/// The estimated size to auto-deleverage for a position to restore the pnl factor of its side.
+#[cfg_attr(feature = "debug", derive(Debug))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct AdlEstimate {
+    /// The recommended size delta in USD to pass to `auto_deleverage`.
+    ///
+    /// It is zero if the pnl factor does not exceed the target or the position is not in profit,
+    /// and is capped by the size of the position.
+    pub recommended_size_delta_usd: u128,
+    /// The current pnl factor of the side of the position.
+    pub current_pnl_factor: u128,
+    /// The target pnl factor after ADL, i.e., the min pnl factor after ADL of the market.
+    pub target_pnl_factor: u128,
+    /// The (uncapped) pnl of the position per unit of size in USD, as a factor.
+    pub position_pnl_per_unit: u128,
+}
+
+impl AdlEstimate {
+    /// Estimate from position and prices.
+    ///
+    /// Closing a size of `s` realizes a pnl of `s * p` that is paid out of the pool,
+    /// so the size `s` restoring the target factor `t` satisfies
+    /// `(pnl - s * p) / (pool_value - s * p) = t`, i.e.,
+    /// `s = (pnl - pool_value * t) / (p * (1 - t))`.
+    pub fn from_position<P>(position: &P, prices: &Prices<u128>) -> gmsol_model::Result<Self>
+    where
+        P: gmsol_model::Position<{ constants::MARKET_DECIMALS }, Num = u128, Signed = i128>,
+    {
+        let market = position.market();
+        let is_long = position.is_long();
+
+        let (pnl_factor, pool_value) = market.pnl_factor_with_pool_value(prices, is_long, true)?;
+        let current_pnl_factor = pnl_factor.max(0).unsigned_abs();
+        let target_pnl_factor = market.pnl_factor_config(PnlFactorKind::MinAfterAdl, is_long)?;
+
+        let size_in_usd = *position.size_in_usd();
+        let (_, uncapped_pnl, _) = position.pnl_value(prices, &size_in_usd)?;
+        let position_pnl_per_unit = if uncapped_pnl.is_positive() {
+            div_to_factor(&uncapped_pnl.unsigned_abs(), &size_in_usd, false).ok_or(
+                gmsol_model::Error::Computation("calculating position pnl per unit"),
+            )?
+        } else {
+            0
+        };
+
+        let recommended_size_delta_usd =
+            if current_pnl_factor <= target_pnl_factor || position_pnl_per_unit == 0 {
+                0
+            } else {
+                let pnl = market
+                    .pnl(&prices.index_token_price, is_long, true)?
+                    .max(0)
+                    .unsigned_abs();
+                let target_pnl = apply_factor(&pool_value, &target_pnl_factor)
+                    .ok_or(gmsol_model::Error::Computation("calculating target pnl"))?;
+                let excess_pnl = pnl.saturating_sub(target_pnl);
+                let realized_pnl_per_unit = constants::MARKET_USD_UNIT
+                    .checked_sub(target_pnl_factor)
+                    .and_then(|factor| apply_factor(&position_pnl_per_unit, &factor))
+                    .ok_or(gmsol_model::Error::Computation(
+                        "calculating realized pnl per unit",
+                    ))?;
+                div_to_factor(&excess_pnl, &realized_pnl_per_unit, true)
+                    .ok_or(gmsol_model::Error::Computation(
+                        "calculating recommended size delta",
+                    ))?
+                    .min(size_in_usd)
+            };
+
+        Ok(Self {
+            recommended_size_delta_usd,
+            current_pnl_factor,
+            target_pnl_factor,
+            position_pnl_per_unit,
+        })
+    }
+}
+
+/// Liquidation Impact.
+///
+/// The estimated impact on the market of liquidating a position.
+#[cfg_attr(feature = "debug", derive(Debug))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct LiquidationImpact {
+    /// The size in USD to close, i.e., the full size of the position.
+    pub size_to_close_usd: u128,
+    /// The price impact value of closing the position. Negative impact is capped
+    /// by the max position impact factor for liquidations.
+    pub price_impact_usd: i128,
+    /// The collateral value remaining after paying the PnL, the price impact and
+    /// all the closing costs including the liquidation fee.
+    pub remaining_collateral_usd: u128,
+    /// The value of the liquidation fee charged for the liquidation.
+    pub keeper_reward_usd: u128,
+    /// The change of the pool value caused by the liquidation, i.e., the recovered
+    /// liquidation fee, or the loss not covered by the collateral if negative.
+    pub pool_impact: i128,
+    /// Whether the liquidation would not make the pool worse-off than the current state.
+    pub is_adverse_impact_acceptable: bool,
+}
+
+impl LiquidationImpact {
+    /// Estimate from position and prices.
+    ///
+    /// The pool value already accounts for the PnL of the position as if it could be
+    /// fully paid by the collateral, so the pool is only worse-off when the collateral
+    /// cannot cover the loss and the costs of closing the position.
+    pub fn from_position<P>(position: &P, prices: &Prices<u128>) -> gmsol_model::Result<Self>
+    where
+        P: gmsol_model::Position<{ constants::MARKET_DECIMALS }, Num = u128, Signed = i128>,
+    {
+        let size_in_usd = *position.size_in_usd();
+        let (pnl, _, _) = position.pnl_value(prices, &size_in_usd)?;
+        let collateral_value = position.collateral_value(prices)?;
+        let collateral_price = position.collateral_price(prices);
+
+        let size_delta_usd = i128::try_from(size_in_usd)
+            .map_err(|_| gmsol_model::Error::Convert)?
+            .checked_neg()
+            .ok_or(gmsol_model::Error::Computation("negating size delta"))?;
+        let mut price_impact_usd = position.position_price_impact(&size_delta_usd)?;
+        if price_impact_usd.is_negative() {
+            position.market().cap_negative_position_price_impact(
+                &size_delta_usd,
+                true,
+                &mut price_impact_usd,
+            )?;
+        }
+
+        let fees = position.position_fees(
+            collateral_price,
+            &size_in_usd,
+            price_impact_usd.is_positive(),
+            true,
+        )?;
+        let to_value = |amount: u128| {
+            amount
+                .checked_mul(*collateral_price.pick_price(false))
+                .and_then(|value| i128::try_from(value).ok())
+                .ok_or(gmsol_model::Error::Computation(
+                    "overflow calculating cost value",
+                ))
+        };
+        let liquidation_fee_value = to_value(
+            fees.liquidation_fees()
+                .map(|fees| *fees.fee_amount())
+                .unwrap_or_default(),
+        )?;
+        let total_cost_value = to_value(fees.total_cost_amount()?)?;
+
+        let remaining_collateral_value = i128::try_from(collateral_value)
+            .ok()
+            .and_then(|value| value.checked_add(pnl))
+            .and_then(|value| value.checked_add(price_impact_usd))
+            .and_then(|value| value.checked_sub(total_cost_value))
+            .ok_or(gmsol_model::Error::Computation(
+                "calculating remaining collateral value",
+            ))?;
+
+        // The liquidation fee is recovered only if the collateral is sufficient.
+        let remaining_before_liquidation_fee = remaining_collateral_value
+            .checked_add(liquidation_fee_value)
+            .ok_or(gmsol_model::Error::Computation(
+                "calculating remaining collateral value before liquidation fee",
+            ))?;
+        let pool_impact = if remaining_before_liquidation_fee.is_negative() {
+            remaining_before_liquidation_fee
+        } else {
+            remaining_before_liquidation_fee.min(liquidation_fee_value)
+        };
+
+        Ok(Self {
+            size_to_close_usd: size_in_usd,
+            price_impact_usd,
+            remaining_collateral_usd: remaining_collateral_value.max(0).unsigned_abs(),
+            keeper_reward_usd: liquidation_fee_value.unsigned_abs(),
+            pool_impact,
+            is_adverse_impact_acceptable: !pool_impact.is_negative(),
+        })
+    }
+}
+
+/// Collateral Coverage.
+///
+/// The collateral coverage of a position calculated with the given prices.
+#[cfg_attr(feature = "debug", derive(Debug))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct CollateralCoverage {
+    /// The collateral value remaining if the position were closed, i.e., the collateral
+    /// value plus the PnL and the negative price impact minus the closing costs
+    /// (excluding the liquidation fee). It is the value checked against the margin
+    /// when determining whether the position is liquidatable.
+    pub collateral_usd: u128,
+    /// The maintenance margin, i.e., the min collateral value required for the position
+    /// not to be liquidated.
+    pub maintenance_margin_usd: u128,
+    /// The initial margin, i.e., the min collateral value required to open a position
+    /// of the current size.
+    pub initial_margin_usd: u128,
+    /// The coverage ratio (as a factor), i.e., `collateral_usd / maintenance_margin_usd`.
+    pub coverage_ratio: u128,
+    /// The index token price at which the collateral exactly meets the maintenance margin,
+    /// assuming the prices of the other tokens stay unchanged.
+    pub margin_call_price: u128,
+    /// Whether the collateral does not exceed the maintenance margin.
+    pub is_margin_called: bool,
+}
+
+impl CollateralCoverage {
+    /// Calculate from position and prices.
+    ///
+    /// Only the PnL of the position depends on the index token price, and it changes
+    /// by `size_in_tokens` per unit of the price, so the margin call price is the price
+    /// offsetting the excess of the collateral over the maintenance margin.
+    /// It is rounded such that the position is margin called at the returned price.
+    pub fn from_position<P>(position: &P, prices: &Prices<u128>) -> gmsol_model::Result<Self>
+    where
+        P: gmsol_model::Position<{ constants::MARKET_DECIMALS }, Num = u128, Signed = i128>,
+    {
+        let market = position.market();
+        let is_long = position.is_long();
+        let size_in_usd = *position.size_in_usd();
+        let size_in_tokens = *position.size_in_tokens();
+        let (pnl, uncapped_pnl, _) = position.pnl_value(prices, &size_in_usd)?;
+        let collateral_value = position.collateral_value(prices)?;
+        let collateral_price = position.collateral_price(prices);
+
+        // Same as the liquidation check: only the negative price impact is accounted,
+        // and the liquidation fee is excluded.
+        let size_delta_usd = i128::try_from(size_in_usd)
+            .map_err(|_| gmsol_model::Error::Convert)?
+            .checked_neg()
+            .ok_or(gmsol_model::Error::Computation("negating size delta"))?;
+        let mut price_impact_usd = position.position_price_impact(&size_delta_usd)?;
+        let has_positive_impact = price_impact_usd.is_positive();
+        if price_impact_usd.is_negative() {
+            market.cap_negative_position_price_impact(
+                &size_delta_usd,
+                true,
+                &mut price_impact_usd,
+            )?;
+        } else {
+            price_impact_usd = 0;
+        }
+        let fees =
+            position.position_fees(collateral_price, &size_in_usd, has_positive_impact, false)?;
+        let total_cost_value = fees
+            .total_cost_amount()?
+            .checked_mul(*collateral_price.pick_price(false))
+            .and_then(|value| i128::try_from(value).ok())
+            .ok_or(gmsol_model::Error::Computation(
+                "overflow calculating total cost value",
+            ))?;
+        let collateral_value_without_pnl = i128::try_from(collateral_value)
+            .ok()
+            .and_then(|value| value.checked_add(price_impact_usd))
+            .and_then(|value| value.checked_sub(total_cost_value))
+            .ok_or(gmsol_model::Error::Computation(
+                "calculating collateral value without pnl",
+            ))?;
+        let collateral_usd = collateral_value_without_pnl
+            .checked_add(pnl)
+            .ok_or(gmsol_model::Error::Computation(
+                "calculating collateral usd",
+            ))?
+            .max(0)
+            .unsigned_abs();
+
+        let params = market.position_params()?;
+        let maintenance_margin_usd = apply_factor(&size_in_usd, params.min_collateral_factor())
+            .ok_or(gmsol_model::Error::Computation(
+                "calculating maintenance margin",
+            ))?
+            .max(*params.min_collateral_value());
+        let initial_margin_factor = market
+            .min_collateral_factor_for_open_interest(&0, is_long)?
+            .max(*params.min_collateral_factor());
+        let initial_margin_usd = apply_factor(&size_in_usd, &initial_margin_factor).ok_or(
+            gmsol_model::Error::Computation("calculating initial margin"),
+        )?;
+
+        let coverage_ratio = div_to_factor(&collateral_usd, &maintenance_margin_usd, false).ok_or(
+            gmsol_model::Error::Computation("calculating coverage ratio"),
+        )?;
+
+        // The PnL is never capped at the margin call price since the position is at a loss.
+        let excess = collateral_value_without_pnl
+            .checked_add(uncapped_pnl)
+            .and_then(|value| value.checked_sub(i128::try_from(maintenance_margin_usd).ok()?))
+            .ok_or(gmsol_model::Error::Computation(
+                "calculating excess collateral",
+            ))?;
+        let price = *prices.index_token_price.pick_price_for_pnl(is_long, false);
+        let margin_call_price = if size_in_tokens == 0 {
+            0
+        } else {
+            // The price must move against the position by at least `excess / size_in_tokens`
+            // to be margin called, or can move in favor of the position by at most
+            // `-excess / size_in_tokens` while staying margin called.
+            let is_adverse = excess.is_positive();
+            let delta = if is_adverse {
+                excess.unsigned_abs().div_ceil(size_in_tokens)
+            } else {
+                excess.unsigned_abs() / size_in_tokens
+            };
+            if is_adverse == is_long {
+                price.saturating_sub(delta)
+            } else {
+                price.saturating_add(delta)
+            }
+        };
+
+        Ok(Self {
+            collateral_usd,
+            maintenance_margin_usd,
+            initial_margin_usd,
+            coverage_ratio,
+            margin_call_price,
+            is_margin_called: collateral_usd <= maintenance_margin_usd,
+        })
+    }
+}
+
+/// Break-even Price.
+///
+/// The index token prices at which the PnL of a position offsets its fees.
+#[cfg_attr(feature = "debug", derive(Debug))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct BreakEvenPrice {
+    /// The index token price at which the PnL offsets all the fees, including the
+    /// projected closing fee.
+    pub break_even_price: u128,
+    /// The average entry price, i.e., the size in USD divided by the size in tokens.
+    pub entry_price: u128,
+    /// The value of the fees accumulated so far, i.e., the opening fee, the pending
+    /// borrowing fee and the pending funding fee.
+    pub accumulated_fees_usd: u128,
+    /// The index token price at which the PnL offsets the accumulated fees.
+    pub fee_adjusted_entry_price: u128,
+    /// The distance (in basis points of the entry price) that the fees have moved the
+    /// break-even price away from the entry price. Positive means the break-even price
+    /// is further away from the current market than the entry price.
+    pub break_even_moved_by_pct: i32,
+}
+
+impl BreakEvenPrice {
+    /// Calculate from position and prices.
+    ///
+    /// The opening fee is not recorded in the position, so it is estimated to be the
+    /// order fee for the current size, i.e., the same as the projected closing fee.
+    /// Price impact is not taken into account.
+    pub fn from_position<P>(position: &P, prices: &Prices<u128>) -> gmsol_model::Result<Self>
+    where
+        P: gmsol_model::Position<{ constants::MARKET_DECIMALS }, Num = u128, Signed = i128>,
+    {
+        let is_long = position.is_long();
+        let size_in_usd = *position.size_in_usd();
+        let size_in_tokens = *position.size_in_tokens();
+        if size_in_tokens == 0 {
+            return Err(gmsol_model::Error::InvalidPosition("empty position"));
+        }
+        let collateral_price = position.collateral_price(prices);
+        let fees = position.position_fees(collateral_price, &size_in_usd, false, false)?;
+        let to_value = |amount: &u128| {
+            amount
+                .checked_mul(*collateral_price.pick_price(false))
+                .ok_or(gmsol_model::Error::Computation(
+                    "overflow calculating fee value",
+                ))
+        };
+
+        let order_fees = fees.order_fees().fee_amounts();
+        let closing_fee_value = order_fees
+            .fee_amount_for_pool()
+            .checked_add(*order_fees.fee_amount_for_receiver())
+            .ok_or(gmsol_model::Error::Computation(
+                "calculating order fee amount",
+            ))
+            .and_then(|amount| to_value(&amount))?;
+        let accumulated_fees_usd = to_value(fees.borrowing_fees().fee_amount())?
+            .checked_add(to_value(fees.funding_fees().amount())?)
+            .and_then(|value| value.checked_add(closing_fee_value))
+            .ok_or(gmsol_model::Error::Computation(
+                "calculating accumulated fees",
+            ))?;
+        let total_fees_usd = accumulated_fees_usd
+            .checked_add(closing_fee_value)
+            .ok_or(gmsol_model::Error::Computation("calculating total fees"))?;
+
+        let entry_price = size_in_usd / size_in_tokens;
+        // The fees are offset only when the price has moved by at least `fees / size_in_tokens`.
+        let adjust = |fees: u128| {
+            let delta = fees.div_ceil(size_in_tokens);
+            if is_long {
+                entry_price.saturating_add(delta)
+            } else {
+                entry_price.saturating_sub(delta)
+            }
+        };
+        let fee_adjusted_entry_price = adjust(accumulated_fees_usd);
+        let break_even_price = adjust(total_fees_usd);
+
+        let moved = break_even_price.abs_diff(entry_price);
+        let moved_by_bps = if entry_price == 0 {
+            0
+        } else {
+            moved
+                .checked_mul_div(&10_000, &entry_price)
+                .and_then(|bps| i32::try_from(bps).ok())
+                .unwrap_or(i32::MAX)
+        };
+
+        Ok(Self {
+            break_even_price,
+            entry_price,
+            accumulated_fees_usd,
+            fee_adjusted_entry_price,
+            break_even_moved_by_pct: moved_by_bps,
+        })
+    }
+}
@@ -63,6 +63,11 @@ impl Shift {
     pub fn tokens(&self) -> &ShiftTokenAccounts {
         &self.tokens
     }
+
+    /// Get shift params.
+    pub fn params(&self) -> &ShiftActionParams {
+        &self.params
+    }
 }
 
 #[zero_copy]
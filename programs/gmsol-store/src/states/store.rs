@@ -9,10 +9,11 @@ use crate::{constants, states::feature::display_feature, CoreError, CoreResult};
 use super::{
     feature::{ActionDisabledFlag, DisabledFeatures, DomainDisabledFlag},
     gt::GtState,
-    Amount, Factor, InitSpace, RoleKey, RoleStore, Seed,
+    Amount, Factor, InitSpace, PendingActionKind, RoleKey, RoleStore, Seed,
 };
 
 const MAX_LEN: usize = 32;
+const MAX_TRUSTED_CHAINLINK_VERIFIERS: usize = 4;
 
 /// Data Store.
 #[account(zero_copy)]
@@ -49,8 +50,14 @@ pub struct Store {
     pub(crate) address: Addresses,
     /// GT State.
     gt: GtState,
+    /// Emergency State.
+    emergency: EmergencyState,
+    /// The number of config snapshots taken.
+    config_snapshot_count: u64,
+    /// Trusted Chainlink verifier programs.
+    trusted_chainlink_verifiers: [Pubkey; MAX_TRUSTED_CHAINLINK_VERIFIERS],
     #[cfg_attr(feature = "debug", debug(skip))]
-    reserved: [u8; 1024],
+    reserved: [u8; 824],
 }
 
 static_assertions::const_assert!(Store::INIT_SPACE + 8 <= 10240);
@@ -91,6 +98,9 @@ impl Store {
     /// Wallet Seed.
     pub const WALLET_SEED: &'static [u8] = b"store_wallet";
 
+    /// Maximum number of trusted Chainlink verifiers.
+    pub const MAX_TRUSTED_CHAINLINK_VERIFIERS: usize = MAX_TRUSTED_CHAINLINK_VERIFIERS;
+
     /// Initialize.
     pub fn init(
         &mut self,
@@ -228,6 +238,27 @@ impl Store {
         self.amount.get(&key)
     }
 
+    /// Get the max number of trades a user can create in a single slot.
+    ///
+    /// Zero means no limit.
+    pub fn max_trades_per_block_per_user(&self) -> u32 {
+        let amount = *self.get_amount_by_key(AmountKey::MaxTradesPerBlockPerUser);
+        u32::try_from(amount).unwrap_or(u32::MAX)
+    }
+
+    /// Get the max number of pending actions of the given kind a market can have.
+    ///
+    /// Zero means no limit.
+    pub fn max_pending_actions(&self, kind: PendingActionKind) -> u32 {
+        let key = match kind {
+            PendingActionKind::Deposit => AmountKey::MaxPendingDeposits,
+            PendingActionKind::Withdrawal => AmountKey::MaxPendingWithdrawals,
+            PendingActionKind::Order => AmountKey::MaxPendingOrders,
+        };
+        let amount = *self.get_amount_by_key(key);
+        u32::try_from(amount).unwrap_or(u32::MAX)
+    }
+
     /// Get amount mutably
     pub fn get_amount_mut(&mut self, key: &str) -> Result<&mut Amount> {
         let key = AmountKey::from_str(key).map_err(|_| error!(CoreError::InvalidStoreConfigKey))?;
@@ -301,11 +332,43 @@ impl Store {
         Ok(index.to_le_bytes())
     }
 
+    /// Get the seconds elapsed at `now` since the end of the claimable time window
+    /// of the given timestamp.
+    ///
+    /// Returns a non-positive value if the time window has not ended.
+    pub fn claimable_dormant_secs(&self, timestamp: i64, now: i64) -> Result<i64> {
+        let window: i64 = self
+            .claimable_time_window()?
+            .get()
+            .try_into()
+            .map_err(|_| error!(CoreError::InvalidArgument))?;
+        let end = self
+            .claimable_time_window_index(timestamp)?
+            .checked_add(1)
+            .and_then(|index| index.checked_mul(window))
+            .ok_or_else(|| error!(CoreError::ValueOverflow))?;
+        Ok(now.saturating_sub(end))
+    }
+
     /// Get holding address.
     pub fn holding(&self) -> &Pubkey {
         &self.address.holding
     }
 
+    /// Get GT buyback address.
+    pub fn buyback(&self) -> &Pubkey {
+        &self.address.buyback
+    }
+
+    /// Validate whether the given address is the GT buyback address.
+    pub fn validate_buyback_address(&self, address: &Pubkey) -> Result<()> {
+        require!(
+            *address != Pubkey::default() && *address == self.address.buyback,
+            CoreError::PermissionDenied
+        );
+        Ok(())
+    }
+
     /// Set the next receiver address of the treasury.
     pub(crate) fn set_next_receiver(&mut self, next_authority: &Pubkey) -> Result<()> {
         self.treasury.set_next_receiver(next_authority)
@@ -365,11 +428,17 @@ impl Store {
     }
 
     /// Validate whether the given features is enabled.
+    ///
+    /// All actions except [`Cancel`](ActionDisabledFlag::Cancel) are disabled
+    /// while the store is paused, so that users can still exit.
     pub fn validate_feature_enabled(
         &self,
         domain: DomainDisabledFlag,
         action: ActionDisabledFlag,
     ) -> Result<()> {
+        if !matches!(action, ActionDisabledFlag::Cancel) {
+            self.validate_not_paused()?;
+        }
         if self.is_feature_disabled(domain, action) {
             msg!("Feature `{}` is disabled", display_feature(domain, action));
             err!(CoreError::FeatureDisabled)
@@ -389,6 +458,94 @@ impl Store {
             .set_disabled(domain, action, disabled)
     }
 
+    /// Returns whether the store is paused.
+    pub fn is_paused(&self) -> bool {
+        self.emergency.is_paused()
+    }
+
+    /// Validate that the store is not paused.
+    pub fn validate_not_paused(&self) -> Result<()> {
+        if self.is_paused() {
+            msg!("The store is paused");
+            err!(CoreError::FeatureDisabled)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Set the store paused or not.
+    pub(crate) fn set_paused(&mut self, paused: bool, now: i64) -> Result<()> {
+        self.emergency.set_paused(paused, now)
+    }
+
+    /// Returns whether the emergency mode is enabled.
+    pub fn is_emergency_mode_enabled(&self) -> bool {
+        self.emergency.is_emergency_mode_enabled()
+    }
+
+    /// Enable the emergency mode.
+    pub(crate) fn enable_emergency_mode(&mut self, now: i64) -> Result<()> {
+        self.emergency.enable_emergency_mode(now)
+    }
+
+    /// Validate that the emergency mode is enabled.
+    pub fn validate_emergency_mode_enabled(&self) -> Result<()> {
+        require!(
+            self.is_emergency_mode_enabled(),
+            CoreError::EmergencyModeNotEnabled
+        );
+        Ok(())
+    }
+
+    /// Get the index of the next config snapshot.
+    pub fn next_config_snapshot_index(&self) -> u64 {
+        self.config_snapshot_count
+    }
+
+    /// Increase the config snapshot count and return the index of the new snapshot.
+    pub(crate) fn next_config_snapshot(&mut self) -> Result<u64> {
+        let index = self.config_snapshot_count;
+        self.config_snapshot_count = index
+            .checked_add(1)
+            .ok_or_else(|| error!(CoreError::ValueOverflow))?;
+        Ok(index)
+    }
+
+    /// Get the trusted Chainlink verifier entries.
+    /// Entries set to the default pubkey are unused.
+    pub fn trusted_chainlink_verifiers(&self) -> &[Pubkey] {
+        &self.trusted_chainlink_verifiers
+    }
+
+    /// Returns whether the given Chainlink verifier program is trusted.
+    pub fn is_trusted_chainlink_verifier(&self, program_id: &Pubkey) -> bool {
+        *program_id != Pubkey::default() && self.trusted_chainlink_verifiers.contains(program_id)
+    }
+
+    /// Validate that the given Chainlink verifier program is trusted.
+    pub fn validate_trusted_chainlink_verifier(&self, program_id: &Pubkey) -> Result<()> {
+        require!(
+            self.is_trusted_chainlink_verifier(program_id),
+            CoreError::UntrustedChainlinkVerifier
+        );
+        Ok(())
+    }
+
+    /// Set the trusted Chainlink verifier entry at the given index.
+    /// Set to the default pubkey to remove the entry.
+    pub(crate) fn set_trusted_chainlink_verifier(
+        &mut self,
+        index: u8,
+        verifier: &Pubkey,
+    ) -> Result<()> {
+        let entry = self
+            .trusted_chainlink_verifiers
+            .get_mut(usize::from(index))
+            .ok_or_else(|| error!(CoreError::InvalidArgument))?;
+        *entry = *verifier;
+        Ok(())
+    }
+
     /// Returns whether the cluster has restarted since last update.
     pub fn has_restarted(&self) -> Result<bool> {
         Ok(self.last_restarted_slot != LastRestartSlot::get()?.last_restart_slot)
@@ -450,6 +607,28 @@ impl Store {
             Ok(discount_factor_for_rank)
         }
     }
+
+    /// Split the claimed fees into `(treasury_amount, buyback_amount)`
+    /// according to the buyback fee factor.
+    pub fn split_fees(&self, amount: u64) -> Result<(u64, u64)> {
+        use gmsol_model::utils::apply_factor;
+
+        let factor = *self.get_factor_by_key(FactorKey::BuybackFeeFactor);
+        require_gte!(
+            constants::MARKET_USD_UNIT,
+            factor,
+            CoreError::InvalidArgument
+        );
+        let buyback_amount: u64 =
+            apply_factor::<_, { constants::MARKET_DECIMALS }>(&u128::from(amount), &factor)
+                .ok_or_else(|| error!(CoreError::ValueOverflow))?
+                .try_into()
+                .map_err(|_| error!(CoreError::ValueOverflow))?;
+        let treasury_amount = amount
+            .checked_sub(buyback_amount)
+            .ok_or_else(|| error!(CoreError::Internal))?;
+        Ok((treasury_amount, buyback_amount))
+    }
 }
 
 /// Store Wallet Signer.
@@ -521,6 +700,62 @@ impl Treasury {
     }
 }
 
+/// Emergency State.
+#[zero_copy]
+#[cfg_attr(feature = "debug", derive(derive_more::Debug))]
+pub struct EmergencyState {
+    paused: u8,
+    emergency_mode: u8,
+    #[cfg_attr(feature = "debug", debug(skip))]
+    padding: [u8; 6],
+    /// The timestamp when the store was paused.
+    paused_at: i64,
+    #[cfg_attr(feature = "debug", debug(skip))]
+    reserved: [u8; 48],
+}
+
+impl EmergencyState {
+    fn is_paused(&self) -> bool {
+        self.paused != 0
+    }
+
+    fn set_paused(&mut self, paused: bool, now: i64) -> Result<()> {
+        require_neq!(self.is_paused(), paused, CoreError::PreconditionsAreNotMet);
+        if paused {
+            self.paused = 1;
+            self.paused_at = now;
+        } else {
+            // Leaving the paused state also disables the emergency mode.
+            self.paused = 0;
+            self.paused_at = 0;
+            self.emergency_mode = 0;
+        }
+        Ok(())
+    }
+
+    fn is_emergency_mode_enabled(&self) -> bool {
+        self.is_paused() && self.emergency_mode != 0
+    }
+
+    fn enable_emergency_mode(&mut self, now: i64) -> Result<()> {
+        require!(self.is_paused(), CoreError::PreconditionsAreNotMet);
+        require!(
+            !self.is_emergency_mode_enabled(),
+            CoreError::PreconditionsAreNotMet
+        );
+        let paused_duration = now
+            .checked_sub(self.paused_at)
+            .ok_or_else(|| error!(CoreError::ValueOverflow))?;
+        require_gte!(
+            paused_duration,
+            constants::EMERGENCY_MODE_MIN_PAUSED_DURATION,
+            CoreError::PreconditionsAreNotMet
+        );
+        self.emergency_mode = 1;
+        Ok(())
+    }
+}
+
 /// Amounts.
 #[zero_copy]
 #[cfg_attr(feature = "debug", derive(derive_more::Debug))]
@@ -532,16 +767,23 @@ pub struct Amounts {
     pub(crate) oracle_max_timestamp_range: Amount,
     pub(crate) oracle_max_future_timestamp_excess: Amount,
     pub(crate) adl_prices_max_staleness: Amount,
+    pub(crate) keeper_assignment_timeout: Amount,
+    pub(crate) max_claimable_account_lifetime: Amount,
+    pub(crate) oracle_price_deviation_window: Amount,
+    pub(crate) max_order_callback_compute_units: Amount,
+    pub(crate) max_trades_per_block_per_user: Amount,
+    pub(crate) max_pending_deposits: Amount,
+    pub(crate) max_pending_withdrawals: Amount,
+    pub(crate) max_pending_orders: Amount,
     #[cfg_attr(feature = "debug", debug(skip))]
-    reserved: [Amount; 126],
+    reserved: [Amount; 118],
 }
 
 /// Amount keys.
-#[derive(strum::EnumString, strum::Display, Clone, Copy)]
+#[derive(strum::EnumString, strum::Display, strum::EnumIter, Clone, Copy)]
 #[strum(serialize_all = "snake_case")]
 #[non_exhaustive]
 #[cfg_attr(feature = "debug", derive(Debug))]
-#[cfg_attr(feature = "enum-iter", derive(strum::EnumIter))]
 #[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
 #[cfg_attr(feature = "clap", clap(rename_all = "snake_case"))]
 pub enum AmountKey {
@@ -559,6 +801,54 @@ pub enum AmountKey {
     OracleMaxFutureTimestampExcess,
     /// Max ADL prices staleness (seconds).
     AdlPricesMaxStaleness,
+    /// Keeper assignment timeout (seconds).
+    ///
+    /// Orders assigned to a keeper can be executed by other keepers after this
+    /// duration has passed since the last update of the order.
+    KeeperAssignmentTimeout,
+    /// Max claimable account lifetime (seconds).
+    ///
+    /// Claimable accounts that have been dormant for longer than this duration
+    /// after the end of their time window can be recovered by the admin.
+    MaxClaimableAccountLifetime,
+    /// Oracle price deviation window (seconds).
+    ///
+    /// A new price from the price feed is compared with the last recorded price of the
+    /// token only if the last price was recorded within this duration.
+    OraclePriceDeviationWindow,
+    /// Max compute units for an order callback.
+    ///
+    /// Invoking the pending callback of an executed order fails if the callback consumes
+    /// more compute units than this. Set to zero to disable order callbacks.
+    MaxOrderCallbackComputeUnits,
+    /// Max number of actions a user can create in a single slot.
+    ///
+    /// Applies to the creation of orders, deposits and withdrawals. Set to zero to
+    /// disable the limit.
+    MaxTradesPerBlockPerUser,
+    /// Max number of pending deposits of a market.
+    ///
+    /// Set to zero to disable the limit. Deposits created while the limit is
+    /// disabled are not counted.
+    MaxPendingDeposits,
+    /// Max number of pending withdrawals of a market.
+    ///
+    /// Set to zero to disable the limit. Withdrawals created while the limit is
+    /// disabled are not counted.
+    MaxPendingWithdrawals,
+    /// Max number of pending orders of a market.
+    ///
+    /// Set to zero to disable the limit. Orders created while the limit is
+    /// disabled are not counted. Decrease orders are never counted, so that
+    /// positions can always be closed.
+    ///
+    /// Limit and trigger orders stay pending until their trigger prices are
+    /// reached, and only cost the owner refundable rent and execution lamports.
+    /// Anyone can therefore occupy the slots of a market with orders that are far
+    /// from the market price and block the creation of other increase and swap
+    /// orders, so the limit should be set well above the expected number of
+    /// resting orders.
+    MaxPendingOrders,
 }
 
 impl Amounts {
@@ -571,10 +861,14 @@ impl Amounts {
         self.oracle_max_future_timestamp_excess =
             constants::DEFAULT_ORACLE_MAX_FUTURE_TIMESTAMP_EXCESS;
         self.adl_prices_max_staleness = constants::DEFAULT_ADL_PRICES_MAX_STALENESS;
+        self.keeper_assignment_timeout = constants::DEFAULT_KEEPER_ASSIGNMENT_TIMEOUT;
+        self.max_claimable_account_lifetime = constants::DEFAULT_MAX_CLAIMABLE_ACCOUNT_LIFETIME;
+        self.oracle_price_deviation_window = constants::DEFAULT_ORACLE_PRICE_DEVIATION_WINDOW;
+        self.max_order_callback_compute_units = constants::DEFAULT_MAX_ORDER_CALLBACK_COMPUTE_UNITS;
     }
 
     /// Get.
-    fn get(&self, key: &AmountKey) -> &Amount {
+    pub(crate) fn get(&self, key: &AmountKey) -> &Amount {
         match key {
             AmountKey::ClaimableTimeWindow => &self.claimable_time_window,
             AmountKey::RecentTimeWindow => &self.recent_time_window,
@@ -583,6 +877,14 @@ impl Amounts {
             AmountKey::OracleMaxTimestampRange => &self.oracle_max_timestamp_range,
             AmountKey::OracleMaxFutureTimestampExcess => &self.oracle_max_future_timestamp_excess,
             AmountKey::AdlPricesMaxStaleness => &self.adl_prices_max_staleness,
+            AmountKey::KeeperAssignmentTimeout => &self.keeper_assignment_timeout,
+            AmountKey::MaxClaimableAccountLifetime => &self.max_claimable_account_lifetime,
+            AmountKey::OraclePriceDeviationWindow => &self.oracle_price_deviation_window,
+            AmountKey::MaxOrderCallbackComputeUnits => &self.max_order_callback_compute_units,
+            AmountKey::MaxTradesPerBlockPerUser => &self.max_trades_per_block_per_user,
+            AmountKey::MaxPendingDeposits => &self.max_pending_deposits,
+            AmountKey::MaxPendingWithdrawals => &self.max_pending_withdrawals,
+            AmountKey::MaxPendingOrders => &self.max_pending_orders,
         }
     }
 
@@ -598,6 +900,14 @@ impl Amounts {
                 &mut self.oracle_max_future_timestamp_excess
             }
             AmountKey::AdlPricesMaxStaleness => &mut self.adl_prices_max_staleness,
+            AmountKey::KeeperAssignmentTimeout => &mut self.keeper_assignment_timeout,
+            AmountKey::MaxClaimableAccountLifetime => &mut self.max_claimable_account_lifetime,
+            AmountKey::OraclePriceDeviationWindow => &mut self.oracle_price_deviation_window,
+            AmountKey::MaxOrderCallbackComputeUnits => &mut self.max_order_callback_compute_units,
+            AmountKey::MaxTradesPerBlockPerUser => &mut self.max_trades_per_block_per_user,
+            AmountKey::MaxPendingDeposits => &mut self.max_pending_deposits,
+            AmountKey::MaxPendingWithdrawals => &mut self.max_pending_withdrawals,
+            AmountKey::MaxPendingOrders => &mut self.max_pending_orders,
         }
     }
 }
@@ -608,16 +918,19 @@ impl Amounts {
 pub struct Factors {
     pub(crate) oracle_ref_price_deviation: Factor,
     pub(crate) order_fee_discount_for_referred_user: Factor,
+    pub(crate) min_deposit_long_usd: Factor,
+    pub(crate) min_deposit_short_usd: Factor,
+    pub(crate) buyback_fee_factor: Factor,
+    pub(crate) supply_tolerance_factor: Factor,
     #[cfg_attr(feature = "debug", debug(skip))]
-    reserved: [Factor; 64],
+    reserved: [Factor; 60],
 }
 
 /// Factor keys.
-#[derive(strum::EnumString, strum::Display, Clone, Copy)]
+#[derive(strum::EnumString, strum::Display, strum::EnumIter, Clone, Copy)]
 #[strum(serialize_all = "snake_case")]
 #[non_exhaustive]
 #[cfg_attr(feature = "debug", derive(Debug))]
-#[cfg_attr(feature = "enum-iter", derive(strum::EnumIter))]
 #[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
 #[cfg_attr(feature = "clap", clap(rename_all = "snake_case"))]
 pub enum FactorKey {
@@ -625,20 +938,37 @@ pub enum FactorKey {
     OracleRefPriceDeviation,
     /// Order fee discount for referred user.
     OrderFeeDiscountForReferredUser,
+    /// Min USD value of the long side of a deposit.
+    MinDepositLongUsd,
+    /// Min USD value of the short side of a deposit.
+    MinDepositShortUsd,
+    /// The fraction of the claimed fees to send to the buyback address,
+    /// the remainder goes to the treasury receiver.
+    ///
+    /// Defaults to zero, i.e., all claimed fees go to the treasury receiver.
+    BuybackFeeFactor,
+    /// The max relative difference between the market token supply and its estimate
+    /// from the market balances to be considered consistent.
+    SupplyToleranceFactor,
 }
 
 impl Factors {
     fn init(&mut self) {
         self.oracle_ref_price_deviation = constants::DEFAULT_ORACLE_REF_PRICE_DEVIATION;
+        self.supply_tolerance_factor = constants::DEFAULT_SUPPLY_TOLERANCE_FACTOR;
     }
 
     /// Get.
-    fn get(&self, key: &FactorKey) -> &Factor {
+    pub(crate) fn get(&self, key: &FactorKey) -> &Factor {
         match key {
             FactorKey::OracleRefPriceDeviation => &self.oracle_ref_price_deviation,
             FactorKey::OrderFeeDiscountForReferredUser => {
                 &self.order_fee_discount_for_referred_user
             }
+            FactorKey::MinDepositLongUsd => &self.min_deposit_long_usd,
+            FactorKey::MinDepositShortUsd => &self.min_deposit_short_usd,
+            FactorKey::BuybackFeeFactor => &self.buyback_fee_factor,
+            FactorKey::SupplyToleranceFactor => &self.supply_tolerance_factor,
         }
     }
 
@@ -649,6 +979,10 @@ impl Factors {
             FactorKey::OrderFeeDiscountForReferredUser => {
                 &mut self.order_fee_discount_for_referred_user
             }
+            FactorKey::MinDepositLongUsd => &mut self.min_deposit_long_usd,
+            FactorKey::MinDepositShortUsd => &mut self.min_deposit_short_usd,
+            FactorKey::BuybackFeeFactor => &mut self.buyback_fee_factor,
+            FactorKey::SupplyToleranceFactor => &mut self.supply_tolerance_factor,
         }
     }
 }
@@ -658,21 +992,23 @@ impl Factors {
 #[cfg_attr(feature = "debug", derive(derive_more::Debug))]
 pub struct Addresses {
     pub(crate) holding: Pubkey,
+    pub(crate) buyback: Pubkey,
     #[cfg_attr(feature = "debug", debug(skip))]
-    reserved: [Pubkey; 30],
+    reserved: [Pubkey; 29],
 }
 
 /// Address keys.
-#[derive(strum::EnumString, strum::Display, Clone, Copy)]
+#[derive(strum::EnumString, strum::Display, strum::EnumIter, Clone, Copy)]
 #[strum(serialize_all = "snake_case")]
 #[non_exhaustive]
 #[cfg_attr(feature = "debug", derive(Debug))]
-#[cfg_attr(feature = "enum-iter", derive(strum::EnumIter))]
 #[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
 #[cfg_attr(feature = "clap", clap(rename_all = "snake_case"))]
 pub enum AddressKey {
     /// Holding.
     Holding,
+    /// GT buyback.
+    Buyback,
 }
 
 impl Addresses {
@@ -681,9 +1017,10 @@ impl Addresses {
     }
 
     /// Get.
-    fn get(&self, key: &AddressKey) -> &Pubkey {
+    pub(crate) fn get(&self, key: &AddressKey) -> &Pubkey {
         match key {
             AddressKey::Holding => &self.holding,
+            AddressKey::Buyback => &self.buyback,
         }
     }
 
@@ -691,6 +1028,169 @@ impl Addresses {
     fn get_mut(&mut self, key: &AddressKey) -> &mut Pubkey {
         match key {
             AddressKey::Holding => &mut self.holding,
+            AddressKey::Buyback => &mut self.buyback,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PAUSED_AT: i64 = 1_700_000_000;
+
+    #[test]
+    fn enable_emergency_mode_after_paused_for_enough_time() {
+        let mut state = EmergencyState::zeroed();
+        assert!(state.enable_emergency_mode(PAUSED_AT).is_err());
+
+        state.set_paused(true, PAUSED_AT).unwrap();
+        assert!(state.is_paused());
+        assert!(state.set_paused(true, PAUSED_AT).is_err());
+
+        let min_duration = constants::EMERGENCY_MODE_MIN_PAUSED_DURATION;
+        assert!(state
+            .enable_emergency_mode(PAUSED_AT + min_duration - 1)
+            .is_err());
+        assert!(!state.is_emergency_mode_enabled());
+
+        state
+            .enable_emergency_mode(PAUSED_AT + min_duration)
+            .unwrap();
+        assert!(state.is_emergency_mode_enabled());
+        assert!(state
+            .enable_emergency_mode(PAUSED_AT + min_duration)
+            .is_err());
+    }
+
+    #[test]
+    fn validate_emergency_mode_enabled() {
+        let mut store = Store::zeroed();
+        assert!(store.validate_emergency_mode_enabled().is_err());
+
+        store.set_paused(true, PAUSED_AT).unwrap();
+        assert!(store.validate_emergency_mode_enabled().is_err());
+
+        let min_duration = constants::EMERGENCY_MODE_MIN_PAUSED_DURATION;
+        store
+            .enable_emergency_mode(PAUSED_AT + min_duration)
+            .unwrap();
+        assert!(store.is_emergency_mode_enabled());
+        store.validate_emergency_mode_enabled().unwrap();
+
+        // The store remains paused in the emergency mode.
+        assert!(store.validate_not_paused().is_err());
+        store
+            .validate_feature_enabled(DomainDisabledFlag::Deposit, ActionDisabledFlag::Cancel)
+            .unwrap();
+    }
+
+    #[test]
+    fn trusted_chainlink_verifiers() {
+        let mut store = Store::zeroed();
+        let verifier = Pubkey::new_unique();
+        assert!(!store.is_trusted_chainlink_verifier(&verifier));
+        assert!(!store.is_trusted_chainlink_verifier(&Pubkey::default()));
+
+        store.set_trusted_chainlink_verifier(1, &verifier).unwrap();
+        assert!(store.is_trusted_chainlink_verifier(&verifier));
+        assert!(!store.is_trusted_chainlink_verifier(&Pubkey::new_unique()));
+        assert!(!store.is_trusted_chainlink_verifier(&Pubkey::default()));
+
+        let max = Store::MAX_TRUSTED_CHAINLINK_VERIFIERS as u8;
+        assert!(store
+            .set_trusted_chainlink_verifier(max, &verifier)
+            .is_err());
+
+        store
+            .set_trusted_chainlink_verifier(1, &Pubkey::default())
+            .unwrap();
+        assert!(!store.is_trusted_chainlink_verifier(&verifier));
+    }
+
+    #[test]
+    fn split_fees() {
+        let mut store = Store::zeroed();
+        store.factor.init();
+        assert_eq!(store.split_fees(1_000).unwrap(), (1_000, 0));
+
+        store.factor.buyback_fee_factor = 30 * constants::MARKET_USD_UNIT / 100;
+        assert_eq!(store.split_fees(1_000).unwrap(), (700, 300));
+        assert_eq!(store.split_fees(1).unwrap(), (1, 0));
+        assert_eq!(
+            store.split_fees(u64::MAX).unwrap(),
+            (12_912_720_851_596_686_131, 5_534_023_222_112_865_484)
+        );
+
+        store.factor.buyback_fee_factor = constants::MARKET_USD_UNIT;
+        assert_eq!(store.split_fees(1_000).unwrap(), (0, 1_000));
+
+        store.factor.buyback_fee_factor = constants::MARKET_USD_UNIT + 1;
+        assert!(store.split_fees(1_000).is_err());
+    }
+
+    #[test]
+    fn split_fees_without_init() {
+        // The factors of stores created before the buyback fee factor was added
+        // are zeroed reserved bytes.
+        let store = Store::zeroed();
+        assert_eq!(store.split_fees(1_000).unwrap(), (1_000, 0));
+        assert_eq!(store.split_fees(u64::MAX).unwrap(), (u64::MAX, 0));
+    }
+
+    #[test]
+    fn paused_store_only_permits_cancel() {
+        let mut store = Store::zeroed();
+        let domains = [
+            DomainDisabledFlag::Deposit,
+            DomainDisabledFlag::MarketIncrease,
+            DomainDisabledFlag::GlvDeposit,
+        ];
+        let actions = [
+            ActionDisabledFlag::Create,
+            ActionDisabledFlag::Update,
+            ActionDisabledFlag::Execute,
+        ];
+
+        store.validate_not_paused().unwrap();
+        store.set_paused(true, PAUSED_AT).unwrap();
+        assert!(store.validate_not_paused().is_err());
+        for domain in domains {
+            for action in actions {
+                assert!(store.validate_feature_enabled(domain, action).is_err());
+            }
+            store
+                .validate_feature_enabled(domain, ActionDisabledFlag::Cancel)
+                .unwrap();
+        }
+
+        store.set_paused(false, PAUSED_AT).unwrap();
+        store.validate_not_paused().unwrap();
+        for domain in domains {
+            for action in actions {
+                store.validate_feature_enabled(domain, action).unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn unpause_disables_emergency_mode() {
+        let mut state = EmergencyState::zeroed();
+        let min_duration = constants::EMERGENCY_MODE_MIN_PAUSED_DURATION;
+        state.set_paused(true, PAUSED_AT).unwrap();
+        state
+            .enable_emergency_mode(PAUSED_AT + min_duration)
+            .unwrap();
+
+        state.set_paused(false, PAUSED_AT + min_duration).unwrap();
+        assert!(!state.is_paused());
+        assert!(!state.is_emergency_mode_enabled());
+
+        // The pause duration must be counted from the latest pause.
+        let paused_again_at = PAUSED_AT + 2 * min_duration;
+        state.set_paused(true, paused_again_at).unwrap();
+        assert!(state
+            .enable_emergency_mode(paused_again_at + min_duration - 1)
+            .is_err());
+    }
+}
@@ -6,6 +6,7 @@ use std::{
 use anchor_lang::prelude::*;
 
 use crate::{
+    constants,
     utils::{
         fixed_str::{bytes_to_fixed_str, fixed_str_to_bytes},
         pubkey::DEFAULT_PUBKEY,
@@ -24,6 +25,12 @@ pub const DEFAULT_PRECISION: u8 = 4;
 /// Default timestamp adjustment.
 pub const DEFAULT_TIMESTAMP_ADJUSTMENT: u32 = 0;
 
+/// Max ratio between the price multiplier and its denominator (and vice versa).
+pub const MAX_PRICE_MULTIPLIER_RATIO: u32 = 1_000_000;
+
+/// Basis points in one.
+const MAX_BPS: u16 = 10_000;
+
 #[cfg(feature = "utils")]
 pub use self::utils::TokenMap;
 
@@ -43,6 +50,8 @@ pub enum Flag {
     Enabled,
     /// Is a synthetic asset.
     Synthetic,
+    /// Is a bridged token.
+    Bridged,
     // CHECK: Cannot have more than `MAX_FLAGS` flags.
 }
 
@@ -67,8 +76,12 @@ pub struct TokenConfig {
     feeds: [FeedConfig; MAX_FEEDS],
     /// Heartbeat duration.
     heartbeat_duration: u32,
+    /// Bridge premium factor (little-endian `i128`).
+    bridge_premium_factor: [u8; 16],
+    /// Max deviation from the last recorded price in basis points, `0` to disable.
+    max_price_deviation_bps: u16,
     #[cfg_attr(feature = "debug", debug(skip))]
-    reserved: [u8; 32],
+    reserved: [u8; 14],
 }
 
 #[cfg(feature = "display")]
@@ -77,9 +90,16 @@ impl std::fmt::Display for TokenConfig {
         writeln!(f, "Name: {}", self.name().unwrap_or("*unknown*"))?;
         writeln!(f, "Enabled: {}", self.is_enabled())?;
         writeln!(f, "Synthetic: {}", self.is_synthetic())?;
+        writeln!(f, "Bridged: {}", self.is_bridged())?;
+        writeln!(f, "Bridge Premium Factor: {}", self.bridge_premium_factor())?;
         writeln!(f, "Decimals: {}", self.token_decimals)?;
         writeln!(f, "Precision: {}", self.precision)?;
         writeln!(f, "Heartbeat: {}", self.heartbeat_duration)?;
+        writeln!(
+            f,
+            "Max Price Deviation (bps): {}",
+            self.max_price_deviation_bps
+        )?;
         writeln!(
             f,
             "Expected Provider: {}",
@@ -163,6 +183,122 @@ impl TokenConfig {
         self.flag(Flag::Synthetic)
     }
 
+    /// Is bridged.
+    pub fn is_bridged(&self) -> bool {
+        self.flag(Flag::Bridged)
+    }
+
+    /// Get the bridge premium factor.
+    pub fn bridge_premium_factor(&self) -> i128 {
+        i128::from_le_bytes(self.bridge_premium_factor)
+    }
+
+    /// Get the bridge premium config.
+    pub fn bridge_premium(&self) -> BridgePremiumConfig {
+        BridgePremiumConfig {
+            is_bridged: self.is_bridged(),
+            bridge_premium_factor: self.bridge_premium_factor(),
+        }
+    }
+
+    /// Set the bridge premium config.
+    pub fn set_bridge_premium(&mut self, config: &BridgePremiumConfig) -> Result<()> {
+        self.set_bridge_premium_factor(config.bridge_premium_factor)?;
+        self.set_flag(Flag::Bridged, config.is_bridged);
+        Ok(())
+    }
+
+    /// Set the bridge premium factor.
+    ///
+    /// The factor must be greater than `-MARKET_USD_UNIT` so that the adjusted price is positive.
+    pub fn set_bridge_premium_factor(&mut self, factor: i128) -> Result<()> {
+        require_gt!(
+            factor,
+            -(constants::MARKET_USD_UNIT as i128),
+            CoreError::InvalidArgument
+        );
+        self.bridge_premium_factor = factor.to_le_bytes();
+        Ok(())
+    }
+
+    /// Get the max deviation from the last recorded price in basis points.
+    ///
+    /// Returns `0` if the check is disabled.
+    pub fn max_price_deviation_bps(&self) -> u16 {
+        self.max_price_deviation_bps
+    }
+
+    /// Set the max deviation from the last recorded price in basis points, `0` to disable.
+    pub fn set_max_price_deviation_bps(&mut self, bps: u16) -> Result<()> {
+        require_gte!(MAX_BPS, bps, CoreError::InvalidArgument);
+        self.max_price_deviation_bps = bps;
+        Ok(())
+    }
+
+    /// Apply the bridge premium to the given price from the price feed.
+    ///
+    /// Returns the price unchanged if the token is not bridged,
+    /// otherwise `price * (1 + bridge_premium_factor / MARKET_USD_UNIT)`.
+    pub fn apply_bridge_premium(&self, price: &gmsol_utils::Price) -> Result<gmsol_utils::Price> {
+        use gmsol_model::num::MulDiv;
+        use gmsol_utils::price::Decimal;
+
+        if !self.is_bridged() {
+            return Ok(*price);
+        }
+
+        let unit = constants::MARKET_USD_UNIT;
+        let multiplier = (unit as i128)
+            .checked_add(self.bridge_premium_factor())
+            .filter(|multiplier| multiplier.is_positive())
+            .ok_or_else(|| error!(CoreError::InvalidArgument))?
+            .unsigned_abs();
+        let apply = |decimal: &Decimal| -> Result<Decimal> {
+            let value = u128::from(decimal.value)
+                .checked_mul_div(&multiplier, &unit)
+                .and_then(|value| u32::try_from(value).ok())
+                .ok_or_else(|| error!(CoreError::ValueOverflow))?;
+            Ok(Decimal {
+                value,
+                decimal_multiplier: decimal.decimal_multiplier,
+            })
+        };
+        Ok(gmsol_utils::Price {
+            min: apply(&price.min)?,
+            max: apply(&price.max)?,
+        })
+    }
+
+    /// Apply the price multiplier of the given provider to the price parsed from its feed.
+    pub fn apply_price_multiplier(
+        &self,
+        price_provider: &PriceProviderKind,
+        price: &gmsol_utils::Price,
+    ) -> Result<gmsol_utils::Price> {
+        use gmsol_model::num::MulDiv;
+        use gmsol_utils::price::Decimal;
+
+        let Some((multiplier, denominator)) =
+            self.get_feed_config(price_provider)?.price_multiplier()
+        else {
+            return Ok(*price);
+        };
+        let apply = |decimal: &Decimal| -> Result<Decimal> {
+            let value = u128::from(decimal.value)
+                .checked_mul_div(&u128::from(multiplier), &u128::from(denominator))
+                .and_then(|value| u32::try_from(value).ok())
+                .ok_or_else(|| error!(CoreError::ValueOverflow))?;
+            Ok(Decimal {
+                value,
+                decimal_multiplier: decimal.decimal_multiplier,
+            })
+        };
+        Ok(gmsol_utils::Price {
+            min: apply(&price.min)?,
+            max: apply(&price.max)?,
+        })
+    }
+
     /// Returns whether the config is a valid pool token config.
     pub fn is_valid_pool_token_config(&self) -> bool {
         !self.is_synthetic()
@@ -205,6 +341,7 @@ impl TokenConfig {
             feeds,
             timestamp_adjustments,
             expected_provider,
+            bridge_premium,
         } = builder;
 
         require_eq!(
@@ -221,14 +358,30 @@ impl TokenConfig {
         self.feeds = feeds
             .into_iter()
             .zip(timestamp_adjustments.into_iter())
-            .map(|(feed, timestamp_adjustment)| {
-                FeedConfig::new(feed).with_timestamp_adjustment(timestamp_adjustment)
+            .enumerate()
+            .map(|(idx, (feed, timestamp_adjustment))| {
+                let config = FeedConfig::new(feed).with_timestamp_adjustment(timestamp_adjustment);
+                // Price bands and multipliers are kept across updates.
+                match self.feeds.get(idx) {
+                    Some(current) => FeedConfig {
+                        min_price: current.min_price,
+                        max_price: current.max_price,
+                        price_multiplier: current.price_multiplier,
+                        price_multiplier_denominator: current.price_multiplier_denominator,
+                        ..config
+                    },
+                    None => config,
+                }
             })
             .collect::<Vec<_>>()
             .try_into()
             .map_err(|_| error!(CoreError::InvalidArgument))?;
         self.expected_provider = expected_provider.unwrap_or(PriceProviderKind::default() as u8);
         self.heartbeat_duration = heartbeat_duration;
+        // The bridge premium config is kept across updates if not provided.
+        if let Some(bridge_premium) = bridge_premium {
+            self.set_bridge_premium(&bridge_premium)?;
+        }
         Ok(())
     }
 
@@ -247,11 +400,51 @@ impl TokenConfig {
         Ok(self.get_feed_config(price_provider)?.timestamp_adjustment)
     }
 
+    /// Get the oracle timestamp with the timestamp adjustment of the given provider applied.
+    pub fn adjusted_timestamp(
+        &self,
+        price_provider: &PriceProviderKind,
+        oracle_ts: i64,
+    ) -> Result<i64> {
+        let timestamp_adjustment = self.timestamp_adjustment(price_provider)?.into();
+        oracle_ts
+            .checked_sub_unsigned(timestamp_adjustment)
+            .ok_or_else(|| error!(CoreError::TokenAmountOverflow))
+    }
+
     /// Heartbeat duration.
     pub fn heartbeat_duration(&self) -> u32 {
         self.heartbeat_duration
     }
 
+    /// Get the price band of the given provider in unit price.
+    ///
+    /// Returns `None` if the price band is disabled. A disabled side of the band
+    /// is returned as `0` for the lower bound and `u128::MAX` for the upper bound.
+    pub fn price_band(&self, price_provider: &PriceProviderKind) -> Result<Option<(u128, u128)>> {
+        use gmsol_utils::price::Decimal;
+
+        let Some((min_price, max_price)) = self.get_feed_config(price_provider)?.price_band()
+        else {
+            return Ok(None);
+        };
+        let decimal_multiplier =
+            Decimal::decimal_multiplier_from_precision(self.token_decimals, self.precision);
+        let to_unit_price = |value: u32| {
+            Decimal {
+                value,
+                decimal_multiplier,
+            }
+            .to_unit_price()
+        };
+        let max = if max_price == 0 {
+            u128::MAX
+        } else {
+            to_unit_price(max_price)
+        };
+        Ok(Some((to_unit_price(min_price), max)))
+    }
+
     /// Get token name.
     pub fn name(&self) -> Result<&str> {
         bytes_to_fixed_str(&self.name)
@@ -274,9 +467,13 @@ pub struct FeedConfig {
     )]
     feed: Pubkey,
     timestamp_adjustment: u32,
+    min_price: u32,
+    max_price: u32,
+    price_multiplier: u32,
+    price_multiplier_denominator: u32,
     #[cfg_attr(feature = "debug", debug(skip))]
     #[cfg_attr(feature = "serde", serde(with = "serde_bytes"))]
-    reserved: [u8; 28],
+    reserved: [u8; 12],
 }
 
 #[cfg(feature = "display")]
@@ -284,9 +481,13 @@ impl std::fmt::Display for FeedConfig {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
-            "feed = {}, timestamp_adjustment = {}",
-            self.feed, self.timestamp_adjustment
-        )
+            "feed = {}, timestamp_adjustment = {}, price_band = [{}, {}]",
+            self.feed, self.timestamp_adjustment, self.min_price, self.max_price
+        )?;
+        if let Some((multiplier, denominator)) = self.price_multiplier() {
+            write!(f, ", price_multiplier = {multiplier}/{denominator}")?;
+        }
+        Ok(())
     }
 }
 
@@ -296,6 +497,10 @@ impl FeedConfig {
         Self {
             feed,
             timestamp_adjustment: DEFAULT_TIMESTAMP_ADJUSTMENT,
+            min_price: 0,
+            max_price: 0,
+            price_multiplier: 1,
+            price_multiplier_denominator: 1,
             reserved: Default::default(),
         }
     }
@@ -306,6 +511,55 @@ impl FeedConfig {
         self
     }
 
+    /// Change the price band.
+    ///
+    /// The bounds are prices of one whole token in USD with the precision of the token,
+    /// i.e. in the same unit as [`Decimal::value`](gmsol_utils::price::Decimal::value).
+    /// A bound of `0` means that side of the band is disabled.
+    pub fn with_price_band(mut self, min_price: u32, max_price: u32) -> Result<Self> {
+        require!(
+            min_price == 0 || max_price == 0 || min_price <= max_price,
+            CoreError::InvalidArgument
+        );
+        self.min_price = min_price;
+        self.max_price = max_price;
+        Ok(self)
+    }
+
+    /// Get the price band, returns `None` if it is disabled.
+    pub fn price_band(&self) -> Option<(u32, u32)> {
+        (self.min_price != 0 || self.max_price != 0).then_some((self.min_price, self.max_price))
+    }
+
+    /// Change the price multiplier.
+    ///
+    /// Prices parsed from the feed are multiplied by `multiplier / denominator`.
+    /// Both must be non-zero, and the ratio must be within
+    /// `[1 / MAX_PRICE_MULTIPLIER_RATIO, MAX_PRICE_MULTIPLIER_RATIO]`.
+    pub fn with_price_multiplier(mut self, multiplier: u32, denominator: u32) -> Result<Self> {
+        require!(
+            multiplier != 0 && denominator != 0,
+            CoreError::InvalidArgument
+        );
+        let max_ratio = u64::from(MAX_PRICE_MULTIPLIER_RATIO);
+        require!(
+            u64::from(multiplier) <= u64::from(denominator) * max_ratio
+                && u64::from(denominator) <= u64::from(multiplier) * max_ratio,
+            CoreError::InvalidArgument
+        );
+        self.price_multiplier = multiplier;
+        self.price_multiplier_denominator = denominator;
+        Ok(self)
+    }
+
+    /// Get the price multiplier as `(multiplier, denominator)`,
+    /// returns `None` if it is not set or is the identity.
+    pub fn price_multiplier(&self) -> Option<(u32, u32)> {
+        let (multiplier, denominator) = (self.price_multiplier, self.price_multiplier_denominator);
+        (multiplier != 0 && denominator != 0 && multiplier != denominator)
+            .then_some((multiplier, denominator))
+    }
+
     /// Get feed.
     pub fn feed(&self) -> &Pubkey {
         &self.feed
@@ -317,6 +571,98 @@ impl FeedConfig {
     }
 }
 
+/// The config of a token, returned by the
+/// [`get_token_config`](crate::gmsol_store::get_token_config) instruction.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "debug", derive(Debug))]
+pub struct TokenConfigInfo {
+    /// Name.
+    pub name: String,
+    /// Whether the token config is enabled.
+    pub is_enabled: bool,
+    /// Whether the token is a synthetic asset.
+    pub is_synthetic: bool,
+    /// Token decimals.
+    pub token_decimals: u8,
+    /// Price precision.
+    pub precision: u8,
+    /// Expected provider, see [`PriceProviderKind`] for valid indices.
+    pub expected_provider: u8,
+    /// Heartbeat duration.
+    pub heartbeat_duration: u32,
+    /// Max deviation from the last recorded price in basis points, `0` means disabled.
+    pub max_price_deviation_bps: u16,
+    /// Bridge premium config.
+    pub bridge_premium: BridgePremiumConfig,
+    /// The configured price feeds.
+    pub feeds: Vec<FeedConfigInfo>,
+}
+
+/// A configured price feed of a token.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "debug", derive(Debug))]
+pub struct FeedConfigInfo {
+    /// Provider, see [`PriceProviderKind`] for valid indices.
+    pub provider: u8,
+    /// Feed address.
+    pub feed: Pubkey,
+    /// Timestamp adjustment.
+    pub timestamp_adjustment: u32,
+    /// Min price of the price band, `0` means disabled.
+    pub min_price: u32,
+    /// Max price of the price band, `0` means disabled.
+    pub max_price: u32,
+    /// Price multiplier.
+    pub price_multiplier: u32,
+    /// Price multiplier denominator.
+    pub price_multiplier_denominator: u32,
+}
+
+impl<'a> TryFrom<&'a TokenConfig> for TokenConfigInfo {
+    type Error = Error;
+
+    fn try_from(config: &'a TokenConfig) -> Result<Self> {
+        let feeds = config
+            .feeds
+            .iter()
+            .enumerate()
+            .filter(|(_, feed)| feed.feed != DEFAULT_PUBKEY)
+            .map(|(idx, feed)| FeedConfigInfo {
+                provider: idx as u8,
+                feed: feed.feed,
+                timestamp_adjustment: feed.timestamp_adjustment,
+                min_price: feed.min_price,
+                max_price: feed.max_price,
+                price_multiplier: feed.price_multiplier,
+                price_multiplier_denominator: feed.price_multiplier_denominator,
+            })
+            .collect();
+        Ok(Self {
+            name: config.name()?.to_owned(),
+            is_enabled: config.is_enabled(),
+            is_synthetic: config.is_synthetic(),
+            token_decimals: config.token_decimals,
+            precision: config.precision,
+            expected_provider: config.expected_provider,
+            heartbeat_duration: config.heartbeat_duration,
+            max_price_deviation_bps: config.max_price_deviation_bps,
+            bridge_premium: config.bridge_premium(),
+            feeds,
+        })
+    }
+}
+
+/// Bridge premium config of a token.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "debug", derive(Debug))]
+pub struct BridgePremiumConfig {
+    /// Whether the token is a bridged token.
+    pub is_bridged: bool,
+    /// The premium (positive) or discount (negative) factor relative to the price
+    /// from the price feed, with the unit of [`MARKET_USD_UNIT`](constants::MARKET_USD_UNIT).
+    pub bridge_premium_factor: i128,
+}
+
 #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
 #[cfg_attr(feature = "debug", derive(Debug))]
 pub struct UpdateTokenConfigParams {
@@ -325,6 +671,7 @@ pub struct UpdateTokenConfigParams {
     feeds: Vec<Pubkey>,
     timestamp_adjustments: Vec<u32>,
     expected_provider: Option<u8>,
+    bridge_premium: Option<BridgePremiumConfig>,
 }
 
 impl Default for UpdateTokenConfigParams {
@@ -335,6 +682,7 @@ impl Default for UpdateTokenConfigParams {
             feeds: vec![DEFAULT_PUBKEY; MAX_FEEDS],
             timestamp_adjustments: vec![DEFAULT_TIMESTAMP_ADJUSTMENT; MAX_FEEDS],
             expected_provider: None,
+            bridge_premium: None,
         }
     }
 }
@@ -353,6 +701,7 @@ impl<'a> From<&'a TokenConfig> for UpdateTokenConfigParams {
             feeds,
             timestamp_adjustments,
             expected_provider: Some(config.expected_provider),
+            bridge_premium: Some(config.bridge_premium()),
         }
     }
 }
@@ -399,6 +748,12 @@ impl UpdateTokenConfigParams {
         self.expected_provider = Some(provider as u8);
         self
     }
+
+    /// Set bridge premium config.
+    pub fn with_bridge_premium(mut self, config: BridgePremiumConfig) -> Self {
+        self.bridge_premium = Some(config);
+        self
+    }
 }
 
 gmsol_utils::fixed_map!(
@@ -572,6 +927,13 @@ pub trait TokenMapAccess {
     }
 }
 
+impl TokenMapRef<'_> {
+    /// Get tokens.
+    pub fn tokens(&self) -> impl Iterator<Item = Pubkey> + '_ {
+        self.header.tokens()
+    }
+}
+
 impl TokenMapAccess for TokenMapRef<'_> {
     fn get(&self, token: &Pubkey) -> Option<&TokenConfig> {
         self.header.get_token_config_unchecked(token, &self.configs)
@@ -710,3 +1072,65 @@ pub mod utils {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use anchor_lang::solana_program::program::MAX_RETURN_DATA;
+    use bytemuck::Zeroable;
+
+    use super::*;
+
+    #[test]
+    fn token_config_info_round_trip() {
+        let pyth_feed = Pubkey::new_unique();
+        let chainlink_feed = Pubkey::new_unique();
+        let params = UpdateTokenConfigParams::default()
+            .update_price_feed(&PriceProviderKind::Pyth, pyth_feed, Some(2))
+            .unwrap()
+            .update_price_feed(&PriceProviderKind::Chainlink, chainlink_feed, None)
+            .unwrap()
+            .with_expected_provider(PriceProviderKind::Pyth)
+            .with_precision(6)
+            .with_heartbeat_duration(60);
+
+        let mut config = TokenConfig::zeroed();
+        config.update("WSOL", false, 9, params, true, true).unwrap();
+
+        let info = TokenConfigInfo::try_from(&config).unwrap();
+        assert_eq!(info.name, "WSOL");
+        assert!(info.is_enabled);
+        assert!(!info.is_synthetic);
+        assert_eq!(info.token_decimals, 9);
+        assert_eq!(info.precision, 6);
+        assert_eq!(info.expected_provider, PriceProviderKind::Pyth as u8);
+        assert_eq!(info.heartbeat_duration, 60);
+        assert_eq!(info.feeds.len(), 2);
+        assert_eq!(info.feeds[0].provider, PriceProviderKind::Pyth as u8);
+        assert_eq!(info.feeds[0].feed, pyth_feed);
+        assert_eq!(info.feeds[0].timestamp_adjustment, 2);
+        assert_eq!(info.feeds[1].provider, PriceProviderKind::Chainlink as u8);
+        assert_eq!(info.feeds[1].feed, chainlink_feed);
+
+        let data = info.try_to_vec().unwrap();
+        assert_eq!(TokenConfigInfo::try_from_slice(&data).unwrap(), info);
+    }
+
+    #[test]
+    fn token_config_info_fits_in_return_data() {
+        let mut params = UpdateTokenConfigParams::default();
+        for idx in 0..MAX_FEEDS {
+            let kind = PriceProviderKind::try_from(idx as u8).unwrap();
+            params = params
+                .update_price_feed(&kind, Pubkey::new_unique(), None)
+                .unwrap();
+        }
+        let mut config = TokenConfig::zeroed();
+        // The name must be terminated by a zero byte.
+        let name = "a".repeat(MAX_NAME_LEN - 1);
+        config.update(&name, true, 9, params, true, true).unwrap();
+
+        let info = TokenConfigInfo::try_from(&config).unwrap();
+        assert_eq!(info.feeds.len(), MAX_FEEDS);
+        assert!(info.try_to_vec().unwrap().len() <= MAX_RETURN_DATA);
+    }
+}
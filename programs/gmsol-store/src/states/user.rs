@@ -28,9 +28,19 @@ pub struct UserHeader {
     pub(crate) referral: Referral,
     /// GT State.
     pub(crate) gt: UserGtState,
+    /// The hash of the reason for freezing this user account.
+    pub(crate) freeze_reason_hash: [u8; 32],
+    /// Activity of the owner.
+    activity: UserActivity,
+    /// The slot of the last trade created by the owner.
+    last_trade_block: u64,
+    /// The number of trades created by the owner in the slot of the last trade.
+    trades_this_block: u32,
+    #[cfg_attr(feature = "debug", debug(skip))]
+    padding_1: [u8; 4],
     #[cfg_attr(feature = "debug", debug(skip))]
     #[cfg_attr(feature = "serde", serde(with = "serde_bytes"))]
-    reserved: [u8; 128],
+    reserved: [u8; 48],
 }
 
 /// User flags.
@@ -40,6 +50,10 @@ pub struct UserHeader {
 pub enum UserFlag {
     /// Is initialized.
     Initialized,
+    /// Is frozen.
+    Frozen,
+    /// Has claimed the referral code transfer reward.
+    ReferralTransferRewardClaimed,
 }
 
 impl UserFlag {
@@ -55,6 +69,100 @@ impl UserHeader {
         self.flags.get_flag(UserFlag::Initialized)
     }
 
+    /// Return whether the user account is frozen.
+    pub fn is_frozen(&self) -> bool {
+        self.flags.get_flag(UserFlag::Frozen)
+    }
+
+    /// Get the hash of the freeze reason.
+    pub fn freeze_reason_hash(&self) -> &[u8; 32] {
+        &self.freeze_reason_hash
+    }
+
+    /// Get the activity of the owner.
+    pub fn activity(&self) -> &UserActivity {
+        &self.activity
+    }
+
+    /// Record the creation of an action.
+    pub(crate) fn record_action(&mut self, kind: UserActionKind, timestamp: i64) {
+        let count = match kind {
+            UserActionKind::Deposit => &mut self.activity.deposit_count,
+            UserActionKind::Withdrawal => &mut self.activity.withdrawal_count,
+            UserActionKind::Shift => &mut self.activity.shift_count,
+        };
+        *count = count.saturating_add(1);
+        self.activity.last_action_ts = timestamp;
+    }
+
+    /// Get the slot of the last trade.
+    pub fn last_trade_block(&self) -> u64 {
+        self.last_trade_block
+    }
+
+    /// Get the number of trades in the slot of the last trade.
+    pub fn trades_this_block(&self) -> u32 {
+        self.trades_this_block
+    }
+
+    /// Record a trade created at the given slot.
+    ///
+    /// The count is reset when the slot changes. A `max_trades_per_block` of zero
+    /// means no limit.
+    pub(crate) fn record_trade_in_block(
+        &mut self,
+        slot: u64,
+        max_trades_per_block: u32,
+    ) -> Result<()> {
+        if slot != self.last_trade_block {
+            self.last_trade_block = slot;
+            self.trades_this_block = 0;
+        }
+        if max_trades_per_block != 0 {
+            require_gt!(
+                max_trades_per_block,
+                self.trades_this_block,
+                CoreError::UserTradeRateLimitExceeded
+            );
+        }
+        self.trades_this_block = self.trades_this_block.saturating_add(1);
+        Ok(())
+    }
+
+    /// Return whether the owner has claimed the referral code transfer reward.
+    pub fn is_referral_transfer_reward_claimed(&self) -> bool {
+        self.flags.get_flag(UserFlag::ReferralTransferRewardClaimed)
+    }
+
+    /// Mark the referral code transfer reward as claimed by the owner.
+    pub(crate) fn set_referral_transfer_reward_claimed(&mut self) {
+        self.flags
+            .set_flag(UserFlag::ReferralTransferRewardClaimed, true);
+    }
+
+    /// Validate that the user account is not frozen.
+    pub(crate) fn validate_not_frozen(&self) -> Result<()> {
+        require!(!self.is_frozen(), CoreError::UserAccountFrozen);
+        Ok(())
+    }
+
+    /// Freeze the user account with the given reason hash.
+    pub(crate) fn freeze(&mut self, reason_hash: [u8; 32]) -> Result<()> {
+        require!(self.is_initialized(), CoreError::InvalidUserAccount);
+        require!(!self.is_frozen(), CoreError::PreconditionsAreNotMet);
+        self.flags.set_flag(UserFlag::Frozen, true);
+        self.freeze_reason_hash = reason_hash;
+        Ok(())
+    }
+
+    /// Unfreeze the user account.
+    pub(crate) fn unfreeze(&mut self) -> Result<()> {
+        require!(self.is_frozen(), CoreError::PreconditionsAreNotMet);
+        self.flags.set_flag(UserFlag::Frozen, false);
+        self.freeze_reason_hash = [0; 32];
+        Ok(())
+    }
+
     /// Initialize.
     pub(crate) fn init(&mut self, store: &Pubkey, owner: &Pubkey, bump: u8) -> Result<()> {
         require!(
@@ -182,9 +290,11 @@ pub struct Referral {
     pub(crate) code: Pubkey,
     /// Number of referee.
     referee_count: u128,
+    /// Cumulative GT rewards received as a referrer.
+    cumulative_reward: u64,
     #[cfg_attr(feature = "debug", debug(skip))]
     #[cfg_attr(feature = "serde", serde(with = "serde_bytes"))]
-    reserved: [u8; 64],
+    reserved: [u8; 56],
 }
 
 impl Referral {
@@ -220,6 +330,69 @@ impl Referral {
     pub fn code(&self) -> Option<&Pubkey> {
         optional_address(&self.code)
     }
+
+    /// Get the number of referees.
+    pub fn referee_count(&self) -> u128 {
+        self.referee_count
+    }
+
+    /// Get the cumulative GT rewards received as a referrer.
+    pub fn cumulative_reward(&self) -> u64 {
+        self.cumulative_reward
+    }
+
+    pub(crate) fn add_reward(&mut self, reward: u64) {
+        self.cumulative_reward = self.cumulative_reward.saturating_add(reward);
+    }
+
+    /// Get referral stats.
+    pub fn stats(&self) -> ReferralStats {
+        ReferralStats {
+            code: self.code().copied(),
+            referee_count: self.referee_count,
+            cumulative_reward: self.cumulative_reward,
+        }
+    }
+}
+
+/// Referral stats of a user.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+#[cfg_attr(feature = "debug", derive(Debug))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ReferralStats {
+    /// The address of the referral code account.
+    pub code: Option<Pubkey>,
+    /// Number of referees.
+    pub referee_count: u128,
+    /// Cumulative GT rewards received as a referrer.
+    pub cumulative_reward: u64,
+}
+
+/// Kind of the actions tracked by [`UserActivity`].
+#[derive(Clone, Copy)]
+pub enum UserActionKind {
+    /// Deposit.
+    Deposit,
+    /// Withdrawal.
+    Withdrawal,
+    /// Shift.
+    Shift,
+}
+
+/// Activity of a user.
+#[zero_copy]
+#[derive(AnchorSerialize, AnchorDeserialize)]
+#[cfg_attr(feature = "debug", derive(Debug))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct UserActivity {
+    /// Number of deposits created by the user.
+    pub deposit_count: u64,
+    /// Number of withdrawals created by the user.
+    pub withdrawal_count: u64,
+    /// Number of shifts created by the user.
+    pub shift_count: u64,
+    /// The timestamp of the last action created by the user.
+    pub last_action_ts: i64,
 }
 
 /// Referral Code.
@@ -238,9 +411,10 @@ pub struct ReferralCodeV2 {
     pub owner: Pubkey,
     /// Next owner.
     next_owner: Pubkey,
+    transfer_reward_claimed: u8,
     #[cfg_attr(feature = "debug", debug(skip))]
     #[cfg_attr(feature = "serde", serde(with = "serde_bytes"))]
-    reserved: [u8; 64],
+    reserved: [u8; 63],
 }
 
 impl ReferralCodeV2 {
@@ -266,6 +440,22 @@ impl ReferralCodeV2 {
         &self.next_owner
     }
 
+    /// Returns whether the transfer reward has been claimed for this code.
+    pub fn is_transfer_reward_claimed(&self) -> bool {
+        self.transfer_reward_claimed != 0
+    }
+
+    /// Mark the transfer reward as claimed.
+    /// Returns `false` if it has already been claimed.
+    pub(crate) fn claim_transfer_reward(&mut self) -> bool {
+        if self.is_transfer_reward_claimed() {
+            false
+        } else {
+            self.transfer_reward_claimed = 1;
+            true
+        }
+    }
+
     pub(crate) fn set_next_owner(&mut self, next_owner: &Pubkey) -> Result<()> {
         require_keys_neq!(
             self.next_owner,
@@ -311,6 +501,88 @@ impl Seed for ReferralCodeV2 {
     const SEED: &'static [u8] = b"referral_code";
 }
 
+/// Referral Reward Vault.
+///
+/// Holds the lamports used to reward the users accepting a transferred referral code.
+#[account(zero_copy)]
+#[cfg_attr(feature = "debug", derive(derive_more::Debug))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ReferralRewardVault {
+    /// Bump.
+    pub(crate) bump: u8,
+    bump_bytes: [u8; 1],
+    #[cfg_attr(feature = "debug", debug(skip))]
+    padding: [u8; 6],
+    /// The reward (in lamports) for accepting a referral code transfer.
+    referral_code_transfer_reward: u64,
+    /// The min paid fee value (in USD) of the new owner to be eligible for the reward.
+    min_paid_fee_value: u128,
+    /// Store.
+    pub store: Pubkey,
+    #[cfg_attr(feature = "debug", debug(skip))]
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes"))]
+    reserved: [u8; 64],
+}
+
+impl ReferralRewardVault {
+    pub(crate) fn init(&mut self, bump: u8, store: &Pubkey) {
+        self.bump = bump;
+        self.bump_bytes = [bump];
+        self.store = *store;
+    }
+
+    pub(crate) fn signer_seeds(&self) -> [&[u8]; 3] {
+        [Self::SEED, self.store.as_ref(), &self.bump_bytes]
+    }
+
+    /// Get the reward (in lamports) for accepting a referral code transfer.
+    pub fn referral_code_transfer_reward(&self) -> u64 {
+        self.referral_code_transfer_reward
+    }
+
+    /// Get the min paid fee value of the new owner to be eligible for the reward.
+    pub fn min_paid_fee_value(&self) -> u128 {
+        self.min_paid_fee_value
+    }
+
+    pub(crate) fn set_referral_code_transfer_reward(
+        &mut self,
+        amount: u64,
+        min_paid_fee_value: u128,
+    ) {
+        self.referral_code_transfer_reward = amount;
+        self.min_paid_fee_value = min_paid_fee_value;
+    }
+
+    /// Returns whether the new owner of a referral code is eligible for the transfer reward.
+    ///
+    /// The reward is paid at most once per referral code and once per owner, and only
+    /// to the owners who have paid enough trading fees, so that it cannot be farmed by
+    /// creating codes and accounts.
+    pub(crate) fn is_eligible(&self, code: &ReferralCodeV2, next_owner: &UserHeader) -> bool {
+        if code.is_transfer_reward_claimed() {
+            msg!("[Referral] the transfer reward of this code has already been claimed");
+            false
+        } else if next_owner.is_referral_transfer_reward_claimed() {
+            msg!("[Referral] the new owner has already claimed a transfer reward");
+            false
+        } else if next_owner.gt.paid_fee_value() < self.min_paid_fee_value {
+            msg!("[Referral] the new owner has not paid enough fees for the transfer reward");
+            false
+        } else {
+            true
+        }
+    }
+}
+
+impl InitSpace for ReferralRewardVault {
+    const INIT_SPACE: usize = std::mem::size_of::<Self>();
+}
+
+impl Seed for ReferralRewardVault {
+    const SEED: &'static [u8] = b"referral_reward_vault";
+}
+
 /// GT State.
 #[zero_copy]
 #[cfg_attr(feature = "debug", derive(derive_more::Debug))]
@@ -351,3 +623,79 @@ impl UserGtState {
         self.amount
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn user(owner: Pubkey) -> UserHeader {
+        let mut user: UserHeader = bytemuck::Zeroable::zeroed();
+        user.owner = owner;
+        user
+    }
+
+    #[test]
+    fn test_referral_stats() {
+        let mut referrer = user(Pubkey::new_unique());
+        let code = Pubkey::new_unique();
+        referrer.referral.set_code(&code).unwrap();
+
+        let mut referees = (0..3)
+            .map(|_| user(Pubkey::new_unique()))
+            .collect::<Vec<_>>();
+        for referee in referees.iter_mut() {
+            referee.referral.set_referrer(&mut referrer).unwrap();
+            assert_eq!(referee.referral.referrer(), Some(&referrer.owner));
+        }
+
+        // The referrer of a user can only be set once.
+        assert!(referees[0].referral.set_referrer(&mut referrer).is_err());
+
+        referrer.referral.add_reward(100);
+        referrer.referral.add_reward(250);
+
+        let stats = referrer.referral().stats();
+        assert_eq!(stats.code, Some(code));
+        assert_eq!(stats.referee_count, 3);
+        assert_eq!(stats.cumulative_reward, 350);
+
+        referrer.referral.add_reward(u64::MAX);
+        assert_eq!(referrer.referral().cumulative_reward(), u64::MAX);
+    }
+
+    #[test]
+    fn test_user_activity() {
+        let mut user = user(Pubkey::new_unique());
+        user.record_action(UserActionKind::Deposit, 10);
+        user.record_action(UserActionKind::Deposit, 20);
+        user.record_action(UserActionKind::Withdrawal, 30);
+
+        let activity = user.activity();
+        assert_eq!(activity.deposit_count, 2);
+        assert_eq!(activity.withdrawal_count, 1);
+        assert_eq!(activity.shift_count, 0);
+        assert_eq!(activity.last_action_ts, 30);
+    }
+
+    #[test]
+    fn test_trade_rate_limit() {
+        let mut user = user(Pubkey::new_unique());
+        let max_trades = 3;
+        for _ in 0..max_trades {
+            user.record_trade_in_block(100, max_trades).unwrap();
+        }
+        assert_eq!(user.trades_this_block(), 3);
+        assert!(user.record_trade_in_block(100, max_trades).is_err());
+
+        // The count is reset in a new slot.
+        user.record_trade_in_block(101, max_trades).unwrap();
+        assert_eq!(user.last_trade_block(), 101);
+        assert_eq!(user.trades_this_block(), 1);
+
+        // No limit if the max is zero.
+        for _ in 0..10 {
+            user.record_trade_in_block(101, 0).unwrap();
+        }
+        assert_eq!(user.trades_this_block(), 11);
+    }
+}
@@ -3,8 +3,8 @@ use anchor_lang::{prelude::*, ZeroCopy};
 use crate::{
     events::EventEmitter,
     states::{
-        common::action::{Action, ActionParams, Closable},
-        NonceBytes, StoreWalletSigner,
+        common::action::{Action, ActionHeader, ActionParams, Closable},
+        Market, NonceBytes, PendingActionKind, StoreWalletSigner,
     },
     CoreError,
 };
@@ -111,8 +111,13 @@ where
         event_emitter: &EventEmitter<'_, 'info>,
     ) -> Result<Success>;
 
+    /// Release the pending slot occupied by the action in its market.
+    fn release_pending_slot(&self, _remaining_accounts: &'info [AccountInfo<'info>]) -> Result<()> {
+        Ok(())
+    }
+
     /// Close Action.
-    fn close(ctx: &Context<'_, '_, '_, 'info, Self>, reason: &str) -> Result<()> {
+    fn close(ctx: &Context<'_, '_, 'info, 'info, Self>, reason: &str) -> Result<()> {
         let accounts = &ctx.accounts;
         accounts.validate()?;
         let should_continue_when_atas_are_missing = accounts.preprocess()?;
@@ -130,6 +135,7 @@ where
             &store_wallet_signer,
             &event_emitter,
         )? {
+            accounts.release_pending_slot(ctx.remaining_accounts)?;
             {
                 let action_address = accounts.action().key();
                 let action = accounts.action().load()?;
@@ -170,3 +176,34 @@ where
         self.action().close(self.rent_receiver())
     }
 }
+
+/// Release the pending slot occupied by the action in its market, if any.
+///
+/// The market of the action must be provided as the first of the `remaining_accounts`
+/// if the action is counted as pending. Otherwise, the `remaining_accounts` are ignored.
+///
+/// The release is skipped if the market has been closed, since closed markets
+/// cannot be re-initialized.
+pub(crate) fn release_pending_slot<'info>(
+    kind: PendingActionKind,
+    header: &mut ActionHeader,
+    remaining_accounts: &'info [AccountInfo<'info>],
+) -> Result<()> {
+    if !header.is_pending_counted() {
+        return Ok(());
+    }
+    let market = remaining_accounts
+        .first()
+        .ok_or_else(|| error!(ErrorCode::AccountNotEnoughKeys))?;
+    require_keys_eq!(market.key(), header.market, CoreError::MarketMismatched);
+    if *market.owner != crate::ID && market.data_is_empty() {
+        header.set_pending_counted(false);
+        return Ok(());
+    }
+    let market = AccountLoader::<Market>::try_from(market)?;
+    market
+        .load_mut()?
+        .indexer_mut()
+        .release_pending_slot(kind, header);
+    Ok(())
+}
@@ -320,8 +320,9 @@ impl<'info> CompleteGtExchange<'info> {
 
         let gt_amount = self.exchange.load()?.amount();
 
-        // Close GT exchange first to validate the preconditions.
-        // This should validate that the GT exchange vault has been confirmed.
+        // Mark the GT exchange as completed first to validate the preconditions.
+        // This should validate that the GT exchange vault has been confirmed and
+        // that the exchange has not been completed.
         let ctx = self.close_gt_exchange_ctx();
         close_gt_exchange(ctx.with_signer(&[&signer.as_seeds()]))?;
 
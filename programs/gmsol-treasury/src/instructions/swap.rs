@@ -124,6 +124,12 @@ pub(crate) fn unchecked_create_swap<'info>(
         acceptable_price: None,
         should_unwrap_native_token: false,
         valid_from_ts: None,
+        assigned_keeper: None,
+        fallback_to_market_after_secs: None,
+        callback_program: None,
+        callback_account: None,
+        callback_data: None,
+        margin_mode: None,
     };
     create_order(
         cpi_ctx
@@ -267,13 +273,18 @@ pub struct CancelSwap<'info> {
 }
 
 /// Cancel a swap with the store program.
+///
+/// The remaining accounts are passed to the store program, which expects the
+/// market of the swap order if the order is counted as pending.
 /// # CHECK
 /// Only [`TREASURY_KEEPER`](crate::roles::TREASURY_KEEPER) is allowed to use.
 pub(crate) fn unchecked_cancel_swap(ctx: Context<CancelSwap>) -> Result<()> {
     let signer = ReceiverSigner::new(ctx.accounts.config.key(), ctx.bumps.receiver);
     let cpi_ctx = ctx.accounts.close_order_ctx();
     close_order(
-        cpi_ctx.with_signer(&[&signer.as_seeds()]),
+        cpi_ctx
+            .with_signer(&[&signer.as_seeds()])
+            .with_remaining_accounts(ctx.remaining_accounts.to_vec()),
         "cancel".to_string(),
     )?;
     Ok(())
@@ -0,0 +1,83 @@
+use anchor_lang::prelude::*;
+use gmsol_store::{
+    constants::ORDER_CALLBACK_AUTHORITY_SEED,
+    states::order::{Order, OrderCallbackArgs},
+};
+
+declare_id!("9deRjCp5k645d7sDBvuyJQodCsE7oe2uhQpggFQGWcCe");
+
+#[program]
+pub mod gmsol_mock_order_callback {
+
+    use super::*;
+
+    pub fn initialize_record(ctx: Context<InitializeRecord>, store: Pubkey) -> Result<()> {
+        ctx.accounts.record.store = store;
+        Ok(())
+    }
+
+    pub fn on_order_executed(ctx: Context<OnOrderExecuted>, args: OrderCallbackArgs) -> Result<()> {
+        let record = &mut ctx.accounts.record;
+        record.invocations += 1;
+        record.last_order = ctx.accounts.order.key();
+        record.last_kind = args.kind.into();
+        record.last_data = args.data;
+        record.last_final_output_amount = args.transfer_out.final_output_token;
+        Ok(())
+    }
+}
+
+pub const RECORD_SEED: &[u8] = b"record";
+
+/// Callback Record.
+#[account]
+#[derive(InitSpace)]
+pub struct CallbackRecord {
+    /// The store whose orders are allowed to invoke the callback.
+    pub store: Pubkey,
+    /// The number of invocations.
+    pub invocations: u64,
+    /// The last executed order.
+    pub last_order: Pubkey,
+    /// The kind of the last executed order.
+    pub last_kind: u8,
+    /// The callback data of the last executed order.
+    pub last_data: [u8; 32],
+    /// The final output token amount of the last executed order.
+    pub last_final_output_amount: u64,
+}
+
+#[derive(Accounts)]
+#[instruction(store: Pubkey)]
+pub struct InitializeRecord<'info> {
+    /// Payer.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// Record Account.
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + CallbackRecord::INIT_SPACE,
+        seeds = [RECORD_SEED, store.as_ref(), payer.key().as_ref()],
+        bump,
+    )]
+    pub record: Account<'info, CallbackRecord>,
+    /// The System Program.
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct OnOrderExecuted<'info> {
+    /// The order callback authority of the store.
+    #[account(
+        seeds = [ORDER_CALLBACK_AUTHORITY_SEED, record.store.as_ref()],
+        bump,
+        seeds::program = gmsol_store::ID,
+    )]
+    pub authority: Signer<'info>,
+    /// The executed order.
+    pub order: AccountLoader<'info, Order>,
+    /// Record Account.
+    #[account(mut)]
+    pub record: Account<'info, CallbackRecord>,
+}